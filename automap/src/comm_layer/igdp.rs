@@ -1,6 +1,6 @@
 // Copyright (c) 2019-2021, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 
-use crate::comm_layer::pcp_pmp_common::MappingConfig;
+use crate::comm_layer::pcp_pmp_common::{MappingConfig, PinholeConfig};
 use crate::comm_layer::{
     AutomapError, HousekeepingThreadCommand, LocalIpFinder, LocalIpFinderReal, Transactor,
 };
@@ -8,7 +8,7 @@ use crate::control_layer::automap_control::{AutomapChange, ChangeHandler};
 use crossbeam_channel::{unbounded, Receiver, Sender};
 use igd::{
     search_gateway, AddPortError, Gateway, GetExternalIpError, PortMappingProtocol,
-    RemovePortError, SearchError, SearchOptions,
+    RemovePortError, RequestError, SearchError, SearchOptions,
 };
 use masq_lib::debug;
 use masq_lib::error;
@@ -17,7 +17,9 @@ use masq_lib::logger::Logger;
 use masq_lib::utils::{AutomapProtocol, ExpectValue};
 use masq_lib::warning;
 use std::any::Any;
-use std::net::{IpAddr, Ipv4Addr, SocketAddrV4};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6, TcpStream};
 use std::ops::Add;
 use std::sync::{Arc, Mutex, MutexGuard};
 use std::thread;
@@ -26,8 +28,13 @@ use std::time::{Duration, Instant};
 
 pub const HOUSEKEEPING_THREAD_LOOP_DELAY_MS: u64 = 100;
 pub const PUBLIC_IP_POLL_DELAY_SECONDS: u64 = 60;
+// Our IGD library only speaks WANIPConnection, so IGDv2 pinholes are requested by hand.
+const FIREWALL_CONTROL_SERVICE: &str = "urn:schemas-upnp-org:service:WANIPv6FirewallControl:1";
+const MAX_PINHOLE_LEASE_SECONDS: u32 = 86400;
+const TCP_PROTOCOL_NUMBER: u8 = 6;
+const ROUTER_HTTP_TIMEOUT: Duration = Duration::from_secs(3);
 
-trait GatewayFactory {
+trait GatewayFactory: Send {
     fn make(&self, options: SearchOptions) -> Result<Box<dyn GatewayWrapper>, SearchError>;
 }
 
@@ -62,6 +69,13 @@ trait GatewayWrapper: Send {
         protocol: PortMappingProtocol,
         external_port: u16,
     ) -> Result<(), RemovePortError>;
+    fn add_pinhole(
+        &self,
+        local_addr: SocketAddrV6,
+        lease_duration: u32,
+    ) -> Result<u16, RequestError>;
+    fn update_pinhole(&self, unique_id: u16, lease_duration: u32) -> Result<(), RequestError>;
+    fn delete_pinhole(&self, unique_id: u16) -> Result<(), RequestError>;
 }
 
 struct GatewayWrapperReal {
@@ -101,12 +115,179 @@ impl GatewayWrapper for GatewayWrapperReal {
     ) -> Result<(), RemovePortError> {
         self.delegate.remove_port(protocol, external_port)
     }
+
+    fn add_pinhole(
+        &self,
+        local_addr: SocketAddrV6,
+        lease_duration: u32,
+    ) -> Result<u16, RequestError> {
+        // An empty RemoteHost and a zero RemotePort are wildcards: anyone may connect.
+        let response = self.firewall_control_action(
+            "AddPinhole",
+            &format!(
+                "<RemoteHost></RemoteHost><RemotePort>0</RemotePort>\
+                 <InternalClient>{}</InternalClient><InternalPort>{}</InternalPort>\
+                 <Protocol>{}</Protocol><LeaseTime>{}</LeaseTime>",
+                local_addr.ip(),
+                local_addr.port(),
+                TCP_PROTOCOL_NUMBER,
+                lease_duration
+            ),
+        )?;
+        match xml_element_text(&response, "UniqueID").map(|id| id.parse::<u16>()) {
+            Some(Ok(unique_id)) => Ok(unique_id),
+            _ => Err(RequestError::InvalidResponse(response)),
+        }
+    }
+
+    fn update_pinhole(&self, unique_id: u16, lease_duration: u32) -> Result<(), RequestError> {
+        self.firewall_control_action(
+            "UpdatePinhole",
+            &format!(
+                "<UniqueID>{}</UniqueID><NewLeaseTime>{}</NewLeaseTime>",
+                unique_id, lease_duration
+            ),
+        )
+        .map(|_| ())
+    }
+
+    fn delete_pinhole(&self, unique_id: u16) -> Result<(), RequestError> {
+        self.firewall_control_action(
+            "DeletePinhole",
+            &format!("<UniqueID>{}</UniqueID>", unique_id),
+        )
+        .map(|_| ())
+    }
 }
 
 impl GatewayWrapperReal {
     fn new(delegate: Gateway) -> Self {
         Self { delegate }
     }
+
+    fn firewall_control_action(
+        &self,
+        action: &str,
+        arguments: &str,
+    ) -> Result<String, RequestError> {
+        let addr = self.delegate.addr;
+        let description = http_exchange(
+            addr,
+            &make_http_request("GET", url_path(&self.delegate.root_url), addr, &[], ""),
+        )?;
+        let control_url =
+            find_control_url(&description, FIREWALL_CONTROL_SERVICE).ok_or_else(|| {
+                RequestError::InvalidResponse(format!(
+                    "Router at {} doesn't offer {}",
+                    addr, FIREWALL_CONTROL_SERVICE
+                ))
+            })?;
+        let soap_action = format!("\"{}#{}\"", FIREWALL_CONTROL_SERVICE, action);
+        http_exchange(
+            addr,
+            &make_http_request(
+                "POST",
+                url_path(control_url),
+                addr,
+                &[
+                    ("Content-Type", "text/xml; charset=\"utf-8\""),
+                    ("SOAPAction", &soap_action),
+                ],
+                &make_soap_envelope(action, arguments),
+            ),
+        )
+    }
+}
+
+fn make_soap_envelope(action: &str, arguments: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\"?>\
+         <s:Envelope xmlns:s=\"http://schemas.xmlsoap.org/soap/envelope/\" \
+         s:encodingStyle=\"http://schemas.xmlsoap.org/soap/encoding/\">\
+         <s:Body><u:{} xmlns:u=\"{}\">{}</u:{}></s:Body></s:Envelope>",
+        action, FIREWALL_CONTROL_SERVICE, arguments, action
+    )
+}
+
+// HTTP/1.0, so that the router can't answer with a chunked body.
+fn make_http_request(
+    method: &str,
+    path: &str,
+    addr: SocketAddrV4,
+    headers: &[(&str, &str)],
+    body: &str,
+) -> String {
+    let mut request = format!("{} {} HTTP/1.0\r\nHost: {}\r\n", method, path, addr);
+    headers
+        .iter()
+        .for_each(|(name, value)| request.push_str(&format!("{}: {}\r\n", name, value)));
+    if !body.is_empty() {
+        request.push_str(&format!("Content-Length: {}\r\n", body.len()));
+    }
+    request.push_str("\r\n");
+    request.push_str(body);
+    request
+}
+
+fn http_exchange(addr: SocketAddrV4, request: &str) -> Result<String, RequestError> {
+    let mut stream = TcpStream::connect_timeout(&SocketAddr::V4(addr), ROUTER_HTTP_TIMEOUT)
+        .map_err(RequestError::IoError)?;
+    stream
+        .set_read_timeout(Some(ROUTER_HTTP_TIMEOUT))
+        .map_err(RequestError::IoError)?;
+    stream
+        .write_all(request.as_bytes())
+        .map_err(RequestError::IoError)?;
+    let mut response = String::new();
+    stream
+        .read_to_string(&mut response)
+        .map_err(RequestError::IoError)?;
+    parse_http_response(&response)
+}
+
+fn parse_http_response(response: &str) -> Result<String, RequestError> {
+    let (head, body) = match response.find("\r\n\r\n") {
+        Some(idx) => (&response[..idx], &response[(idx + 4)..]),
+        None => return Err(RequestError::InvalidResponse(response.to_string())),
+    };
+    match head
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+    {
+        Some("200") => Ok(body.to_string()),
+        Some("500") => match xml_element_text(body, "errorCode").map(|code| code.parse::<u16>()) {
+            Some(Ok(code)) => Err(RequestError::ErrorCode(
+                code,
+                xml_element_text(body, "errorDescription")
+                    .unwrap_or("")
+                    .to_string(),
+            )),
+            _ => Err(RequestError::InvalidResponse(body.to_string())),
+        },
+        _ => Err(RequestError::InvalidResponse(head.to_string())),
+    }
+}
+
+fn url_path(url: &str) -> &str {
+    match url.strip_prefix("http://") {
+        Some(rest) => rest.find('/').map(|idx| &rest[idx..]).unwrap_or("/"),
+        None => url,
+    }
+}
+
+fn find_control_url<'a>(description: &'a str, service_type: &str) -> Option<&'a str> {
+    let service = &description[description.find(service_type)?..];
+    let service = &service[..service.find("</service>").unwrap_or(service.len())];
+    xml_element_text(service, "controlURL")
+}
+
+fn xml_element_text<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let open_tag = format!("<{}>", tag);
+    let close_tag = format!("</{}>", tag);
+    let start = xml.find(&open_tag)? + open_tag.len();
+    let len = xml[start..].find(&close_tag)?;
+    Some(xml[start..(start + len)].trim())
 }
 
 struct IgdpTransactorInner {
@@ -114,6 +295,7 @@ struct IgdpTransactorInner {
     housekeeping_commander_opt: Option<Sender<HousekeepingThreadCommand>>,
     public_ip_opt: Option<Ipv4Addr>,
     mapping_adder: Box<dyn MappingAdder>,
+    pinhole_ids: HashMap<(Ipv6Addr, u16), u16>,
     logger: Logger,
 }
 
@@ -244,6 +426,109 @@ impl Transactor for IgdpTransactor {
         }
     }
 
+    fn add_pinhole(
+        &self,
+        router_ip: IpAddr,
+        local_ip: Ipv6Addr,
+        hole_port: u16,
+        lifetime: u32,
+    ) -> Result<u32, AutomapError> {
+        self.ensure_gateway()?;
+        let mut inner = self.inner();
+        let lease = lifetime.clamp(1, MAX_PINHOLE_LEASE_SECONDS);
+        debug!(
+            inner.logger,
+            "Adding pinhole for port {} on {} through router at {} for {} seconds",
+            hole_port,
+            local_ip,
+            router_ip,
+            lease
+        );
+        let add_result = inner
+            .gateway_opt
+            .as_ref()
+            .expect("ensure_gateway() failed")
+            .add_pinhole(SocketAddrV6::new(local_ip, hole_port, 0, 0), lease);
+        let unique_id = match add_result {
+            Ok(unique_id) => unique_id,
+            Err(e) => {
+                warning!(
+                    inner.logger,
+                    "Failed to add {}sec pinhole for port {} on {}: \"{:?}\"",
+                    lease,
+                    hole_port,
+                    local_ip,
+                    e
+                );
+                return Err(AutomapError::PermanentMappingError(format!("{:?}", e)));
+            }
+        };
+        inner.pinhole_ids.insert((local_ip, hole_port), unique_id);
+        let pinhole_config = PinholeConfig {
+            local_ip,
+            hole_port,
+            next_lifetime: Duration::from_secs(lease as u64),
+            remap_interval: Duration::from_secs((lease / 2) as u64),
+        };
+        match inner.housekeeping_commander_opt.as_ref() {
+            Some(commander) => commander
+                .try_send(HousekeepingThreadCommand::InitializePinholeConfig(
+                    pinhole_config,
+                ))
+                .expect("Housekeeping thread died"),
+            None => panic!("Start housekeeping thread before calling add_pinhole()"),
+        }
+        Ok(lease / 2)
+    }
+
+    fn delete_pinhole(
+        &self,
+        router_ip: IpAddr,
+        local_ip: Ipv6Addr,
+        hole_port: u16,
+    ) -> Result<(), AutomapError> {
+        self.ensure_gateway()?;
+        let mut inner = self.inner();
+        debug!(
+            inner.logger,
+            "Deleting pinhole for port {} on {} through router at {}",
+            hole_port,
+            local_ip,
+            router_ip
+        );
+        let unique_id = match inner.pinhole_ids.get(&(local_ip, hole_port)) {
+            Some(unique_id) => *unique_id,
+            None => {
+                return Err(AutomapError::DeleteMappingError(format!(
+                    "No pinhole for port {} on {}",
+                    hole_port, local_ip
+                )))
+            }
+        };
+        match inner
+            .gateway_opt
+            .as_ref()
+            .expect("ensure_gateway() failed")
+            .delete_pinhole(unique_id)
+        {
+            Ok(_) => {
+                inner.pinhole_ids.remove(&(local_ip, hole_port));
+                Ok(())
+            }
+            Err(e) => {
+                warning!(
+                    inner.logger,
+                    "Can't delete pinhole for port {} on {} through router at {}: \"{:?}\"",
+                    hole_port,
+                    local_ip,
+                    router_ip,
+                    e
+                );
+                Err(AutomapError::DeleteMappingError(format!("{:?}", e)))
+            }
+        }
+    }
+
     fn protocol(&self) -> AutomapProtocol {
         AutomapProtocol::Igdp
     }
@@ -339,6 +624,7 @@ impl IgdpTransactor {
             housekeeping_commander_opt: None,
             public_ip_opt: None,
             mapping_adder: Box::new(MappingAdderReal::new()),
+            pinhole_ids: HashMap::new(),
             logger: Logger::new("IgdpTransactor"),
         }));
         Self {
@@ -388,6 +674,7 @@ impl IgdpTransactor {
         let mut last_remapped = Instant::now();
         let mut last_announcement_check = Instant::now();
         let mut mapping_config_opt = None;
+        let mut pinholes: HashMap<(Ipv6Addr, u16), (PinholeConfig, Instant)> = HashMap::new();
         loop {
             thread::sleep(housekeeping_thread_loop_delay);
             if last_announcement_check
@@ -404,6 +691,7 @@ impl IgdpTransactor {
                     break;
                 }
             }
+            Self::renew_pinholes_if_necessary(&change_handler, &inner_arc, &mut pinholes);
             match rx.try_recv() {
                 Ok(HousekeepingThreadCommand::InitializeMappingConfig(mapping_config)) => {
                     mapping_config_opt = Some(mapping_config);
@@ -418,6 +706,20 @@ impl IgdpTransactor {
                         }
                     }
                 }
+                Ok(HousekeepingThreadCommand::InitializePinholeConfig(pinhole_config)) => {
+                    let key = (pinhole_config.local_ip, pinhole_config.hole_port);
+                    let inner = inner_arc.lock().expect("IgdpTransactor died");
+                    if inner.pinhole_ids.contains_key(&key) {
+                        pinholes.insert(key, (pinhole_config, Instant::now()));
+                    } else {
+                        error!(
+                            inner.logger,
+                            "IGDP cannot maintain a pinhole for port {} on {} that it didn't open; ignoring",
+                            pinhole_config.hole_port,
+                            pinhole_config.local_ip
+                        );
+                    }
+                }
                 Ok(HousekeepingThreadCommand::Stop) => break,
                 Err(_) => continue,
             }
@@ -473,6 +775,44 @@ impl IgdpTransactor {
         }
     }
 
+    fn renew_pinholes_if_necessary(
+        change_handler: &ChangeHandler,
+        inner_arc: &Arc<Mutex<IgdpTransactorInner>>,
+        pinholes: &mut HashMap<(Ipv6Addr, u16), (PinholeConfig, Instant)>,
+    ) {
+        if pinholes.is_empty() {
+            return;
+        }
+        let inner = inner_arc.lock().expect("IgdpTransactor died");
+        // Pinholes that have been deleted since the last pass have no ID anymore.
+        pinholes.retain(|key, (pinhole_config, last_renewed)| {
+            let unique_id = match inner.pinhole_ids.get(key) {
+                Some(unique_id) => *unique_id,
+                None => return false,
+            };
+            if last_renewed.elapsed().gt(&pinhole_config.remap_interval) {
+                info!(
+                    inner.logger,
+                    "Renewing pinhole for port {} on {}",
+                    pinhole_config.hole_port,
+                    pinhole_config.local_ip
+                );
+                if let Err(e) = inner
+                    .gateway_opt
+                    .as_ref()
+                    .expectv("gateway_opt")
+                    .update_pinhole(unique_id, pinhole_config.next_lifetime_secs().max(1))
+                {
+                    let e = AutomapError::PermanentMappingError(format!("{:?}", e));
+                    error!(inner.logger, "Pinhole renewal failure: {:?}", e);
+                    change_handler(AutomapChange::Error(e));
+                }
+                *last_renewed = Instant::now();
+            }
+            true
+        });
+    }
+
     fn remap_port(
         mapping_adder: &dyn MappingAdder,
         gateway: &dyn GatewayWrapper,
@@ -640,6 +980,12 @@ mod tests {
         add_port_results: RefCell<Vec<Result<(), AddPortError>>>,
         remove_port_params: Arc<Mutex<Vec<(PortMappingProtocol, u16)>>>,
         remove_port_results: RefCell<Vec<Result<(), RemovePortError>>>,
+        add_pinhole_params: Arc<Mutex<Vec<(SocketAddrV6, u32)>>>,
+        add_pinhole_results: RefCell<Vec<Result<u16, RequestError>>>,
+        update_pinhole_params: Arc<Mutex<Vec<(u16, u32)>>>,
+        update_pinhole_results: RefCell<Vec<Result<(), RequestError>>>,
+        delete_pinhole_params: Arc<Mutex<Vec<u16>>>,
+        delete_pinhole_results: RefCell<Vec<Result<(), RequestError>>>,
     }
 
     impl GatewayWrapper for GatewayWrapperMock {
@@ -690,6 +1036,31 @@ mod tests {
                 .push((protocol, external_port));
             self.remove_port_results.borrow_mut().remove(0)
         }
+
+        fn add_pinhole(
+            &self,
+            local_addr: SocketAddrV6,
+            lease_duration: u32,
+        ) -> Result<u16, RequestError> {
+            self.add_pinhole_params
+                .lock()
+                .unwrap()
+                .push((local_addr, lease_duration));
+            self.add_pinhole_results.borrow_mut().remove(0)
+        }
+
+        fn update_pinhole(&self, unique_id: u16, lease_duration: u32) -> Result<(), RequestError> {
+            self.update_pinhole_params
+                .lock()
+                .unwrap()
+                .push((unique_id, lease_duration));
+            self.update_pinhole_results.borrow_mut().remove(0)
+        }
+
+        fn delete_pinhole(&self, unique_id: u16) -> Result<(), RequestError> {
+            self.delete_pinhole_params.lock().unwrap().push(unique_id);
+            self.delete_pinhole_results.borrow_mut().remove(0)
+        }
     }
 
     impl GatewayWrapperMock {
@@ -701,6 +1072,12 @@ mod tests {
                 add_port_results: RefCell::new(vec![]),
                 remove_port_params: Arc::new(Mutex::new(vec![])),
                 remove_port_results: RefCell::new(vec![]),
+                add_pinhole_params: Arc::new(Mutex::new(vec![])),
+                add_pinhole_results: RefCell::new(vec![]),
+                update_pinhole_params: Arc::new(Mutex::new(vec![])),
+                update_pinhole_results: RefCell::new(vec![]),
+                delete_pinhole_params: Arc::new(Mutex::new(vec![])),
+                delete_pinhole_results: RefCell::new(vec![]),
             }
         }
 
@@ -739,6 +1116,36 @@ mod tests {
             self.remove_port_results.borrow_mut().push(result);
             self
         }
+
+        fn add_pinhole_params(mut self, params: &Arc<Mutex<Vec<(SocketAddrV6, u32)>>>) -> Self {
+            self.add_pinhole_params = params.clone();
+            self
+        }
+
+        fn add_pinhole_result(self, result: Result<u16, RequestError>) -> Self {
+            self.add_pinhole_results.borrow_mut().push(result);
+            self
+        }
+
+        fn update_pinhole_params(mut self, params: &Arc<Mutex<Vec<(u16, u32)>>>) -> Self {
+            self.update_pinhole_params = params.clone();
+            self
+        }
+
+        fn update_pinhole_result(self, result: Result<(), RequestError>) -> Self {
+            self.update_pinhole_results.borrow_mut().push(result);
+            self
+        }
+
+        fn delete_pinhole_params(mut self, params: &Arc<Mutex<Vec<u16>>>) -> Self {
+            self.delete_pinhole_params = params.clone();
+            self
+        }
+
+        fn delete_pinhole_result(self, result: Result<(), RequestError>) -> Self {
+            self.delete_pinhole_results.borrow_mut().push(result);
+            self
+        }
     }
 
     struct MappingAdderMock {
@@ -794,6 +1201,252 @@ mod tests {
         assert_eq!(method, AutomapProtocol::Igdp);
     }
 
+    #[test]
+    fn add_pinhole_works() {
+        let add_pinhole_params_arc = Arc::new(Mutex::new(vec![]));
+        let local_ip = Ipv6Addr::from_str("2001:db8::1234").unwrap();
+        let router_ip = IpAddr::from_str("192.168.0.1").unwrap();
+        let gateway = GatewayWrapperMock::new()
+            .add_pinhole_params(&add_pinhole_params_arc)
+            .add_pinhole_result(Ok(42));
+        let gateway_factory = GatewayFactoryMock::new().make_result(Ok(gateway));
+        let mut subject = IgdpTransactor::new();
+        subject.gateway_factory = Box::new(gateway_factory);
+        let (tx, rx) = unbounded();
+        subject.inner_arc.lock().unwrap().housekeeping_commander_opt = Some(tx);
+
+        let result = subject.add_pinhole(router_ip, local_ip, 1234, 600);
+
+        assert_eq!(result, Ok(300));
+        let add_pinhole_params = add_pinhole_params_arc.lock().unwrap();
+        assert_eq!(
+            *add_pinhole_params,
+            vec![(SocketAddrV6::new(local_ip, 1234, 0, 0), 600)]
+        );
+        assert_eq!(
+            subject.inner_arc.lock().unwrap().pinhole_ids,
+            vec![((local_ip, 1234), 42)]
+                .into_iter()
+                .collect::<HashMap<_, _>>()
+        );
+        match rx.try_recv().unwrap() {
+            HousekeepingThreadCommand::InitializePinholeConfig(pinhole_config) => assert_eq!(
+                pinhole_config,
+                PinholeConfig {
+                    local_ip,
+                    hole_port: 1234,
+                    next_lifetime: Duration::from_secs(600),
+                    remap_interval: Duration::from_secs(300),
+                }
+            ),
+            x => panic!("Expecting InitializePinholeConfig, got {:?}", x),
+        }
+    }
+
+    #[test]
+    fn add_pinhole_limits_lease_to_what_igdv2_allows() {
+        let add_pinhole_params_arc = Arc::new(Mutex::new(vec![]));
+        let local_ip = Ipv6Addr::from_str("2001:db8::1234").unwrap();
+        let gateway = GatewayWrapperMock::new()
+            .add_pinhole_params(&add_pinhole_params_arc)
+            .add_pinhole_result(Ok(42));
+        let gateway_factory = GatewayFactoryMock::new().make_result(Ok(gateway));
+        let mut subject = IgdpTransactor::new();
+        subject.gateway_factory = Box::new(gateway_factory);
+        subject.inner_arc.lock().unwrap().housekeeping_commander_opt = Some(unbounded().0);
+
+        let result = subject.add_pinhole(
+            IpAddr::from_str("192.168.0.1").unwrap(),
+            local_ip,
+            1234,
+            u32::MAX,
+        );
+
+        assert_eq!(result, Ok(43200));
+        let add_pinhole_params = add_pinhole_params_arc.lock().unwrap();
+        assert_eq!(add_pinhole_params[0].1, 86400);
+    }
+
+    #[test]
+    fn add_pinhole_handles_error() {
+        init_test_logging();
+        let local_ip = Ipv6Addr::from_str("2001:db8::1234").unwrap();
+        let gateway = GatewayWrapperMock::new().add_pinhole_result(Err(RequestError::ErrorCode(
+            606,
+            "Action not authorized".to_string(),
+        )));
+        let gateway_factory = GatewayFactoryMock::new().make_result(Ok(gateway));
+        let mut subject = IgdpTransactor::new();
+        subject.gateway_factory = Box::new(gateway_factory);
+
+        let result = subject.add_pinhole(
+            IpAddr::from_str("192.168.0.1").unwrap(),
+            local_ip,
+            1234,
+            600,
+        );
+
+        assert_eq!(
+            result,
+            Err(AutomapError::PermanentMappingError(
+                "ErrorCode(606, \"Action not authorized\")".to_string()
+            ))
+        );
+        assert!(subject.inner_arc.lock().unwrap().pinhole_ids.is_empty());
+        TestLogHandler::new().exists_log_containing("WARN: IgdpTransactor: Failed to add 600sec pinhole for port 1234 on 2001:db8::1234: \"ErrorCode(606, \"Action not authorized\")\"");
+    }
+
+    #[test]
+    fn delete_pinhole_works() {
+        let delete_pinhole_params_arc = Arc::new(Mutex::new(vec![]));
+        let local_ip = Ipv6Addr::from_str("2001:db8::1234").unwrap();
+        let gateway = GatewayWrapperMock::new()
+            .delete_pinhole_params(&delete_pinhole_params_arc)
+            .delete_pinhole_result(Ok(()));
+        let gateway_factory = GatewayFactoryMock::new().make_result(Ok(gateway));
+        let mut subject = IgdpTransactor::new();
+        subject.gateway_factory = Box::new(gateway_factory);
+        {
+            let mut inner = subject.inner_arc.lock().unwrap();
+            inner.pinhole_ids.insert((local_ip, 1234), 42);
+            inner.pinhole_ids.insert((local_ip, 2345), 43);
+        }
+
+        let result =
+            subject.delete_pinhole(IpAddr::from_str("192.168.0.1").unwrap(), local_ip, 1234);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(*delete_pinhole_params_arc.lock().unwrap(), vec![42]);
+        assert_eq!(
+            subject.inner_arc.lock().unwrap().pinhole_ids,
+            vec![((local_ip, 2345), 43)]
+                .into_iter()
+                .collect::<HashMap<_, _>>()
+        );
+    }
+
+    #[test]
+    fn delete_pinhole_complains_about_pinhole_it_did_not_open() {
+        let local_ip = Ipv6Addr::from_str("2001:db8::1234").unwrap();
+        let gateway_factory = GatewayFactoryMock::new().make_result(Ok(GatewayWrapperMock::new()));
+        let mut subject = IgdpTransactor::new();
+        subject.gateway_factory = Box::new(gateway_factory);
+
+        let result =
+            subject.delete_pinhole(IpAddr::from_str("192.168.0.1").unwrap(), local_ip, 1234);
+
+        assert_eq!(
+            result,
+            Err(AutomapError::DeleteMappingError(
+                "No pinhole for port 1234 on 2001:db8::1234".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn delete_pinhole_handles_error() {
+        let local_ip = Ipv6Addr::from_str("2001:db8::1234").unwrap();
+        let gateway = GatewayWrapperMock::new()
+            .delete_pinhole_result(Err(RequestError::ErrorCode(704, "NoSuchEntry".to_string())));
+        let gateway_factory = GatewayFactoryMock::new().make_result(Ok(gateway));
+        let mut subject = IgdpTransactor::new();
+        subject.gateway_factory = Box::new(gateway_factory);
+        subject
+            .inner_arc
+            .lock()
+            .unwrap()
+            .pinhole_ids
+            .insert((local_ip, 1234), 42);
+
+        let result =
+            subject.delete_pinhole(IpAddr::from_str("192.168.0.1").unwrap(), local_ip, 1234);
+
+        assert_eq!(
+            result,
+            Err(AutomapError::DeleteMappingError(
+                "ErrorCode(704, \"NoSuchEntry\")".to_string()
+            ))
+        );
+        assert_eq!(
+            subject
+                .inner_arc
+                .lock()
+                .unwrap()
+                .pinhole_ids
+                .get(&(local_ip, 1234)),
+            Some(&42)
+        );
+    }
+
+    #[test]
+    fn parse_http_response_returns_body_of_successful_response() {
+        let response = "HTTP/1.0 200 OK\r\nContent-Type: text/xml\r\n\r\n<UniqueID>42</UniqueID>";
+
+        let result = parse_http_response(response);
+
+        assert_eq!(result.unwrap(), "<UniqueID>42</UniqueID>".to_string());
+    }
+
+    #[test]
+    fn parse_http_response_extracts_upnp_error_from_soap_fault() {
+        let response = "HTTP/1.0 500 Internal Server Error\r\n\r\n<s:Fault><detail><UPnPError>\
+            <errorCode>606</errorCode><errorDescription>Action not authorized</errorDescription>\
+            </UPnPError></detail></s:Fault>";
+
+        let result = parse_http_response(response);
+
+        assert_eq!(
+            format!("{:?}", result),
+            "Err(ErrorCode(606, \"Action not authorized\"))".to_string()
+        );
+    }
+
+    #[test]
+    fn find_control_url_picks_the_firewall_control_service() {
+        let description = "<serviceList><service>\
+            <serviceType>urn:schemas-upnp-org:service:WANIPConnection:2</serviceType>\
+            <controlURL>/ctl/IPConn</controlURL></service><service>\
+            <serviceType>urn:schemas-upnp-org:service:WANIPv6FirewallControl:1</serviceType>\
+            <serviceId>urn:upnp-org:serviceId:WANIPv6Firewall1</serviceId>\
+            <controlURL>/ctl/IP6FCtl</controlURL></service></serviceList>";
+
+        let result = find_control_url(description, FIREWALL_CONTROL_SERVICE);
+
+        assert_eq!(result, Some("/ctl/IP6FCtl"));
+        assert_eq!(
+            find_control_url("<serviceList/>", FIREWALL_CONTROL_SERVICE),
+            None
+        );
+    }
+
+    #[test]
+    fn url_path_strips_scheme_and_authority() {
+        assert_eq!(
+            url_path("http://192.168.0.1:5000/rootDesc.xml"),
+            "/rootDesc.xml"
+        );
+        assert_eq!(url_path("http://192.168.0.1:5000"), "/");
+        assert_eq!(url_path("/ctl/IP6FCtl"), "/ctl/IP6FCtl");
+    }
+
+    #[test]
+    fn make_http_request_includes_headers_and_body() {
+        let result = make_http_request(
+            "POST",
+            "/ctl/IP6FCtl",
+            SocketAddrV4::from_str("192.168.0.1:5000").unwrap(),
+            &[("SOAPAction", "\"booga\"")],
+            "body",
+        );
+
+        assert_eq!(
+            result,
+            "POST /ctl/IP6FCtl HTTP/1.0\r\nHost: 192.168.0.1:5000\r\nSOAPAction: \"booga\"\r\n\
+             Content-Length: 4\r\n\r\nbody"
+                .to_string()
+        );
+    }
+
     #[test]
     fn find_routers_works() {
         let make_params_arc = Arc::new(Mutex::new(vec![]));
@@ -1218,6 +1871,7 @@ mod tests {
             housekeeping_commander_opt: Some(tx.clone()),
             public_ip_opt: None,
             mapping_adder: Box::new(MappingAdderMock::new()), // no provision for add_mapping()
+            pinhole_ids: HashMap::new(),
             logger: Logger::new("no_remap_test"),
         }));
         tx.send(HousekeepingThreadCommand::InitializeMappingConfig(
@@ -1259,6 +1913,7 @@ mod tests {
             housekeeping_commander_opt: None,
             public_ip_opt: None,
             mapping_adder,
+            pinhole_ids: HashMap::new(),
             logger: Logger::new("timed_remap_test"),
         }));
         let mapping_config = MappingConfig {
@@ -1293,6 +1948,7 @@ mod tests {
             housekeeping_commander_opt: Some(tx.clone()),
             public_ip_opt: Some(public_ip),
             mapping_adder: Box::new(MappingAdderMock::new()),
+            pinhole_ids: HashMap::new(),
             logger: Logger::new("test"),
         }));
         tx.send(HousekeepingThreadCommand::SetRemapIntervalMs(1234))
@@ -1307,6 +1963,130 @@ mod tests {
         );
     }
 
+    #[test]
+    fn thread_guts_renews_pinhole_when_interval_runs_out() {
+        init_test_logging();
+        let (tx, rx) = unbounded();
+        let update_pinhole_params_arc = Arc::new(Mutex::new(vec![]));
+        let local_ip = Ipv6Addr::from_str("2001:db8::1234").unwrap();
+        let gateway = GatewayWrapperMock::new()
+            .update_pinhole_params(&update_pinhole_params_arc)
+            .update_pinhole_result(Ok(()));
+        let inner_arc = Arc::new(Mutex::new(IgdpTransactorInner {
+            gateway_opt: Some(Box::new(gateway)),
+            housekeeping_commander_opt: Some(tx.clone()),
+            public_ip_opt: None,
+            mapping_adder: Box::new(MappingAdderMock::new()),
+            pinhole_ids: vec![((local_ip, 6689), 42)].into_iter().collect(),
+            logger: Logger::new("timed_pinhole_test"),
+        }));
+        tx.send(HousekeepingThreadCommand::InitializePinholeConfig(
+            PinholeConfig {
+                local_ip,
+                hole_port: 6689,
+                next_lifetime: Duration::from_secs(600),
+                remap_interval: Duration::from_secs(0),
+            },
+        ))
+        .unwrap();
+        tx.send(HousekeepingThreadCommand::Stop).unwrap();
+
+        let _ = IgdpTransactor::thread_guts(
+            Duration::from_millis(1),
+            Duration::from_secs(100),
+            Box::new(|_| ()),
+            inner_arc,
+            rx,
+        );
+
+        assert_eq!(*update_pinhole_params_arc.lock().unwrap(), vec![(42, 600)]);
+        TestLogHandler::new().exists_log_containing(
+            "INFO: timed_pinhole_test: Renewing pinhole for port 6689 on 2001:db8::1234",
+        );
+    }
+
+    #[test]
+    fn thread_guts_logs_and_ignores_pinhole_config_it_did_not_open() {
+        init_test_logging();
+        let (tx, rx) = unbounded();
+        // No update_pinhole_result; if the MUT tries to renew the pinhole, the test will fail.
+        let inner_arc = Arc::new(Mutex::new(IgdpTransactorInner {
+            gateway_opt: Some(Box::new(GatewayWrapperMock::new())),
+            housekeeping_commander_opt: Some(tx.clone()),
+            public_ip_opt: None,
+            mapping_adder: Box::new(MappingAdderMock::new()),
+            pinhole_ids: HashMap::new(),
+            logger: Logger::new("thread_guts_logs_and_ignores_pinhole_config_it_did_not_open"),
+        }));
+        tx.send(HousekeepingThreadCommand::InitializePinholeConfig(
+            PinholeConfig {
+                local_ip: Ipv6Addr::from_str("2001:db8::1234").unwrap(),
+                hole_port: 6689,
+                next_lifetime: Duration::from_secs(600),
+                remap_interval: Duration::from_secs(0),
+            },
+        ))
+        .unwrap();
+        tx.send(HousekeepingThreadCommand::Stop).unwrap();
+
+        let _ = IgdpTransactor::thread_guts(
+            Duration::from_millis(1),
+            Duration::from_secs(100),
+            Box::new(|_| ()),
+            inner_arc,
+            rx,
+        );
+
+        TestLogHandler::new().exists_log_containing("ERROR: thread_guts_logs_and_ignores_pinhole_config_it_did_not_open: IGDP cannot maintain a pinhole for port 6689 on 2001:db8::1234 that it didn't open; ignoring");
+    }
+
+    #[test]
+    fn renew_pinholes_if_necessary_reports_failure_and_forgets_deleted_pinholes() {
+        init_test_logging();
+        let local_ip = Ipv6Addr::from_str("2001:db8::1234").unwrap();
+        let gateway = GatewayWrapperMock::new()
+            .update_pinhole_result(Err(RequestError::ErrorCode(704, "NoSuchEntry".to_string())));
+        let inner_arc = Arc::new(Mutex::new(IgdpTransactorInner {
+            gateway_opt: Some(Box::new(gateway)),
+            housekeeping_commander_opt: None,
+            public_ip_opt: None,
+            mapping_adder: Box::new(MappingAdderMock::new()),
+            pinhole_ids: vec![((local_ip, 6689), 42)].into_iter().collect(),
+            logger: Logger::new("renew_pinholes_test"),
+        }));
+        let change_log_arc = Arc::new(Mutex::new(vec![]));
+        let change_log_inner = change_log_arc.clone();
+        let change_handler: ChangeHandler =
+            Box::new(move |change| change_log_inner.lock().unwrap().push(change));
+        let pinhole_config = PinholeConfig {
+            local_ip,
+            hole_port: 6689,
+            next_lifetime: Duration::from_secs(600),
+            remap_interval: Duration::from_secs(0),
+        };
+        let deleted_config = PinholeConfig {
+            hole_port: 7789,
+            ..pinhole_config
+        };
+        let mut pinholes = vec![
+            ((local_ip, 6689), (pinhole_config, Instant::now())),
+            ((local_ip, 7789), (deleted_config, Instant::now())),
+        ]
+        .into_iter()
+        .collect::<HashMap<(Ipv6Addr, u16), (PinholeConfig, Instant)>>();
+
+        IgdpTransactor::renew_pinholes_if_necessary(&change_handler, &inner_arc, &mut pinholes);
+
+        assert_eq!(pinholes.keys().collect::<Vec<_>>(), vec![&(local_ip, 6689)]);
+        assert_eq!(
+            *change_log_arc.lock().unwrap(),
+            vec![AutomapChange::Error(AutomapError::PermanentMappingError(
+                "ErrorCode(704, \"NoSuchEntry\")".to_string()
+            ))]
+        );
+        TestLogHandler::new().exists_log_containing("ERROR: renew_pinholes_test: Pinhole renewal failure: PermanentMappingError(\"ErrorCode(704, \\\"NoSuchEntry\\\")\")");
+    }
+
     #[test]
     fn ensure_gateway_handles_missing_gateway() {
         let gateway_factory =
@@ -1328,6 +2108,7 @@ mod tests {
             housekeeping_commander_opt: None,
             public_ip_opt: Some(new_public_ip),
             mapping_adder: Box::new(MappingAdderMock::new()),
+            pinhole_ids: HashMap::new(),
             logger: Logger::new("thread_guts_iteration_handles_missing_mapping_config"),
         }));
         let change_handler: ChangeHandler = Box::new(move |_| panic!("Shouldn't be called"));
@@ -1355,6 +2136,7 @@ mod tests {
             mapping_adder: Box::new(MappingAdderMock::new().add_mapping_result(Err(
                 AutomapError::PermanentMappingError("Booga".to_string()),
             ))),
+            pinhole_ids: HashMap::new(),
             logger: Logger::new("test"),
         }));
         let change_log_arc = Arc::new(Mutex::new(vec![]));
@@ -1402,6 +2184,7 @@ mod tests {
             housekeeping_commander_opt: None,
             public_ip_opt: Some(Ipv4Addr::from_str("1.2.3.4").unwrap()),
             mapping_adder: Box::new(mapping_adder),
+            pinhole_ids: HashMap::new(),
             logger: Logger::new("thread_guts_iteration_reports_router_error_to_change_handler"),
         }));
         let change_log_arc = Arc::new(Mutex::new(vec![]));
@@ -1449,6 +2232,7 @@ mod tests {
             housekeeping_commander_opt: None,
             public_ip_opt: None,
             mapping_adder: Box::new(MappingAdderMock::new()),
+            pinhole_ids: HashMap::new(),
             logger: Logger::new("test"),
         };
         let mapping_config = MappingConfig {
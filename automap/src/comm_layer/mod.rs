@@ -9,7 +9,7 @@ use crossbeam_channel::Sender;
 
 use masq_lib::utils::AutomapProtocol;
 
-use crate::comm_layer::pcp_pmp_common::MappingConfig;
+use crate::comm_layer::pcp_pmp_common::{MappingConfig, PinholeConfig};
 use crate::control_layer::automap_control::ChangeHandler;
 use crate::protocols::utils::ParseError;
 
@@ -94,7 +94,7 @@ impl AutomapError {
     }
 }
 
-pub trait Transactor {
+pub trait Transactor: Send {
     fn find_routers(&self) -> Result<Vec<IpAddr>, AutomapError>;
     fn get_public_ip(&self, router_ip: IpAddr) -> Result<IpAddr, AutomapError>;
     fn add_mapping(
@@ -106,6 +106,19 @@ pub trait Transactor {
     fn add_permanent_mapping(&self, router_ip: IpAddr, hole_port: u16)
         -> Result<u32, AutomapError>;
     fn delete_mapping(&self, router_ip: IpAddr, hole_port: u16) -> Result<(), AutomapError>;
+    fn add_pinhole(
+        &self,
+        router_ip: IpAddr,
+        local_ip: Ipv6Addr,
+        hole_port: u16,
+        lifetime: u32,
+    ) -> Result<u32, AutomapError>;
+    fn delete_pinhole(
+        &self,
+        router_ip: IpAddr,
+        local_ip: Ipv6Addr,
+        hole_port: u16,
+    ) -> Result<(), AutomapError>;
     fn protocol(&self) -> AutomapProtocol;
     fn start_housekeeping_thread(
         &mut self,
@@ -127,6 +140,7 @@ pub enum HousekeepingThreadCommand {
     Stop,
     SetRemapIntervalMs(u64),
     InitializeMappingConfig(MappingConfig),
+    InitializePinholeConfig(PinholeConfig),
 }
 
 pub trait LocalIpFinder: Send {
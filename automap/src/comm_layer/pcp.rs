@@ -1,9 +1,10 @@
 // Copyright (c) 2019-2021, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 
 use std::any::Any;
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::io::ErrorKind;
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 use std::sync::{Arc, Mutex, MutexGuard};
 use std::thread::JoinHandle;
 use std::time::{Duration, Instant};
@@ -20,7 +21,7 @@ use masq_lib::{debug, warning};
 
 use crate::comm_layer::pcp_pmp_common::{
     find_routers, make_local_socket_address, FreePortFactory, FreePortFactoryReal, MappingConfig,
-    UdpSocketFactoryReal, UdpSocketWrapper, UdpSocketWrapperFactory,
+    PinholeConfig, UdpSocketFactoryReal, UdpSocketWrapper, UdpSocketWrapperFactory,
     HOUSEKEEPING_THREAD_LOOP_DELAY_MILLIS, ROUTER_PORT,
 };
 use crate::comm_layer::{
@@ -32,6 +33,10 @@ use crate::protocols::pcp::map_packet::{MapOpcodeData, Protocol};
 use crate::protocols::pcp::pcp_packet::{Opcode, PcpPacket, ResultCode};
 use crate::protocols::utils::{Direction, Packet};
 
+// RFC 7723: PCP servers listen on this anycast address, which lets an IPv6 pinhole request
+// reach the router when the only router address we know is an IPv4 one.
+const PCP_IPV6_ANYCAST_ADDRESS: Ipv6Addr = Ipv6Addr::new(0x2001, 0x1, 0, 0, 0, 0, 0, 0x1);
+
 trait MappingNonceFactory: Send {
     fn make(&self) -> [u8; 12];
 }
@@ -179,6 +184,75 @@ impl Transactor for PcpTransactor {
             .map(|_| ())
     }
 
+    fn add_pinhole(
+        &self,
+        router_ip: IpAddr,
+        local_ip: Ipv6Addr,
+        hole_port: u16,
+        lifetime: u32,
+    ) -> Result<u32, AutomapError> {
+        debug!(
+            self.logger,
+            "Adding pinhole for port {} on {} through router at {} for {} seconds",
+            hole_port,
+            local_ip,
+            router_ip,
+            lifetime
+        );
+        let inner = self.inner();
+        let mut pinhole_config = PinholeConfig {
+            local_ip,
+            hole_port,
+            next_lifetime: Duration::from_secs(lifetime as u64),
+            remap_interval: Duration::from_secs(0),
+        };
+        let approved_lifetime = inner
+            .mapping_transactor
+            .transact_pinhole(
+                &inner.factories,
+                SocketAddr::new(router_ip, self.router_port),
+                &mut pinhole_config,
+            )?
+            .0;
+        self.housekeeper_commander_opt
+            .as_ref()
+            .expect("Start housekeeping thread before adding a pinhole")
+            .try_send(HousekeepingThreadCommand::InitializePinholeConfig(
+                pinhole_config,
+            ))
+            .expect("Housekeeping thread panicked");
+        Ok(approved_lifetime / 2)
+    }
+
+    fn delete_pinhole(
+        &self,
+        router_ip: IpAddr,
+        local_ip: Ipv6Addr,
+        hole_port: u16,
+    ) -> Result<(), AutomapError> {
+        debug!(
+            self.logger,
+            "Deleting pinhole for port {} on {} through router at {}",
+            hole_port,
+            local_ip,
+            router_ip
+        );
+        let inner = self.inner();
+        inner
+            .mapping_transactor
+            .transact_pinhole(
+                &inner.factories,
+                SocketAddr::new(router_ip, self.router_port),
+                &mut PinholeConfig {
+                    local_ip,
+                    hole_port,
+                    next_lifetime: Duration::from_secs(0),
+                    remap_interval: Duration::from_secs(0),
+                },
+            )
+            .map(|_| ())
+    }
+
     fn protocol(&self) -> AutomapProtocol {
         AutomapProtocol::Pcp
     }
@@ -284,6 +358,7 @@ impl PcpTransactor {
     ) -> ChangeHandler {
         let mut last_remapped = Instant::now();
         let mut mapping_config_opt: Option<MappingConfig> = None;
+        let mut pinholes: HashMap<(Ipv6Addr, u16), (PinholeConfig, Instant)> = HashMap::new();
         loop {
             match rx.try_recv() {
                 Ok(HousekeepingThreadCommand::Stop) => {
@@ -311,6 +386,12 @@ impl PcpTransactor {
                 Ok(HousekeepingThreadCommand::InitializeMappingConfig(mapping_config)) => {
                     mapping_config_opt.replace(mapping_config);
                 }
+                Ok(HousekeepingThreadCommand::InitializePinholeConfig(pinhole_config)) => {
+                    pinholes.insert(
+                        (pinhole_config.local_ip, pinhole_config.hole_port),
+                        (pinhole_config, Instant::now()),
+                    );
+                }
                 Err(_) => (),
             }
             thread::sleep(Duration::from_millis(read_timeout_millis)); // replaces IP-change check
@@ -335,6 +416,18 @@ impl PcpTransactor {
                     }
                 }
             }
+            for (pinhole_config, last_repinholed) in pinholes.values_mut() {
+                if last_repinholed.elapsed().gt(&pinhole_config.remap_interval) {
+                    let inner = inner_arc.lock().expect("PcpTransactor is dead");
+                    if let Err(e) =
+                        Self::renew_pinhole(&inner, router_addr, pinhole_config, &logger)
+                    {
+                        error!(logger, "Pinhole renewal failure: {:?}", e);
+                        change_handler(AutomapChange::Error(e));
+                    }
+                    *last_repinholed = Instant::now();
+                }
+            }
         }
         change_handler
     }
@@ -357,6 +450,25 @@ impl PcpTransactor {
             .transact(&inner.factories, router_addr, mapping_config)?
             .0)
     }
+
+    fn renew_pinhole(
+        inner: &PcpTransactorInner,
+        router_addr: SocketAddr,
+        pinhole_config: &mut PinholeConfig,
+        logger: &Logger,
+    ) -> Result<u32, AutomapError> {
+        info!(
+            logger,
+            "Renewing pinhole for port {} on {}", pinhole_config.hole_port, pinhole_config.local_ip
+        );
+        if pinhole_config.next_lifetime_secs() < 1 {
+            pinhole_config.next_lifetime = Duration::from_secs(1);
+        }
+        Ok(inner
+            .mapping_transactor
+            .transact_pinhole(&inner.factories, router_addr, pinhole_config)?
+            .0)
+    }
 }
 
 trait MappingTransactor: Send {
@@ -366,6 +478,12 @@ trait MappingTransactor: Send {
         router_addr: SocketAddr,
         mapping_config: &mut MappingConfig,
     ) -> Result<(u32, MapOpcodeData), AutomapError>;
+    fn transact_pinhole(
+        &self,
+        factories: &Factories,
+        router_addr: SocketAddr,
+        pinhole_config: &mut PinholeConfig,
+    ) -> Result<(u32, MapOpcodeData), AutomapError>;
 }
 
 struct MappingTransactorReal {
@@ -386,24 +504,124 @@ impl MappingTransactor for MappingTransactorReal {
             router_addr,
             mapping_config.next_lifetime_secs()
         );
-        let (socket_addr, socket_result, local_ip_result, mapping_nonce) =
+        let (socket_addr, socket_result, mapping_nonce) =
             Self::employ_factories(factories, router_addr.ip());
-        let packet = PcpPacket {
+        let local_ip_result = factories.local_ip_finder.find();
+        let packet = Self::make_map_request(
+            local_ip_result?,
+            IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)),
+            mapping_nonce,
+            mapping_config.hole_port,
+            mapping_config.next_lifetime_secs(),
+        );
+        self.exchange(packet, socket_addr, socket_result, router_addr)
+            .map(|(approved_lifetime, opcode_data)| {
+                mapping_config.next_lifetime = Duration::from_secs(approved_lifetime as u64);
+                mapping_config.remap_interval = Duration::from_secs((approved_lifetime / 2) as u64);
+                (approved_lifetime, opcode_data)
+            })
+    }
+
+    fn transact_pinhole(
+        &self,
+        factories: &Factories,
+        router_addr: SocketAddr,
+        pinhole_config: &mut PinholeConfig,
+    ) -> Result<(u32, MapOpcodeData), AutomapError> {
+        debug!(
+            self.logger,
+            "Pinhole transaction: port {} on {} through router at {} for {} seconds",
+            pinhole_config.hole_port,
+            pinhole_config.local_ip,
+            router_addr,
+            pinhole_config.next_lifetime_secs()
+        );
+        // RFC 6887 section 8.1: the server rejects a request whose client IP doesn't match the
+        // source address of the packet, so this has to go out over IPv6 from the local address.
+        let router_addr = match router_addr {
+            SocketAddr::V6(_) => router_addr,
+            SocketAddr::V4(v4_addr) => {
+                SocketAddr::new(IpAddr::V6(PCP_IPV6_ANYCAST_ADDRESS), v4_addr.port())
+            }
+        };
+        let socket_addr = SocketAddr::new(
+            IpAddr::V6(pinhole_config.local_ip),
+            factories.free_port_factory.make(),
+        );
+        let socket_result = factories.socket_factory.make(socket_addr);
+        let mapping_nonce = factories.mapping_nonce_factory.make();
+        // RFC 6887 section 11.1: an all-zeros IPv6 suggested external address asks the PCP
+        // server for a firewall pinhole rather than an IPv4 translation.
+        let packet = Self::make_map_request(
+            IpAddr::V6(pinhole_config.local_ip),
+            IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+            mapping_nonce,
+            pinhole_config.hole_port,
+            pinhole_config.next_lifetime_secs(),
+        );
+        self.exchange(packet, socket_addr, socket_result, router_addr)
+            .map(|(approved_lifetime, opcode_data)| {
+                pinhole_config.next_lifetime = Duration::from_secs(approved_lifetime as u64);
+                pinhole_config.remap_interval = Duration::from_secs((approved_lifetime / 2) as u64);
+                (approved_lifetime, opcode_data)
+            })
+    }
+}
+
+impl Default for MappingTransactorReal {
+    fn default() -> Self {
+        MappingTransactorReal {
+            logger: Logger::new("PcpTransactor"),
+        }
+    }
+}
+
+impl MappingTransactorReal {
+    fn employ_factories(
+        factories: &Factories,
+        router_ip: IpAddr,
+    ) -> (SocketAddr, io::Result<Box<dyn UdpSocketWrapper>>, [u8; 12]) {
+        let free_port = factories.free_port_factory.make();
+        let socket_addr = make_local_socket_address(router_ip.is_ipv4(), free_port);
+        (
+            socket_addr,
+            factories.socket_factory.make(socket_addr),
+            factories.mapping_nonce_factory.make(),
+        )
+    }
+
+    fn make_map_request(
+        client_ip: IpAddr,
+        suggested_external_ip: IpAddr,
+        mapping_nonce: [u8; 12],
+        hole_port: u16,
+        lifetime: u32,
+    ) -> PcpPacket {
+        PcpPacket {
             direction: Direction::Request,
             opcode: Opcode::Map,
             result_code_opt: None,
-            lifetime: mapping_config.next_lifetime_secs(),
-            client_ip_opt: Some(local_ip_result?),
+            lifetime,
+            client_ip_opt: Some(client_ip),
             epoch_time_opt: None,
             opcode_data: Box::new(MapOpcodeData {
                 mapping_nonce,
                 protocol: Protocol::Tcp,
-                internal_port: mapping_config.hole_port,
-                external_port: mapping_config.hole_port,
-                external_ip_address: IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)),
+                internal_port: hole_port,
+                external_port: hole_port,
+                external_ip_address: suggested_external_ip,
             }),
             options: vec![],
-        };
+        }
+    }
+
+    fn exchange(
+        &self,
+        packet: PcpPacket,
+        socket_addr: SocketAddr,
+        socket_result: io::Result<Box<dyn UdpSocketWrapper>>,
+        router_addr: SocketAddr,
+    ) -> Result<(u32, MapOpcodeData), AutomapError> {
         let mut buffer = [0u8; 1100];
         let request_len = packet
             .marshal(&mut buffer)
@@ -493,43 +711,7 @@ impl MappingTransactor for MappingTransactorReal {
             );
             return Err(e);
         }
-        Self::compute_mapping_result(response, router_addr, &self.logger).map(
-            |(approved_lifetime, opcode_data)| {
-                mapping_config.next_lifetime = Duration::from_secs(approved_lifetime as u64);
-                mapping_config.remap_interval = Duration::from_secs((approved_lifetime / 2) as u64);
-                (approved_lifetime, opcode_data)
-            },
-        )
-    }
-}
-
-impl Default for MappingTransactorReal {
-    fn default() -> Self {
-        MappingTransactorReal {
-            logger: Logger::new("PcpTransactor"),
-        }
-    }
-}
-
-impl MappingTransactorReal {
-    #[allow(clippy::type_complexity)]
-    fn employ_factories(
-        factories: &Factories,
-        router_ip: IpAddr,
-    ) -> (
-        SocketAddr,
-        io::Result<Box<dyn UdpSocketWrapper>>,
-        Result<IpAddr, AutomapError>,
-        [u8; 12],
-    ) {
-        let free_port = factories.free_port_factory.make();
-        let socket_addr = make_local_socket_address(router_ip.is_ipv4(), free_port);
-        (
-            socket_addr,
-            factories.socket_factory.make(socket_addr),
-            factories.local_ip_finder.find(),
-            factories.mapping_nonce_factory.make(),
-        )
+        Self::compute_mapping_result(response, router_addr, &self.logger)
     }
 
     fn compute_mapping_result(
@@ -614,6 +796,8 @@ mod tests {
     struct MappingTransactorMock {
         transact_params: Arc<Mutex<Vec<(*const (), SocketAddr, MappingConfig)>>>,
         transact_results: RefCell<Vec<Result<(u32, MapOpcodeData), AutomapError>>>,
+        transact_pinhole_params: Arc<Mutex<Vec<(SocketAddr, PinholeConfig)>>>,
+        transact_pinhole_results: RefCell<Vec<Result<(u32, MapOpcodeData), AutomapError>>>,
     }
 
     unsafe impl Send for MappingTransactorMock {}
@@ -636,6 +820,23 @@ mod tests {
                 self.transact_results.borrow()[0].clone()
             }
         }
+
+        fn transact_pinhole(
+            &self,
+            _factories: &Factories,
+            router_addr: SocketAddr,
+            pinhole_config: &mut PinholeConfig,
+        ) -> Result<(u32, MapOpcodeData), AutomapError> {
+            self.transact_pinhole_params
+                .lock()
+                .unwrap()
+                .push((router_addr, *pinhole_config));
+            if self.transact_pinhole_results.borrow().len() > 1 {
+                self.transact_pinhole_results.borrow_mut().remove(0)
+            } else {
+                self.transact_pinhole_results.borrow()[0].clone()
+            }
+        }
     }
 
     impl MappingTransactorMock {
@@ -643,9 +844,28 @@ mod tests {
             Self {
                 transact_params: Arc::new(Mutex::new(vec![])),
                 transact_results: RefCell::new(vec![]),
+                transact_pinhole_params: Arc::new(Mutex::new(vec![])),
+                transact_pinhole_results: RefCell::new(vec![]),
             }
         }
 
+        fn transact_pinhole_params(
+            mut self,
+            params: &Arc<Mutex<Vec<(SocketAddr, PinholeConfig)>>>,
+        ) -> Self {
+            self.transact_pinhole_params = params.clone();
+            self
+        }
+
+        // Note: the last result supplied will be returned over and over
+        fn transact_pinhole_result(
+            self,
+            result: Result<(u32, MapOpcodeData), AutomapError>,
+        ) -> Self {
+            self.transact_pinhole_results.borrow_mut().push(result);
+            self
+        }
+
         fn transact_params(
             mut self,
             params: &Arc<Mutex<Vec<(*const (), SocketAddr, MappingConfig)>>>,
@@ -1297,6 +1517,222 @@ mod tests {
         );
     }
 
+    #[test]
+    fn add_pinhole_works() {
+        let make_params_arc = Arc::new(Mutex::new(vec![]));
+        let send_to_params_arc = Arc::new(Mutex::new(vec![]));
+        let router_ip = IpAddr::from_str("2001:db8::1").unwrap();
+        let local_ip = Ipv6Addr::from_str("2001:db8::1234").unwrap();
+        let request_packet = PcpPacket {
+            direction: Direction::Request,
+            opcode: Opcode::Map,
+            result_code_opt: None,
+            lifetime: 10000,
+            client_ip_opt: Some(IpAddr::V6(local_ip)),
+            epoch_time_opt: None,
+            opcode_data: Box::new(MapOpcodeData {
+                mapping_nonce: [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12],
+                protocol: Protocol::Tcp,
+                internal_port: 6666,
+                external_port: 6666,
+                external_ip_address: IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+            }),
+            options: vec![],
+        };
+        let mut request = [0u8; 1100];
+        let request_len = request_packet.marshal(&mut request).unwrap();
+        let mut response_packet = vanilla_response();
+        response_packet.opcode = Opcode::Map;
+        response_packet.lifetime = 8000;
+        response_packet.opcode_data = Box::new(MapOpcodeData {
+            mapping_nonce: [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12],
+            protocol: Protocol::Tcp,
+            internal_port: 6666,
+            external_port: 6666,
+            external_ip_address: IpAddr::V6(local_ip),
+        });
+        let mut response = [0u8; 1100];
+        let response_len = response_packet.marshal(&mut response).unwrap();
+        let socket = UdpSocketWrapperMock::new()
+            .set_read_timeout_result(Ok(()))
+            .send_to_params(&send_to_params_arc)
+            .send_to_result(Ok(1000))
+            .recv_from_result(
+                Ok((1000, SocketAddr::new(router_ip, ROUTER_PORT))),
+                response[0..response_len].to_vec(),
+            );
+        let socket_factory = UdpSocketWrapperFactoryMock::new()
+            .make_params(&make_params_arc)
+            .make_result(Ok(socket));
+        let nonce_factory =
+            MappingNonceFactoryMock::new().make_result([1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]);
+        let free_port_factory = FreePortFactoryMock::new().make_result(34567);
+        let (tx, rx) = unbounded();
+        let mut subject = PcpTransactor::default();
+        subject.housekeeper_commander_opt = Some(tx);
+        {
+            let factories = &mut subject.inner_arc.lock().unwrap().factories;
+            factories.socket_factory = Box::new(socket_factory);
+            factories.mapping_nonce_factory = Box::new(nonce_factory);
+            factories.free_port_factory = Box::new(free_port_factory);
+            // no results specified; the pinhole must not ask for the IPv4 local address
+            factories.local_ip_finder = Box::new(LocalIpFinderMock::new());
+        }
+
+        let result = subject.add_pinhole(router_ip, local_ip, 6666, 10000);
+
+        assert_eq!(result, Ok(4000));
+        let pinhole_config = match rx.try_recv().unwrap() {
+            HousekeepingThreadCommand::InitializePinholeConfig(pc) => pc,
+            x => panic!("Expecting InitializePinholeConfig, got {:?}", x),
+        };
+        assert_eq!(
+            pinhole_config,
+            PinholeConfig {
+                local_ip,
+                hole_port: 6666,
+                next_lifetime: Duration::from_secs(8000),
+                remap_interval: Duration::from_secs(4000),
+            }
+        );
+        let make_params = make_params_arc.lock().unwrap();
+        assert_eq!(
+            *make_params,
+            vec![SocketAddr::new(IpAddr::V6(local_ip), 34567)]
+        );
+        let send_to_params = send_to_params_arc.lock().unwrap();
+        assert_eq!(
+            *send_to_params,
+            vec![(
+                request[0..request_len].to_vec(),
+                SocketAddr::new(router_ip, ROUTER_PORT)
+            )]
+        );
+    }
+
+    #[test]
+    fn add_pinhole_sends_request_over_ipv6_even_if_router_address_is_ipv4() {
+        let make_params_arc = Arc::new(Mutex::new(vec![]));
+        let send_to_params_arc = Arc::new(Mutex::new(vec![]));
+        let router_ip = IpAddr::from_str("192.168.0.1").unwrap();
+        let local_ip = Ipv6Addr::from_str("2001:db8::1234").unwrap();
+        let mut response_packet = vanilla_response();
+        response_packet.opcode = Opcode::Map;
+        response_packet.lifetime = 8000;
+        response_packet.opcode_data = vanilla_map_response();
+        let mut response = [0u8; 1100];
+        let response_len = response_packet.marshal(&mut response).unwrap();
+        let socket = UdpSocketWrapperMock::new()
+            .set_read_timeout_result(Ok(()))
+            .send_to_params(&send_to_params_arc)
+            .send_to_result(Ok(1000))
+            .recv_from_result(
+                Ok((
+                    1000,
+                    SocketAddr::new(IpAddr::V6(PCP_IPV6_ANYCAST_ADDRESS), ROUTER_PORT),
+                )),
+                response[0..response_len].to_vec(),
+            );
+        let socket_factory = UdpSocketWrapperFactoryMock::new()
+            .make_params(&make_params_arc)
+            .make_result(Ok(socket));
+        let nonce_factory =
+            MappingNonceFactoryMock::new().make_result([1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]);
+        let free_port_factory = FreePortFactoryMock::new().make_result(34567);
+        let (tx, _rx) = unbounded();
+        let mut subject = PcpTransactor::default();
+        subject.housekeeper_commander_opt = Some(tx);
+        {
+            let factories = &mut subject.inner_arc.lock().unwrap().factories;
+            factories.socket_factory = Box::new(socket_factory);
+            factories.mapping_nonce_factory = Box::new(nonce_factory);
+            factories.free_port_factory = Box::new(free_port_factory);
+        }
+
+        let result = subject.add_pinhole(router_ip, local_ip, 6666, 10000);
+
+        assert_eq!(result, Ok(4000));
+        let make_params = make_params_arc.lock().unwrap();
+        assert_eq!(
+            *make_params,
+            vec![SocketAddr::new(IpAddr::V6(local_ip), 34567)]
+        );
+        let send_to_params = send_to_params_arc.lock().unwrap();
+        let (request, target) = &send_to_params[0];
+        assert_eq!(
+            *target,
+            SocketAddr::new(IpAddr::V6(PCP_IPV6_ANYCAST_ADDRESS), ROUTER_PORT)
+        );
+        let request_packet = PcpPacket::try_from(request.as_slice()).unwrap();
+        assert_eq!(request_packet.client_ip_opt, Some(IpAddr::V6(local_ip)));
+    }
+
+    #[test]
+    fn add_pinhole_handles_failure() {
+        let router_ip = IpAddr::from_str("2001:db8::1").unwrap();
+        let local_ip = Ipv6Addr::from_str("2001:db8::1234").unwrap();
+        let mut packet = vanilla_response();
+        packet.opcode = Opcode::Map;
+        packet.result_code_opt = Some(ResultCode::UnsuppProtocol);
+        packet.opcode_data = vanilla_map_response();
+        let mut response = [0u8; 1100];
+        let response_len = packet.marshal(&mut response).unwrap();
+        let socket = UdpSocketWrapperMock::new()
+            .set_read_timeout_result(Ok(()))
+            .send_to_result(Ok(1000))
+            .recv_from_result(
+                Ok((1000, SocketAddr::new(router_ip, ROUTER_PORT))),
+                response[0..response_len].to_vec(),
+            );
+        let socket_factory = UdpSocketWrapperFactoryMock::new().make_result(Ok(socket));
+        let nonce_factory =
+            MappingNonceFactoryMock::new().make_result([1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]);
+        let subject = PcpTransactor::default();
+        {
+            let factories = &mut subject.inner_arc.lock().unwrap().factories;
+            factories.socket_factory = Box::new(socket_factory);
+            factories.mapping_nonce_factory = Box::new(nonce_factory);
+        }
+
+        let result = subject.add_pinhole(router_ip, local_ip, 6666, 1234);
+
+        assert_eq!(
+            result,
+            Err(AutomapError::PermanentMappingError(
+                "UnsuppProtocol".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn delete_pinhole_works() {
+        let transact_pinhole_params_arc = Arc::new(Mutex::new(vec![]));
+        let router_ip = IpAddr::from_str("2001:db8::1").unwrap();
+        let local_ip = Ipv6Addr::from_str("2001:db8::1234").unwrap();
+        let mapping_transactor = MappingTransactorMock::new()
+            .transact_pinhole_params(&transact_pinhole_params_arc)
+            .transact_pinhole_result(Ok((0, MapOpcodeData::default())));
+        let subject = PcpTransactor::default();
+        subject.inner_arc.lock().unwrap().mapping_transactor = Box::new(mapping_transactor);
+
+        let result = subject.delete_pinhole(router_ip, local_ip, 6666);
+
+        assert_eq!(result, Ok(()));
+        let transact_pinhole_params = transact_pinhole_params_arc.lock().unwrap();
+        assert_eq!(
+            *transact_pinhole_params,
+            vec![(
+                SocketAddr::new(router_ip, ROUTER_PORT),
+                PinholeConfig {
+                    local_ip,
+                    hole_port: 6666,
+                    next_lifetime: Duration::from_secs(0),
+                    remap_interval: Duration::from_secs(0),
+                }
+            )]
+        );
+    }
+
     #[test]
     fn start_housekeeping_thread_doesnt_work_if_change_handler_stopper_is_populated() {
         let mut subject = PcpTransactor::default();
@@ -1529,6 +1965,161 @@ mod tests {
         TestLogHandler::new().exists_log_containing("INFO: timed_remap_test: Remapping port 6689");
     }
 
+    #[test]
+    fn thread_guts_renews_pinhole_when_interval_runs_out() {
+        init_test_logging();
+        let (tx, rx) = unbounded();
+        let transact_pinhole_params_arc = Arc::new(Mutex::new(vec![]));
+        let local_ip = Ipv6Addr::from_str("2001:db8::1234").unwrap();
+        let mapping_transactor = MappingTransactorMock::new()
+            .transact_pinhole_params(&transact_pinhole_params_arc)
+            .transact_pinhole_result(Ok((1000, MapOpcodeData::default())))
+            .transact_pinhole_result(Ok((1000, MapOpcodeData::default())));
+        let inner_arc = Arc::new(Mutex::new(PcpTransactorInner {
+            mapping_transactor: Box::new(mapping_transactor),
+            factories: Factories::default(),
+        }));
+        let router_addr = SocketAddr::from_str("[2001:db8::1]:5351").unwrap();
+        let pinhole_config = PinholeConfig {
+            local_ip,
+            hole_port: 6689,
+            next_lifetime: Duration::from_secs(1000),
+            remap_interval: Duration::from_millis(40),
+        };
+        tx.send(HousekeepingThreadCommand::InitializePinholeConfig(
+            pinhole_config,
+        ))
+        .unwrap();
+
+        let handle = thread::spawn(move || {
+            let _ = PcpTransactor::thread_guts(
+                &rx,
+                inner_arc,
+                router_addr,
+                Box::new(|_| ()),
+                10,
+                Logger::new("timed_pinhole_test"),
+            );
+        });
+
+        thread::sleep(Duration::from_millis(100));
+        tx.send(HousekeepingThreadCommand::Stop).unwrap();
+        handle.join().unwrap();
+        let transact_pinhole_params = transact_pinhole_params_arc.lock().unwrap();
+        assert_eq!(transact_pinhole_params[0], (router_addr, pinhole_config));
+        TestLogHandler::new().exists_log_containing(
+            "INFO: timed_pinhole_test: Renewing pinhole for port 6689 on 2001:db8::1234",
+        );
+    }
+
+    #[test]
+    fn thread_guts_renews_pinholes_for_more_than_one_port() {
+        let (tx, rx) = unbounded();
+        let transact_pinhole_params_arc = Arc::new(Mutex::new(vec![]));
+        let local_ip = Ipv6Addr::from_str("2001:db8::1234").unwrap();
+        let mapping_transactor = MappingTransactorMock::new()
+            .transact_pinhole_params(&transact_pinhole_params_arc)
+            .transact_pinhole_result(Ok((1000, MapOpcodeData::default())));
+        let inner_arc = Arc::new(Mutex::new(PcpTransactorInner {
+            mapping_transactor: Box::new(mapping_transactor),
+            factories: Factories::default(),
+        }));
+        let router_addr = SocketAddr::from_str("[2001:db8::1]:5351").unwrap();
+        let first_config = PinholeConfig {
+            local_ip,
+            hole_port: 6689,
+            next_lifetime: Duration::from_secs(1000),
+            remap_interval: Duration::from_millis(40),
+        };
+        let second_config = PinholeConfig {
+            hole_port: 7789,
+            ..first_config
+        };
+        tx.send(HousekeepingThreadCommand::InitializePinholeConfig(
+            first_config,
+        ))
+        .unwrap();
+        tx.send(HousekeepingThreadCommand::InitializePinholeConfig(
+            second_config,
+        ))
+        .unwrap();
+
+        let handle = thread::spawn(move || {
+            let _ = PcpTransactor::thread_guts(
+                &rx,
+                inner_arc,
+                router_addr,
+                Box::new(|_| ()),
+                10,
+                Logger::new("thread_guts_renews_pinholes_for_more_than_one_port"),
+            );
+        });
+
+        thread::sleep(Duration::from_millis(100));
+        tx.send(HousekeepingThreadCommand::Stop).unwrap();
+        handle.join().unwrap();
+        let renewed_ports = transact_pinhole_params_arc
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, pinhole_config)| pinhole_config.hole_port)
+            .collect::<HashSet<u16>>();
+        assert_eq!(
+            renewed_ports,
+            vec![6689, 7789].into_iter().collect::<HashSet<u16>>()
+        );
+    }
+
+    #[test]
+    fn thread_guts_reports_pinhole_renewal_failure() {
+        init_test_logging();
+        let (tx, rx) = unbounded();
+        let change_log_arc = Arc::new(Mutex::new(vec![]));
+        let inner_cla = change_log_arc.clone();
+        let change_handler: ChangeHandler = Box::new(move |change| {
+            inner_cla.lock().unwrap().push(change);
+        });
+        let mapping_transactor = MappingTransactorMock::new().transact_pinhole_result(Err(
+            AutomapError::TemporaryMappingError("NoResources".to_string()),
+        ));
+        let inner_arc = Arc::new(Mutex::new(PcpTransactorInner {
+            mapping_transactor: Box::new(mapping_transactor),
+            factories: Factories::default(),
+        }));
+        tx.send(HousekeepingThreadCommand::InitializePinholeConfig(
+            PinholeConfig {
+                local_ip: Ipv6Addr::from_str("2001:db8::1234").unwrap(),
+                hole_port: 6689,
+                next_lifetime: Duration::from_secs(1000),
+                remap_interval: Duration::from_millis(10),
+            },
+        ))
+        .unwrap();
+
+        let handle = thread::spawn(move || {
+            let _ = PcpTransactor::thread_guts(
+                &rx,
+                inner_arc,
+                SocketAddr::from_str("[2001:db8::1]:5351").unwrap(),
+                change_handler,
+                20,
+                Logger::new("thread_guts_reports_pinhole_renewal_failure"),
+            );
+        });
+
+        thread::sleep(Duration::from_millis(30));
+        tx.send(HousekeepingThreadCommand::Stop).unwrap();
+        handle.join().unwrap();
+        let change_log = change_log_arc.lock().unwrap();
+        assert_eq!(
+            change_log[0],
+            AutomapChange::Error(AutomapError::TemporaryMappingError(
+                "NoResources".to_string()
+            ))
+        );
+        TestLogHandler::new().exists_log_containing("ERROR: thread_guts_reports_pinhole_renewal_failure: Pinhole renewal failure: TemporaryMappingError(\"NoResources\")");
+    }
+
     #[test]
     fn thread_guts_logs_and_continues_if_remap_interval_is_set_before_mapping_config() {
         init_test_logging();
@@ -40,6 +40,22 @@ impl MappingConfig {
     }
 }
 
+// An IPv6 pinhole isn't a translation: the router's firewall just lets inbound traffic through
+// to the local address and port. So instead of a public IP, we have to remember whose pinhole it is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PinholeConfig {
+    pub local_ip: Ipv6Addr,
+    pub hole_port: u16,
+    pub next_lifetime: Duration,
+    pub remap_interval: Duration,
+}
+
+impl PinholeConfig {
+    pub fn next_lifetime_secs(&self) -> u32 {
+        self.next_lifetime.as_secs() as u32
+    }
+}
+
 pub trait UdpSocketWrapper: Send {
     fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)>;
     fn send_to(&self, buf: &[u8], addr: SocketAddr) -> io::Result<usize>;
@@ -202,6 +218,20 @@ pub mod tests {
         assert_eq!(result, 0);
     }
 
+    #[test]
+    fn pinhole_config_next_lifetime_secs_truncates_fractional_seconds() {
+        let subject = PinholeConfig {
+            local_ip: Ipv6Addr::LOCALHOST,
+            hole_port: 0,
+            next_lifetime: Duration::from_millis(2999),
+            remap_interval: Duration::from_millis(0),
+        };
+
+        let result = subject.next_lifetime_secs();
+
+        assert_eq!(result, 2);
+    }
+
     #[test]
     fn free_port_factory_works() {
         let subject = FreePortFactoryReal::new();
@@ -3,7 +3,7 @@
 use std::any::Any;
 use std::convert::TryFrom;
 use std::io::ErrorKind;
-use std::net::{IpAddr, SocketAddr};
+use std::net::{IpAddr, Ipv6Addr, SocketAddr};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::thread::JoinHandle;
@@ -153,6 +153,26 @@ impl Transactor for PmpTransactor {
         Ok(())
     }
 
+    fn add_pinhole(
+        &self,
+        _router_ip: IpAddr,
+        local_ip: Ipv6Addr,
+        _hole_port: u16,
+        _lifetime: u32,
+    ) -> Result<u32, AutomapError> {
+        warning!(self.logger, "PMP cannot open pinholes for IPv6 addresses");
+        Err(AutomapError::IPv6Unsupported(local_ip))
+    }
+
+    fn delete_pinhole(
+        &self,
+        _router_ip: IpAddr,
+        local_ip: Ipv6Addr,
+        _hole_port: u16,
+    ) -> Result<(), AutomapError> {
+        Err(AutomapError::IPv6Unsupported(local_ip))
+    }
+
     fn protocol(&self) -> AutomapProtocol {
         AutomapProtocol::Pmp
     }
@@ -370,6 +390,12 @@ impl ThreadGuts {
             Ok(HousekeepingThreadCommand::InitializeMappingConfig(mapping_config)) => {
                 mapping_config_opt.replace(mapping_config);
             }
+            Ok(HousekeepingThreadCommand::InitializePinholeConfig(pinhole_config)) => {
+                error!(
+                    self.logger,
+                    "PMP cannot maintain a pinhole for {}; ignoring", pinhole_config.local_ip
+                );
+            }
             Err(_) => (),
         };
         thread::sleep(Duration::from_millis(self.read_timeout_millis)); // used to be a read timeout
@@ -1197,6 +1223,23 @@ mod tests {
         let _ = subject.add_permanent_mapping(IpAddr::from_str("0.0.0.0").unwrap(), 0);
     }
 
+    #[test]
+    fn pinholes_are_not_supported() {
+        init_test_logging();
+        let subject = PmpTransactor::default();
+        let local_ip = Ipv6Addr::from_str("2001:db8::1234").unwrap();
+        let router_ip = IpAddr::from_str("1.2.3.4").unwrap();
+
+        let add_result = subject.add_pinhole(router_ip, local_ip, 1234, 600);
+        let delete_result = subject.delete_pinhole(router_ip, local_ip, 1234);
+
+        assert_eq!(add_result, Err(AutomapError::IPv6Unsupported(local_ip)));
+        assert_eq!(delete_result, Err(AutomapError::IPv6Unsupported(local_ip)));
+        TestLogHandler::new().exists_log_containing(
+            "WARN: PmpTransactor: PMP cannot open pinholes for IPv6 addresses",
+        );
+    }
+
     #[test]
     fn delete_mapping_works() {
         let router_ip = IpAddr::from_str("1.2.3.4").unwrap();
@@ -7,13 +7,15 @@ use crate::comm_layer::{
     AutomapError, HousekeepingThreadCommand, Transactor, DEFAULT_MAPPING_LIFETIME_SECONDS,
 };
 use crossbeam_channel::Sender;
-use masq_lib::debug;
 use masq_lib::logger::Logger;
 use masq_lib::utils::{plus, AutomapProtocol};
+use masq_lib::{debug, warning};
 use std::cell::{RefCell, RefMut};
 use std::collections::HashSet;
 use std::fmt::Debug;
-use std::net::IpAddr;
+use std::net::{IpAddr, Ipv6Addr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
 
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub enum AutomapChange {
@@ -23,11 +25,32 @@ pub enum AutomapChange {
 
 pub type ChangeHandler = Box<dyn Fn(AutomapChange) + Send>;
 
-pub trait AutomapControl {
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct AutomapStatus {
+    pub protocol_opt: Option<AutomapProtocol>,
+    pub public_ip_opt: Option<IpAddr>,
+    pub hole_ports: Vec<u16>,
+    pub lease_opt: Option<LeaseStatus>,
+    pub last_error_opt: Option<AutomapError>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LeaseStatus {
+    // None if the router would only grant a permanent mapping
+    pub lifetime_opt: Option<Duration>,
+    pub next_remap: SystemTime,
+}
+
+pub trait AutomapControl: Send {
     fn get_public_ip(&mut self) -> Result<IpAddr, AutomapError>;
     fn add_mapping(&mut self, hole_port: u16) -> Result<(), AutomapError>;
+    fn add_pinhole(&mut self, local_ip: Ipv6Addr, hole_port: u16) -> Result<(), AutomapError>;
     fn delete_mappings(&mut self) -> Result<(), AutomapError>;
+    fn remap(&mut self) -> Result<(), AutomapError>;
+    // Falls back to the other protocols, just as at startup, if the requested one doesn't work
+    fn switch_protocol(&mut self, protocol: AutomapProtocol) -> Result<(), AutomapError>;
     fn get_mapping_protocol(&self) -> Option<AutomapProtocol>;
+    fn get_status(&self) -> AutomapStatus;
 }
 
 #[derive(PartialEq, Eq, Debug)]
@@ -44,6 +67,37 @@ struct ProtocolInfo<T: PartialEq + Debug> {
     router_ip: IpAddr,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Lease {
+    lifetime_opt: Option<Duration>,
+    remap_interval: Duration,
+    granted_at: SystemTime,
+}
+
+impl Lease {
+    fn new(remap_after_sec: u32, lifetime_secs_opt: Option<u32>) -> Self {
+        Self {
+            lifetime_opt: lifetime_secs_opt.map(|secs| Duration::from_secs(secs as u64)),
+            remap_interval: Duration::from_secs(remap_after_sec as u64),
+            granted_at: SystemTime::now(),
+        }
+    }
+
+    // The housekeeping thread remaps on its own without reporting back, so we extrapolate.
+    fn next_remap(&self, now: SystemTime) -> SystemTime {
+        let interval_ms = self.remap_interval.as_millis();
+        if interval_ms == 0 {
+            return self.granted_at;
+        }
+        let elapsed_ms = now
+            .duration_since(self.granted_at)
+            .unwrap_or_default()
+            .as_millis();
+        let remaps = elapsed_ms / interval_ms + 1;
+        self.granted_at + Duration::from_millis((remaps * interval_ms) as u64)
+    }
+}
+
 struct HousekeepingTools {
     change_handler_opt: Option<ChangeHandler>,
     housekeeping_thread_commander_opt: Option<Sender<HousekeepingThreadCommand>>,
@@ -54,7 +108,11 @@ pub struct AutomapControlReal {
     housekeeping_tools: RefCell<HousekeepingTools>,
     usual_protocol_opt: Option<AutomapProtocol>,
     hole_ports: HashSet<u16>,
+    pinholes: HashSet<(Ipv6Addr, u16)>,
     inner_opt: Option<AutomapControlRealInner>,
+    public_ip_opt: Option<IpAddr>,
+    lease_opt: Option<Lease>,
+    last_error_arc: Arc<Mutex<Option<AutomapError>>>,
     logger: Logger,
 }
 
@@ -64,8 +122,10 @@ impl AutomapControl for AutomapControlReal {
         let experiment = Box::new(move |transactor: &dyn Transactor, router_ip: IpAddr| {
             transactor.get_public_ip(router_ip)
         });
-        let protocol_info = self.calculate_protocol_info(experiment)?;
+        let result = self.calculate_protocol_info(experiment);
+        let protocol_info = self.record_error(result)?;
         debug!(self.logger, "Public IP {:?}", protocol_info.payload);
+        self.public_ip_opt = Some(protocol_info.payload);
         Ok(protocol_info.payload)
     }
 
@@ -73,14 +133,17 @@ impl AutomapControl for AutomapControlReal {
         debug!(self.logger, "Adding mapping for port {}", hole_port);
         let experiment = Box::new(move |transactor: &dyn Transactor, router_ip: IpAddr| {
             match transactor.add_mapping(router_ip, hole_port, DEFAULT_MAPPING_LIFETIME_SECONDS) {
-                Ok(remap_after_sec) => Ok(remap_after_sec),
-                Err(AutomapError::PermanentLeasesOnly) => {
-                    transactor.add_permanent_mapping(router_ip, hole_port)
+                Ok(remap_after_sec) => {
+                    Ok((remap_after_sec, Some(DEFAULT_MAPPING_LIFETIME_SECONDS)))
                 }
+                Err(AutomapError::PermanentLeasesOnly) => transactor
+                    .add_permanent_mapping(router_ip, hole_port)
+                    .map(|remap_after_sec| (remap_after_sec, None)),
                 Err(e) => Err(e),
             }
         });
-        let protocol_info = self.calculate_protocol_info(experiment)?;
+        let result = self.calculate_protocol_info(experiment);
+        let protocol_info = self.record_error(result)?;
         let transactor_idx = self
             .inner_opt
             .as_ref()
@@ -88,7 +151,8 @@ impl AutomapControl for AutomapControlReal {
             .transactor_idx;
         self.usual_protocol_opt = Some(self.transactors.borrow()[transactor_idx].protocol());
         self.hole_ports.insert(hole_port);
-        let remap_after_sec: u32 = protocol_info.payload;
+        let (remap_after_sec, lifetime_secs_opt) = protocol_info.payload;
+        self.lease_opt = Some(Lease::new(remap_after_sec, lifetime_secs_opt));
         self.housekeeping_tools
             .borrow()
             .housekeeping_thread_commander_opt
@@ -101,39 +165,165 @@ impl AutomapControl for AutomapControlReal {
         Ok(())
     }
 
+    fn add_pinhole(&mut self, local_ip: Ipv6Addr, hole_port: u16) -> Result<(), AutomapError> {
+        debug!(
+            self.logger,
+            "Adding pinhole for port {} on {}", hole_port, local_ip
+        );
+        let experiment = Box::new(move |transactor: &dyn Transactor, router_ip: IpAddr| {
+            transactor.add_pinhole(
+                router_ip,
+                local_ip,
+                hole_port,
+                DEFAULT_MAPPING_LIFETIME_SECONDS,
+            )
+        });
+        // The Transactor hands its housekeeping thread the renewal interval itself; pinholes
+        // don't go through SetRemapIntervalMs, which belongs to the IPv4 mapping.
+        let result = self.calculate_protocol_info(experiment);
+        let protocol_info = self.record_error(result)?;
+        let transactor_idx = self
+            .inner_opt
+            .as_ref()
+            .expect("inner disappeared")
+            .transactor_idx;
+        self.usual_protocol_opt = Some(self.transactors.borrow()[transactor_idx].protocol());
+        self.pinholes.insert((local_ip, hole_port));
+        self.lease_opt = Some(Lease::new(
+            protocol_info.payload,
+            Some(DEFAULT_MAPPING_LIFETIME_SECONDS),
+        ));
+        Ok(())
+    }
+
     fn delete_mappings(&mut self) -> Result<(), AutomapError> {
-        match &self.inner_opt {
-            None => Err(AutomapError::DeleteMappingError(
-                "No port mapping to remove".to_string(),
-            )),
-            Some(inner) => {
-                debug!(self.logger, "Deleting public mappings");
-                let transactor = &mut self.transactors.borrow_mut()[inner.transactor_idx];
-                let init: Vec<AutomapError> = vec![];
-                let errors =
-                    self.hole_ports.iter().fold(init, |so_far, hole_port| {
-                        match transactor.delete_mapping(inner.router_ip, *hole_port) {
-                            Ok(_) => so_far,
-                            Err(e) => plus(so_far, e),
-                        }
-                    });
-                let _ = transactor.stop_housekeeping_thread();
-                if errors.is_empty() {
-                    Ok(())
-                } else {
-                    Err(errors[0].clone())
+        let inner = match self.inner_opt.take() {
+            None => {
+                return self.record_error(Err(AutomapError::DeleteMappingError(
+                    "No port mapping to remove".to_string(),
+                )))
+            }
+            Some(inner) => inner,
+        };
+        debug!(self.logger, "Deleting public mappings");
+        let errors = {
+            let transactor = &mut self.transactors.borrow_mut()[inner.transactor_idx];
+            let init: Vec<AutomapError> = vec![];
+            let errors = self.hole_ports.iter().fold(init, |so_far, hole_port| {
+                match transactor.delete_mapping(inner.router_ip, *hole_port) {
+                    Ok(_) => so_far,
+                    Err(e) => plus(so_far, e),
                 }
+            });
+            let errors = self
+                .pinholes
+                .iter()
+                .fold(errors, |so_far, (local_ip, hole_port)| {
+                    match transactor.delete_pinhole(inner.router_ip, *local_ip, *hole_port) {
+                        Ok(_) => so_far,
+                        Err(e) => plus(so_far, e),
+                    }
+                });
+            self.stop_housekeeper(transactor.as_mut());
+            errors
+        };
+        self.hole_ports.clear();
+        self.pinholes.clear();
+        self.lease_opt = None;
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            self.record_error(Err(errors[0].clone()))
+        }
+    }
+
+    fn remap(&mut self) -> Result<(), AutomapError> {
+        if self.hole_ports.is_empty() && self.pinholes.is_empty() {
+            return self.record_error(Err(AutomapError::TemporaryMappingError(
+                "No port mapping to renew".to_string(),
+            )));
+        }
+        debug!(self.logger, "Remapping on demand");
+        let hole_ports = self.hole_ports.iter().copied().collect::<Vec<u16>>();
+        let pinholes = self
+            .pinholes
+            .iter()
+            .copied()
+            .collect::<Vec<(Ipv6Addr, u16)>>();
+        hole_ports
+            .into_iter()
+            .try_for_each(|hole_port| self.add_mapping(hole_port))?;
+        pinholes
+            .into_iter()
+            .try_for_each(|(local_ip, hole_port)| self.add_pinhole(local_ip, hole_port))
+    }
+
+    fn switch_protocol(&mut self, protocol: AutomapProtocol) -> Result<(), AutomapError> {
+        debug!(self.logger, "Switching to {} on demand", protocol);
+        let hole_ports = self.hole_ports.iter().copied().collect::<Vec<u16>>();
+        let pinholes = self
+            .pinholes
+            .iter()
+            .copied()
+            .collect::<Vec<(Ipv6Addr, u16)>>();
+        if self.inner_opt.is_some() {
+            if let Err(e) = self.delete_mappings() {
+                warning!(self.logger, "Couldn't remove old mappings: {:?}", e);
             }
         }
+        self.usual_protocol_opt = Some(protocol);
+        self.get_public_ip()?;
+        hole_ports
+            .into_iter()
+            .try_for_each(|hole_port| self.add_mapping(hole_port))?;
+        pinholes
+            .into_iter()
+            .try_for_each(|(local_ip, hole_port)| self.add_pinhole(local_ip, hole_port))
     }
 
     fn get_mapping_protocol(&self) -> Option<AutomapProtocol> {
         self.usual_protocol_opt
     }
+
+    fn get_status(&self) -> AutomapStatus {
+        let mut hole_ports = self
+            .hole_ports
+            .iter()
+            .chain(self.pinholes.iter().map(|(_, hole_port)| hole_port))
+            .copied()
+            .collect::<Vec<u16>>();
+        hole_ports.sort_unstable();
+        hole_ports.dedup();
+        AutomapStatus {
+            protocol_opt: self.usual_protocol_opt,
+            public_ip_opt: self.public_ip_opt,
+            hole_ports,
+            lease_opt: self.lease_opt.map(|lease| LeaseStatus {
+                lifetime_opt: lease.lifetime_opt,
+                next_remap: lease.next_remap(SystemTime::now()),
+            }),
+            last_error_opt: self
+                .last_error_arc
+                .lock()
+                .expect("Automap error record is poisoned")
+                .clone(),
+        }
+    }
 }
 
 impl AutomapControlReal {
     pub fn new(usual_protocol_opt: Option<AutomapProtocol>, change_handler: ChangeHandler) -> Self {
+        let last_error_arc = Arc::new(Mutex::new(None));
+        let last_error_arc_inner = last_error_arc.clone();
+        let change_handler: ChangeHandler = Box::new(move |change: AutomapChange| {
+            if let AutomapChange::Error(e) = &change {
+                last_error_arc_inner
+                    .lock()
+                    .expect("Automap error record is poisoned")
+                    .replace(e.clone());
+            }
+            change_handler(change)
+        });
         Self {
             transactors: RefCell::new(vec![
                 Box::new(PcpTransactor::default()),
@@ -146,11 +336,38 @@ impl AutomapControlReal {
             }),
             usual_protocol_opt,
             hole_ports: HashSet::new(),
+            pinholes: HashSet::new(),
             inner_opt: None,
+            public_ip_opt: None,
+            lease_opt: None,
+            last_error_arc,
             logger: Logger::new("AutomapControl"),
         }
     }
 
+    fn record_error<T>(&self, result: Result<T, AutomapError>) -> Result<T, AutomapError> {
+        if let Err(e) = &result {
+            self.last_error_arc
+                .lock()
+                .expect("Automap error record is poisoned")
+                .replace(e.clone());
+        }
+        result
+    }
+
+    fn stop_housekeeper(&self, transactor: &mut dyn Transactor) {
+        let mut housekeeping_tools = self.housekeeping_tools.borrow_mut();
+        housekeeping_tools.housekeeping_thread_commander_opt = None;
+        match transactor.stop_housekeeping_thread() {
+            Ok(change_handler) => {
+                housekeeping_tools
+                    .change_handler_opt
+                    .replace(change_handler);
+            }
+            Err(e) => debug!(self.logger, "Housekeeping thread didn't stop: {:?}", e),
+        }
+    }
+
     fn maybe_start_housekeeper(
         &self,
         transactor: &mut dyn Transactor,
@@ -961,6 +1178,108 @@ mod tests {
         assert_eq!(result, Err(AutomapError::NoLocalIpAddress));
     }
 
+    #[test]
+    fn add_pinhole_chooses_protocol_and_delegates_to_transactor() {
+        let add_pinhole_params_arc = Arc::new(Mutex::new(vec![]));
+        let local_ip = Ipv6Addr::from_str("2001:db8::1234").unwrap();
+        let (tx, rx) = unbounded();
+        let subject = make_general_failure_subject();
+        let transactor = TransactorMock::new(AutomapProtocol::Pcp)
+            .find_routers_result(Ok(vec![*ROUTER_IP]))
+            .start_housekeeping_thread_result(Ok(tx))
+            .add_pinhole_params(&add_pinhole_params_arc)
+            .add_pinhole_result(Ok(300));
+        let mut subject = replace_transactor(subject, Box::new(transactor));
+        subject.housekeeping_tools.borrow_mut().change_handler_opt = Some(Box::new(|_| ()));
+
+        let result = subject.add_pinhole(local_ip, 4567);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(subject.usual_protocol_opt, Some(AutomapProtocol::Pcp));
+        assert_eq!(
+            subject.pinholes.iter().collect::<Vec<&(Ipv6Addr, u16)>>(),
+            vec![&(local_ip, 4567)]
+        );
+        assert!(subject.hole_ports.is_empty());
+        let add_pinhole_params = add_pinhole_params_arc.lock().unwrap();
+        assert_eq!(*add_pinhole_params, vec![(*ROUTER_IP, local_ip, 4567, 600)]);
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    #[test]
+    fn add_pinhole_reports_unsupported_protocol() {
+        let local_ip = Ipv6Addr::from_str("2001:db8::1234").unwrap();
+        let subject = make_null_subject();
+        let mut transactor = TransactorMock::new(AutomapProtocol::Pmp)
+            .add_pinhole_result(Err(AutomapError::IPv6Unsupported(local_ip)));
+        transactor.housekeeping_thread_started = true;
+        let mut subject = replace_transactor(subject, Box::new(transactor));
+        subject.housekeeping_tools.borrow_mut().change_handler_opt = None;
+        subject.inner_opt = Some(AutomapControlRealInner {
+            router_ip: *ROUTER_IP,
+            transactor_idx: 1,
+        });
+
+        let result = subject.add_pinhole(local_ip, 4567);
+
+        assert_eq!(result, Err(AutomapError::IPv6Unsupported(local_ip)));
+        assert!(subject.pinholes.is_empty());
+    }
+
+    #[test]
+    fn delete_mappings_deletes_pinholes_too() {
+        let delete_mapping_params_arc = Arc::new(Mutex::new(vec![]));
+        let delete_pinhole_params_arc = Arc::new(Mutex::new(vec![]));
+        let local_ip = Ipv6Addr::from_str("2001:db8::1234").unwrap();
+        let mut subject = make_active_two_port_subject(0);
+        subject.pinholes = vec![(local_ip, 4567)].into_iter().collect();
+        let transactor = TransactorMock::new(AutomapProtocol::Pcp)
+            .delete_mapping_params(&delete_mapping_params_arc)
+            .delete_mapping_result(Ok(()))
+            .delete_mapping_result(Ok(()))
+            .delete_pinhole_params(&delete_pinhole_params_arc)
+            .delete_pinhole_result(Err(AutomapError::TemporaryMappingError(
+                "NoResources".to_string(),
+            )))
+            .stop_housekeeping_thread_result(Ok(Box::new(|_| ())));
+        let mut subject = replace_transactor(subject, Box::new(transactor));
+
+        let result = subject.delete_mappings();
+
+        assert_eq!(
+            result,
+            Err(AutomapError::TemporaryMappingError(
+                "NoResources".to_string()
+            ))
+        );
+        assert_eq!(delete_mapping_params_arc.lock().unwrap().len(), 2);
+        let delete_pinhole_params = delete_pinhole_params_arc.lock().unwrap();
+        assert_eq!(*delete_pinhole_params, vec![(*ROUTER_IP, local_ip, 4567)]);
+        assert!(subject.hole_ports.is_empty());
+        assert!(subject.pinholes.is_empty());
+    }
+
+    #[test]
+    fn remap_after_delete_mappings_has_nothing_to_renew() {
+        let subject = make_active_two_port_subject(0);
+        let transactor = TransactorMock::new(AutomapProtocol::Pcp)
+            .delete_mapping_result(Ok(()))
+            .delete_mapping_result(Ok(()))
+            .stop_housekeeping_thread_result(Ok(Box::new(|_| ())));
+        let mut subject = replace_transactor(subject, Box::new(transactor));
+        subject.delete_mappings().unwrap();
+
+        let result = subject.remap();
+
+        assert_eq!(
+            result,
+            Err(AutomapError::TemporaryMappingError(
+                "No port mapping to renew".to_string()
+            ))
+        );
+        assert_eq!(subject.get_status().hole_ports, Vec::<u16>::new());
+    }
+
     #[test]
     fn delete_mappings_complains_if_no_active_protocol() {
         let mut subject = make_null_subject();
@@ -1030,6 +1349,186 @@ mod tests {
         assert_eq!(*stop_change_handler_params, vec![()]);
     }
 
+    #[test]
+    fn delete_mappings_reclaims_change_handler_and_forgets_lease() {
+        let (tx, _rx) = unbounded();
+        let mut subject = make_active_two_port_subject(1);
+        subject.lease_opt = Some(Lease::new(300, Some(600)));
+        {
+            let mut housekeeping_tools = subject.housekeeping_tools.borrow_mut();
+            housekeeping_tools.change_handler_opt = None;
+            housekeeping_tools.housekeeping_thread_commander_opt = Some(tx);
+        }
+        let transactor = TransactorMock::new(AutomapProtocol::Pmp)
+            .delete_mapping_result(Ok(()))
+            .delete_mapping_result(Ok(()))
+            .stop_housekeeping_thread_result(Ok(Box::new(|_| ())));
+        let mut subject = replace_transactor(subject, Box::new(transactor));
+
+        let result = subject.delete_mappings();
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(subject.inner_opt, None);
+        assert_eq!(subject.lease_opt, None);
+        assert!(subject.hole_ports.is_empty());
+        assert!(subject.pinholes.is_empty());
+        let housekeeping_tools = subject.housekeeping_tools.borrow();
+        assert!(housekeeping_tools.change_handler_opt.is_some());
+        assert!(housekeeping_tools
+            .housekeeping_thread_commander_opt
+            .is_none());
+    }
+
+    #[test]
+    fn remap_adds_all_mappings_again() {
+        let add_mapping_params_arc = Arc::new(Mutex::new(vec![]));
+        let (tx, rx) = unbounded();
+        let mut subject = make_active_two_port_subject(0);
+        {
+            let mut housekeeping_tools = subject.housekeeping_tools.borrow_mut();
+            housekeeping_tools.change_handler_opt = None;
+            housekeeping_tools.housekeeping_thread_commander_opt = Some(tx);
+        }
+        let mut transactor = TransactorMock::new(AutomapProtocol::Pcp)
+            .add_mapping_params(&add_mapping_params_arc)
+            .add_mapping_result(Ok(300))
+            .add_mapping_result(Ok(300));
+        transactor.housekeeping_thread_started = true;
+        let mut subject = replace_transactor(subject, Box::new(transactor));
+
+        let result = subject.remap();
+
+        assert_eq!(result, Ok(()));
+        let mut add_mapping_params = add_mapping_params_arc.lock().unwrap().clone();
+        add_mapping_params.sort();
+        assert_eq!(
+            add_mapping_params,
+            vec![(*ROUTER_IP, 4567, 600), (*ROUTER_IP, 5678, 600)]
+        );
+        assert_eq!(
+            rx.try_recv(),
+            Ok(HousekeepingThreadCommand::SetRemapIntervalMs(300000))
+        );
+        assert_eq!(
+            rx.try_recv(),
+            Ok(HousekeepingThreadCommand::SetRemapIntervalMs(300000))
+        );
+        assert_eq!(subject.usual_protocol_opt, Some(AutomapProtocol::Pcp));
+    }
+
+    #[test]
+    fn remap_complains_if_nothing_is_mapped() {
+        let mut subject = make_null_subject();
+
+        let result = subject.remap();
+
+        let expected_error =
+            AutomapError::TemporaryMappingError("No port mapping to renew".to_string());
+        assert_eq!(result, Err(expected_error.clone()));
+        assert_eq!(subject.get_status().last_error_opt, Some(expected_error));
+    }
+
+    #[test]
+    fn switch_protocol_deletes_old_mappings_and_maps_through_the_new_protocol() {
+        let delete_mapping_params_arc = Arc::new(Mutex::new(vec![]));
+        let add_mapping_params_arc = Arc::new(Mutex::new(vec![]));
+        let (tx, _rx) = unbounded();
+        let mut subject = make_active_two_port_subject(0);
+        subject.usual_protocol_opt = Some(AutomapProtocol::Pcp);
+        let old_transactor = TransactorMock::new(AutomapProtocol::Pcp)
+            .delete_mapping_params(&delete_mapping_params_arc)
+            .delete_mapping_result(Ok(()))
+            .delete_mapping_result(Ok(()))
+            .stop_housekeeping_thread_result(Ok(Box::new(|_| ())));
+        let new_transactor = TransactorMock::new(AutomapProtocol::Pmp)
+            .find_routers_result(Ok(vec![*ROUTER_IP]))
+            .start_housekeeping_thread_result(Ok(tx))
+            .get_public_ip_result(Ok(*PUBLIC_IP))
+            .add_mapping_params(&add_mapping_params_arc)
+            .add_mapping_result(Ok(300))
+            .add_mapping_result(Ok(300));
+        let subject = replace_transactor(subject, Box::new(old_transactor));
+        let mut subject = replace_transactor(subject, Box::new(new_transactor));
+
+        let result = subject.switch_protocol(AutomapProtocol::Pmp);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(delete_mapping_params_arc.lock().unwrap().len(), 2);
+        assert_eq!(add_mapping_params_arc.lock().unwrap().len(), 2);
+        let status = subject.get_status();
+        assert_eq!(status.protocol_opt, Some(AutomapProtocol::Pmp));
+        assert_eq!(status.public_ip_opt, Some(*PUBLIC_IP));
+        assert_eq!(status.hole_ports, vec![4567, 5678]);
+        assert_eq!(
+            status.lease_opt.unwrap().lifetime_opt,
+            Some(Duration::from_secs(600))
+        );
+    }
+
+    #[test]
+    fn get_status_reports_freshly_made_mapping() {
+        let (tx, _rx) = unbounded();
+        let mut subject = make_general_success_subject(
+            AutomapProtocol::Pcp,
+            &Arc::new(Mutex::new(vec![])),
+            &Arc::new(Mutex::new(vec![])),
+            &Arc::new(Mutex::new(vec![])),
+            tx,
+        );
+        subject.get_public_ip().unwrap();
+        subject.add_mapping(4567).unwrap();
+        let before = SystemTime::now();
+
+        let result = subject.get_status();
+
+        let after = SystemTime::now();
+        assert_eq!(result.protocol_opt, Some(AutomapProtocol::Pcp));
+        assert_eq!(result.public_ip_opt, Some(*PUBLIC_IP));
+        assert_eq!(result.hole_ports, vec![4567]);
+        assert_eq!(result.last_error_opt, None);
+        let lease = result.lease_opt.unwrap();
+        assert_eq!(lease.lifetime_opt, Some(Duration::from_secs(600)));
+        assert!(lease.next_remap > before);
+        assert!(lease.next_remap <= after + Duration::from_secs(1000));
+    }
+
+    #[test]
+    fn get_status_remembers_errors_from_the_housekeeping_thread() {
+        let subject = AutomapControlReal::new(None, Box::new(|_| ()));
+        let change_handler = subject
+            .housekeeping_tools
+            .borrow_mut()
+            .change_handler_opt
+            .take()
+            .unwrap();
+
+        change_handler(AutomapChange::Error(AutomapError::HousekeeperCrashed));
+
+        assert_eq!(
+            subject.get_status().last_error_opt,
+            Some(AutomapError::HousekeeperCrashed)
+        );
+    }
+
+    #[test]
+    fn lease_extrapolates_next_remap_from_the_grant() {
+        let granted_at = SystemTime::now();
+        let subject = Lease {
+            lifetime_opt: Some(Duration::from_secs(600)),
+            remap_interval: Duration::from_secs(300),
+            granted_at,
+        };
+
+        assert_eq!(
+            subject.next_remap(granted_at),
+            granted_at + Duration::from_secs(300)
+        );
+        assert_eq!(
+            subject.next_remap(granted_at + Duration::from_secs(750)),
+            granted_at + Duration::from_secs(900)
+        );
+    }
+
     #[test]
     fn get_mapping_protocol_returns_usual_mapping_protocol_opt() {
         let mut subject = make_null_subject();
@@ -15,7 +15,7 @@ use masq_lib::utils::AutomapProtocol;
 use std::any::Any;
 use std::cell::RefCell;
 use std::io::ErrorKind;
-use std::net::{IpAddr, SocketAddr};
+use std::net::{IpAddr, Ipv6Addr, SocketAddr};
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
@@ -232,6 +232,10 @@ pub struct TransactorMock {
     add_permanent_mapping_results: RefCell<Vec<Result<u32, AutomapError>>>,
     delete_mapping_params: Arc<Mutex<Vec<(IpAddr, u16)>>>,
     delete_mapping_results: RefCell<Vec<Result<(), AutomapError>>>,
+    add_pinhole_params: Arc<Mutex<Vec<(IpAddr, Ipv6Addr, u16, u32)>>>,
+    add_pinhole_results: RefCell<Vec<Result<u32, AutomapError>>>,
+    delete_pinhole_params: Arc<Mutex<Vec<(IpAddr, Ipv6Addr, u16)>>>,
+    delete_pinhole_results: RefCell<Vec<Result<(), AutomapError>>>,
     start_housekeeping_thread_params: Arc<Mutex<Vec<(ChangeHandler, IpAddr)>>>,
     start_housekeeping_thread_results:
         RefCell<Vec<Result<Sender<HousekeepingThreadCommand>, AutomapError>>>,
@@ -291,6 +295,36 @@ impl Transactor for TransactorMock {
         self.delete_mapping_results.borrow_mut().remove(0)
     }
 
+    fn add_pinhole(
+        &self,
+        router_ip: IpAddr,
+        local_ip: Ipv6Addr,
+        hole_port: u16,
+        lifetime: u32,
+    ) -> Result<u32, AutomapError> {
+        if !self.housekeeping_thread_started {
+            panic!("Housekeeping thread must be started before add_pinhole()")
+        }
+        self.add_pinhole_params
+            .lock()
+            .unwrap()
+            .push((router_ip, local_ip, hole_port, lifetime));
+        self.add_pinhole_results.borrow_mut().remove(0)
+    }
+
+    fn delete_pinhole(
+        &self,
+        router_ip: IpAddr,
+        local_ip: Ipv6Addr,
+        hole_port: u16,
+    ) -> Result<(), AutomapError> {
+        self.delete_pinhole_params
+            .lock()
+            .unwrap()
+            .push((router_ip, local_ip, hole_port));
+        self.delete_pinhole_results.borrow_mut().remove(0)
+    }
+
     fn protocol(&self) -> AutomapProtocol {
         self.protocol
     }
@@ -341,6 +375,10 @@ impl TransactorMock {
             add_permanent_mapping_results: RefCell::new(vec![]),
             delete_mapping_params: Arc::new(Mutex::new(vec![])),
             delete_mapping_results: RefCell::new(vec![]),
+            add_pinhole_params: Arc::new(Mutex::new(vec![])),
+            add_pinhole_results: RefCell::new(vec![]),
+            delete_pinhole_params: Arc::new(Mutex::new(vec![])),
+            delete_pinhole_results: RefCell::new(vec![]),
             start_housekeeping_thread_params: Arc::new(Mutex::new(vec![])),
             start_housekeeping_thread_results: RefCell::new(vec![]),
             stop_housekeeping_thread_params: Arc::new(Mutex::new(vec![])),
@@ -394,6 +432,34 @@ impl TransactorMock {
         self
     }
 
+    #[allow(clippy::type_complexity)]
+    pub fn add_pinhole_params(
+        mut self,
+        params: &Arc<Mutex<Vec<(IpAddr, Ipv6Addr, u16, u32)>>>,
+    ) -> Self {
+        self.add_pinhole_params = params.clone();
+        self
+    }
+
+    pub fn add_pinhole_result(self, result: Result<u32, AutomapError>) -> Self {
+        self.add_pinhole_results.borrow_mut().push(result);
+        self
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub fn delete_pinhole_params(
+        mut self,
+        params: &Arc<Mutex<Vec<(IpAddr, Ipv6Addr, u16)>>>,
+    ) -> Self {
+        self.delete_pinhole_params = params.clone();
+        self
+    }
+
+    pub fn delete_pinhole_result(self, result: Result<(), AutomapError>) -> Self {
+        self.delete_pinhole_results.borrow_mut().push(result);
+        self
+    }
+
     pub fn start_housekeeping_thread_result(
         self,
         result: Result<Sender<HousekeepingThreadCommand>, AutomapError>,
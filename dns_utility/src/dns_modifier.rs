@@ -1,10 +1,17 @@
 // Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 
+use masq_lib::short_writeln;
 use std::io;
+use std::io::Write;
 
 pub trait DnsModifier {
     fn type_name(&self) -> &'static str;
     fn subvert(&self) -> Result<(), String>;
     fn revert(&self) -> Result<(), String>;
     fn inspect(&self, stdout: &mut (dyn io::Write + Send)) -> Result<(), String>;
+    // Describes the revert journal, if any, and whether the live settings have drifted from it
+    fn verbose_status(&self, stdout: &mut (dyn io::Write + Send)) -> Result<(), String> {
+        short_writeln!(stdout, "Revert journal: not kept by {}", self.type_name());
+        Ok(())
+    }
 }
@@ -5,8 +5,12 @@ use std::fs::File;
 #[cfg(target_os = "linux")]
 use std::path::Path;
 
+#[cfg(target_os = "linux")]
+use crate::network_manager_dns_modifier::NetworkManagerDnsModifier;
 #[cfg(target_os = "linux")]
 use crate::resolv_conf_dns_modifier::ResolvConfDnsModifier;
+#[cfg(target_os = "linux")]
+use crate::systemd_resolved_dns_modifier::SystemdResolvedDnsModifier;
 
 #[cfg(target_os = "macos")]
 use crate::dynamic_store_dns_modifier::DynamicStoreDnsModifier;
@@ -35,9 +39,12 @@ impl DnsModifierFactoryReal {
     }
 }
 
-const QUALIFIER_FACTORIES: [&dyn QualifierFactory; 3] = [
+// On Linux, the daemons that own /etc/resolv.conf must be asked before the file itself
+const QUALIFIER_FACTORIES: [&dyn QualifierFactory; 5] = [
     &DynamicStoreQualifierFactory {},
     &WinQualifierFactory {},
+    &SystemdResolvedQualifierFactory {},
+    &NetworkManagerQualifierFactory {},
     &ResolvConfQualifierFactory {},
 ];
 
@@ -46,6 +53,62 @@ trait QualifierFactory {
     fn make(&self) -> Box<dyn DnsModifier>;
 }
 
+struct SystemdResolvedQualifierFactory;
+#[cfg(target_os = "linux")]
+impl QualifierFactory for SystemdResolvedQualifierFactory {
+    fn system_qualifies(&self) -> bool {
+        Self::qualifies_under(Path::new("/"))
+    }
+    fn make(&self) -> Box<dyn DnsModifier> {
+        Box::new(SystemdResolvedDnsModifier::new())
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl SystemdResolvedQualifierFactory {
+    fn qualifies_under(root: &Path) -> bool {
+        SystemdResolvedDnsModifier::manages_dns(root)
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+impl QualifierFactory for SystemdResolvedQualifierFactory {
+    fn system_qualifies(&self) -> bool {
+        false
+    }
+    fn make(&self) -> Box<dyn DnsModifier> {
+        panic!("Should never be called")
+    }
+}
+
+struct NetworkManagerQualifierFactory;
+#[cfg(target_os = "linux")]
+impl QualifierFactory for NetworkManagerQualifierFactory {
+    fn system_qualifies(&self) -> bool {
+        Self::qualifies_under(Path::new("/"))
+    }
+    fn make(&self) -> Box<dyn DnsModifier> {
+        Box::new(NetworkManagerDnsModifier::new())
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl NetworkManagerQualifierFactory {
+    fn qualifies_under(root: &Path) -> bool {
+        NetworkManagerDnsModifier::manages_dns(root)
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+impl QualifierFactory for NetworkManagerQualifierFactory {
+    fn system_qualifies(&self) -> bool {
+        false
+    }
+    fn make(&self) -> Box<dyn DnsModifier> {
+        panic!("Should never be called")
+    }
+}
+
 struct ResolvConfQualifierFactory;
 #[cfg(target_os = "linux")]
 impl QualifierFactory for ResolvConfQualifierFactory {
@@ -112,6 +175,14 @@ impl QualifierFactory for DynamicStoreQualifierFactory {
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(target_os = "linux")]
+    use std::env;
+    #[cfg(target_os = "linux")]
+    use std::fs;
+    #[cfg(target_os = "linux")]
+    use std::os::unix::fs::symlink;
+    #[cfg(target_os = "linux")]
+    use std::path::PathBuf;
 
     #[test]
     fn resolv_conf_qualifier_factory_works_on_this_os() {
@@ -134,6 +205,84 @@ mod tests {
         }
     }
 
+    #[test]
+    fn systemd_resolved_qualifier_factory_works_on_this_os() {
+        #[cfg(target_os = "linux")]
+        {
+            let resolved_root = make_root("systemd_resolved_qualifier_factory_resolved");
+            fs::create_dir_all(resolved_root.join("etc")).unwrap();
+            symlink(
+                "../run/systemd/resolve/stub-resolv.conf",
+                resolved_root.join("etc").join("resolv.conf"),
+            )
+            .unwrap();
+            let plain_root = make_root("systemd_resolved_qualifier_factory_plain");
+            fs::create_dir_all(plain_root.join("etc")).unwrap();
+            fs::write(
+                plain_root.join("etc").join("resolv.conf"),
+                "nameserver 8.8.8.8\n",
+            )
+            .unwrap();
+
+            assert_eq!(
+                SystemdResolvedQualifierFactory::qualifies_under(&resolved_root),
+                true
+            );
+            assert_eq!(
+                SystemdResolvedQualifierFactory::qualifies_under(&plain_root),
+                false
+            );
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            let subject = SystemdResolvedQualifierFactory {};
+
+            let result = subject.system_qualifies();
+
+            assert_eq!(result, false)
+        }
+    }
+
+    #[test]
+    fn network_manager_qualifier_factory_works_on_this_os() {
+        #[cfg(target_os = "linux")]
+        {
+            let managed_root = make_root("network_manager_qualifier_factory_managed");
+            fs::create_dir_all(managed_root.join("etc/NetworkManager/system-connections")).unwrap();
+            fs::write(
+                managed_root.join("etc").join("resolv.conf"),
+                "# Generated by NetworkManager\nnameserver 8.8.8.8\n",
+            )
+            .unwrap();
+            let unmanaged_root = make_root("network_manager_qualifier_factory_unmanaged");
+            fs::create_dir_all(unmanaged_root.join("etc")).unwrap();
+            fs::write(
+                unmanaged_root.join("etc").join("resolv.conf"),
+                "# Generated by NetworkManager\nnameserver 8.8.8.8\n",
+            )
+            .unwrap();
+
+            assert_eq!(
+                NetworkManagerQualifierFactory::qualifies_under(&managed_root),
+                true
+            );
+            assert_eq!(
+                NetworkManagerQualifierFactory::qualifies_under(&unmanaged_root),
+                false
+            );
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            let subject = NetworkManagerQualifierFactory {};
+
+            let result = subject.system_qualifies();
+
+            assert_eq!(result, false)
+        }
+    }
+
     #[test]
     fn win_qualifier_factory_works_on_this_os() {
         let subject = WinQualifierFactory {};
@@ -176,4 +325,15 @@ mod tests {
 
         // no panic; test passes
     }
+
+    #[cfg(target_os = "linux")]
+    fn make_root(test_name: &str) -> PathBuf {
+        let cur_dir = env::current_dir().unwrap();
+        let generated_dir = cur_dir.join(Path::new("generated"));
+        let suite_dir = generated_dir.join(Path::new("DnsModifierFactory"));
+        let base_dir = suite_dir.join(Path::new(test_name));
+        let _ = fs::remove_dir_all(base_dir.clone()); // don't care if it doesn't exist
+        fs::create_dir_all(base_dir.clone()).unwrap();
+        base_dir
+    }
 }
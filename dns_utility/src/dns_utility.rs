@@ -10,7 +10,7 @@ enum Action {
     Subvert,
     Revert,
     Inspect,
-    Status,
+    Status { verbose: bool },
 }
 
 pub struct DnsUtility {
@@ -32,7 +32,10 @@ impl Command<u8> for DnsUtility {
             a if a[1] == "subvert" => Action::Subvert,
             a if a[1] == "revert" => Action::Revert,
             a if a[1] == "inspect" => Action::Inspect,
-            a if a[1] == "status" => Action::Status,
+            a if a[1] == "status" && a.len() == 2 => Action::Status { verbose: false },
+            a if a[1] == "status" && a.len() == 3 && a[2] == "--verbose" => {
+                Action::Status { verbose: true }
+            }
             _ => return DnsUtility::usage(streams),
         };
         self.perform_action(action, streams)
@@ -59,8 +62,8 @@ impl DnsUtility {
             Action::Subvert => (modifier.subvert(), "subvert DNS"),
             Action::Revert => (modifier.revert(), "revert DNS"),
             Action::Inspect => (modifier.inspect(streams.stdout), "inspect DNS"),
-            Action::Status => (
-                self.retrieve_status(modifier, streams.stdout),
+            Action::Status { verbose } => (
+                self.retrieve_status(modifier, verbose, streams.stdout),
                 "display DNS status",
             ),
         };
@@ -76,6 +79,7 @@ impl DnsUtility {
     fn retrieve_status(
         &self,
         modifier: Box<dyn DnsModifier>,
+        verbose: bool,
         stdout: &mut (dyn Write + Send),
     ) -> Result<(), String> {
         let mut stream_buf: Vec<u8> = vec![];
@@ -88,6 +92,9 @@ impl DnsUtility {
             ),
         };
         short_writeln!(stdout, "{}", status);
+        if verbose {
+            modifier.verbose_status(stdout)?;
+        }
         Ok(())
     }
 
@@ -101,7 +108,7 @@ impl DnsUtility {
     fn usage(streams: &mut StdStreams<'_>) -> u8 {
         short_writeln!(
             streams.stderr,
-            "Usage: dns_utility [ subvert | revert | inspect | status ]"
+            "Usage: dns_utility [ subvert | revert | inspect | status [ --verbose ] ]"
         );
         1
     }
@@ -120,6 +127,8 @@ mod tests {
         revert_results: RefCell<Vec<Result<(), String>>>,
         inspect_to_stdout: RefCell<Vec<String>>,
         inspect_results: RefCell<Vec<Result<(), String>>>,
+        verbose_status_to_stdout: RefCell<Vec<String>>,
+        verbose_status_results: RefCell<Vec<Result<(), String>>>,
     }
 
     impl DnsModifier for DnsModifierMock {
@@ -139,6 +148,16 @@ mod tests {
             write!(stdout, "{}", self.inspect_to_stdout.borrow_mut().remove(0)).unwrap();
             self.inspect_results.borrow_mut().remove(0)
         }
+
+        fn verbose_status(&self, stdout: &mut (dyn io::Write + Send)) -> Result<(), String> {
+            write!(
+                stdout,
+                "{}",
+                self.verbose_status_to_stdout.borrow_mut().remove(0)
+            )
+            .unwrap();
+            self.verbose_status_results.borrow_mut().remove(0)
+        }
     }
 
     impl DnsModifierMock {
@@ -148,6 +167,8 @@ mod tests {
                 revert_results: RefCell::new(vec![]),
                 inspect_to_stdout: RefCell::new(vec![]),
                 inspect_results: RefCell::new(vec![]),
+                verbose_status_to_stdout: RefCell::new(vec![]),
+                verbose_status_results: RefCell::new(vec![]),
             }
         }
 
@@ -170,6 +191,16 @@ mod tests {
             self.inspect_results.borrow_mut().push(result);
             self
         }
+
+        pub fn verbose_status_result(
+            self,
+            to_stdout: String,
+            result: Result<(), String>,
+        ) -> DnsModifierMock {
+            self.verbose_status_to_stdout.borrow_mut().push(to_stdout);
+            self.verbose_status_results.borrow_mut().push(result);
+            self
+        }
     }
 
     #[derive(Default)]
@@ -206,7 +237,9 @@ mod tests {
         assert_eq!(result, 1);
         assert_eq!(
             holder.stderr.get_string(),
-            String::from("Usage: dns_utility [ subvert | revert | inspect | status ]\n")
+            String::from(
+                "Usage: dns_utility [ subvert | revert | inspect | status [ --verbose ] ]\n"
+            )
         );
     }
 
@@ -223,7 +256,9 @@ mod tests {
         assert_eq!(result, 1);
         assert_eq!(
             holder.stderr.get_string(),
-            String::from("Usage: dns_utility [ subvert | revert | inspect | status ]\n")
+            String::from(
+                "Usage: dns_utility [ subvert | revert | inspect | status [ --verbose ] ]\n"
+            )
         );
     }
 
@@ -435,4 +470,80 @@ mod tests {
         assert_eq!(holder.stderr.get_string(), String::new());
         assert_eq!(holder.stdout.get_string(), String::from("reverted\n"));
     }
+
+    #[test]
+    fn go_with_status_verbose_parameters_adds_verbose_status_to_status() {
+        let mut holder = FakeStreamHolder::new();
+        let dns_modifier = DnsModifierMock::new()
+            .inspect_result("127.0.0.1\n".to_string(), Ok(()))
+            .verbose_status_result("Revert journal: none\n".to_string(), Ok(()));
+        let factory = DnsModifierFactoryMock::new().make_result(Some(Box::new(dns_modifier)));
+        let mut subject = DnsUtility::new();
+        subject.factory = Box::new(factory);
+
+        let result = subject.go(
+            &mut holder.streams(),
+            &[
+                String::new(),
+                String::from("status"),
+                String::from("--verbose"),
+            ],
+        );
+
+        assert_eq!(result, 0);
+        assert_eq!(holder.stderr.get_string(), String::new());
+        assert_eq!(
+            holder.stdout.get_string(),
+            String::from("subverted\nRevert journal: none\n")
+        );
+    }
+
+    #[test]
+    fn go_with_status_verbose_parameters_handles_verbose_status_failure() {
+        let mut holder = FakeStreamHolder::new();
+        let dns_modifier = DnsModifierMock::new()
+            .inspect_result("127.0.0.1\n".to_string(), Ok(()))
+            .verbose_status_result(String::new(), Err(String::from("blooga blooga")));
+        let factory = DnsModifierFactoryMock::new().make_result(Some(Box::new(dns_modifier)));
+        let mut subject = DnsUtility::new();
+        subject.factory = Box::new(factory);
+
+        let result = subject.go(
+            &mut holder.streams(),
+            &[
+                String::new(),
+                String::from("status"),
+                String::from("--verbose"),
+            ],
+        );
+
+        assert_eq!(result, 1);
+        assert_eq!(
+            holder.stderr.get_string(),
+            String::from("Cannot display DNS status: blooga blooga\n")
+        );
+    }
+
+    #[test]
+    fn go_with_status_and_unknown_option_prints_usage_to_stderr_and_exits_with_error() {
+        let mut holder = FakeStreamHolder::new();
+        let mut subject = DnsUtility::new();
+
+        let result = subject.go(
+            &mut holder.streams(),
+            &[
+                String::new(),
+                String::from("status"),
+                String::from("--booga"),
+            ],
+        );
+
+        assert_eq!(result, 1);
+        assert_eq!(
+            holder.stderr.get_string(),
+            String::from(
+                "Usage: dns_utility [ subvert | revert | inspect | status [ --verbose ] ]\n"
+            )
+        );
+    }
 }
@@ -18,7 +18,11 @@ pub mod dynamic_store_dns_modifier;
 pub mod ipconfig_wrapper;
 #[cfg(target_os = "windows")]
 pub mod netsh;
+pub mod network_manager_dns_modifier;
 pub mod resolv_conf_dns_modifier;
+pub mod revert_journal;
+pub mod system_command;
+pub mod systemd_resolved_dns_modifier;
 pub mod utils;
 #[cfg(target_os = "windows")]
 pub mod win_dns_modifier;
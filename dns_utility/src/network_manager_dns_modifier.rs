@@ -0,0 +1,1078 @@
+// Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+#![cfg(target_os = "linux")]
+use crate::dns_modifier::DnsModifier;
+use crate::revert_journal::{pack_files, unpack_files, JournalStore, RevertJournal, JOURNAL_PATH};
+use crate::system_command::run_command;
+use masq_lib::short_writeln;
+use std::fs;
+use std::io;
+use std::io::ErrorKind;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const CONNECTIONS_DIR: &str = "etc/NetworkManager/system-connections";
+const BEGIN_MARKER: &str = "#masq-dns-begin";
+const END_MARKER: &str = "#masq-dns-end";
+const BACKUP_PREFIX: &str = "#masq-dns-backup#";
+const IPV4_SETTINGS: [&str; 2] = ["dns=127.0.0.1;", "ignore-auto-dns=true"];
+// MASQ's DNS server only listens on IPv4, so IPv6 servers just have to be kept away
+const IPV6_SETTINGS: [&str; 1] = ["ignore-auto-dns=true"];
+
+pub trait NetworkManagerBus {
+    fn active_connections(&self) -> Result<Vec<String>, String>;
+    fn reload_connections(&self) -> Result<(), String>;
+    fn reactivate(&self, connection_id: &str) -> Result<(), String>;
+    fn dns_servers(&self) -> Result<Vec<String>, String>;
+}
+
+#[derive(Default)]
+pub struct NetworkManagerBusReal {}
+
+impl NetworkManagerBus for NetworkManagerBusReal {
+    fn active_connections(&self) -> Result<Vec<String>, String> {
+        let output = run_command(
+            "nmcli",
+            &["-t", "-f", "NAME", "connection", "show", "--active"],
+        )?;
+        Ok(output
+            .lines()
+            .map(|line| line.replace("\\:", ":"))
+            .collect())
+    }
+
+    fn reload_connections(&self) -> Result<(), String> {
+        run_command("nmcli", &["connection", "reload"]).map(|_| ())
+    }
+
+    fn reactivate(&self, connection_id: &str) -> Result<(), String> {
+        run_command("nmcli", &["connection", "up", "id", connection_id]).map(|_| ())
+    }
+
+    fn dns_servers(&self) -> Result<Vec<String>, String> {
+        let output = run_command("nmcli", &["-t", "-f", "IP4.DNS,IP6.DNS", "device", "show"])?;
+        Ok(parse_nmcli_dns(&output))
+    }
+}
+
+// Lines look like "IP4.DNS[1]:192.168.0.1"; colons inside values are escaped
+fn parse_nmcli_dns(output: &str) -> Vec<String> {
+    let mut servers: Vec<String> = vec![];
+    output
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(_, address)| address.replace("\\:", ":"))
+        .filter(|address| !address.is_empty())
+        .for_each(|address| {
+            if !servers.contains(&address) {
+                servers.push(address)
+            }
+        });
+    servers
+}
+
+struct Keyfile {
+    connection_id: String,
+    path: PathBuf,
+    contents: String,
+}
+
+pub struct NetworkManagerDnsModifier {
+    root: PathBuf,
+    bus: Box<dyn NetworkManagerBus>,
+}
+
+impl DnsModifier for NetworkManagerDnsModifier {
+    fn type_name(&self) -> &'static str {
+        "NetworkManagerDnsModifier"
+    }
+
+    fn subvert(&self) -> Result<(), String> {
+        let keyfiles = self.active_keyfiles()?;
+        if keyfiles.is_empty() {
+            return Err(String::from(
+                "This system does not appear to be connected to a network",
+            ));
+        }
+        let changes = Self::transform_keyfiles(keyfiles, Self::subvert_contents);
+        if changes.is_empty() {
+            return Ok(());
+        }
+        self.write_journal(&changes)?;
+        self.rewrite_keyfiles(changes)
+    }
+
+    fn revert(&self) -> Result<(), String> {
+        let journal_store = self.journal_store();
+        match journal_store.read()? {
+            Some(journal) => {
+                let journal = journal.check_modifier_type(self.type_name())?;
+                self.restore_journaled_keyfiles(&journal)?
+            }
+            // Subverted by a dns_utility that didn't keep a journal
+            None => {
+                let keyfiles = self.active_keyfiles()?;
+                self.rewrite_keyfiles(Self::transform_keyfiles(keyfiles, Self::revert_contents))?
+            }
+        }
+        journal_store.remove()
+    }
+
+    fn inspect(&self, stdout: &mut (dyn io::Write + Send)) -> Result<(), String> {
+        let servers = self.bus.dns_servers()?;
+        if servers.is_empty() {
+            return Err(String::from(
+                "This system does not appear to be connected to a network",
+            ));
+        }
+        let output_list = servers.into_iter().fold(String::new(), |so_far, server| {
+            format!("{}{}\n", so_far, server)
+        });
+        write!(stdout, "{}", output_list).expect("stdout doesn't work");
+        Ok(())
+    }
+
+    fn verbose_status(&self, stdout: &mut (dyn io::Write + Send)) -> Result<(), String> {
+        let journal = match self.journal_store().read_for_status(stdout) {
+            Some(journal) => journal,
+            None => return Ok(()),
+        };
+        let live_files = Self::unpack_journal(&journal)?
+            .into_iter()
+            .map(|(path, _)| {
+                self.read_keyfile(&path)
+                    .map(|contents_opt| (path.clone(), contents_opt.unwrap_or_default()))
+            })
+            .collect::<Result<Vec<(String, String)>, String>>()?;
+        let live_contents = pack_files(&live_files);
+        let drift = if journal.matches_subverted(&live_contents) {
+            "none; the connection profiles are as dns_utility left them"
+        } else if journal.matches_original(&live_contents) {
+            "the connection profiles already hold the original settings"
+        } else {
+            "the connection profiles have changed since they were subverted; revert will restore the original settings from the journal"
+        };
+        short_writeln!(stdout, "Drift: {}", drift);
+        Ok(())
+    }
+}
+
+impl Default for NetworkManagerDnsModifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NetworkManagerDnsModifier {
+    pub fn new() -> Self {
+        Self {
+            root: PathBuf::from("/"),
+            bus: Box::new(NetworkManagerBusReal::default()),
+        }
+    }
+
+    // NetworkManager either links /etc/resolv.conf into its runtime directory or signs the file
+    pub fn manages_dns(root: &Path) -> bool {
+        if !root.join(CONNECTIONS_DIR).is_dir() {
+            return false;
+        }
+        let resolv_conf = root.join("etc").join("resolv.conf");
+        match fs::read_link(&resolv_conf) {
+            Ok(target) => target.to_string_lossy().contains("NetworkManager"),
+            Err(_) => fs::read_to_string(&resolv_conf)
+                .map(|contents| contents.contains("Generated by NetworkManager"))
+                .unwrap_or(false),
+        }
+    }
+
+    fn active_keyfiles(&self) -> Result<Vec<Keyfile>, String> {
+        let active_connections = self.bus.active_connections()?;
+        let entries = match fs::read_dir(self.root.join(CONNECTIONS_DIR)) {
+            Ok(entries) => entries,
+            Err(e) => return Err(format!("/{} could not be read: {}", CONNECTIONS_DIR, e)),
+        };
+        let mut keyfiles = vec![];
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_file() {
+                continue;
+            }
+            let contents = match fs::read_to_string(&path) {
+                Ok(contents) => contents,
+                Err(e) => return Err(format!("{:?} could not be read: {}", path, e)),
+            };
+            let connection_id = match Self::connection_id(&contents) {
+                Some(connection_id) if active_connections.contains(&connection_id) => connection_id,
+                _ => continue,
+            };
+            keyfiles.push(Keyfile {
+                connection_id,
+                path,
+                contents,
+            });
+        }
+        keyfiles.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(keyfiles)
+    }
+
+    // Pairs each keyfile that needs to change with its new contents
+    fn transform_keyfiles(
+        keyfiles: Vec<Keyfile>,
+        transform: fn(&str) -> Option<String>,
+    ) -> Vec<(Keyfile, String)> {
+        keyfiles
+            .into_iter()
+            .filter_map(|keyfile| transform(&keyfile.contents).map(|contents| (keyfile, contents)))
+            .collect()
+    }
+
+    fn rewrite_keyfiles(&self, changes: Vec<(Keyfile, String)>) -> Result<(), String> {
+        let mut changed_connection_ids = vec![];
+        for (keyfile, contents) in changes {
+            if let Err(e) = fs::write(&keyfile.path, contents) {
+                return Err(format!("{:?} could not be modified: {}", keyfile.path, e));
+            }
+            changed_connection_ids.push(keyfile.connection_id);
+        }
+        self.reactivate_connections(changed_connection_ids)
+    }
+
+    fn reactivate_connections(&self, connection_ids: Vec<String>) -> Result<(), String> {
+        if connection_ids.is_empty() {
+            return Ok(());
+        }
+        self.bus.reload_connections()?;
+        connection_ids
+            .iter()
+            .try_for_each(|connection_id| self.bus.reactivate(connection_id))
+    }
+
+    fn journal_store(&self) -> JournalStore {
+        JournalStore::new(&self.root)
+    }
+
+    // Keyfiles journaled by an earlier subversion keep their original contents; a connection that
+    // has become active since then is added to the journal.
+    fn write_journal(&self, changes: &[(Keyfile, String)]) -> Result<(), String> {
+        let journal_store = self.journal_store();
+        let mut original_files = match journal_store.read()? {
+            Some(journal) if journal.modifier_type == self.type_name() => {
+                Self::unpack_journal(&journal)?
+            }
+            _ => vec![],
+        };
+        changes.iter().for_each(|(keyfile, _)| {
+            let path = self.relative_path(&keyfile.path);
+            if !original_files
+                .iter()
+                .any(|(journaled, _)| *journaled == path)
+            {
+                original_files.push((path, keyfile.contents.clone()));
+            }
+        });
+        let subverted_files = original_files
+            .iter()
+            .map(|(path, _)| {
+                let contents_opt = changes
+                    .iter()
+                    .find(|(keyfile, _)| self.relative_path(&keyfile.path) == *path)
+                    .map(|(_, contents)| contents.clone());
+                match contents_opt {
+                    Some(contents) => Ok((path.clone(), contents)),
+                    None => self
+                        .read_keyfile(path)
+                        .map(|contents_opt| (path.clone(), contents_opt.unwrap_or_default())),
+                }
+            })
+            .collect::<Result<Vec<(String, String)>, String>>()?;
+        journal_store.write(&RevertJournal::new(
+            self.type_name(),
+            &pack_files(&original_files),
+            &pack_files(&subverted_files),
+        ))
+    }
+
+    fn restore_journaled_keyfiles(&self, journal: &RevertJournal) -> Result<(), String> {
+        let active_connections = self.bus.active_connections()?;
+        let mut changed_connection_ids = vec![];
+        for (path, original_contents) in Self::unpack_journal(journal)? {
+            if self.read_keyfile(&path)?.as_ref() == Some(&original_contents) {
+                continue;
+            }
+            if let Err(e) = fs::write(self.root.join(&path), &original_contents) {
+                return Err(format!("/{} could not be modified: {}", path, e));
+            }
+            match Self::connection_id(&original_contents) {
+                Some(connection_id) if active_connections.contains(&connection_id) => {
+                    changed_connection_ids.push(connection_id)
+                }
+                _ => (),
+            }
+        }
+        self.reactivate_connections(changed_connection_ids)
+    }
+
+    fn unpack_journal(journal: &RevertJournal) -> Result<Vec<(String, String)>, String> {
+        unpack_files(&journal.original_contents).map_err(|e| {
+            format!(
+                "/{} is damaged: {}; delete it to revert without it",
+                JOURNAL_PATH, e
+            )
+        })
+    }
+
+    fn read_keyfile(&self, path: &str) -> Result<Option<String>, String> {
+        match fs::read_to_string(self.root.join(path)) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(ref e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("/{} could not be read: {}", path, e)),
+        }
+    }
+
+    fn relative_path(&self, path: &Path) -> String {
+        path.strip_prefix(&self.root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string()
+    }
+
+    fn connection_id(contents: &str) -> Option<String> {
+        let lines: Vec<&str> = contents.lines().collect();
+        let (begin, end) = Self::find_section(&lines, "connection")?;
+        lines[begin..end]
+            .iter()
+            .find_map(|line| Self::value_of(line, "id"))
+            .map(|id| id.to_string())
+    }
+
+    // Returns None if the keyfile doesn't need to change
+    fn subvert_contents(contents: &str) -> Option<String> {
+        if contents.lines().any(|line| line.trim() == BEGIN_MARKER) {
+            return None;
+        }
+        let mut lines: Vec<String> = contents.lines().map(|line| line.to_string()).collect();
+        let mut changed = false;
+        for (section, settings) in [("ipv4", &IPV4_SETTINGS[..]), ("ipv6", &IPV6_SETTINGS[..])] {
+            changed |= Self::subvert_section(&mut lines, section, settings);
+        }
+        if changed {
+            Some(lines.join("\n") + "\n")
+        } else {
+            None
+        }
+    }
+
+    fn subvert_section(lines: &mut Vec<String>, section: &str, settings: &[&str]) -> bool {
+        let line_refs: Vec<&str> = lines.iter().map(|line| line.as_str()).collect();
+        let (begin, end) = match Self::find_section(&line_refs, section) {
+            Some(range) => range,
+            None => return false,
+        };
+        let method_opt = line_refs[begin..end]
+            .iter()
+            .find_map(|line| Self::value_of(line, "method"));
+        if matches!(method_opt, Some("disabled") | Some("ignore")) {
+            return false;
+        }
+        (begin..end).for_each(|index| {
+            if Self::value_of(&lines[index], "dns").is_some()
+                || Self::value_of(&lines[index], "ignore-auto-dns").is_some()
+            {
+                lines[index] = format!("{}{}", BACKUP_PREFIX, lines[index]);
+            }
+        });
+        let mut block = vec![BEGIN_MARKER.to_string()];
+        block.extend(settings.iter().map(|setting| setting.to_string()));
+        block.push(END_MARKER.to_string());
+        lines.splice(begin..begin, block);
+        true
+    }
+
+    // Returns None if the keyfile was never subverted
+    fn revert_contents(contents: &str) -> Option<String> {
+        if !contents.lines().any(|line| line.trim() == BEGIN_MARKER) {
+            return None;
+        }
+        let mut in_block = false;
+        let lines: Vec<&str> = contents
+            .lines()
+            .filter(|line| match line.trim() {
+                BEGIN_MARKER => {
+                    in_block = true;
+                    false
+                }
+                END_MARKER => {
+                    in_block = false;
+                    false
+                }
+                _ => !in_block,
+            })
+            .map(|line| line.strip_prefix(BACKUP_PREFIX).unwrap_or(line))
+            .collect();
+        Some(lines.join("\n") + "\n")
+    }
+
+    // Returns the range of lines between the section header and the next header
+    fn find_section(lines: &[&str], name: &str) -> Option<(usize, usize)> {
+        let header = format!("[{}]", name);
+        let header_index = lines.iter().position(|line| line.trim() == header)?;
+        let end = lines[(header_index + 1)..]
+            .iter()
+            .position(|line| line.trim_start().starts_with('['))
+            .map(|offset| header_index + 1 + offset)
+            .unwrap_or(lines.len());
+        Some((header_index + 1, end))
+    }
+
+    fn value_of<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+        let (line_key, value) = line.split_once('=')?;
+        if line_key.trim() == key {
+            Some(value.trim())
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use masq_lib::test_utils::fake_stream_holder::FakeStreamHolder;
+    use std::cell::RefCell;
+    use std::env;
+    use std::os::unix::fs::symlink;
+    use std::sync::{Arc, Mutex};
+
+    const WIRED_KEYFILE: &str = "\
+[connection]
+id=Wired connection 1
+type=ethernet
+
+[ipv4]
+dns=8.8.8.8;
+method=auto
+
+[ipv6]
+addr-gen-mode=stable-privacy
+method=auto
+";
+
+    const SUBVERTED_WIRED_KEYFILE: &str = "\
+[connection]
+id=Wired connection 1
+type=ethernet
+
+[ipv4]
+#masq-dns-begin
+dns=127.0.0.1;
+ignore-auto-dns=true
+#masq-dns-end
+#masq-dns-backup#dns=8.8.8.8;
+method=auto
+
+[ipv6]
+#masq-dns-begin
+ignore-auto-dns=true
+#masq-dns-end
+addr-gen-mode=stable-privacy
+method=auto
+";
+
+    #[derive(Default)]
+    struct NetworkManagerBusMock {
+        active_connections_results: RefCell<Vec<Result<Vec<String>, String>>>,
+        reload_connections_results: RefCell<Vec<Result<(), String>>>,
+        reactivate_params: Arc<Mutex<Vec<String>>>,
+        reactivate_results: RefCell<Vec<Result<(), String>>>,
+        dns_servers_results: RefCell<Vec<Result<Vec<String>, String>>>,
+    }
+
+    impl NetworkManagerBus for NetworkManagerBusMock {
+        fn active_connections(&self) -> Result<Vec<String>, String> {
+            self.active_connections_results.borrow_mut().remove(0)
+        }
+
+        fn reload_connections(&self) -> Result<(), String> {
+            self.reload_connections_results.borrow_mut().remove(0)
+        }
+
+        fn reactivate(&self, connection_id: &str) -> Result<(), String> {
+            self.reactivate_params
+                .lock()
+                .unwrap()
+                .push(connection_id.to_string());
+            self.reactivate_results.borrow_mut().remove(0)
+        }
+
+        fn dns_servers(&self) -> Result<Vec<String>, String> {
+            self.dns_servers_results.borrow_mut().remove(0)
+        }
+    }
+
+    impl NetworkManagerBusMock {
+        fn active_connections_result(self, result: Result<Vec<String>, String>) -> Self {
+            self.active_connections_results.borrow_mut().push(result);
+            self
+        }
+
+        fn reload_connections_result(self, result: Result<(), String>) -> Self {
+            self.reload_connections_results.borrow_mut().push(result);
+            self
+        }
+
+        fn reactivate_params(mut self, params: &Arc<Mutex<Vec<String>>>) -> Self {
+            self.reactivate_params = params.clone();
+            self
+        }
+
+        fn reactivate_result(self, result: Result<(), String>) -> Self {
+            self.reactivate_results.borrow_mut().push(result);
+            self
+        }
+
+        fn dns_servers_result(self, result: Result<Vec<String>, String>) -> Self {
+            self.dns_servers_results.borrow_mut().push(result);
+            self
+        }
+    }
+
+    #[test]
+    fn instance_knows_its_type_name() {
+        let subject = NetworkManagerDnsModifier::new();
+
+        let result = subject.type_name();
+
+        assert_eq!(result, "NetworkManagerDnsModifier");
+    }
+
+    #[test]
+    fn parse_nmcli_dns_collects_distinct_servers() {
+        let output = "IP4.DNS[1]:192.168.0.1\nIP4.DNS[2]:1.1.1.1\nIP6.DNS[1]:fe80\\:\\:1\nIP4.DNS[1]:192.168.0.1\n";
+
+        let result = parse_nmcli_dns(output);
+
+        assert_eq!(
+            result,
+            vec![
+                "192.168.0.1".to_string(),
+                "1.1.1.1".to_string(),
+                "fe80::1".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn manages_dns_recognizes_resolv_conf_symlinked_to_network_manager() {
+        let root = make_root("manages_dns_recognizes_resolv_conf_symlinked_to_network_manager");
+        make_keyfile(&root, "wired.nmconnection", WIRED_KEYFILE);
+        symlink(
+            "/run/NetworkManager/resolv.conf",
+            root.join("etc").join("resolv.conf"),
+        )
+        .unwrap();
+
+        let result = NetworkManagerDnsModifier::manages_dns(&root);
+
+        assert_eq!(result, true);
+    }
+
+    #[test]
+    fn manages_dns_recognizes_resolv_conf_generated_by_network_manager() {
+        let root = make_root("manages_dns_recognizes_resolv_conf_generated_by_network_manager");
+        make_keyfile(&root, "wired.nmconnection", WIRED_KEYFILE);
+        fs::write(
+            root.join("etc").join("resolv.conf"),
+            "# Generated by NetworkManager\nnameserver 8.8.8.8\n",
+        )
+        .unwrap();
+
+        let result = NetworkManagerDnsModifier::manages_dns(&root);
+
+        assert_eq!(result, true);
+    }
+
+    #[test]
+    fn manages_dns_rejects_resolv_conf_written_by_someone_else() {
+        let root = make_root("manages_dns_rejects_resolv_conf_written_by_someone_else");
+        make_keyfile(&root, "wired.nmconnection", WIRED_KEYFILE);
+        fs::write(root.join("etc").join("resolv.conf"), "nameserver 8.8.8.8\n").unwrap();
+
+        let result = NetworkManagerDnsModifier::manages_dns(&root);
+
+        assert_eq!(result, false);
+    }
+
+    #[test]
+    fn manages_dns_rejects_system_without_connection_profiles() {
+        let root = make_root("manages_dns_rejects_system_without_connection_profiles");
+        fs::create_dir_all(root.join("etc")).unwrap();
+        fs::write(
+            root.join("etc").join("resolv.conf"),
+            "# Generated by NetworkManager\nnameserver 8.8.8.8\n",
+        )
+        .unwrap();
+
+        let result = NetworkManagerDnsModifier::manages_dns(&root);
+
+        assert_eq!(result, false);
+    }
+
+    #[test]
+    fn subvert_contents_leaves_disabled_sections_alone() {
+        let contents = "[connection]\nid=vpn\n\n[ipv4]\nmethod=disabled\n\n[ipv6]\nmethod=ignore\n";
+
+        let result = NetworkManagerDnsModifier::subvert_contents(contents);
+
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn subvert_contents_and_revert_contents_are_inverses() {
+        let subverted = NetworkManagerDnsModifier::subvert_contents(WIRED_KEYFILE).unwrap();
+        let reverted = NetworkManagerDnsModifier::revert_contents(&subverted).unwrap();
+
+        assert_eq!(subverted, SUBVERTED_WIRED_KEYFILE.to_string());
+        assert_eq!(reverted, WIRED_KEYFILE.to_string());
+    }
+
+    #[test]
+    fn subvert_rewrites_active_connections_and_reactivates_them() {
+        let root = make_root("subvert_rewrites_active_connections_and_reactivates_them");
+        let inactive_keyfile = WIRED_KEYFILE.replace("Wired connection 1", "Coffee Shop");
+        make_keyfile(&root, "wired.nmconnection", WIRED_KEYFILE);
+        make_keyfile(&root, "coffee.nmconnection", &inactive_keyfile);
+        let reactivate_params_arc = Arc::new(Mutex::new(vec![]));
+        let bus = NetworkManagerBusMock::default()
+            .active_connections_result(Ok(vec!["Wired connection 1".to_string()]))
+            .reload_connections_result(Ok(()))
+            .reactivate_params(&reactivate_params_arc)
+            .reactivate_result(Ok(()));
+        let subject = make_subject(&root, bus);
+
+        let result = subject.subvert();
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            get_keyfile(&root, "wired.nmconnection"),
+            SUBVERTED_WIRED_KEYFILE.to_string()
+        );
+        assert_eq!(get_keyfile(&root, "coffee.nmconnection"), inactive_keyfile);
+        assert_eq!(
+            *reactivate_params_arc.lock().unwrap(),
+            vec!["Wired connection 1".to_string()]
+        );
+    }
+
+    #[test]
+    fn subvert_writes_revert_journal() {
+        let root = make_root("subvert_writes_revert_journal");
+        make_keyfile(&root, "wired.nmconnection", WIRED_KEYFILE);
+        let bus = NetworkManagerBusMock::default()
+            .active_connections_result(Ok(vec!["Wired connection 1".to_string()]))
+            .reload_connections_result(Ok(()))
+            .reactivate_result(Ok(()));
+        let subject = make_subject(&root, bus);
+
+        let result = subject.subvert();
+
+        assert_eq!(result, Ok(()));
+        let journal = JournalStore::new(&root).read().unwrap().unwrap();
+        assert_eq!(
+            journal.modifier_type,
+            "NetworkManagerDnsModifier".to_string()
+        );
+        assert_eq!(
+            unpack_files(&journal.original_contents),
+            Ok(vec![(
+                "etc/NetworkManager/system-connections/wired.nmconnection".to_string(),
+                WIRED_KEYFILE.to_string()
+            )])
+        );
+        assert_eq!(
+            journal.matches_subverted(&pack_files(&[(
+                "etc/NetworkManager/system-connections/wired.nmconnection".to_string(),
+                SUBVERTED_WIRED_KEYFILE.to_string()
+            )])),
+            true
+        );
+    }
+
+    #[test]
+    fn subvert_adds_newly_active_connection_to_existing_journal() {
+        let root = make_root("subvert_adds_newly_active_connection_to_existing_journal");
+        let wifi_keyfile = WIRED_KEYFILE.replace("Wired connection 1", "Coffee Shop");
+        make_keyfile(&root, "wired.nmconnection", WIRED_KEYFILE);
+        make_keyfile(&root, "coffee.nmconnection", &wifi_keyfile);
+        let bus = NetworkManagerBusMock::default()
+            .active_connections_result(Ok(vec!["Wired connection 1".to_string()]))
+            .reload_connections_result(Ok(()))
+            .reactivate_result(Ok(()))
+            .active_connections_result(Ok(vec![
+                "Wired connection 1".to_string(),
+                "Coffee Shop".to_string(),
+            ]))
+            .reload_connections_result(Ok(()))
+            .reactivate_result(Ok(()));
+        let subject = make_subject(&root, bus);
+        subject.subvert().unwrap();
+
+        let result = subject.subvert();
+
+        assert_eq!(result, Ok(()));
+        let journal = JournalStore::new(&root).read().unwrap().unwrap();
+        assert_eq!(
+            unpack_files(&journal.original_contents),
+            Ok(vec![
+                (
+                    "etc/NetworkManager/system-connections/wired.nmconnection".to_string(),
+                    WIRED_KEYFILE.to_string()
+                ),
+                (
+                    "etc/NetworkManager/system-connections/coffee.nmconnection".to_string(),
+                    wifi_keyfile.clone()
+                )
+            ])
+        );
+        assert_eq!(
+            journal.matches_subverted(&pack_files(&[
+                (
+                    "etc/NetworkManager/system-connections/wired.nmconnection".to_string(),
+                    get_keyfile(&root, "wired.nmconnection")
+                ),
+                (
+                    "etc/NetworkManager/system-connections/coffee.nmconnection".to_string(),
+                    get_keyfile(&root, "coffee.nmconnection")
+                )
+            ])),
+            true
+        );
+    }
+
+    #[test]
+    fn subvert_does_not_modify_keyfiles_if_journal_cannot_be_written() {
+        let root = make_root("subvert_does_not_modify_keyfiles_if_journal_cannot_be_written");
+        make_keyfile(&root, "wired.nmconnection", WIRED_KEYFILE);
+        fs::create_dir_all(root.join("var").join("lib")).unwrap();
+        fs::write(root.join("var").join("lib").join("masq"), "not a directory").unwrap();
+        let bus = NetworkManagerBusMock::default()
+            .active_connections_result(Ok(vec!["Wired connection 1".to_string()]));
+        let subject = make_subject(&root, bus);
+
+        let result = subject.subvert();
+
+        let msg = result.err().unwrap();
+        assert!(
+            msg.starts_with("/var/lib/masq/dns_utility/revert-journal could not be written: "),
+            "{}",
+            msg
+        );
+        assert_eq!(
+            get_keyfile(&root, "wired.nmconnection"),
+            WIRED_KEYFILE.to_string()
+        );
+    }
+
+    #[test]
+    fn subvert_backs_off_if_dns_is_already_subverted() {
+        let root = make_root("subvert_backs_off_if_dns_is_already_subverted");
+        make_keyfile(&root, "wired.nmconnection", SUBVERTED_WIRED_KEYFILE);
+        let bus = NetworkManagerBusMock::default()
+            .active_connections_result(Ok(vec!["Wired connection 1".to_string()]));
+        let subject = make_subject(&root, bus);
+
+        let result = subject.subvert();
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            get_keyfile(&root, "wired.nmconnection"),
+            SUBVERTED_WIRED_KEYFILE.to_string()
+        );
+    }
+
+    #[test]
+    fn subvert_complains_if_no_active_connection_has_a_profile() {
+        let root = make_root("subvert_complains_if_no_active_connection_has_a_profile");
+        make_keyfile(&root, "wired.nmconnection", WIRED_KEYFILE);
+        let bus =
+            NetworkManagerBusMock::default().active_connections_result(Ok(vec!["lo".to_string()]));
+        let subject = make_subject(&root, bus);
+
+        let result = subject.subvert();
+
+        assert_eq!(
+            result,
+            Err("This system does not appear to be connected to a network".to_string())
+        );
+    }
+
+    #[test]
+    fn subvert_complains_if_connection_profiles_are_missing() {
+        let root = make_root("subvert_complains_if_connection_profiles_are_missing");
+        let bus = NetworkManagerBusMock::default()
+            .active_connections_result(Ok(vec!["Wired connection 1".to_string()]));
+        let subject = make_subject(&root, bus);
+
+        let result = subject.subvert();
+
+        let msg = result.err().unwrap();
+        assert!(
+            msg.starts_with("/etc/NetworkManager/system-connections could not be read: "),
+            "{}",
+            msg
+        );
+    }
+
+    #[test]
+    fn subvert_reports_failure_to_reactivate_connection() {
+        let root = make_root("subvert_reports_failure_to_reactivate_connection");
+        make_keyfile(&root, "wired.nmconnection", WIRED_KEYFILE);
+        let bus = NetworkManagerBusMock::default()
+            .active_connections_result(Ok(vec!["Wired connection 1".to_string()]))
+            .reload_connections_result(Ok(()))
+            .reactivate_result(Err("booga".to_string()));
+        let subject = make_subject(&root, bus);
+
+        let result = subject.subvert();
+
+        assert_eq!(result, Err("booga".to_string()));
+    }
+
+    #[test]
+    fn revert_restores_active_connections_and_reactivates_them() {
+        let root = make_root("revert_restores_active_connections_and_reactivates_them");
+        make_keyfile(&root, "wired.nmconnection", SUBVERTED_WIRED_KEYFILE);
+        let reactivate_params_arc = Arc::new(Mutex::new(vec![]));
+        let bus = NetworkManagerBusMock::default()
+            .active_connections_result(Ok(vec!["Wired connection 1".to_string()]))
+            .reload_connections_result(Ok(()))
+            .reactivate_params(&reactivate_params_arc)
+            .reactivate_result(Ok(()));
+        let subject = make_subject(&root, bus);
+
+        let result = subject.revert();
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            get_keyfile(&root, "wired.nmconnection"),
+            WIRED_KEYFILE.to_string()
+        );
+        assert_eq!(
+            *reactivate_params_arc.lock().unwrap(),
+            vec!["Wired connection 1".to_string()]
+        );
+    }
+
+    #[test]
+    fn revert_restores_journaled_keyfiles_even_if_they_were_regenerated() {
+        let root = make_root("revert_restores_journaled_keyfiles_even_if_they_were_regenerated");
+        make_keyfile(&root, "wired.nmconnection", WIRED_KEYFILE);
+        let reactivate_params_arc = Arc::new(Mutex::new(vec![]));
+        let bus = NetworkManagerBusMock::default()
+            .active_connections_result(Ok(vec!["Wired connection 1".to_string()]))
+            .reload_connections_result(Ok(()))
+            .reactivate_result(Ok(()))
+            .active_connections_result(Ok(vec!["Wired connection 1".to_string()]))
+            .reload_connections_result(Ok(()))
+            .reactivate_params(&reactivate_params_arc)
+            .reactivate_result(Ok(()));
+        let subject = make_subject(&root, bus);
+        subject.subvert().unwrap();
+        let regenerated_keyfile =
+            "[connection]\nid=Wired connection 1\n\n[ipv4]\ndns=127.0.0.1;\nmethod=auto\n";
+        make_keyfile(&root, "wired.nmconnection", regenerated_keyfile);
+
+        let result = subject.revert();
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            get_keyfile(&root, "wired.nmconnection"),
+            WIRED_KEYFILE.to_string()
+        );
+        assert_eq!(JournalStore::new(&root).read(), Ok(None));
+        assert_eq!(
+            *reactivate_params_arc.lock().unwrap(),
+            vec![
+                "Wired connection 1".to_string(),
+                "Wired connection 1".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn revert_refuses_journal_written_by_another_modifier() {
+        let root = make_root("revert_refuses_journal_written_by_another_modifier");
+        make_keyfile(&root, "wired.nmconnection", SUBVERTED_WIRED_KEYFILE);
+        JournalStore::new(&root)
+            .write(&RevertJournal::new(
+                "ResolvConfDnsModifier",
+                "nameserver 8.8.8.8\n",
+                "",
+            ))
+            .unwrap();
+        let subject = make_subject(&root, NetworkManagerBusMock::default());
+
+        let result = subject.revert();
+
+        assert_eq!(
+            result,
+            Err("/var/lib/masq/dns_utility/revert-journal was written by ResolvConfDnsModifier, not NetworkManagerDnsModifier".to_string())
+        );
+        assert_eq!(
+            get_keyfile(&root, "wired.nmconnection"),
+            SUBVERTED_WIRED_KEYFILE.to_string()
+        );
+    }
+
+    #[test]
+    fn revert_backs_off_if_dns_is_not_subverted() {
+        let root = make_root("revert_backs_off_if_dns_is_not_subverted");
+        make_keyfile(&root, "wired.nmconnection", WIRED_KEYFILE);
+        let bus = NetworkManagerBusMock::default()
+            .active_connections_result(Ok(vec!["Wired connection 1".to_string()]));
+        let subject = make_subject(&root, bus);
+
+        let result = subject.revert();
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            get_keyfile(&root, "wired.nmconnection"),
+            WIRED_KEYFILE.to_string()
+        );
+    }
+
+    #[test]
+    fn inspect_reports_servers_known_to_network_manager() {
+        let root = make_root("inspect_reports_servers_known_to_network_manager");
+        let mut stream_holder = FakeStreamHolder::new();
+        let bus = NetworkManagerBusMock::default()
+            .dns_servers_result(Ok(vec!["8.8.8.8".to_string(), "9.9.9.9".to_string()]));
+        let subject = make_subject(&root, bus);
+
+        let result = subject.inspect(stream_holder.streams().stdout);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            stream_holder.stdout.get_string(),
+            "8.8.8.8\n9.9.9.9\n".to_string()
+        );
+    }
+
+    #[test]
+    fn inspect_complains_if_system_is_not_connected() {
+        let root = make_root("inspect_complains_if_system_is_not_connected");
+        let mut stream_holder = FakeStreamHolder::new();
+        let bus = NetworkManagerBusMock::default().dns_servers_result(Ok(vec![]));
+        let subject = make_subject(&root, bus);
+
+        let result = subject.inspect(stream_holder.streams().stdout);
+
+        assert_eq!(
+            result,
+            Err("This system does not appear to be connected to a network".to_string())
+        );
+    }
+
+    #[test]
+    fn verbose_status_reports_missing_journal() {
+        let root = make_root("verbose_status_reports_missing_journal");
+        let mut stream_holder = FakeStreamHolder::new();
+        let subject = make_subject(&root, NetworkManagerBusMock::default());
+
+        let result = subject.verbose_status(stream_holder.streams().stdout);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            stream_holder.stdout.get_string(),
+            "Revert journal: none\n".to_string()
+        );
+    }
+
+    #[test]
+    fn verbose_status_reports_undrifted_subversion() {
+        let root = make_root("verbose_status_reports_undrifted_subversion");
+        make_keyfile(&root, "wired.nmconnection", WIRED_KEYFILE);
+        let mut stream_holder = FakeStreamHolder::new();
+        let subject = make_subversion(&root);
+        let journal = JournalStore::new(&root).read().unwrap().unwrap();
+
+        let result = subject.verbose_status(stream_holder.streams().stdout);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            stream_holder.stdout.get_string(),
+            format!(
+                "Revert journal: /var/lib/masq/dns_utility/revert-journal (version 1), written by NetworkManagerDnsModifier at {} seconds after the epoch\nDrift: none; the connection profiles are as dns_utility left them\n",
+                journal.timestamp
+            )
+        );
+    }
+
+    #[test]
+    fn verbose_status_reports_drift() {
+        let root = make_root("verbose_status_reports_drift");
+        make_keyfile(&root, "wired.nmconnection", WIRED_KEYFILE);
+        let mut stream_holder = FakeStreamHolder::new();
+        let subject = make_subversion(&root);
+        fs::remove_file(root.join(CONNECTIONS_DIR).join("wired.nmconnection")).unwrap();
+
+        let result = subject.verbose_status(stream_holder.streams().stdout);
+
+        assert_eq!(result, Ok(()));
+        assert!(stream_holder.stdout.get_string().ends_with(
+            "\nDrift: the connection profiles have changed since they were subverted; revert will restore the original settings from the journal\n"
+        ));
+    }
+
+    #[test]
+    fn verbose_status_reports_original_settings_put_back_by_someone_else() {
+        let root = make_root("verbose_status_reports_original_settings_put_back_by_someone_else");
+        make_keyfile(&root, "wired.nmconnection", WIRED_KEYFILE);
+        let mut stream_holder = FakeStreamHolder::new();
+        let subject = make_subversion(&root);
+        make_keyfile(&root, "wired.nmconnection", WIRED_KEYFILE);
+
+        let result = subject.verbose_status(stream_holder.streams().stdout);
+
+        assert_eq!(result, Ok(()));
+        assert!(stream_holder
+            .stdout
+            .get_string()
+            .ends_with("\nDrift: the connection profiles already hold the original settings\n"));
+    }
+
+    fn make_subversion(root: &Path) -> NetworkManagerDnsModifier {
+        let bus = NetworkManagerBusMock::default()
+            .active_connections_result(Ok(vec!["Wired connection 1".to_string()]))
+            .reload_connections_result(Ok(()))
+            .reactivate_result(Ok(()));
+        let subject = make_subject(root, bus);
+        subject.subvert().unwrap();
+        subject
+    }
+
+    fn make_subject(root: &Path, bus: NetworkManagerBusMock) -> NetworkManagerDnsModifier {
+        NetworkManagerDnsModifier {
+            root: root.to_path_buf(),
+            bus: Box::new(bus),
+        }
+    }
+
+    fn make_root(test_name: &str) -> PathBuf {
+        let cur_dir = env::current_dir().unwrap();
+        let generated_dir = cur_dir.join(Path::new("generated"));
+        let suite_dir = generated_dir.join(Path::new("NetworkManagerDnsModifier"));
+        let base_dir = suite_dir.join(Path::new(test_name));
+        let _ = fs::remove_dir_all(base_dir.clone()); // don't care if it doesn't exist
+        fs::create_dir_all(base_dir.clone()).unwrap();
+        base_dir
+    }
+
+    fn make_keyfile(root: &Path, name: &str, contents: &str) {
+        let dir = root.join(CONNECTIONS_DIR);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(name), contents).unwrap();
+    }
+
+    fn get_keyfile(root: &Path, name: &str) -> String {
+        fs::read_to_string(root.join(CONNECTIONS_DIR).join(name)).unwrap()
+    }
+}
@@ -1,6 +1,8 @@
 // Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 #![cfg(target_os = "linux")]
 use crate::dns_modifier::DnsModifier;
+use crate::revert_journal::{JournalStore, RevertJournal};
+use masq_lib::short_writeln;
 use regex::Regex;
 use std::fs::File;
 use std::fs::OpenOptions;
@@ -26,15 +28,32 @@ impl DnsModifier for ResolvConfDnsModifier {
     #[allow(unused_mut)]
     fn subvert(&self) -> Result<(), String> {
         let (mut file, contents_before) = self.open_resolv_conf(true)?;
-        let contents_after = self.subvert_contents(contents_before)?;
+        let contents_after = self.subvert_contents(contents_before.clone())?;
+        if contents_after != contents_before {
+            self.journal_store().write(&RevertJournal::new(
+                self.type_name(),
+                &contents_before,
+                &contents_after,
+            ))?;
+        }
         self.replace_contents(file, contents_after)
     }
 
     #[allow(unused_mut)]
     fn revert(&self) -> Result<(), String> {
         let (mut file, contents_before) = self.open_resolv_conf(true)?;
-        let contents_after = self.revert_contents(contents_before)?;
-        self.replace_contents(file, contents_after)
+        let journal_store = self.journal_store();
+        let contents_after = match journal_store.read()? {
+            Some(journal) => {
+                journal
+                    .check_modifier_type(self.type_name())?
+                    .original_contents
+            }
+            // Subverted by a dns_utility that didn't keep a journal
+            None => self.revert_contents(contents_before)?,
+        };
+        self.replace_contents(file, contents_after)?;
+        journal_store.remove()
     }
 
     #[allow(unused_mut)]
@@ -42,6 +61,23 @@ impl DnsModifier for ResolvConfDnsModifier {
         let (_, contents) = self.open_resolv_conf(false)?;
         self.inspect_contents(contents, stdout)
     }
+
+    fn verbose_status(&self, stdout: &mut (dyn io::Write + Send)) -> Result<(), String> {
+        let journal = match self.journal_store().read_for_status(stdout) {
+            Some(journal) => journal,
+            None => return Ok(()),
+        };
+        let (_, live_contents) = self.open_resolv_conf(false)?;
+        let drift = if journal.matches_subverted(&live_contents) {
+            "none; /etc/resolv.conf is as dns_utility left it"
+        } else if journal.matches_original(&live_contents) {
+            "/etc/resolv.conf already holds the original settings"
+        } else {
+            "/etc/resolv.conf has changed since it was subverted; revert will restore the original settings from the journal"
+        };
+        short_writeln!(stdout, "Drift: {}", drift);
+        Ok(())
+    }
 }
 
 impl Default for ResolvConfDnsModifier {
@@ -96,6 +132,10 @@ impl ResolvConfDnsModifier {
         Ok((file, contents))
     }
 
+    fn journal_store(&self) -> JournalStore {
+        JournalStore::new(&self.root)
+    }
+
     fn process_msg(msg: &str, for_write: bool) -> String {
         if for_write {
             format!("{} and could not be modified", msg)
@@ -284,6 +324,7 @@ impl ResolvConfDnsModifier {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::revert_journal::JOURNAL_PATH;
     use masq_lib::test_utils::fake_stream_holder::FakeStreamHolder;
     use std::env;
     use std::fs;
@@ -842,6 +883,221 @@ mod tests {
         assert_eq!(result.is_ok(), true);
     }
 
+    #[test]
+    fn subvert_writes_revert_journal() {
+        let root = make_root("subvert_writes_revert_journal");
+        make_resolv_conf(&root, "nameserver 8.8.8.8\n");
+        let mut subject = ResolvConfDnsModifier::new();
+        subject.root = root.clone();
+
+        let result = subject.subvert();
+
+        assert_eq!(result, Ok(()));
+        let journal = JournalStore::new(&root).read().unwrap().unwrap();
+        assert_eq!(journal.modifier_type, "ResolvConfDnsModifier".to_string());
+        assert_eq!(
+            journal.original_contents,
+            "nameserver 8.8.8.8\n".to_string()
+        );
+        assert_eq!(journal.matches_subverted(&get_resolv_conf(&root)), true);
+    }
+
+    #[test]
+    fn subvert_leaves_journal_alone_if_dns_is_already_subverted() {
+        let root = make_root("subvert_leaves_journal_alone_if_dns_is_already_subverted");
+        make_resolv_conf(&root, "#nameserver 8.8.8.8\nnameserver 127.0.0.1\n");
+        let journal = RevertJournal::new(
+            "ResolvConfDnsModifier",
+            "nameserver 8.8.8.8\n",
+            "#nameserver 8.8.8.8\nnameserver 127.0.0.1\n",
+        );
+        JournalStore::new(&root).write(&journal).unwrap();
+        let mut subject = ResolvConfDnsModifier::new();
+        subject.root = root.clone();
+
+        let result = subject.subvert();
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(JournalStore::new(&root).read(), Ok(Some(journal)));
+    }
+
+    #[test]
+    fn subvert_does_not_modify_resolv_conf_if_journal_cannot_be_written() {
+        let root = make_root("subvert_does_not_modify_resolv_conf_if_journal_cannot_be_written");
+        make_resolv_conf(&root, "nameserver 8.8.8.8\n");
+        fs::create_dir_all(root.join("var").join("lib")).unwrap();
+        fs::write(root.join("var").join("lib").join("masq"), "not a directory").unwrap();
+        let mut subject = ResolvConfDnsModifier::new();
+        subject.root = root.clone();
+
+        let result = subject.subvert();
+
+        let msg = result.err().unwrap();
+        assert!(
+            msg.starts_with("/var/lib/masq/dns_utility/revert-journal could not be written: "),
+            "{}",
+            msg
+        );
+        assert_eq!(get_resolv_conf(&root), "nameserver 8.8.8.8\n".to_string());
+    }
+
+    #[test]
+    fn revert_restores_journaled_contents_even_if_resolv_conf_was_regenerated() {
+        let root =
+            make_root("revert_restores_journaled_contents_even_if_resolv_conf_was_regenerated");
+        make_resolv_conf(&root, "nameserver 8.8.8.8\n");
+        let mut subject = ResolvConfDnsModifier::new();
+        subject.root = root.clone();
+        subject.subvert().unwrap();
+        make_resolv_conf(&root, "nameserver 127.0.0.1\n");
+
+        let result = subject.revert();
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(get_resolv_conf(&root), "nameserver 8.8.8.8\n".to_string());
+        assert_eq!(JournalStore::new(&root).read(), Ok(None));
+    }
+
+    #[test]
+    fn revert_refuses_damaged_journal() {
+        let root = make_root("revert_refuses_damaged_journal");
+        make_resolv_conf(&root, "#nameserver 8.8.8.8\nnameserver 127.0.0.1\n");
+        let journal_path = root.join(JOURNAL_PATH);
+        fs::create_dir_all(journal_path.parent().unwrap()).unwrap();
+        fs::write(&journal_path, "booga").unwrap();
+        let mut subject = ResolvConfDnsModifier::new();
+        subject.root = root.clone();
+
+        let result = subject.revert();
+
+        assert_eq!(
+            result,
+            Err("/var/lib/masq/dns_utility/revert-journal is damaged: it has no contents section; delete it to revert without it".to_string())
+        );
+        assert_eq!(
+            get_resolv_conf(&root),
+            "#nameserver 8.8.8.8\nnameserver 127.0.0.1\n".to_string()
+        );
+    }
+
+    #[test]
+    fn revert_refuses_journal_written_by_another_modifier() {
+        let root = make_root("revert_refuses_journal_written_by_another_modifier");
+        make_resolv_conf(&root, "nameserver 127.0.0.1\n");
+        JournalStore::new(&root)
+            .write(&RevertJournal::new(
+                "BoogaDnsModifier",
+                "nameserver 8.8.8.8\n",
+                "",
+            ))
+            .unwrap();
+        let mut subject = ResolvConfDnsModifier::new();
+        subject.root = root.clone();
+
+        let result = subject.revert();
+
+        assert_eq!(
+            result,
+            Err("/var/lib/masq/dns_utility/revert-journal was written by BoogaDnsModifier, not ResolvConfDnsModifier".to_string())
+        );
+    }
+
+    #[test]
+    fn verbose_status_reports_missing_journal() {
+        let mut stream_holder = FakeStreamHolder::new();
+        let root = make_root("verbose_status_reports_missing_journal");
+        make_resolv_conf(&root, "nameserver 8.8.8.8\n");
+        let mut subject = ResolvConfDnsModifier::new();
+        subject.root = root;
+
+        let result = subject.verbose_status(stream_holder.streams().stdout);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            stream_holder.stdout.get_string(),
+            "Revert journal: none\n".to_string()
+        );
+    }
+
+    #[test]
+    fn verbose_status_reports_undrifted_subversion() {
+        let mut stream_holder = FakeStreamHolder::new();
+        let root = make_root("verbose_status_reports_undrifted_subversion");
+        make_resolv_conf(&root, "nameserver 8.8.8.8\n");
+        let mut subject = ResolvConfDnsModifier::new();
+        subject.root = root.clone();
+        subject.subvert().unwrap();
+        let journal = JournalStore::new(&root).read().unwrap().unwrap();
+
+        let result = subject.verbose_status(stream_holder.streams().stdout);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            stream_holder.stdout.get_string(),
+            format!(
+                "Revert journal: /var/lib/masq/dns_utility/revert-journal (version 1), written by ResolvConfDnsModifier at {} seconds after the epoch\nDrift: none; /etc/resolv.conf is as dns_utility left it\n",
+                journal.timestamp
+            )
+        );
+    }
+
+    #[test]
+    fn verbose_status_reports_drift() {
+        let mut stream_holder = FakeStreamHolder::new();
+        let root = make_root("verbose_status_reports_drift");
+        make_resolv_conf(&root, "nameserver 8.8.8.8\n");
+        let mut subject = ResolvConfDnsModifier::new();
+        subject.root = root.clone();
+        subject.subvert().unwrap();
+        make_resolv_conf(&root, "nameserver 192.168.0.1\n");
+
+        let result = subject.verbose_status(stream_holder.streams().stdout);
+
+        assert_eq!(result, Ok(()));
+        assert!(stream_holder.stdout.get_string().ends_with(
+            "\nDrift: /etc/resolv.conf has changed since it was subverted; revert will restore the original settings from the journal\n"
+        ));
+    }
+
+    #[test]
+    fn verbose_status_reports_original_settings_put_back_by_someone_else() {
+        let mut stream_holder = FakeStreamHolder::new();
+        let root = make_root("verbose_status_reports_original_settings_put_back_by_someone_else");
+        make_resolv_conf(&root, "nameserver 8.8.8.8\n");
+        let mut subject = ResolvConfDnsModifier::new();
+        subject.root = root.clone();
+        subject.subvert().unwrap();
+        make_resolv_conf(&root, "nameserver 8.8.8.8\n");
+
+        let result = subject.verbose_status(stream_holder.streams().stdout);
+
+        assert_eq!(result, Ok(()));
+        assert!(stream_holder
+            .stdout
+            .get_string()
+            .ends_with("\nDrift: /etc/resolv.conf already holds the original settings\n"));
+    }
+
+    #[test]
+    fn verbose_status_reports_damaged_journal() {
+        let mut stream_holder = FakeStreamHolder::new();
+        let root = make_root("verbose_status_reports_damaged_journal");
+        make_resolv_conf(&root, "nameserver 8.8.8.8\n");
+        let journal_path = root.join(JOURNAL_PATH);
+        fs::create_dir_all(journal_path.parent().unwrap()).unwrap();
+        fs::write(&journal_path, "booga").unwrap();
+        let mut subject = ResolvConfDnsModifier::new();
+        subject.root = root;
+
+        let result = subject.verbose_status(stream_holder.streams().stdout);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            stream_holder.stdout.get_string(),
+            "Revert journal: /var/lib/masq/dns_utility/revert-journal is damaged: it has no contents section; delete it to revert without it\n".to_string()
+        );
+    }
+
     fn make_root(test_name: &str) -> PathBuf {
         let cur_dir = env::current_dir().unwrap();
         let generated_dir = cur_dir.join(Path::new("generated"));
@@ -0,0 +1,425 @@
+// Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+use masq_lib::short_writeln;
+use masq_lib::utils::fnv1a_64;
+use std::fs;
+use std::io;
+use std::io::ErrorKind;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const JOURNAL_VERSION: u32 = 1;
+pub const JOURNAL_PATH: &str = "var/lib/masq/dns_utility/revert-journal";
+const HEADER_PREFIX: &str = "MASQ dns_utility revert journal v";
+
+// Everything needed to put a system's DNS settings back the way they were before subversion,
+// even if the live configuration has been regenerated or lost its comments since then.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RevertJournal {
+    pub modifier_type: String,
+    pub timestamp: u64,
+    pub original_contents: String,
+    pub subverted_checksum: String,
+}
+
+impl RevertJournal {
+    pub fn new(modifier_type: &str, original_contents: &str, subverted_contents: &str) -> Self {
+        Self {
+            modifier_type: modifier_type.to_string(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("System clock is before the epoch")
+                .as_secs(),
+            original_contents: original_contents.to_string(),
+            subverted_checksum: checksum(subverted_contents),
+        }
+    }
+
+    pub fn serialize(&self) -> String {
+        format!(
+            "{}{}\nmodifier: {}\ntimestamp: {}\nchecksum: {}\nsubverted-checksum: {}\n\n{}",
+            HEADER_PREFIX,
+            JOURNAL_VERSION,
+            self.modifier_type,
+            self.timestamp,
+            checksum(&self.original_contents),
+            self.subverted_checksum,
+            self.original_contents
+        )
+    }
+
+    pub fn deserialize(text: &str) -> Result<Self, String> {
+        let (header, original_contents) = match text.split_once("\n\n") {
+            Some(pair) => pair,
+            None => return Err("it has no contents section".to_string()),
+        };
+        let mut lines = header.lines();
+        let version = lines
+            .next()
+            .and_then(|line| line.strip_prefix(HEADER_PREFIX))
+            .and_then(|version| version.parse::<u32>().ok());
+        match version {
+            Some(JOURNAL_VERSION) => (),
+            Some(version) => return Err(format!("version {} is not supported", version)),
+            None => return Err("it has no version header".to_string()),
+        }
+        let fields: Vec<(&str, &str)> = lines.filter_map(|line| line.split_once(": ")).collect();
+        let field = |name: &str| -> Result<String, String> {
+            fields
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+                .ok_or_else(|| format!("it has no {} field", name))
+        };
+        let timestamp = match field("timestamp")?.parse::<u64>() {
+            Ok(timestamp) => timestamp,
+            Err(_) => return Err("its timestamp is not a number".to_string()),
+        };
+        if field("checksum")? != checksum(original_contents) {
+            return Err("its checksum doesn't match its contents".to_string());
+        }
+        Ok(Self {
+            modifier_type: field("modifier")?,
+            timestamp,
+            original_contents: original_contents.to_string(),
+            subverted_checksum: field("subverted-checksum")?,
+        })
+    }
+
+    pub fn matches_subverted(&self, live_contents: &str) -> bool {
+        checksum(live_contents) == self.subverted_checksum
+    }
+
+    pub fn matches_original(&self, live_contents: &str) -> bool {
+        live_contents == self.original_contents
+    }
+
+    pub fn check_modifier_type(self, type_name: &str) -> Result<Self, String> {
+        if self.modifier_type == type_name {
+            Ok(self)
+        } else {
+            Err(format!(
+                "/{} was written by {}, not {}",
+                JOURNAL_PATH, self.modifier_type, type_name
+            ))
+        }
+    }
+
+    pub fn description(&self) -> String {
+        format!(
+            "/{} (version {}), written by {} at {} seconds after the epoch",
+            JOURNAL_PATH, JOURNAL_VERSION, self.modifier_type, self.timestamp
+        )
+    }
+}
+
+// Modifiers that change several files journal them all together. Each file is introduced by a
+// "file <length> <path>" line, so nothing in its contents can be mistaken for the next one.
+pub fn pack_files(files: &[(String, String)]) -> String {
+    files
+        .iter()
+        .fold(String::new(), |so_far, (path, contents)| {
+            format!("{}file {} {}\n{}", so_far, contents.len(), path, contents)
+        })
+}
+
+pub fn unpack_files(packed: &str) -> Result<Vec<(String, String)>, String> {
+    let mut files = vec![];
+    let mut remainder = packed;
+    while !remainder.is_empty() {
+        let (header, rest) = match remainder.split_once('\n') {
+            Some(pair) => pair,
+            None => return Err("a file header is unterminated".to_string()),
+        };
+        let (length, path) = match header
+            .strip_prefix("file ")
+            .and_then(|header| header.split_once(' '))
+            .and_then(|(length, path)| length.parse::<usize>().ok().map(|length| (length, path)))
+        {
+            Some(pair) => pair,
+            None => return Err(format!("'{}' is not a file header", header)),
+        };
+        let contents = match rest.get(..length) {
+            Some(contents) => contents,
+            None => return Err(format!("the contents of {} are truncated", path)),
+        };
+        files.push((path.to_string(), contents.to_string()));
+        remainder = &rest[length..];
+    }
+    Ok(files)
+}
+
+pub fn checksum(contents: &str) -> String {
+    format!("{:016x}", fnv1a_64(contents.as_bytes()))
+}
+
+pub struct JournalStore {
+    path: PathBuf,
+}
+
+impl JournalStore {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            path: root.join(JOURNAL_PATH),
+        }
+    }
+
+    pub fn read(&self) -> Result<Option<RevertJournal>, String> {
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(ref e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("/{} could not be read: {}", JOURNAL_PATH, e)),
+        };
+        match RevertJournal::deserialize(&text) {
+            Ok(journal) => Ok(Some(journal)),
+            Err(e) => Err(format!(
+                "/{} is damaged: {}; delete it to revert without it",
+                JOURNAL_PATH, e
+            )),
+        }
+    }
+
+    // Reports the journal, or why there isn't a usable one, for status --verbose
+    pub fn read_for_status(&self, stdout: &mut (dyn io::Write + Send)) -> Option<RevertJournal> {
+        match self.read() {
+            Ok(Some(journal)) => {
+                short_writeln!(stdout, "Revert journal: {}", journal.description());
+                Some(journal)
+            }
+            Ok(None) => {
+                short_writeln!(stdout, "Revert journal: none");
+                None
+            }
+            Err(msg) => {
+                short_writeln!(stdout, "Revert journal: {}", msg);
+                None
+            }
+        }
+    }
+
+    // Written aside and renamed into place, so a crash can't leave half a journal behind
+    pub fn write(&self, journal: &RevertJournal) -> Result<(), String> {
+        let temp_path = self.path.with_extension("new");
+        self.path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&temp_path, journal.serialize()))
+            .and_then(|_| fs::rename(&temp_path, &self.path))
+            .map_err(|e| format!("/{} could not be written: {}", JOURNAL_PATH, e))
+    }
+
+    pub fn remove(&self) -> Result<(), String> {
+        match fs::remove_file(&self.path) {
+            Ok(_) => Ok(()),
+            Err(ref e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(format!("/{} could not be removed: {}", JOURNAL_PATH, e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use masq_lib::test_utils::fake_stream_holder::FakeStreamHolder;
+    use std::env;
+
+    #[test]
+    fn constants_have_correct_values() {
+        assert_eq!(JOURNAL_VERSION, 1);
+        assert_eq!(JOURNAL_PATH, "var/lib/masq/dns_utility/revert-journal");
+    }
+
+    #[test]
+    fn checksum_is_fnv_1a_in_hex() {
+        assert_eq!(checksum(""), "cbf29ce484222325".to_string());
+        assert_eq!(checksum("a"), "af63dc4c8601ec8c".to_string());
+    }
+
+    #[test]
+    fn serialize_and_deserialize_are_inverses() {
+        let journal = RevertJournal {
+            modifier_type: "ResolvConfDnsModifier".to_string(),
+            timestamp: 1234567890,
+            original_contents: "nameserver 8.8.8.8\n\nnameserver 9.9.9.9\n".to_string(),
+            subverted_checksum: checksum("nameserver 127.0.0.1\n"),
+        };
+
+        let text = journal.serialize();
+        let result = RevertJournal::deserialize(&text);
+
+        assert_eq!(
+            text,
+            format!(
+                "MASQ dns_utility revert journal v1\nmodifier: ResolvConfDnsModifier\ntimestamp: 1234567890\nchecksum: {}\nsubverted-checksum: {}\n\nnameserver 8.8.8.8\n\nnameserver 9.9.9.9\n",
+                checksum("nameserver 8.8.8.8\n\nnameserver 9.9.9.9\n"),
+                checksum("nameserver 127.0.0.1\n")
+            )
+        );
+        assert_eq!(result, Ok(journal));
+    }
+
+    #[test]
+    fn deserialize_detects_tampered_contents() {
+        let text = RevertJournal::new("ResolvConfDnsModifier", "nameserver 8.8.8.8\n", "")
+            .serialize()
+            .replace("8.8.8.8", "6.6.6.6");
+
+        let result = RevertJournal::deserialize(&text);
+
+        assert_eq!(
+            result,
+            Err("its checksum doesn't match its contents".to_string())
+        );
+    }
+
+    #[test]
+    fn deserialize_rejects_unknown_version() {
+        let text = RevertJournal::new("ResolvConfDnsModifier", "nameserver 8.8.8.8\n", "")
+            .serialize()
+            .replace("journal v1", "journal v2");
+
+        let result = RevertJournal::deserialize(&text);
+
+        assert_eq!(result, Err("version 2 is not supported".to_string()));
+    }
+
+    #[test]
+    fn deserialize_rejects_missing_fields() {
+        let text = RevertJournal::new("ResolvConfDnsModifier", "nameserver 8.8.8.8\n", "")
+            .serialize()
+            .replace("modifier: ", "modifeir: ");
+
+        let result = RevertJournal::deserialize(&text);
+
+        assert_eq!(result, Err("it has no modifier field".to_string()));
+    }
+
+    #[test]
+    fn journal_recognizes_subverted_and_original_contents() {
+        let subject = RevertJournal::new(
+            "ResolvConfDnsModifier",
+            "nameserver 8.8.8.8\n",
+            "#nameserver 8.8.8.8\nnameserver 127.0.0.1\n",
+        );
+
+        assert_eq!(
+            subject.matches_subverted("#nameserver 8.8.8.8\nnameserver 127.0.0.1\n"),
+            true
+        );
+        assert_eq!(subject.matches_subverted("nameserver 8.8.8.8\n"), false);
+        assert_eq!(subject.matches_original("nameserver 8.8.8.8\n"), true);
+        assert_eq!(subject.matches_original("nameserver 1.1.1.1\n"), false);
+    }
+
+    #[test]
+    fn journal_checks_its_modifier_type() {
+        let subject = RevertJournal::new("ResolvConfDnsModifier", "nameserver 8.8.8.8\n", "");
+
+        assert_eq!(
+            subject.clone().check_modifier_type("ResolvConfDnsModifier"),
+            Ok(subject.clone())
+        );
+        assert_eq!(
+            subject.check_modifier_type("BoogaDnsModifier"),
+            Err("/var/lib/masq/dns_utility/revert-journal was written by ResolvConfDnsModifier, not BoogaDnsModifier".to_string())
+        );
+    }
+
+    #[test]
+    fn pack_files_and_unpack_files_are_inverses() {
+        let files = vec![
+            (
+                "etc/NetworkManager/system-connections/Wired connection 1.nmconnection".to_string(),
+                "[connection]\nid=Wired connection 1\nfile 3 booga\n".to_string(),
+            ),
+            ("empty".to_string(), String::new()),
+            ("unicode".to_string(), "ñandú\n".to_string()),
+        ];
+
+        let packed = pack_files(&files);
+        let result = unpack_files(&packed);
+
+        assert_eq!(result, Ok(files));
+        assert_eq!(unpack_files(""), Ok(vec![]));
+    }
+
+    #[test]
+    fn unpack_files_rejects_damaged_packing() {
+        assert_eq!(
+            unpack_files("file 3 booga"),
+            Err("a file header is unterminated".to_string())
+        );
+        assert_eq!(
+            unpack_files("booga\nabc"),
+            Err("'booga' is not a file header".to_string())
+        );
+        assert_eq!(
+            unpack_files("file 30 booga\nabc"),
+            Err("the contents of booga are truncated".to_string())
+        );
+        assert_eq!(
+            unpack_files("file 1 booga\nñ"),
+            Err("the contents of booga are truncated".to_string())
+        );
+    }
+
+    #[test]
+    fn journal_store_writes_reads_and_removes_journal() {
+        let root = make_root("journal_store_writes_reads_and_removes_journal");
+        let subject = JournalStore::new(&root);
+        let journal = RevertJournal::new("ResolvConfDnsModifier", "nameserver 8.8.8.8\n", "");
+
+        assert_eq!(subject.read(), Ok(None));
+        assert_eq!(subject.write(&journal), Ok(()));
+        assert_eq!(subject.read(), Ok(Some(journal)));
+        assert_eq!(subject.remove(), Ok(()));
+        assert_eq!(subject.read(), Ok(None));
+        assert_eq!(subject.remove(), Ok(()));
+    }
+
+    #[test]
+    fn journal_store_describes_journal_for_status() {
+        let root = make_root("journal_store_describes_journal_for_status");
+        let subject = JournalStore::new(&root);
+        let journal = RevertJournal::new("ResolvConfDnsModifier", "nameserver 8.8.8.8\n", "");
+        subject.write(&journal).unwrap();
+        let mut stream_holder = FakeStreamHolder::new();
+
+        let result = subject.read_for_status(stream_holder.streams().stdout);
+
+        assert_eq!(
+            stream_holder.stdout.get_string(),
+            format!(
+                "Revert journal: /var/lib/masq/dns_utility/revert-journal (version 1), written by ResolvConfDnsModifier at {} seconds after the epoch\n",
+                journal.timestamp
+            )
+        );
+        assert_eq!(result, Some(journal));
+    }
+
+    #[test]
+    fn journal_store_reports_damaged_journal() {
+        let root = make_root("journal_store_reports_damaged_journal");
+        let path = root.join(JOURNAL_PATH);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "booga").unwrap();
+        let subject = JournalStore::new(&root);
+
+        let result = subject.read();
+
+        assert_eq!(
+            result,
+            Err("/var/lib/masq/dns_utility/revert-journal is damaged: it has no contents section; delete it to revert without it".to_string())
+        );
+    }
+
+    fn make_root(test_name: &str) -> PathBuf {
+        let cur_dir = env::current_dir().unwrap();
+        let generated_dir = cur_dir.join(Path::new("generated"));
+        let suite_dir = generated_dir.join(Path::new("RevertJournal"));
+        let base_dir = suite_dir.join(Path::new(test_name));
+        let _ = fs::remove_dir_all(base_dir.clone()); // don't care if it doesn't exist
+        fs::create_dir_all(base_dir.clone()).unwrap();
+        base_dir
+    }
+}
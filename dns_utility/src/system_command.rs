@@ -0,0 +1,56 @@
+// Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+#![cfg(target_os = "linux")]
+use std::process;
+
+pub fn run_command(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = match process::Command::new(program).args(args).output() {
+        Ok(output) => output,
+        Err(e) => return Err(format!("Could not run {}: {}", program, e)),
+    };
+    if !output.status.success() {
+        return Err(format!(
+            "'{} {}' failed: {}",
+            program,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    match String::from_utf8(output.stdout) {
+        Ok(stdout) => Ok(stdout),
+        Err(e) => Err(format!("{} produced non-UTF-8 output: {}", program, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_command_returns_stdout_on_success() {
+        let result = run_command("sh", &["-c", "echo booga"]);
+
+        assert_eq!(result, Ok("booga\n".to_string()));
+    }
+
+    #[test]
+    fn run_command_returns_stderr_on_failure() {
+        let result = run_command("sh", &["-c", "echo booga >&2; exit 1"]);
+
+        assert_eq!(
+            result,
+            Err("'sh -c echo booga >&2; exit 1' failed: booga".to_string())
+        );
+    }
+
+    #[test]
+    fn run_command_complains_about_missing_program() {
+        let result = run_command("no_such_program_for_dns_utility", &[]);
+
+        let msg = result.err().unwrap();
+        assert!(
+            msg.starts_with("Could not run no_such_program_for_dns_utility: "),
+            "{}",
+            msg
+        );
+    }
+}
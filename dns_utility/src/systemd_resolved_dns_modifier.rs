@@ -0,0 +1,645 @@
+// Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+#![cfg(target_os = "linux")]
+use crate::dns_modifier::DnsModifier;
+use crate::revert_journal::{JournalStore, RevertJournal};
+use crate::system_command::run_command;
+use masq_lib::short_writeln;
+use std::fs;
+use std::io;
+use std::io::ErrorKind;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const DROP_IN_DIR: &str = "etc/systemd/resolved.conf.d";
+const DROP_IN_FILE: &str = "masq-dns.conf";
+// The ~. routing domain makes the global DNS server preferred over the per-link ones
+const DROP_IN_CONTENTS: &str = "\
+# Written by dns_utility; 'dns_utility revert' removes this file
+[Resolve]
+DNS=127.0.0.1
+Domains=~.
+";
+
+pub trait ResolvedBus {
+    fn restart(&self) -> Result<(), String>;
+    fn dns_servers(&self) -> Result<Vec<String>, String>;
+}
+
+#[derive(Default)]
+pub struct ResolvedBusReal {}
+
+impl ResolvedBus for ResolvedBusReal {
+    fn restart(&self) -> Result<(), String> {
+        run_command("systemctl", &["restart", "systemd-resolved"]).map(|_| ())
+    }
+
+    fn dns_servers(&self) -> Result<Vec<String>, String> {
+        let output = run_command("resolvectl", &["dns"])?;
+        Ok(parse_resolvectl_dns(&output))
+    }
+}
+
+// Lines look like "Global: 1.1.1.1" or "Link 2 (eth0): 192.168.0.1 fe80::1%2"
+fn parse_resolvectl_dns(output: &str) -> Vec<String> {
+    let mut servers: Vec<String> = vec![];
+    output
+        .lines()
+        .filter_map(|line| line.split_once(": "))
+        .flat_map(|(_, addresses)| addresses.split_whitespace())
+        .for_each(|address| {
+            if !servers.iter().any(|server| server == address) {
+                servers.push(address.to_string())
+            }
+        });
+    servers
+}
+
+pub struct SystemdResolvedDnsModifier {
+    root: PathBuf,
+    bus: Box<dyn ResolvedBus>,
+}
+
+impl DnsModifier for SystemdResolvedDnsModifier {
+    fn type_name(&self) -> &'static str {
+        "SystemdResolvedDnsModifier"
+    }
+
+    fn subvert(&self) -> Result<(), String> {
+        if self.drop_in_path().exists() {
+            return Ok(());
+        }
+        Self::check_disconnected(&self.bus.dns_servers()?)?;
+        // Before subversion there was no drop-in, so the original contents are empty
+        self.journal_store()
+            .write(&RevertJournal::new(self.type_name(), "", DROP_IN_CONTENTS))?;
+        self.write_drop_in()?;
+        self.bus.restart()
+    }
+
+    fn revert(&self) -> Result<(), String> {
+        let journal_store = self.journal_store();
+        if let Some(journal) = journal_store.read()? {
+            journal.check_modifier_type(self.type_name())?;
+        }
+        match fs::remove_file(self.drop_in_path()) {
+            Ok(_) => self.bus.restart()?,
+            Err(ref e) if e.kind() == ErrorKind::NotFound => (),
+            Err(e) => {
+                return Err(format!(
+                    "/{}/{} could not be removed: {}",
+                    DROP_IN_DIR, DROP_IN_FILE, e
+                ))
+            }
+        }
+        journal_store.remove()
+    }
+
+    fn inspect(&self, stdout: &mut (dyn io::Write + Send)) -> Result<(), String> {
+        let servers = match self.read_drop_in_servers()? {
+            Some(servers) => servers,
+            None => self.bus.dns_servers()?,
+        };
+        Self::check_disconnected(&servers)?;
+        let output_list = servers.into_iter().fold(String::new(), |so_far, server| {
+            format!("{}{}\n", so_far, server)
+        });
+        write!(stdout, "{}", output_list).expect("stdout doesn't work");
+        Ok(())
+    }
+
+    fn verbose_status(&self, stdout: &mut (dyn io::Write + Send)) -> Result<(), String> {
+        let journal = match self.journal_store().read_for_status(stdout) {
+            Some(journal) => journal,
+            None => return Ok(()),
+        };
+        let drift = match self.read_drop_in()? {
+            Some(contents) if journal.matches_subverted(&contents) => format!(
+                "none; /{}/{} is as dns_utility left it",
+                DROP_IN_DIR, DROP_IN_FILE
+            ),
+            Some(_) => format!(
+                "/{}/{} has changed since it was subverted; revert will remove it",
+                DROP_IN_DIR, DROP_IN_FILE
+            ),
+            None => format!(
+                "/{}/{} has been removed; the original settings are already in effect",
+                DROP_IN_DIR, DROP_IN_FILE
+            ),
+        };
+        short_writeln!(stdout, "Drift: {}", drift);
+        Ok(())
+    }
+}
+
+impl Default for SystemdResolvedDnsModifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SystemdResolvedDnsModifier {
+    pub fn new() -> Self {
+        Self {
+            root: PathBuf::from("/"),
+            bus: Box::new(ResolvedBusReal::default()),
+        }
+    }
+
+    // When systemd-resolved is in charge, /etc/resolv.conf is a symlink into its runtime directory
+    pub fn manages_dns(root: &Path) -> bool {
+        match fs::read_link(root.join("etc").join("resolv.conf")) {
+            Ok(target) => target.to_string_lossy().contains("systemd/resolve"),
+            Err(_) => false,
+        }
+    }
+
+    fn journal_store(&self) -> JournalStore {
+        JournalStore::new(&self.root)
+    }
+
+    fn drop_in_path(&self) -> PathBuf {
+        self.root.join(DROP_IN_DIR).join(DROP_IN_FILE)
+    }
+
+    fn write_drop_in(&self) -> Result<(), String> {
+        fs::create_dir_all(self.root.join(DROP_IN_DIR))
+            .and_then(|_| fs::write(self.drop_in_path(), DROP_IN_CONTENTS))
+            .map_err(|e| {
+                format!(
+                    "/{}/{} could not be written: {}",
+                    DROP_IN_DIR, DROP_IN_FILE, e
+                )
+            })
+    }
+
+    fn read_drop_in(&self) -> Result<Option<String>, String> {
+        match fs::read_to_string(self.drop_in_path()) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(ref e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!(
+                "/{}/{} could not be read: {}",
+                DROP_IN_DIR, DROP_IN_FILE, e
+            )),
+        }
+    }
+
+    fn read_drop_in_servers(&self) -> Result<Option<Vec<String>>, String> {
+        Ok(self.read_drop_in()?.map(|contents| {
+            contents
+                .lines()
+                .filter_map(|line| line.trim().strip_prefix("DNS="))
+                .flat_map(|servers| servers.split_whitespace())
+                .map(|server| server.to_string())
+                .collect()
+        }))
+    }
+
+    fn check_disconnected(servers: &[String]) -> Result<(), String> {
+        if servers.is_empty() {
+            Err(String::from(
+                "This system does not appear to be connected to a network",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use masq_lib::test_utils::fake_stream_holder::FakeStreamHolder;
+    use std::cell::RefCell;
+    use std::env;
+    use std::os::unix::fs::symlink;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct ResolvedBusMock {
+        restart_params: Arc<Mutex<Vec<()>>>,
+        restart_results: RefCell<Vec<Result<(), String>>>,
+        dns_servers_results: RefCell<Vec<Result<Vec<String>, String>>>,
+    }
+
+    impl ResolvedBus for ResolvedBusMock {
+        fn restart(&self) -> Result<(), String> {
+            self.restart_params.lock().unwrap().push(());
+            self.restart_results.borrow_mut().remove(0)
+        }
+
+        fn dns_servers(&self) -> Result<Vec<String>, String> {
+            self.dns_servers_results.borrow_mut().remove(0)
+        }
+    }
+
+    impl ResolvedBusMock {
+        fn restart_params(mut self, params: &Arc<Mutex<Vec<()>>>) -> Self {
+            self.restart_params = params.clone();
+            self
+        }
+
+        fn restart_result(self, result: Result<(), String>) -> Self {
+            self.restart_results.borrow_mut().push(result);
+            self
+        }
+
+        fn dns_servers_result(self, result: Result<Vec<String>, String>) -> Self {
+            self.dns_servers_results.borrow_mut().push(result);
+            self
+        }
+    }
+
+    #[test]
+    fn instance_knows_its_type_name() {
+        let subject = SystemdResolvedDnsModifier::new();
+
+        let result = subject.type_name();
+
+        assert_eq!(result, "SystemdResolvedDnsModifier");
+    }
+
+    #[test]
+    fn parse_resolvectl_dns_collects_distinct_servers() {
+        let output = "Global: 1.1.1.1\nLink 2 (eth0): 192.168.0.1 fe80::1%2\nLink 3 (wlan0):\nLink 4 (wg0): 192.168.0.1\n";
+
+        let result = parse_resolvectl_dns(output);
+
+        assert_eq!(
+            result,
+            vec![
+                "1.1.1.1".to_string(),
+                "192.168.0.1".to_string(),
+                "fe80::1%2".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn manages_dns_recognizes_resolv_conf_symlinked_to_systemd_resolved() {
+        let root = make_root("manages_dns_recognizes_resolv_conf_symlinked_to_systemd_resolved");
+        fs::create_dir_all(root.join("etc")).unwrap();
+        symlink(
+            "../run/systemd/resolve/stub-resolv.conf",
+            root.join("etc").join("resolv.conf"),
+        )
+        .unwrap();
+
+        let result = SystemdResolvedDnsModifier::manages_dns(&root);
+
+        assert_eq!(result, true);
+    }
+
+    #[test]
+    fn manages_dns_rejects_plain_resolv_conf() {
+        let root = make_root("manages_dns_rejects_plain_resolv_conf");
+        fs::create_dir_all(root.join("etc")).unwrap();
+        fs::write(root.join("etc").join("resolv.conf"), "nameserver 8.8.8.8\n").unwrap();
+
+        let result = SystemdResolvedDnsModifier::manages_dns(&root);
+
+        assert_eq!(result, false);
+    }
+
+    #[test]
+    fn subvert_writes_drop_in_and_restarts_resolved() {
+        let root = make_root("subvert_writes_drop_in_and_restarts_resolved");
+        let restart_params_arc = Arc::new(Mutex::new(vec![]));
+        let bus = ResolvedBusMock::default()
+            .dns_servers_result(Ok(vec!["192.168.0.1".to_string()]))
+            .restart_params(&restart_params_arc)
+            .restart_result(Ok(()));
+        let subject = make_subject(&root, bus);
+
+        let result = subject.subvert();
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(get_drop_in(&root), DROP_IN_CONTENTS.to_string());
+        assert_eq!(restart_params_arc.lock().unwrap().len(), 1);
+        let journal = JournalStore::new(&root).read().unwrap().unwrap();
+        assert_eq!(
+            journal.modifier_type,
+            "SystemdResolvedDnsModifier".to_string()
+        );
+        assert_eq!(journal.original_contents, String::new());
+        assert_eq!(journal.matches_subverted(&get_drop_in(&root)), true);
+    }
+
+    #[test]
+    fn subvert_does_not_write_drop_in_if_journal_cannot_be_written() {
+        let root = make_root("subvert_does_not_write_drop_in_if_journal_cannot_be_written");
+        fs::create_dir_all(root.join("var").join("lib")).unwrap();
+        fs::write(root.join("var").join("lib").join("masq"), "not a directory").unwrap();
+        let bus = ResolvedBusMock::default().dns_servers_result(Ok(vec!["1.1.1.1".to_string()]));
+        let subject = make_subject(&root, bus);
+
+        let result = subject.subvert();
+
+        let msg = result.err().unwrap();
+        assert!(
+            msg.starts_with("/var/lib/masq/dns_utility/revert-journal could not be written: "),
+            "{}",
+            msg
+        );
+        assert_eq!(subject.drop_in_path().exists(), false);
+    }
+
+    #[test]
+    fn subvert_backs_off_if_dns_is_already_subverted() {
+        let root = make_root("subvert_backs_off_if_dns_is_already_subverted");
+        make_drop_in(&root, DROP_IN_CONTENTS);
+        let subject = make_subject(&root, ResolvedBusMock::default());
+
+        let result = subject.subvert();
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(get_drop_in(&root), DROP_IN_CONTENTS.to_string());
+        assert_eq!(JournalStore::new(&root).read(), Ok(None));
+    }
+
+    #[test]
+    fn subvert_complains_if_system_is_not_connected() {
+        let root = make_root("subvert_complains_if_system_is_not_connected");
+        let bus = ResolvedBusMock::default().dns_servers_result(Ok(vec![]));
+        let subject = make_subject(&root, bus);
+
+        let result = subject.subvert();
+
+        assert_eq!(
+            result,
+            Err("This system does not appear to be connected to a network".to_string())
+        );
+        assert_eq!(subject.drop_in_path().exists(), false);
+    }
+
+    #[test]
+    fn subvert_complains_if_drop_in_cannot_be_written() {
+        let root = make_root("subvert_complains_if_drop_in_cannot_be_written");
+        fs::create_dir_all(root.join("etc").join("systemd")).unwrap();
+        fs::write(root.join(DROP_IN_DIR), "not a directory").unwrap();
+        let bus = ResolvedBusMock::default().dns_servers_result(Ok(vec!["1.1.1.1".to_string()]));
+        let subject = make_subject(&root, bus);
+
+        let result = subject.subvert();
+
+        let msg = result.err().unwrap();
+        assert!(
+            msg.starts_with("/etc/systemd/resolved.conf.d/masq-dns.conf could not be written: "),
+            "{}",
+            msg
+        );
+    }
+
+    #[test]
+    fn subvert_reports_failure_to_restart_resolved() {
+        let root = make_root("subvert_reports_failure_to_restart_resolved");
+        let bus = ResolvedBusMock::default()
+            .dns_servers_result(Ok(vec!["1.1.1.1".to_string()]))
+            .restart_result(Err(
+                "'systemctl restart systemd-resolved' failed: booga".to_string()
+            ));
+        let subject = make_subject(&root, bus);
+
+        let result = subject.subvert();
+
+        assert_eq!(
+            result,
+            Err("'systemctl restart systemd-resolved' failed: booga".to_string())
+        );
+    }
+
+    #[test]
+    fn revert_removes_drop_in_and_journal_and_restarts_resolved() {
+        let root = make_root("revert_removes_drop_in_and_journal_and_restarts_resolved");
+        let restart_params_arc = Arc::new(Mutex::new(vec![]));
+        let bus = ResolvedBusMock::default()
+            .dns_servers_result(Ok(vec!["1.1.1.1".to_string()]))
+            .restart_params(&restart_params_arc)
+            .restart_result(Ok(()))
+            .restart_result(Ok(()));
+        let subject = make_subject(&root, bus);
+        subject.subvert().unwrap();
+
+        let result = subject.revert();
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(subject.drop_in_path().exists(), false);
+        assert_eq!(JournalStore::new(&root).read(), Ok(None));
+        assert_eq!(restart_params_arc.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn revert_removes_drop_in_left_by_a_dns_utility_that_kept_no_journal() {
+        let root = make_root("revert_removes_drop_in_left_by_a_dns_utility_that_kept_no_journal");
+        make_drop_in(&root, DROP_IN_CONTENTS);
+        let restart_params_arc = Arc::new(Mutex::new(vec![]));
+        let bus = ResolvedBusMock::default()
+            .restart_params(&restart_params_arc)
+            .restart_result(Ok(()));
+        let subject = make_subject(&root, bus);
+
+        let result = subject.revert();
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(subject.drop_in_path().exists(), false);
+        assert_eq!(restart_params_arc.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn revert_removes_journal_even_if_drop_in_is_already_gone() {
+        let root = make_root("revert_removes_journal_even_if_drop_in_is_already_gone");
+        JournalStore::new(&root)
+            .write(&RevertJournal::new(
+                "SystemdResolvedDnsModifier",
+                "",
+                DROP_IN_CONTENTS,
+            ))
+            .unwrap();
+        let subject = make_subject(&root, ResolvedBusMock::default());
+
+        let result = subject.revert();
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(JournalStore::new(&root).read(), Ok(None));
+    }
+
+    #[test]
+    fn revert_refuses_journal_written_by_another_modifier() {
+        let root = make_root("revert_refuses_journal_written_by_another_modifier");
+        make_drop_in(&root, DROP_IN_CONTENTS);
+        JournalStore::new(&root)
+            .write(&RevertJournal::new(
+                "ResolvConfDnsModifier",
+                "nameserver 8.8.8.8\n",
+                "",
+            ))
+            .unwrap();
+        let subject = make_subject(&root, ResolvedBusMock::default());
+
+        let result = subject.revert();
+
+        assert_eq!(
+            result,
+            Err("/var/lib/masq/dns_utility/revert-journal was written by ResolvConfDnsModifier, not SystemdResolvedDnsModifier".to_string())
+        );
+        assert_eq!(get_drop_in(&root), DROP_IN_CONTENTS.to_string());
+    }
+
+    #[test]
+    fn revert_backs_off_if_dns_is_not_subverted() {
+        let root = make_root("revert_backs_off_if_dns_is_not_subverted");
+        let subject = make_subject(&root, ResolvedBusMock::default());
+
+        let result = subject.revert();
+
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn inspect_reports_drop_in_server_when_subverted() {
+        let root = make_root("inspect_reports_drop_in_server_when_subverted");
+        make_drop_in(&root, DROP_IN_CONTENTS);
+        let mut stream_holder = FakeStreamHolder::new();
+        let subject = make_subject(&root, ResolvedBusMock::default());
+
+        let result = subject.inspect(stream_holder.streams().stdout);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(stream_holder.stdout.get_string(), "127.0.0.1\n".to_string());
+    }
+
+    #[test]
+    fn inspect_reports_servers_known_to_resolved_when_not_subverted() {
+        let root = make_root("inspect_reports_servers_known_to_resolved_when_not_subverted");
+        let mut stream_holder = FakeStreamHolder::new();
+        let bus = ResolvedBusMock::default()
+            .dns_servers_result(Ok(vec!["8.8.8.8".to_string(), "9.9.9.9".to_string()]));
+        let subject = make_subject(&root, bus);
+
+        let result = subject.inspect(stream_holder.streams().stdout);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            stream_holder.stdout.get_string(),
+            "8.8.8.8\n9.9.9.9\n".to_string()
+        );
+    }
+
+    #[test]
+    fn inspect_complains_if_system_is_not_connected() {
+        let root = make_root("inspect_complains_if_system_is_not_connected");
+        let mut stream_holder = FakeStreamHolder::new();
+        let bus = ResolvedBusMock::default().dns_servers_result(Ok(vec![]));
+        let subject = make_subject(&root, bus);
+
+        let result = subject.inspect(stream_holder.streams().stdout);
+
+        assert_eq!(
+            result,
+            Err("This system does not appear to be connected to a network".to_string())
+        );
+        assert_eq!(stream_holder.stdout.get_string(), String::new());
+    }
+
+    #[test]
+    fn verbose_status_reports_missing_journal() {
+        let root = make_root("verbose_status_reports_missing_journal");
+        let mut stream_holder = FakeStreamHolder::new();
+        let subject = make_subject(&root, ResolvedBusMock::default());
+
+        let result = subject.verbose_status(stream_holder.streams().stdout);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            stream_holder.stdout.get_string(),
+            "Revert journal: none\n".to_string()
+        );
+    }
+
+    #[test]
+    fn verbose_status_reports_undrifted_subversion() {
+        let root = make_root("verbose_status_reports_undrifted_subversion");
+        let mut stream_holder = FakeStreamHolder::new();
+        let bus = ResolvedBusMock::default()
+            .dns_servers_result(Ok(vec!["1.1.1.1".to_string()]))
+            .restart_result(Ok(()));
+        let subject = make_subject(&root, bus);
+        subject.subvert().unwrap();
+        let journal = JournalStore::new(&root).read().unwrap().unwrap();
+
+        let result = subject.verbose_status(stream_holder.streams().stdout);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            stream_holder.stdout.get_string(),
+            format!(
+                "Revert journal: /var/lib/masq/dns_utility/revert-journal (version 1), written by SystemdResolvedDnsModifier at {} seconds after the epoch\nDrift: none; /etc/systemd/resolved.conf.d/masq-dns.conf is as dns_utility left it\n",
+                journal.timestamp
+            )
+        );
+    }
+
+    #[test]
+    fn verbose_status_reports_changed_drop_in() {
+        let root = make_root("verbose_status_reports_changed_drop_in");
+        let mut stream_holder = FakeStreamHolder::new();
+        let bus = ResolvedBusMock::default()
+            .dns_servers_result(Ok(vec!["1.1.1.1".to_string()]))
+            .restart_result(Ok(()));
+        let subject = make_subject(&root, bus);
+        subject.subvert().unwrap();
+        make_drop_in(&root, "[Resolve]\nDNS=127.0.0.1 9.9.9.9\n");
+
+        let result = subject.verbose_status(stream_holder.streams().stdout);
+
+        assert_eq!(result, Ok(()));
+        assert!(stream_holder.stdout.get_string().ends_with(
+            "\nDrift: /etc/systemd/resolved.conf.d/masq-dns.conf has changed since it was subverted; revert will remove it\n"
+        ));
+    }
+
+    #[test]
+    fn verbose_status_reports_removed_drop_in() {
+        let root = make_root("verbose_status_reports_removed_drop_in");
+        let mut stream_holder = FakeStreamHolder::new();
+        let bus = ResolvedBusMock::default()
+            .dns_servers_result(Ok(vec!["1.1.1.1".to_string()]))
+            .restart_result(Ok(()));
+        let subject = make_subject(&root, bus);
+        subject.subvert().unwrap();
+        fs::remove_file(subject.drop_in_path()).unwrap();
+
+        let result = subject.verbose_status(stream_holder.streams().stdout);
+
+        assert_eq!(result, Ok(()));
+        assert!(stream_holder.stdout.get_string().ends_with(
+            "\nDrift: /etc/systemd/resolved.conf.d/masq-dns.conf has been removed; the original settings are already in effect\n"
+        ));
+    }
+
+    fn make_subject(root: &Path, bus: ResolvedBusMock) -> SystemdResolvedDnsModifier {
+        SystemdResolvedDnsModifier {
+            root: root.to_path_buf(),
+            bus: Box::new(bus),
+        }
+    }
+
+    fn make_root(test_name: &str) -> PathBuf {
+        let cur_dir = env::current_dir().unwrap();
+        let generated_dir = cur_dir.join(Path::new("generated"));
+        let suite_dir = generated_dir.join(Path::new("SystemdResolvedDnsModifier"));
+        let base_dir = suite_dir.join(Path::new(test_name));
+        let _ = fs::remove_dir_all(base_dir.clone()); // don't care if it doesn't exist
+        fs::create_dir_all(base_dir.clone()).unwrap();
+        base_dir
+    }
+
+    fn make_drop_in(root: &Path, contents: &str) {
+        let dir = root.join(DROP_IN_DIR);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(DROP_IN_FILE), contents).unwrap();
+    }
+
+    fn get_drop_in(root: &Path) -> String {
+        fs::read_to_string(root.join(DROP_IN_DIR).join(DROP_IN_FILE)).unwrap()
+    }
+}
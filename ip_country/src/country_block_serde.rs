@@ -5,7 +5,7 @@ use crate::countries::Countries;
 use crate::country_block_serde::semi_private_items::{
     DeserializerPrivate, Difference, IPIntoOctets, IPIntoSegments, PlusMinusOneIP,
 };
-use crate::country_block_stream::{CountryBlock, IpRange};
+use crate::country_block_stream::{Country, CountryBlock, IpRange};
 use std::fmt::Debug;
 use std::marker::PhantomData;
 use std::net::{Ipv4Addr, Ipv6Addr};
@@ -452,6 +452,19 @@ impl<'a> Ipv4CountryBlockDeserializer<'a> {
     }
 }
 
+impl<'a> Ipv4CountryBlockDeserializer<'a> {
+    pub fn try_decode(
+        country_data: (Vec<u64>, usize),
+        countries: &'a Countries,
+    ) -> Result<Vec<CountryBlock>, String> {
+        Self::try_decode_generic(
+            country_data,
+            Ipv4Addr::new(0xFF, 0xFF, 0xFF, 0xFE),
+            countries,
+        )
+    }
+}
+
 impl<'a> Iterator for Ipv4CountryBlockDeserializer<'a> {
     type Item = CountryBlock;
 
@@ -474,6 +487,21 @@ impl<'a> Ipv6CountryBlockDeserializer<'a> {
     }
 }
 
+impl<'a> Ipv6CountryBlockDeserializer<'a> {
+    pub fn try_decode(
+        country_data: (Vec<u64>, usize),
+        countries: &'a Countries,
+    ) -> Result<Vec<CountryBlock>, String> {
+        Self::try_decode_generic(
+            country_data,
+            Ipv6Addr::new(
+                0xFFFF, 0xFFFF, 0xFFFF, 0xFFFF, 0xFFFF, 0xFFFF, 0xFFFF, 0xFFFE,
+            ),
+            countries,
+        )
+    }
+}
+
 impl<'a> Iterator for Ipv6CountryBlockDeserializer<'a> {
     type Item = CountryBlock;
 
@@ -545,6 +573,37 @@ where
         }
     }
 
+    // Unlike new_generic() and next_generic(), which trust the compiled-in data, this validates
+    // every record, so that a damaged data file is reported instead of crashing the Node
+    fn try_decode_generic(
+        country_data: (Vec<u64>, usize),
+        previous_start: IPType,
+        countries: &'a Countries,
+    ) -> Result<Vec<CountryBlock>, String> {
+        let (words, bit_count) = &country_data;
+        if *bit_count > words.len() * 64 {
+            return Err(format!(
+                "it claims {} bits of block data but holds only {}",
+                bit_count,
+                words.len() * 64
+            ));
+        }
+        let mut bit_queue = bit_queue_from_country_data(country_data);
+        let prev_record = Self::get_record_generic(&mut bit_queue, previous_start)
+            .ok_or_else(|| "it holds no blocks".to_string())?;
+        let mut deserializer = Self {
+            prev_record,
+            bit_queue,
+            empty: false,
+            countries,
+        };
+        let mut blocks = vec![];
+        while let Some(block_result) = deserializer.try_next_generic() {
+            blocks.push(block_result?);
+        }
+        Ok(blocks)
+    }
+
     fn get_record_generic(
         bit_queue: &mut BitQueue,
         prev_start: IPType,
@@ -564,9 +623,21 @@ where
     }
 
     fn next_generic(&mut self) -> Option<CountryBlock> {
+        self.try_next_generic()
+            .map(|block_result| block_result.expect("Country not found"))
+    }
+
+    fn try_next_generic(&mut self) -> Option<Result<CountryBlock, String>> {
         if self.empty {
             return None;
         }
+        let country = match self.prev_record_country() {
+            Ok(country) => country,
+            Err(e) => {
+                self.empty = true;
+                return Some(Err(e));
+            }
+        };
         let next_record_opt =
             Self::get_record_generic(&mut self.bit_queue, self.prev_record.start.ip);
         match next_record_opt {
@@ -576,29 +647,29 @@ where
                         self.prev_record.start.ip,
                         IPType::minus_one_ip(next_record.start.ip),
                     )),
-                    country: self
-                        .countries
-                        .country_from_index(self.prev_record.country_idx)
-                        .expect("Country not found")
-                        .clone(),
+                    country,
                 };
                 self.prev_record = next_record;
-                Some(prev_block)
+                Some(Ok(prev_block))
             }
             None => {
                 self.empty = true;
-                Some(CountryBlock {
+                Some(Ok(CountryBlock {
                     ip_range: IpRange::from((self.prev_record.start.ip, Self::max_ip_value())),
-                    country: self
-                        .countries
-                        .country_from_index(self.prev_record.country_idx)
-                        .expect("Country not found")
-                        .clone(),
-                })
+                    country,
+                }))
             }
         }
     }
 
+    fn prev_record_country(&self) -> Result<Country, String> {
+        let country_idx = self.prev_record.country_idx;
+        self.countries
+            .country_from_index(country_idx)
+            .map(|country| country.clone())
+            .map_err(|_| format!("it refers to nonexistent country index {}", country_idx))
+    }
+
     fn read_differences_generic(
         bit_queue: &mut BitQueue,
         difference_count: usize,
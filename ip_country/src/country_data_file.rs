@@ -0,0 +1,394 @@
+// Copyright (c) 2024, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::checksum::fnv1a_64;
+use crate::countries::Countries;
+use crate::country_block_serde::FinalBitQueue;
+use crate::country_block_stream::Country;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+pub const COUNTRY_DATA_FILE_NAME: &str = "ip_country.dat";
+pub const COUNTRY_DATA_FORMAT_VERSION: u16 = 1;
+const MAGIC: &[u8; 8] = b"MASQIPCD";
+
+/*
+
+Data File Format
+
+The data file carries the same compressed country-block data that generate_rust_code() writes into
+dbip_country.rs, so that a Node can pick up fresh geolocation data without being rebuilt. All
+integers are big-endian.
+
+<magic: the eight bytes "MASQIPCD">
+<format version: u16>
+<database version: u16 length, then that many bytes of UTF-8>
+<country count: u16>
+<for each country, in index order: u16 length + UTF-8 ISO3166 code, u16 length + UTF-8 name>
+<IPv4 block count: u64> <IPv4 bit count: u64> <ceil(bit count / 64) u64 words of IPv4 data>
+<IPv6 block count: u64> <IPv6 bit count: u64> <ceil(bit count / 64) u64 words of IPv6 data>
+<checksum: u64 64-bit FNV-1a of every byte before it>
+
+The first country must be the "ZZ" sentinel, because the deserializer assigns index 0 to the
+addresses the data doesn't cover.
+
+*/
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CountryData {
+    pub data: (Vec<u64>, usize),
+    pub block_count: usize,
+}
+
+impl From<FinalBitQueue> for CountryData {
+    fn from(mut final_bit_queue: FinalBitQueue) -> Self {
+        let bit_count = final_bit_queue.bit_queue.len();
+        let mut words = vec![];
+        while !final_bit_queue.bit_queue.is_empty() {
+            let chunk_size = final_bit_queue.bit_queue.len().min(64);
+            words.push(
+                final_bit_queue
+                    .bit_queue
+                    .take_bits(chunk_size)
+                    .expect("There should be bits left!"),
+            );
+        }
+        Self {
+            data: (words, bit_count),
+            block_count: final_bit_queue.block_count,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CountryDataFile {
+    pub version: String,
+    pub countries: Countries,
+    pub ipv4: CountryData,
+    pub ipv6: CountryData,
+}
+
+impl CountryDataFile {
+    pub fn new(
+        version: &str,
+        countries: Countries,
+        final_ipv4: FinalBitQueue,
+        final_ipv6: FinalBitQueue,
+    ) -> Self {
+        Self {
+            version: version.to_string(),
+            countries,
+            ipv4: CountryData::from(final_ipv4),
+            ipv6: CountryData::from(final_ipv6),
+        }
+    }
+
+    pub fn read(path: &Path) -> Result<Option<Self>, String> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(ref e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("{} could not be read: {}", path.display(), e)),
+        };
+        match Self::from_bytes(&bytes) {
+            Ok(data_file) => Ok(Some(data_file)),
+            Err(e) => Err(format!(
+                "{} is not a usable country database: {}",
+                path.display(),
+                e
+            )),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(COUNTRY_DATA_FORMAT_VERSION.to_be_bytes());
+        write_string(&mut bytes, &self.version);
+        bytes.extend((self.countries.len() as u16).to_be_bytes());
+        self.countries.iter().for_each(|country| {
+            write_string(&mut bytes, &country.iso3166);
+            write_string(&mut bytes, &country.name);
+        });
+        write_country_data(&mut bytes, &self.ipv4);
+        write_country_data(&mut bytes, &self.ipv6);
+        let checksum = fnv1a_64(&bytes);
+        bytes.extend(checksum.to_be_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < MAGIC.len() + 8 || &bytes[0..MAGIC.len()] != MAGIC {
+            return Err("it doesn't start with the country-database signature".to_string());
+        }
+        let (body, checksum_bytes) = bytes.split_at(bytes.len() - 8);
+        let mut reader = ByteReader::new(checksum_bytes);
+        if reader.u64()? != fnv1a_64(body) {
+            return Err("its checksum doesn't match its contents".to_string());
+        }
+        let mut reader = ByteReader::new(&body[MAGIC.len()..]);
+        match reader.u16()? {
+            COUNTRY_DATA_FORMAT_VERSION => (),
+            format_version => {
+                return Err(format!(
+                    "format version {} is not supported",
+                    format_version
+                ))
+            }
+        }
+        let version = reader.string()?;
+        let country_count = reader.u16()? as usize;
+        let countries = (0..country_count)
+            .map(|index| {
+                let iso3166 = reader.string()?;
+                let name = reader.string()?;
+                Ok(Country::new(index, &iso3166, &name))
+            })
+            .collect::<Result<Vec<Country>, String>>()?;
+        match countries.first() {
+            Some(country) if country.iso3166 == "ZZ" => (),
+            _ => return Err("its first country is not the ZZ sentinel".to_string()),
+        }
+        let ipv4 = reader.country_data()?;
+        let ipv6 = reader.country_data()?;
+        if !reader.is_exhausted() {
+            return Err("it has extra data after the IPv6 blocks".to_string());
+        }
+        Ok(Self {
+            version,
+            countries: Countries::from(countries),
+            ipv4,
+            ipv6,
+        })
+    }
+}
+
+fn write_string(bytes: &mut Vec<u8>, string: &str) {
+    bytes.extend((string.len() as u16).to_be_bytes());
+    bytes.extend(string.as_bytes());
+}
+
+fn write_country_data(bytes: &mut Vec<u8>, country_data: &CountryData) {
+    bytes.extend((country_data.block_count as u64).to_be_bytes());
+    bytes.extend((country_data.data.1 as u64).to_be_bytes());
+    country_data
+        .data
+        .0
+        .iter()
+        .for_each(|word| bytes.extend(word.to_be_bytes()));
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0 }
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'a [u8], String> {
+        if self.bytes.len() - self.offset < count {
+            return Err("it is truncated".to_string());
+        }
+        let slice = &self.bytes[self.offset..(self.offset + count)];
+        self.offset += count;
+        Ok(slice)
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let mut buf = [0u8; 2];
+        buf.copy_from_slice(self.take(2)?);
+        Ok(u16::from_be_bytes(buf))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(self.take(8)?);
+        Ok(u64::from_be_bytes(buf))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let len = self.u16()? as usize;
+        String::from_utf8(self.take(len)?.to_vec())
+            .map_err(|_| "it contains a string that isn't UTF-8".to_string())
+    }
+
+    fn country_data(&mut self) -> Result<CountryData, String> {
+        let block_count = self.u64()? as usize;
+        let bit_count = self.u64()? as usize;
+        let word_count = bit_count / 64 + usize::from(bit_count % 64 != 0);
+        let words = (0..word_count)
+            .map(|_| self.u64())
+            .collect::<Result<Vec<u64>, String>>()?;
+        Ok(CountryData {
+            data: (words, bit_count),
+            block_count,
+        })
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.offset == self.bytes.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit_queue::BitQueue;
+    use crate::dbip_country;
+    use std::env;
+    use std::path::PathBuf;
+
+    #[test]
+    fn constants_have_correct_values() {
+        assert_eq!(COUNTRY_DATA_FILE_NAME, "ip_country.dat");
+        assert_eq!(COUNTRY_DATA_FORMAT_VERSION, 1);
+        assert_eq!(MAGIC, b"MASQIPCD");
+    }
+
+    #[test]
+    fn country_data_is_made_from_final_bit_queue() {
+        let mut bit_queue = BitQueue::new();
+        bit_queue.add_bits(0x1122334455667788, 64);
+        bit_queue.add_bits(0x1F, 5);
+        let final_bit_queue = FinalBitQueue {
+            bit_queue,
+            block_count: 3,
+        };
+
+        let result = CountryData::from(final_bit_queue);
+
+        assert_eq!(
+            result,
+            CountryData {
+                data: (vec![0x1122334455667788, 0x1F], 69),
+                block_count: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn to_bytes_and_from_bytes_are_inverses() {
+        let subject = compiled_in_data_file("2024-06");
+
+        let result = CountryDataFile::from_bytes(&subject.to_bytes());
+
+        assert_eq!(result, Ok(subject));
+    }
+
+    #[test]
+    fn from_bytes_rejects_wrong_signature() {
+        let mut bytes = compiled_in_data_file("2024-06").to_bytes();
+        bytes[0] = b'X';
+
+        let result = CountryDataFile::from_bytes(&bytes);
+
+        assert_eq!(
+            result,
+            Err("it doesn't start with the country-database signature".to_string())
+        );
+    }
+
+    #[test]
+    fn from_bytes_rejects_tampered_data() {
+        let mut bytes = compiled_in_data_file("2024-06").to_bytes();
+        let middle = bytes.len() / 2;
+        bytes[middle] ^= 0x01;
+
+        let result = CountryDataFile::from_bytes(&bytes);
+
+        assert_eq!(
+            result,
+            Err("its checksum doesn't match its contents".to_string())
+        );
+    }
+
+    #[test]
+    fn from_bytes_rejects_unknown_format_version() {
+        let bytes = compiled_in_data_file("2024-06").to_bytes();
+        let mut body = bytes[0..(bytes.len() - 8)].to_vec();
+        body[9] = 2;
+
+        let result = CountryDataFile::from_bytes(&with_checksum(body));
+
+        assert_eq!(result, Err("format version 2 is not supported".to_string()));
+    }
+
+    #[test]
+    fn from_bytes_rejects_truncated_data() {
+        let bytes = compiled_in_data_file("2024-06").to_bytes();
+        let body = bytes[0..(bytes.len() - 16)].to_vec();
+
+        let result = CountryDataFile::from_bytes(&with_checksum(body));
+
+        assert_eq!(result, Err("it is truncated".to_string()));
+    }
+
+    #[test]
+    fn from_bytes_rejects_data_without_sentinel() {
+        let mut subject = compiled_in_data_file("2024-06");
+        subject.countries = Countries::from(vec![Country::new(0, "AD", "Andorra")]);
+
+        let result = CountryDataFile::from_bytes(&subject.to_bytes());
+
+        assert_eq!(
+            result,
+            Err("its first country is not the ZZ sentinel".to_string())
+        );
+    }
+
+    #[test]
+    fn read_handles_missing_good_and_bad_files() {
+        let dir = make_dir("read_handles_missing_good_and_bad_files");
+        let path = dir.join(COUNTRY_DATA_FILE_NAME);
+        let data_file = compiled_in_data_file("2024-06");
+
+        let missing_result = CountryDataFile::read(&path);
+        fs::write(&path, data_file.to_bytes()).unwrap();
+        let good_result = CountryDataFile::read(&path);
+        fs::write(&path, b"booga").unwrap();
+        let bad_result = CountryDataFile::read(&path);
+
+        assert_eq!(missing_result, Ok(None));
+        assert_eq!(good_result, Ok(Some(data_file)));
+        assert_eq!(
+            bad_result,
+            Err(format!(
+                "{} is not a usable country database: it doesn't start with the country-database signature",
+                path.display()
+            ))
+        );
+    }
+
+    fn compiled_in_data_file(version: &str) -> CountryDataFile {
+        CountryDataFile {
+            version: version.to_string(),
+            countries: dbip_country::COUNTRIES.clone(),
+            ipv4: CountryData {
+                data: dbip_country::ipv4_country_data(),
+                block_count: dbip_country::ipv4_country_block_count(),
+            },
+            ipv6: CountryData {
+                data: dbip_country::ipv6_country_data(),
+                block_count: dbip_country::ipv6_country_block_count(),
+            },
+        }
+    }
+
+    fn with_checksum(mut body: Vec<u8>) -> Vec<u8> {
+        let checksum = fnv1a_64(&body);
+        body.extend(checksum.to_be_bytes());
+        body
+    }
+
+    fn make_dir(test_name: &str) -> PathBuf {
+        let dir = env::current_dir()
+            .unwrap()
+            .join("generated")
+            .join("CountryDataFile")
+            .join(test_name);
+        let _ = fs::remove_dir_all(&dir); // don't care if it doesn't exist
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+}
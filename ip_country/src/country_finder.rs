@@ -1,12 +1,17 @@
 // Copyright (c) 2024, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 
 use crate::countries::Countries;
-use crate::country_block_serde::CountryBlockDeserializer;
+use crate::country_block_serde::{
+    CountryBlockDeserializer, Ipv4CountryBlockDeserializer, Ipv6CountryBlockDeserializer,
+};
 use crate::country_block_stream::{Country, CountryBlock};
+use crate::country_data_file::CountryDataFile;
 use crate::dbip_country;
 use itertools::Itertools;
 use lazy_static::lazy_static;
+use std::borrow::Cow;
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::{Arc, RwLock};
 
 lazy_static! {
     pub static ref COUNTRY_CODE_FINDER: CountryCodeFinder<'static> = CountryCodeFinder::new(
@@ -14,10 +19,80 @@ lazy_static! {
         dbip_country::ipv4_country_data(),
         dbip_country::ipv6_country_data()
     );
+    // Country data loaded from a data file at runtime; when absent, COUNTRY_CODE_FINDER is used
+    static ref LOADED_COUNTRY_DATA: RwLock<Option<Arc<LoadedCountryData>>> = RwLock::new(None);
+}
+
+struct LoadedCountryData {
+    version: String,
+    finder: CountryCodeFinder<'static>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ActiveCountryData {
+    // None means the data compiled into this binary is in use
+    pub version_opt: Option<String>,
+    pub country_count: usize,
+    pub ipv4_block_count: usize,
+    pub ipv6_block_count: usize,
+}
+
+impl ActiveCountryData {
+    fn new(version_opt: Option<String>, finder: &CountryCodeFinder) -> Self {
+        Self {
+            version_opt,
+            country_count: finder.countries.len(),
+            ipv4_block_count: finder.ipv4.len(),
+            ipv6_block_count: finder.ipv6.len(),
+        }
+    }
+}
+
+pub fn find_country_code(ip_addr: IpAddr) -> Option<String> {
+    let loaded_opt = read_loaded_country_data();
+    let finder = match loaded_opt.as_ref() {
+        Some(loaded) => &loaded.finder,
+        None => &*COUNTRY_CODE_FINDER,
+    };
+    finder
+        .find_country(ip_addr)
+        .map(|country| country.iso3166.clone())
+}
+
+pub fn install_country_data(data_file: CountryDataFile) -> Result<ActiveCountryData, String> {
+    let version = data_file.version.clone();
+    let finder = CountryCodeFinder::from_data_file(data_file)?;
+    let active = ActiveCountryData::new(Some(version.clone()), &finder);
+    *LOADED_COUNTRY_DATA
+        .write()
+        .expect("Country data lock is poisoned") =
+        Some(Arc::new(LoadedCountryData { version, finder }));
+    Ok(active)
+}
+
+pub fn uninstall_country_data() -> ActiveCountryData {
+    *LOADED_COUNTRY_DATA
+        .write()
+        .expect("Country data lock is poisoned") = None;
+    active_country_data()
+}
+
+pub fn active_country_data() -> ActiveCountryData {
+    match read_loaded_country_data() {
+        Some(loaded) => ActiveCountryData::new(Some(loaded.version.clone()), &loaded.finder),
+        None => ActiveCountryData::new(None, &COUNTRY_CODE_FINDER),
+    }
+}
+
+fn read_loaded_country_data() -> Option<Arc<LoadedCountryData>> {
+    LOADED_COUNTRY_DATA
+        .read()
+        .expect("Country data lock is poisoned")
+        .clone()
 }
 
 pub struct CountryCodeFinder<'a> {
-    pub countries: &'a Countries,
+    pub countries: Cow<'a, Countries>,
     pub ipv4: Vec<CountryBlock>,
     pub ipv6: Vec<CountryBlock>,
 }
@@ -29,7 +104,7 @@ impl<'a> CountryCodeFinder<'a> {
         ipv6_data: (Vec<u64>, usize),
     ) -> Self {
         Self {
-            countries,
+            countries: Cow::Borrowed(countries),
             ipv4: CountryBlockDeserializer::<Ipv4Addr, u8, 4>::new(ipv4_data, countries)
                 .into_iter()
                 .collect_vec(),
@@ -39,6 +114,36 @@ impl<'a> CountryCodeFinder<'a> {
         }
     }
 
+    pub fn from_data_file(
+        data_file: CountryDataFile,
+    ) -> Result<CountryCodeFinder<'static>, String> {
+        let ipv4 =
+            Ipv4CountryBlockDeserializer::try_decode(data_file.ipv4.data, &data_file.countries)
+                .map_err(|e| format!("its IPv4 data is damaged: {}", e))?;
+        let ipv6 =
+            Ipv6CountryBlockDeserializer::try_decode(data_file.ipv6.data, &data_file.countries)
+                .map_err(|e| format!("its IPv6 data is damaged: {}", e))?;
+        if ipv4.len() != data_file.ipv4.block_count {
+            return Err(format!(
+                "its IPv4 data holds {} blocks instead of {}",
+                ipv4.len(),
+                data_file.ipv4.block_count
+            ));
+        }
+        if ipv6.len() != data_file.ipv6.block_count {
+            return Err(format!(
+                "its IPv6 data holds {} blocks instead of {}",
+                ipv6.len(),
+                data_file.ipv6.block_count
+            ));
+        }
+        Ok(CountryCodeFinder {
+            countries: Cow::Owned(data_file.countries),
+            ipv4,
+            ipv6,
+        })
+    }
+
     pub fn find_country(&'a self, ip_addr: IpAddr) -> Option<&'a Country> {
         let country_blocks: &[CountryBlock] = match ip_addr {
             IpAddr::V4(_) => self.ipv4.as_slice(),
@@ -67,8 +172,8 @@ impl<'a> CountryCodeFinder<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::country_block_serde::{Ipv4CountryBlockDeserializer, Ipv6CountryBlockDeserializer};
     use crate::country_block_stream::IpRange;
+    use crate::country_data_file::CountryData;
     use crate::dbip_country;
     use std::str::FromStr;
     use std::time::SystemTime;
@@ -288,6 +393,131 @@ mod tests {
         );
     }
 
+    #[test]
+    fn finder_from_data_file_matches_compiled_in_finder() {
+        let result = CountryCodeFinder::from_data_file(compiled_in_data_file("2024-06")).unwrap();
+
+        assert_eq!(*result.countries, *COUNTRY_CODE_FINDER.countries);
+        assert_eq!(result.ipv4, COUNTRY_CODE_FINDER.ipv4);
+        assert_eq!(result.ipv6, COUNTRY_CODE_FINDER.ipv6);
+    }
+
+    #[test]
+    fn finder_from_data_file_rejects_wrong_block_counts() {
+        let mut ipv4_data_file = compiled_in_data_file("2024-06");
+        ipv4_data_file.ipv4.block_count += 1;
+        let mut ipv6_data_file = compiled_in_data_file("2024-06");
+        ipv6_data_file.ipv6.block_count -= 1;
+
+        let ipv4_result = CountryCodeFinder::from_data_file(ipv4_data_file);
+        let ipv6_result = CountryCodeFinder::from_data_file(ipv6_data_file);
+
+        assert_eq!(
+            ipv4_result.err(),
+            Some(format!(
+                "its IPv4 data holds {} blocks instead of {}",
+                dbip_country::ipv4_country_block_count(),
+                dbip_country::ipv4_country_block_count() + 1
+            ))
+        );
+        assert_eq!(
+            ipv6_result.err(),
+            Some(format!(
+                "its IPv6 data holds {} blocks instead of {}",
+                dbip_country::ipv6_country_block_count(),
+                dbip_country::ipv6_country_block_count() - 1
+            ))
+        );
+    }
+
+    #[test]
+    fn finder_from_data_file_rejects_damaged_block_data() {
+        let mut empty_data_file = compiled_in_data_file("2024-06");
+        empty_data_file.ipv4.data = (vec![], 0);
+        let mut overclaiming_data_file = compiled_in_data_file("2024-06");
+        overclaiming_data_file.ipv6.data = (vec![0], 65);
+        let mut sentinel_only_data_file = compiled_in_data_file("2024-06");
+        sentinel_only_data_file.countries =
+            Countries::from(vec![Country::new(0, "ZZ", "Sentinel")]);
+
+        let empty_result = CountryCodeFinder::from_data_file(empty_data_file);
+        let overclaiming_result = CountryCodeFinder::from_data_file(overclaiming_data_file);
+        let sentinel_only_result = CountryCodeFinder::from_data_file(sentinel_only_data_file);
+
+        assert_eq!(
+            empty_result.err(),
+            Some("its IPv4 data is damaged: it holds no blocks".to_string())
+        );
+        assert_eq!(
+            overclaiming_result.err(),
+            Some(
+                "its IPv6 data is damaged: it claims 65 bits of block data but holds only 64"
+                    .to_string()
+            )
+        );
+        let sentinel_only_error = sentinel_only_result.err().unwrap();
+        assert!(
+            sentinel_only_error
+                .starts_with("its IPv4 data is damaged: it refers to nonexistent country index "),
+            "{}",
+            sentinel_only_error
+        );
+    }
+
+    // The only test that touches the loaded data, so nothing else sees it change
+    #[test]
+    fn loaded_country_data_replaces_and_yields_to_compiled_in_data() {
+        let ip_addr = match &COUNTRY_CODE_FINDER
+            .ipv4
+            .iter()
+            .find(|block| block.country.iso3166 == "AE")
+            .unwrap()
+            .ip_range
+        {
+            IpRange::V4(start, _) => IpAddr::V4(*start),
+            _ => panic!("Expected IPv4"),
+        };
+        let compiled_in = ActiveCountryData {
+            version_opt: None,
+            country_count: dbip_country::COUNTRIES.len(),
+            ipv4_block_count: dbip_country::ipv4_country_block_count(),
+            ipv6_block_count: dbip_country::ipv6_country_block_count(),
+        };
+        let loaded = ActiveCountryData {
+            version_opt: Some("2024-06".to_string()),
+            ..compiled_in.clone()
+        };
+
+        let before = active_country_data();
+        let install_result = install_country_data(compiled_in_data_file("2024-06"));
+        let during = active_country_data();
+        let found_during = find_country_code(ip_addr);
+        let uninstall_result = uninstall_country_data();
+        let found_after = find_country_code(ip_addr);
+
+        assert_eq!(before, compiled_in);
+        assert_eq!(install_result, Ok(loaded.clone()));
+        assert_eq!(during, loaded);
+        assert_eq!(found_during, Some("AE".to_string()));
+        assert_eq!(uninstall_result, compiled_in);
+        assert_eq!(found_after, Some("AE".to_string()));
+    }
+
+    fn compiled_in_data_file(version: &str) -> CountryDataFile {
+        CountryDataFile {
+            version: version.to_string(),
+            countries: dbip_country::COUNTRIES.clone(),
+            ipv4: CountryData {
+                data: dbip_country::ipv4_country_data(),
+                block_count: dbip_country::ipv4_country_block_count(),
+            },
+            ipv6: CountryData {
+                data: dbip_country::ipv6_country_data(),
+                block_count: dbip_country::ipv6_country_block_count(),
+            },
+        }
+    }
+
     #[test]
     fn check_ipv4_ipv6_country_blocks_length() {
         let country_block_len_ipv4 = COUNTRY_CODE_FINDER.ipv4.len();
@@ -3,6 +3,7 @@
 use crate::bit_queue::BitQueue;
 use crate::countries::Countries;
 use crate::country_block_serde::FinalBitQueue;
+use crate::country_data_file::CountryDataFile;
 use crate::ip_country_csv::CSVParser;
 use crate::ip_country_mmdb::MMDBParser;
 use std::any::Any;
@@ -17,9 +18,21 @@ pub fn ip_country(
     stderr: &mut dyn io::Write,
     parser_factory: &dyn DBIPParserFactory,
 ) -> i32 {
+    let data_file_version_opt = match data_file_version(&args) {
+        Ok(version_opt) => version_opt,
+        Err(msg) => {
+            write!(stderr, "{}", msg).expect("expected error output");
+            return 1;
+        }
+    };
     let parser = parser_factory.make(&args);
     let mut errors: Vec<String> = vec![];
     let (final_ipv4, final_ipv6, countries) = parser.parse(stdin, &mut errors);
+    if let Some(version) = data_file_version_opt {
+        return generate_data_file(
+            &version, final_ipv4, final_ipv6, countries, errors, stdout, stderr,
+        );
+    }
     if let Err(error) = generate_rust_code(final_ipv4, final_ipv6, countries, stdout) {
         errors.push(format!("Error generating Rust code: {:?}", error))
     }
@@ -47,6 +60,44 @@ pub fn ip_country(
     }
 }
 
+// With --data-file <VERSION>, the output is a binary data file the Node can load from its data
+// directory, rather than Rust code to be compiled into it.
+fn data_file_version(args: &[String]) -> Result<Option<String>, String> {
+    match args.iter().position(|arg| arg == "--data-file") {
+        None => Ok(None),
+        Some(index) => match args.get(index + 1) {
+            Some(version) if !version.starts_with("--") => Ok(Some(version.clone())),
+            _ => Err(
+                "--data-file requires a database version, such as --data-file 2024-06".to_string(),
+            ),
+        },
+    }
+}
+
+fn generate_data_file(
+    version: &str,
+    final_ipv4: FinalBitQueue,
+    final_ipv6: FinalBitQueue,
+    countries: Countries,
+    mut errors: Vec<String>,
+    stdout: &mut dyn io::Write,
+    stderr: &mut dyn io::Write,
+) -> i32 {
+    // A data file with errors in it could be loaded by mistake, so none is written at all
+    if errors.is_empty() {
+        let data_file = CountryDataFile::new(version, countries, final_ipv4, final_ipv6);
+        if let Err(error) = stdout.write_all(&data_file.to_bytes()) {
+            errors.push(format!("Error writing data file: {:?}", error))
+        }
+    }
+    if errors.is_empty() {
+        0
+    } else {
+        write!(stderr, "{}", errors.join("\n")).expect("expected error list output");
+        1
+    }
+}
+
 pub trait DBIPParserFactory {
     fn make(&self, args: &[String]) -> Box<dyn DBIPParser>;
 }
@@ -513,6 +564,118 @@ Second error"#
         assert_eq!(stdout_string, "\n            *** DO NOT USE THIS CODE ***\n            It will produce incorrect results.\n            The process that generated it found these errors:\n\nError generating Rust code: Custom { kind: WriteZero, error: \"Bad file Descriptor\" }\n\n            Fix the errors and regenerate the code.\n            *** DO NOT USE THIS CODE ***\n");
     }
 
+    #[test]
+    fn data_file_happy_path_test() {
+        let mut stdin = ByteArrayReader::new(TEST_DATA.as_bytes());
+        let mut stdout = ByteArrayWriter::new();
+        let mut stderr = ByteArrayWriter::new();
+        let ipv4_result = final_bit_queue(0x1122334455667788, 12);
+        let ipv6_result = final_bit_queue(0x8877665544332211, 21);
+        let parser = DBIPParserMock::new().parse_errors(vec![]).parse_result((
+            ipv4_result,
+            ipv6_result,
+            &TEST_COUNTRIES,
+        ));
+        let make_params_arc = Arc::new(Mutex::new(vec![]));
+        let parser_factory = DBIPParserFactoryMock::new()
+            .make_params(&make_params_arc)
+            .make_result(parser);
+        let args = vec![
+            "--csv".to_string(),
+            "--data-file".to_string(),
+            "2024-06".to_string(),
+        ];
+
+        let result = ip_country(
+            args.clone(),
+            &mut stdin,
+            &mut stdout,
+            &mut stderr,
+            &parser_factory,
+        );
+
+        assert_eq!(result, 0);
+        let make_params = make_params_arc.lock().unwrap();
+        assert_eq!(*make_params, vec![args]);
+        let expected_data_file = CountryDataFile::new(
+            "2024-06",
+            TEST_COUNTRIES.clone(),
+            final_bit_queue(0x1122334455667788, 12),
+            final_bit_queue(0x8877665544332211, 21),
+        );
+        assert_eq!(
+            CountryDataFile::from_bytes(&stdout.get_bytes()),
+            Ok(expected_data_file)
+        );
+        assert_eq!(stderr.get_bytes(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn data_file_sad_path_test() {
+        let mut stdin = ByteArrayReader::new(TEST_DATA.as_bytes());
+        let mut stdout = ByteArrayWriter::new();
+        let mut stderr = ByteArrayWriter::new();
+        let ipv4_result = final_bit_queue(0x1122334455667788, 12);
+        let ipv6_result = final_bit_queue(0x8877665544332211, 21);
+        let parser = DBIPParserMock::new()
+            .parse_errors(vec!["First error", "Second error"])
+            .parse_result((ipv4_result, ipv6_result, &TEST_COUNTRIES));
+        let parser_factory = DBIPParserFactoryMock::new().make_result(parser);
+        let args = vec!["--data-file".to_string(), "2024-06".to_string()];
+
+        let result = ip_country(args, &mut stdin, &mut stdout, &mut stderr, &parser_factory);
+
+        assert_eq!(result, 1);
+        assert_eq!(stdout.get_bytes(), Vec::<u8>::new());
+        let stderr_string = String::from_utf8(stderr.get_bytes()).unwrap();
+        assert_eq!(stderr_string, "First error\nSecond error".to_string());
+    }
+
+    #[test]
+    fn data_file_without_version_is_rejected_before_parsing() {
+        let mut stdin = ByteArrayReader::new(TEST_DATA.as_bytes());
+        let mut stdout = ByteArrayWriter::new();
+        let mut stderr = ByteArrayWriter::new();
+        let make_params_arc = Arc::new(Mutex::new(vec![]));
+        let parser_factory = DBIPParserFactoryMock::new().make_params(&make_params_arc);
+        let args = vec!["--data-file".to_string(), "--csv".to_string()];
+
+        let result = ip_country(args, &mut stdin, &mut stdout, &mut stderr, &parser_factory);
+
+        assert_eq!(result, 1);
+        assert_eq!(make_params_arc.lock().unwrap().len(), 0);
+        assert_eq!(stdout.get_bytes(), Vec::<u8>::new());
+        let stderr_string = String::from_utf8(stderr.get_bytes()).unwrap();
+        assert_eq!(
+            stderr_string,
+            "--data-file requires a database version, such as --data-file 2024-06".to_string()
+        );
+    }
+
+    #[test]
+    fn write_error_from_data_file() {
+        let stdin = &mut ByteArrayReader::new(TEST_DATA.as_bytes());
+        let stdout = &mut ByteArrayWriter::new();
+        let stderr = &mut ByteArrayWriter::new();
+        stdout.reject_next_write(Error::new(ErrorKind::WriteZero, "Bad file Descriptor"));
+        let parser = DBIPParserMock::new().parse_errors(vec![]).parse_result((
+            final_bit_queue(0x1122334455667788, 12),
+            final_bit_queue(0x8877665544332211, 21),
+            &TEST_COUNTRIES,
+        ));
+        let parser_factory = DBIPParserFactoryMock::new().make_result(parser);
+        let args = vec!["--data-file".to_string(), "2024-06".to_string()];
+
+        let result = ip_country(args, stdin, stdout, stderr, &parser_factory);
+
+        assert_eq!(result, 1);
+        let stderr_string = String::from_utf8(stderr.get_bytes()).unwrap();
+        assert_eq!(
+            stderr_string,
+            "Error writing data file: Custom { kind: WriteZero, error: \"Bad file Descriptor\" }"
+        );
+    }
+
     fn final_bit_queue(contents: u64, block_count: usize) -> FinalBitQueue {
         let mut bit_queue = BitQueue::new();
         bit_queue.add_bits(contents, 64);
@@ -1,9 +1,11 @@
 // Copyright (c) 2024, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 
 pub mod bit_queue;
+pub mod checksum;
 pub mod countries;
 pub mod country_block_serde;
 pub mod country_block_stream;
+pub mod country_data_file;
 pub mod country_finder;
 pub mod ip_country;
 pub mod ip_country_csv;
@@ -1,22 +1,31 @@
 // Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 
 use crate::command_factory::CommandFactoryError::{CommandSyntax, UnrecognizedSubcommand};
+use crate::commands::automap_command::AutomapCommand;
+use crate::commands::backup_command::BackupCommand;
+use crate::commands::bans_command::BansCommand;
 use crate::commands::change_password_command::ChangePasswordCommand;
 use crate::commands::check_password_command::CheckPasswordCommand;
 use crate::commands::commands_common::Command;
 use crate::commands::configuration_command::ConfigurationCommand;
 use crate::commands::connection_status_command::ConnectionStatusCommand;
+use crate::commands::country_database_command::CountryDatabaseCommand;
 use crate::commands::crash_command::CrashCommand;
 use crate::commands::descriptor_command::DescriptorCommand;
+use crate::commands::encryption_command::EncryptionCommand;
 use crate::commands::exit_location_command::SetExitLocationCommand;
+use crate::commands::export_ledger_command::ExportLedgerCommand;
 use crate::commands::financials_command::FinancialsCommand;
 use crate::commands::generate_wallets_command::GenerateWalletsCommand;
+use crate::commands::gossip_rejections_command::GossipRejectionsCommand;
 use crate::commands::neighborhood_graph_command::GetNeighborhoodGraphCommand;
 use crate::commands::recover_wallets_command::RecoverWalletsCommand;
+use crate::commands::rotate_key_command::RotateKeyCommand;
 use crate::commands::scan_command::ScanCommand;
 use crate::commands::set_configuration_command::SetConfigurationCommand;
 use crate::commands::setup_command::SetupCommand;
 use crate::commands::shutdown_command::ShutdownCommand;
+use crate::commands::simulate_thresholds_command::SimulateThresholdsCommand;
 use crate::commands::start_command::StartCommand;
 use crate::commands::wallet_addresses_command::WalletAddressesCommand;
 
@@ -36,6 +45,15 @@ pub struct CommandFactoryReal;
 impl CommandFactory for CommandFactoryReal {
     fn make(&self, pieces: &[String]) -> Result<Box<dyn Command>, CommandFactoryError> {
         let boxed_command: Box<dyn Command> = match pieces[0].as_str() {
+            "automap" => match AutomapCommand::new(pieces) {
+                Ok(command) => Box::new(command),
+                Err(msg) => return Err(CommandSyntax(msg)),
+            },
+            "backup" => Box::new(BackupCommand::new()),
+            "bans" => match BansCommand::new(pieces) {
+                Ok(command) => Box::new(command),
+                Err(msg) => return Err(CommandSyntax(msg)),
+            },
             "change-password" => match ChangePasswordCommand::new_change(pieces) {
                 Ok(command) => Box::new(command),
                 Err(msg) => return Err(CommandSyntax(msg)),
@@ -48,16 +66,28 @@ impl CommandFactory for CommandFactoryReal {
                 Ok(command) => Box::new(command),
                 Err(msg) => return Err(CommandSyntax(msg)),
             },
-            "connection-status" => Box::new(ConnectionStatusCommand::new()),
+            "connection-status" => match ConnectionStatusCommand::new(pieces) {
+                Ok(command) => Box::new(command),
+                Err(msg) => return Err(CommandSyntax(msg)),
+            },
+            "country-database" => match CountryDatabaseCommand::new(pieces) {
+                Ok(command) => Box::new(command),
+                Err(msg) => return Err(CommandSyntax(msg)),
+            },
             "crash" => match CrashCommand::new(pieces) {
                 Ok(command) => Box::new(command),
                 Err(msg) => return Err(CommandSyntax(msg)),
             },
             "descriptor" => Box::new(DescriptorCommand::new()),
+            "encryption" => Box::new(EncryptionCommand::new()),
             "exit-location" => match SetExitLocationCommand::new(pieces) {
                 Ok(command) => Box::new(command),
                 Err(msg) => return Err(CommandSyntax(msg)),
             },
+            "export-ledger" => match ExportLedgerCommand::new(pieces) {
+                Ok(command) => Box::new(command),
+                Err(msg) => return Err(CommandSyntax(msg)),
+            },
             "financials" => match FinancialsCommand::new(pieces) {
                 Ok(command) => Box::new(command),
                 Err(msg) => return Err(CommandSyntax(msg)),
@@ -66,6 +96,10 @@ impl CommandFactory for CommandFactoryReal {
                 Ok(command) => Box::new(command),
                 Err(msg) => return Err(CommandSyntax(msg)),
             },
+            "gossip-rejections" => match GossipRejectionsCommand::new(pieces) {
+                Ok(command) => Box::new(command),
+                Err(msg) => return Err(CommandSyntax(msg)),
+            },
             "neighborhood-graph" => match GetNeighborhoodGraphCommand::new(pieces) {
                 Ok(command) => Box::new(command),
                 Err(msg) => return Err(CommandSyntax(msg)),
@@ -74,6 +108,7 @@ impl CommandFactory for CommandFactoryReal {
                 Ok(command) => Box::new(command),
                 Err(msg) => return Err(CommandSyntax(msg)),
             },
+            "rotate-key" => Box::new(RotateKeyCommand::new()),
             "scan" => match ScanCommand::new(pieces) {
                 Ok(command) => Box::new(command),
                 Err(msg) => return Err(CommandSyntax(msg)),
@@ -91,6 +126,10 @@ impl CommandFactory for CommandFactoryReal {
                 Err(msg) => return Err(CommandSyntax(msg)),
             },
             "shutdown" => Box::new(ShutdownCommand::new()),
+            "simulate-thresholds" => match SimulateThresholdsCommand::new(pieces) {
+                Ok(command) => Box::new(command),
+                Err(msg) => return Err(CommandSyntax(msg)),
+            },
             "start" => Box::new(StartCommand::new()),
             "wallet-addresses" => match WalletAddressesCommand::new(pieces) {
                 Ok(command) => Box::new(command),
@@ -113,7 +152,8 @@ impl CommandFactoryReal {
 mod tests {
     use super::*;
     use crate::command_factory::CommandFactoryError::UnrecognizedSubcommand;
-    use masq_lib::messages::CountryGroups;
+    use crate::commands::neighborhood_graph_command::NeighborhoodGraphFormat;
+    use masq_lib::messages::{CountryGroups, UiGetNeighborhoodGraphRequest};
 
     #[test]
     fn complains_about_unrecognized_subcommand() {
@@ -222,7 +262,10 @@ mod tests {
             .as_any()
             .downcast_ref::<ConnectionStatusCommand>()
             .unwrap();
-        assert_eq!(connnection_status_command, &ConnectionStatusCommand {});
+        assert_eq!(
+            connnection_status_command,
+            &ConnectionStatusCommand { verbose: false }
+        );
     }
 
     #[test]
@@ -308,7 +351,10 @@ mod tests {
                 .as_any()
                 .downcast_ref::<GetNeighborhoodGraphCommand>()
                 .unwrap(),
-            &GetNeighborhoodGraphCommand {}
+            &GetNeighborhoodGraphCommand {
+                request: UiGetNeighborhoodGraphRequest::default(),
+                format: NeighborhoodGraphFormat::Dot,
+            }
         );
     }
 
@@ -393,7 +439,7 @@ mod tests {
                 .as_any()
                 .downcast_ref::<ConnectionStatusCommand>()
                 .unwrap(),
-            &ConnectionStatusCommand {}
+            &ConnectionStatusCommand { verbose: false }
         );
     }
 
@@ -0,0 +1,351 @@
+// Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::command_context::CommandContext;
+use crate::commands::commands_common::CommandError::Payload;
+use crate::commands::commands_common::{dump_parameter_line, transaction, Command, CommandError};
+use clap::{App, Arg, ArgGroup, SubCommand};
+use masq_lib::as_any_ref_in_trait_impl;
+use masq_lib::constants::AUTOMAP_INACTIVE_ERROR;
+use masq_lib::messages::{UiAutomapAction, UiAutomapRequest, UiAutomapResponse};
+use masq_lib::short_writeln;
+use std::io::Write;
+
+// Remapping and switching protocols mean conversations with the router, which can be slow
+pub const AUTOMAP_COMMAND_TIMEOUT_MILLIS: u64 = 10000;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct AutomapCommand {
+    pub action_opt: Option<UiAutomapAction>,
+}
+
+const AUTOMAP_ABOUT: &str =
+    "Displays the state of the port mapping the Node has made on your router, \
+    optionally changing it first.";
+const REMAP_HELP: &str =
+    "Renews the port mapping right away instead of waiting for the next scheduled remap.";
+const PROTOCOL_HELP: &str =
+    "Removes the current port mapping and makes a new one using the specified \
+    protocol. If that protocol doesn't work, the others are tried, just as at startup.";
+const DELETE_HELP: &str =
+    "Removes the port mapping from your router. The Node won't be reachable from \
+    outside your network until it's remapped.";
+
+pub fn automap_subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("automap")
+        .about(AUTOMAP_ABOUT)
+        .arg(
+            Arg::with_name("remap")
+                .long("remap")
+                .help(REMAP_HELP)
+                .takes_value(false)
+                .required(false),
+        )
+        .arg(
+            Arg::with_name("protocol")
+                .long("protocol")
+                .value_name("PROTOCOL")
+                .help(PROTOCOL_HELP)
+                .possible_values(&["PCP", "PMP", "IGDP"])
+                .case_insensitive(true)
+                .required(false),
+        )
+        .arg(
+            Arg::with_name("delete")
+                .long("delete")
+                .help(DELETE_HELP)
+                .takes_value(false)
+                .required(false),
+        )
+        .group(ArgGroup::with_name("action").args(&["remap", "protocol", "delete"]))
+}
+
+impl Command for AutomapCommand {
+    fn execute(&self, context: &mut dyn CommandContext) -> Result<(), CommandError> {
+        let input = UiAutomapRequest {
+            action_opt: self.action_opt.clone(),
+        };
+        let output: Result<UiAutomapResponse, CommandError> =
+            transaction(input, context, AUTOMAP_COMMAND_TIMEOUT_MILLIS);
+        match output {
+            Ok(response) => {
+                Self::dump_status(context.stdout(), response);
+                Ok(())
+            }
+            Err(Payload(code, message)) if code == AUTOMAP_INACTIVE_ERROR => {
+                short_writeln!(context.stderr(), "{}", message);
+                Err(Payload(code, message))
+            }
+            Err(e) => {
+                short_writeln!(context.stderr(), "Automap command failed: {:?}", e);
+                Err(e)
+            }
+        }
+    }
+
+    as_any_ref_in_trait_impl!();
+}
+
+impl AutomapCommand {
+    pub fn new(pieces: &[String]) -> Result<Self, String> {
+        let matches = match automap_subcommand().get_matches_from_safe(pieces) {
+            Ok(matches) => matches,
+            Err(e) => return Err(format!("{}", e)),
+        };
+        let action_opt = if matches.is_present("remap") {
+            Some(UiAutomapAction::Remap)
+        } else if let Some(protocol) = matches.value_of("protocol") {
+            Some(UiAutomapAction::SwitchProtocol(protocol.to_uppercase()))
+        } else if matches.is_present("delete") {
+            Some(UiAutomapAction::DeleteMappings)
+        } else {
+            None
+        };
+        Ok(Self { action_opt })
+    }
+
+    fn dump_status(stream: &mut dyn Write, response: UiAutomapResponse) {
+        dump_parameter_line(stream, "NAME", "VALUE");
+        dump_parameter_line(
+            stream,
+            "Protocol:",
+            &response.protocol_opt.unwrap_or_else(|| "[?]".to_string()),
+        );
+        dump_parameter_line(
+            stream,
+            "Public IP:",
+            &response.public_ip_opt.unwrap_or_else(|| "[?]".to_string()),
+        );
+        let hole_ports = if response.hole_ports.is_empty() {
+            "[None]".to_string()
+        } else {
+            response
+                .hole_ports
+                .iter()
+                .map(|port| port.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+        dump_parameter_line(stream, "Mapped ports:", &hole_ports);
+        let lease_lifetime = match response.lease_lifetime_secs_opt {
+            None => "[No mapping]".to_string(),
+            Some(0) => "[Permanent]".to_string(),
+            Some(secs) => format!("{} s", secs),
+        };
+        dump_parameter_line(stream, "Lease lifetime:", &lease_lifetime);
+        dump_parameter_line(
+            stream,
+            "Next remap:",
+            &response
+                .next_remap_secs_opt
+                .map(|secs| format!("in {} s", secs))
+                .unwrap_or_else(|| "[No mapping]".to_string()),
+        );
+        dump_parameter_line(
+            stream,
+            "Last error:",
+            &response
+                .last_error_opt
+                .unwrap_or_else(|| "[None]".to_string()),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_context::ContextError;
+    use crate::command_factory::{CommandFactory, CommandFactoryReal};
+    use crate::test_utils::mocks::CommandContextMock;
+    use masq_lib::messages::ToMessageBody;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn constants_have_correct_values() {
+        assert_eq!(AUTOMAP_COMMAND_TIMEOUT_MILLIS, 10000);
+        assert_eq!(
+            AUTOMAP_ABOUT,
+            "Displays the state of the port mapping the Node has made on your router, \
+            optionally changing it first."
+        );
+        assert_eq!(
+            REMAP_HELP,
+            "Renews the port mapping right away instead of waiting for the next scheduled remap."
+        );
+        assert_eq!(
+            PROTOCOL_HELP,
+            "Removes the current port mapping and makes a new one using the specified \
+            protocol. If that protocol doesn't work, the others are tried, just as at startup."
+        );
+        assert_eq!(
+            DELETE_HELP,
+            "Removes the port mapping from your router. The Node won't be reachable from \
+            outside your network until it's remapped."
+        );
+    }
+
+    fn action_from(args: &[&str]) -> Option<UiAutomapAction> {
+        let pieces = args.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+        let command = CommandFactoryReal::new().make(&pieces).unwrap();
+        let automap_command = command.as_any().downcast_ref::<AutomapCommand>().unwrap();
+        automap_command.action_opt.clone()
+    }
+
+    #[test]
+    fn command_factory_interprets_actions() {
+        assert_eq!(action_from(&["automap"]), None);
+        assert_eq!(
+            action_from(&["automap", "--remap"]),
+            Some(UiAutomapAction::Remap)
+        );
+        assert_eq!(
+            action_from(&["automap", "--protocol", "pmp"]),
+            Some(UiAutomapAction::SwitchProtocol("PMP".to_string()))
+        );
+        assert_eq!(
+            action_from(&["automap", "--delete"]),
+            Some(UiAutomapAction::DeleteMappings)
+        );
+    }
+
+    #[test]
+    fn actions_are_mutually_exclusive() {
+        let result = AutomapCommand::new(&[
+            "automap".to_string(),
+            "--remap".to_string(),
+            "--delete".to_string(),
+        ]);
+
+        let msg = result.err().unwrap();
+        assert!(
+            msg.contains("cannot be used with"),
+            "{} doesn't complain about conflicting arguments",
+            msg
+        );
+    }
+
+    #[test]
+    fn automap_command_happy_path() {
+        let transact_params_arc = Arc::new(Mutex::new(vec![]));
+        let response = UiAutomapResponse {
+            protocol_opt: Some("PCP".to_string()),
+            public_ip_opt: Some("1.2.3.4".to_string()),
+            hole_ports: vec![1234, 2345],
+            lease_lifetime_secs_opt: Some(600),
+            next_remap_secs_opt: Some(279),
+            last_error_opt: Some("HousekeeperCrashed".to_string()),
+        };
+        let mut context = CommandContextMock::new()
+            .transact_params(&transact_params_arc)
+            .transact_result(Ok(response.tmb(4321)));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = AutomapCommand {
+            action_opt: Some(UiAutomapAction::Remap),
+        };
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        let transact_params = transact_params_arc.lock().unwrap();
+        assert_eq!(
+            *transact_params,
+            vec![(
+                UiAutomapRequest {
+                    action_opt: Some(UiAutomapAction::Remap)
+                }
+                .tmb(0),
+                AUTOMAP_COMMAND_TIMEOUT_MILLIS
+            )]
+        );
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            "\
+|NAME                              VALUE
+|Protocol:                         PCP
+|Public IP:                        1.2.3.4
+|Mapped ports:                     1234, 2345
+|Lease lifetime:                   600 s
+|Next remap:                       in 279 s
+|Last error:                       HousekeeperCrashed
+"
+            .replace('|', "")
+        );
+        assert_eq!(stderr_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn automap_command_shows_missing_mapping() {
+        let response = UiAutomapResponse {
+            protocol_opt: None,
+            public_ip_opt: None,
+            hole_ports: vec![],
+            lease_lifetime_secs_opt: None,
+            next_remap_secs_opt: None,
+            last_error_opt: None,
+        };
+        let mut context = CommandContextMock::new().transact_result(Ok(response.tmb(4321)));
+        let stdout_arc = context.stdout_arc();
+        let subject = AutomapCommand { action_opt: None };
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            "\
+|NAME                              VALUE
+|Protocol:                         [?]
+|Public IP:                        [?]
+|Mapped ports:                     [None]
+|Lease lifetime:                   [No mapping]
+|Next remap:                       [No mapping]
+|Last error:                       [None]
+"
+            .replace('|', "")
+        );
+    }
+
+    #[test]
+    fn automap_command_explains_when_automap_is_inactive() {
+        let mut context = CommandContextMock::new().transact_result(Err(
+            ContextError::PayloadError(AUTOMAP_INACTIVE_ERROR, "Automap is not in use".to_string()),
+        ));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = AutomapCommand { action_opt: None };
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(
+            result,
+            Err(Payload(
+                AUTOMAP_INACTIVE_ERROR,
+                "Automap is not in use".to_string()
+            ))
+        );
+        assert_eq!(stdout_arc.lock().unwrap().get_string(), String::new());
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "Automap is not in use\n"
+        );
+    }
+
+    #[test]
+    fn automap_command_handles_send_failure() {
+        let mut context = CommandContextMock::new()
+            .transact_result(Err(ContextError::ConnectionDropped("blah".to_string())));
+        let stderr_arc = context.stderr_arc();
+        let subject = AutomapCommand { action_opt: None };
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(
+            result,
+            Err(CommandError::ConnectionProblem("blah".to_string()))
+        );
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "Automap command failed: ConnectionProblem(\"blah\")\n"
+        );
+    }
+}
@@ -0,0 +1,192 @@
+// Copyright (c) 2024, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::command_context::CommandContext;
+use crate::commands::commands_common::CommandError::Payload;
+use crate::commands::commands_common::{
+    dump_parameter_line, format_unix_timestamp, transaction, Command, CommandError,
+};
+use clap::{App, SubCommand};
+use masq_lib::as_any_ref_in_trait_impl;
+use masq_lib::constants::DATABASE_BACKUP_ERROR;
+use masq_lib::messages::{UiBackupRequest, UiBackupResponse};
+use masq_lib::short_writeln;
+use std::io::Write;
+
+// Copying a large database is done in steps and can take a while
+pub const BACKUP_COMMAND_TIMEOUT_MILLIS: u64 = 30000;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct BackupCommand {}
+
+const BACKUP_SUBCOMMAND_ABOUT: &str =
+    "Makes a backup copy of the Node's database in the 'backups' folder of its data directory, \
+     removing the oldest copies beyond the five most recent. The Node also makes one every day. \
+     To restore a copy, stop the Node and run it with --restore-backup. Only valid if Node is \
+     already running.";
+
+pub fn backup_subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("backup").about(BACKUP_SUBCOMMAND_ABOUT)
+}
+
+impl Command for BackupCommand {
+    fn execute(&self, context: &mut dyn CommandContext) -> Result<(), CommandError> {
+        let input = UiBackupRequest {};
+        let output: Result<UiBackupResponse, CommandError> =
+            transaction(input, context, BACKUP_COMMAND_TIMEOUT_MILLIS);
+        match output {
+            Ok(response) => {
+                Self::dump_backup(context.stdout(), response);
+                Ok(())
+            }
+            Err(Payload(code, message)) if code == DATABASE_BACKUP_ERROR => {
+                short_writeln!(context.stderr(), "{}", message);
+                Err(Payload(code, message))
+            }
+            Err(e) => {
+                short_writeln!(context.stderr(), "Backup failed: {:?}", e);
+                Err(e)
+            }
+        }
+    }
+
+    as_any_ref_in_trait_impl!();
+}
+
+impl BackupCommand {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn dump_backup(stream: &mut dyn Write, response: UiBackupResponse) {
+        dump_parameter_line(stream, "NAME", "VALUE");
+        dump_parameter_line(stream, "Backup file:", &response.path);
+        dump_parameter_line(
+            stream,
+            "Schema version:",
+            &response.schema_version.to_string(),
+        );
+        dump_parameter_line(
+            stream,
+            "Taken at:",
+            &format_unix_timestamp(response.timestamp),
+        );
+        response
+            .removed_backups
+            .iter()
+            .for_each(|path| dump_parameter_line(stream, "Removed:", path));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_context::ContextError;
+    use crate::command_factory::{CommandFactory, CommandFactoryReal};
+    use crate::test_utils::mocks::CommandContextMock;
+    use masq_lib::messages::ToMessageBody;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn constants_have_correct_values() {
+        assert_eq!(BACKUP_COMMAND_TIMEOUT_MILLIS, 30000);
+        assert_eq!(
+            BACKUP_SUBCOMMAND_ABOUT,
+            "Makes a backup copy of the Node's database in the 'backups' folder of its data \
+             directory, removing the oldest copies beyond the five most recent. The Node also \
+             makes one every day. To restore a copy, stop the Node and run it with \
+             --restore-backup. Only valid if Node is already running."
+        );
+    }
+
+    #[test]
+    fn command_factory_makes_backup_command() {
+        let factory = CommandFactoryReal::new();
+
+        let result = factory.make(&["backup".to_string()]).unwrap();
+
+        assert_eq!(
+            result.as_any().downcast_ref::<BackupCommand>(),
+            Some(&BackupCommand {})
+        );
+    }
+
+    #[test]
+    fn backup_command_happy_path() {
+        let transact_params_arc = Arc::new(Mutex::new(vec![]));
+        let response = UiBackupResponse {
+            path: "/data/backups/node-data-1700000000-v13.db".to_string(),
+            timestamp: 1_700_000_000,
+            schema_version: 13,
+            removed_backups: vec![
+                "/data/backups/node-data-1600000000-v12.db".to_string(),
+                "/data/backups/node-data-1500000000-v12.db".to_string(),
+            ],
+        };
+        let mut context = CommandContextMock::new()
+            .transact_params(&transact_params_arc)
+            .transact_result(Ok(response.tmb(4321)));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = BackupCommand::new();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        let transact_params = transact_params_arc.lock().unwrap();
+        assert_eq!(
+            *transact_params,
+            vec![(UiBackupRequest {}.tmb(0), BACKUP_COMMAND_TIMEOUT_MILLIS)]
+        );
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            "\
+|NAME                              VALUE
+|Backup file:                      /data/backups/node-data-1700000000-v13.db
+|Schema version:                   13
+|Taken at:                         2023-11-14T22:13:20Z
+|Removed:                          /data/backups/node-data-1600000000-v12.db
+|Removed:                          /data/backups/node-data-1500000000-v12.db
+"
+            .replace('|', "")
+        );
+        assert_eq!(stderr_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn backup_command_explains_failed_backup() {
+        let mut context = CommandContextMock::new().transact_result(Err(
+            ContextError::PayloadError(DATABASE_BACKUP_ERROR, "Disk is full".to_string()),
+        ));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = BackupCommand::new();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(
+            result,
+            Err(Payload(DATABASE_BACKUP_ERROR, "Disk is full".to_string()))
+        );
+        assert_eq!(stdout_arc.lock().unwrap().get_string(), String::new());
+        assert_eq!(stderr_arc.lock().unwrap().get_string(), "Disk is full\n");
+    }
+
+    #[test]
+    fn backup_command_handles_send_failure() {
+        let mut context = CommandContextMock::new()
+            .transact_result(Err(ContextError::ConnectionDropped("blah".to_string())));
+        let stderr_arc = context.stderr_arc();
+        let subject = BackupCommand::new();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(
+            result,
+            Err(CommandError::ConnectionProblem("blah".to_string()))
+        );
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "Backup failed: ConnectionProblem(\"blah\")\n"
+        );
+    }
+}
@@ -0,0 +1,507 @@
+// Copyright (c) 2024, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::command_context::CommandContext;
+use crate::commands::commands_common::CommandError::Payload;
+use crate::commands::commands_common::{
+    dump_parameter_line, format_unix_timestamp, transaction, Command, CommandError,
+    STANDARD_COMMAND_TIMEOUT_MILLIS,
+};
+use clap::{App, Arg, ArgGroup, SubCommand};
+use masq_lib::as_any_ref_in_trait_impl;
+use masq_lib::constants::BAN_LIST_ERROR;
+use masq_lib::messages::{
+    UiBanListRequest, UiBanListResponse, UiBanRequest, UiBanResponse, UiBannedWallet,
+    UiPardonRequest, UiPardonResponse,
+};
+use masq_lib::short_writeln;
+use std::io::Write;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum BansAction {
+    List,
+    Ban {
+        wallet: String,
+        note_opt: Option<String>,
+    },
+    Pardon {
+        wallet: String,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct BansCommand {
+    pub action: BansAction,
+}
+
+const BANS_ABOUT: &str =
+    "Displays the wallets the Node refuses to serve, with the reason and the balance at the time \
+    of the ban, optionally banning or pardoning a wallet first.";
+const BAN_HELP: &str =
+    "Bans the wallet with the given address by hand. A manual ban is never lifted automatically, \
+    no matter how the wallet's debt develops.";
+const NOTE_HELP: &str =
+    "A remark to keep with the ban. If the wallet is banned already, the remark replaces its old one.";
+const PARDON_HELP: &str =
+    "Lifts the ban on the wallet with the given address. A pardoned wallet isn't banned again for \
+    delinquency until it has paid its debt down.";
+
+pub fn bans_subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("bans")
+        .about(BANS_ABOUT)
+        .arg(
+            Arg::with_name("ban")
+                .help(BAN_HELP)
+                .long("ban")
+                .value_name("WALLET")
+                .takes_value(true)
+                .required(false),
+        )
+        .arg(
+            Arg::with_name("note")
+                .help(NOTE_HELP)
+                .long("note")
+                .value_name("NOTE")
+                .takes_value(true)
+                .required(false)
+                .requires("ban"),
+        )
+        .arg(
+            Arg::with_name("pardon")
+                .help(PARDON_HELP)
+                .long("pardon")
+                .value_name("WALLET")
+                .takes_value(true)
+                .required(false),
+        )
+        .group(ArgGroup::with_name("action").args(&["ban", "pardon"]))
+}
+
+impl Command for BansCommand {
+    fn execute(&self, context: &mut dyn CommandContext) -> Result<(), CommandError> {
+        let result = match &self.action {
+            BansAction::List => Self::list(context),
+            BansAction::Ban { wallet, note_opt } => Self::ban(context, wallet, note_opt),
+            BansAction::Pardon { wallet } => Self::pardon(context, wallet),
+        };
+        match result {
+            Ok(()) => Ok(()),
+            Err(Payload(code, message)) if code == BAN_LIST_ERROR => {
+                short_writeln!(context.stderr(), "{}", message);
+                Err(Payload(code, message))
+            }
+            Err(e) => {
+                short_writeln!(context.stderr(), "Bans command failed: {:?}", e);
+                Err(e)
+            }
+        }
+    }
+
+    as_any_ref_in_trait_impl!();
+}
+
+impl BansCommand {
+    pub fn new(pieces: &[String]) -> Result<Self, String> {
+        let matches = match bans_subcommand().get_matches_from_safe(pieces) {
+            Ok(matches) => matches,
+            Err(e) => return Err(format!("{}", e)),
+        };
+        let action = if let Some(wallet) = matches.value_of("ban") {
+            BansAction::Ban {
+                wallet: wallet.to_string(),
+                note_opt: matches.value_of("note").map(|note| note.to_string()),
+            }
+        } else if let Some(wallet) = matches.value_of("pardon") {
+            BansAction::Pardon {
+                wallet: wallet.to_string(),
+            }
+        } else {
+            BansAction::List
+        };
+        Ok(Self { action })
+    }
+
+    fn list(context: &mut dyn CommandContext) -> Result<(), CommandError> {
+        let response: UiBanListResponse = transaction(
+            UiBanListRequest {},
+            context,
+            STANDARD_COMMAND_TIMEOUT_MILLIS,
+        )?;
+        if response.bans.is_empty() {
+            short_writeln!(context.stdout(), "No wallets are banned.");
+        } else {
+            response
+                .bans
+                .iter()
+                .for_each(|ban| Self::dump_ban(context.stdout(), ban));
+        }
+        Ok(())
+    }
+
+    fn ban(
+        context: &mut dyn CommandContext,
+        wallet: &str,
+        note_opt: &Option<String>,
+    ) -> Result<(), CommandError> {
+        let input = UiBanRequest {
+            wallet: wallet.to_string(),
+            note_opt: note_opt.clone(),
+        };
+        let response: UiBanResponse = transaction(input, context, STANDARD_COMMAND_TIMEOUT_MILLIS)?;
+        if response.newly_banned {
+            short_writeln!(context.stdout(), "Wallet {} is now banned.", wallet);
+        } else if note_opt.is_some() {
+            short_writeln!(
+                context.stdout(),
+                "Wallet {} was banned already; its note has been replaced.",
+                wallet
+            );
+        } else {
+            short_writeln!(context.stdout(), "Wallet {} was banned already.", wallet);
+        }
+        Ok(())
+    }
+
+    fn pardon(context: &mut dyn CommandContext, wallet: &str) -> Result<(), CommandError> {
+        let input = UiPardonRequest {
+            wallet: wallet.to_string(),
+        };
+        let _: UiPardonResponse = transaction(input, context, STANDARD_COMMAND_TIMEOUT_MILLIS)?;
+        short_writeln!(context.stdout(), "Wallet {} has been pardoned.", wallet);
+        Ok(())
+    }
+
+    fn dump_ban(stream: &mut dyn Write, ban: &UiBannedWallet) {
+        dump_parameter_line(stream, "Wallet:", &ban.wallet);
+        dump_parameter_line(
+            stream,
+            "Banned at:",
+            &ban.banned_at_opt
+                .map(format_unix_timestamp)
+                .unwrap_or_else(|| "[?]".to_string()),
+        );
+        dump_parameter_line(stream, "Reason:", &ban.reason);
+        dump_parameter_line(
+            stream,
+            "Balance at ban time:",
+            &ban.balance_wei_opt
+                .as_ref()
+                .map(|balance| format!("{} wei", balance))
+                .unwrap_or_else(|| "[?]".to_string()),
+        );
+        if let Some(note) = ban.note_opt.as_ref() {
+            dump_parameter_line(stream, "Note:", note);
+        }
+        short_writeln!(stream, "");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_context::ContextError;
+    use crate::command_factory::{CommandFactory, CommandFactoryError, CommandFactoryReal};
+    use crate::test_utils::mocks::CommandContextMock;
+    use masq_lib::messages::ToMessageBody;
+    use std::sync::{Arc, Mutex};
+
+    const WALLET: &str = "0x000000000000000000000000000000000000abcd";
+
+    #[test]
+    fn constants_have_correct_values() {
+        assert_eq!(
+            BANS_ABOUT,
+            "Displays the wallets the Node refuses to serve, with the reason and the balance at the time \
+            of the ban, optionally banning or pardoning a wallet first."
+        );
+        assert_eq!(
+            BAN_HELP,
+            "Bans the wallet with the given address by hand. A manual ban is never lifted automatically, \
+            no matter how the wallet's debt develops."
+        );
+        assert_eq!(
+            NOTE_HELP,
+            "A remark to keep with the ban. If the wallet is banned already, the remark replaces its old one."
+        );
+        assert_eq!(
+            PARDON_HELP,
+            "Lifts the ban on the wallet with the given address. A pardoned wallet isn't banned again for \
+            delinquency until it has paid its debt down."
+        );
+    }
+
+    #[test]
+    fn command_factory_interprets_the_actions() {
+        let factory = CommandFactoryReal::new();
+        let make = |pieces: &[&str]| {
+            factory
+                .make(
+                    &pieces
+                        .iter()
+                        .map(|piece| piece.to_string())
+                        .collect::<Vec<String>>(),
+                )
+                .unwrap()
+        };
+
+        let list = make(&["bans"]);
+        let ban = make(&["bans", "--ban", WALLET, "--note", "Spammer"]);
+        let pardon = make(&["bans", "--pardon", WALLET]);
+
+        assert_eq!(
+            list.as_any().downcast_ref::<BansCommand>(),
+            Some(&BansCommand {
+                action: BansAction::List
+            })
+        );
+        assert_eq!(
+            ban.as_any().downcast_ref::<BansCommand>(),
+            Some(&BansCommand {
+                action: BansAction::Ban {
+                    wallet: WALLET.to_string(),
+                    note_opt: Some("Spammer".to_string())
+                }
+            })
+        );
+        assert_eq!(
+            pardon.as_any().downcast_ref::<BansCommand>(),
+            Some(&BansCommand {
+                action: BansAction::Pardon {
+                    wallet: WALLET.to_string()
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn command_factory_rejects_conflicting_or_orphaned_arguments() {
+        let factory = CommandFactoryReal::new();
+
+        vec![
+            vec!["bans", "--ban", WALLET, "--pardon", WALLET],
+            vec!["bans", "--note", "Spammer"],
+            vec!["bans", "--pardon", WALLET, "--note", "Spammer"],
+        ]
+        .into_iter()
+        .for_each(|pieces| {
+            let result = factory.make(
+                &pieces
+                    .iter()
+                    .map(|piece| piece.to_string())
+                    .collect::<Vec<String>>(),
+            );
+
+            match result {
+                Err(CommandFactoryError::CommandSyntax(_)) => (),
+                x => panic!(
+                    "Expected syntax error for {:?}, got {:?}",
+                    pieces,
+                    x.map(|_| ())
+                ),
+            }
+        });
+    }
+
+    #[test]
+    fn bans_command_lists_the_bans() {
+        let transact_params_arc = Arc::new(Mutex::new(vec![]));
+        let mut context = CommandContextMock::new()
+            .transact_params(&transact_params_arc)
+            .transact_result(Ok(UiBanListResponse {
+                bans: vec![
+                    UiBannedWallet {
+                        wallet: WALLET.to_string(),
+                        banned_at_opt: Some(1_700_000_000),
+                        reason: "manual".to_string(),
+                        balance_wei_opt: Some("123456".to_string()),
+                        note_opt: Some("Spammer".to_string()),
+                    },
+                    UiBannedWallet {
+                        wallet: "0x0000000000000000000000000000000000001234".to_string(),
+                        banned_at_opt: None,
+                        reason: "unknown".to_string(),
+                        balance_wei_opt: None,
+                        note_opt: None,
+                    },
+                ],
+            }
+            .tmb(0)));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = BansCommand {
+            action: BansAction::List,
+        };
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        let transact_params = transact_params_arc.lock().unwrap();
+        assert_eq!(
+            *transact_params,
+            vec![(UiBanListRequest {}.tmb(0), STANDARD_COMMAND_TIMEOUT_MILLIS)]
+        );
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            "\
+Wallet:                           0x000000000000000000000000000000000000abcd
+Banned at:                        2023-11-14T22:13:20Z
+Reason:                           manual
+Balance at ban time:              123456 wei
+Note:                             Spammer
+
+Wallet:                           0x0000000000000000000000000000000000001234
+Banned at:                        [?]
+Reason:                           unknown
+Balance at ban time:              [?]
+
+"
+        );
+        assert_eq!(stderr_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn bans_command_reports_an_empty_ban_list() {
+        let mut context = CommandContextMock::new()
+            .transact_result(Ok(UiBanListResponse { bans: vec![] }.tmb(0)));
+        let stdout_arc = context.stdout_arc();
+        let subject = BansCommand {
+            action: BansAction::List,
+        };
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            "No wallets are banned.\n"
+        );
+    }
+
+    #[test]
+    fn bans_command_bans_a_wallet() {
+        let transact_params_arc = Arc::new(Mutex::new(vec![]));
+        let mut context = CommandContextMock::new()
+            .transact_params(&transact_params_arc)
+            .transact_result(Ok(UiBanResponse { newly_banned: true }.tmb(0)))
+            .transact_result(Ok(UiBanResponse {
+                newly_banned: false,
+            }
+            .tmb(0)));
+        let stdout_arc = context.stdout_arc();
+        let subject = BansCommand {
+            action: BansAction::Ban {
+                wallet: WALLET.to_string(),
+                note_opt: Some("Spammer".to_string()),
+            },
+        };
+
+        let first_result = subject.execute(&mut context);
+        let second_result = subject.execute(&mut context);
+
+        assert_eq!(first_result, Ok(()));
+        assert_eq!(second_result, Ok(()));
+        let expected_request = UiBanRequest {
+            wallet: WALLET.to_string(),
+            note_opt: Some("Spammer".to_string()),
+        }
+        .tmb(0);
+        let transact_params = transact_params_arc.lock().unwrap();
+        assert_eq!(
+            *transact_params,
+            vec![
+                (expected_request.clone(), STANDARD_COMMAND_TIMEOUT_MILLIS),
+                (expected_request, STANDARD_COMMAND_TIMEOUT_MILLIS)
+            ]
+        );
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            format!(
+                "Wallet {} is now banned.\n\
+                Wallet {} was banned already; its note has been replaced.\n",
+                WALLET, WALLET
+            )
+        );
+    }
+
+    #[test]
+    fn bans_command_pardons_a_wallet() {
+        let transact_params_arc = Arc::new(Mutex::new(vec![]));
+        let mut context = CommandContextMock::new()
+            .transact_params(&transact_params_arc)
+            .transact_result(Ok(UiPardonResponse {}.tmb(0)));
+        let stdout_arc = context.stdout_arc();
+        let subject = BansCommand {
+            action: BansAction::Pardon {
+                wallet: WALLET.to_string(),
+            },
+        };
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        let transact_params = transact_params_arc.lock().unwrap();
+        assert_eq!(
+            *transact_params,
+            vec![(
+                UiPardonRequest {
+                    wallet: WALLET.to_string()
+                }
+                .tmb(0),
+                STANDARD_COMMAND_TIMEOUT_MILLIS
+            )]
+        );
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            format!("Wallet {} has been pardoned.\n", WALLET)
+        );
+    }
+
+    #[test]
+    fn bans_command_shows_ban_list_errors_plainly() {
+        let mut context = CommandContextMock::new().transact_result(Err(
+            ContextError::PayloadError(BAN_LIST_ERROR, format!("Wallet {} is not banned", WALLET)),
+        ));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = BansCommand {
+            action: BansAction::Pardon {
+                wallet: WALLET.to_string(),
+            },
+        };
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(
+            result,
+            Err(Payload(
+                BAN_LIST_ERROR,
+                format!("Wallet {} is not banned", WALLET)
+            ))
+        );
+        assert_eq!(stdout_arc.lock().unwrap().get_string(), String::new());
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            format!("Wallet {} is not banned\n", WALLET)
+        );
+    }
+
+    #[test]
+    fn bans_command_sad_path() {
+        let mut context = CommandContextMock::new()
+            .transact_result(Err(ContextError::ConnectionDropped("Booga".to_string())));
+        let stderr_arc = context.stderr_arc();
+        let subject = BansCommand {
+            action: BansAction::List,
+        };
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(
+            result,
+            Err(CommandError::ConnectionProblem("Booga".to_string()))
+        );
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "Bans command failed: ConnectionProblem(\"Booga\")\n"
+        );
+    }
+}
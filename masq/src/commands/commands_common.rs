@@ -12,6 +12,8 @@ use std::any::Any;
 use std::fmt::Debug;
 use std::fmt::Display;
 use std::io::Write;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
 
 pub const STANDARD_COMMAND_TIMEOUT_MILLIS: u64 = 1000;
 pub const STANDARD_COLUMN_WIDTH: usize = 33;
@@ -117,6 +119,14 @@ pub(in crate::commands) fn dump_parameter_line(stream: &mut dyn Write, name: &st
     );
 }
 
+// Falls back to the raw number of seconds if the timestamp is out of the representable range
+pub(in crate::commands) fn format_unix_timestamp(timestamp: i64) -> String {
+    OffsetDateTime::from_unix_timestamp(timestamp)
+        .ok()
+        .and_then(|date_time| date_time.format(&Rfc3339).ok())
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -135,6 +145,12 @@ mod tests {
         assert_eq!(STANDARD_COLUMN_WIDTH, 33)
     }
 
+    #[test]
+    fn format_unix_timestamp_works() {
+        assert_eq!(format_unix_timestamp(1_700_000_000), "2023-11-14T22:13:20Z");
+        assert_eq!(format_unix_timestamp(i64::MAX), i64::MAX.to_string());
+    }
+
     #[test]
     fn two_way_transaction_passes_dropped_connection_error() {
         let mut context = CommandContextMock::new()
@@ -0,0 +1,259 @@
+// Copyright (c) 2024, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::command_context::CommandContext;
+use crate::commands::commands_common::CommandError::Payload;
+use crate::commands::commands_common::{dump_parameter_line, transaction, Command, CommandError};
+use clap::{App, Arg, SubCommand};
+use masq_lib::as_any_ref_in_trait_impl;
+use masq_lib::constants::COUNTRY_DATABASE_ERROR;
+use masq_lib::messages::{UiCountryDatabaseRequest, UiCountryDatabaseResponse};
+use masq_lib::short_writeln;
+use std::io::Write;
+
+// Reloading means decompressing the whole database, which takes a while for real DB-IP data
+pub const COUNTRY_DATABASE_COMMAND_TIMEOUT_MILLIS: u64 = 15000;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CountryDatabaseCommand {
+    pub reload: bool,
+}
+
+const COUNTRY_DATABASE_ABOUT: &str =
+    "Displays the version of the IP-to-country database the Node uses to locate other Nodes \
+    and choose exit countries.";
+const RELOAD_HELP: &str =
+    "Reads the database file in the Node's data directory again before displaying its version. \
+    If the file is missing, the Node goes back to the database it was built with; if the file is \
+    unusable, the Node keeps the database it has.";
+
+pub fn country_database_subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("country-database")
+        .about(COUNTRY_DATABASE_ABOUT)
+        .arg(
+            Arg::with_name("reload")
+                .long("reload")
+                .help(RELOAD_HELP)
+                .takes_value(false)
+                .required(false),
+        )
+}
+
+impl Command for CountryDatabaseCommand {
+    fn execute(&self, context: &mut dyn CommandContext) -> Result<(), CommandError> {
+        let input = UiCountryDatabaseRequest {
+            reload: self.reload,
+        };
+        let output: Result<UiCountryDatabaseResponse, CommandError> =
+            transaction(input, context, COUNTRY_DATABASE_COMMAND_TIMEOUT_MILLIS);
+        match output {
+            Ok(response) => {
+                Self::dump_database(context.stdout(), response);
+                Ok(())
+            }
+            Err(Payload(code, message)) if code == COUNTRY_DATABASE_ERROR => {
+                short_writeln!(context.stderr(), "{}", message);
+                Err(Payload(code, message))
+            }
+            Err(e) => {
+                short_writeln!(context.stderr(), "Country database command failed: {:?}", e);
+                Err(e)
+            }
+        }
+    }
+
+    as_any_ref_in_trait_impl!();
+}
+
+impl CountryDatabaseCommand {
+    pub fn new(pieces: &[String]) -> Result<Self, String> {
+        let matches = match country_database_subcommand().get_matches_from_safe(pieces) {
+            Ok(matches) => matches,
+            Err(e) => return Err(format!("{}", e)),
+        };
+        Ok(Self {
+            reload: matches.is_present("reload"),
+        })
+    }
+
+    fn dump_database(stream: &mut dyn Write, response: UiCountryDatabaseResponse) {
+        dump_parameter_line(stream, "NAME", "VALUE");
+        dump_parameter_line(
+            stream,
+            "Version:",
+            &response
+                .version_opt
+                .unwrap_or_else(|| "[Built into the Node]".to_string()),
+        );
+        dump_parameter_line(stream, "Data file:", &response.data_file_path);
+        dump_parameter_line(stream, "Countries:", &response.country_count.to_string());
+        dump_parameter_line(
+            stream,
+            "IPv4 blocks:",
+            &response.ipv4_block_count.to_string(),
+        );
+        dump_parameter_line(
+            stream,
+            "IPv6 blocks:",
+            &response.ipv6_block_count.to_string(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_context::ContextError;
+    use crate::command_factory::{CommandFactory, CommandFactoryReal};
+    use crate::test_utils::mocks::CommandContextMock;
+    use masq_lib::messages::ToMessageBody;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn constants_have_correct_values() {
+        assert_eq!(COUNTRY_DATABASE_COMMAND_TIMEOUT_MILLIS, 15000);
+        assert_eq!(
+            COUNTRY_DATABASE_ABOUT,
+            "Displays the version of the IP-to-country database the Node uses to locate other Nodes \
+            and choose exit countries."
+        );
+        assert_eq!(
+            RELOAD_HELP,
+            "Reads the database file in the Node's data directory again before displaying its version. \
+            If the file is missing, the Node goes back to the database it was built with; if the file is \
+            unusable, the Node keeps the database it has."
+        );
+    }
+
+    #[test]
+    fn command_factory_interprets_reload() {
+        let factory = CommandFactoryReal::new();
+        let plain = factory.make(&["country-database".to_string()]).unwrap();
+        let reload = factory
+            .make(&["country-database".to_string(), "--reload".to_string()])
+            .unwrap();
+
+        assert_eq!(
+            plain.as_any().downcast_ref::<CountryDatabaseCommand>(),
+            Some(&CountryDatabaseCommand { reload: false })
+        );
+        assert_eq!(
+            reload.as_any().downcast_ref::<CountryDatabaseCommand>(),
+            Some(&CountryDatabaseCommand { reload: true })
+        );
+    }
+
+    #[test]
+    fn country_database_command_happy_path() {
+        let transact_params_arc = Arc::new(Mutex::new(vec![]));
+        let response = UiCountryDatabaseResponse {
+            version_opt: Some("2024-06".to_string()),
+            data_file_path: "/home/booga/.local/share/MASQ/polygon-mainnet/ip_country.dat"
+                .to_string(),
+            country_count: 251,
+            ipv4_block_count: 1234,
+            ipv6_block_count: 2345,
+        };
+        let mut context = CommandContextMock::new()
+            .transact_params(&transact_params_arc)
+            .transact_result(Ok(response.tmb(4321)));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = CountryDatabaseCommand { reload: true };
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        let transact_params = transact_params_arc.lock().unwrap();
+        assert_eq!(
+            *transact_params,
+            vec![(
+                UiCountryDatabaseRequest { reload: true }.tmb(0),
+                COUNTRY_DATABASE_COMMAND_TIMEOUT_MILLIS
+            )]
+        );
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            "\
+|NAME                              VALUE
+|Version:                          2024-06
+|Data file:                        /home/booga/.local/share/MASQ/polygon-mainnet/ip_country.dat
+|Countries:                        251
+|IPv4 blocks:                      1234
+|IPv6 blocks:                      2345
+"
+            .replace('|', "")
+        );
+        assert_eq!(stderr_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn country_database_command_shows_built_in_database() {
+        let response = UiCountryDatabaseResponse {
+            version_opt: None,
+            data_file_path: "/data/ip_country.dat".to_string(),
+            country_count: 250,
+            ipv4_block_count: 1234,
+            ipv6_block_count: 2345,
+        };
+        let mut context = CommandContextMock::new().transact_result(Ok(response.tmb(4321)));
+        let stdout_arc = context.stdout_arc();
+        let subject = CountryDatabaseCommand { reload: false };
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            "\
+|NAME                              VALUE
+|Version:                          [Built into the Node]
+|Data file:                        /data/ip_country.dat
+|Countries:                        250
+|IPv4 blocks:                      1234
+|IPv6 blocks:                      2345
+"
+            .replace('|', "")
+        );
+    }
+
+    #[test]
+    fn country_database_command_explains_unusable_data_file() {
+        let mut context = CommandContextMock::new().transact_result(Err(
+            ContextError::PayloadError(COUNTRY_DATABASE_ERROR, "it is truncated".to_string()),
+        ));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = CountryDatabaseCommand { reload: true };
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(
+            result,
+            Err(Payload(
+                COUNTRY_DATABASE_ERROR,
+                "it is truncated".to_string()
+            ))
+        );
+        assert_eq!(stdout_arc.lock().unwrap().get_string(), String::new());
+        assert_eq!(stderr_arc.lock().unwrap().get_string(), "it is truncated\n");
+    }
+
+    #[test]
+    fn country_database_command_handles_send_failure() {
+        let mut context = CommandContextMock::new()
+            .transact_result(Err(ContextError::ConnectionDropped("blah".to_string())));
+        let stderr_arc = context.stderr_arc();
+        let subject = CountryDatabaseCommand { reload: false };
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(
+            result,
+            Err(CommandError::ConnectionProblem("blah".to_string()))
+        );
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "Country database command failed: ConnectionProblem(\"blah\")\n"
+        );
+    }
+}
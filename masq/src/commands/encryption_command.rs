@@ -0,0 +1,191 @@
+// Copyright (c) 2024, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::command_context::CommandContext;
+use crate::commands::commands_common::{
+    dump_parameter_line, transaction, Command, CommandError, STANDARD_COMMAND_TIMEOUT_MILLIS,
+};
+use clap::{App, SubCommand};
+use masq_lib::as_any_ref_in_trait_impl;
+use masq_lib::messages::{UiEncryptionRequest, UiEncryptionResponse};
+use masq_lib::short_writeln;
+use std::io::Write;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct EncryptionCommand {}
+
+const ENCRYPTION_SUBCOMMAND_ABOUT: &str =
+    "Displays how the secrets in the Node's database are encrypted: the key-derivation \
+     function and its parameters. A database written by an older Node is re-encrypted the next \
+     time its password is supplied. Only valid if Node is already running.";
+
+pub fn encryption_subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("encryption").about(ENCRYPTION_SUBCOMMAND_ABOUT)
+}
+
+impl Command for EncryptionCommand {
+    fn execute(&self, context: &mut dyn CommandContext) -> Result<(), CommandError> {
+        let input = UiEncryptionRequest {};
+        let output: Result<UiEncryptionResponse, CommandError> =
+            transaction(input, context, STANDARD_COMMAND_TIMEOUT_MILLIS);
+        match output {
+            Ok(response) => {
+                Self::dump_encryption(context.stdout(), response);
+                Ok(())
+            }
+            Err(e) => {
+                short_writeln!(context.stderr(), "Encryption retrieval failed: {:?}", e);
+                Err(e)
+            }
+        }
+    }
+
+    as_any_ref_in_trait_impl!();
+}
+
+impl EncryptionCommand {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn dump_encryption(stream: &mut dyn Write, response: UiEncryptionResponse) {
+        let scheme = match response.scheme.as_str() {
+            "none" => "[no database password]",
+            "legacy" => "legacy (re-encrypted when the password is next supplied)",
+            other => other,
+        };
+        dump_parameter_line(stream, "NAME", "VALUE");
+        dump_parameter_line(stream, "Key derivation:", scheme);
+        if let Some(ops_limit) = response.ops_limit_opt {
+            dump_parameter_line(stream, "Iterations:", &ops_limit.to_string());
+        }
+        if let Some(mem_limit_kib) = response.mem_limit_kib_opt {
+            dump_parameter_line(stream, "Memory:", &format!("{} KiB", mem_limit_kib));
+        }
+        if let Some(salt) = response.salt_opt {
+            dump_parameter_line(stream, "Salt:", &salt);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_context::ContextError;
+    use crate::command_factory::{CommandFactory, CommandFactoryReal};
+    use crate::test_utils::mocks::CommandContextMock;
+    use masq_lib::messages::ToMessageBody;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn constants_have_correct_values() {
+        assert_eq!(
+            ENCRYPTION_SUBCOMMAND_ABOUT,
+            "Displays how the secrets in the Node's database are encrypted: the key-derivation \
+             function and its parameters. A database written by an older Node is re-encrypted \
+             the next time its password is supplied. Only valid if Node is already running."
+        );
+    }
+
+    #[test]
+    fn command_factory_makes_encryption_command() {
+        let factory = CommandFactoryReal::new();
+
+        let result = factory.make(&["encryption".to_string()]).unwrap();
+
+        assert_eq!(
+            result.as_any().downcast_ref::<EncryptionCommand>(),
+            Some(&EncryptionCommand {})
+        );
+    }
+
+    #[test]
+    fn encryption_command_shows_kdf_parameters() {
+        let transact_params_arc = Arc::new(Mutex::new(vec![]));
+        let response = UiEncryptionResponse {
+            scheme: "argon2id".to_string(),
+            ops_limit_opt: Some(2),
+            mem_limit_kib_opt: Some(65536),
+            salt_opt: Some("0102030405060708090a0b0c0d0e0f10".to_string()),
+        };
+        let mut context = CommandContextMock::new()
+            .transact_params(&transact_params_arc)
+            .transact_result(Ok(response.tmb(4321)));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = EncryptionCommand::new();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        let transact_params = transact_params_arc.lock().unwrap();
+        assert_eq!(
+            *transact_params,
+            vec![(
+                UiEncryptionRequest {}.tmb(0),
+                STANDARD_COMMAND_TIMEOUT_MILLIS
+            )]
+        );
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            "\
+|NAME                              VALUE
+|Key derivation:                   argon2id
+|Iterations:                       2
+|Memory:                           65536 KiB
+|Salt:                             0102030405060708090a0b0c0d0e0f10
+"
+            .replace('|', "")
+        );
+        assert_eq!(stderr_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn encryption_command_explains_legacy_and_passwordless_databases() {
+        let make_response = |scheme: &str| UiEncryptionResponse {
+            scheme: scheme.to_string(),
+            ops_limit_opt: None,
+            mem_limit_kib_opt: None,
+            salt_opt: None,
+        };
+        let mut context = CommandContextMock::new()
+            .transact_result(Ok(make_response("legacy").tmb(1234)))
+            .transact_result(Ok(make_response("none").tmb(4321)));
+        let stdout_arc = context.stdout_arc();
+        let subject = EncryptionCommand::new();
+
+        let legacy_result = subject.execute(&mut context);
+        let none_result = subject.execute(&mut context);
+
+        assert_eq!(legacy_result, Ok(()));
+        assert_eq!(none_result, Ok(()));
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            "\
+|NAME                              VALUE
+|Key derivation:                   legacy (re-encrypted when the password is next supplied)
+|NAME                              VALUE
+|Key derivation:                   [no database password]
+"
+            .replace('|', "")
+        );
+    }
+
+    #[test]
+    fn encryption_command_handles_send_failure() {
+        let mut context = CommandContextMock::new()
+            .transact_result(Err(ContextError::ConnectionDropped("blah".to_string())));
+        let stderr_arc = context.stderr_arc();
+        let subject = EncryptionCommand::new();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(
+            result,
+            Err(CommandError::ConnectionProblem("blah".to_string()))
+        );
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "Encryption retrieval failed: ConnectionProblem(\"blah\")\n"
+        );
+    }
+}
@@ -139,6 +139,15 @@ impl Command for SetExitLocationCommand {
                 {
                     short_writeln!(context.stdout(), "Exit location is Unset.");
                 }
+                for mismatch in exit_location_response.country_mismatches {
+                    short_writeln!(
+                        context.stdout(),
+                        "Neighbor {} claims to be in {}, but its IP address is in {}; it is not used for exit-location",
+                        mismatch.public_key,
+                        mismatch.declared_country,
+                        mismatch.observed_country
+                    );
+                }
                 Ok(())
             }
             Err(Payload(code, message)) => {
@@ -210,7 +219,7 @@ pub mod tests {
     use crate::test_utils::mocks::CommandContextMock;
     use masq_lib::constants::EXIT_COUNTRY_MISSING_COUNTRIES_ERROR;
     use masq_lib::messages::{
-        CountryGroups, ExitLocation, ToMessageBody, UiSetExitLocationRequest,
+        CountryGroups, ExitLocation, ToMessageBody, UiCountryMismatch, UiSetExitLocationRequest,
         UiSetExitLocationResponse,
     };
     use std::sync::{Arc, Mutex};
@@ -291,6 +300,7 @@ pub mod tests {
             exit_country_selection: vec![],
             exit_countries: None,
             missing_countries: vec![],
+            country_mismatches: vec![],
         }
         .tmb(1234));
 
@@ -337,6 +347,7 @@ pub mod tests {
                 ],
                 exit_countries: None,
                 missing_countries: vec![],
+                country_mismatches: vec![],
             }
             .tmb(0)));
         let stderr_arc = context.stderr_arc();
@@ -395,6 +406,7 @@ pub mod tests {
                 }],
                 exit_countries: None,
                 missing_countries: vec![],
+                country_mismatches: vec![],
             }
             .tmb(0)));
         let stdout_arc = context.stdout_arc();
@@ -456,6 +468,7 @@ pub mod tests {
                 exit_country_selection: vec![],
                 exit_countries: Some(vec!["CZ".to_string()]),
                 missing_countries: vec![],
+                country_mismatches: vec![],
             }
             .tmb(0)));
         let stderr_arc = context.stderr_arc();
@@ -489,6 +502,50 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn reports_neighbors_whose_countries_do_not_match_their_ip_addresses() {
+        let mut context =
+            CommandContextMock::new().transact_result(Ok(UiSetExitLocationResponse {
+                fallback_routing: true,
+                exit_country_selection: vec![],
+                exit_countries: None,
+                missing_countries: vec![],
+                country_mismatches: vec![
+                    UiCountryMismatch {
+                        public_key: "AQIDBA".to_string(),
+                        declared_country: "CZ".to_string(),
+                        observed_country: "US".to_string(),
+                    },
+                    UiCountryMismatch {
+                        public_key: "BAUGBw".to_string(),
+                        declared_country: "SK".to_string(),
+                        observed_country: "AU".to_string(),
+                    },
+                ],
+            }
+            .tmb(0)));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = SetExitLocationCommand::new(&[
+            "exit-location".to_string(),
+            "--fallback-routing".to_string(),
+        ])
+        .unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            "Fallback Routing is set.\n\
+            Exit location is Unset.\n\
+            Neighbor AQIDBA claims to be in CZ, but its IP address is in US; it is not used for exit-location\n\
+            Neighbor BAUGBw claims to be in SK, but its IP address is in AU; it is not used for exit-location\n"
+                .to_string()
+        );
+        assert_eq!(stderr_arc.lock().unwrap().get_string(), "".to_string());
+    }
+
     #[test]
     fn providing_show_countries_with_country_codes_fails() {
         let result = SetExitLocationCommand::new(&[
@@ -0,0 +1,403 @@
+// Copyright (c) 2024, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::command_context::CommandContext;
+use crate::commands::commands_common::{format_unix_timestamp, transaction, Command, CommandError};
+use clap::{App, Arg, SubCommand};
+use masq_lib::as_any_ref_in_trait_impl;
+use masq_lib::messages::{UiLedgerEntry, UiLedgerExportRequest, UiLedgerExportResponse};
+use masq_lib::short_writeln;
+use std::io::Write;
+use time::{Date, Month};
+
+// A ledger covering years of traffic is a big message
+pub const EXPORT_LEDGER_COMMAND_TIMEOUT_MILLIS: u64 = 30000;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LedgerExportFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ExportLedgerCommand {
+    pub start_timestamp_opt: Option<i64>,
+    pub end_timestamp_opt: Option<i64>,
+    pub format: LedgerExportFormat,
+}
+
+const EXPORT_LEDGER_ABOUT: &str =
+    "Writes the Node's accounting ledger to the screen: its service charges, summed up per \
+    counterparty for each hour, and every payment it sent or received, oldest first. Redirect the \
+    output to a file to keep it.";
+const FROM_HELP: &str = "The first day (YYYY-MM-DD, UTC) to export. Defaults to the beginning of \
+    the ledger.";
+const TO_HELP: &str = "The last day (YYYY-MM-DD, UTC) to export, included entirely. Defaults to \
+    the end of the ledger.";
+const FORMAT_HELP: &str =
+    "Whether to write the ledger as comma-separated values or as a JSON array.";
+
+pub fn export_ledger_subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("export-ledger")
+        .about(EXPORT_LEDGER_ABOUT)
+        .arg(
+            Arg::with_name("from")
+                .help(FROM_HELP)
+                .long("from")
+                .value_name("FROM")
+                .takes_value(true)
+                .required(false)
+                .validator(validate_date),
+        )
+        .arg(
+            Arg::with_name("to")
+                .help(TO_HELP)
+                .long("to")
+                .value_name("TO")
+                .takes_value(true)
+                .required(false)
+                .validator(validate_date),
+        )
+        .arg(
+            Arg::with_name("format")
+                .help(FORMAT_HELP)
+                .long("format")
+                .value_name("FORMAT")
+                .takes_value(true)
+                .possible_values(&["csv", "json"])
+                .default_value("csv")
+                .case_insensitive(true),
+        )
+}
+
+fn validate_date(date: String) -> Result<(), String> {
+    parse_date(&date).map(|_| ())
+}
+
+fn parse_date(date: &str) -> Result<Date, String> {
+    let complain = || format!("'{}' is not a date in the form YYYY-MM-DD", date);
+    let pieces = date.split('-').collect::<Vec<&str>>();
+    if pieces.len() != 3 {
+        return Err(complain());
+    }
+    let year = pieces[0].parse::<i32>().map_err(|_| complain())?;
+    let month = pieces[1].parse::<u8>().map_err(|_| complain())?;
+    let day = pieces[2].parse::<u8>().map_err(|_| complain())?;
+    let month = Month::try_from(month).map_err(|_| complain())?;
+    Date::from_calendar_date(year, month, day).map_err(|_| complain())
+}
+
+fn start_of_day(date: Date) -> i64 {
+    date.midnight().assume_utc().unix_timestamp()
+}
+
+impl Command for ExportLedgerCommand {
+    fn execute(&self, context: &mut dyn CommandContext) -> Result<(), CommandError> {
+        let input = UiLedgerExportRequest {
+            start_timestamp_opt: self.start_timestamp_opt,
+            end_timestamp_opt: self.end_timestamp_opt,
+        };
+        let output: Result<UiLedgerExportResponse, CommandError> =
+            transaction(input, context, EXPORT_LEDGER_COMMAND_TIMEOUT_MILLIS);
+        match output {
+            Ok(response) => {
+                match self.format {
+                    LedgerExportFormat::Csv => Self::dump_csv(context.stdout(), &response.entries),
+                    LedgerExportFormat::Json => {
+                        Self::dump_json(context.stdout(), &response.entries)
+                    }
+                }
+                Ok(())
+            }
+            Err(e) => {
+                short_writeln!(context.stderr(), "Ledger export failed: {:?}", e);
+                Err(e)
+            }
+        }
+    }
+
+    as_any_ref_in_trait_impl!();
+}
+
+impl ExportLedgerCommand {
+    pub fn new(pieces: &[String]) -> Result<Self, String> {
+        let matches = match export_ledger_subcommand().get_matches_from_safe(pieces) {
+            Ok(matches) => matches,
+            Err(e) => return Err(format!("{}", e)),
+        };
+        let date_opt = |name: &str| {
+            matches
+                .value_of(name)
+                .map(|date| parse_date(date).expect("clap validator let a bad date through"))
+        };
+        let start_timestamp_opt = date_opt("from").map(start_of_day);
+        let end_timestamp_opt = date_opt("to").map(|date| start_of_day(date) + SECONDS_PER_DAY);
+        if let (Some(start), Some(end)) = (start_timestamp_opt, end_timestamp_opt) {
+            if start >= end {
+                return Err("The --from date must not be later than the --to date".to_string());
+            }
+        }
+        let format = match matches
+            .value_of("format")
+            .expect("format parameter is not properly defaulted")
+            .to_lowercase()
+            .as_str()
+        {
+            "json" => LedgerExportFormat::Json,
+            _ => LedgerExportFormat::Csv,
+        };
+        Ok(Self {
+            start_timestamp_opt,
+            end_timestamp_opt,
+            format,
+        })
+    }
+
+    fn dump_csv(stream: &mut dyn Write, entries: &[UiLedgerEntry]) {
+        short_writeln!(stream, "timestamp,kind,wallet,amount_wei,transaction_hash");
+        entries.iter().for_each(|entry| {
+            short_writeln!(
+                stream,
+                "{},{},{},{},{}",
+                format_unix_timestamp(entry.timestamp),
+                entry.kind,
+                entry.wallet,
+                entry.amount_wei,
+                entry.transaction_hash_opt.as_deref().unwrap_or("")
+            )
+        });
+    }
+
+    fn dump_json(stream: &mut dyn Write, entries: &[UiLedgerEntry]) {
+        let json =
+            serde_json::to_string_pretty(entries).expect("Ledger entries failed to serialize");
+        short_writeln!(stream, "{}", json);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_context::ContextError;
+    use crate::command_factory::{CommandFactory, CommandFactoryError, CommandFactoryReal};
+    use crate::test_utils::mocks::CommandContextMock;
+    use masq_lib::messages::ToMessageBody;
+    use std::sync::{Arc, Mutex};
+
+    fn make_entries() -> Vec<UiLedgerEntry> {
+        vec![
+            UiLedgerEntry {
+                timestamp: 1_700_000_000,
+                kind: "exit_provided".to_string(),
+                wallet: "0x000000000000000000000000000000000000abcd".to_string(),
+                amount_wei: "123456".to_string(),
+                transaction_hash_opt: None,
+            },
+            UiLedgerEntry {
+                timestamp: 1_700_086_400,
+                kind: "payment_received".to_string(),
+                wallet: "0x000000000000000000000000000000000000abcd".to_string(),
+                amount_wei: "340282366920938463463374607431768211455".to_string(),
+                transaction_hash_opt: Some(
+                    "0x0000000000000000000000000000000000000000000000000000000000001a2b"
+                        .to_string(),
+                ),
+            },
+        ]
+    }
+
+    #[test]
+    fn constants_have_correct_values() {
+        assert_eq!(EXPORT_LEDGER_COMMAND_TIMEOUT_MILLIS, 30000);
+        assert_eq!(
+            EXPORT_LEDGER_ABOUT,
+            "Writes the Node's accounting ledger to the screen: its service charges, summed up per \
+            counterparty for each hour, and every payment it sent or received, oldest first. Redirect \
+            the output to a file to keep it."
+        );
+        assert_eq!(
+            FROM_HELP,
+            "The first day (YYYY-MM-DD, UTC) to export. Defaults to the beginning of the ledger."
+        );
+        assert_eq!(
+            TO_HELP,
+            "The last day (YYYY-MM-DD, UTC) to export, included entirely. Defaults to the end of the ledger."
+        );
+        assert_eq!(
+            FORMAT_HELP,
+            "Whether to write the ledger as comma-separated values or as a JSON array."
+        );
+    }
+
+    #[test]
+    fn command_factory_interprets_dates_and_format() {
+        let factory = CommandFactoryReal::new();
+        let plain = factory.make(&["export-ledger".to_string()]).unwrap();
+        let bounded = factory
+            .make(&[
+                "export-ledger".to_string(),
+                "--from".to_string(),
+                "2023-11-14".to_string(),
+                "--to".to_string(),
+                "2023-11-15".to_string(),
+                "--format".to_string(),
+                "JSON".to_string(),
+            ])
+            .unwrap();
+
+        assert_eq!(
+            plain.as_any().downcast_ref::<ExportLedgerCommand>(),
+            Some(&ExportLedgerCommand {
+                start_timestamp_opt: None,
+                end_timestamp_opt: None,
+                format: LedgerExportFormat::Csv,
+            })
+        );
+        assert_eq!(
+            bounded.as_any().downcast_ref::<ExportLedgerCommand>(),
+            Some(&ExportLedgerCommand {
+                start_timestamp_opt: Some(1_699_920_000),
+                end_timestamp_opt: Some(1_700_092_800),
+                format: LedgerExportFormat::Json,
+            })
+        );
+    }
+
+    #[test]
+    fn command_factory_rejects_malformed_dates() {
+        let factory = CommandFactoryReal::new();
+
+        vec!["2023-13-01", "2023-02-30", "20231114", "yesterday"]
+            .into_iter()
+            .for_each(|date| {
+                let result = factory.make(&[
+                    "export-ledger".to_string(),
+                    "--from".to_string(),
+                    date.to_string(),
+                ]);
+
+                match result {
+                    Err(CommandFactoryError::CommandSyntax(msg)) => assert!(
+                        msg.contains(&format!("'{}' is not a date in the form YYYY-MM-DD", date)),
+                        "{}",
+                        msg
+                    ),
+                    x => panic!(
+                        "Expected syntax error for {}, got {:?}",
+                        date,
+                        x.map(|_| ())
+                    ),
+                }
+            });
+    }
+
+    #[test]
+    fn command_factory_rejects_an_inverted_range() {
+        let factory = CommandFactoryReal::new();
+
+        let result = factory.make(&[
+            "export-ledger".to_string(),
+            "--from".to_string(),
+            "2023-11-15".to_string(),
+            "--to".to_string(),
+            "2023-11-14".to_string(),
+        ]);
+
+        assert_eq!(
+            result.map(|_| ()),
+            Err(CommandFactoryError::CommandSyntax(
+                "The --from date must not be later than the --to date".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn export_ledger_command_writes_csv() {
+        let transact_params_arc = Arc::new(Mutex::new(vec![]));
+        let mut context = CommandContextMock::new()
+            .transact_params(&transact_params_arc)
+            .transact_result(Ok(UiLedgerExportResponse {
+                entries: make_entries(),
+            }
+            .tmb(0)));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = ExportLedgerCommand {
+            start_timestamp_opt: Some(1_699_920_000),
+            end_timestamp_opt: None,
+            format: LedgerExportFormat::Csv,
+        };
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        let transact_params = transact_params_arc.lock().unwrap();
+        assert_eq!(
+            *transact_params,
+            vec![(
+                UiLedgerExportRequest {
+                    start_timestamp_opt: Some(1_699_920_000),
+                    end_timestamp_opt: None,
+                }
+                .tmb(0),
+                EXPORT_LEDGER_COMMAND_TIMEOUT_MILLIS
+            )]
+        );
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            "timestamp,kind,wallet,amount_wei,transaction_hash\n\
+            2023-11-14T22:13:20Z,exit_provided,0x000000000000000000000000000000000000abcd,123456,\n\
+            2023-11-15T22:13:20Z,payment_received,0x000000000000000000000000000000000000abcd,\
+            340282366920938463463374607431768211455,\
+            0x0000000000000000000000000000000000000000000000000000000000001a2b\n"
+        );
+        assert_eq!(stderr_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn export_ledger_command_writes_json() {
+        let mut context = CommandContextMock::new().transact_result(Ok(UiLedgerExportResponse {
+            entries: make_entries(),
+        }
+        .tmb(0)));
+        let stdout_arc = context.stdout_arc();
+        let subject = ExportLedgerCommand {
+            start_timestamp_opt: None,
+            end_timestamp_opt: None,
+            format: LedgerExportFormat::Json,
+        };
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        let stdout = stdout_arc.lock().unwrap().get_string();
+        let entries: Vec<UiLedgerEntry> = serde_json::from_str(&stdout).unwrap();
+        assert_eq!(entries, make_entries());
+    }
+
+    #[test]
+    fn export_ledger_command_sad_path() {
+        let mut context = CommandContextMock::new()
+            .transact_result(Err(ContextError::ConnectionDropped("Booga".to_string())));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = ExportLedgerCommand {
+            start_timestamp_opt: None,
+            end_timestamp_opt: None,
+            format: LedgerExportFormat::Csv,
+        };
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(
+            result,
+            Err(CommandError::ConnectionProblem("Booga".to_string()))
+        );
+        assert_eq!(stdout_arc.lock().unwrap().get_string(), String::new());
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "Ledger export failed: ConnectionProblem(\"Booga\")\n"
+        );
+    }
+}
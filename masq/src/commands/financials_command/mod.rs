@@ -1029,6 +1029,7 @@ mod tests {
                             age_s: 5645405400,
                             balance_gwei: 68843325667,
                             pending_payable_hash_opt: None,
+                            written_off: false,
                         },
                         UiPayableAccount {
                             wallet: "0x6DbcCaC5596b7ac986ff8F7ca06F212aEB444440".to_string(),
@@ -1038,6 +1039,7 @@ mod tests {
                                 "0x0290db1d56121112f4d45c1c3f36348644f6afd20b759b762f1dba9c4949066e"
                                     .to_string(),
                             ),
+                            written_off: false,
                         },
                     ]),
                     receivable_opt: Some(vec![
@@ -1063,6 +1065,7 @@ mod tests {
                             "0x0290db1d56121112f4d45c1c3f36348644f6afd20b759b762f1dba9c4949066e"
                                 .to_string(),
                         ),
+                        written_off: false,
                     }]),
                     receivable_opt: None,
                 }
@@ -1356,6 +1359,7 @@ mod tests {
                         "0x0290db1d56121112f4d45c1c3f36348644f6afd20b759b762f1dba9c4949066e"
                             .to_string(),
                     ),
+                    written_off: false,
                 }]),
                 receivable_opt: Some(vec![UiReceivableAccount {
                     wallet: "0x8bA50675e590b545D2128905b89039256Eaa24F6".to_string(),
@@ -1607,12 +1611,14 @@ mod tests {
                             "0x3648c8b8c7e067ac30b80b6936159326d564dd13b7ae465b26647154ada2c638"
                                 .to_string(),
                         ),
+                        written_off: false,
                     },
                     UiPayableAccount {
                         wallet: "0xEA674fdac714fd979de3EdF0F56AA9716B198ec8".to_string(),
                         age_s: 28120444,
                         balance_gwei: 97524120,
                         pending_payable_hash_opt: None,
+                        written_off: false,
                     },
                 ]),
                 receivable_opt: Some(vec![
@@ -1693,18 +1699,21 @@ mod tests {
                             "0x5fe272ed1e941cc05fbd624ec4b1546cd03c25d53e24ba2c18b11feb83cd4581"
                                 .to_string(),
                         ),
+                        written_off: false,
                     },
                     UiPayableAccount {
                         wallet: "0xA884A2F1A5Ec6C2e499644666a5E6af97B966888".to_string(),
                         age_s: 70000,
                         balance_gwei: 708090,
                         pending_payable_hash_opt: None,
+                        written_off: false,
                     },
                     UiPayableAccount {
                         wallet: "0x6DbcCaC5596b7ac986ff8F7ca06F212aEB444440".to_string(),
                         age_s: 6089909,
                         balance_gwei: 66658,
                         pending_payable_hash_opt: None,
+                        written_off: false,
                     },
                 ]),
                 receivable_opt: None,
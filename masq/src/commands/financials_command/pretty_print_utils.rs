@@ -27,8 +27,11 @@ pub(in crate::commands::financials_command) mod restricted {
                 self.wallet.to_string(),
                 self.age_s.separate_with_commas(),
                 process_gwei_into_requested_format(self.balance_gwei, is_gwei),
+                // A written-off debt is never pending, so the flag can share the column
                 if let Some(hash) = &self.pending_payable_hash_opt {
                     hash.to_string()
+                } else if self.written_off {
+                    "Written off".to_string()
                 } else {
                     "None".to_string()
                 },
@@ -252,6 +255,7 @@ mod tests {
     use crate::commands::financials_command::pretty_print_utils::restricted::{
         figure_out_max_widths, StringValuesFormattableAccount,
     };
+    use masq_lib::messages::UiPayableAccount;
 
     #[derive(Clone)]
     struct TestAccount {
@@ -311,4 +315,23 @@ mod tests {
         //the second number is always 42 as the length of wallet address
         assert_eq!(result, vec![3, 42, 5, 10])
     }
+
+    #[test]
+    fn payable_account_shows_written_off_flag_in_place_of_pending_tx() {
+        let make_account = |pending_payable_hash_opt: Option<&str>, written_off| UiPayableAccount {
+            wallet: "0x6e250504DdfFDb986C4F0bb8Df162503B4118b05".to_string(),
+            age_s: 1000,
+            balance_gwei: 12,
+            pending_payable_hash_opt: pending_payable_hash_opt.map(|hash| hash.to_string()),
+            written_off,
+        };
+
+        let written_off = make_account(None, true).convert_to_strings(1, true);
+        let unflagged = make_account(None, false).convert_to_strings(2, true);
+        let pending = make_account(Some("0x1234"), false).convert_to_strings(3, true);
+
+        assert_eq!(written_off[4], "Written off");
+        assert_eq!(unflagged[4], "None");
+        assert_eq!(pending[4], "0x1234")
+    }
 }
@@ -0,0 +1,236 @@
+// Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::command_context::CommandContext;
+use crate::commands::commands_common::{
+    dump_parameter_line, format_unix_timestamp, transaction, Command, CommandError,
+    STANDARD_COMMAND_TIMEOUT_MILLIS,
+};
+use clap::{App, SubCommand};
+use masq_lib::as_any_ref_in_trait_impl;
+use masq_lib::messages::{
+    UiGossipRejections, UiGossipRejectionsRequest, UiGossipRejectionsResponse,
+};
+use masq_lib::short_writeln;
+use std::io::Write;
+
+const GOSSIP_REJECTIONS_ABOUT: &str =
+    "Displays, for each peer whose Gossip the Node has refused, how often and why it was refused, \
+    and whether the peer is banned because of it.";
+
+pub fn gossip_rejections_subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("gossip-rejections").about(GOSSIP_REJECTIONS_ABOUT)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct GossipRejectionsCommand {}
+
+impl Command for GossipRejectionsCommand {
+    fn execute(&self, context: &mut dyn CommandContext) -> Result<(), CommandError> {
+        let output: Result<UiGossipRejectionsResponse, CommandError> = transaction(
+            UiGossipRejectionsRequest {},
+            context,
+            STANDARD_COMMAND_TIMEOUT_MILLIS,
+        );
+        match output {
+            Ok(response) if response.peers.is_empty() => {
+                short_writeln!(context.stdout(), "No Gossip has been rejected.");
+                Ok(())
+            }
+            Ok(response) => {
+                response
+                    .peers
+                    .iter()
+                    .for_each(|peer| Self::dump_peer(context.stdout(), peer));
+                Ok(())
+            }
+            Err(e) => {
+                short_writeln!(
+                    context.stderr(),
+                    "Gossip rejections retrieval failed: {:?}",
+                    e
+                );
+                Err(e)
+            }
+        }
+    }
+
+    as_any_ref_in_trait_impl!();
+}
+
+impl GossipRejectionsCommand {
+    pub fn new(pieces: &[String]) -> Result<Self, String> {
+        match gossip_rejections_subcommand().get_matches_from_safe(pieces) {
+            Ok(_) => Ok(GossipRejectionsCommand {}),
+            Err(e) => Err(format!("{}", e)),
+        }
+    }
+
+    fn dump_peer(stream: &mut dyn Write, peer: &UiGossipRejections) {
+        dump_parameter_line(stream, "Peer IP:", &peer.peer_ip);
+        dump_parameter_line(
+            stream,
+            "Over its rate limit:",
+            &peer.rate_limited.to_string(),
+        );
+        dump_parameter_line(
+            stream,
+            "While the Node was overloaded:",
+            &peer.overloaded.to_string(),
+        );
+        dump_parameter_line(stream, "While banned:", &peer.while_banned.to_string());
+        dump_parameter_line(stream, "Malformed:", &peer.malformed.to_string());
+        dump_parameter_line(stream, "Bad signature:", &peer.bad_signature.to_string());
+        dump_parameter_line(
+            stream,
+            "Rejected as malicious:",
+            &peer.malefactor.to_string(),
+        );
+        dump_parameter_line(stream, "Times banned:", &peer.ban_count.to_string());
+        if let Some(banned_until) = peer.banned_until_opt {
+            dump_parameter_line(
+                stream,
+                "Banned until:",
+                &format_unix_timestamp(banned_until as i64),
+            );
+        }
+        short_writeln!(stream, "");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_context::ContextError;
+    use crate::command_factory::{CommandFactory, CommandFactoryReal};
+    use crate::commands::commands_common::CommandError::ConnectionProblem;
+    use crate::test_utils::mocks::CommandContextMock;
+    use masq_lib::messages::ToMessageBody;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn constants_have_correct_values() {
+        assert_eq!(
+            GOSSIP_REJECTIONS_ABOUT,
+            "Displays, for each peer whose Gossip the Node has refused, how often and why it was \
+            refused, and whether the peer is banned because of it."
+        );
+    }
+
+    #[test]
+    fn command_factory_makes_the_command() {
+        let factory = CommandFactoryReal::new();
+
+        let command = factory.make(&["gossip-rejections".to_string()]).unwrap();
+
+        assert_eq!(
+            command.as_any().downcast_ref::<GossipRejectionsCommand>(),
+            Some(&GossipRejectionsCommand {})
+        );
+    }
+
+    #[test]
+    fn displays_the_rejections_of_each_peer() {
+        let transact_params_arc = Arc::new(Mutex::new(vec![]));
+        let mut context = CommandContextMock::new()
+            .transact_params(&transact_params_arc)
+            .transact_result(Ok(UiGossipRejectionsResponse {
+                peers: vec![
+                    UiGossipRejections {
+                        peer_ip: "1.2.3.4".to_string(),
+                        rate_limited: 1,
+                        overloaded: 2,
+                        while_banned: 3,
+                        malformed: 4,
+                        bad_signature: 5,
+                        malefactor: 6,
+                        ban_count: 7,
+                        banned_until_opt: Some(1_700_000_000),
+                    },
+                    UiGossipRejections {
+                        peer_ip: "2.3.4.5".to_string(),
+                        rate_limited: 8,
+                        overloaded: 0,
+                        while_banned: 0,
+                        malformed: 0,
+                        bad_signature: 0,
+                        malefactor: 0,
+                        ban_count: 0,
+                        banned_until_opt: None,
+                    },
+                ],
+            }
+            .tmb(0)));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = GossipRejectionsCommand::new(&["gossip-rejections".to_string()]).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        let transact_params = transact_params_arc.lock().unwrap();
+        assert_eq!(
+            *transact_params,
+            vec![(
+                UiGossipRejectionsRequest {}.tmb(0),
+                STANDARD_COMMAND_TIMEOUT_MILLIS
+            )]
+        );
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            "\
+Peer IP:                          1.2.3.4
+Over its rate limit:              1
+While the Node was overloaded:    2
+While banned:                     3
+Malformed:                        4
+Bad signature:                    5
+Rejected as malicious:            6
+Times banned:                     7
+Banned until:                     2023-11-14T22:13:20Z
+
+Peer IP:                          2.3.4.5
+Over its rate limit:              8
+While the Node was overloaded:    0
+While banned:                     0
+Malformed:                        0
+Bad signature:                    0
+Rejected as malicious:            0
+Times banned:                     0
+
+"
+        );
+        assert_eq!(stderr_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn says_so_when_no_gossip_has_been_rejected() {
+        let mut context = CommandContextMock::new()
+            .transact_result(Ok(UiGossipRejectionsResponse { peers: vec![] }.tmb(0)));
+        let stdout_arc = context.stdout_arc();
+        let subject = GossipRejectionsCommand::new(&["gossip-rejections".to_string()]).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            "No Gossip has been rejected.\n"
+        );
+    }
+
+    #[test]
+    fn reports_failure_to_retrieve_rejections() {
+        let mut context = CommandContextMock::new()
+            .transact_result(Err(ContextError::ConnectionDropped("Booga".to_string())));
+        let stderr_arc = context.stderr_arc();
+        let subject = GossipRejectionsCommand::new(&["gossip-rejections".to_string()]).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Err(ConnectionProblem("Booga".to_string())));
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "Gossip rejections retrieval failed: ConnectionProblem(\"Booga\")\n"
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use clap::{App, SubCommand};
+use clap::{App, Arg, SubCommand};
 use masq_lib::{as_any_ref_in_trait_impl, short_writeln};
 
 use crate::command_context::CommandContext;
@@ -6,38 +6,280 @@ use crate::commands::commands_common::CommandError::Payload;
 use crate::commands::commands_common::{
     transaction, Command, CommandError, STANDARD_COMMAND_TIMEOUT_MILLIS,
 };
-use masq_lib::messages::{UiGetNeighborhoodGraphRequest, UiGetNeighborhoodGraphResponse};
+use masq_lib::messages::{
+    UiGetNeighborhoodGraphRequest, UiGetNeighborhoodGraphResponse, UiNeighborhoodGraphNode,
+};
+use std::collections::HashSet;
+use std::io::Write;
+
+const NEIGHBORHOOD_GRAPH_HELP: &str = "Displays the Node's neighborhood database. By default the \
+    result is delivered in digraph format, documentation at https://graphviz.org/documentation/";
+const FORMAT_HELP: &str = "Whether to deliver the neighborhood as a Graphviz digraph, as a JSON \
+    description of each Node, or as GraphML.";
+const COUNTRY_HELP: &str = "Comma-separated two-letter country codes. Only Nodes in one of these \
+    countries are shown.";
+const MIN_DEGREE_HELP: &str = "Only Nodes with at least this many neighbors are shown.";
+const MAX_DEGREE_HELP: &str = "Only Nodes with at most this many neighbors are shown.";
+const MAX_DISTANCE_HELP: &str = "Only Nodes that this Node can reach through at most this many \
+    neighbor links are shown.";
 
-const NEIGHBORHOOD_GRAPH_HELP: &str = "Use this command plainly, without any flags or arguments. The result will be delivered in digraph format, documentation at https://graphviz.org/documentation/";
+const GRAPHML_NODE_KEYS: [(&str, &str); 12] = [
+    ("nodeAddr", "string"),
+    ("version", "int"),
+    ("countryCode", "string"),
+    ("contradictedCountryCode", "string"),
+    ("acceptsConnections", "boolean"),
+    ("routesData", "boolean"),
+    ("relationship", "string"),
+    ("distance", "int"),
+    ("routingByteRate", "long"),
+    ("routingServiceRate", "long"),
+    ("exitByteRate", "long"),
+    ("exitServiceRate", "long"),
+];
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum NeighborhoodGraphFormat {
+    Dot,
+    Json,
+    GraphMl,
+}
 
 pub fn get_neighborhood_graph_subcommand() -> App<'static, 'static> {
-    SubCommand::with_name("neighborhood-graph").about(NEIGHBORHOOD_GRAPH_HELP)
+    SubCommand::with_name("neighborhood-graph")
+        .about(NEIGHBORHOOD_GRAPH_HELP)
+        .arg(
+            Arg::with_name("format")
+                .help(FORMAT_HELP)
+                .long("format")
+                .value_name("FORMAT")
+                .takes_value(true)
+                .possible_values(&["dot", "json", "graphml"])
+                .default_value("dot")
+                .case_insensitive(true),
+        )
+        .arg(
+            Arg::with_name("country")
+                .help(COUNTRY_HELP)
+                .long("country")
+                .value_name("COUNTRY-CODES")
+                .takes_value(true)
+                .required(false)
+                .validator(validate_country_codes),
+        )
+        .arg(count_arg("min-degree", "MIN-DEGREE", MIN_DEGREE_HELP))
+        .arg(count_arg("max-degree", "MAX-DEGREE", MAX_DEGREE_HELP))
+        .arg(count_arg("max-distance", "MAX-DISTANCE", MAX_DISTANCE_HELP))
+}
+
+fn count_arg<'a>(name: &'a str, value_name: &'a str, help: &'a str) -> Arg<'a, 'a> {
+    Arg::with_name(name)
+        .help(help)
+        .long(name)
+        .value_name(value_name)
+        .takes_value(true)
+        .required(false)
+        .validator(validate_count)
+}
+
+fn validate_country_codes(country_codes: String) -> Result<(), String> {
+    match parse_country_codes(&country_codes)
+        .iter()
+        .find(|cc| cc.len() != 2 || !cc.chars().all(|c| c.is_ascii_alphabetic()))
+    {
+        Some(cc) => Err(format!("'{}' is not a two-letter country code", cc)),
+        None => Ok(()),
+    }
+}
+
+fn parse_country_codes(country_codes: &str) -> Vec<String> {
+    country_codes
+        .split(',')
+        .map(|cc| cc.trim().to_uppercase())
+        .collect()
+}
+
+fn validate_count(count: String) -> Result<(), String> {
+    match count.parse::<u32>() {
+        Ok(_) => Ok(()),
+        Err(_) => Err(format!("'{}' is not a non-negative whole number", count)),
+    }
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
 }
 
 #[derive(Debug, PartialEq, Eq)]
-pub struct GetNeighborhoodGraphCommand {}
+pub struct GetNeighborhoodGraphCommand {
+    pub request: UiGetNeighborhoodGraphRequest,
+    pub format: NeighborhoodGraphFormat,
+}
 
 impl GetNeighborhoodGraphCommand {
     pub fn new(pieces: &[String]) -> Result<Self, String> {
-        match get_neighborhood_graph_subcommand().get_matches_from_safe(pieces) {
-            Ok(_) => Ok(GetNeighborhoodGraphCommand {}),
-            Err(e) => Err(format!("GetNeighborhoodGraphCommand {}", e)),
+        let matches = match get_neighborhood_graph_subcommand().get_matches_from_safe(pieces) {
+            Ok(matches) => matches,
+            Err(e) => return Err(format!("GetNeighborhoodGraphCommand {}", e)),
+        };
+        let count_opt = |name: &str| {
+            matches.value_of(name).map(|count| {
+                count
+                    .parse::<u32>()
+                    .expect("clap validator let a bad count through")
+            })
+        };
+        let request = UiGetNeighborhoodGraphRequest {
+            country_codes_opt: matches.value_of("country").map(parse_country_codes),
+            min_degree_opt: count_opt("min-degree"),
+            max_degree_opt: count_opt("max-degree"),
+            max_distance_opt: count_opt("max-distance"),
+        };
+        if let (Some(min), Some(max)) = (request.min_degree_opt, request.max_degree_opt) {
+            if min > max {
+                return Err(
+                    "The --min-degree value must not be greater than the --max-degree value"
+                        .to_string(),
+                );
+            }
+        }
+        let format = match matches
+            .value_of("format")
+            .expect("format parameter is not properly defaulted")
+            .to_lowercase()
+            .as_str()
+        {
+            "json" => NeighborhoodGraphFormat::Json,
+            "graphml" => NeighborhoodGraphFormat::GraphMl,
+            _ => NeighborhoodGraphFormat::Dot,
+        };
+        Ok(GetNeighborhoodGraphCommand { request, format })
+    }
+
+    fn dump_dot(stream: &mut dyn Write, response: &UiGetNeighborhoodGraphResponse) {
+        short_writeln!(
+            stream,
+            "Graph of the Node's neighborhood database: {}",
+            response.graph.as_str()
+        );
+        if let Some(shortfall) = &response.route_diversity_shortfall_opt {
+            short_writeln!(stream, "Route diversity: {}", shortfall);
         }
     }
+
+    fn dump_json(stream: &mut dyn Write, response: &UiGetNeighborhoodGraphResponse) {
+        let json = serde_json::to_string_pretty(&serde_json::json!({
+            "nodes": response.nodes,
+            "routeDiversityShortfallOpt": response.route_diversity_shortfall_opt,
+        }))
+        .expect("Neighborhood graph failed to serialize");
+        short_writeln!(stream, "{}", json);
+    }
+
+    fn dump_graphml(stream: &mut dyn Write, nodes: &[UiNeighborhoodGraphNode]) {
+        short_writeln!(stream, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>");
+        short_writeln!(
+            stream,
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">"
+        );
+        GRAPHML_NODE_KEYS.iter().for_each(|(name, attr_type)| {
+            short_writeln!(
+                stream,
+                "  <key id=\"{}\" for=\"node\" attr.name=\"{}\" attr.type=\"{}\"/>",
+                name,
+                name,
+                attr_type
+            )
+        });
+        short_writeln!(
+            stream,
+            "  <graph id=\"neighborhood\" edgedefault=\"directed\">"
+        );
+        nodes
+            .iter()
+            .for_each(|node| Self::dump_graphml_node(stream, node));
+        let present: HashSet<&String> = nodes.iter().map(|node| &node.public_key).collect();
+        nodes.iter().for_each(|node| {
+            node.neighbors
+                .iter()
+                .filter(|neighbor| present.contains(neighbor))
+                .for_each(|neighbor| {
+                    short_writeln!(
+                        stream,
+                        "    <edge source=\"{}\" target=\"{}\"/>",
+                        xml_escape(&node.public_key),
+                        xml_escape(neighbor)
+                    )
+                })
+        });
+        short_writeln!(stream, "  </graph>");
+        short_writeln!(stream, "</graphml>");
+    }
+
+    fn dump_graphml_node(stream: &mut dyn Write, node: &UiNeighborhoodGraphNode) {
+        short_writeln!(stream, "    <node id=\"{}\">", xml_escape(&node.public_key));
+        let mut data = vec![];
+        if let Some(node_addr) = &node.node_addr_opt {
+            data.push(("nodeAddr", node_addr.clone()));
+        }
+        data.push(("version", node.version.to_string()));
+        if let Some(country_code) = &node.country_code_opt {
+            data.push(("countryCode", country_code.clone()));
+        }
+        if let Some(country_code) = &node.contradicted_country_code_opt {
+            data.push(("contradictedCountryCode", country_code.clone()));
+        }
+        data.push(("acceptsConnections", node.accepts_connections.to_string()));
+        data.push(("routesData", node.routes_data.to_string()));
+        data.push(("relationship", format!("{:?}", node.relationship)));
+        if let Some(distance) = node.distance_opt {
+            data.push(("distance", distance.to_string()));
+        }
+        data.push((
+            "routingByteRate",
+            node.rate_pack.routing_byte_rate.to_string(),
+        ));
+        data.push((
+            "routingServiceRate",
+            node.rate_pack.routing_service_rate.to_string(),
+        ));
+        data.push(("exitByteRate", node.rate_pack.exit_byte_rate.to_string()));
+        data.push((
+            "exitServiceRate",
+            node.rate_pack.exit_service_rate.to_string(),
+        ));
+        data.into_iter().for_each(|(key, value)| {
+            short_writeln!(
+                stream,
+                "      <data key=\"{}\">{}</data>",
+                key,
+                xml_escape(&value)
+            )
+        });
+        short_writeln!(stream, "    </node>");
+    }
 }
 
 impl Command for GetNeighborhoodGraphCommand {
     fn execute(&self, context: &mut dyn CommandContext) -> Result<(), CommandError> {
-        let input = UiGetNeighborhoodGraphRequest {};
-        let output: Result<UiGetNeighborhoodGraphResponse, CommandError> =
-            transaction(input, context, STANDARD_COMMAND_TIMEOUT_MILLIS);
+        let output: Result<UiGetNeighborhoodGraphResponse, CommandError> = transaction(
+            self.request.clone(),
+            context,
+            STANDARD_COMMAND_TIMEOUT_MILLIS,
+        );
         match output {
-            Ok(neighborhood_graph) => {
-                short_writeln!(
-                    context.stdout(),
-                    "Graph of the Node's neighborhood database: {}",
-                    neighborhood_graph.graph.as_str()
-                );
+            Ok(response) => {
+                match self.format {
+                    NeighborhoodGraphFormat::Dot => Self::dump_dot(context.stdout(), &response),
+                    NeighborhoodGraphFormat::Json => Self::dump_json(context.stdout(), &response),
+                    NeighborhoodGraphFormat::GraphMl => {
+                        Self::dump_graphml(context.stdout(), &response.nodes)
+                    }
+                }
                 Ok(())
             }
             Err(Payload(code, message)) => {
@@ -58,16 +300,278 @@ impl Command for GetNeighborhoodGraphCommand {
 pub mod tests {
     use super::*;
     use crate::test_utils::mocks::CommandContextMock;
-    use masq_lib::messages::ToMessageBody;
+    use masq_lib::messages::{ToMessageBody, UiNeighborRelationship, UiRatePack};
     use std::sync::{Arc, Mutex};
 
+    fn make_nodes() -> Vec<UiNeighborhoodGraphNode> {
+        vec![
+            UiNeighborhoodGraphNode {
+                public_key: "AQIDBA".to_string(),
+                node_addr_opt: Some("1.2.3.4:1234".to_string()),
+                version: 3,
+                rate_pack: UiRatePack {
+                    routing_byte_rate: 1,
+                    routing_service_rate: 2,
+                    exit_byte_rate: 3,
+                    exit_service_rate: 4,
+                },
+                country_code_opt: Some("AU".to_string()),
+                contradicted_country_code_opt: None,
+                accepts_connections: true,
+                routes_data: true,
+                relationship: UiNeighborRelationship::Root,
+                distance_opt: Some(0),
+                neighbors: vec!["AgMEBQ".to_string(), "Yj<&>".to_string()],
+            },
+            UiNeighborhoodGraphNode {
+                public_key: "AgMEBQ".to_string(),
+                node_addr_opt: None,
+                version: 0,
+                rate_pack: UiRatePack {
+                    routing_byte_rate: 5,
+                    routing_service_rate: 6,
+                    exit_byte_rate: 7,
+                    exit_service_rate: 8,
+                },
+                country_code_opt: None,
+                contradicted_country_code_opt: Some("FR".to_string()),
+                accepts_connections: false,
+                routes_data: true,
+                relationship: UiNeighborRelationship::Half,
+                distance_opt: None,
+                neighbors: vec!["AQIDBA".to_string()],
+            },
+        ]
+    }
+
+    fn make_response() -> UiGetNeighborhoodGraphResponse {
+        UiGetNeighborhoodGraphResponse {
+            graph: "digraph db { }".to_string(),
+            nodes: make_nodes(),
+            route_diversity_shortfall_opt: None,
+        }
+    }
+
+    #[test]
+    fn constants_have_correct_values() {
+        assert_eq!(
+            NEIGHBORHOOD_GRAPH_HELP,
+            "Displays the Node's neighborhood database. By default the result is delivered in \
+            digraph format, documentation at https://graphviz.org/documentation/"
+        );
+        assert_eq!(
+            FORMAT_HELP,
+            "Whether to deliver the neighborhood as a Graphviz digraph, as a JSON description of \
+            each Node, or as GraphML."
+        );
+        assert_eq!(
+            COUNTRY_HELP,
+            "Comma-separated two-letter country codes. Only Nodes in one of these countries are \
+            shown."
+        );
+        assert_eq!(
+            MIN_DEGREE_HELP,
+            "Only Nodes with at least this many neighbors are shown."
+        );
+        assert_eq!(
+            MAX_DEGREE_HELP,
+            "Only Nodes with at most this many neighbors are shown."
+        );
+        assert_eq!(
+            MAX_DISTANCE_HELP,
+            "Only Nodes that this Node can reach through at most this many neighbor links are \
+            shown."
+        );
+    }
+
+    #[test]
+    fn filters_and_format_are_parsed() {
+        let result = GetNeighborhoodGraphCommand::new(&[
+            "neighborhood-graph".to_string(),
+            "--format".to_string(),
+            "GraphML".to_string(),
+            "--country".to_string(),
+            "au, fr".to_string(),
+            "--min-degree".to_string(),
+            "2".to_string(),
+            "--max-degree".to_string(),
+            "5".to_string(),
+            "--max-distance".to_string(),
+            "3".to_string(),
+        ]);
+
+        assert_eq!(
+            result,
+            Ok(GetNeighborhoodGraphCommand {
+                request: UiGetNeighborhoodGraphRequest {
+                    country_codes_opt: Some(vec!["AU".to_string(), "FR".to_string()]),
+                    min_degree_opt: Some(2),
+                    max_degree_opt: Some(5),
+                    max_distance_opt: Some(3),
+                },
+                format: NeighborhoodGraphFormat::GraphMl,
+            })
+        );
+    }
+
+    #[test]
+    fn bad_filters_are_rejected() {
+        let make = |args: &[&str]| {
+            let mut pieces = vec!["neighborhood-graph".to_string()];
+            pieces.extend(args.iter().map(|arg| arg.to_string()));
+            GetNeighborhoodGraphCommand::new(&pieces)
+        };
+
+        let bad_country = make(&["--country", "AU,FRA"]).err().unwrap();
+        let bad_degree = make(&["--min-degree", "two"]).err().unwrap();
+        let inverted_degrees = make(&["--min-degree", "3", "--max-degree", "2"]);
+        let bad_format = make(&["--format", "svg"]).err().unwrap();
+
+        assert!(
+            bad_country.contains("'FRA' is not a two-letter country code"),
+            "{}",
+            bad_country
+        );
+        assert!(
+            bad_degree.contains("'two' is not a non-negative whole number"),
+            "{}",
+            bad_degree
+        );
+        assert_eq!(
+            inverted_degrees,
+            Err(
+                "The --min-degree value must not be greater than the --max-degree value"
+                    .to_string()
+            )
+        );
+        assert!(bad_format.contains("svg"), "{}", bad_format);
+    }
+
+    #[test]
+    fn sends_filters_and_prints_nodes_as_json() {
+        let transact_params_arc = Arc::new(Mutex::new(vec![]));
+        let mut context = CommandContextMock::new()
+            .transact_params(&transact_params_arc)
+            .transact_result(Ok(make_response().tmb(0)));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = GetNeighborhoodGraphCommand::new(&[
+            "neighborhood-graph".to_string(),
+            "--format".to_string(),
+            "json".to_string(),
+            "--max-distance".to_string(),
+            "1".to_string(),
+        ])
+        .unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        let transact_params = transact_params_arc.lock().unwrap();
+        assert_eq!(
+            *transact_params,
+            vec![(
+                UiGetNeighborhoodGraphRequest {
+                    max_distance_opt: Some(1),
+                    ..UiGetNeighborhoodGraphRequest::default()
+                }
+                .tmb(0),
+                STANDARD_COMMAND_TIMEOUT_MILLIS
+            )]
+        );
+        let stdout = stdout_arc.lock().unwrap().get_string();
+        let parsed: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        let nodes: Vec<UiNeighborhoodGraphNode> =
+            serde_json::from_value(parsed["nodes"].clone()).unwrap();
+        assert_eq!(nodes, make_nodes());
+        assert_eq!(
+            parsed["routeDiversityShortfallOpt"],
+            serde_json::Value::Null
+        );
+        assert_eq!(stderr_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn prints_nodes_as_graphml() {
+        let mut context = CommandContextMock::new().transact_result(Ok(make_response().tmb(0)));
+        let stdout_arc = context.stdout_arc();
+        let subject = GetNeighborhoodGraphCommand::new(&[
+            "neighborhood-graph".to_string(),
+            "--format".to_string(),
+            "graphml".to_string(),
+        ])
+        .unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            "\
+<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">
+  <key id=\"nodeAddr\" for=\"node\" attr.name=\"nodeAddr\" attr.type=\"string\"/>
+  <key id=\"version\" for=\"node\" attr.name=\"version\" attr.type=\"int\"/>
+  <key id=\"countryCode\" for=\"node\" attr.name=\"countryCode\" attr.type=\"string\"/>
+  <key id=\"contradictedCountryCode\" for=\"node\" attr.name=\"contradictedCountryCode\" attr.type=\"string\"/>
+  <key id=\"acceptsConnections\" for=\"node\" attr.name=\"acceptsConnections\" attr.type=\"boolean\"/>
+  <key id=\"routesData\" for=\"node\" attr.name=\"routesData\" attr.type=\"boolean\"/>
+  <key id=\"relationship\" for=\"node\" attr.name=\"relationship\" attr.type=\"string\"/>
+  <key id=\"distance\" for=\"node\" attr.name=\"distance\" attr.type=\"int\"/>
+  <key id=\"routingByteRate\" for=\"node\" attr.name=\"routingByteRate\" attr.type=\"long\"/>
+  <key id=\"routingServiceRate\" for=\"node\" attr.name=\"routingServiceRate\" attr.type=\"long\"/>
+  <key id=\"exitByteRate\" for=\"node\" attr.name=\"exitByteRate\" attr.type=\"long\"/>
+  <key id=\"exitServiceRate\" for=\"node\" attr.name=\"exitServiceRate\" attr.type=\"long\"/>
+  <graph id=\"neighborhood\" edgedefault=\"directed\">
+    <node id=\"AQIDBA\">
+      <data key=\"nodeAddr\">1.2.3.4:1234</data>
+      <data key=\"version\">3</data>
+      <data key=\"countryCode\">AU</data>
+      <data key=\"acceptsConnections\">true</data>
+      <data key=\"routesData\">true</data>
+      <data key=\"relationship\">Root</data>
+      <data key=\"distance\">0</data>
+      <data key=\"routingByteRate\">1</data>
+      <data key=\"routingServiceRate\">2</data>
+      <data key=\"exitByteRate\">3</data>
+      <data key=\"exitServiceRate\">4</data>
+    </node>
+    <node id=\"AgMEBQ\">
+      <data key=\"version\">0</data>
+      <data key=\"contradictedCountryCode\">FR</data>
+      <data key=\"acceptsConnections\">false</data>
+      <data key=\"routesData\">true</data>
+      <data key=\"relationship\">Half</data>
+      <data key=\"routingByteRate\">5</data>
+      <data key=\"routingServiceRate\">6</data>
+      <data key=\"exitByteRate\">7</data>
+      <data key=\"exitServiceRate\">8</data>
+    </node>
+    <edge source=\"AQIDBA\" target=\"AgMEBQ\"/>
+    <edge source=\"AgMEBQ\" target=\"AQIDBA\"/>
+  </graph>
+</graphml>
+"
+        );
+    }
+
+    #[test]
+    fn xml_escape_escapes_markup_characters() {
+        assert_eq!(
+            xml_escape("a&b<c>d\"e'f"),
+            "a&amp;b&lt;c&gt;d&quot;e&apos;f"
+        );
+    }
+
     #[test]
     fn can_deserialize_ui_get_neighborhood_graph() {
         let transact_params_arc = Arc::new(Mutex::new(vec![]));
         let mut context = CommandContextMock::new()
             .transact_params(&transact_params_arc)
             .transact_result(Ok(UiGetNeighborhoodGraphResponse {
-                graph: "digraph db { \"AQIDBA\" [label=\"AR v0 AU\\nAQIDBA\\n1.2.3.4:1234\"]; \"HZ5vwwJPhfUZVy85E76GZUUam9SMgyaw+QaZvAMuizo\" [label=\"AR v0 ZZ\\nHZ5vwwJP\\n9.9.9.9:9999\"] [style=filled]; \"AgMEBQ\" [label=\"AR v0 FR\\nAgMEBQ\\n2.3.4.5:2345\"]; \"AwQFBg\" [label=\"AR v0 CN\\nAwQFBg\\n3.4.5.6:3456\"]; \"BAUGBw\" [label=\"AR v0 US\\nBAUGBw\\n4.5.6.7:4567\"]; \"AQIDBA\" -> \"HZ5vwwJPhfUZVy85E76GZUUam9SMgyaw+QaZvAMuizo\"; \"AQIDBA\" -> \"AgMEBQ\"; \"HZ5vwwJPhfUZVy85E76GZUUam9SMgyaw+QaZvAMuizo\" -> \"AQIDBA\"; \"AgMEBQ\" -> \"AwQFBg\"; \"AgMEBQ\" -> \"AQIDBA\"; \"AwQFBg\" -> \"BAUGBw\"; \"AwQFBg\" -> \"AgMEBQ\"; \"BAUGBw\" -> \"AwQFBg\"; }".to_string()
+                graph: "digraph db { \"AQIDBA\" [label=\"AR v0 AU\\nAQIDBA\\n1.2.3.4:1234\"]; \"HZ5vwwJPhfUZVy85E76GZUUam9SMgyaw+QaZvAMuizo\" [label=\"AR v0 ZZ\\nHZ5vwwJP\\n9.9.9.9:9999\"] [style=filled]; \"AgMEBQ\" [label=\"AR v0 FR\\nAgMEBQ\\n2.3.4.5:2345\"]; \"AwQFBg\" [label=\"AR v0 CN\\nAwQFBg\\n3.4.5.6:3456\"]; \"BAUGBw\" [label=\"AR v0 US\\nBAUGBw\\n4.5.6.7:4567\"]; \"AQIDBA\" -> \"HZ5vwwJPhfUZVy85E76GZUUam9SMgyaw+QaZvAMuizo\"; \"AQIDBA\" -> \"AgMEBQ\"; \"HZ5vwwJPhfUZVy85E76GZUUam9SMgyaw+QaZvAMuizo\" -> \"AQIDBA\"; \"AgMEBQ\" -> \"AwQFBg\"; \"AgMEBQ\" -> \"AQIDBA\"; \"AwQFBg\" -> \"BAUGBw\"; \"AwQFBg\" -> \"AgMEBQ\"; \"BAUGBw\" -> \"AwQFBg\"; }".to_string(),
+                nodes: vec![],
+                route_diversity_shortfall_opt: None,
             }.tmb(0)));
         let stderr_arc = context.stderr_arc();
         let stdout_arc = context.stdout_arc();
@@ -77,7 +581,7 @@ pub mod tests {
         let result = subject.execute(&mut context);
 
         assert_eq!(result, Ok(()));
-        let expected_request = UiGetNeighborhoodGraphRequest {};
+        let expected_request = UiGetNeighborhoodGraphRequest::default();
         let transact_params = transact_params_arc.lock().unwrap();
         let expected_message_body = expected_request.tmb(0);
         assert_eq!(
@@ -90,4 +594,34 @@ pub mod tests {
         let stderr = stderr_arc.lock().unwrap();
         assert_eq!(&stderr.get_string(), "");
     }
+
+    #[test]
+    fn reports_route_diversity_shortfall_along_with_the_graph() {
+        let mut context =
+            CommandContextMock::new().transact_result(Ok(UiGetNeighborhoodGraphResponse {
+                graph: "digraph db { }".to_string(),
+                nodes: vec![],
+                route_diversity_shortfall_opt: Some(
+                    "No route could satisfy route diversity 'subnet', although routes without it \
+                     are available"
+                        .to_string(),
+                ),
+            }
+            .tmb(0)));
+        let stderr_arc = context.stderr_arc();
+        let stdout_arc = context.stdout_arc();
+        let subject =
+            GetNeighborhoodGraphCommand::new(&["neighborhood-graph".to_string()]).unwrap();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            &stdout_arc.lock().unwrap().get_string(),
+            "Graph of the Node's neighborhood database: digraph db { }\n\
+             Route diversity: No route could satisfy route diversity 'subnet', although routes \
+             without it are available\n"
+        );
+        assert_eq!(&stderr_arc.lock().unwrap().get_string(), "");
+    }
 }
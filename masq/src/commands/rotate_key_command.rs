@@ -0,0 +1,156 @@
+// Copyright (c) 2024, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::command_context::CommandContext;
+use crate::commands::commands_common::{
+    dump_parameter_line, transaction, Command, CommandError, STANDARD_COMMAND_TIMEOUT_MILLIS,
+};
+use clap::{App, SubCommand};
+use masq_lib::as_any_ref_in_trait_impl;
+use masq_lib::messages::{UiRotateKeyRequest, UiRotateKeyResponse};
+use masq_lib::short_writeln;
+use std::io::Write;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RotateKeyCommand {}
+
+const ROTATE_KEY_SUBCOMMAND_ABOUT: &str =
+    "Gives the Node a new key pair, so that its public key can't be used to follow it around the \
+     network for long. Neighbors are told about the new key in Gossip signed by the old one, and \
+     the old key keeps working for a grace period. The Node's descriptor changes along with its \
+     key. Only valid if Node is already running.";
+
+pub fn rotate_key_subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("rotate-key").about(ROTATE_KEY_SUBCOMMAND_ABOUT)
+}
+
+impl Command for RotateKeyCommand {
+    fn execute(&self, context: &mut dyn CommandContext) -> Result<(), CommandError> {
+        let input = UiRotateKeyRequest {};
+        let output: Result<UiRotateKeyResponse, CommandError> =
+            transaction(input, context, STANDARD_COMMAND_TIMEOUT_MILLIS);
+        match output {
+            Ok(response) => {
+                Self::dump_rotation(context.stdout(), response);
+                Ok(())
+            }
+            Err(e) => {
+                short_writeln!(context.stderr(), "Key rotation failed: {:?}", e);
+                Err(e)
+            }
+        }
+    }
+
+    as_any_ref_in_trait_impl!();
+}
+
+impl RotateKeyCommand {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn dump_rotation(stream: &mut dyn Write, response: UiRotateKeyResponse) {
+        dump_parameter_line(stream, "NAME", "VALUE");
+        dump_parameter_line(stream, "New public key:", &response.public_key);
+        dump_parameter_line(
+            stream,
+            "Previous public key:",
+            &response.previous_public_key,
+        );
+        dump_parameter_line(
+            stream,
+            "Previous key honored for:",
+            &format!("{} seconds", response.grace_period_secs),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_context::ContextError;
+    use crate::command_factory::{CommandFactory, CommandFactoryReal};
+    use crate::test_utils::mocks::CommandContextMock;
+    use masq_lib::messages::ToMessageBody;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn constants_have_correct_values() {
+        assert_eq!(
+            ROTATE_KEY_SUBCOMMAND_ABOUT,
+            "Gives the Node a new key pair, so that its public key can't be used to follow it \
+             around the network for long. Neighbors are told about the new key in Gossip signed \
+             by the old one, and the old key keeps working for a grace period. The Node's \
+             descriptor changes along with its key. Only valid if Node is already running."
+        );
+    }
+
+    #[test]
+    fn command_factory_makes_rotate_key_command() {
+        let factory = CommandFactoryReal::new();
+
+        let result = factory.make(&["rotate-key".to_string()]).unwrap();
+
+        assert_eq!(
+            result.as_any().downcast_ref::<RotateKeyCommand>(),
+            Some(&RotateKeyCommand {})
+        );
+    }
+
+    #[test]
+    fn rotate_key_command_happy_path() {
+        let transact_params_arc = Arc::new(Mutex::new(vec![]));
+        let response = UiRotateKeyResponse {
+            public_key: "CQgHBg".to_string(),
+            previous_public_key: "AQIDBA".to_string(),
+            grace_period_secs: 600,
+        };
+        let mut context = CommandContextMock::new()
+            .transact_params(&transact_params_arc)
+            .transact_result(Ok(response.tmb(4321)));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = RotateKeyCommand::new();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        let transact_params = transact_params_arc.lock().unwrap();
+        assert_eq!(
+            *transact_params,
+            vec![(
+                UiRotateKeyRequest {}.tmb(0),
+                STANDARD_COMMAND_TIMEOUT_MILLIS
+            )]
+        );
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            "\
+|NAME                              VALUE
+|New public key:                   CQgHBg
+|Previous public key:              AQIDBA
+|Previous key honored for:         600 seconds
+"
+            .replace('|', "")
+        );
+        assert_eq!(stderr_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn rotate_key_command_handles_send_failure() {
+        let mut context = CommandContextMock::new()
+            .transact_result(Err(ContextError::ConnectionDropped("blah".to_string())));
+        let stderr_arc = context.stderr_arc();
+        let subject = RotateKeyCommand::new();
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(
+            result,
+            Err(CommandError::ConnectionProblem("blah".to_string()))
+        );
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            "Key rotation failed: ConnectionProblem(\"blah\")\n"
+        );
+    }
+}
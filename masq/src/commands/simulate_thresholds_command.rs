@@ -0,0 +1,483 @@
+// Copyright (c) 2024, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::command_context::CommandContext;
+use crate::commands::commands_common::{
+    dump_parameter_line, transaction, Command, CommandError, STANDARD_COMMAND_TIMEOUT_MILLIS,
+};
+use clap::{App, Arg, SubCommand};
+use masq_lib::as_any_ref_in_trait_impl;
+use masq_lib::messages::{
+    UiDebtSnapshot, UiPaymentThresholds, UiThresholdCurvePoint, UiThresholdSimulationRequest,
+    UiThresholdSimulationResponse, UiThresholdSimulationStep,
+};
+use masq_lib::shared_schema::common_validators::validate_separate_u64_values;
+use masq_lib::short_writeln;
+use std::io::Write;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct SimulateThresholdsCommand {
+    pub payment_thresholds_opt: Option<UiPaymentThresholds>,
+    pub history: Vec<UiDebtSnapshot>,
+}
+
+const SIMULATE_THRESHOLDS_ABOUT: &str =
+    "Shows how a set of payment thresholds would treat a debt, without changing anything: the \
+    curves below which payables are left unpaid and receivables are tolerated, and, for a given \
+    history of a debt, when it would be paid and when its debtor would be banned or unbanned.";
+const PAYMENT_THRESHOLDS_HELP: &str =
+    "The thresholds to try out, in the same form as the --payment-thresholds parameter of the \
+    Node: Debt Threshold gwei|Maturity Threshold Sec|Payment Grace Period Sec|Permanent Debt \
+    Allowed gwei|Threshold Interval Sec|Unban Below gwei. Defaults to the thresholds the Node is \
+    using now.";
+const HISTORY_HELP: &str =
+    "Snapshots of a single debt, in the order they occur, separated by commas. Each one is the \
+    age of the debt in seconds and its balance in gwei, separated by a colon: for example \
+    '1000:5000,2000:4000'.";
+
+pub fn simulate_thresholds_subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("simulate-thresholds")
+        .about(SIMULATE_THRESHOLDS_ABOUT)
+        .arg(
+            Arg::with_name("payment-thresholds")
+                .help(PAYMENT_THRESHOLDS_HELP)
+                .long("payment-thresholds")
+                .value_name("PAYMENT-THRESHOLDS")
+                .takes_value(true)
+                .required(false)
+                .validator(validate_payment_thresholds),
+        )
+        .arg(
+            Arg::with_name("history")
+                .help(HISTORY_HELP)
+                .long("history")
+                .value_name("HISTORY")
+                .takes_value(true)
+                .required(false)
+                .validator(validate_history),
+        )
+}
+
+fn validate_payment_thresholds(thresholds: String) -> Result<(), String> {
+    parse_payment_thresholds(&thresholds).map(|_| ())
+}
+
+fn validate_history(history: String) -> Result<(), String> {
+    parse_history(&history).map(|_| ())
+}
+
+fn parse_payment_thresholds(thresholds: &str) -> Result<UiPaymentThresholds, String> {
+    validate_separate_u64_values(thresholds.to_string())?;
+    let values = thresholds
+        .split('|')
+        .map(|value| {
+            value
+                .parse::<u64>()
+                .expect("validator let a bad number through")
+        })
+        .collect::<Vec<u64>>();
+    if values.len() != 6 {
+        return Err(format!(
+            "'{}' must consist of exactly 6 values, delimited by vertical bars",
+            thresholds
+        ));
+    }
+    Ok(UiPaymentThresholds {
+        debt_threshold_gwei: values[0],
+        maturity_threshold_sec: values[1],
+        payment_grace_period_sec: values[2],
+        permanent_debt_allowed_gwei: values[3],
+        threshold_interval_sec: values[4],
+        unban_below_gwei: values[5],
+    })
+}
+
+fn parse_history(history: &str) -> Result<Vec<UiDebtSnapshot>, String> {
+    history
+        .split(',')
+        .map(|snapshot| {
+            let complain = || format!("'{}' is not a snapshot in the form AGE:BALANCE", snapshot);
+            let (age, balance) = snapshot.split_once(':').ok_or_else(complain)?;
+            Ok(UiDebtSnapshot {
+                age_sec: age.trim().parse::<u64>().map_err(|_| complain())?,
+                balance_gwei: balance.trim().parse::<u64>().map_err(|_| complain())?,
+            })
+        })
+        .collect()
+}
+
+impl Command for SimulateThresholdsCommand {
+    fn execute(&self, context: &mut dyn CommandContext) -> Result<(), CommandError> {
+        let input = UiThresholdSimulationRequest {
+            payment_thresholds_opt: self.payment_thresholds_opt.clone(),
+            history: self.history.clone(),
+        };
+        let output: Result<UiThresholdSimulationResponse, CommandError> =
+            transaction(input, context, STANDARD_COMMAND_TIMEOUT_MILLIS);
+        match output {
+            Ok(response) => {
+                Self::dump_thresholds(context.stdout(), &response.payment_thresholds);
+                Self::dump_curve(context.stdout(), &response.curve);
+                if !response.steps.is_empty() {
+                    Self::dump_steps(context.stdout(), &response.steps);
+                }
+                Ok(())
+            }
+            Err(e) => {
+                short_writeln!(context.stderr(), "Threshold simulation failed: {:?}", e);
+                Err(e)
+            }
+        }
+    }
+
+    as_any_ref_in_trait_impl!();
+}
+
+impl SimulateThresholdsCommand {
+    pub fn new(pieces: &[String]) -> Result<Self, String> {
+        let matches = match simulate_thresholds_subcommand().get_matches_from_safe(pieces) {
+            Ok(matches) => matches,
+            Err(e) => return Err(format!("{}", e)),
+        };
+        let payment_thresholds_opt = matches.value_of("payment-thresholds").map(|thresholds| {
+            parse_payment_thresholds(thresholds).expect("clap validator let bad thresholds through")
+        });
+        let history = matches
+            .value_of("history")
+            .map(|history| {
+                parse_history(history).expect("clap validator let a bad history through")
+            })
+            .unwrap_or_default();
+        Ok(Self {
+            payment_thresholds_opt,
+            history,
+        })
+    }
+
+    fn dump_thresholds(stream: &mut dyn Write, thresholds: &UiPaymentThresholds) {
+        short_writeln!(stream, "Payment thresholds");
+        [
+            ("Debt threshold:", thresholds.debt_threshold_gwei, "gwei"),
+            (
+                "Maturity threshold:",
+                thresholds.maturity_threshold_sec,
+                "s",
+            ),
+            (
+                "Payment grace period:",
+                thresholds.payment_grace_period_sec,
+                "s",
+            ),
+            (
+                "Permanent debt allowed:",
+                thresholds.permanent_debt_allowed_gwei,
+                "gwei",
+            ),
+            (
+                "Threshold interval:",
+                thresholds.threshold_interval_sec,
+                "s",
+            ),
+            ("Unban below:", thresholds.unban_below_gwei, "gwei"),
+        ]
+        .iter()
+        .for_each(|(name, value, unit)| {
+            dump_parameter_line(stream, name, &format!("{} {}", value, unit))
+        });
+    }
+
+    fn dump_curve(stream: &mut dyn Write, curve: &[UiThresholdCurvePoint]) {
+        let gwei_or_dash = |value_opt: Option<u64>| match value_opt {
+            Some(value) => value.to_string(),
+            None => "-".to_string(),
+        };
+        short_writeln!(stream, "\nCurve");
+        short_writeln!(
+            stream,
+            "{:>12}  {:>24}  {:>28}",
+            "Age (s)",
+            "Payable threshold (gwei)",
+            "Delinquency threshold (gwei)"
+        );
+        curve.iter().for_each(|point| {
+            short_writeln!(
+                stream,
+                "{:>12}  {:>24}  {:>28}",
+                point.age_sec,
+                gwei_or_dash(point.payable_threshold_gwei_opt),
+                gwei_or_dash(point.delinquency_threshold_gwei_opt)
+            )
+        });
+    }
+
+    fn dump_steps(stream: &mut dyn Write, steps: &[UiThresholdSimulationStep]) {
+        let yes_or_no = |flag: bool| if flag { "yes" } else { "no" };
+        short_writeln!(stream, "\nHistory");
+        short_writeln!(
+            stream,
+            "{:>12}  {:>20}  {:>11}  {:>10}  {}",
+            "Age (s)",
+            "Balance (gwei)",
+            "Payment due",
+            "Delinquent",
+            "Event"
+        );
+        steps.iter().for_each(|step| {
+            let event = if step.newly_banned {
+                "banned"
+            } else if step.newly_unbanned {
+                "unbanned"
+            } else {
+                ""
+            };
+            short_writeln!(
+                stream,
+                "{:>12}  {:>20}  {:>11}  {:>10}  {}",
+                step.age_sec,
+                step.balance_gwei,
+                yes_or_no(step.payment_due),
+                yes_or_no(step.delinquent),
+                event
+            )
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_context::ContextError;
+    use crate::command_factory::{CommandFactory, CommandFactoryError, CommandFactoryReal};
+    use crate::commands::commands_common::CommandError::Payload;
+    use crate::test_utils::mocks::CommandContextMock;
+    use masq_lib::constants::THRESHOLD_SIMULATION_ERROR;
+    use masq_lib::messages::ToMessageBody;
+    use std::sync::{Arc, Mutex};
+
+    fn make_thresholds() -> UiPaymentThresholds {
+        UiPaymentThresholds {
+            threshold_interval_sec: 8_000,
+            debt_threshold_gwei: 10_000,
+            payment_grace_period_sec: 500,
+            maturity_threshold_sec: 1_000,
+            permanent_debt_allowed_gwei: 2_000,
+            unban_below_gwei: 1_000,
+        }
+    }
+
+    #[test]
+    fn constants_have_correct_values() {
+        assert_eq!(
+            SIMULATE_THRESHOLDS_ABOUT,
+            "Shows how a set of payment thresholds would treat a debt, without changing anything: the \
+            curves below which payables are left unpaid and receivables are tolerated, and, for a given \
+            history of a debt, when it would be paid and when its debtor would be banned or unbanned."
+        );
+        assert_eq!(
+            PAYMENT_THRESHOLDS_HELP,
+            "The thresholds to try out, in the same form as the --payment-thresholds parameter of the \
+            Node: Debt Threshold gwei|Maturity Threshold Sec|Payment Grace Period Sec|Permanent Debt \
+            Allowed gwei|Threshold Interval Sec|Unban Below gwei. Defaults to the thresholds the Node is \
+            using now."
+        );
+        assert_eq!(
+            HISTORY_HELP,
+            "Snapshots of a single debt, in the order they occur, separated by commas. Each one is the \
+            age of the debt in seconds and its balance in gwei, separated by a colon: for example \
+            '1000:5000,2000:4000'."
+        );
+    }
+
+    #[test]
+    fn command_factory_interprets_thresholds_and_history() {
+        let factory = CommandFactoryReal::new();
+        let plain = factory.make(&["simulate-thresholds".to_string()]).unwrap();
+        let full = factory
+            .make(&[
+                "simulate-thresholds".to_string(),
+                "--payment-thresholds".to_string(),
+                "10000|1000|500|2000|8000|1000".to_string(),
+                "--history".to_string(),
+                "900:50000, 3000:8600".to_string(),
+            ])
+            .unwrap();
+
+        assert_eq!(
+            plain.as_any().downcast_ref::<SimulateThresholdsCommand>(),
+            Some(&SimulateThresholdsCommand {
+                payment_thresholds_opt: None,
+                history: vec![],
+            })
+        );
+        assert_eq!(
+            full.as_any().downcast_ref::<SimulateThresholdsCommand>(),
+            Some(&SimulateThresholdsCommand {
+                payment_thresholds_opt: Some(make_thresholds()),
+                history: vec![
+                    UiDebtSnapshot {
+                        age_sec: 900,
+                        balance_gwei: 50_000
+                    },
+                    UiDebtSnapshot {
+                        age_sec: 3_000,
+                        balance_gwei: 8_600
+                    },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn command_factory_rejects_malformed_parameters() {
+        let factory = CommandFactoryReal::new();
+        let attempt = |name: &str, value: &str| match factory.make(&[
+            "simulate-thresholds".to_string(),
+            format!("--{}", name),
+            value.to_string(),
+        ]) {
+            Err(CommandFactoryError::CommandSyntax(msg)) => msg,
+            x => panic!(
+                "Expected syntax error for {}, got {:?}",
+                value,
+                x.map(|_| ())
+            ),
+        };
+
+        let too_few = attempt("payment-thresholds", "10000|1000|500");
+        let no_colon = attempt("history", "900:50000,3000");
+        let not_a_number = attempt("history", "900:lots");
+
+        assert!(
+            too_few.contains(
+                "'10000|1000|500' must consist of exactly 6 values, delimited by vertical bars"
+            ),
+            "{}",
+            too_few
+        );
+        assert!(
+            no_colon.contains("'3000' is not a snapshot in the form AGE:BALANCE"),
+            "{}",
+            no_colon
+        );
+        assert!(
+            not_a_number.contains("'900:lots' is not a snapshot in the form AGE:BALANCE"),
+            "{}",
+            not_a_number
+        );
+    }
+
+    #[test]
+    fn simulate_thresholds_command_writes_tables() {
+        let transact_params_arc = Arc::new(Mutex::new(vec![]));
+        let mut context = CommandContextMock::new()
+            .transact_params(&transact_params_arc)
+            .transact_result(Ok(UiThresholdSimulationResponse {
+                payment_thresholds: make_thresholds(),
+                curve: vec![
+                    UiThresholdCurvePoint {
+                        age_sec: 1_000,
+                        payable_threshold_gwei_opt: None,
+                        delinquency_threshold_gwei_opt: None,
+                    },
+                    UiThresholdCurvePoint {
+                        age_sec: 5_000,
+                        payable_threshold_gwei_opt: Some(6_000),
+                        delinquency_threshold_gwei_opt: Some(6_500),
+                    },
+                ],
+                steps: vec![
+                    UiThresholdSimulationStep {
+                        age_sec: 3_000,
+                        balance_gwei: 8_600,
+                        payment_due: true,
+                        delinquent: true,
+                        newly_banned: true,
+                        newly_unbanned: false,
+                    },
+                    UiThresholdSimulationStep {
+                        age_sec: 7_000,
+                        balance_gwei: 1_000,
+                        payment_due: false,
+                        delinquent: false,
+                        newly_banned: false,
+                        newly_unbanned: true,
+                    },
+                ],
+            }
+            .tmb(0)));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let history = vec![UiDebtSnapshot {
+            age_sec: 3_000,
+            balance_gwei: 8_600,
+        }];
+        let subject = SimulateThresholdsCommand {
+            payment_thresholds_opt: Some(make_thresholds()),
+            history: history.clone(),
+        };
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(result, Ok(()));
+        let transact_params = transact_params_arc.lock().unwrap();
+        assert_eq!(
+            *transact_params,
+            vec![(
+                UiThresholdSimulationRequest {
+                    payment_thresholds_opt: Some(make_thresholds()),
+                    history,
+                }
+                .tmb(0),
+                STANDARD_COMMAND_TIMEOUT_MILLIS
+            )]
+        );
+        assert_eq!(
+            stdout_arc.lock().unwrap().get_string(),
+            "Payment thresholds\n\
+            Debt threshold:                   10000 gwei\n\
+            Maturity threshold:               1000 s\n\
+            Payment grace period:             500 s\n\
+            Permanent debt allowed:           2000 gwei\n\
+            Threshold interval:               8000 s\n\
+            Unban below:                      1000 gwei\n\
+            \n\
+            Curve\n\
+            \x20    Age (s)  Payable threshold (gwei)  Delinquency threshold (gwei)\n\
+            \x20       1000                         -                             -\n\
+            \x20       5000                      6000                          6500\n\
+            \n\
+            History\n\
+            \x20    Age (s)        Balance (gwei)  Payment due  Delinquent  Event\n\
+            \x20       3000                  8600          yes         yes  banned\n\
+            \x20       7000                  1000           no          no  unbanned\n"
+        );
+        assert_eq!(stderr_arc.lock().unwrap().get_string(), String::new());
+    }
+
+    #[test]
+    fn simulate_thresholds_command_reports_rejected_thresholds() {
+        let mut context = CommandContextMock::new().transact_result(Err(
+            ContextError::PayloadError(THRESHOLD_SIMULATION_ERROR, "Booga".to_string()),
+        ));
+        let stdout_arc = context.stdout_arc();
+        let stderr_arc = context.stderr_arc();
+        let subject = SimulateThresholdsCommand {
+            payment_thresholds_opt: None,
+            history: vec![],
+        };
+
+        let result = subject.execute(&mut context);
+
+        assert_eq!(
+            result,
+            Err(Payload(THRESHOLD_SIMULATION_ERROR, "Booga".to_string()))
+        );
+        assert_eq!(stdout_arc.lock().unwrap().get_string(), String::new());
+        assert_eq!(
+            stderr_arc.lock().unwrap().get_string(),
+            format!(
+                "Threshold simulation failed: Payload({}, \"Booga\")\n",
+                THRESHOLD_SIMULATION_ERROR
+            )
+        );
+    }
+}
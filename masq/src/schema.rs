@@ -1,22 +1,31 @@
 // Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 
+use crate::commands::automap_command::automap_subcommand;
+use crate::commands::backup_command::backup_subcommand;
+use crate::commands::bans_command::bans_subcommand;
 use crate::commands::change_password_command::{
     change_password_subcommand, set_password_subcommand,
 };
 use crate::commands::check_password_command::check_password_subcommand;
 use crate::commands::configuration_command::configuration_subcommand;
 use crate::commands::connection_status_command::connection_status_subcommand;
+use crate::commands::country_database_command::country_database_subcommand;
 use crate::commands::crash_command::crash_subcommand;
 use crate::commands::descriptor_command::descriptor_subcommand;
+use crate::commands::encryption_command::encryption_subcommand;
 use crate::commands::exit_location_command::exit_location_subcommand;
+use crate::commands::export_ledger_command::export_ledger_subcommand;
 use crate::commands::financials_command::args_validation::financials_subcommand;
 use crate::commands::generate_wallets_command::generate_wallets_subcommand;
+use crate::commands::gossip_rejections_command::gossip_rejections_subcommand;
 use crate::commands::neighborhood_graph_command::get_neighborhood_graph_subcommand;
 use crate::commands::recover_wallets_command::recover_wallets_subcommand;
+use crate::commands::rotate_key_command::rotate_key_subcommand;
 use crate::commands::scan_command::scan_subcommand;
 use crate::commands::set_configuration_command::set_configuration_subcommand;
 use crate::commands::setup_command::setup_subcommand;
 use crate::commands::shutdown_command::shutdown_subcommand;
+use crate::commands::simulate_thresholds_command::simulate_thresholds_subcommand;
 use crate::commands::start_command::start_subcommand;
 use crate::commands::wallet_addresses_command::wallet_addresses_subcommand;
 use clap::{App, AppSettings, Arg};
@@ -63,22 +72,31 @@ pub fn app() -> App<'static, 'static> {
                 .validator(validate_ui_port)
                 .help(UI_PORT_HELP.as_str()),
         )
+        .subcommand(automap_subcommand())
+        .subcommand(backup_subcommand())
+        .subcommand(bans_subcommand())
         .subcommand(change_password_subcommand())
         .subcommand(check_password_subcommand())
         .subcommand(crash_subcommand())
         .subcommand(configuration_subcommand())
         .subcommand(connection_status_subcommand())
+        .subcommand(country_database_subcommand())
         .subcommand(descriptor_subcommand())
+        .subcommand(encryption_subcommand())
         .subcommand(exit_location_subcommand())
+        .subcommand(export_ledger_subcommand())
         .subcommand(financials_subcommand())
         .subcommand(generate_wallets_subcommand())
         .subcommand(get_neighborhood_graph_subcommand())
+        .subcommand(gossip_rejections_subcommand())
         .subcommand(recover_wallets_subcommand())
+        .subcommand(rotate_key_subcommand())
         .subcommand(scan_subcommand())
         .subcommand(set_configuration_subcommand())
         .subcommand(set_password_subcommand())
         .subcommand(setup_subcommand())
         .subcommand(shutdown_subcommand())
+        .subcommand(simulate_thresholds_subcommand())
         .subcommand(start_subcommand())
         .subcommand(wallet_addresses_subcommand())
 }
@@ -2,13 +2,18 @@
 
 use crate::blockchains::chains::Chain;
 use crate::constants::{
+    BASE_GAS_LIMIT_CONST_PART, BASE_MAINNET_CONFIRMATION_DEPTH,
     BASE_MAINNET_CONTRACT_CREATION_BLOCK, BASE_MAINNET_FULL_IDENTIFIER,
-    BASE_SEPOLIA_CONTRACT_CREATION_BLOCK, BASE_SEPOLIA_FULL_IDENTIFIER, DEV_CHAIN_FULL_IDENTIFIER,
-    ETH_MAINNET_CONTRACT_CREATION_BLOCK, ETH_MAINNET_FULL_IDENTIFIER,
+    BASE_SEPOLIA_CONFIRMATION_DEPTH, BASE_SEPOLIA_CONTRACT_CREATION_BLOCK,
+    BASE_SEPOLIA_FULL_IDENTIFIER, DEV_CHAIN_FULL_IDENTIFIER, ETH_GAS_LIMIT_CONST_PART,
+    ETH_MAINNET_CONFIRMATION_DEPTH, ETH_MAINNET_CONTRACT_CREATION_BLOCK,
+    ETH_MAINNET_FULL_IDENTIFIER, ETH_ROPSTEN_CONFIRMATION_DEPTH,
     ETH_ROPSTEN_CONTRACT_CREATION_BLOCK, ETH_ROPSTEN_FULL_IDENTIFIER,
-    MULTINODE_TESTNET_CONTRACT_CREATION_BLOCK, POLYGON_AMOY_CONTRACT_CREATION_BLOCK,
-    POLYGON_AMOY_FULL_IDENTIFIER, POLYGON_MAINNET_CONTRACT_CREATION_BLOCK,
-    POLYGON_MAINNET_FULL_IDENTIFIER,
+    MULTINODE_TESTNET_CONFIRMATION_DEPTH, MULTINODE_TESTNET_CONTRACT_CREATION_BLOCK,
+    MULTINODE_TESTNET_GAS_LIMIT_CONST_PART, POLYGON_AMOY_CONFIRMATION_DEPTH,
+    POLYGON_AMOY_CONTRACT_CREATION_BLOCK, POLYGON_AMOY_FULL_IDENTIFIER,
+    POLYGON_GAS_LIMIT_CONST_PART, POLYGON_MAINNET_CONFIRMATION_DEPTH,
+    POLYGON_MAINNET_CONTRACT_CREATION_BLOCK, POLYGON_MAINNET_FULL_IDENTIFIER,
 };
 use ethereum_types::{Address, H160};
 
@@ -19,6 +24,9 @@ pub const CHAINS: [BlockchainRecord; 7] = [
         literal_identifier: POLYGON_MAINNET_FULL_IDENTIFIER,
         contract: POLYGON_MAINNET_CONTRACT_ADDRESS,
         contract_creation_block: POLYGON_MAINNET_CONTRACT_CREATION_BLOCK,
+        confirmation_depth: POLYGON_MAINNET_CONFIRMATION_DEPTH,
+        gas_limit_const_part: POLYGON_GAS_LIMIT_CONST_PART,
+        multisend_contract_opt: None,
     },
     BlockchainRecord {
         self_id: Chain::EthMainnet,
@@ -26,6 +34,9 @@ pub const CHAINS: [BlockchainRecord; 7] = [
         literal_identifier: ETH_MAINNET_FULL_IDENTIFIER,
         contract: ETH_MAINNET_CONTRACT_ADDRESS,
         contract_creation_block: ETH_MAINNET_CONTRACT_CREATION_BLOCK,
+        confirmation_depth: ETH_MAINNET_CONFIRMATION_DEPTH,
+        gas_limit_const_part: ETH_GAS_LIMIT_CONST_PART,
+        multisend_contract_opt: None,
     },
     BlockchainRecord {
         self_id: Chain::BaseMainnet,
@@ -33,6 +44,9 @@ pub const CHAINS: [BlockchainRecord; 7] = [
         literal_identifier: BASE_MAINNET_FULL_IDENTIFIER,
         contract: BASE_MAINNET_CONTRACT_ADDRESS,
         contract_creation_block: BASE_MAINNET_CONTRACT_CREATION_BLOCK,
+        confirmation_depth: BASE_MAINNET_CONFIRMATION_DEPTH,
+        gas_limit_const_part: BASE_GAS_LIMIT_CONST_PART,
+        multisend_contract_opt: None,
     },
     BlockchainRecord {
         self_id: Chain::BaseSepolia,
@@ -40,6 +54,9 @@ pub const CHAINS: [BlockchainRecord; 7] = [
         literal_identifier: BASE_SEPOLIA_FULL_IDENTIFIER,
         contract: BASE_SEPOLIA_TESTNET_CONTRACT_ADDRESS,
         contract_creation_block: BASE_SEPOLIA_CONTRACT_CREATION_BLOCK,
+        confirmation_depth: BASE_SEPOLIA_CONFIRMATION_DEPTH,
+        gas_limit_const_part: BASE_GAS_LIMIT_CONST_PART,
+        multisend_contract_opt: None,
     },
     BlockchainRecord {
         self_id: Chain::PolyAmoy,
@@ -47,6 +64,9 @@ pub const CHAINS: [BlockchainRecord; 7] = [
         literal_identifier: POLYGON_AMOY_FULL_IDENTIFIER,
         contract: POLYGON_AMOY_TESTNET_CONTRACT_ADDRESS,
         contract_creation_block: POLYGON_AMOY_CONTRACT_CREATION_BLOCK,
+        confirmation_depth: POLYGON_AMOY_CONFIRMATION_DEPTH,
+        gas_limit_const_part: POLYGON_GAS_LIMIT_CONST_PART,
+        multisend_contract_opt: None,
     },
     BlockchainRecord {
         self_id: Chain::EthRopsten,
@@ -54,6 +74,9 @@ pub const CHAINS: [BlockchainRecord; 7] = [
         literal_identifier: ETH_ROPSTEN_FULL_IDENTIFIER,
         contract: ETH_ROPSTEN_TESTNET_CONTRACT_ADDRESS,
         contract_creation_block: ETH_ROPSTEN_CONTRACT_CREATION_BLOCK,
+        confirmation_depth: ETH_ROPSTEN_CONFIRMATION_DEPTH,
+        gas_limit_const_part: ETH_GAS_LIMIT_CONST_PART,
+        multisend_contract_opt: None,
     },
     BlockchainRecord {
         self_id: Chain::Dev,
@@ -61,6 +84,9 @@ pub const CHAINS: [BlockchainRecord; 7] = [
         literal_identifier: DEV_CHAIN_FULL_IDENTIFIER,
         contract: MULTINODE_TESTNET_CONTRACT_ADDRESS,
         contract_creation_block: MULTINODE_TESTNET_CONTRACT_CREATION_BLOCK,
+        confirmation_depth: MULTINODE_TESTNET_CONFIRMATION_DEPTH,
+        gas_limit_const_part: MULTINODE_TESTNET_GAS_LIMIT_CONST_PART,
+        multisend_contract_opt: None,
     },
 ];
 
@@ -71,6 +97,11 @@ pub struct BlockchainRecord {
     pub literal_identifier: &'static str,
     pub contract: Address,
     pub contract_creation_block: u64,
+    pub confirmation_depth: u64,
+    pub gas_limit_const_part: u128,
+    // Never compiled in: official chains get theirs from the chains.toml file, custom chains
+    // from their definitions there. See Chain::multisend_contract_opt().
+    pub multisend_contract_opt: Option<Address>,
 }
 
 // $tMASQ (Amoy)
@@ -197,6 +228,9 @@ mod tests {
                 literal_identifier: "eth-mainnet",
                 contract: ETH_MAINNET_CONTRACT_ADDRESS,
                 contract_creation_block: ETH_MAINNET_CONTRACT_CREATION_BLOCK,
+                confirmation_depth: ETH_MAINNET_CONFIRMATION_DEPTH,
+                gas_limit_const_part: ETH_GAS_LIMIT_CONST_PART,
+                multisend_contract_opt: None,
             }
         );
     }
@@ -213,6 +247,9 @@ mod tests {
                 literal_identifier: "eth-ropsten",
                 contract: ETH_ROPSTEN_TESTNET_CONTRACT_ADDRESS,
                 contract_creation_block: ETH_ROPSTEN_CONTRACT_CREATION_BLOCK,
+                confirmation_depth: ETH_ROPSTEN_CONFIRMATION_DEPTH,
+                gas_limit_const_part: ETH_GAS_LIMIT_CONST_PART,
+                multisend_contract_opt: None,
             }
         );
     }
@@ -229,6 +266,9 @@ mod tests {
                 literal_identifier: "polygon-mainnet",
                 contract: POLYGON_MAINNET_CONTRACT_ADDRESS,
                 contract_creation_block: POLYGON_MAINNET_CONTRACT_CREATION_BLOCK,
+                confirmation_depth: POLYGON_MAINNET_CONFIRMATION_DEPTH,
+                gas_limit_const_part: POLYGON_GAS_LIMIT_CONST_PART,
+                multisend_contract_opt: None,
             }
         );
     }
@@ -245,6 +285,9 @@ mod tests {
                 literal_identifier: "polygon-amoy",
                 contract: POLYGON_AMOY_TESTNET_CONTRACT_ADDRESS,
                 contract_creation_block: POLYGON_AMOY_CONTRACT_CREATION_BLOCK,
+                confirmation_depth: POLYGON_AMOY_CONFIRMATION_DEPTH,
+                gas_limit_const_part: POLYGON_GAS_LIMIT_CONST_PART,
+                multisend_contract_opt: None,
             }
         );
     }
@@ -261,6 +304,9 @@ mod tests {
                 literal_identifier: "base-mainnet",
                 contract: BASE_MAINNET_CONTRACT_ADDRESS,
                 contract_creation_block: BASE_MAINNET_CONTRACT_CREATION_BLOCK,
+                confirmation_depth: BASE_MAINNET_CONFIRMATION_DEPTH,
+                gas_limit_const_part: BASE_GAS_LIMIT_CONST_PART,
+                multisend_contract_opt: None,
             }
         );
     }
@@ -277,6 +323,9 @@ mod tests {
                 literal_identifier: "base-sepolia",
                 contract: BASE_SEPOLIA_TESTNET_CONTRACT_ADDRESS,
                 contract_creation_block: BASE_SEPOLIA_CONTRACT_CREATION_BLOCK,
+                confirmation_depth: BASE_SEPOLIA_CONFIRMATION_DEPTH,
+                gas_limit_const_part: BASE_GAS_LIMIT_CONST_PART,
+                multisend_contract_opt: None,
             }
         );
     }
@@ -293,6 +342,9 @@ mod tests {
                 literal_identifier: "dev",
                 contract: MULTINODE_TESTNET_CONTRACT_ADDRESS,
                 contract_creation_block: MULTINODE_TESTNET_CONTRACT_CREATION_BLOCK,
+                confirmation_depth: MULTINODE_TESTNET_CONFIRMATION_DEPTH,
+                gas_limit_const_part: MULTINODE_TESTNET_GAS_LIMIT_CONST_PART,
+                multisend_contract_opt: None,
             }
         );
     }
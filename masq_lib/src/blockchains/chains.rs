@@ -1,11 +1,13 @@
 // Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 
 use crate::blockchains::blockchain_records::{BlockchainRecord, CHAINS};
+use crate::blockchains::custom_chains::{custom_chain_record_opt, official_multisend_contract_opt};
 use crate::constants::{
     BASE_MAINNET_FULL_IDENTIFIER, BASE_SEPOLIA_FULL_IDENTIFIER, DEFAULT_CHAIN,
     DEV_CHAIN_FULL_IDENTIFIER, ETH_MAINNET_FULL_IDENTIFIER, ETH_ROPSTEN_FULL_IDENTIFIER,
     POLYGON_AMOY_FULL_IDENTIFIER, POLYGON_MAINNET_FULL_IDENTIFIER,
 };
+use ethereum_types::Address;
 use serde_derive::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 
@@ -18,6 +20,8 @@ pub enum Chain {
     BaseMainnet,
     BaseSepolia,
     Dev,
+    // Defined in the data directory rather than here; identified by its numeric chain id
+    Custom(u64),
 }
 
 impl Default for Chain {
@@ -42,6 +46,8 @@ impl From<&str> for Chain {
             Chain::EthRopsten
         } else if str == DEV_CHAIN_FULL_IDENTIFIER {
             Chain::Dev
+        } else if let Some(record) = custom_chain_record_opt(&|b| b.literal_identifier == str) {
+            record.self_id
         } else {
             panic!("Clap let in a wrong value for chain: '{}'; if this happens we need to track down the slit", str)
         }
@@ -58,6 +64,7 @@ impl Display for Chain {
             Chain::BaseMainnet => BASE_MAINNET_FULL_IDENTIFIER,
             Chain::BaseSepolia => BASE_SEPOLIA_FULL_IDENTIFIER,
             Chain::Dev => DEV_CHAIN_FULL_IDENTIFIER,
+            Chain::Custom(_) => self.rec().literal_identifier,
         };
         write!(f, "{}", chain_name)
     }
@@ -68,10 +75,18 @@ impl Chain {
         CHAINS
             .iter()
             .find(|b| &b.self_id == self)
+            .or_else(|| custom_chain_record_opt(&|b| &b.self_id == self))
             .unwrap_or_else(|| panic!("BlockchainRecord for '{:?}' doesn't exist", self))
         //untested panic - but works as an expect()
     }
 
+    // The disperse-style contract, if any, through which payables may be settled together
+    pub fn multisend_contract_opt(&self) -> Option<Address> {
+        self.rec()
+            .multisend_contract_opt
+            .or_else(|| official_multisend_contract_opt(*self))
+    }
+
     pub fn is_mainnet(&self) -> bool {
         Self::mainnets()
             .iter()
@@ -85,6 +100,7 @@ impl Chain {
 
 pub fn chain_from_chain_identifier_opt(identifier: &str) -> Option<Chain> {
     return_record_opt_standard_impl(&|b: &&BlockchainRecord| b.literal_identifier == identifier)
+        .or_else(|| custom_chain_record_opt(&|b| b.literal_identifier == identifier))
         .map(|record| record.self_id)
 }
 
@@ -159,6 +175,9 @@ mod tests {
             literal_identifier: "",
             contract: Default::default(),
             contract_creation_block: 0,
+            confirmation_depth: 0,
+            gas_limit_const_part: 0,
+            multisend_contract_opt: None,
         }
     }
 
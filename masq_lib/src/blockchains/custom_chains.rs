@@ -0,0 +1,456 @@
+// Copyright (c) 2024, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::blockchains::blockchain_records::{BlockchainRecord, CHAINS};
+use crate::blockchains::chains::Chain;
+use crate::constants::CUSTOM_CHAINS_FILE_NAME;
+use ethereum_types::Address;
+use lazy_static::lazy_static;
+use serde_derive::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::RwLock;
+
+lazy_static! {
+    // Records are leaked so that they live as long as the compiled-in ones
+    static ref CUSTOM_CHAINS: RwLock<Vec<&'static BlockchainRecord>> = RwLock::new(vec![]);
+    static ref OFFICIAL_MULTISEND_CONTRACTS: RwLock<HashMap<Chain, Address>> =
+        RwLock::new(HashMap::new());
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+struct CustomChainsFile {
+    #[serde(default)]
+    chain: Vec<CustomChainDefinition>,
+    #[serde(default)]
+    multisend: Vec<MultisendDefinition>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct CustomChainDefinition {
+    pub name: String,
+    pub chain_id: u64,
+    pub contract: Address,
+    pub contract_creation_block: u64,
+    pub confirmation_depth: u64,
+    pub gas_limit_const_part: u64,
+    // A disperse-style contract through which many payables can be settled in one transaction
+    #[serde(default)]
+    pub multisend_contract: Option<Address>,
+}
+
+// Names a disperse-style contract for an official chain. Nothing is paid through it until the
+// consuming wallet has approved it a sufficient allowance of the chain's token.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct MultisendDefinition {
+    pub chain: String,
+    pub contract: Address,
+}
+
+// Reads the chains.toml file in the data directory, if there is one, and makes the chains it
+// defines known to the rest of the code. Loading the same file again is harmless.
+pub fn load_custom_chains(data_directory: &Path) -> Result<Vec<Chain>, String> {
+    let path = data_directory.join(CUSTOM_CHAINS_FILE_NAME);
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let contents = fs::read_to_string(&path)
+        .map_err(|e| format!("Couldn't read {}: {}", path.display(), e))?;
+    let file: CustomChainsFile = toml::from_str(&contents)
+        .map_err(|e| format!("Couldn't parse {}: {}", path.display(), e))?;
+    let chains = file
+        .chain
+        .into_iter()
+        .map(|definition| {
+            register_custom_chain(definition).map_err(|e| format!("{}: {}", path.display(), e))
+        })
+        .collect::<Result<Vec<Chain>, String>>()?;
+    file.multisend.into_iter().try_for_each(|definition| {
+        register_multisend_contract(definition).map_err(|e| format!("{}: {}", path.display(), e))
+    })?;
+    Ok(chains)
+}
+
+pub fn register_custom_chain(definition: CustomChainDefinition) -> Result<Chain, String> {
+    validate_name(&definition.name)?;
+    if let Some(official) = CHAINS
+        .iter()
+        .find(|record| record.num_chain_id == definition.chain_id)
+    {
+        return Err(format!(
+            "Chain id {} already belongs to the official chain '{}'",
+            definition.chain_id, official.literal_identifier
+        ));
+    }
+    let mut custom_chains = CUSTOM_CHAINS.write().expect("Custom chains poisoned");
+    let existing_opt = custom_chains
+        .iter()
+        .find(|existing| {
+            existing.literal_identifier == definition.name
+                || existing.num_chain_id == definition.chain_id
+        })
+        .copied();
+    match existing_opt {
+        Some(existing) if *existing == make_record(&definition, existing.literal_identifier) => {
+            Ok(existing.self_id)
+        }
+        Some(existing) => Err(format!(
+            "Chain '{}' with id {} conflicts with the chain '{}' with id {} defined earlier",
+            definition.name,
+            definition.chain_id,
+            existing.literal_identifier,
+            existing.num_chain_id
+        )),
+        None => {
+            let name: &'static str = Box::leak(definition.name.clone().into_boxed_str());
+            let record: &'static BlockchainRecord =
+                Box::leak(Box::new(make_record(&definition, name)));
+            custom_chains.push(record);
+            Ok(record.self_id)
+        }
+    }
+}
+
+pub fn register_multisend_contract(definition: MultisendDefinition) -> Result<Chain, String> {
+    let chain = match CHAINS
+        .iter()
+        .find(|record| record.literal_identifier == definition.chain)
+    {
+        Some(record) => record.self_id,
+        None => {
+            return Err(format!(
+                "Chain '{}' is not an official chain; a custom chain names its multisend \
+                contract in its own definition",
+                definition.chain
+            ))
+        }
+    };
+    let mut contracts = OFFICIAL_MULTISEND_CONTRACTS
+        .write()
+        .expect("Multisend contracts poisoned");
+    match contracts.get(&chain) {
+        Some(existing) if *existing == definition.contract => Ok(chain),
+        Some(existing) => Err(format!(
+            "Chain '{}' already has the multisend contract {:?}",
+            definition.chain, existing
+        )),
+        None => {
+            contracts.insert(chain, definition.contract);
+            Ok(chain)
+        }
+    }
+}
+
+pub fn official_multisend_contract_opt(chain: Chain) -> Option<Address> {
+    OFFICIAL_MULTISEND_CONTRACTS
+        .read()
+        .expect("Multisend contracts poisoned")
+        .get(&chain)
+        .copied()
+}
+
+pub fn custom_chain_record_opt(
+    closure: &dyn Fn(&BlockchainRecord) -> bool,
+) -> Option<&'static BlockchainRecord> {
+    CUSTOM_CHAINS
+        .read()
+        .expect("Custom chains poisoned")
+        .iter()
+        .find(|record| closure(record))
+        .copied()
+}
+
+fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        return Err(format!(
+            "Chain name '{}' must consist of lowercase letters, digits and hyphens",
+            name
+        ));
+    }
+    if CHAINS
+        .iter()
+        .any(|record| record.literal_identifier == name)
+    {
+        return Err(format!(
+            "Chain name '{}' belongs to an official chain",
+            name
+        ));
+    }
+    Ok(())
+}
+
+fn make_record(
+    definition: &CustomChainDefinition,
+    literal_identifier: &'static str,
+) -> BlockchainRecord {
+    BlockchainRecord {
+        self_id: Chain::Custom(definition.chain_id),
+        num_chain_id: definition.chain_id,
+        literal_identifier,
+        contract: definition.contract,
+        contract_creation_block: definition.contract_creation_block,
+        confirmation_depth: definition.confirmation_depth,
+        gas_limit_const_part: definition.gas_limit_const_part as u128,
+        multisend_contract_opt: definition.multisend_contract,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchains::chains::chain_from_chain_identifier_opt;
+    use crate::test_utils::utils::ensure_node_home_directory_exists;
+    use ethereum_types::H160;
+
+    // The registry is global, so every test uses chain names and ids of its own
+    fn make_definition(name: &str, chain_id: u64) -> CustomChainDefinition {
+        CustomChainDefinition {
+            name: name.to_string(),
+            chain_id,
+            contract: H160([0x42; 20]),
+            contract_creation_block: 1234,
+            confirmation_depth: 3,
+            gas_limit_const_part: 60_000,
+            multisend_contract: None,
+        }
+    }
+
+    #[test]
+    fn registered_chain_behaves_like_an_official_one() {
+        let result = register_custom_chain(make_definition("local-devnet", 31337)).unwrap();
+
+        assert_eq!(result, Chain::Custom(31337));
+        assert_eq!(
+            result.rec(),
+            &BlockchainRecord {
+                self_id: Chain::Custom(31337),
+                num_chain_id: 31337,
+                literal_identifier: "local-devnet",
+                contract: H160([0x42; 20]),
+                contract_creation_block: 1234,
+                confirmation_depth: 3,
+                gas_limit_const_part: 60_000,
+                multisend_contract_opt: None,
+            }
+        );
+        assert_eq!(result.to_string(), "local-devnet".to_string());
+        assert_eq!(Chain::from("local-devnet"), result);
+        assert_eq!(
+            chain_from_chain_identifier_opt("local-devnet"),
+            Some(result)
+        );
+        assert_eq!(result.is_mainnet(), false);
+    }
+
+    #[test]
+    fn registering_the_same_chain_twice_is_harmless() {
+        let first = register_custom_chain(make_definition("twice-devnet", 31338));
+
+        let second = register_custom_chain(make_definition("twice-devnet", 31338));
+
+        assert_eq!(first, Ok(Chain::Custom(31338)));
+        assert_eq!(second, Ok(Chain::Custom(31338)));
+    }
+
+    #[test]
+    fn conflicting_chains_are_rejected() {
+        register_custom_chain(make_definition("conflict-devnet", 31339)).unwrap();
+        let mut changed_contract = make_definition("conflict-devnet", 31339);
+        changed_contract.contract = H160([0x43; 20]);
+
+        let same_name = register_custom_chain(make_definition("conflict-devnet", 31340));
+        let same_id = register_custom_chain(make_definition("other-devnet", 31339));
+        let different_contract = register_custom_chain(changed_contract);
+        let official_name = register_custom_chain(make_definition("polygon-mainnet", 31341));
+        let official_id = register_custom_chain(make_definition("fake-polygon", 137));
+        let bad_name = register_custom_chain(make_definition("My Devnet", 31342));
+
+        assert_eq!(
+            same_name,
+            Err(
+                "Chain 'conflict-devnet' with id 31340 conflicts with the chain 'conflict-devnet' \
+                with id 31339 defined earlier"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            same_id,
+            Err(
+                "Chain 'other-devnet' with id 31339 conflicts with the chain 'conflict-devnet' \
+                with id 31339 defined earlier"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            different_contract,
+            Err(
+                "Chain 'conflict-devnet' with id 31339 conflicts with the chain 'conflict-devnet' \
+                with id 31339 defined earlier"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            official_name,
+            Err("Chain name 'polygon-mainnet' belongs to an official chain".to_string())
+        );
+        assert_eq!(
+            official_id,
+            Err("Chain id 137 already belongs to the official chain 'polygon-mainnet'".to_string())
+        );
+        assert_eq!(
+            bad_name,
+            Err(
+                "Chain name 'My Devnet' must consist of lowercase letters, digits and hyphens"
+                    .to_string()
+            )
+        );
+        assert_eq!(chain_from_chain_identifier_opt("other-devnet"), None);
+    }
+
+    #[test]
+    fn load_custom_chains_reads_the_file_in_the_data_directory() {
+        let data_dir = ensure_node_home_directory_exists(
+            "custom_chains",
+            "load_custom_chains_reads_the_file_in_the_data_directory",
+        );
+        fs::write(
+            data_dir.join(CUSTOM_CHAINS_FILE_NAME),
+            "[[chain]]\n\
+            name = \"file-devnet-one\"\n\
+            chain_id = 31343\n\
+            contract = \"0x4242424242424242424242424242424242424242\"\n\
+            contract_creation_block = 1234\n\
+            confirmation_depth = 3\n\
+            gas_limit_const_part = 60000\n\
+            \n\
+            [[chain]]\n\
+            name = \"file-devnet-two\"\n\
+            chain_id = 31344\n\
+            contract = \"0x4242424242424242424242424242424242424242\"\n\
+            contract_creation_block = 0\n\
+            confirmation_depth = 0\n\
+            gas_limit_const_part = 55000\n\
+            multisend_contract = \"0x4343434343434343434343434343434343434343\"\n",
+        )
+        .unwrap();
+
+        let result = load_custom_chains(&data_dir);
+
+        assert_eq!(result, Ok(vec![Chain::Custom(31343), Chain::Custom(31344)]));
+        assert_eq!(Chain::Custom(31343).rec().contract, H160([0x42; 20]));
+        assert_eq!(Chain::Custom(31344).rec().gas_limit_const_part, 55_000);
+        assert_eq!(Chain::Custom(31343).multisend_contract_opt(), None);
+        assert_eq!(
+            Chain::Custom(31344).multisend_contract_opt(),
+            Some(H160([0x43; 20]))
+        );
+    }
+
+    #[test]
+    fn multisend_contract_can_be_named_for_an_official_chain() {
+        let result = register_multisend_contract(MultisendDefinition {
+            chain: "dev".to_string(),
+            contract: H160([0x44; 20]),
+        });
+        let again = register_multisend_contract(MultisendDefinition {
+            chain: "dev".to_string(),
+            contract: H160([0x44; 20]),
+        });
+        let conflicting = register_multisend_contract(MultisendDefinition {
+            chain: "dev".to_string(),
+            contract: H160([0x45; 20]),
+        });
+        let custom = register_multisend_contract(MultisendDefinition {
+            chain: "some-devnet".to_string(),
+            contract: H160([0x44; 20]),
+        });
+
+        assert_eq!(result, Ok(Chain::Dev));
+        assert_eq!(again, Ok(Chain::Dev));
+        assert_eq!(
+            conflicting,
+            Err(format!(
+                "Chain 'dev' already has the multisend contract {:?}",
+                H160([0x44; 20])
+            ))
+        );
+        assert_eq!(
+            custom,
+            Err(
+                "Chain 'some-devnet' is not an official chain; a custom chain names its \
+                multisend contract in its own definition"
+                    .to_string()
+            )
+        );
+        assert_eq!(Chain::Dev.rec().multisend_contract_opt, None);
+        assert_eq!(Chain::Dev.multisend_contract_opt(), Some(H160([0x44; 20])));
+        assert_eq!(official_multisend_contract_opt(Chain::PolyAmoy), None);
+    }
+
+    #[test]
+    fn load_custom_chains_reads_multisend_contracts_for_official_chains() {
+        let data_dir = ensure_node_home_directory_exists(
+            "custom_chains",
+            "load_custom_chains_reads_multisend_contracts_for_official_chains",
+        );
+        fs::write(
+            data_dir.join(CUSTOM_CHAINS_FILE_NAME),
+            "[[multisend]]\n\
+            chain = \"base-sepolia\"\n\
+            contract = \"0x4646464646464646464646464646464646464646\"\n",
+        )
+        .unwrap();
+
+        let result = load_custom_chains(&data_dir);
+
+        assert_eq!(result, Ok(vec![]));
+        assert_eq!(
+            Chain::BaseSepolia.multisend_contract_opt(),
+            Some(H160([0x46; 20]))
+        );
+    }
+
+    #[test]
+    fn load_custom_chains_is_satisfied_without_a_file() {
+        let data_dir = ensure_node_home_directory_exists(
+            "custom_chains",
+            "load_custom_chains_is_satisfied_without_a_file",
+        );
+
+        let result = load_custom_chains(&data_dir);
+
+        assert_eq!(result, Ok(vec![]));
+    }
+
+    #[test]
+    fn load_custom_chains_complains_about_a_malformed_file() {
+        let data_dir = ensure_node_home_directory_exists(
+            "custom_chains",
+            "load_custom_chains_complains_about_a_malformed_file",
+        );
+        let path = data_dir.join(CUSTOM_CHAINS_FILE_NAME);
+        fs::write(
+            &path,
+            "[[chain]]\nname = \"incomplete-devnet\"\nchain_id = 31345\n",
+        )
+        .unwrap();
+
+        let result = load_custom_chains(&data_dir);
+
+        let message = result.unwrap_err();
+        assert!(
+            message.starts_with(&format!("Couldn't parse {}: ", path.display())),
+            "{}",
+            message
+        );
+        assert!(message.contains("missing field `contract`"), "{}", message);
+        assert_eq!(chain_from_chain_identifier_opt("incomplete-devnet"), None);
+    }
+}
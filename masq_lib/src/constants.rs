@@ -5,7 +5,7 @@ use crate::data_version::DataVersion;
 use const_format::concatcp;
 
 pub const DEFAULT_CHAIN: Chain = Chain::BaseMainnet;
-pub const CURRENT_SCHEMA_VERSION: usize = 11;
+pub const CURRENT_SCHEMA_VERSION: usize = 16;
 
 pub const HIGHEST_RANDOM_CLANDESTINE_PORT: u16 = 9999;
 pub const HTTP_PORT: u16 = 80;
@@ -36,6 +36,27 @@ pub const BASE_MAINNET_CONTRACT_CREATION_BLOCK: u64 = 19_711_235;
 pub const BASE_SEPOLIA_CONTRACT_CREATION_BLOCK: u64 = 14_732_730;
 pub const MULTINODE_TESTNET_CONTRACT_CREATION_BLOCK: u64 = 0;
 
+// Number of blocks a received payment must be buried under before the Node credits it
+pub const ETH_MAINNET_CONFIRMATION_DEPTH: u64 = 12;
+pub const ETH_ROPSTEN_CONFIRMATION_DEPTH: u64 = 12;
+pub const POLYGON_MAINNET_CONFIRMATION_DEPTH: u64 = 128;
+pub const POLYGON_AMOY_CONFIRMATION_DEPTH: u64 = 128;
+pub const BASE_MAINNET_CONFIRMATION_DEPTH: u64 = 10;
+pub const BASE_SEPOLIA_CONFIRMATION_DEPTH: u64 = 10;
+pub const MULTINODE_TESTNET_CONFIRMATION_DEPTH: u64 = 0;
+
+// Gas a MASQ transfer needs on top of the gas paid for the bytes of its transaction data
+pub const ETH_GAS_LIMIT_CONST_PART: u128 = 55_000;
+pub const POLYGON_GAS_LIMIT_CONST_PART: u128 = 70_000;
+pub const BASE_GAS_LIMIT_CONST_PART: u128 = 70_000;
+pub const MULTINODE_TESTNET_GAS_LIMIT_CONST_PART: u128 = 55_000;
+
+// Definitions of chains beyond the official ones, looked for in the data directory
+pub const CUSTOM_CHAINS_FILE_NAME: &str = "chains.toml";
+
+// HTTP request headers that carry the framing of the request, and so must never be scrubbed
+pub const UNSCRUBBABLE_HTTP_HEADERS: &[&str] = &["host", "content-length", "transfer-encoding"];
+
 //Migration versions
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
@@ -47,6 +68,7 @@ pub const DNS_RESOLVER_FAILURE_CURRENT_VERSION: DataVersion = DataVersion { majo
 pub const GOSSIP_CURRENT_VERSION: DataVersion = DataVersion { major: 0, minor: 1 };
 pub const GOSSIP_FAILURE_CURRENT_VERSION: DataVersion = DataVersion { major: 0, minor: 1 };
 pub const NODE_RECORD_INNER_CURRENT_VERSION: DataVersion = DataVersion { major: 0, minor: 1 };
+pub const LIVE_CORES_PACKAGE_CURRENT_VERSION: DataVersion = DataVersion { major: 0, minor: 1 };
 
 //error codes
 ////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -67,6 +89,7 @@ pub const UNRECOGNIZED_PARAMETER: u64 = CONFIGURATOR_PREFIX | 11;
 pub const NON_PARSABLE_VALUE: u64 = CONFIGURATOR_PREFIX | 12;
 pub const MISSING_DATA: u64 = CONFIGURATOR_PREFIX | 13;
 pub const UNKNOWN_ERROR: u64 = CONFIGURATOR_PREFIX | 14;
+pub const DATABASE_BACKUP_ERROR: u64 = CONFIGURATOR_PREFIX | 15;
 
 //moved from masq_lib/messages
 pub const UI_NODE_COMMUNICATION_PREFIX: u64 = 0x8000_0000_0000_0000;
@@ -78,12 +101,17 @@ pub const SETUP_ERROR: u64 = UI_NODE_COMMUNICATION_PREFIX | 5;
 pub const TIMEOUT_ERROR: u64 = UI_NODE_COMMUNICATION_PREFIX | 6;
 pub const SCAN_ERROR: u64 = UI_NODE_COMMUNICATION_PREFIX | 7;
 pub const EXIT_COUNTRY_MISSING_COUNTRIES_ERROR: u64 = UI_NODE_COMMUNICATION_PREFIX | 8;
+pub const AUTOMAP_INACTIVE_ERROR: u64 = UI_NODE_COMMUNICATION_PREFIX | 9;
+pub const AUTOMAP_ERROR: u64 = UI_NODE_COMMUNICATION_PREFIX | 10;
+pub const COUNTRY_DATABASE_ERROR: u64 = UI_NODE_COMMUNICATION_PREFIX | 11;
 
 //accountant
 pub const ACCOUNTANT_PREFIX: u64 = 0x0040_0000_0000_0000;
 pub const REQUEST_WITH_NO_VALUES: u64 = ACCOUNTANT_PREFIX | 1;
 pub const REQUEST_WITH_MUTUALLY_EXCLUSIVE_PARAMS: u64 = ACCOUNTANT_PREFIX | 2;
 pub const VALUE_EXCEEDS_ALLOWED_LIMIT: u64 = ACCOUNTANT_PREFIX | 3;
+pub const BAN_LIST_ERROR: u64 = ACCOUNTANT_PREFIX | 4;
+pub const THRESHOLD_SIMULATION_ERROR: u64 = ACCOUNTANT_PREFIX | 5;
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
@@ -134,6 +162,22 @@ mod tests {
         assert_eq!(BASE_MAINNET_CONTRACT_CREATION_BLOCK, 19_711_235);
         assert_eq!(BASE_SEPOLIA_CONTRACT_CREATION_BLOCK, 14_732_730);
         assert_eq!(MULTINODE_TESTNET_CONTRACT_CREATION_BLOCK, 0);
+        assert_eq!(ETH_MAINNET_CONFIRMATION_DEPTH, 12);
+        assert_eq!(ETH_ROPSTEN_CONFIRMATION_DEPTH, 12);
+        assert_eq!(POLYGON_MAINNET_CONFIRMATION_DEPTH, 128);
+        assert_eq!(POLYGON_AMOY_CONFIRMATION_DEPTH, 128);
+        assert_eq!(BASE_MAINNET_CONFIRMATION_DEPTH, 10);
+        assert_eq!(BASE_SEPOLIA_CONFIRMATION_DEPTH, 10);
+        assert_eq!(MULTINODE_TESTNET_CONFIRMATION_DEPTH, 0);
+        assert_eq!(ETH_GAS_LIMIT_CONST_PART, 55_000);
+        assert_eq!(POLYGON_GAS_LIMIT_CONST_PART, 70_000);
+        assert_eq!(BASE_GAS_LIMIT_CONST_PART, 70_000);
+        assert_eq!(MULTINODE_TESTNET_GAS_LIMIT_CONST_PART, 55_000);
+        assert_eq!(CUSTOM_CHAINS_FILE_NAME, "chains.toml");
+        assert_eq!(
+            UNSCRUBBABLE_HTTP_HEADERS,
+            &["host", "content-length", "transfer-encoding"]
+        );
         assert_eq!(CONFIGURATOR_PREFIX, 0x0001_0000_0000_0000);
         assert_eq!(CONFIGURATOR_READ_ERROR, CONFIGURATOR_PREFIX | 1);
         assert_eq!(CONFIGURATOR_WRITE_ERROR, CONFIGURATOR_PREFIX | 2);
@@ -152,6 +196,7 @@ mod tests {
         assert_eq!(NON_PARSABLE_VALUE, CONFIGURATOR_PREFIX | 12);
         assert_eq!(MISSING_DATA, CONFIGURATOR_PREFIX | 13);
         assert_eq!(UNKNOWN_ERROR, CONFIGURATOR_PREFIX | 14);
+        assert_eq!(DATABASE_BACKUP_ERROR, CONFIGURATOR_PREFIX | 15);
         assert_eq!(UI_NODE_COMMUNICATION_PREFIX, 0x8000_0000_0000_0000);
         assert_eq!(NODE_LAUNCH_ERROR, UI_NODE_COMMUNICATION_PREFIX | 1);
         assert_eq!(NODE_NOT_RUNNING_ERROR, UI_NODE_COMMUNICATION_PREFIX | 2);
@@ -160,6 +205,9 @@ mod tests {
         assert_eq!(SETUP_ERROR, UI_NODE_COMMUNICATION_PREFIX | 5);
         assert_eq!(TIMEOUT_ERROR, UI_NODE_COMMUNICATION_PREFIX | 6);
         assert_eq!(SCAN_ERROR, UI_NODE_COMMUNICATION_PREFIX | 7);
+        assert_eq!(AUTOMAP_INACTIVE_ERROR, UI_NODE_COMMUNICATION_PREFIX | 9);
+        assert_eq!(AUTOMAP_ERROR, UI_NODE_COMMUNICATION_PREFIX | 10);
+        assert_eq!(COUNTRY_DATABASE_ERROR, UI_NODE_COMMUNICATION_PREFIX | 11);
         assert_eq!(ACCOUNTANT_PREFIX, 0x0040_0000_0000_0000);
         assert_eq!(REQUEST_WITH_NO_VALUES, ACCOUNTANT_PREFIX | 1);
         assert_eq!(
@@ -167,6 +215,8 @@ mod tests {
             ACCOUNTANT_PREFIX | 2
         );
         assert_eq!(VALUE_EXCEEDS_ALLOWED_LIMIT, ACCOUNTANT_PREFIX | 3);
+        assert_eq!(BAN_LIST_ERROR, ACCOUNTANT_PREFIX | 4);
+        assert_eq!(THRESHOLD_SIMULATION_ERROR, ACCOUNTANT_PREFIX | 5);
         assert_eq!(CENTRAL_DELIMITER, '@');
         assert_eq!(CHAIN_IDENTIFIER_DELIMITER, ':');
         assert_eq!(POLYGON_FAMILY, "polygon");
@@ -201,6 +251,10 @@ mod tests {
             NODE_RECORD_INNER_CURRENT_VERSION,
             DataVersion { major: 0, minor: 1 }
         );
+        assert_eq!(
+            LIVE_CORES_PACKAGE_CURRENT_VERSION,
+            DataVersion { major: 0, minor: 1 }
+        );
         assert_eq!(PAYLOAD_ZERO_SIZE, 0usize);
     }
 
@@ -213,6 +267,7 @@ mod tests {
             GOSSIP_CURRENT_VERSION,
             GOSSIP_FAILURE_CURRENT_VERSION,
             NODE_RECORD_INNER_CURRENT_VERSION,
+            LIVE_CORES_PACKAGE_CURRENT_VERSION,
         ]
         .into_iter()
         .for_each(|item| {
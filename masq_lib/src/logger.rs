@@ -1,4 +1,4 @@
-use std::fmt::{Debug, Formatter};
+use std::fmt::{Debug, Display, Formatter};
 // Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 use crate::constants::{
     CLIENT_REQUEST_PAYLOAD_CURRENT_VERSION, CLIENT_RESPONSE_PAYLOAD_CURRENT_VERSION,
@@ -17,25 +17,35 @@ use crate::ui_gateway::NodeToUiMessage;
 use actix::Recipient;
 use lazy_static::lazy_static;
 use log::logger;
-use log::Level;
+pub use log::Level;
 #[allow(unused_imports)]
 use log::Metadata;
 #[allow(unused_imports)]
 use log::Record;
+use serde_derive::Serialize;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::str::FromStr;
 use std::sync::Mutex;
 use std::{io, thread};
 use time::format_description::parse;
+use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
 
-pub static mut POINTER_TO_FORMAT_FUNCTION: fn(
-    &mut dyn io::Write,
-    OffsetDateTime,
-    &Record,
-) -> Result<(), io::Error> = heading_format_function;
+pub type FormatFunction = fn(&mut dyn io::Write, OffsetDateTime, &Record) -> Result<(), io::Error>;
+pub type LogFields = Vec<(&'static str, String)>;
+
+pub static mut POINTER_TO_FORMAT_FUNCTION: FormatFunction = heading_format_function;
 const UI_MESSAGE_LOG_LEVEL: Level = Level::Info;
 pub const TIME_FORMATTING_STRING: &str =
     "[year]-[month]-[day] [hour]:[minute]:[second].[subsecond digits:3]";
 
+thread_local! {
+    // The format functions run synchronously on the thread that logs, so this is how the fields of
+    // a structured log reach them without altering the log::Record.
+    static LOG_FIELDS: RefCell<LogFields> = RefCell::new(vec![]);
+}
+
 lazy_static! {
     pub static ref LOG_RECIPIENT_OPT: Mutex<Option<Recipient<NodeToUiMessage>>> = Mutex::new(None);
 }
@@ -67,6 +77,11 @@ impl Debug for Logger {
 
 #[macro_export]
 macro_rules! trace {
+    ($logger: expr; $($key: literal => $value: expr),+; $($arg:tt)*) => {
+        $logger.structured($crate::logger::Level::Trace, || {
+            (format!($($arg)*), vec![$(($key, $value.to_string())),+])
+        })
+    };
     ($logger: expr, $($arg:tt)*) => {
         $logger.trace(|| format!($($arg)*))
     };
@@ -74,6 +89,11 @@ macro_rules! trace {
 
 #[macro_export]
 macro_rules! debug {
+    ($logger: expr; $($key: literal => $value: expr),+; $($arg:tt)*) => {
+        $logger.structured($crate::logger::Level::Debug, || {
+            (format!($($arg)*), vec![$(($key, $value.to_string())),+])
+        })
+    };
     ($logger: expr, $($arg:tt)*) => {
         $logger.debug(|| format!($($arg)*))
     };
@@ -81,6 +101,11 @@ macro_rules! debug {
 
 #[macro_export]
 macro_rules! info {
+    ($logger: expr; $($key: literal => $value: expr),+; $($arg:tt)*) => {
+        $logger.structured($crate::logger::Level::Info, || {
+            (format!($($arg)*), vec![$(($key, $value.to_string())),+])
+        })
+    };
     ($logger: expr, $($arg:tt)*) => {
         $logger.info(|| format!($($arg)*))
     };
@@ -88,6 +113,11 @@ macro_rules! info {
 
 #[macro_export]
 macro_rules! warning {
+    ($logger: expr; $($key: literal => $value: expr),+; $($arg:tt)*) => {
+        $logger.structured($crate::logger::Level::Warn, || {
+            (format!($($arg)*), vec![$(($key, $value.to_string())),+])
+        })
+    };
     ($logger: expr, $($arg:tt)*) => {
         $logger.warning(|| format!($($arg)*))
     };
@@ -95,6 +125,11 @@ macro_rules! warning {
 
 #[macro_export]
 macro_rules! error {
+    ($logger: expr; $($key: literal => $value: expr),+; $($arg:tt)*) => {
+        $logger.structured($crate::logger::Level::Error, || {
+            (format!($($arg)*), vec![$(($key, $value.to_string())),+])
+        })
+    };
     ($logger: expr, $($arg:tt)*) => {
         $logger.error(|| format!($($arg)*))
     };
@@ -180,22 +215,32 @@ impl Logger {
         self.level_enabled(Level::Error)
     }
 
-    fn generic_log<F>(&self, level: Level, log_function: F)
+    pub fn structured<F>(&self, level: Level, log_function: F)
     where
-        F: FnOnce() -> String,
+        F: FnOnce() -> (String, LogFields),
     {
         match (self.level_enabled(level), level.le(&UI_MESSAGE_LOG_LEVEL)) {
             (true, true) => {
-                let msg = log_function();
-                self.log(level, msg.clone());
+                let (msg, fields) = log_function();
+                self.log_with_fields(level, msg.clone(), fields);
                 Self::transmit(msg, level.into());
             }
-            (true, false) => self.log(level, log_function()),
-            (false, true) => Self::transmit(log_function(), level.into()),
+            (true, false) => {
+                let (msg, fields) = log_function();
+                self.log_with_fields(level, msg, fields)
+            }
+            (false, true) => Self::transmit(log_function().0, level.into()),
             _ => {}
         }
     }
 
+    fn generic_log<F>(&self, level: Level, log_function: F)
+    where
+        F: FnOnce() -> String,
+    {
+        self.structured(level, || (log_function(), vec![]))
+    }
+
     pub fn log(&self, level: Level, msg: String) {
         logger().log(
             &Record::builder()
@@ -206,6 +251,12 @@ impl Logger {
         );
     }
 
+    pub fn log_with_fields(&self, level: Level, msg: String, fields: LogFields) {
+        LOG_FIELDS.with(|log_fields| *log_fields.borrow_mut() = fields);
+        self.log(level, msg);
+        LOG_FIELDS.with(|log_fields| log_fields.borrow_mut().clear());
+    }
+
     pub fn log_file_heading() -> String {
         format!(
             "
@@ -230,6 +281,46 @@ impl Logger {
         )
     }
 
+    pub fn json_log_file_heading(timestamp: OffsetDateTime) -> String {
+        let fields = vec![
+            ("node_version", env!("CARGO_PKG_VERSION").to_string()),
+            (
+                "database_schema_version",
+                CURRENT_SCHEMA_VERSION.to_string(),
+            ),
+            ("os", std::env::consts::OS.to_string()),
+            (
+                "client_request_payload_migrations",
+                CLIENT_REQUEST_PAYLOAD_CURRENT_VERSION.to_string(),
+            ),
+            (
+                "client_response_payload_migrations",
+                CLIENT_RESPONSE_PAYLOAD_CURRENT_VERSION.to_string(),
+            ),
+            (
+                "dns_resolve_failure_migrations",
+                DNS_RESOLVER_FAILURE_CURRENT_VERSION.to_string(),
+            ),
+            ("gossip_migrations", GOSSIP_CURRENT_VERSION.to_string()),
+            (
+                "gossip_failure_migrations",
+                GOSSIP_FAILURE_CURRENT_VERSION.to_string(),
+            ),
+            (
+                "node_record_inner_migrations",
+                NODE_RECORD_INNER_CURRENT_VERSION.to_string(),
+            ),
+        ];
+        JsonLogRecord::new(
+            timestamp,
+            Level::Info,
+            "LogFileHeading",
+            "MASQ Node log file heading".to_string(),
+            fields,
+        )
+        .to_string()
+    }
+
     fn data_version_pretty_print(dv: DataVersion) -> String {
         format!("({}.{})", dv.major, dv.minor)
     }
@@ -295,6 +386,89 @@ pub fn real_format_function(
     write.write_fmt(*record.args())
 }
 
+pub fn json_format_function(
+    write: &mut dyn io::Write,
+    timestamp: OffsetDateTime,
+    record: &Record,
+) -> Result<(), io::Error> {
+    let fields = LOG_FIELDS.with(|log_fields| log_fields.borrow().clone());
+    let json_record = JsonLogRecord::new(
+        timestamp,
+        record.level(),
+        record.module_path().unwrap_or("<unnamed>"),
+        record.args().to_string(),
+        fields,
+    );
+    write.write_all(json_record.to_string().as_bytes())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!("Unknown log format: '{}'", s)),
+        }
+    }
+}
+
+impl LogFormat {
+    pub fn format_function(self) -> FormatFunction {
+        match self {
+            LogFormat::Text => real_format_function,
+            LogFormat::Json => json_format_function,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonLogRecord<'a> {
+    timestamp: String,
+    level: String,
+    thread: String,
+    logger: &'a str,
+    message: String,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    fields: BTreeMap<&'static str, String>,
+}
+
+impl<'a> JsonLogRecord<'a> {
+    fn new(
+        timestamp: OffsetDateTime,
+        level: Level,
+        logger: &'a str,
+        message: String,
+        fields: LogFields,
+    ) -> Self {
+        let thread_id_str = format!("{:?}", thread::current().id());
+        Self {
+            timestamp: timestamp
+                .format(&Rfc3339)
+                .expect("Unable to format date and time."),
+            level: level.to_string(),
+            thread: thread_id_str[9..(thread_id_str.len() - 1)].to_string(),
+            logger,
+            message,
+            fields: fields.into_iter().collect(),
+        }
+    }
+}
+
+impl Display for JsonLogRecord<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let json = serde_json::to_string(self).expect("Log record serialization failed");
+        write!(f, "{}", json)
+    }
+}
+
 #[cfg(feature = "log_recipient_test")]
 lazy_static! {
     pub static ref INITIALIZATION_COUNTER: Mutex<MutexIncrementInset> =
@@ -347,6 +521,7 @@ mod tests {
     use std::thread;
     use std::thread::{JoinHandle, ThreadId};
     use std::time::{Duration, SystemTime};
+    use test_utilities::byte_array_reader_writer::ByteArrayWriter;
     use time::format_description::parse;
     use time::OffsetDateTime;
 
@@ -979,6 +1154,133 @@ mod tests {
         tlh.exists_log_containing("error! 42");
     }
 
+    #[test]
+    fn macros_with_fields_log_the_message() {
+        init_test_logging();
+        let _guard = prepare_test_environment();
+        let logger = Logger::new("test");
+
+        trace!(logger; "field" => 1; "trace! with fields {}", 42);
+        debug!(logger; "field" => 2; "debug! with fields {}", 42);
+        info!(logger; "field" => 3, "another" => "x"; "info! with fields {}", 42);
+        warning!(logger; "field" => 4; "warning! with fields {}", 42);
+        error!(logger; "field" => 5; "error! with fields {}", 42);
+
+        let tlh = TestLogHandler::new();
+        tlh.exists_log_containing("TRACE: test: trace! with fields 42");
+        tlh.exists_log_containing("DEBUG: test: debug! with fields 42");
+        tlh.exists_log_containing("INFO: test: info! with fields 42");
+        tlh.exists_log_containing("WARN: test: warning! with fields 42");
+        tlh.exists_log_containing("ERROR: test: error! with fields 42");
+        LOG_FIELDS.with(|log_fields| assert!(log_fields.borrow().is_empty()));
+    }
+
+    #[test]
+    fn json_format_function_writes_one_object_per_record() {
+        let timestamp = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        let mut buffer = ByteArrayWriter::new();
+        LOG_FIELDS.with(|log_fields| {
+            *log_fields.borrow_mut() = vec![
+                ("wallet", "0x1234".to_string()),
+                ("amount", "5".to_string()),
+            ]
+        });
+
+        json_format_function(
+            &mut buffer,
+            timestamp,
+            &Record::builder()
+                .args(format_args!("Paid \"{}\"", "debt"))
+                .module_path(Some("Accountant"))
+                .level(Level::Warn)
+                .build(),
+        )
+        .unwrap();
+
+        LOG_FIELDS.with(|log_fields| log_fields.borrow_mut().clear());
+        let thread_id = thread_id_as_string(thread::current().id());
+        assert_eq!(
+            buffer.get_string(),
+            format!(
+                "{{\"timestamp\":\"2023-11-14T22:13:20Z\",\"level\":\"WARN\",\"thread\":\"{}\",\
+                 \"logger\":\"Accountant\",\"message\":\"Paid \\\"debt\\\"\",\
+                 \"fields\":{{\"amount\":\"5\",\"wallet\":\"0x1234\"}}}}",
+                thread_id
+            )
+        );
+    }
+
+    #[test]
+    fn json_format_function_omits_missing_fields() {
+        let timestamp = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        let mut buffer = ByteArrayWriter::new();
+
+        json_format_function(
+            &mut buffer,
+            timestamp,
+            &Record::builder()
+                .args(format_args!("Plain message"))
+                .level(Level::Info)
+                .build(),
+        )
+        .unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&buffer.get_string()).unwrap();
+        assert_eq!(json["logger"], "<unnamed>");
+        assert_eq!(json["message"], "Plain message");
+        assert_eq!(json.get("fields"), None);
+    }
+
+    #[test]
+    fn json_log_file_heading_contains_heading_data() {
+        let timestamp = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+
+        let result = Logger::json_log_file_heading(timestamp);
+
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(json["timestamp"], "2023-11-14T22:13:20Z");
+        assert_eq!(json["level"], "INFO");
+        assert_eq!(json["logger"], "LogFileHeading");
+        let fields = &json["fields"];
+        assert_eq!(fields["node_version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(
+            fields["database_schema_version"],
+            CURRENT_SCHEMA_VERSION.to_string()
+        );
+        assert_eq!(fields["os"], std::env::consts::OS);
+        assert_eq!(
+            fields["client_request_payload_migrations"],
+            CLIENT_REQUEST_PAYLOAD_CURRENT_VERSION.to_string()
+        );
+        assert_eq!(
+            fields["node_record_inner_migrations"],
+            NODE_RECORD_INNER_CURRENT_VERSION.to_string()
+        );
+        assert_eq!(result.contains('\n'), false);
+    }
+
+    #[test]
+    fn log_format_can_be_parsed() {
+        assert_eq!(LogFormat::from_str("text"), Ok(LogFormat::Text));
+        assert_eq!(LogFormat::from_str("JSON"), Ok(LogFormat::Json));
+        assert_eq!(
+            LogFormat::from_str("xml"),
+            Err("Unknown log format: 'xml'".to_string())
+        );
+    }
+
+    #[test]
+    fn log_format_chooses_format_function() {
+        assert_eq!(
+            LogFormat::Text.format_function() as usize,
+            real_format_function as usize
+        );
+        assert_eq!(
+            LogFormat::Json.format_function() as usize,
+            json_format_function as usize
+        );
+    }
+
     #[test]
     fn debug_for_logger() {
         let logger = Logger::new("my new logger");
@@ -436,6 +436,94 @@ fire_and_forget_message!(UiUnmarshalError, "unmarshalError");
 // These messages are sent to or by the Node only
 ///////////////////////////////////////////////////////////////////
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum UiAutomapAction {
+    Remap,
+    SwitchProtocol(String),
+    DeleteMappings,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiAutomapRequest {
+    #[serde(rename = "actionOpt")]
+    pub action_opt: Option<UiAutomapAction>,
+}
+conversation_message!(UiAutomapRequest, "automap");
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiAutomapResponse {
+    #[serde(rename = "protocolOpt")]
+    pub protocol_opt: Option<String>,
+    #[serde(rename = "publicIpOpt")]
+    pub public_ip_opt: Option<String>,
+    #[serde(rename = "holePorts")]
+    pub hole_ports: Vec<u16>,
+    // Zero means the router granted a permanent mapping
+    #[serde(rename = "leaseLifetimeSecsOpt")]
+    pub lease_lifetime_secs_opt: Option<u64>,
+    // Counted from the moment the response was made
+    #[serde(rename = "nextRemapSecsOpt")]
+    pub next_remap_secs_opt: Option<u64>,
+    #[serde(rename = "lastErrorOpt")]
+    pub last_error_opt: Option<String>,
+}
+conversation_message!(UiAutomapResponse, "automap");
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiBackupRequest {}
+conversation_message!(UiBackupRequest, "backup");
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiBackupResponse {
+    pub path: String,
+    pub timestamp: i64,
+    #[serde(rename = "schemaVersion")]
+    pub schema_version: u64,
+    // Older backups deleted to keep the number of copies within the limit
+    #[serde(rename = "removedBackups")]
+    pub removed_backups: Vec<String>,
+}
+conversation_message!(UiBackupResponse, "backup");
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiBanListRequest {}
+conversation_message!(UiBanListRequest, "banList");
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiBannedWallet {
+    pub wallet: String,
+    #[serde(rename = "bannedAtOpt")]
+    pub banned_at_opt: Option<i64>,
+    // "delinquency", "manual", or "unknown" for bans older than the ban metadata
+    pub reason: String,
+    #[serde(rename = "balanceWeiOpt")]
+    pub balance_wei_opt: Option<String>,
+    #[serde(rename = "noteOpt")]
+    pub note_opt: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiBanListResponse {
+    pub bans: Vec<UiBannedWallet>,
+}
+conversation_message!(UiBanListResponse, "banList");
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiBanRequest {
+    pub wallet: String,
+    #[serde(rename = "noteOpt")]
+    pub note_opt: Option<String>,
+}
+conversation_message!(UiBanRequest, "ban");
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiBanResponse {
+    // False if the wallet was banned already; a note in the request then replaces its old one
+    #[serde(rename = "newlyBanned")]
+    pub newly_banned: bool,
+}
+conversation_message!(UiBanResponse, "ban");
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct UiChangePasswordRequest {
     #[serde(rename = "oldPasswordOpt")]
@@ -569,13 +657,74 @@ pub struct UiConnectionStatusRequest {}
 
 conversation_message!(UiConnectionStatusRequest, "connectionStatus");
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum UiNeighborConnectionStage {
+    StageZero,
+    TcpConnectionEstablished,
+    NeighborshipEstablished,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum UiNeighborConnectionFailure {
+    TcpConnectionFailed,
+    NoGossipResponseReceived,
+    PassLoopFound,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiNeighborConnectionProgress {
+    #[serde(rename = "nodeDescriptor")]
+    pub node_descriptor: String,
+    // Differs from the descriptor's address once the Node has been passed to another Node
+    #[serde(rename = "currentPeerAddr")]
+    pub current_peer_addr: String,
+    pub stage: UiNeighborConnectionStage,
+    // Present only if the stage is Failed
+    #[serde(rename = "failureOpt")]
+    pub failure_opt: Option<UiNeighborConnectionFailure>,
+    // Seconds since the Unix epoch; absent if the stage hasn't changed since connecting began
+    #[serde(rename = "stageChangedAtOpt")]
+    pub stage_changed_at_opt: Option<u64>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct UiConnectionStatusResponse {
     pub stage: UiConnectionStage,
+    #[serde(rename = "fullNeighborCount")]
+    pub full_neighbor_count: u32,
+    #[serde(rename = "possibleExitCount")]
+    pub possible_exit_count: u32,
+    // One entry for each Node descriptor the Node is trying to connect through
+    #[serde(rename = "neighborProgress")]
+    pub neighbor_progress: Vec<UiNeighborConnectionProgress>,
 }
 
 conversation_message!(UiConnectionStatusResponse, "connectionStatus");
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiCountryDatabaseRequest {
+    // Re-read the country database file from the data directory before responding
+    pub reload: bool,
+}
+conversation_message!(UiCountryDatabaseRequest, "countryDatabase");
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiCountryDatabaseResponse {
+    // None means the Node is using the country data it was built with
+    #[serde(rename = "versionOpt")]
+    pub version_opt: Option<String>,
+    #[serde(rename = "dataFilePath")]
+    pub data_file_path: String,
+    #[serde(rename = "countryCount")]
+    pub country_count: u64,
+    #[serde(rename = "ipv4BlockCount")]
+    pub ipv4_block_count: u64,
+    #[serde(rename = "ipv6BlockCount")]
+    pub ipv6_block_count: u64,
+}
+conversation_message!(UiCountryDatabaseResponse, "countryDatabase");
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct UiDescriptorRequest {}
 conversation_message!(UiDescriptorRequest, "descriptor");
@@ -587,6 +736,23 @@ pub struct UiDescriptorResponse {
 }
 conversation_message!(UiDescriptorResponse, "descriptor");
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiEncryptionRequest {}
+conversation_message!(UiEncryptionRequest, "encryption");
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiEncryptionResponse {
+    // "none" if the database has no password, "legacy" until it has been re-encrypted, else the KDF
+    pub scheme: String,
+    #[serde(rename = "opsLimitOpt")]
+    pub ops_limit_opt: Option<u64>,
+    #[serde(rename = "memLimitKibOpt")]
+    pub mem_limit_kib_opt: Option<u64>,
+    #[serde(rename = "saltOpt")]
+    pub salt_opt: Option<String>,
+}
+conversation_message!(UiEncryptionResponse, "encryption");
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct UiFinancialsRequest {
     #[serde(rename = "statsRequired")]
@@ -681,6 +847,8 @@ pub struct UiPayableAccount {
     pub balance_gwei: u64,
     #[serde(rename = "pendingPayableHashOpt")]
     pub pending_payable_hash_opt: Option<String>,
+    #[serde(rename = "writtenOff")]
+    pub written_off: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
@@ -730,6 +898,34 @@ pub struct UiGenerateWalletsResponse {
 }
 conversation_message!(UiGenerateWalletsResponse, "generateWallets");
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiLedgerExportRequest {
+    // Seconds since the Unix epoch; the start is inclusive and the end is exclusive
+    #[serde(rename = "startTimestampOpt")]
+    pub start_timestamp_opt: Option<i64>,
+    #[serde(rename = "endTimestampOpt")]
+    pub end_timestamp_opt: Option<i64>,
+}
+conversation_message!(UiLedgerExportRequest, "ledgerExport");
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiLedgerEntry {
+    pub timestamp: i64,
+    pub kind: String,
+    pub wallet: String,
+    // A decimal string, because wei amounts can outgrow the integers JSON parsers handle safely
+    #[serde(rename = "amountWei")]
+    pub amount_wei: String,
+    #[serde(rename = "transactionHashOpt")]
+    pub transaction_hash_opt: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiLedgerExportResponse {
+    pub entries: Vec<UiLedgerEntry>,
+}
+conversation_message!(UiLedgerExportResponse, "ledgerExport");
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct UiLogBroadcast {
     pub msg: String,
@@ -749,6 +945,16 @@ pub enum SerializableLogLevel {
 pub struct UiNewPasswordBroadcast {}
 fire_and_forget_message!(UiNewPasswordBroadcast, "newPassword");
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiPardonRequest {
+    pub wallet: String,
+}
+conversation_message!(UiPardonRequest, "pardon");
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiPardonResponse {}
+conversation_message!(UiPardonResponse, "pardon");
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct UiRecoverSeedSpec {
     #[serde(rename = "mnemonicPhrase")]
@@ -780,6 +986,22 @@ conversation_message!(UiRecoverWalletsRequest, "recoverWallets");
 pub struct UiRecoverWalletsResponse {}
 conversation_message!(UiRecoverWalletsResponse, "recoverWallets");
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiRotateKeyRequest {}
+conversation_message!(UiRotateKeyRequest, "rotateKey");
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiRotateKeyResponse {
+    #[serde(rename = "publicKey")]
+    pub public_key: String,
+    #[serde(rename = "previousPublicKey")]
+    pub previous_public_key: String,
+    // How long the previous key is still honored
+    #[serde(rename = "gracePeriodSecs")]
+    pub grace_period_secs: u32,
+}
+conversation_message!(UiRotateKeyResponse, "rotateKey");
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Hash)]
 pub enum ScanType {
     Payables,
@@ -831,6 +1053,57 @@ conversation_message!(UiShutdownRequest, "shutdown");
 pub struct UiShutdownResponse {}
 conversation_message!(UiShutdownResponse, "shutdown");
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiDebtSnapshot {
+    #[serde(rename = "ageSec")]
+    pub age_sec: u64,
+    #[serde(rename = "balanceGwei")]
+    pub balance_gwei: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiThresholdSimulationRequest {
+    // If absent, the Node simulates the thresholds it is currently using
+    #[serde(rename = "paymentThresholdsOpt")]
+    pub payment_thresholds_opt: Option<UiPaymentThresholds>,
+    pub history: Vec<UiDebtSnapshot>,
+}
+conversation_message!(UiThresholdSimulationRequest, "thresholdSimulation");
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiThresholdCurvePoint {
+    #[serde(rename = "ageSec")]
+    pub age_sec: u64,
+    #[serde(rename = "payableThresholdGweiOpt")]
+    pub payable_threshold_gwei_opt: Option<u64>,
+    #[serde(rename = "delinquencyThresholdGweiOpt")]
+    pub delinquency_threshold_gwei_opt: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiThresholdSimulationStep {
+    #[serde(rename = "ageSec")]
+    pub age_sec: u64,
+    #[serde(rename = "balanceGwei")]
+    pub balance_gwei: u64,
+    #[serde(rename = "paymentDue")]
+    pub payment_due: bool,
+    pub delinquent: bool,
+    #[serde(rename = "newlyBanned")]
+    pub newly_banned: bool,
+    #[serde(rename = "newlyUnbanned")]
+    pub newly_unbanned: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiThresholdSimulationResponse {
+    #[serde(rename = "paymentThresholds")]
+    pub payment_thresholds: UiPaymentThresholds,
+    pub curve: Vec<UiThresholdCurvePoint>,
+    pub steps: Vec<UiThresholdSimulationStep>,
+}
+conversation_message!(UiThresholdSimulationResponse, "thresholdSimulation");
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct UiWalletAddressesRequest {
     #[serde(rename = "dbPassword")]
@@ -908,21 +1181,114 @@ pub struct UiSetExitLocationResponse {
     pub exit_countries: Option<Vec<String>>,
     #[serde(rename = "missingCountries")]
     pub missing_countries: Vec<String>,
+    #[serde(rename = "countryMismatches")]
+    pub country_mismatches: Vec<UiCountryMismatch>,
 }
 conversation_message!(UiSetExitLocationResponse, "exitLocation");
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
-pub struct UiGetNeighborhoodGraphRequest {}
+pub struct UiCountryMismatch {
+    #[serde(rename = "publicKey")]
+    pub public_key: String,
+    #[serde(rename = "declaredCountry")]
+    pub declared_country: String,
+    #[serde(rename = "observedCountry")]
+    pub observed_country: String,
+}
+
+// Every filter that is present must be satisfied for a Node to be included
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct UiGetNeighborhoodGraphRequest {
+    #[serde(rename = "countryCodesOpt")]
+    pub country_codes_opt: Option<Vec<String>>,
+    #[serde(rename = "minDegreeOpt")]
+    pub min_degree_opt: Option<u32>,
+    #[serde(rename = "maxDegreeOpt")]
+    pub max_degree_opt: Option<u32>,
+    #[serde(rename = "maxDistanceOpt")]
+    pub max_distance_opt: Option<u32>,
+}
 
 conversation_message!(UiGetNeighborhoodGraphRequest, "neighborhoodGraph");
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum UiNeighborRelationship {
+    Root,
+    Full,
+    Half,
+    Introduced,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiNeighborhoodGraphNode {
+    #[serde(rename = "publicKey")]
+    pub public_key: String,
+    #[serde(rename = "nodeAddrOpt")]
+    pub node_addr_opt: Option<String>,
+    pub version: u32,
+    #[serde(rename = "ratePack")]
+    pub rate_pack: UiRatePack,
+    // Only a country the country data agrees with
+    #[serde(rename = "countryCodeOpt")]
+    pub country_code_opt: Option<String>,
+    // The country the Node declared, if the country data contradicts it
+    #[serde(rename = "contradictedCountryCodeOpt")]
+    pub contradicted_country_code_opt: Option<String>,
+    #[serde(rename = "acceptsConnections")]
+    pub accepts_connections: bool,
+    #[serde(rename = "routesData")]
+    pub routes_data: bool,
+    pub relationship: UiNeighborRelationship,
+    // Hops from this Node along neighbor links; absent if the Node can't be reached that way
+    #[serde(rename = "distanceOpt")]
+    pub distance_opt: Option<u32>,
+    pub neighbors: Vec<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct UiGetNeighborhoodGraphResponse {
     pub graph: String,
+    pub nodes: Vec<UiNeighborhoodGraphNode>,
+    #[serde(rename = "routeDiversityShortfallOpt")]
+    pub route_diversity_shortfall_opt: Option<String>,
 }
 
 conversation_message!(UiGetNeighborhoodGraphResponse, "neighborhoodGraph");
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiGossipRejectionsRequest {}
+
+conversation_message!(UiGossipRejectionsRequest, "gossipRejections");
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiGossipRejections {
+    #[serde(rename = "peerIp")]
+    pub peer_ip: String,
+    // Gossip dropped because the peer sent it faster than its own rate limit allows
+    #[serde(rename = "rateLimited")]
+    pub rate_limited: u64,
+    // Gossip dropped because the Node as a whole was receiving more than it could handle
+    pub overloaded: u64,
+    #[serde(rename = "whileBanned")]
+    pub while_banned: u64,
+    pub malformed: u64,
+    #[serde(rename = "badSignature")]
+    pub bad_signature: u64,
+    pub malefactor: u64,
+    #[serde(rename = "banCount")]
+    pub ban_count: u32,
+    // Seconds since the Unix epoch; absent unless the peer is banned right now
+    #[serde(rename = "bannedUntilOpt")]
+    pub banned_until_opt: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UiGossipRejectionsResponse {
+    pub peers: Vec<UiGossipRejections>,
+}
+
+conversation_message!(UiGossipRejectionsResponse, "gossipRejections");
+
 #[cfg(test)]
 mod tests {
     use super::*;
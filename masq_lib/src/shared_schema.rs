@@ -22,7 +22,8 @@ pub const BLOCKCHAIN_SERVICE_HELP: &str =
      https://mainnet.base.org \nhttps://base.llamarpc.com \nhttps://1rpc.io/base \nhttps://base-rpc.publicnode.com";
 pub const CHAIN_HELP: &str =
     "The blockchain network MASQ Node will configure itself to use. You must ensure the \
-    Ethereum client specified by --blockchain-service-url communicates with the same blockchain network.";
+    Ethereum client specified by --blockchain-service-url communicates with the same blockchain network. \
+    Besides the official networks, you may name one defined in a chains.toml file in the --data-directory.";
 pub const CONFIG_FILE_HELP: &str =
     "Optional TOML file containing configuration that doesn't often change. Should contain only \
      scalar items, string or numeric, whose names are exactly the same as the command-line parameters \
@@ -54,11 +55,27 @@ pub const EARNING_WALLET_HELP: &str =
      (case-insensitive). If you already have a derivation-path earning wallet, don't supply this. \
      If you have supplied an earning wallet address before, either don't supply it again or be \
      careful to supply exactly the same one you supplied before.";
+pub const EARNING_WALLET_SHARES_HELP: &str =
+    "Additional Ethereum wallet addresses that receive a percentage of your earnings, in the form \
+     <address>:<percentage>,<address>:<percentage> (e.g. 0x0123...cdef:30,0x4567...89ab:20). The \
+     --earning-wallet keeps whatever is left over, so the percentages must total less than 100. \
+     Payments to any of these wallets are recognized as yours. Once supplied, the shares are \
+     remembered until you supply different ones; supply the parameter with no value to stop sharing.";
 pub const IP_ADDRESS_HELP: &str = "The public IP address of your MASQ Node: that is, the IPv4 \
      address at which other Nodes can contact yours. If you're running your Node behind \
      a router, this will be the IP address of the router. If this IP address starts with 192.168 or 10.0, \
      it's a local address rather than a public address, and other Nodes won't be able to see yours. \
      --ip is meaningless except in --neighborhood-mode standard.";
+pub const KEY_ROTATION_INTERVAL_HELP: &str =
+    "How often, in hours, the Node generates a new key pair and hands its identity over to it, so that its \
+     public key doesn't serve as a long-lived tracking handle. Neighbors learn of the new key through Gossip \
+     signed by both the old and new keys, and the old key is still honored for a short grace period afterward. \
+     If you leave this out, the key is rotated only when you ask for it with the rotate-key command.";
+pub const LOG_FORMAT_HELP: &str =
+    "The layout of the lines in the Node's logfile. 'text' writes the traditional human-readable lines; \
+     'json' writes one JSON object per line, with the timestamp, level, thread, logger name, message and any \
+     structured fields as separate keys, which is easier for log collectors to parse. The logfile is rotated and \
+     compressed the same way in both formats.";
 pub const LOG_LEVEL_HELP: &str =
     "The minimum severity of the logs that should appear in the Node's logfile. You should probably not specify \
      a level lower than the default unless you're doing testing or forensics: a Node at the 'trace' log level \
@@ -135,11 +152,30 @@ pub const MIN_HOPS_HELP: &str =
     If you want to specify a minimum hops count, you can do so by entering a number after the \
     '--min-hops' parameter. For example, '--min-hops 4' would require at least 4 hops. If you fail \
     to provide this argument, the system will default to a minimum hops count of 3.";
+pub const MIN_ROUTE_PREFIXES_HELP: &str =
+    "The fewest distinct networks that the hops of each of the Node's routes must come from, counting a /24 \
+     network for IPv4 or a /48 network for IPv6 as one. Routes that draw on fewer networks than this aren't used, \
+     even if that means no route can be found. If you leave this out, there is no minimum.";
 pub const REAL_USER_HELP: &str =
     "The user whose identity Node will assume when dropping privileges after bootstrapping. Since Node refuses to \
      run with root privilege after bootstrapping, you might want to use this if you start the Node as root, or if \
      you start the Node using pkexec or some other method that doesn't populate the SUDO_xxx variables. Use a value \
      like <uid>:<gid>:<home directory>.";
+pub const ROUTE_DIVERSITY_HELP: &str =
+    "How far apart the Nodes on each of the Node's routes must be, so that one operator running many Nodes in \
+     one subnet or with one hosting provider can't easily occupy every hop of a route. 'subnet' keeps any two \
+     hops out of the same /24 (IPv4) or /48 (IPv6) network; 'country' does that and also keeps any two hops \
+     out of the same country; 'off' imposes no constraint. There's no way to keep hops out of the same country \
+     without also keeping them out of the same network. Unless it's 'off', a Node whose IP address isn't known \
+     can't be a hop, since it could be in any network. Routes that can't satisfy the constraint aren't used, \
+     and the neighborhood-graph command will say so. If you leave this out, the default is 'subnet'.";
+pub const SCRUB_HEADERS_HELP: &str =
+    "Identifying headers to scrub from plain HTTP requests before they leave this Node for the exit Node: \
+     'on' scrubs X-Forwarded-For, Forwarded, Via, X-Real-IP, Referer, User-Agent and Cookie; you can also name \
+     the headers yourself, separated by commas. Referer is cut down to the origin of the referring page, User-Agent \
+     is replaced with a generic one, and Cookie is removed only from requests made on behalf of some other site; \
+     any other header you name is removed. Host, Content-Length and Transfer-Encoding can't be scrubbed. TLS \
+     traffic is encrypted end to end and can't be scrubbed. If you leave this out, the default is 'off'.";
 pub const SCANS_HELP: &str =
     "The Node, when running, performs various periodic scans, including scanning for payables that need to be paid, \
     for pending payables that have arrived (and are no longer pending), for incoming receivables that need to be \
@@ -184,6 +220,16 @@ pub const PAYMENT_THRESHOLDS_HELP: &str = "\
      6. Unban Below gwei: When a delinquent Node has been banned due to non-payment, the receivables balance must be paid \
      below this level -- in gwei of MASQ -- to cause them to be unbanned. In most cases, you'll want this to be set the same \
      as Permanent Debt Allowed gwei.";
+pub const PAYABLE_WRITE_OFF_HELP: &str = "\
+     These three parameters describe when debts to other Nodes are written off instead of being paid. A \
+     written-off payable is excluded from payable scans and from the payable totals, but it is still listed by the \
+     'financials' command with a flag; a new debt to the same wallet revives it. A rule with a zero in any of its \
+     values is switched off, and by default both rules are off. The parameters must be always supplied all together, \
+     delimited by vertical bars and in the right order.\n\n\
+     1. Unseen Days: A payable whose creditor's wallet hasn't appeared in Gossip, nor been paid, for this many days \
+     is written off.\n\n\
+     2. Dust Threshold gwei: A payable smaller than this -- in gwei of MASQ -- is considered dust.\n\n\
+     3. Dust Days: A dust payable that hasn't been paid for this many days is written off.";
 pub const SCAN_INTERVALS_HELP:&str = "\
      These three intervals describe the length of three different scan cycles running automatically in the background \
      since the Node has connected to a qualified neighborhood that consists of neighbors enabling a complete 3-hop \
@@ -234,7 +280,7 @@ pub fn chain_arg<'a>() -> Arg<'a, 'a> {
         .value_name("CHAIN")
         .min_values(0)
         .max_values(1)
-        .possible_values(official_chain_names())
+        .validator(common_validators::validate_chain)
         .help(CHAIN_HELP)
 }
 
@@ -349,7 +395,7 @@ pub fn ui_port_arg(help: &str) -> Arg {
         .help(help)
 }
 
-fn common_parameter_with_separate_u64_values<'a>(name: &'a str, help: &'a str) -> Arg<'a, 'a> {
+pub fn common_parameter_with_separate_u64_values<'a>(name: &'a str, help: &'a str) -> Arg<'a, 'a> {
     Arg::with_name(name)
         .long(name)
         .value_name(Box::leak(name.to_uppercase().into_boxed_str()))
@@ -411,6 +457,15 @@ pub fn shared_app(head: App<'static, 'static>) -> App<'static, 'static> {
         EARNING_WALLET_HELP,
         common_validators::validate_ethereum_address,
     ))
+    .arg(
+        Arg::with_name("earning-wallet-shares")
+            .long("earning-wallet-shares")
+            .value_name("EARNING-WALLET-SHARES")
+            .min_values(0)
+            .max_values(1)
+            .validator(common_validators::validate_earning_wallet_shares)
+            .help(EARNING_WALLET_SHARES_HELP),
+    )
     .arg(
         Arg::with_name("fake-public-key")
             .long("fake-public-key")
@@ -429,6 +484,25 @@ pub fn shared_app(head: App<'static, 'static>) -> App<'static, 'static> {
             .validator(common_validators::validate_ip_address)
             .help(IP_ADDRESS_HELP),
     )
+    .arg(
+        Arg::with_name("key-rotation-interval")
+            .long("key-rotation-interval")
+            .value_name("HOURS")
+            .min_values(0)
+            .max_values(1)
+            .validator(common_validators::validate_non_zero_u16)
+            .help(KEY_ROTATION_INTERVAL_HELP),
+    )
+    .arg(
+        Arg::with_name("log-format")
+            .long("log-format")
+            .value_name("FORMAT")
+            .min_values(0)
+            .max_values(1)
+            .possible_values(&["text", "json"])
+            .case_insensitive(true)
+            .help(LOG_FORMAT_HELP),
+    )
     .arg(
         Arg::with_name("log-level")
             .long("log-level")
@@ -450,6 +524,15 @@ pub fn shared_app(head: App<'static, 'static>) -> App<'static, 'static> {
             .help(MAPPING_PROTOCOL_HELP),
     )
     .arg(min_hops_arg())
+    .arg(
+        Arg::with_name("min-route-prefixes")
+            .long("min-route-prefixes")
+            .value_name("PREFIXES")
+            .min_values(0)
+            .max_values(1)
+            .validator(common_validators::validate_non_zero_u16)
+            .help(MIN_ROUTE_PREFIXES_HELP),
+    )
     .arg(
         Arg::with_name("neighborhood-mode")
             .long("neighborhood-mode")
@@ -468,6 +551,16 @@ pub fn shared_app(head: App<'static, 'static>) -> App<'static, 'static> {
             .help(NEIGHBORS_HELP),
     )
     .arg(real_user_arg())
+    .arg(
+        Arg::with_name("route-diversity")
+            .long("route-diversity")
+            .value_name("DIVERSITY")
+            .min_values(0)
+            .max_values(1)
+            .possible_values(&["off", "subnet", "country"])
+            .case_insensitive(true)
+            .help(ROUTE_DIVERSITY_HELP),
+    )
     .arg(
         Arg::with_name("scans")
             .long("scans")
@@ -476,6 +569,15 @@ pub fn shared_app(head: App<'static, 'static>) -> App<'static, 'static> {
             .possible_values(&["on", "off"])
             .help(SCANS_HELP),
     )
+    .arg(
+        Arg::with_name("scrub-headers")
+            .long("scrub-headers")
+            .value_name("HEADERS")
+            .min_values(0)
+            .max_values(1)
+            .validator(common_validators::validate_scrubbed_headers)
+            .help(SCRUB_HEADERS_HELP),
+    )
     .arg(common_parameter_with_separate_u64_values(
         "scan-intervals",
         SCAN_INTERVALS_HELP,
@@ -488,10 +590,17 @@ pub fn shared_app(head: App<'static, 'static>) -> App<'static, 'static> {
         "payment-thresholds",
         PAYMENT_THRESHOLDS_HELP,
     ))
+    .arg(common_parameter_with_separate_u64_values(
+        "payable-write-off",
+        PAYABLE_WRITE_OFF_HELP,
+    ))
 }
 
 pub mod common_validators {
-    use crate::constants::LOWEST_USABLE_INSECURE_PORT;
+    use crate::blockchains::chains::chain_from_chain_identifier_opt;
+    use crate::constants::{
+        CUSTOM_CHAINS_FILE_NAME, LOWEST_USABLE_INSECURE_PORT, UNSCRUBBABLE_HTTP_HEADERS,
+    };
     use ip_country_lib::dbip_country::COUNTRIES;
     use regex::Regex;
     use std::net::IpAddr;
@@ -522,6 +631,19 @@ pub mod common_validators {
         }
     }
 
+    // Custom chains must have been loaded from the data directory before this runs
+    pub fn validate_chain(chain: String) -> Result<(), String> {
+        match chain_from_chain_identifier_opt(&chain) {
+            Some(_) => Ok(()),
+            None => Err(format!(
+                "'{}' is neither an official chain ({}) nor defined in {}",
+                chain,
+                super::official_chain_names().join(", "),
+                CUSTOM_CHAINS_FILE_NAME
+            )),
+        }
+    }
+
     pub fn validate_clandestine_port(clandestine_port: String) -> Result<(), String> {
         match clandestine_port.parse::<u16>() {
             Ok(clandestine_port) if clandestine_port >= LOWEST_USABLE_INSECURE_PORT => Ok(()),
@@ -600,6 +722,47 @@ pub mod common_validators {
         }
     }
 
+    pub fn validate_earning_wallet_shares(shares: String) -> Result<(), String> {
+        let mut total = 0u32;
+        for share in shares.split(',') {
+            let (address, percentage) = match share.trim().split_once(':') {
+                Some(pair) => pair,
+                None => return Err(shares),
+            };
+            validate_ethereum_address(address.to_string()).map_err(|_| shares.clone())?;
+            match percentage.parse::<u8>() {
+                Ok(percentage) if percentage > 0 => total += percentage as u32,
+                _ => return Err(shares),
+            }
+        }
+        if total < 100 {
+            Ok(())
+        } else {
+            Err(shares)
+        }
+    }
+
+    pub fn validate_scrubbed_headers(headers: String) -> Result<(), String> {
+        match headers.to_lowercase().as_str() {
+            "on" | "off" => return Ok(()),
+            _ => (),
+        }
+        let token = Regex::new("^[A-Za-z0-9!#$%&'*+.^_`|~-]+$")
+            .expect("Failed to compile regular expression");
+        for header in headers.split(',').map(|header| header.trim()) {
+            if !token.is_match(header) {
+                return Err(headers);
+            }
+            if UNSCRUBBABLE_HTTP_HEADERS.contains(&header.to_lowercase().as_str()) {
+                return Err(format!(
+                    "{} is needed to deliver the request and can't be scrubbed",
+                    header
+                ));
+            }
+        }
+        Ok(())
+    }
+
     pub fn validate_derivation_path(path: String) -> Result<(), String> {
         let possible_path = path.parse::<DerivationPath>();
 
@@ -727,6 +890,7 @@ impl ConfiguratorError {
 mod tests {
     use super::*;
     use crate::blockchains::chains::Chain;
+    use crate::blockchains::custom_chains::{register_custom_chain, CustomChainDefinition};
     use crate::shared_schema::common_validators::validate_non_zero_u16;
     use crate::shared_schema::{common_validators, official_chain_names};
     use std::collections::HashSet;
@@ -748,7 +912,8 @@ mod tests {
         assert_eq!(
             CHAIN_HELP,
             "The blockchain network MASQ Node will configure itself to use. You must ensure the \
-             Ethereum client specified by --blockchain-service-url communicates with the same blockchain network."
+             Ethereum client specified by --blockchain-service-url communicates with the same blockchain network. \
+             Besides the official networks, you may name one defined in a chains.toml file in the --data-directory."
         );
         assert_eq!(
             CONFIG_FILE_HELP,
@@ -795,6 +960,15 @@ mod tests {
              If you have supplied an earning wallet address before, either don't supply it again or be \
              careful to supply exactly the same one you supplied before."
         );
+        assert_eq!(
+            EARNING_WALLET_SHARES_HELP,
+            "Additional Ethereum wallet addresses that receive a percentage of your earnings, in the \
+             form <address>:<percentage>,<address>:<percentage> (e.g. 0x0123...cdef:30,0x4567...89ab:20). \
+             The --earning-wallet keeps whatever is left over, so the percentages must total less \
+             than 100. Payments to any of these wallets are recognized as yours. Once supplied, the \
+             shares are remembered until you supply different ones; supply the parameter with no \
+             value to stop sharing."
+        );
         assert_eq!(
             IP_ADDRESS_HELP,
             "The public IP address of your MASQ Node: that is, the IPv4 \
@@ -803,6 +977,20 @@ mod tests {
              it's a local address rather than a public address, and other Nodes won't be able to see yours. \
              --ip is meaningless except in --neighborhood-mode standard."
         );
+        assert_eq!(
+            KEY_ROTATION_INTERVAL_HELP,
+            "How often, in hours, the Node generates a new key pair and hands its identity over to it, so that its \
+             public key doesn't serve as a long-lived tracking handle. Neighbors learn of the new key through Gossip \
+             signed by both the old and new keys, and the old key is still honored for a short grace period afterward. \
+             If you leave this out, the key is rotated only when you ask for it with the rotate-key command."
+        );
+        assert_eq!(
+            LOG_FORMAT_HELP,
+            "The layout of the lines in the Node's logfile. 'text' writes the traditional human-readable lines; \
+             'json' writes one JSON object per line, with the timestamp, level, thread, logger name, message and any \
+             structured fields as separate keys, which is easier for log collectors to parse. The logfile is rotated and \
+             compressed the same way in both formats."
+        );
         assert_eq!(
             LOG_LEVEL_HELP,
             "The minimum severity of the logs that should appear in the Node's logfile. You should probably not specify \
@@ -884,6 +1072,31 @@ mod tests {
              '--min-hops' parameter. For example, '--min-hops 4' would require at least 4 hops. If you fail \
              to provide this argument, the system will default to a minimum hops count of 3."
         );
+        assert_eq!(
+            MIN_ROUTE_PREFIXES_HELP,
+            "The fewest distinct networks that the hops of each of the Node's routes must come from, counting a /24 \
+             network for IPv4 or a /48 network for IPv6 as one. Routes that draw on fewer networks than this aren't used, \
+             even if that means no route can be found. If you leave this out, there is no minimum."
+        );
+        assert_eq!(
+            ROUTE_DIVERSITY_HELP,
+            "How far apart the Nodes on each of the Node's routes must be, so that one operator running many Nodes in \
+             one subnet or with one hosting provider can't easily occupy every hop of a route. 'subnet' keeps any two \
+             hops out of the same /24 (IPv4) or /48 (IPv6) network; 'country' does that and also keeps any two hops \
+             out of the same country; 'off' imposes no constraint. There's no way to keep hops out of the same country \
+             without also keeping them out of the same network. Unless it's 'off', a Node whose IP address isn't known \
+             can't be a hop, since it could be in any network. Routes that can't satisfy the constraint aren't used, \
+             and the neighborhood-graph command will say so. If you leave this out, the default is 'subnet'."
+        );
+        assert_eq!(
+            SCRUB_HEADERS_HELP,
+            "Identifying headers to scrub from plain HTTP requests before they leave this Node for the exit Node: \
+             'on' scrubs X-Forwarded-For, Forwarded, Via, X-Real-IP, Referer, User-Agent and Cookie; you can also name \
+             the headers yourself, separated by commas. Referer is cut down to the origin of the referring page, User-Agent \
+             is replaced with a generic one, and Cookie is removed only from requests made on behalf of some other site; \
+             any other header you name is removed. Host, Content-Length and Transfer-Encoding can't be scrubbed. TLS \
+             traffic is encrypted end to end and can't be scrubbed. If you leave this out, the default is 'off'."
+        );
         assert_eq!(
             REAL_USER_HELP,
             "The user whose identity Node will assume when dropping privileges after bootstrapping. Since Node refuses to \
@@ -963,6 +1176,18 @@ mod tests {
              below this level -- in gwei of MASQ -- to cause them to be unbanned. In most cases, you'll want this to be set the same \
              as Permanent Debt Allowed gwei."
         );
+        assert_eq!(
+            PAYABLE_WRITE_OFF_HELP,
+            "These three parameters describe when debts to other Nodes are written off instead of being paid. A \
+             written-off payable is excluded from payable scans and from the payable totals, but it is still listed by the \
+             'financials' command with a flag; a new debt to the same wallet revives it. A rule with a zero in any of its \
+             values is switched off, and by default both rules are off. The parameters must be always supplied all together, \
+             delimited by vertical bars and in the right order.\n\n\
+             1. Unseen Days: A payable whose creditor's wallet hasn't appeared in Gossip, nor been paid, for this many days \
+             is written off.\n\n\
+             2. Dust Threshold gwei: A payable smaller than this -- in gwei of MASQ -- is considered dust.\n\n\
+             3. Dust Days: A dust payable that hasn't been paid for this many days is written off."
+        );
         assert_eq!(
             SCAN_INTERVALS_HELP,
             "These three intervals describe the length of three different scan cycles running automatically in the background \
@@ -1075,6 +1300,46 @@ mod tests {
         assert_eq!(Ok(()), result);
     }
 
+    #[test]
+    fn validate_chain_accepts_official_chains() {
+        official_chain_names().iter().for_each(|name| {
+            assert_eq!(common_validators::validate_chain(name.to_string()), Ok(()))
+        });
+    }
+
+    #[test]
+    fn validate_chain_accepts_registered_custom_chains() {
+        register_custom_chain(CustomChainDefinition {
+            name: "validated-devnet".to_string(),
+            chain_id: 31346,
+            contract: Default::default(),
+            contract_creation_block: 0,
+            confirmation_depth: 0,
+            gas_limit_const_part: 55_000,
+            multisend_contract: None,
+        })
+        .unwrap();
+
+        let result = common_validators::validate_chain("validated-devnet".to_string());
+
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn validate_chain_rejects_unknown_chains() {
+        let result = common_validators::validate_chain("bitcoin".to_string());
+
+        assert_eq!(
+            result,
+            Err(
+                "'bitcoin' is neither an official chain (polygon-mainnet, eth-mainnet, \
+                base-mainnet, base-sepolia, polygon-amoy, eth-ropsten, dev) nor defined in \
+                chains.toml"
+                    .to_string()
+            )
+        );
+    }
+
     #[test]
     fn validate_clandestine_port_rejects_badly_formatted_port_number() {
         let result = common_validators::validate_clandestine_port(String::from("booga"));
@@ -1103,6 +1368,70 @@ mod tests {
         assert_eq!(result, Ok(()));
     }
 
+    #[test]
+    fn validate_earning_wallet_shares_accepts_shares_totaling_less_than_100() {
+        let result = common_validators::validate_earning_wallet_shares(
+            "0x0123456789012345678901234567890123456789:30, \
+             0xabcdefabcdefabcdefabcdefabcdefabcdefabcd:69"
+                .to_string(),
+        );
+
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn validate_scrubbed_headers_accepts_on_off_and_lists_of_header_names() {
+        vec!["on", "OFF", "Referer", "x-forwarded-for, Via,User-Agent"]
+            .into_iter()
+            .for_each(|headers| {
+                let result = common_validators::validate_scrubbed_headers(headers.to_string());
+
+                assert_eq!(result, Ok(()), "{}", headers);
+            });
+    }
+
+    #[test]
+    fn validate_scrubbed_headers_rejects_malformed_lists() {
+        vec!["", "Referer,", "X Forwarded For", "Referer:"]
+            .into_iter()
+            .for_each(|headers| {
+                let result = common_validators::validate_scrubbed_headers(headers.to_string());
+
+                assert_eq!(result, Err(headers.to_string()));
+            });
+    }
+
+    #[test]
+    fn validate_scrubbed_headers_rejects_headers_that_frame_the_request() {
+        let result =
+            common_validators::validate_scrubbed_headers("Referer, Content-Length".to_string());
+
+        assert_eq!(
+            result,
+            Err(
+                "Content-Length is needed to deliver the request and can't be scrubbed".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn validate_earning_wallet_shares_rejects_bad_shares() {
+        vec![
+            "0x0123456789012345678901234567890123456789",
+            "0x0123456789012345678901234567890123456789:0",
+            "0x0123456789012345678901234567890123456789:booga",
+            "0x01234567890123456789:30",
+            "0x0123456789012345678901234567890123456789:50,\
+             0xabcdefabcdefabcdefabcdefabcdefabcdefabcd:50",
+        ]
+        .into_iter()
+        .for_each(|shares| {
+            let result = common_validators::validate_earning_wallet_shares(shares.to_string());
+
+            assert_eq!(result, Err(shares.to_string()));
+        });
+    }
+
     #[test]
     fn validate_gas_price_zero() {
         let result = common_validators::validate_gas_price("0".to_string());
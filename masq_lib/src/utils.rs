@@ -2,6 +2,7 @@
 
 use crate::blockchains::chains::Chain;
 use dirs::{data_local_dir, home_dir};
+pub use ip_country_lib::checksum::fnv1a_64;
 use lazy_static::lazy_static;
 use std::fmt;
 use std::fmt::{Debug, Display, Formatter};
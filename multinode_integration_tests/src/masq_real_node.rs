@@ -181,6 +181,9 @@ impl NodeStartupConfig {
         args.push(self.neighborhood_mode.clone());
         args.push("--min-hops".to_string());
         args.push(format!("{}", self.min_hops as usize));
+        // All the Nodes in a cluster share the cluster's subnet, so their routes can't be diverse
+        args.push("--route-diversity".to_string());
+        args.push("off".to_string());
         if let LocalIpInfo::DistributedKnown(ip_addr) = self.ip_info {
             args.push("--ip".to_string());
             args.push(ip_addr.to_string());
@@ -1524,6 +1527,8 @@ mod tests {
                 "consume-only",
                 "--min-hops",
                 "6",
+                "--route-diversity",
+                "off",
                 "--ip",
                 "1.3.5.7",
                 "--neighbors",
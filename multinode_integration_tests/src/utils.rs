@@ -15,6 +15,7 @@ use node_lib::db_config::config_dao::{ConfigDao, ConfigDaoReal};
 use node_lib::neighborhood::gossip::AccessibleGossipRecord;
 use node_lib::neighborhood::node_record::NodeRecordInner_0v1;
 use node_lib::sub_lib::cryptde::{CryptData, PlainData};
+use node_lib::sub_lib::earning_wallet_shares::EarningWalletShares;
 use std::collections::BTreeSet;
 use std::io::{ErrorKind, Read, Write};
 use std::net::TcpStream;
@@ -135,6 +136,9 @@ impl From<&dyn MASQNode> for AccessibleGossipRecord {
                 routes_data: masq_node.routes_data(),
                 version: 0,
                 country_code_opt: masq_node.country_code_opt(),
+                earning_wallet_shares: EarningWalletShares::default(),
+                key_handover_opt: None,
+                session_key_opt: None,
             },
             node_addr_opt: Some(masq_node.node_addr()),
             signed_gossip: PlainData::new(b""),
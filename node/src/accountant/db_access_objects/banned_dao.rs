@@ -1,12 +1,16 @@
 // Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 
-use crate::accountant::db_access_objects::utils::{DaoFactoryReal, VigilantRusqliteFlatten};
+use crate::accountant::db_access_objects::utils::{
+    from_time_t, to_time_t, DaoFactoryReal, VigilantRusqliteFlatten,
+};
+use crate::accountant::db_big_integer::big_int_divider::BigIntDivider;
 use crate::database::rusqlite_wrappers::ConnectionWrapper;
 use crate::sub_lib::wallet::Wallet;
 use lazy_static::lazy_static;
-use rusqlite::{Error, ErrorCode, ToSql};
+use rusqlite::{named_params, Row, ToSql};
 use std::collections::HashSet;
 use std::sync::RwLock;
+use std::time::SystemTime;
 
 lazy_static! {
     pub static ref BAN_CACHE: BannedCache = BannedCache::default();
@@ -54,7 +58,7 @@ pub struct BannedCacheLoaderReal {}
 impl BannedCacheLoader for BannedCacheLoaderReal {
     fn load(&self, conn: Box<dyn ConnectionWrapper>) {
         let mut stmt = conn
-            .prepare("select wallet_address from banned")
+            .prepare("select wallet_address from banned where pardoned = 0")
             .expect("Failed to prepare statement");
         stmt.query_map([], |row| row.get::<usize, Wallet>(0))
             .expect("Failed to query banned table")
@@ -63,10 +67,58 @@ impl BannedCacheLoader for BannedCacheLoaderReal {
     }
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BanReason {
+    Delinquency,
+    Manual,
+}
+
+impl BanReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Delinquency => "delinquency",
+            Self::Manual => "manual",
+        }
+    }
+}
+
+impl TryFrom<&str> for BanReason {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "delinquency" => Ok(Self::Delinquency),
+            "manual" => Ok(Self::Manual),
+            x => Err(format!("Unrecognized ban reason: '{}'", x)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BanDetails {
+    pub timestamp: SystemTime,
+    pub reason: BanReason,
+    pub balance_wei_opt: Option<i128>,
+    pub note_opt: Option<String>,
+}
+
+// Bans recorded before the schema carried any metadata come back with all the options empty
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BannedAccount {
+    pub wallet: Wallet,
+    pub banned_at_opt: Option<SystemTime>,
+    pub reason_opt: Option<BanReason>,
+    pub balance_wei_opt: Option<i128>,
+    pub note_opt: Option<String>,
+}
+
 pub trait BannedDao: Send {
-    fn ban_list(&self) -> Vec<Wallet>;
-    fn ban(&self, wallet: &Wallet);
+    fn ban_list(&self) -> Vec<BannedAccount>;
+    fn ban(&self, wallet: &Wallet, details: BanDetails);
     fn unban(&self, wallet: &Wallet);
+    // A pardoned wallet stays out of the automatic delinquency bans until its debt is paid down
+    fn pardon(&self, wallet: &Wallet) -> bool;
+    fn annotate(&self, wallet: &Wallet, note: &str) -> bool;
 }
 
 pub trait BannedDaoFactory {
@@ -87,49 +139,85 @@ impl BannedDaoReal {
     pub fn new(conn: Box<dyn ConnectionWrapper>) -> Self {
         Self { conn }
     }
+
+    fn create_banned_account(row: &Row) -> rusqlite::Result<BannedAccount> {
+        let wallet: Wallet = row.get(0)?;
+        let banned_at_opt: Option<i64> = row.get(1)?;
+        let reason_opt: Option<String> = row.get(2)?;
+        let balance_high_b_opt: Option<i64> = row.get(3)?;
+        let balance_low_b_opt: Option<i64> = row.get(4)?;
+        let note_opt: Option<String> = row.get(5)?;
+        let balance_wei_opt = match (balance_high_b_opt, balance_low_b_opt) {
+            (Some(high_b), Some(low_b)) => Some(BigIntDivider::reconstitute(high_b, low_b)),
+            _ => None,
+        };
+        Ok(BannedAccount {
+            wallet,
+            banned_at_opt: banned_at_opt.map(from_time_t),
+            reason_opt: reason_opt.and_then(|reason| BanReason::try_from(reason.as_str()).ok()),
+            balance_wei_opt,
+            note_opt,
+        })
+    }
 }
 
 impl BannedDao for BannedDaoReal {
-    fn ban_list(&self) -> Vec<Wallet> {
+    fn ban_list(&self) -> Vec<BannedAccount> {
         let mut stmt = self
             .conn
-            .prepare("select wallet_address from banned")
+            .prepare(
+                "select wallet_address, banned_at, reason, balance_high_b, balance_low_b, note \
+                from banned where pardoned = 0 order by wallet_address",
+            )
             .expect("Failed to prepare a statement");
-        stmt.query_map([], |row| row.get(0))
+        stmt.query_map([], Self::create_banned_account)
             .expect("Couldn't retrieve delinquency-ban list: database corrupt")
             .vigilant_flatten()
             .collect()
     }
 
-    fn ban(&self, wallet: &Wallet) {
+    fn ban(&self, wallet: &Wallet, details: BanDetails) {
         if BAN_CACHE.is_banned(wallet) {
             return;
         }
 
+        // A pardoned wallet still has its row; banning it again overwrites the old record
         let mut stmt = self
             .conn
-            .prepare("insert into banned (wallet_address) values (?)")
+            .prepare(
+                "insert into banned (wallet_address, banned_at, reason, balance_high_b, balance_low_b, note, pardoned) \
+                values (:wallet, :banned_at, :reason, :balance_high_b, :balance_low_b, :note, 0) \
+                on conflict (wallet_address) do update set banned_at = :banned_at, reason = :reason, \
+                balance_high_b = :balance_high_b, balance_low_b = :balance_low_b, note = :note, pardoned = 0",
+            )
             .expect("Failed to prepare a statement");
-        let params: &[&dyn ToSql] = &[&wallet];
-        match stmt.execute(params) {
+        let (balance_high_b_opt, balance_low_b_opt) = match details.balance_wei_opt {
+            Some(balance_wei) => {
+                let (high_b, low_b) = BigIntDivider::deconstruct(balance_wei);
+                (Some(high_b), Some(low_b))
+            }
+            None => (None, None),
+        };
+        match stmt.execute(named_params! {
+            ":wallet": wallet,
+            ":banned_at": to_time_t(details.timestamp),
+            ":reason": details.reason.as_str(),
+            ":balance_high_b": balance_high_b_opt,
+            ":balance_low_b": balance_low_b_opt,
+            ":note": details.note_opt,
+        }) {
             Ok(_) => BAN_CACHE.insert(wallet.clone()),
-            Err(e) => match e {
-                Error::SqliteFailure(e, _) if e.code == ErrorCode::ConstraintViolation => {
-                    BAN_CACHE.insert(wallet.clone())
-                }
-                _ => panic!(
-                    "Could not initiate delinquency ban for {} because of database corruption: {}",
-                    wallet, e
-                ),
-            },
+            Err(e) => panic!(
+                "Could not initiate {} ban for {} because of database corruption: {}",
+                details.reason.as_str(),
+                wallet,
+                e
+            ),
         }
     }
 
     fn unban(&self, wallet: &Wallet) {
-        if !BAN_CACHE.is_banned(wallet) {
-            return;
-        }
-
+        // Pardoned wallets are no longer in the cache but their rows must go as well
         let mut stmt = self
             .conn
             .prepare("delete from banned where wallet_address = ?")
@@ -143,6 +231,40 @@ impl BannedDao for BannedDaoReal {
             ),
         }
     }
+
+    fn pardon(&self, wallet: &Wallet) -> bool {
+        let mut stmt = self
+            .conn
+            .prepare("update banned set pardoned = 1 where wallet_address = ? and pardoned = 0")
+            .expect("Failed to prepare a statement");
+        let params: &[&dyn ToSql] = &[&wallet];
+        match stmt.execute(params) {
+            Ok(0) => false,
+            Ok(_) => {
+                BAN_CACHE.remove(wallet);
+                true
+            }
+            Err(e) => panic!(
+                "Could not pardon {} because of database corruption: {}",
+                wallet, e
+            ),
+        }
+    }
+
+    fn annotate(&self, wallet: &Wallet, note: &str) -> bool {
+        let mut stmt = self
+            .conn
+            .prepare("update banned set note = ? where wallet_address = ? and pardoned = 0")
+            .expect("Failed to prepare a statement");
+        let params: &[&dyn ToSql] = &[&note, &wallet];
+        match stmt.execute(params) {
+            Ok(rows) => rows > 0,
+            Err(e) => panic!(
+                "Could not annotate the ban of {} because of database corruption: {}",
+                wallet, e
+            ),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -155,6 +277,24 @@ mod tests {
     use masq_lib::test_utils::utils::{
         ensure_node_home_directory_does_not_exist, ensure_node_home_directory_exists,
     };
+    use std::time::Duration;
+
+    fn make_ban_details(reason: BanReason) -> BanDetails {
+        BanDetails {
+            timestamp: from_time_t(1_700_000_000),
+            reason,
+            balance_wei_opt: Some(123_456_789_000_000_000_000),
+            note_opt: None,
+        }
+    }
+
+    fn make_subject(test_name: &str) -> BannedDaoReal {
+        let home_dir = ensure_node_home_directory_does_not_exist("banned_dao", test_name);
+        let conn = DbInitializerReal::default()
+            .initialize(&home_dir, DbInitializationConfig::test_default())
+            .unwrap();
+        BannedDaoReal::new(conn)
+    }
 
     #[test]
     fn banned_dao_can_ban_a_wallet_address() {
@@ -170,7 +310,10 @@ mod tests {
             BannedDaoReal::new(conn)
         };
 
-        subject.ban(&make_wallet("donalddrumph"));
+        subject.ban(
+            &make_wallet("donalddrumph"),
+            make_ban_details(BanReason::Delinquency),
+        );
 
         let conn = db_initializer
             .initialize(&home_dir, DbInitializationConfig::test_default())
@@ -198,11 +341,19 @@ mod tests {
             BannedDaoReal::new(conn)
         };
 
-        subject.ban(&make_wallet("no_duplicate_wallets"));
-        subject.ban(&make_wallet("no_duplicate_wallets"));
+        subject.ban(
+            &make_wallet("no_duplicate_wallets"),
+            make_ban_details(BanReason::Delinquency),
+        );
+        subject.ban(
+            &make_wallet("no_duplicate_wallets"),
+            make_ban_details(BanReason::Manual),
+        );
 
         let ban_list = subject.ban_list();
-        assert_eq!(vec![make_wallet("no_duplicate_wallets")], ban_list);
+        assert_eq!(ban_list.len(), 1);
+        assert_eq!(ban_list[0].wallet, make_wallet("no_duplicate_wallets"));
+        assert_eq!(ban_list[0].reason_opt, Some(BanReason::Delinquency));
     }
 
     #[test]
@@ -217,7 +368,10 @@ mod tests {
             BannedDaoReal::new(conn)
         };
 
-        subject.ban(&make_wallet("forgot_to_init"));
+        subject.ban(
+            &make_wallet("forgot_to_init"),
+            make_ban_details(BanReason::Delinquency),
+        );
     }
 
     #[test]
@@ -309,7 +463,7 @@ mod tests {
         let subject = BannedDaoReal::new(conn);
 
         let ban_me_baby = make_wallet("BAN_ME_BABY");
-        subject.ban(&ban_me_baby.clone());
+        subject.ban(&ban_me_baby, make_ban_details(BanReason::Delinquency));
 
         assert!(BAN_CACHE.is_banned(&ban_me_baby))
     }
@@ -335,6 +489,181 @@ mod tests {
         assert!(!BAN_CACHE.is_banned(&unban_me_baby));
     }
 
+    #[test]
+    fn ban_list_returns_the_details_of_the_bans() {
+        let subject = make_subject("ban_list_returns_the_details_of_the_bans");
+        let wallet_1 = make_wallet("delinquent");
+        let wallet_2 = make_wallet("manually_banned");
+        let wallet_3 = make_wallet("legacy_ban");
+        let details_1 = make_ban_details(BanReason::Delinquency);
+        let details_2 = BanDetails {
+            timestamp: from_time_t(1_700_000_000) + Duration::from_secs(3600),
+            reason: BanReason::Manual,
+            balance_wei_opt: None,
+            note_opt: Some("Spammer".to_string()),
+        };
+        subject.ban(&wallet_1, details_1.clone());
+        subject.ban(&wallet_2, details_2.clone());
+        let params: &[&dyn ToSql] = &[&wallet_3];
+        subject
+            .conn
+            .prepare("insert into banned (wallet_address) values (?)")
+            .unwrap()
+            .execute(params)
+            .unwrap();
+
+        let result = subject.ban_list();
+
+        assert_eq!(
+            result,
+            vec![
+                BannedAccount {
+                    wallet: wallet_1,
+                    banned_at_opt: Some(details_1.timestamp),
+                    reason_opt: Some(BanReason::Delinquency),
+                    balance_wei_opt: details_1.balance_wei_opt,
+                    note_opt: None,
+                },
+                BannedAccount {
+                    wallet: wallet_3,
+                    banned_at_opt: None,
+                    reason_opt: None,
+                    balance_wei_opt: None,
+                    note_opt: None,
+                },
+                BannedAccount {
+                    wallet: wallet_2,
+                    banned_at_opt: Some(details_2.timestamp),
+                    reason_opt: Some(BanReason::Manual),
+                    balance_wei_opt: None,
+                    note_opt: Some("Spammer".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn pardon_hides_the_ban_and_removes_it_from_the_cache() {
+        let subject = make_subject("pardon_hides_the_ban_and_removes_it_from_the_cache");
+        let wallet = make_wallet("pardon_me");
+        subject.ban(&wallet, make_ban_details(BanReason::Delinquency));
+
+        let result = subject.pardon(&wallet);
+
+        assert_eq!(result, true);
+        assert!(!BAN_CACHE.is_banned(&wallet));
+        assert_eq!(subject.ban_list(), vec![]);
+        let params: &[&dyn ToSql] = &[&wallet];
+        let pardoned: i64 = subject
+            .conn
+            .prepare("select pardoned from banned where wallet_address = ?")
+            .unwrap()
+            .query_row(params, |row| row.get(0))
+            .unwrap();
+        assert_eq!(pardoned, 1);
+    }
+
+    #[test]
+    fn pardon_returns_false_for_a_wallet_that_is_not_banned() {
+        let subject = make_subject("pardon_returns_false_for_a_wallet_that_is_not_banned");
+        let wallet = make_wallet("never_banned");
+
+        let result = subject.pardon(&wallet);
+
+        assert_eq!(result, false);
+    }
+
+    #[test]
+    fn pardoned_wallet_can_be_banned_again() {
+        let subject = make_subject("pardoned_wallet_can_be_banned_again");
+        let wallet = make_wallet("repeat_offender");
+        subject.ban(&wallet, make_ban_details(BanReason::Delinquency));
+        subject.pardon(&wallet);
+        let details = BanDetails {
+            timestamp: from_time_t(1_800_000_000),
+            reason: BanReason::Manual,
+            balance_wei_opt: Some(-5),
+            note_opt: Some("Once more".to_string()),
+        };
+
+        subject.ban(&wallet, details);
+
+        assert!(BAN_CACHE.is_banned(&wallet));
+        assert_eq!(
+            subject.ban_list(),
+            vec![BannedAccount {
+                wallet,
+                banned_at_opt: Some(from_time_t(1_800_000_000)),
+                reason_opt: Some(BanReason::Manual),
+                balance_wei_opt: Some(-5),
+                note_opt: Some("Once more".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn unban_deletes_a_pardoned_ban() {
+        let subject = make_subject("unban_deletes_a_pardoned_ban");
+        let wallet = make_wallet("paid_up_at_last");
+        subject.ban(&wallet, make_ban_details(BanReason::Delinquency));
+        subject.pardon(&wallet);
+
+        subject.unban(&wallet);
+
+        let count: i64 = subject
+            .conn
+            .prepare("select count(*) from banned")
+            .unwrap()
+            .query_row([], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn annotate_writes_the_note_of_an_active_ban_only() {
+        let subject = make_subject("annotate_writes_the_note_of_an_active_ban_only");
+        let banned_wallet = make_wallet("annotated");
+        let pardoned_wallet = make_wallet("pardoned");
+        subject.ban(&banned_wallet, make_ban_details(BanReason::Manual));
+        subject.ban(&pardoned_wallet, make_ban_details(BanReason::Manual));
+        subject.pardon(&pardoned_wallet);
+
+        let banned_result = subject.annotate(&banned_wallet, "Friendly node, check later");
+        let pardoned_result = subject.annotate(&pardoned_wallet, "Nobody will see this");
+        let unknown_result = subject.annotate(&make_wallet("unknown"), "Nor this");
+
+        assert_eq!(banned_result, true);
+        assert_eq!(pardoned_result, false);
+        assert_eq!(unknown_result, false);
+        let ban_list = subject.ban_list();
+        assert_eq!(ban_list.len(), 1);
+        assert_eq!(
+            ban_list[0].note_opt,
+            Some("Friendly node, check later".to_string())
+        );
+    }
+
+    #[test]
+    fn cache_loader_skips_pardoned_wallets() {
+        let home_dir = ensure_node_home_directory_does_not_exist(
+            "banned_dao",
+            "cache_loader_skips_pardoned_wallets",
+        );
+        let conn = DbInitializerReal::default()
+            .initialize(&home_dir, DbInitializationConfig::test_default())
+            .unwrap();
+        let wallet = make_wallet("LOADER_PARDONED");
+        let params: &[&dyn ToSql] = &[&wallet];
+        conn.prepare("insert into banned (wallet_address, pardoned) values (?, 1)")
+            .unwrap()
+            .execute(params)
+            .unwrap();
+
+        BannedCacheLoaderReal {}.load(conn);
+
+        assert!(!BAN_CACHE.is_banned(&wallet));
+    }
+
     #[test]
     fn insert_adds_a_wallet_to_the_cache() {
         let now_banned_wallet = make_paying_wallet(b"NOW_BANNED_WALLET");
@@ -0,0 +1,539 @@
+// Copyright (c) 2024, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::accountant::checked_conversion;
+use crate::accountant::db_access_objects::utils::{
+    from_time_t, to_time_t, DaoFactoryReal, VigilantRusqliteFlatten,
+};
+use crate::accountant::db_big_integer::big_int_divider::BigIntDivider;
+use crate::blockchain::blockchain_bridge::PendingPayableFingerprint;
+use crate::database::rusqlite_wrappers::{ConnectionWrapper, TransactionSafeWrapper};
+use crate::sub_lib::wallet::Wallet;
+use masq_lib::utils::ExpectValue;
+use rusqlite::{OptionalExtension, Row, Statement, ToSql};
+use std::fmt::Debug;
+use std::str::FromStr;
+use std::time::SystemTime;
+use web3::types::H256;
+
+// Service charges are summed up per counterparty and kind over periods this long, so the ledger
+// grows with the number of counterparties instead of with the number of CORES packages
+pub const CHARGE_PERIOD_SECS: i64 = 3600;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum LedgerDaoError {
+    InsertionFailed(String),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LedgerEntryKind {
+    RoutingServiceProvided,
+    ExitServiceProvided,
+    RoutingServiceConsumed,
+    ExitServiceConsumed,
+    PaymentSent,
+    PaymentReceived,
+}
+
+impl LedgerEntryKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::RoutingServiceProvided => "routing_provided",
+            Self::ExitServiceProvided => "exit_provided",
+            Self::RoutingServiceConsumed => "routing_consumed",
+            Self::ExitServiceConsumed => "exit_consumed",
+            Self::PaymentSent => "payment_sent",
+            Self::PaymentReceived => "payment_received",
+        }
+    }
+}
+
+impl TryFrom<&str> for LedgerEntryKind {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(match value {
+            "routing_provided" => Self::RoutingServiceProvided,
+            "exit_provided" => Self::ExitServiceProvided,
+            "routing_consumed" => Self::RoutingServiceConsumed,
+            "exit_consumed" => Self::ExitServiceConsumed,
+            "payment_sent" => Self::PaymentSent,
+            "payment_received" => Self::PaymentReceived,
+            x => return Err(format!("Unrecognized ledger entry kind: '{}'", x)),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LedgerEntry {
+    pub timestamp: SystemTime,
+    pub kind: LedgerEntryKind,
+    pub wallet: Wallet,
+    pub amount_wei: u128,
+    pub transaction_hash_opt: Option<H256>,
+}
+
+pub trait LedgerDao: Debug + Send {
+    fn record(&self, entries: &[LedgerEntry]) -> Result<(), LedgerDaoError>;
+
+    // Adds each charge to the entry of its counterparty and kind for the period it falls into;
+    // that entry is timestamped with the beginning of the period
+    fn accumulate_charges(&self, charges: &[LedgerEntry]) -> Result<(), LedgerDaoError>;
+
+    // Lets the received payments land in the ledger in the same transaction that credits them
+    fn record_by_guest_transaction(
+        &self,
+        txn: &mut TransactionSafeWrapper,
+        entries: &[LedgerEntry],
+    ) -> Result<(), LedgerDaoError>;
+
+    // Must be called before the fingerprints are detached from their payable records
+    fn record_confirmed_payments(
+        &self,
+        confirmed_payables: &[PendingPayableFingerprint],
+    ) -> Result<(), LedgerDaoError>;
+
+    // The range is half-open: entries at 'from' are included, entries at 'to' are not
+    fn entries(&self, from_opt: Option<SystemTime>, to_opt: Option<SystemTime>)
+        -> Vec<LedgerEntry>;
+}
+
+pub trait LedgerDaoFactory {
+    fn make(&self) -> Box<dyn LedgerDao>;
+}
+
+impl LedgerDaoFactory for DaoFactoryReal {
+    fn make(&self) -> Box<dyn LedgerDao> {
+        Box::new(LedgerDaoReal::new(self.make_connection()))
+    }
+}
+
+#[derive(Debug)]
+pub struct LedgerDaoReal {
+    conn: Box<dyn ConnectionWrapper>,
+}
+
+impl LedgerDao for LedgerDaoReal {
+    fn record(&self, entries: &[LedgerEntry]) -> Result<(), LedgerDaoError> {
+        let prepare_stm = |stm: &str| self.conn.prepare(stm);
+        Self::insert_entries(prepare_stm, entries)
+    }
+
+    fn accumulate_charges(&self, charges: &[LedgerEntry]) -> Result<(), LedgerDaoError> {
+        charges.iter().try_for_each(|charge| {
+            let time_t = to_time_t(charge.timestamp);
+            let period_start = time_t - time_t.rem_euclid(CHARGE_PERIOD_SECS);
+            match self.period_total_opt(period_start, charge)? {
+                Some((rowid, total)) => self.update_period_total(rowid, total + charge.amount_wei),
+                None => self.record(&[LedgerEntry {
+                    timestamp: from_time_t(period_start),
+                    ..charge.clone()
+                }]),
+            }
+        })
+    }
+
+    fn record_by_guest_transaction(
+        &self,
+        txn: &mut TransactionSafeWrapper,
+        entries: &[LedgerEntry],
+    ) -> Result<(), LedgerDaoError> {
+        let prepare_stm = |stm: &str| txn.prepare(stm);
+        Self::insert_entries(prepare_stm, entries)
+    }
+
+    fn record_confirmed_payments(
+        &self,
+        confirmed_payables: &[PendingPayableFingerprint],
+    ) -> Result<(), LedgerDaoError> {
+        let mut stm = self
+            .conn
+            .prepare(
+                "insert into ledger (timestamp, kind, wallet_address, amount_high_b, amount_low_b, transaction_hash) \
+                select :timestamp, :kind, wallet_address, :amount_high_b, :amount_low_b, :transaction_hash \
+                from payable where pending_payable_rowid = :rowid",
+            )
+            .map_err(|e| LedgerDaoError::InsertionFailed(e.to_string()))?;
+        confirmed_payables.iter().try_for_each(|fingerprint| {
+            let (amount_high_b, amount_low_b) =
+                BigIntDivider::deconstruct(checked_conversion::<u128, i128>(fingerprint.amount));
+            let params: &[(&str, &dyn ToSql)] = &[
+                (":timestamp", &to_time_t(fingerprint.timestamp)),
+                (":kind", &LedgerEntryKind::PaymentSent.as_str()),
+                (":amount_high_b", &amount_high_b),
+                (":amount_low_b", &amount_low_b),
+                (":transaction_hash", &format!("{:?}", fingerprint.hash)),
+                (":rowid", &checked_conversion::<u64, i64>(fingerprint.rowid)),
+            ];
+            match stm.execute(params) {
+                Ok(1) => Ok(()),
+                Ok(x) => Err(LedgerDaoError::InsertionFailed(format!(
+                    "{} payable records belong to the confirmed transaction {:?}; expected 1",
+                    x, fingerprint.hash
+                ))),
+                Err(e) => Err(LedgerDaoError::InsertionFailed(e.to_string())),
+            }
+        })
+    }
+
+    fn entries(
+        &self,
+        from_opt: Option<SystemTime>,
+        to_opt: Option<SystemTime>,
+    ) -> Vec<LedgerEntry> {
+        let from = from_opt.map(to_time_t).unwrap_or(i64::MIN);
+        let to = to_opt.map(to_time_t).unwrap_or(i64::MAX);
+        let mut stm = self
+            .conn
+            .prepare(
+                "select timestamp, kind, wallet_address, amount_high_b, amount_low_b, transaction_hash \
+                from ledger where timestamp >= ? and timestamp < ? order by timestamp, rowid",
+            )
+            .expect("Failed to prepare a statement");
+        stm.query_map([from, to], Self::entry_from_row)
+            .expect("Couldn't retrieve the ledger: database corrupt")
+            .vigilant_flatten()
+            .collect()
+    }
+}
+
+impl LedgerDaoReal {
+    pub fn new(conn: Box<dyn ConnectionWrapper>) -> Self {
+        Self { conn }
+    }
+
+    fn period_total_opt(
+        &self,
+        period_start: i64,
+        charge: &LedgerEntry,
+    ) -> Result<Option<(i64, u128)>, LedgerDaoError> {
+        let params: &[&dyn ToSql] = &[&period_start, &charge.kind.as_str(), &charge.wallet];
+        self.conn
+            .prepare(
+                "select rowid, amount_high_b, amount_low_b from ledger where timestamp = ? \
+                and kind = ? and wallet_address = ? and transaction_hash is null",
+            )
+            .and_then(|mut stm| {
+                stm.query_row(params, |row| {
+                    let rowid: i64 = row.get(0)?;
+                    let amount_high_b: i64 = row.get(1)?;
+                    let amount_low_b: i64 = row.get(2)?;
+                    Ok((
+                        rowid,
+                        checked_conversion::<i128, u128>(BigIntDivider::reconstitute(
+                            amount_high_b,
+                            amount_low_b,
+                        )),
+                    ))
+                })
+                .optional()
+            })
+            .map_err(|e| LedgerDaoError::InsertionFailed(e.to_string()))
+    }
+
+    fn update_period_total(&self, rowid: i64, total: u128) -> Result<(), LedgerDaoError> {
+        let (amount_high_b, amount_low_b) =
+            BigIntDivider::deconstruct(checked_conversion::<u128, i128>(total));
+        let params: &[&dyn ToSql] = &[&amount_high_b, &amount_low_b, &rowid];
+        self.conn
+            .prepare("update ledger set amount_high_b = ?, amount_low_b = ? where rowid = ?")
+            .and_then(|mut stm| stm.execute(params))
+            .map(|_| ())
+            .map_err(|e| LedgerDaoError::InsertionFailed(e.to_string()))
+    }
+
+    fn insert_entries<'a, P>(prepare_stm: P, entries: &[LedgerEntry]) -> Result<(), LedgerDaoError>
+    where
+        P: FnOnce(&str) -> Result<Statement<'a>, rusqlite::Error> + 'a,
+    {
+        let mut stm = prepare_stm(
+            "insert into ledger (timestamp, kind, wallet_address, amount_high_b, amount_low_b, transaction_hash) \
+            values (?, ?, ?, ?, ?, ?)",
+        )
+        .map_err(|e| LedgerDaoError::InsertionFailed(e.to_string()))?;
+        entries.iter().try_for_each(|entry| {
+            let (amount_high_b, amount_low_b) =
+                BigIntDivider::deconstruct(checked_conversion::<u128, i128>(entry.amount_wei));
+            let transaction_hash_opt = entry.transaction_hash_opt.map(|hash| format!("{:?}", hash));
+            let params: &[&dyn ToSql] = &[
+                &to_time_t(entry.timestamp),
+                &entry.kind.as_str(),
+                &entry.wallet,
+                &amount_high_b,
+                &amount_low_b,
+                &transaction_hash_opt,
+            ];
+            stm.execute(params)
+                .map(|_| ())
+                .map_err(|e| LedgerDaoError::InsertionFailed(e.to_string()))
+        })
+    }
+
+    fn entry_from_row(row: &Row) -> rusqlite::Result<LedgerEntry> {
+        let timestamp: i64 = row.get(0).expectv("timestamp");
+        let kind: String = row.get(1).expectv("kind");
+        let wallet: Wallet = row.get(2).expectv("wallet_address");
+        let amount_high_b: i64 = row.get(3).expectv("amount_high_b");
+        let amount_low_b: i64 = row.get(4).expectv("amount_low_b");
+        let transaction_hash_opt: Option<String> = row.get(5).expectv("transaction_hash");
+        Ok(LedgerEntry {
+            timestamp: from_time_t(timestamp),
+            kind: LedgerEntryKind::try_from(kind.as_str())
+                .unwrap_or_else(|e| panic!("Ledger is corrupt: {}", e)),
+            wallet,
+            amount_wei: checked_conversion::<i128, u128>(BigIntDivider::reconstitute(
+                amount_high_b,
+                amount_low_b,
+            )),
+            transaction_hash_opt: transaction_hash_opt.map(|hash_str| {
+                H256::from_str(&hash_str[2..]).unwrap_or_else(|e| {
+                    panic!(
+                        "Ledger is corrupt: transaction hash {} is malformed: {:?}",
+                        hash_str, e
+                    )
+                })
+            }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accountant::db_access_objects::payable_dao::{PayableDao, PayableDaoReal};
+    use crate::blockchain::test_utils::make_tx_hash;
+    use crate::database::db_initializer::{
+        DbInitializationConfig, DbInitializer, DbInitializerReal,
+    };
+    use crate::test_utils::make_wallet;
+    use masq_lib::test_utils::utils::ensure_node_home_directory_exists;
+    use std::time::Duration;
+
+    fn make_subject(test_name: &str) -> LedgerDaoReal {
+        let home_dir = ensure_node_home_directory_exists("ledger_dao", test_name);
+        let conn = DbInitializerReal::default()
+            .initialize(&home_dir, DbInitializationConfig::test_default())
+            .unwrap();
+        LedgerDaoReal::new(conn)
+    }
+
+    fn make_entry(n: u64, kind: LedgerEntryKind) -> LedgerEntry {
+        LedgerEntry {
+            timestamp: from_time_t(1_700_000_000 + n as i64),
+            kind,
+            wallet: make_wallet(&format!("wallet{}", n)),
+            amount_wei: n as u128 * 1_000_000_000_000,
+            transaction_hash_opt: None,
+        }
+    }
+
+    #[test]
+    fn ledger_entry_kinds_survive_the_round_trip() {
+        vec![
+            LedgerEntryKind::RoutingServiceProvided,
+            LedgerEntryKind::ExitServiceProvided,
+            LedgerEntryKind::RoutingServiceConsumed,
+            LedgerEntryKind::ExitServiceConsumed,
+            LedgerEntryKind::PaymentSent,
+            LedgerEntryKind::PaymentReceived,
+        ]
+        .into_iter()
+        .for_each(|kind| assert_eq!(LedgerEntryKind::try_from(kind.as_str()), Ok(kind)));
+        assert_eq!(
+            LedgerEntryKind::try_from("booga"),
+            Err("Unrecognized ledger entry kind: 'booga'".to_string())
+        );
+    }
+
+    #[test]
+    fn record_and_entries_work() {
+        let subject = make_subject("record_and_entries_work");
+        let service_entry = make_entry(1, LedgerEntryKind::ExitServiceProvided);
+        let mut payment_entry = make_entry(2, LedgerEntryKind::PaymentReceived);
+        payment_entry.amount_wei = u64::MAX as u128 * 3;
+        payment_entry.transaction_hash_opt = Some(make_tx_hash(0x1234));
+
+        let result = subject.record(&[payment_entry.clone(), service_entry.clone()]);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            subject.entries(None, None),
+            vec![service_entry, payment_entry]
+        );
+    }
+
+    #[test]
+    fn entries_respects_the_time_range() {
+        let subject = make_subject("entries_respects_the_time_range");
+        let entries = (1..=4)
+            .map(|n| make_entry(n, LedgerEntryKind::RoutingServiceConsumed))
+            .collect::<Vec<_>>();
+        subject.record(&entries).unwrap();
+
+        let result = subject.entries(
+            Some(from_time_t(1_700_000_002)),
+            Some(from_time_t(1_700_000_004)),
+        );
+
+        assert_eq!(result, entries[1..3].to_vec());
+        assert_eq!(
+            subject.entries(Some(from_time_t(1_700_000_004)), None),
+            entries[3..].to_vec()
+        );
+        assert_eq!(
+            subject.entries(None, Some(from_time_t(1_700_000_002))),
+            entries[..1].to_vec()
+        );
+    }
+
+    #[test]
+    fn accumulate_charges_adds_up_charges_per_counterparty_kind_and_period() {
+        let subject =
+            make_subject("accumulate_charges_adds_up_charges_per_counterparty_kind_and_period");
+        let period_start = 472_222 * CHARGE_PERIOD_SECS;
+        let wallet_a = make_wallet("wallet_a");
+        let wallet_b = make_wallet("wallet_b");
+        let charge =
+            |secs: i64, kind: LedgerEntryKind, wallet: &Wallet, amount_wei: u128| LedgerEntry {
+                timestamp: from_time_t(period_start + secs),
+                kind,
+                wallet: wallet.clone(),
+                amount_wei,
+                transaction_hash_opt: None,
+            };
+        let mut payment = charge(5, LedgerEntryKind::PaymentReceived, &wallet_a, 1_000_000);
+        payment.transaction_hash_opt = Some(make_tx_hash(0x1234));
+        subject.record(&[payment.clone()]).unwrap();
+        let routing = LedgerEntryKind::RoutingServiceProvided;
+        let exit = LedgerEntryKind::ExitServiceProvided;
+
+        let result = subject.accumulate_charges(&[
+            charge(10, routing, &wallet_a, u64::MAX as u128 * 2),
+            charge(20, exit, &wallet_a, 5),
+            charge(30, routing, &wallet_b, 7),
+            charge(3599, routing, &wallet_a, 200),
+            charge(3600, routing, &wallet_a, 1_000),
+        ]);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            subject.entries(None, None),
+            vec![
+                charge(0, routing, &wallet_a, u64::MAX as u128 * 2 + 200),
+                charge(0, exit, &wallet_a, 5),
+                charge(0, routing, &wallet_b, 7),
+                payment,
+                charge(3600, routing, &wallet_a, 1_000),
+            ]
+        );
+    }
+
+    #[test]
+    fn record_by_guest_transaction_is_undone_if_the_transaction_is_not_committed() {
+        let home_dir = ensure_node_home_directory_exists(
+            "ledger_dao",
+            "record_by_guest_transaction_is_undone_if_the_transaction_is_not_committed",
+        );
+        let db_initializer = DbInitializerReal::default();
+        let mut host_conn = db_initializer
+            .initialize(&home_dir, DbInitializationConfig::test_default())
+            .unwrap();
+        let subject = LedgerDaoReal::new(
+            db_initializer
+                .initialize(&home_dir, DbInitializationConfig::test_default())
+                .unwrap(),
+        );
+        let committed_entry = make_entry(1, LedgerEntryKind::PaymentReceived);
+        let abandoned_entry = make_entry(2, LedgerEntryKind::PaymentReceived);
+        {
+            let mut txn = host_conn.transaction().unwrap();
+            subject
+                .record_by_guest_transaction(&mut txn, &[committed_entry.clone()])
+                .unwrap();
+            txn.commit().unwrap();
+        }
+
+        {
+            let mut txn = host_conn.transaction().unwrap();
+            subject
+                .record_by_guest_transaction(&mut txn, &[abandoned_entry])
+                .unwrap();
+        }
+
+        assert_eq!(subject.entries(None, None), vec![committed_entry]);
+    }
+
+    #[test]
+    fn record_confirmed_payments_finds_the_wallets_of_pending_payables() {
+        let home_dir = ensure_node_home_directory_exists(
+            "ledger_dao",
+            "record_confirmed_payments_finds_the_wallets_of_pending_payables",
+        );
+        let db_initializer = DbInitializerReal::default();
+        let payable_dao = PayableDaoReal::new(
+            db_initializer
+                .initialize(&home_dir, DbInitializationConfig::test_default())
+                .unwrap(),
+        );
+        let subject = LedgerDaoReal::new(
+            db_initializer
+                .initialize(&home_dir, DbInitializationConfig::test_default())
+                .unwrap(),
+        );
+        let wallet = make_wallet("creditor");
+        let timestamp = from_time_t(1_700_000_000);
+        payable_dao
+            .more_money_payable(timestamp, &wallet, 123_456_789)
+            .unwrap();
+        payable_dao
+            .mark_pending_payables_rowids(&[(&wallet, 7)])
+            .unwrap();
+        let fingerprint = PendingPayableFingerprint {
+            rowid: 7,
+            timestamp: timestamp + Duration::from_secs(60),
+            hash: make_tx_hash(0xabc),
+            attempt: 1,
+            amount: 123_000_000,
+            process_error: None,
+        };
+
+        let result = subject.record_confirmed_payments(&[fingerprint.clone()]);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            subject.entries(None, None),
+            vec![LedgerEntry {
+                timestamp: fingerprint.timestamp,
+                kind: LedgerEntryKind::PaymentSent,
+                wallet,
+                amount_wei: 123_000_000,
+                transaction_hash_opt: Some(make_tx_hash(0xabc)),
+            }]
+        );
+    }
+
+    #[test]
+    fn record_confirmed_payments_complains_about_an_unknown_fingerprint() {
+        let subject =
+            make_subject("record_confirmed_payments_complains_about_an_unknown_fingerprint");
+        let fingerprint = PendingPayableFingerprint {
+            rowid: 7,
+            timestamp: from_time_t(1_700_000_000),
+            hash: make_tx_hash(0xabc),
+            attempt: 1,
+            amount: 123_000_000,
+            process_error: None,
+        };
+
+        let result = subject.record_confirmed_payments(&[fingerprint]);
+
+        assert_eq!(
+            result,
+            Err(LedgerDaoError::InsertionFailed(
+                "0 payable records belong to the confirmed transaction \
+                0x0000000000000000000000000000000000000000000000000000000000000abc; expected 1"
+                    .to_string()
+            ))
+        );
+        assert_eq!(subject.entries(None, None), vec![]);
+    }
+}
@@ -1,6 +1,7 @@
 // Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 
 pub mod banned_dao;
+pub mod ledger_dao;
 pub mod payable_dao;
 pub mod pending_payable_dao;
 pub mod receivable_dao;
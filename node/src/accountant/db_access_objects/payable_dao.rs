@@ -13,16 +13,21 @@ use crate::accountant::db_access_objects::utils::{
 use crate::accountant::db_access_objects::payable_dao::mark_pending_payable_associated_functions::{
     compose_case_expression, execute_command, serialize_wallets,
 };
-use crate::accountant::{checked_conversion, sign_conversion, PendingPayableId};
+use crate::accountant::{
+    checked_conversion, comma_joined_stringifiable, gwei_to_wei, sign_conversion,
+    PendingPayableId,
+};
 use crate::blockchain::blockchain_bridge::PendingPayableFingerprint;
 use crate::database::rusqlite_wrappers::ConnectionWrapper;
+use crate::sub_lib::accountant::PayableWriteOffPolicy;
+use crate::sub_lib::earning_wallet_shares::EarningWalletShares;
 use crate::sub_lib::wallet::Wallet;
 #[cfg(test)]
 use ethereum_types::{BigEndianHash, U256};
 use masq_lib::utils::ExpectValue;
 #[cfg(test)]
 use rusqlite::OptionalExtension;
-use rusqlite::{Error, Row};
+use rusqlite::{Error, Row, ToSql};
 use std::fmt::Debug;
 use std::str::FromStr;
 use std::time::SystemTime;
@@ -41,6 +46,7 @@ pub struct PayableAccount {
     pub balance_wei: u128,
     pub last_paid_timestamp: SystemTime,
     pub pending_payable_opt: Option<PendingPayableId>,
+    pub written_off: bool,
 }
 
 pub trait PayableDao: Debug + Send {
@@ -63,6 +69,29 @@ pub trait PayableDao: Debug + Send {
 
     fn non_pending_payables(&self) -> Vec<PayableAccount>;
 
+    fn write_off_stale_payables(
+        &self,
+        policy: &PayableWriteOffPolicy,
+        now: SystemTime,
+    ) -> Result<Vec<Wallet>, PayableDaoError>;
+
+    fn mark_seen_in_gossip(
+        &self,
+        wallets: &[Wallet],
+        timestamp: SystemTime,
+    ) -> Result<(), PayableDaoError>;
+
+    fn record_earning_wallet_shares(
+        &self,
+        earning_wallet_shares: &[(Wallet, EarningWalletShares)],
+    ) -> Result<(), PayableDaoError>;
+
+    fn split_among_earning_wallet_shares(
+        &self,
+        wallets: &[Wallet],
+        now: SystemTime,
+    ) -> Result<Vec<PayableAccount>, PayableDaoError>;
+
     fn custom_query(&self, custom_query: CustomQuery<u64>) -> Option<Vec<PayableAccount>>;
 
     fn total(&self) -> u128;
@@ -94,11 +123,15 @@ impl PayableDao for PayableDaoReal {
         wallet: &Wallet,
         amount: u128,
     ) -> Result<(), PayableDaoError> {
-        let main_sql = "insert into payable (wallet_address, balance_high_b, balance_low_b, last_paid_timestamp, pending_payable_rowid) \
-                values (:wallet, :balance_high_b, :balance_low_b, :last_paid_timestamp, null) on conflict (wallet_address) do update set \
-                balance_high_b = balance_high_b + :balance_high_b, balance_low_b = balance_low_b + :balance_low_b where wallet_address = :wallet";
+        // A new debt revives a written-off one, since the creditor is evidently still around
+        let main_sql = "insert into payable (wallet_address, balance_high_b, balance_low_b, last_paid_timestamp, pending_payable_rowid, \
+                last_seen_timestamp, written_off_timestamp) values (:wallet, :balance_high_b, :balance_low_b, :last_paid_timestamp, null, \
+                :last_paid_timestamp, null) on conflict (wallet_address) do update set balance_high_b = balance_high_b + :balance_high_b, \
+                balance_low_b = balance_low_b + :balance_low_b, last_seen_timestamp = :last_paid_timestamp, written_off_timestamp = null \
+                where wallet_address = :wallet";
         let update_clause_with_compensated_overflow = "update payable set \
-                balance_high_b = :balance_high_b, balance_low_b = :balance_low_b where wallet_address = :wallet";
+                balance_high_b = :balance_high_b, balance_low_b = :balance_low_b, last_seen_timestamp = :last_paid_timestamp, \
+                written_off_timestamp = null where wallet_address = :wallet";
 
         let last_paid_timestamp = to_time_t(timestamp);
         let params = SQLParamsBuilder::default()
@@ -108,7 +141,7 @@ impl PayableDao for PayableDaoReal {
                 amount,
                 WeiChangeDirection::Addition,
             ))
-            .other_params(vec![ParamByUse::BeforeOverflowOnly(
+            .other_params(vec![ParamByUse::BeforeAndAfterOverflow(
                 DisplayableRusqliteParamPair::new(":last_paid_timestamp", &last_paid_timestamp),
             )])
             .build();
@@ -177,7 +210,7 @@ impl PayableDao for PayableDaoReal {
     fn non_pending_payables(&self) -> Vec<PayableAccount> {
         let sql = "\
         select wallet_address, balance_high_b, balance_low_b, last_paid_timestamp from \
-        payable where pending_payable_rowid is null";
+        payable where pending_payable_rowid is null and written_off_timestamp is null";
         let mut stmt = self.conn.prepare(sql).expect("Internal error");
         stmt.query_map([], |row| {
             let wallet_result: Result<Wallet, Error> = row.get(0);
@@ -198,6 +231,7 @@ impl PayableDao for PayableDaoReal {
                         )),
                         last_paid_timestamp: utils::from_time_t(last_paid_timestamp),
                         pending_payable_opt: None,
+                        written_off: false,
                     })
                 }
                 _ => panic!("Database is corrupt: PAYABLE table columns and/or types"),
@@ -208,6 +242,150 @@ impl PayableDao for PayableDaoReal {
         .collect()
     }
 
+    fn write_off_stale_payables(
+        &self,
+        policy: &PayableWriteOffPolicy,
+        now: SystemTime,
+    ) -> Result<Vec<Wallet>, PayableDaoError> {
+        let now = to_time_t(now);
+        let days_ago = |days: u64| now - checked_conversion::<u64, i64>(days * 86_400);
+        let mut conditions = vec![];
+        if policy.unseen_rule_enabled() {
+            conditions.push(format!(
+                "(max(last_paid_timestamp, coalesce(last_seen_timestamp, 0)) <= {})",
+                days_ago(policy.unseen_days)
+            ))
+        }
+        if policy.dust_rule_enabled() {
+            let (high_b, low_b) =
+                BigIntDivider::deconstruct(gwei_to_wei(policy.dust_threshold_gwei));
+            conditions.push(format!(
+                "(((balance_high_b < {high_b}) or ((balance_high_b = {high_b}) and \
+                (balance_low_b < {low_b}))) and (last_paid_timestamp <= {}))",
+                days_ago(policy.dust_days)
+            ))
+        }
+        if conditions.is_empty() {
+            return Ok(vec![]);
+        }
+        let sql = format!(
+            "update payable set written_off_timestamp = {} where written_off_timestamp is null \
+            and pending_payable_rowid is null and ({}) returning wallet_address",
+            now,
+            conditions.join(" or ")
+        );
+        let mut stmt = self.conn.prepare(&sql).expect("Internal error");
+        let rows = stmt
+            .query_map([], |row| row.get::<usize, Wallet>(0))
+            .map_err(|e| PayableDaoError::RusqliteError(e.to_string()))?;
+        rows.map(|wallet_result| {
+            wallet_result.map_err(|e| PayableDaoError::RusqliteError(e.to_string()))
+        })
+        .collect()
+    }
+
+    fn mark_seen_in_gossip(
+        &self,
+        wallets: &[Wallet],
+        timestamp: SystemTime,
+    ) -> Result<(), PayableDaoError> {
+        if wallets.is_empty() {
+            return Ok(());
+        }
+        //the Wallet type is secure against SQL injections
+        let sql = format!(
+            "update payable set last_seen_timestamp = ? where wallet_address in ({})",
+            comma_joined_stringifiable(wallets, |wallet| format!("'{}'", wallet))
+        );
+        match self
+            .conn
+            .prepare(&sql)
+            .expect("Internal error")
+            .execute([to_time_t(timestamp)])
+        {
+            Ok(_) => Ok(()),
+            Err(e) => Err(PayableDaoError::RusqliteError(e.to_string())),
+        }
+    }
+
+    fn record_earning_wallet_shares(
+        &self,
+        earning_wallet_shares: &[(Wallet, EarningWalletShares)],
+    ) -> Result<(), PayableDaoError> {
+        let mut stmt = self
+            .conn
+            .prepare("update payable set earning_wallet_shares = ? where wallet_address = ?")
+            .expect("Internal error");
+        earning_wallet_shares
+            .iter()
+            .try_for_each(|(wallet, shares)| {
+                let shares_opt = (!shares.is_empty()).then(|| shares.to_string());
+                let params: &[&dyn ToSql] = &[&shares_opt, wallet];
+                stmt.execute(params).map(|_| ())
+            })
+            .map_err(|e| PayableDaoError::RusqliteError(e.to_string()))
+    }
+
+    // Moves each share wallet's portion of the debts owed to the given wallets into the share
+    // wallet's own row, so that the debt to a Node stays in one row until it's about to be paid.
+    fn split_among_earning_wallet_shares(
+        &self,
+        wallets: &[Wallet],
+        now: SystemTime,
+    ) -> Result<Vec<PayableAccount>, PayableDaoError> {
+        if wallets.is_empty() {
+            return Ok(vec![]);
+        }
+        //the Wallet type is secure against SQL injections
+        let sql = format!(
+            "select wallet_address, balance_high_b, balance_low_b, earning_wallet_shares from \
+            payable where earning_wallet_shares is not null and pending_payable_rowid is null \
+            and written_off_timestamp is null and wallet_address in ({})",
+            comma_joined_stringifiable(wallets, |wallet| format!("'{}'", wallet))
+        );
+        let shared_debts = {
+            let mut stmt = self.conn.prepare(&sql).expect("Internal error");
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok((
+                        row.get::<usize, Wallet>(0)?,
+                        BigIntDivider::reconstitute(row.get(1)?, row.get(2)?),
+                        row.get::<usize, String>(3)?,
+                    ))
+                })
+                .map_err(|e| PayableDaoError::RusqliteError(e.to_string()))?;
+            rows.collect::<Result<Vec<_>, Error>>()
+                .map_err(|e| PayableDaoError::RusqliteError(e.to_string()))?
+        };
+        let mut split_wallets = vec![];
+        for (wallet, balance, shares) in shared_debts {
+            // The shares came in Gossip, so they get validated before any money moves
+            let shares = match EarningWalletShares::from_str(&shares) {
+                Ok(shares) => shares,
+                Err(_) => continue,
+            };
+            let portions = shares.split(checked_conversion::<i128, u128>(balance));
+            if portions.is_empty() {
+                continue;
+            }
+            let total = portions.iter().map(|(_, portion)| portion).sum();
+            self.less_money_payable(&wallet, total)?;
+            for (share_wallet, portion) in portions {
+                self.more_money_payable(now, &share_wallet, portion)?;
+                split_wallets.push(share_wallet);
+            }
+            split_wallets.push(wallet);
+        }
+        if split_wallets.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(self
+            .non_pending_payables()
+            .into_iter()
+            .filter(|account| split_wallets.contains(&account.wallet))
+            .collect())
+    }
+
     fn custom_query(&self, custom_query: CustomQuery<u64>) -> Option<Vec<PayableAccount>> {
         let variant_top = TopStmConfig{
             limit_clause: "limit :limit_count",
@@ -248,6 +426,7 @@ impl PayableDao for PayableDaoReal {
             self.conn.as_ref(),
             &Self::table_name(),
             "balance",
+            "where written_off_timestamp is null",
             value_completer,
         ))
         .unwrap_or_else(|num| {
@@ -261,8 +440,8 @@ impl PayableDao for PayableDaoReal {
     #[cfg(test)]
     fn account_status(&self, wallet: &Wallet) -> Option<PayableAccount> {
         let stm = "\
-            select balance_high_b, balance_low_b, last_paid_timestamp, pending_payable_rowid \
-            from payable \
+            select balance_high_b, balance_low_b, last_paid_timestamp, pending_payable_rowid, \
+            written_off_timestamp from payable \
             where wallet_address = ?";
         let mut stmt = self.conn.prepare(stm).unwrap();
         stmt.query_row(&[&wallet], |row| {
@@ -270,13 +449,21 @@ impl PayableDao for PayableDaoReal {
             let low_bytes_result = row.get(1);
             let last_paid_timestamp_result = row.get(2);
             let pending_payable_rowid_result: Result<Option<i64>, Error> = row.get(3);
+            let written_off_timestamp_result: Result<Option<i64>, Error> = row.get(4);
             match (
                 high_bytes_result,
                 low_bytes_result,
                 last_paid_timestamp_result,
                 pending_payable_rowid_result,
+                written_off_timestamp_result,
             ) {
-                (Ok(high_bytes), Ok(low_bytes), Ok(last_paid_timestamp), Ok(rowid)) => {
+                (
+                    Ok(high_bytes),
+                    Ok(low_bytes),
+                    Ok(last_paid_timestamp),
+                    Ok(rowid),
+                    Ok(written_off_opt),
+                ) => {
                     Ok(PayableAccount {
                         wallet: wallet.clone(),
                         balance_wei: checked_conversion::<i128, u128>(BigIntDivider::reconstitute(
@@ -290,6 +477,7 @@ impl PayableDao for PayableDaoReal {
                             )),
                             None => None,
                         },
+                        written_off: written_off_opt.is_some(),
                     })
                 }
                 e => panic!(
@@ -311,6 +499,30 @@ impl PayableDaoReal {
         }
     }
 
+    fn less_money_payable(&self, wallet: &Wallet, amount: u128) -> Result<(), PayableDaoError> {
+        let main_sql = "update payable set balance_high_b = balance_high_b + :balance_high_b, \
+                balance_low_b = balance_low_b + :balance_low_b where wallet_address = :wallet";
+        let update_clause_with_compensated_overflow = "update payable set \
+                balance_high_b = :balance_high_b, balance_low_b = :balance_low_b \
+                where wallet_address = :wallet";
+
+        let params = SQLParamsBuilder::default()
+            .key(WalletAddress(wallet))
+            .wei_change(WeiChange::new(
+                "balance",
+                amount,
+                WeiChangeDirection::Subtraction,
+            ))
+            .build();
+
+        self.big_int_db_processor.execute(
+            Either::Left(self.conn.as_ref()),
+            BigIntSqlConfig::new(main_sql, update_clause_with_compensated_overflow, params),
+        )?;
+
+        Ok(())
+    }
+
     fn create_payable_account(row: &Row) -> rusqlite::Result<PayableAccount> {
         let wallet_result: Result<Wallet, Error> = row.get(0);
         let balance_high_bytes_result = row.get(1);
@@ -318,6 +530,7 @@ impl PayableDaoReal {
         let last_paid_timestamp_result = row.get(3);
         let pending_payable_rowid_result: Result<Option<i64>, Error> = row.get(4);
         let pending_payable_hash_result: Result<Option<String>, Error> = row.get(5);
+        let written_off_timestamp_result: Result<Option<i64>, Error> = row.get(6);
         match (
             wallet_result,
             balance_high_bytes_result,
@@ -325,6 +538,7 @@ impl PayableDaoReal {
             last_paid_timestamp_result,
             pending_payable_rowid_result,
             pending_payable_hash_result,
+            written_off_timestamp_result,
         ) {
             (
                 Ok(wallet),
@@ -333,6 +547,7 @@ impl PayableDaoReal {
                 Ok(last_paid_timestamp),
                 Ok(rowid_opt),
                 Ok(hash_opt),
+                Ok(written_off_opt),
             ) => Ok(PayableAccount {
                 wallet,
                 balance_wei: checked_conversion::<i128, u128>(BigIntDivider::reconstitute(
@@ -348,6 +563,7 @@ impl PayableDaoReal {
                             .unwrap_or_else(|_| panic!("wrong form of tx hash {}", hash_str)),
                     )
                 }),
+                written_off: written_off_opt.is_some(),
             }),
             e => panic!(
                 "Database is corrupt: PAYABLE table columns and/or types: {:?}",
@@ -364,7 +580,8 @@ impl PayableDaoReal {
                balance_low_b,
                last_paid_timestamp,
                pending_payable_rowid,
-               pending_payable.transaction_hash
+               pending_payable.transaction_hash,
+               written_off_timestamp
            from
                payable
            left join pending_payable on
@@ -547,6 +764,7 @@ mod tests {
     use crate::accountant::test_utils::{assert_account_creation_fn_fails_on_finding_wrong_columns_and_value_types, make_pending_payable_fingerprint, trick_rusqlite_with_read_only_conn};
     use crate::blockchain::test_utils::make_tx_hash;
     use crate::database::rusqlite_wrappers::ConnectionWrapperReal;
+    use crate::sub_lib::earning_wallet_shares::WalletShare;
     use crate::database::db_initializer::{
         DbInitializationConfig, DbInitializer, DbInitializerReal, DATABASE_FILE,
     };
@@ -554,7 +772,7 @@ mod tests {
     use masq_lib::messages::TopRecordsOrdering::{Age, Balance};
     use masq_lib::test_utils::utils::ensure_node_home_directory_exists;
     use rusqlite::{Connection, OpenFlags};
-    use rusqlite::{ToSql};
+    use std::collections::HashSet;
     use std::path::Path;
     use std::str::FromStr;
     use crate::database::test_utils::ConnectionWrapperMock;
@@ -748,6 +966,7 @@ mod tests {
                     balance_wei: u128::try_from(BigIntDivider::reconstitute(12345, 1)).unwrap(),
                     last_paid_timestamp: from_time_t(45678),
                     pending_payable_opt: None,
+                    written_off: false,
                 },
                 PayableAccount {
                     wallet: wallet_1,
@@ -757,6 +976,7 @@ mod tests {
                         pending_payable_rowid_1,
                         make_tx_hash(0)
                     )),
+                    written_off: false,
                 },
                 //notice the hashes are garbage generated by a test method not knowing doing better
                 PayableAccount {
@@ -766,7 +986,8 @@ mod tests {
                     pending_payable_opt: Some(PendingPayableId::new(
                         pending_payable_rowid_2,
                         make_tx_hash(0)
-                    ))
+                    )),
+                    written_off: false,
                 }
             ]
         )
@@ -1022,6 +1243,7 @@ mod tests {
                 setup_holder.fingerprint_1.rowid,
                 H256::from_uint(&U256::from(0)),
             )), //hash is just garbage
+            written_off: false,
         };
         let expected_status_before_2 = PayableAccount {
             wallet: setup_holder.wallet_2.clone(),
@@ -1031,18 +1253,21 @@ mod tests {
                 setup_holder.fingerprint_2.rowid,
                 H256::from_uint(&U256::from(0)),
             )), //hash is just garbage
+            written_off: false,
         };
         let expected_resulting_status_1 = PayableAccount {
             wallet: setup_holder.wallet_1.clone(),
             balance_wei: expected_balance_after_1,
             last_paid_timestamp: setup_holder.fingerprint_1.timestamp,
             pending_payable_opt: None,
+            written_off: false,
         };
         let expected_resulting_status_2 = PayableAccount {
             wallet: setup_holder.wallet_2.clone(),
             balance_wei: expected_balance_after_2,
             last_paid_timestamp: setup_holder.fingerprint_2.timestamp,
             pending_payable_opt: None,
+            written_off: false,
         };
         assert_eq!(status_1_before_opt, Some(expected_status_before_1));
         assert_eq!(status_2_before_opt, Some(expected_status_before_2));
@@ -1131,6 +1356,7 @@ mod tests {
             balance_wei: 1_111_111 - setup_holder.fingerprint_1.amount,
             last_paid_timestamp: setup_holder.fingerprint_1.timestamp,
             pending_payable_opt: None,
+            written_off: false,
         };
 
         let result = subject
@@ -1204,18 +1430,461 @@ mod tests {
                     wallet: make_wallet("foobar"),
                     balance_wei: 1234567890123456 as u128,
                     last_paid_timestamp: from_time_t(111_111_111),
-                    pending_payable_opt: None
+                    pending_payable_opt: None,
+                    written_off: false,
                 },
                 PayableAccount {
                     wallet: make_wallet("barfoo"),
                     balance_wei: 1234567890123456 as u128,
                     last_paid_timestamp: from_time_t(111_111_111),
-                    pending_payable_opt: None
+                    pending_payable_opt: None,
+                    written_off: false,
                 },
             ]
         );
     }
 
+    #[test]
+    fn non_pending_payables_skips_written_off_payables() {
+        let home_dir = ensure_node_home_directory_exists(
+            "payable_dao",
+            "non_pending_payables_skips_written_off_payables",
+        );
+        let conn = DbInitializerReal::default()
+            .initialize(&home_dir, DbInitializationConfig::test_default())
+            .unwrap();
+        let written_off_wallet = make_wallet("written_off");
+        let live_wallet = make_wallet("live");
+        insert_payable_record_fn(&*conn, &written_off_wallet.to_string(), 5000, 111_111, None);
+        insert_payable_record_fn(&*conn, &live_wallet.to_string(), 6000, 222_222, None);
+        set_payable_timestamps(&*conn, &written_off_wallet, None, Some(333_333));
+        let subject = PayableDaoReal::new(conn);
+
+        let result = subject.non_pending_payables();
+
+        assert_eq!(
+            result,
+            vec![PayableAccount {
+                wallet: live_wallet,
+                balance_wei: 6000,
+                last_paid_timestamp: from_time_t(222_222),
+                pending_payable_opt: None,
+                written_off: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn more_money_payable_revives_written_off_payable_and_marks_its_creditor_seen() {
+        let home_dir = ensure_node_home_directory_exists(
+            "payable_dao",
+            "more_money_payable_revives_written_off_payable_and_marks_its_creditor_seen",
+        );
+        let conn = DbInitializerReal::default()
+            .initialize(&home_dir, DbInitializationConfig::test_default())
+            .unwrap();
+        let wallet = make_wallet("booga");
+        insert_payable_record_fn(&*conn, &wallet.to_string(), 5000, 111_111, None);
+        set_payable_timestamps(&*conn, &wallet, Some(111_111), Some(222_222));
+        let subject = PayableDaoReal::new(conn);
+        assert_eq!(subject.account_status(&wallet).unwrap().written_off, true);
+
+        subject
+            .more_money_payable(from_time_t(333_333), &wallet, 1000)
+            .unwrap();
+
+        let status = subject.account_status(&wallet).unwrap();
+        assert_eq!(status.balance_wei, 6000);
+        assert_eq!(status.last_paid_timestamp, from_time_t(111_111));
+        assert_eq!(status.written_off, false);
+        assert_eq!(
+            read_payable_timestamps(subject.conn.as_ref(), &wallet),
+            (Some(333_333), None)
+        );
+    }
+
+    #[test]
+    fn write_off_stale_payables_applies_both_rules_of_the_policy() {
+        let home_dir = ensure_node_home_directory_exists(
+            "payable_dao",
+            "write_off_stale_payables_applies_both_rules_of_the_policy",
+        );
+        let conn = DbInitializerReal::default()
+            .initialize(&home_dir, DbInitializationConfig::test_default())
+            .unwrap();
+        let now = now_time_t();
+        let day = 86_400;
+        let policy = PayableWriteOffPolicy {
+            unseen_days: 30,
+            dust_threshold_gwei: 1_000,
+            dust_days: 10,
+        };
+        let insert = |name: &str, balance: i128, last_paid: i64, last_seen_opt: Option<i64>| {
+            let wallet = make_wallet(name);
+            insert_payable_record_fn(&*conn, &wallet.to_string(), balance, last_paid, None);
+            set_payable_timestamps(&*conn, &wallet, last_seen_opt, None);
+            wallet
+        };
+        let unseen = insert(
+            "unseen",
+            gwei_to_wei(50_000_u64),
+            now - 40 * day,
+            Some(now - 31 * day),
+        );
+        let never_seen = insert("never_seen", gwei_to_wei(50_000_u64), now - 31 * day, None);
+        let seen_lately = insert(
+            "seen_lately",
+            gwei_to_wei(50_000_u64),
+            now - 40 * day,
+            Some(now - day),
+        );
+        let paid_lately = insert("paid_lately", gwei_to_wei(50_000_u64), now - 2 * day, None);
+        let old_dust = insert("old_dust", gwei_to_wei(999_u64), now - 11 * day, Some(now));
+        let fresh_dust = insert("fresh_dust", gwei_to_wei(999_u64), now - 9 * day, Some(now));
+        let old_non_dust = insert(
+            "old_non_dust",
+            gwei_to_wei(1_000_u64),
+            now - 11 * day,
+            Some(now),
+        );
+        let pending = insert("pending", gwei_to_wei(999_u64), now - 40 * day, None);
+        conn.prepare("update payable set pending_payable_rowid = 5 where wallet_address = ?")
+            .unwrap()
+            .execute([&pending])
+            .unwrap();
+        let subject = PayableDaoReal::new(conn);
+
+        let result = subject
+            .write_off_stale_payables(&policy, from_time_t(now))
+            .unwrap();
+
+        assert_eq!(
+            result.into_iter().collect::<HashSet<_>>(),
+            vec![unseen.clone(), never_seen.clone(), old_dust.clone()]
+                .into_iter()
+                .collect::<HashSet<_>>()
+        );
+        let written_off_timestamp =
+            |wallet: &Wallet| read_payable_timestamps(subject.conn.as_ref(), wallet).1;
+        assert_eq!(written_off_timestamp(&unseen), Some(now));
+        assert_eq!(written_off_timestamp(&never_seen), Some(now));
+        assert_eq!(written_off_timestamp(&old_dust), Some(now));
+        vec![seen_lately, paid_lately, fresh_dust, old_non_dust, pending]
+            .iter()
+            .for_each(|wallet| assert_eq!(written_off_timestamp(wallet), None));
+        let second_round = subject
+            .write_off_stale_payables(&policy, from_time_t(now + 1))
+            .unwrap();
+        assert_eq!(second_round, vec![]);
+        assert_eq!(written_off_timestamp(&unseen), Some(now));
+    }
+
+    #[test]
+    fn write_off_stale_payables_does_nothing_if_the_policy_is_disabled() {
+        let home_dir = ensure_node_home_directory_exists(
+            "payable_dao",
+            "write_off_stale_payables_does_nothing_if_the_policy_is_disabled",
+        );
+        let conn = DbInitializerReal::default()
+            .initialize(&home_dir, DbInitializationConfig::test_default())
+            .unwrap();
+        let wallet = make_wallet("ancient");
+        insert_payable_record_fn(&*conn, &wallet.to_string(), 1, 1_000, None);
+        let subject = PayableDaoReal::new(conn);
+
+        let result = subject
+            .write_off_stale_payables(&PayableWriteOffPolicy::default(), SystemTime::now())
+            .unwrap();
+
+        assert_eq!(result, vec![]);
+        assert_eq!(subject.account_status(&wallet).unwrap().written_off, false);
+    }
+
+    #[test]
+    fn write_off_stale_payables_handles_error() {
+        let home_dir = ensure_node_home_directory_exists(
+            "payable_dao",
+            "write_off_stale_payables_handles_error",
+        );
+        let conn = payable_read_only_conn(&home_dir);
+        let subject = PayableDaoReal::new(Box::new(ConnectionWrapperReal::new(conn)));
+        let policy = PayableWriteOffPolicy {
+            unseen_days: 30,
+            dust_threshold_gwei: 0,
+            dust_days: 0,
+        };
+
+        let result = subject.write_off_stale_payables(&policy, SystemTime::now());
+
+        assert_eq!(
+            result,
+            Err(PayableDaoError::RusqliteError(
+                "attempt to write a readonly database".to_string()
+            ))
+        )
+    }
+
+    #[test]
+    fn mark_seen_in_gossip_updates_only_the_given_wallets() {
+        let home_dir = ensure_node_home_directory_exists(
+            "payable_dao",
+            "mark_seen_in_gossip_updates_only_the_given_wallets",
+        );
+        let conn = DbInitializerReal::default()
+            .initialize(&home_dir, DbInitializationConfig::test_default())
+            .unwrap();
+        let wallet_1 = make_wallet("wallet_1");
+        let wallet_2 = make_wallet("wallet_2");
+        let wallet_3 = make_wallet("wallet_3");
+        vec![&wallet_1, &wallet_2, &wallet_3]
+            .into_iter()
+            .for_each(|wallet| {
+                insert_payable_record_fn(&*conn, &wallet.to_string(), 5000, 111_111, None);
+                set_payable_timestamps(&*conn, wallet, Some(222_222), None)
+            });
+        let subject = PayableDaoReal::new(conn);
+
+        let result = subject.mark_seen_in_gossip(
+            &[wallet_1.clone(), wallet_3.clone(), make_wallet("unknown")],
+            from_time_t(333_333),
+        );
+
+        assert_eq!(result, Ok(()));
+        let last_seen = |wallet: &Wallet| read_payable_timestamps(subject.conn.as_ref(), wallet).0;
+        assert_eq!(last_seen(&wallet_1), Some(333_333));
+        assert_eq!(last_seen(&wallet_2), Some(222_222));
+        assert_eq!(last_seen(&wallet_3), Some(333_333));
+    }
+
+    #[test]
+    fn mark_seen_in_gossip_handles_error() {
+        let home_dir =
+            ensure_node_home_directory_exists("payable_dao", "mark_seen_in_gossip_handles_error");
+        let conn = payable_read_only_conn(&home_dir);
+        let subject = PayableDaoReal::new(Box::new(ConnectionWrapperReal::new(conn)));
+
+        let result = subject.mark_seen_in_gossip(&[make_wallet("booga")], SystemTime::now());
+
+        assert_eq!(
+            result,
+            Err(PayableDaoError::RusqliteError(
+                "attempt to write a readonly database".to_string()
+            ))
+        )
+    }
+
+    fn make_shares(shares: &[(&str, u8)]) -> EarningWalletShares {
+        EarningWalletShares::new(
+            shares
+                .iter()
+                .map(|(wallet, percentage)| WalletShare {
+                    wallet: make_wallet(wallet),
+                    percentage: *percentage,
+                })
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    fn set_earning_wallet_shares(conn: &dyn ConnectionWrapper, wallet: &Wallet, shares: &str) {
+        let params: &[&dyn ToSql] = &[&shares, wallet];
+        conn.prepare("update payable set earning_wallet_shares = ? where wallet_address = ?")
+            .unwrap()
+            .execute(params)
+            .unwrap();
+    }
+
+    fn read_earning_wallet_shares(conn: &dyn ConnectionWrapper, wallet: &Wallet) -> Option<String> {
+        conn.prepare("select earning_wallet_shares from payable where wallet_address = ?")
+            .unwrap()
+            .query_row([wallet], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn record_earning_wallet_shares_stores_shares_and_forgets_them_when_there_are_none() {
+        let home_dir = ensure_node_home_directory_exists(
+            "payable_dao",
+            "record_earning_wallet_shares_stores_shares_and_forgets_them_when_there_are_none",
+        );
+        let conn = DbInitializerReal::default()
+            .initialize(&home_dir, DbInitializationConfig::test_default())
+            .unwrap();
+        let wallet_1 = make_wallet("wallet_1");
+        let wallet_2 = make_wallet("wallet_2");
+        let wallet_3 = make_wallet("wallet_3");
+        vec![&wallet_1, &wallet_2, &wallet_3]
+            .into_iter()
+            .for_each(|wallet| {
+                insert_payable_record_fn(&*conn, &wallet.to_string(), 5000, 111_111, None);
+                set_earning_wallet_shares(&*conn, wallet, "old shares")
+            });
+        let shares = make_shares(&[("share", 25)]);
+        let subject = PayableDaoReal::new(conn);
+
+        let result = subject.record_earning_wallet_shares(&[
+            (wallet_1.clone(), shares.clone()),
+            (wallet_3.clone(), EarningWalletShares::default()),
+            (make_wallet("unknown"), shares.clone()),
+        ]);
+
+        assert_eq!(result, Ok(()));
+        let stored_shares =
+            |wallet: &Wallet| read_earning_wallet_shares(subject.conn.as_ref(), wallet);
+        assert_eq!(stored_shares(&wallet_1), Some(shares.to_string()));
+        assert_eq!(stored_shares(&wallet_2), Some("old shares".to_string()));
+        assert_eq!(stored_shares(&wallet_3), None);
+    }
+
+    #[test]
+    fn record_earning_wallet_shares_handles_error() {
+        let home_dir = ensure_node_home_directory_exists(
+            "payable_dao",
+            "record_earning_wallet_shares_handles_error",
+        );
+        let conn = payable_read_only_conn(&home_dir);
+        let subject = PayableDaoReal::new(Box::new(ConnectionWrapperReal::new(conn)));
+
+        let result = subject
+            .record_earning_wallet_shares(&[(make_wallet("booga"), make_shares(&[("share", 25)]))]);
+
+        assert_eq!(
+            result,
+            Err(PayableDaoError::RusqliteError(
+                "attempt to write a readonly database".to_string()
+            ))
+        )
+    }
+
+    #[test]
+    fn split_among_earning_wallet_shares_moves_the_shares_portions_into_their_own_rows() {
+        let home_dir = ensure_node_home_directory_exists(
+            "payable_dao",
+            "split_among_earning_wallet_shares_moves_the_shares_portions_into_their_own_rows",
+        );
+        let conn = DbInitializerReal::default()
+            .initialize(&home_dir, DbInitializationConfig::test_default())
+            .unwrap();
+        let sharing = make_wallet("sharing");
+        let not_asked_for = make_wallet("not_asked_for");
+        let not_sharing = make_wallet("not_sharing");
+        let pending = make_wallet("pending");
+        let existing_share = make_wallet("existing_share");
+        let new_share = make_wallet("new_share");
+        let shares = make_shares(&[("new_share", 30), ("existing_share", 20)]).to_string();
+        insert_payable_record_fn(&*conn, &sharing.to_string(), 1_000_001, 111_111, None);
+        insert_payable_record_fn(&*conn, &not_asked_for.to_string(), 1_000_000, 111_111, None);
+        insert_payable_record_fn(&*conn, &not_sharing.to_string(), 1_000_000, 111_111, None);
+        insert_payable_record_fn(&*conn, &pending.to_string(), 1_000_000, 111_111, Some(1));
+        insert_payable_record_fn(&*conn, &existing_share.to_string(), 5_000, 222_222, None);
+        vec![&sharing, &not_asked_for, &pending]
+            .into_iter()
+            .for_each(|wallet| set_earning_wallet_shares(&*conn, wallet, &shares));
+        let subject = PayableDaoReal::new(conn);
+
+        let result = subject.split_among_earning_wallet_shares(
+            &[sharing.clone(), not_sharing.clone(), pending.clone()],
+            from_time_t(333_333),
+        );
+
+        let sort = |mut accounts: Vec<PayableAccount>| {
+            accounts.sort_by_key(|account| account.wallet.to_string());
+            accounts
+        };
+        let account = |wallet: &Wallet, balance_wei: u128, last_paid: i64| PayableAccount {
+            wallet: wallet.clone(),
+            balance_wei,
+            last_paid_timestamp: from_time_t(last_paid),
+            pending_payable_opt: None,
+            written_off: false,
+        };
+        let sharing_account = account(&sharing, 500_001, 111_111);
+        let existing_share_account = account(&existing_share, 205_000, 222_222);
+        let new_share_account = account(&new_share, 300_000, 333_333);
+        assert_eq!(
+            result.map(sort),
+            Ok(sort(vec![
+                sharing_account.clone(),
+                existing_share_account.clone(),
+                new_share_account.clone()
+            ]))
+        );
+        assert_eq!(
+            sort(subject.non_pending_payables()),
+            sort(vec![
+                sharing_account,
+                account(&not_asked_for, 1_000_000, 111_111),
+                account(&not_sharing, 1_000_000, 111_111),
+                existing_share_account,
+                new_share_account
+            ])
+        );
+        assert_eq!(
+            subject.account_status(&pending).unwrap().balance_wei,
+            1_000_000
+        );
+    }
+
+    #[test]
+    fn split_among_earning_wallet_shares_ignores_shares_that_do_not_add_up() {
+        let home_dir = ensure_node_home_directory_exists(
+            "payable_dao",
+            "split_among_earning_wallet_shares_ignores_shares_that_do_not_add_up",
+        );
+        let conn = DbInitializerReal::default()
+            .initialize(&home_dir, DbInitializationConfig::test_default())
+            .unwrap();
+        let sharing = make_wallet("sharing");
+        insert_payable_record_fn(&*conn, &sharing.to_string(), 1_000_000, 111_111, None);
+        set_earning_wallet_shares(
+            &*conn,
+            &sharing,
+            &format!("{}:60,{}:60", make_wallet("first"), make_wallet("second")),
+        );
+        let subject = PayableDaoReal::new(conn);
+
+        let result =
+            subject.split_among_earning_wallet_shares(&[sharing.clone()], SystemTime::now());
+
+        assert_eq!(result, Ok(vec![]));
+        assert_eq!(subject.non_pending_payables().len(), 1);
+        assert_eq!(
+            subject.account_status(&sharing).unwrap().balance_wei,
+            1_000_000
+        );
+    }
+
+    fn set_payable_timestamps(
+        conn: &dyn ConnectionWrapper,
+        wallet: &Wallet,
+        last_seen_opt: Option<i64>,
+        written_off_opt: Option<i64>,
+    ) {
+        let params: &[&dyn ToSql] = &[&last_seen_opt, &written_off_opt, wallet];
+        conn.prepare(
+            "update payable set last_seen_timestamp = ?, written_off_timestamp = ? \
+            where wallet_address = ?",
+        )
+        .unwrap()
+        .execute(params)
+        .unwrap();
+    }
+
+    fn read_payable_timestamps(
+        conn: &dyn ConnectionWrapper,
+        wallet: &Wallet,
+    ) -> (Option<i64>, Option<i64>) {
+        conn.prepare(
+            "select last_seen_timestamp, written_off_timestamp from payable \
+            where wallet_address = ?",
+        )
+        .unwrap()
+        .query_row([wallet], |row| {
+            Ok((row.get(0).unwrap(), row.get(1).unwrap()))
+        })
+        .unwrap()
+    }
+
     #[test]
     fn custom_query_handles_empty_table_in_top_records_mode() {
         let main_test_setup = |_conn: &dyn ConnectionWrapper, _insert: InsertPayableHelperFn| {};
@@ -1325,7 +1994,8 @@ mod tests {
                     wallet: Wallet::new("0x2222222222222222222222222222222222222222"),
                     balance_wei: 7_562_000_300_000,
                     last_paid_timestamp: from_time_t(now - 86_001),
-                    pending_payable_opt: None
+                    pending_payable_opt: None,
+                    written_off: false,
                 },
                 PayableAccount {
                     wallet: Wallet::new("0x5555555555555555555555555555555555555555"),
@@ -1337,13 +2007,74 @@ mod tests {
                             "abc4546cce78230a2312e12f3acb78747340456fe5237896666100143abcd223"
                         )
                         .unwrap()
-                    ))
+                    )),
+                    written_off: false,
                 },
                 PayableAccount {
                     wallet: Wallet::new("0x4444444444444444444444444444444444444444"),
                     balance_wei: 10_000_000_100,
                     last_paid_timestamp: from_time_t(now - 86_300),
-                    pending_payable_opt: None
+                    pending_payable_opt: None,
+                    written_off: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn custom_query_keeps_written_off_payables_and_flags_them() {
+        let now = now_time_t();
+        let main_test_setup = |conn: &dyn ConnectionWrapper, insert: InsertPayableHelperFn| {
+            insert(
+                conn,
+                "0x1111111111111111111111111111111111111111",
+                5_000_000_000,
+                now - 86_400,
+                None,
+            );
+            insert(
+                conn,
+                "0x2222222222222222222222222222222222222222",
+                3_000_000_000,
+                now - 86_400,
+                None,
+            );
+            conn.prepare(
+                "update payable set written_off_timestamp = 1 \
+                where wallet_address = '0x1111111111111111111111111111111111111111'",
+            )
+            .unwrap()
+            .execute([])
+            .unwrap();
+        };
+        let subject = custom_query_test_body_for_payable(
+            "custom_query_keeps_written_off_payables_and_flags_them",
+            main_test_setup,
+        );
+
+        let result = subject
+            .custom_query(CustomQuery::TopRecords {
+                count: 5,
+                ordered_by: Balance,
+            })
+            .unwrap();
+
+        assert_eq!(
+            result,
+            vec![
+                PayableAccount {
+                    wallet: Wallet::new("0x1111111111111111111111111111111111111111"),
+                    balance_wei: 5_000_000_000,
+                    last_paid_timestamp: from_time_t(now - 86_400),
+                    pending_payable_opt: None,
+                    written_off: true,
+                },
+                PayableAccount {
+                    wallet: Wallet::new("0x2222222222222222222222222222222222222222"),
+                    balance_wei: 3_000_000_000,
+                    last_paid_timestamp: from_time_t(now - 86_400),
+                    pending_payable_opt: None,
+                    written_off: false,
                 },
             ]
         );
@@ -1381,19 +2112,22 @@ mod tests {
                             "abc4546cce78230a2312e12f3acb78747340456fe5237896666100143abcd223"
                         )
                         .unwrap()
-                    ))
+                    )),
+                    written_off: false,
                 },
                 PayableAccount {
                     wallet: Wallet::new("0x1111111111111111111111111111111111111111"),
                     balance_wei: 1_000_000_002,
                     last_paid_timestamp: from_time_t(now - 86_401),
-                    pending_payable_opt: None
+                    pending_payable_opt: None,
+                    written_off: false,
                 },
                 PayableAccount {
                     wallet: Wallet::new("0x4444444444444444444444444444444444444444"),
                     balance_wei: 10_000_000_100,
                     last_paid_timestamp: from_time_t(now - 86_300),
-                    pending_payable_opt: None
+                    pending_payable_opt: None,
+                    written_off: false,
                 },
             ]
         );
@@ -1493,13 +2227,15 @@ mod tests {
                     wallet: Wallet::new("0x7777777777777777777777777777777777777777"),
                     balance_wei: gwei_to_wei(2_500_647_000_u32),
                     last_paid_timestamp: from_time_t(now - 80_333),
-                    pending_payable_opt: None
+                    pending_payable_opt: None,
+                    written_off: false,
                 },
                 PayableAccount {
                     wallet: Wallet::new("0x6666666666666666666666666666666666666666"),
                     balance_wei: gwei_to_wei(1_800_456_000_u32),
                     last_paid_timestamp: from_time_t(now - 100_401),
-                    pending_payable_opt: None
+                    pending_payable_opt: None,
+                    written_off: false,
                 },
                 PayableAccount {
                     wallet: Wallet::new("0x2222222222222222222222222222222222222222"),
@@ -1511,7 +2247,8 @@ mod tests {
                             "abc4546cce78230a2312e12f3acb78747340456fe5237896666100143abcd223"
                         )
                         .unwrap()
-                    ))
+                    )),
+                    written_off: false,
                 }
             ]
         );
@@ -1559,7 +2296,8 @@ mod tests {
                 wallet: Wallet::new("0x2222222222222222222222222222222222222222"),
                 balance_wei: 30_000_300_000,
                 last_paid_timestamp: from_time_t(timestamp_2),
-                pending_payable_opt: None
+                pending_payable_opt: None,
+                written_off: false,
             },]
         )
     }
@@ -1606,6 +2344,38 @@ mod tests {
         assert_eq!(total, 4_000_123_123)
     }
 
+    #[test]
+    fn total_leaves_out_written_off_payables() {
+        let home_dir = ensure_node_home_directory_exists(
+            "payable_dao",
+            "total_leaves_out_written_off_payables",
+        );
+        let conn = DbInitializerReal::default()
+            .initialize(&home_dir, DbInitializationConfig::test_default())
+            .unwrap();
+        let written_off_wallet = make_wallet("written_off");
+        insert_payable_record_fn(
+            &*conn,
+            &written_off_wallet.to_string(),
+            1_000,
+            111_111,
+            None,
+        );
+        insert_payable_record_fn(
+            &*conn,
+            &make_wallet("live").to_string(),
+            2_000,
+            111_111,
+            None,
+        );
+        set_payable_timestamps(&*conn, &written_off_wallet, None, Some(222_222));
+        let subject = PayableDaoReal::new(conn);
+
+        let total = subject.total();
+
+        assert_eq!(total, 2_000)
+    }
+
     #[test]
     #[should_panic(
         expected = "database corrupted: found negative value -999999 in payable table for row id 2"
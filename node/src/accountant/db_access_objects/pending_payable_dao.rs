@@ -9,9 +9,10 @@ use crate::blockchain::blockchain_bridge::PendingPayableFingerprint;
 use crate::blockchain::blockchain_interface::blockchain_interface_web3::HashAndAmount;
 use crate::database::rusqlite_wrappers::ConnectionWrapper;
 use crate::sub_lib::wallet::Wallet;
+use itertools::Itertools;
 use masq_lib::utils::ExpectValue;
 use rusqlite::Row;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::str::FromStr;
 use std::time::SystemTime;
@@ -34,8 +35,11 @@ pub struct TransactionHashes {
 }
 
 pub trait PendingPayableDao {
-    // Note that the order of the returned results is not guaranteed
+    // Fingerprints sharing one hash (a multisend transaction) come in the order they were
+    // inserted; apart from that, the order of the returned results is not guaranteed
     fn fingerprints_rowids(&self, hashes: &[H256]) -> TransactionHashes;
+    // Fingerprints written before their recipients were recorded are left out
+    fn fingerprints_recipients(&self, ids: &[u64]) -> HashMap<u64, Wallet>;
     fn return_all_errorless_fingerprints(&self) -> Vec<PendingPayableFingerprint>;
     fn insert_new_fingerprints(
         &self,
@@ -59,7 +63,8 @@ impl PendingPayableDao for PendingPayableDaoReal<'_> {
         }
 
         let sql = format!(
-            "select transaction_hash, rowid from pending_payable where transaction_hash in ({})",
+            "select transaction_hash, rowid from pending_payable where transaction_hash in ({}) \
+            order by rowid",
             comma_joined_stringifiable(hashes, |hash| format!("'{:?}'", hash))
         );
 
@@ -78,6 +83,7 @@ impl PendingPayableDao for PendingPayableDaoReal<'_> {
         let hashes_of_missing_rowids = hashes
             .iter()
             .filter(|hash| !hashes_of_found_records.contains(hash))
+            .unique()
             .cloned()
             .collect();
 
@@ -87,6 +93,31 @@ impl PendingPayableDao for PendingPayableDaoReal<'_> {
         }
     }
 
+    fn fingerprints_recipients(&self, ids: &[u64]) -> HashMap<u64, Wallet> {
+        let sql = format!(
+            "select rowid, recipient_wallet from pending_payable where rowid in ({}) \
+            and recipient_wallet is not null",
+            Self::serialize_ids(ids)
+        );
+        self.conn
+            .prepare(&sql)
+            .expect("Internal error")
+            .query_map([], |row| {
+                let rowid: u64 = Self::get_with_expect(row, 0);
+                let wallet_str: String = Self::get_with_expect(row, 1);
+                let wallet = Wallet::from_str(&wallet_str).unwrap_or_else(|e| {
+                    panic!(
+                        "Invalid recipient wallet (\"{}\": {:?}) - database corrupt",
+                        wallet_str, e
+                    )
+                });
+                Ok((rowid, wallet))
+            })
+            .expect("map query failed")
+            .vigilant_flatten()
+            .collect()
+    }
+
     fn return_all_errorless_fingerprints(&self) -> Vec<PendingPayableFingerprint> {
         let mut stm = self
             .conn
@@ -138,16 +169,20 @@ impl PendingPayableDao for PendingPayableDaoReal<'_> {
                 let amount_checked = checked_conversion::<u128, i128>(hash_and_amount.amount);
                 let (high_bytes, low_bytes) = BigIntDivider::deconstruct(amount_checked);
                 format!(
-                    "('{:?}', {}, {}, {}, 1, null)",
-                    hash_and_amount.hash, high_bytes, low_bytes, time_t
+                    "('{:?}', {}, {}, {}, 1, null, '{:?}')",
+                    hash_and_amount.hash,
+                    high_bytes,
+                    low_bytes,
+                    time_t,
+                    hash_and_amount.recipient_address
                 )
             })
         }
 
         let insert_sql = format!(
             "insert into pending_payable (\
-            transaction_hash, amount_high_b, amount_low_b, payable_timestamp, attempt, process_error\
-            ) values {}",
+            transaction_hash, amount_high_b, amount_low_b, payable_timestamp, attempt, \
+            process_error, recipient_wallet) values {}",
             values_clause_for_fingerprints_to_insert(hashes_and_amounts, batch_wide_timestamp)
         );
         match self
@@ -285,8 +320,10 @@ mod tests {
     };
     use crate::database::rusqlite_wrappers::ConnectionWrapperReal;
     use crate::database::test_utils::ConnectionWrapperMock;
+    use crate::test_utils::make_wallet;
     use masq_lib::test_utils::utils::ensure_node_home_directory_exists;
     use rusqlite::{Connection, OpenFlags};
+    use std::collections::HashMap;
     use std::str::FromStr;
     use std::time::SystemTime;
     use web3::types::H256;
@@ -309,10 +346,12 @@ mod tests {
         let hash_and_amount_1 = HashAndAmount {
             hash: hash_1,
             amount: amount_1,
+            recipient_address: make_wallet("creditor").address(),
         };
         let hash_and_amount_2 = HashAndAmount {
             hash: hash_2,
             amount: amount_2,
+            recipient_address: make_wallet("creditor").address(),
         };
 
         let _ = subject
@@ -367,7 +406,11 @@ mod tests {
         let amount = 55556;
         let timestamp = from_time_t(200_000_000);
         let subject = PendingPayableDaoReal::new(Box::new(wrapped_conn));
-        let hash_and_amount = HashAndAmount { hash, amount };
+        let hash_and_amount = HashAndAmount {
+            hash,
+            amount,
+            recipient_address: make_wallet("creditor").address(),
+        };
 
         let result = subject.insert_new_fingerprints(&[hash_and_amount], timestamp);
 
@@ -399,6 +442,7 @@ mod tests {
         let hash_and_amount = HashAndAmount {
             hash: hash_1,
             amount: amount_1,
+            recipient_address: make_wallet("creditor").address(),
         };
 
         let _ = subject.insert_new_fingerprints(&[hash_and_amount], batch_wide_timestamp);
@@ -426,10 +470,12 @@ mod tests {
         let hash_and_amount_1 = HashAndAmount {
             hash: hash_1,
             amount: 4567,
+            recipient_address: make_wallet("creditor").address(),
         };
         let hash_and_amount_2 = HashAndAmount {
             hash: hash_2,
             amount: 6789,
+            recipient_address: make_wallet("creditor").address(),
         };
         let fingerprints_init_input = vec![hash_and_amount_1, hash_and_amount_2];
         {
@@ -479,6 +525,7 @@ mod tests {
                 &[HashAndAmount {
                     hash: hash_2,
                     amount: 8901234,
+                    recipient_address: make_wallet("creditor").address(),
                 }],
                 SystemTime::now(),
             )
@@ -488,6 +535,7 @@ mod tests {
                 &[HashAndAmount {
                     hash: hash_3,
                     amount: 1234567,
+                    recipient_address: make_wallet("creditor").address(),
                 }],
                 SystemTime::now(),
             )
@@ -500,6 +548,132 @@ mod tests {
         assert_eq!(result.no_rowid_results, vec![hash_1, hash_2, hash_4]);
     }
 
+    #[test]
+    fn fingerprints_rowids_when_several_fingerprints_share_a_hash() {
+        let home_dir = ensure_node_home_directory_exists(
+            "pending_payable_dao",
+            "fingerprints_rowids_when_several_fingerprints_share_a_hash",
+        );
+        let wrapped_conn = DbInitializerReal::default()
+            .initialize(&home_dir, DbInitializationConfig::test_default())
+            .unwrap();
+        let subject = PendingPayableDaoReal::new(wrapped_conn);
+        let multisend_hash = make_tx_hash(12121);
+        let single_hash = make_tx_hash(34343);
+        let missing_hash = make_tx_hash(56565);
+        subject
+            .insert_new_fingerprints(
+                &[
+                    HashAndAmount {
+                        hash: single_hash,
+                        amount: 111,
+                        recipient_address: make_wallet("creditor").address(),
+                    },
+                    HashAndAmount {
+                        hash: multisend_hash,
+                        amount: 222,
+                        recipient_address: make_wallet("creditor").address(),
+                    },
+                    HashAndAmount {
+                        hash: multisend_hash,
+                        amount: 333,
+                        recipient_address: make_wallet("creditor").address(),
+                    },
+                ],
+                SystemTime::now(),
+            )
+            .unwrap();
+
+        let result = subject.fingerprints_rowids(&[
+            multisend_hash,
+            single_hash,
+            multisend_hash,
+            missing_hash,
+            missing_hash,
+        ]);
+
+        assert_eq!(
+            result.rowid_results,
+            vec![(1, single_hash), (2, multisend_hash), (3, multisend_hash)]
+        );
+        assert_eq!(result.no_rowid_results, vec![missing_hash]);
+    }
+
+    #[test]
+    fn fingerprints_recipients_leaves_out_fingerprints_without_a_recipient() {
+        let home_dir = ensure_node_home_directory_exists(
+            "pending_payable_dao",
+            "fingerprints_recipients_leaves_out_fingerprints_without_a_recipient",
+        );
+        let wrapped_conn = DbInitializerReal::default()
+            .initialize(&home_dir, DbInitializationConfig::test_default())
+            .unwrap();
+        {
+            wrapped_conn
+                .prepare("insert into pending_payable \
+                (rowid, transaction_hash, amount_high_b, amount_low_b, payable_timestamp, attempt, process_error) \
+                values (1, '0x1234', 0, 111, 10000000000, 1, null)")
+                .unwrap()
+                .execute([])
+                .unwrap();
+        }
+        let subject = PendingPayableDaoReal::new(wrapped_conn);
+        let multisend_hash = make_tx_hash(12121);
+        subject
+            .insert_new_fingerprints(
+                &[
+                    HashAndAmount {
+                        hash: multisend_hash,
+                        amount: 222,
+                        recipient_address: make_wallet("creditor_a").address(),
+                    },
+                    HashAndAmount {
+                        hash: multisend_hash,
+                        amount: 333,
+                        recipient_address: make_wallet("creditor_b").address(),
+                    },
+                ],
+                SystemTime::now(),
+            )
+            .unwrap();
+
+        let result = subject.fingerprints_recipients(&[1, 2, 3, 4]);
+
+        assert_eq!(
+            result,
+            HashMap::from([
+                (2, make_wallet("creditor_a")),
+                (3, make_wallet("creditor_b"))
+            ])
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Invalid recipient wallet (\"silly_wallet\": InvalidAddress) - database corrupt"
+    )]
+    fn fingerprints_recipients_panics_on_malformed_wallet() {
+        let home_dir = ensure_node_home_directory_exists(
+            "pending_payable_dao",
+            "fingerprints_recipients_panics_on_malformed_wallet",
+        );
+        let wrapped_conn = DbInitializerReal::default()
+            .initialize(&home_dir, DbInitializationConfig::test_default())
+            .unwrap();
+        {
+            wrapped_conn
+                .prepare("insert into pending_payable \
+                (rowid, transaction_hash, amount_high_b, amount_low_b, payable_timestamp, attempt, process_error, recipient_wallet) \
+                values (1, '0x1234', 0, 111, 10000000000, 1, null, 'silly_wallet')")
+                .unwrap()
+                .execute([])
+                .unwrap();
+        }
+        let subject = PendingPayableDaoReal::new(wrapped_conn);
+
+        let _ = subject.fingerprints_recipients(&[1]);
+    }
+
     #[test]
     fn return_all_errorless_fingerprints_works_when_no_records_with_error_marks() {
         let home_dir = ensure_node_home_directory_exists(
@@ -518,10 +692,12 @@ mod tests {
         let hash_and_amount_1 = HashAndAmount {
             hash: hash_1,
             amount: amount_1,
+            recipient_address: make_wallet("creditor").address(),
         };
         let hash_and_amount_2 = HashAndAmount {
             hash: hash_2,
             amount: amount_2,
+            recipient_address: make_wallet("creditor").address(),
         };
 
         {
@@ -574,8 +750,13 @@ mod tests {
         let hash_and_amount_1 = HashAndAmount {
             hash: make_tx_hash(11119),
             amount: 2000,
+            recipient_address: make_wallet("creditor").address(),
+        };
+        let hash_and_amount_2 = HashAndAmount {
+            hash,
+            amount,
+            recipient_address: make_wallet("creditor").address(),
         };
-        let hash_and_amount_2 = HashAndAmount { hash, amount };
         {
             subject
                 .insert_new_fingerprints(&[hash_and_amount_1, hash_and_amount_2], timestamp)
@@ -641,14 +822,17 @@ mod tests {
                         HashAndAmount {
                             hash: make_tx_hash(1234),
                             amount: 1111,
+                            recipient_address: make_wallet("creditor").address(),
                         },
                         HashAndAmount {
                             hash: make_tx_hash(2345),
                             amount: 5555,
+                            recipient_address: make_wallet("creditor").address(),
                         },
                         HashAndAmount {
                             hash: make_tx_hash(3456),
                             amount: 2222,
+                            recipient_address: make_wallet("creditor").address(),
                         },
                     ],
                     SystemTime::now(),
@@ -717,6 +901,7 @@ mod tests {
                     &[HashAndAmount {
                         hash: make_tx_hash(666666),
                         amount: 5555,
+                        recipient_address: make_wallet("creditor").address(),
                     }],
                     SystemTime::now(),
                 )
@@ -741,14 +926,17 @@ mod tests {
         let hash_and_amount_1 = HashAndAmount {
             hash: hash_1,
             amount: 1122,
+            recipient_address: make_wallet("creditor").address(),
         };
         let hash_and_amount_2 = HashAndAmount {
             hash: hash_2,
             amount: 2233,
+            recipient_address: make_wallet("creditor").address(),
         };
         let hash_and_amount_3 = HashAndAmount {
             hash: hash_3,
             amount: 3344,
+            recipient_address: make_wallet("creditor").address(),
         };
         let timestamp = from_time_t(190_000_000);
         let subject = PendingPayableDaoReal::new(conn);
@@ -837,10 +1025,12 @@ mod tests {
         let hash_and_amount_1 = HashAndAmount {
             hash: hash_1,
             amount: amount_1,
+            recipient_address: make_wallet("creditor").address(),
         };
         let hash_and_amount_2 = HashAndAmount {
             hash: hash_2,
             amount: amount_2,
+            recipient_address: make_wallet("creditor").address(),
         };
         let timestamp = from_time_t(190_000_000);
         let subject = PendingPayableDaoReal::new(conn);
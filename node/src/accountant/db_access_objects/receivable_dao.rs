@@ -76,7 +76,6 @@ pub trait ReceivableDao {
 
     fn total(&self) -> i128;
 
-    // Test-only method but because of shares with multi-node tests #[cfg(test)] cannot be applied
     fn account_status(&self, wallet: &Wallet) -> Option<ReceivableAccount>;
 
     as_any_ref_in_trait!();
@@ -233,7 +232,8 @@ impl ReceivableDao for ReceivableDaoReal {
             select r.wallet_address, r.balance_high_b, r.balance_low_b, r.last_received_timestamp
             from receivable r inner join banned b on r.wallet_address = b.wallet_address
             where
-                (r.balance_high_b < :unban_balance_high_b) or ((balance_high_b = :unban_balance_high_b) and (balance_low_b <= :unban_balance_low_b))
+                (b.reason is null or b.reason <> 'manual' or b.pardoned = 1)
+                and ((r.balance_high_b < :unban_balance_high_b) or ((balance_high_b = :unban_balance_high_b) and (balance_low_b <= :unban_balance_low_b)))
         "
         );
         let mut stmt = self.conn.prepare(sql).expect("Couldn't prepare statement");
@@ -288,6 +288,7 @@ impl ReceivableDao for ReceivableDaoReal {
             self.conn.as_ref(),
             &Self::table_name(),
             "balance",
+            "",
             value_creation,
         )
     }
@@ -501,6 +502,7 @@ mod tests {
         assert_account_creation_fn_fails_on_finding_wrong_columns_and_value_types,
         make_receivable_account, trick_rusqlite_with_read_only_conn,
     };
+    use crate::blockchain::test_utils::make_tx_hash;
     use crate::database::db_initializer::{DbInitializationConfig, DbInitializer, DATABASE_FILE};
     use crate::database::db_initializer::{DbInitializerReal, ExternalData};
     use crate::database::rusqlite_wrappers::ConnectionWrapperReal;
@@ -574,6 +576,7 @@ mod tests {
             block_number: 42u64,
             from: make_wallet("some_address"),
             wei_amount: u128::MAX,
+            transaction_hash: make_tx_hash(1),
         }];
 
         let _ = subject.more_money_received(SystemTime::now(), &payments);
@@ -599,6 +602,7 @@ mod tests {
             block_number: 42u64,
             from: make_wallet("some_address"),
             wei_amount: 18446744073709551615,
+            transaction_hash: make_tx_hash(2),
         }];
 
         let _ = subject.more_money_received(SystemTime::now(), &payments);
@@ -798,11 +802,13 @@ mod tests {
                 from: debtor1.clone(),
                 wei_amount: first_newly_received,
                 block_number: 35_u64,
+                transaction_hash: make_tx_hash(3),
             },
             BlockchainTransaction {
                 from: debtor2.clone(),
                 wei_amount: second_newly_received,
                 block_number: 57_u64,
+                transaction_hash: make_tx_hash(4),
             },
         ];
 
@@ -865,16 +871,19 @@ mod tests {
             block_number: 4444,
             from: first_tracked_wallet.clone(),
             wei_amount: 1111,
+            transaction_hash: make_tx_hash(5),
         };
         let transaction_2 = BlockchainTransaction {
             block_number: 4446,
             from: unknown_wallet.clone(),
             wei_amount: 2222,
+            transaction_hash: make_tx_hash(6),
         };
         let transaction_3 = BlockchainTransaction {
             block_number: 4450,
             from: second_tracked_wallet.clone(),
             wei_amount: 9999,
+            transaction_hash: make_tx_hash(7),
         };
         let transactions = vec![transaction_1, transaction_2, transaction_3];
 
@@ -936,6 +945,7 @@ mod tests {
             block_number: 123_456,
             from: wallet,
             wei_amount: 45_678,
+            transaction_hash: make_tx_hash(8),
         };
         let transactions = vec![transaction];
 
@@ -983,6 +993,7 @@ mod tests {
             block_number: 123_456,
             from: make_wallet("abc"),
             wei_amount: 1,
+            transaction_hash: make_tx_hash(9),
         };
         let transactions = vec![transaction];
 
@@ -1049,11 +1060,13 @@ mod tests {
             block_number: 123_456,
             from: first_wallet.clone(),
             wei_amount: 45_678,
+            transaction_hash: make_tx_hash(10),
         };
         let second_transaction = BlockchainTransaction {
             block_number: 789_123,
             from: second_wallet,
             wei_amount: 111_222,
+            transaction_hash: make_tx_hash(11),
         };
         let transactions = vec![first_transaction, second_transaction];
 
@@ -1152,6 +1165,7 @@ mod tests {
             block_number: 1234,
             from: wallet,
             wei_amount: 1_000_000_000,
+            transaction_hash: make_tx_hash(12),
         };
         let txn = conn.transaction().unwrap();
         let logger = Logger::new(test_name);
@@ -1475,6 +1489,56 @@ mod tests {
         assert_eq!(result.len(), 1);
     }
 
+    #[test]
+    fn paid_delinquencies_ignores_manual_bans_unless_pardoned() {
+        let payment_thresholds = PaymentThresholds {
+            maturity_threshold_sec: 0,
+            payment_grace_period_sec: 0,
+            permanent_debt_allowed_gwei: 0,
+            debt_threshold_gwei: 0,
+            threshold_interval_sec: 0,
+            unban_below_gwei: 50,
+        };
+        let mut paid_delinquent = make_receivable_account(1234, true);
+        paid_delinquent.balance_wei = gwei_to_wei(25);
+        let mut manually_banned = make_receivable_account(2345, true);
+        manually_banned.balance_wei = gwei_to_wei(25);
+        let mut manually_banned_then_pardoned = make_receivable_account(3456, true);
+        manually_banned_then_pardoned.balance_wei = gwei_to_wei(25);
+        let home_dir = ensure_node_home_directory_exists(
+            "receivable_dao",
+            "paid_delinquencies_ignores_manual_bans_unless_pardoned",
+        );
+        let db_initializer = DbInitializerReal::default();
+        let conn = db_initializer
+            .initialize(&home_dir, DbInitializationConfig::test_default())
+            .unwrap();
+        add_receivable_account(&conn, &paid_delinquent);
+        add_receivable_account(&conn, &manually_banned);
+        add_banned_account(&conn, &paid_delinquent);
+        add_banned_account(&conn, &manually_banned);
+        add_receivable_account(&conn, &manually_banned_then_pardoned);
+        add_banned_account(&conn, &manually_banned_then_pardoned);
+        conn.prepare("update banned set reason = 'manual' where wallet_address in (?, ?)")
+            .unwrap()
+            .execute(&[
+                &manually_banned.wallet,
+                &manually_banned_then_pardoned.wallet,
+            ])
+            .unwrap();
+        conn.prepare("update banned set pardoned = 1 where wallet_address = ?")
+            .unwrap()
+            .execute(&[&manually_banned_then_pardoned.wallet])
+            .unwrap();
+        let subject = ReceivableDaoReal::new(conn);
+
+        let result = subject.paid_delinquencies(&payment_thresholds);
+
+        assert_contains(&result, &paid_delinquent);
+        assert_contains(&result, &manually_banned_then_pardoned);
+        assert_eq!(result.len(), 2);
+    }
+
     #[test]
     fn custom_query_handles_empty_table_in_top_records_mode() {
         let main_test_setup = |_conn: &dyn ConnectionWrapper, _insert: InsertReceivableHelperFn| {};
@@ -275,6 +275,7 @@ pub fn remap_payable_accounts(accounts: Vec<PayableAccount>) -> Vec<UiPayableAcc
             pending_payable_hash_opt: account
                 .pending_payable_opt
                 .map(|full_id| full_id.hash.to_string()),
+            written_off: account.written_off,
         })
         .collect()
 }
@@ -328,10 +329,12 @@ pub fn sum_i128_values_from_table(
     conn: &dyn ConnectionWrapper,
     table: &str,
     param_name: &str,
+    where_clause: &str,
     value_completer: fn(usize, &Row) -> rusqlite::Result<i128>,
 ) -> i128 {
     let mut row_number = 0;
-    let select_stm = format!("select {param_name}_high_b, {param_name}_low_b from {table}");
+    let select_stm =
+        format!("select {param_name}_high_b, {param_name}_low_b from {table} {where_clause}");
     conn.prepare(&select_stm)
         .expect("select stm error")
         .query_map([], |row| {
@@ -520,12 +523,14 @@ mod tests {
                 balance_wei: 4_888_123_457,
                 last_paid_timestamp: SystemTime::now(),
                 pending_payable_opt: None,
+                written_off: false,
             },
             PayableAccount {
                 wallet: make_wallet("ac3665c"),
                 balance_wei: 565_122_333,
                 last_paid_timestamp: SystemTime::now(),
                 pending_payable_opt: None,
+                written_off: false,
             },
         ];
         remap_payable_accounts(accounts);
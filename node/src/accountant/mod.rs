@@ -5,19 +5,23 @@ pub mod db_big_integer;
 pub mod financials;
 pub mod payment_adjuster;
 pub mod scanners;
+pub mod threshold_simulation;
 
 #[cfg(test)]
 pub mod test_utils;
 
 use core::fmt::Debug;
-use masq_lib::constants::{SCAN_ERROR, WEIS_IN_GWEI};
+use masq_lib::constants::{BAN_LIST_ERROR, SCAN_ERROR, THRESHOLD_SIMULATION_ERROR, WEIS_IN_GWEI};
 use std::cell::{Ref, RefCell};
 
+use crate::accountant::db_access_objects::banned_dao::{BanDetails, BanReason, BannedDao};
+use crate::accountant::db_access_objects::ledger_dao::{LedgerDao, LedgerEntry, LedgerEntryKind};
 use crate::accountant::db_access_objects::payable_dao::{PayableDao, PayableDaoError};
 use crate::accountant::db_access_objects::pending_payable_dao::PendingPayableDao;
 use crate::accountant::db_access_objects::receivable_dao::{ReceivableDao, ReceivableDaoError};
 use crate::accountant::db_access_objects::utils::{
-    remap_payable_accounts, remap_receivable_accounts, CustomQuery, DaoFactoryReal,
+    from_time_t, remap_payable_accounts, remap_receivable_accounts, to_time_t, CustomQuery,
+    DaoFactoryReal,
 };
 use crate::accountant::financials::visibility_restricted_module::{
     check_query_is_within_tech_limits, financials_entry_check,
@@ -40,8 +44,11 @@ use crate::sub_lib::accountant::FinancialStatistics;
 use crate::sub_lib::accountant::ReportExitServiceProvidedMessage;
 use crate::sub_lib::accountant::ReportRoutingServiceProvidedMessage;
 use crate::sub_lib::accountant::ReportServicesConsumedMessage;
+use crate::sub_lib::accountant::WalletsSeenInGossip;
 use crate::sub_lib::accountant::{MessageIdGenerator, MessageIdGeneratorReal};
+use crate::sub_lib::accountant::PaymentThresholds;
 use crate::sub_lib::blockchain_bridge::OutboundPaymentsInstructions;
+use crate::sub_lib::earning_wallet_shares::EarningWalletShares;
 use crate::sub_lib::neighborhood::{ConfigChange, ConfigChangeMsg};
 use crate::sub_lib::peer_actors::{BindMessage, StartMessage};
 use crate::sub_lib::utils::{handle_ui_crash_request, NODE_MAILBOX_CAPACITY};
@@ -60,8 +67,10 @@ use masq_lib::logger::Logger;
 use masq_lib::messages::UiFinancialsResponse;
 use masq_lib::messages::{FromMessageBody, ToMessageBody, UiFinancialsRequest};
 use masq_lib::messages::{
-    QueryResults, ScanType, UiFinancialStatistics, UiPayableAccount, UiReceivableAccount,
-    UiScanRequest,
+    QueryResults, ScanType, UiBanListRequest, UiBanListResponse, UiBanRequest, UiBanResponse,
+    UiBannedWallet, UiFinancialStatistics, UiLedgerEntry, UiLedgerExportRequest,
+    UiLedgerExportResponse, UiPardonRequest, UiPardonResponse, UiPayableAccount,
+    UiReceivableAccount, UiScanRequest, UiThresholdSimulationRequest,
 };
 use masq_lib::ui_gateway::MessageTarget::ClientId;
 use masq_lib::ui_gateway::{MessageBody, MessagePath};
@@ -74,6 +83,7 @@ use std::fmt::Display;
 use std::ops::{Div, Mul};
 use std::path::Path;
 use std::rc::Rc;
+use std::str::FromStr;
 use std::time::SystemTime;
 use web3::types::H256;
 use crate::blockchain::blockchain_interface::blockchain_interface_web3::lower_level_interface_web3::TransactionReceiptResult;
@@ -85,10 +95,14 @@ pub struct Accountant {
     suppress_initial_scans: bool,
     consuming_wallet_opt: Option<Wallet>,
     earning_wallet: Wallet,
+    earning_wallet_shares: EarningWalletShares,
     payable_dao: Box<dyn PayableDao>,
     receivable_dao: Box<dyn ReceivableDao>,
     pending_payable_dao: Box<dyn PendingPayableDao>,
+    banned_dao: Box<dyn BannedDao>,
+    ledger_dao: Box<dyn LedgerDao>,
     crashable: bool,
+    payment_thresholds: Rc<PaymentThresholds>,
     scanners: Scanners,
     scan_schedulers: ScanSchedulers,
     financial_statistics: Rc<RefCell<FinancialStatistics>>,
@@ -351,6 +365,14 @@ impl Handler<ReportServicesConsumedMessage> for Accountant {
     }
 }
 
+impl Handler<WalletsSeenInGossip> for Accountant {
+    type Result = ();
+
+    fn handle(&mut self, msg: WalletsSeenInGossip, _ctx: &mut Self::Context) -> Self::Result {
+        self.handle_wallets_seen_in_gossip(msg);
+    }
+}
+
 pub trait SkeletonOptHolder {
     fn skeleton_opt(&self) -> Option<ResponseSkeleton>;
 }
@@ -405,6 +427,18 @@ impl Handler<NodeFromUiMessage> for Accountant {
         let client_id = msg.client_id;
         if let Ok((request, context_id)) = UiFinancialsRequest::fmb(msg.body.clone()) {
             self.handle_financials(&request, client_id, context_id)
+        } else if let Ok((request, context_id)) = UiLedgerExportRequest::fmb(msg.body.clone()) {
+            self.handle_ledger_export(&request, client_id, context_id)
+        } else if let Ok((_, context_id)) = UiBanListRequest::fmb(msg.body.clone()) {
+            self.handle_ban_list(client_id, context_id)
+        } else if let Ok((request, context_id)) = UiBanRequest::fmb(msg.body.clone()) {
+            self.handle_ban(&request, client_id, context_id)
+        } else if let Ok((request, context_id)) = UiPardonRequest::fmb(msg.body.clone()) {
+            self.handle_pardon(&request, client_id, context_id)
+        } else if let Ok((request, context_id)) =
+            UiThresholdSimulationRequest::fmb(msg.body.clone())
+        {
+            self.handle_threshold_simulation(&request, client_id, context_id)
         } else if let Ok((body, context_id)) = UiScanRequest::fmb(msg.body.clone()) {
             self.handle_externally_triggered_scan(
                 ctx,
@@ -422,27 +456,36 @@ impl Handler<NodeFromUiMessage> for Accountant {
 
 impl Accountant {
     pub fn new(config: BootstrapperConfig, dao_factories: DaoFactories) -> Accountant {
-        let payment_thresholds = config.payment_thresholds_opt.expectv("Payment thresholds");
+        let payment_thresholds =
+            Rc::new(config.payment_thresholds_opt.expectv("Payment thresholds"));
         let scan_intervals = config.scan_intervals_opt.expectv("Scan Intervals");
         let earning_wallet = config.earning_wallet.clone();
         let financial_statistics = Rc::new(RefCell::new(FinancialStatistics::default()));
+        let ledger_dao = dao_factories.ledger_dao_factory.make();
         let payable_dao = dao_factories.payable_dao_factory.make();
         let pending_payable_dao = dao_factories.pending_payable_dao_factory.make();
         let receivable_dao = dao_factories.receivable_dao_factory.make();
+        let banned_dao = dao_factories.banned_dao_factory.make();
         let scanners = Scanners::new(
             dao_factories,
-            Rc::new(payment_thresholds),
+            Rc::clone(&payment_thresholds),
             config.when_pending_too_long_sec,
             Rc::clone(&financial_statistics),
+            config.earning_wallet_shares.clone(),
+            config.payable_write_off_policy,
         );
 
         Accountant {
             suppress_initial_scans: config.suppress_initial_scans,
             consuming_wallet_opt: config.consuming_wallet_opt.clone(),
             earning_wallet,
+            earning_wallet_shares: config.earning_wallet_shares.clone(),
             payable_dao,
             receivable_dao,
             pending_payable_dao,
+            banned_dao,
+            ledger_dao,
+            payment_thresholds,
             scanners,
             crashable: config.crash_point == CrashPoint::Message,
             scan_schedulers: ScanSchedulers::new(scan_intervals),
@@ -467,6 +510,7 @@ impl Accountant {
             report_routing_service_provided: recipient!(addr, ReportRoutingServiceProvidedMessage),
             report_exit_service_provided: recipient!(addr, ReportExitServiceProvidedMessage),
             report_services_consumed: recipient!(addr, ReportServicesConsumedMessage),
+            report_wallets_seen_in_gossip: recipient!(addr, WalletsSeenInGossip),
             report_payable_payments_setup: recipient!(addr, BlockchainAgentWithContextMessage),
             report_inbound_payments: recipient!(addr, ReceivedPayments),
             init_pending_payable_fingerprints: recipient!(addr, PendingPayableFingerprintSeeds),
@@ -488,6 +532,7 @@ impl Accountant {
         timestamp: SystemTime,
         payload_size: usize,
         wallet: &Wallet,
+        kind: LedgerEntryKind,
     ) {
         let byte_charge = byte_rate as u128 * (payload_size as u128);
         let total_charge = service_rate as u128 + byte_charge;
@@ -495,7 +540,7 @@ impl Accountant {
             match self.receivable_dao
                 .as_ref()
                 .more_money_receivable(timestamp, wallet, total_charge) {
-                Ok(_) => (),
+                Ok(_) => self.record_in_ledger(timestamp, kind, wallet, total_charge),
                 Err(ReceivableDaoError::SignConversion(_)) => error!(
                     self.logger,
                     "Overflow error recording service provided for {}: service rate {}, byte rate {}, payload size {}. Skipping",
@@ -522,6 +567,7 @@ impl Accountant {
         timestamp: SystemTime,
         payload_size: usize,
         wallet: &Wallet,
+        kind: LedgerEntryKind,
     ) {
         let byte_charge = byte_rate as u128 * (payload_size as u128);
         let total_charge = service_rate as u128 + byte_charge;
@@ -529,7 +575,7 @@ impl Accountant {
             match self.payable_dao
                 .as_ref()
                 .more_money_payable(timestamp, wallet, total_charge) {
-                Ok(_) => (),
+                Ok(_) => self.record_in_ledger(timestamp, kind, wallet, total_charge),
                 Err(PayableDaoError::SignConversion(_)) => error!(
                     self.logger,
                     "Overflow error recording consumed services from {}: total charge {}, service rate {}, byte rate {}, payload size {}. Skipping",
@@ -550,10 +596,43 @@ impl Accountant {
         }
     }
 
+    fn record_in_ledger(
+        &self,
+        timestamp: SystemTime,
+        kind: LedgerEntryKind,
+        wallet: &Wallet,
+        amount_wei: u128,
+    ) {
+        let entry = LedgerEntry {
+            timestamp,
+            kind,
+            wallet: wallet.clone(),
+            amount_wei,
+            transaction_hash_opt: None,
+        };
+        self.ledger_dao
+            .accumulate_charges(&[entry])
+            .unwrap_or_else(|e| {
+                panic!(
+                    "Recording {} for {} in the ledger but has hit fatal database error: {:?}",
+                    kind.as_str(),
+                    wallet,
+                    e
+                )
+            })
+    }
+
     fn our_wallet(&self, wallet: &Wallet) -> bool {
         match &self.consuming_wallet_opt {
             Some(ref consuming) if consuming.address() == wallet.address() => true,
-            _ => wallet.address() == self.earning_wallet.address(),
+            _ => {
+                wallet.address() == self.earning_wallet.address()
+                    || self
+                        .earning_wallet_shares
+                        .shares()
+                        .iter()
+                        .any(|share| share.wallet.address() == wallet.address())
+            }
         }
     }
 
@@ -580,26 +659,57 @@ impl Accountant {
     }
 
     fn handle_config_change_msg(&mut self, msg: ConfigChangeMsg) {
-        if let ConfigChange::UpdateWallets(wallet_pair) = msg.change {
-            if self.earning_wallet != wallet_pair.earning_wallet {
-                info!(
-                    self.logger,
-                    "Earning Wallet has been updated: {}", wallet_pair.earning_wallet
-                );
-                self.earning_wallet = wallet_pair.earning_wallet;
+        match &msg.change {
+            ConfigChange::UpdateWallets(wallet_pair) => {
+                if self.earning_wallet != wallet_pair.earning_wallet {
+                    info!(
+                        self.logger,
+                        "Earning Wallet has been updated: {}", wallet_pair.earning_wallet
+                    );
+                    self.earning_wallet = wallet_pair.earning_wallet.clone();
+                }
+                if self.consuming_wallet_opt != Some(wallet_pair.consuming_wallet.clone()) {
+                    info!(
+                        self.logger,
+                        "Consuming Wallet has been updated: {}", wallet_pair.consuming_wallet
+                    );
+                    self.consuming_wallet_opt = Some(wallet_pair.consuming_wallet.clone());
+                }
             }
-            if self.consuming_wallet_opt != Some(wallet_pair.consuming_wallet.clone()) {
+            ConfigChange::UpdatePaymentThresholds(payment_thresholds) => {
+                self.update_payment_thresholds(*payment_thresholds)
+            }
+            ConfigChange::UpdateScanIntervals(scan_intervals) => {
                 info!(
                     self.logger,
-                    "Consuming Wallet has been updated: {}", wallet_pair.consuming_wallet
+                    "Scan intervals have been updated: {}", scan_intervals
                 );
-                self.consuming_wallet_opt = Some(wallet_pair.consuming_wallet);
+                // Scans already scheduled still run at their original times; the new intervals
+                // apply from there on
+                self.scan_schedulers = ScanSchedulers::new(*scan_intervals);
             }
-        } else {
-            trace!(self.logger, "Ignored irrelevant message: {:?}", msg);
+            _ => trace!(self.logger, "Ignored irrelevant message: {:?}", msg),
         }
     }
 
+    fn update_payment_thresholds(&mut self, payment_thresholds: PaymentThresholds) {
+        info!(
+            self.logger,
+            "Payment thresholds have been updated: {}", payment_thresholds
+        );
+        let payment_thresholds = Rc::new(payment_thresholds);
+        self.scanners
+            .payable
+            .update_payment_thresholds(Rc::clone(&payment_thresholds));
+        self.scanners
+            .pending_payable
+            .update_payment_thresholds(Rc::clone(&payment_thresholds));
+        self.scanners
+            .receivable
+            .update_payment_thresholds(Rc::clone(&payment_thresholds));
+        self.payment_thresholds = payment_thresholds;
+    }
+
     fn schedule_next_scan(&self, scan_type: ScanType, ctx: &mut Context<Self>) {
         self.scan_schedulers
             .schedulers
@@ -608,6 +718,29 @@ impl Accountant {
             .schedule(ctx)
     }
 
+    fn handle_wallets_seen_in_gossip(&self, msg: WalletsSeenInGossip) {
+        if let Err(e) = self
+            .payable_dao
+            .mark_seen_in_gossip(&msg.wallets, msg.timestamp)
+        {
+            error!(
+                self.logger,
+                "Failed to record {} wallets seen in Gossip: {:?}",
+                msg.wallets.len(),
+                e
+            )
+        }
+        if let Err(e) = self
+            .payable_dao
+            .record_earning_wallet_shares(&msg.earning_wallet_shares)
+        {
+            error!(
+                self.logger,
+                "Failed to record earning wallet shares seen in Gossip: {:?}", e
+            )
+        }
+    }
+
     fn handle_report_routing_service_provided_message(
         &mut self,
         msg: ReportRoutingServiceProvidedMessage,
@@ -622,6 +755,7 @@ impl Accountant {
             msg.timestamp,
             msg.payload_size,
             &msg.paying_wallet,
+            LedgerEntryKind::RoutingServiceProvided,
         );
     }
 
@@ -643,6 +777,7 @@ impl Accountant {
             msg.timestamp,
             msg.payload_size,
             &msg.paying_wallet,
+            LedgerEntryKind::ExitServiceProvided,
         );
     }
 
@@ -669,6 +804,7 @@ impl Accountant {
             msg.timestamp,
             msg.exit.payload_size,
             &msg.exit.earning_wallet,
+            LedgerEntryKind::ExitServiceConsumed,
         );
         msg.routing.iter().for_each(|routing_service| {
             debug!(
@@ -684,6 +820,7 @@ impl Accountant {
                 msg.timestamp,
                 msg.routing_payload_size,
                 &routing_service.earning_wallet,
+                LedgerEntryKind::RoutingServiceConsumed,
             );
         })
     }
@@ -723,6 +860,151 @@ impl Accountant {
             .expect("UiGateway is dead");
     }
 
+    fn handle_ledger_export(&self, msg: &UiLedgerExportRequest, client_id: u64, context_id: u64) {
+        let entries = self
+            .ledger_dao
+            .entries(
+                msg.start_timestamp_opt.map(from_time_t),
+                msg.end_timestamp_opt.map(from_time_t),
+            )
+            .into_iter()
+            .map(|entry| UiLedgerEntry {
+                timestamp: to_time_t(entry.timestamp),
+                kind: entry.kind.as_str().to_string(),
+                wallet: entry.wallet.to_string(),
+                amount_wei: entry.amount_wei.to_string(),
+                transaction_hash_opt: entry.transaction_hash_opt.map(|hash| format!("{:?}", hash)),
+            })
+            .collect();
+        self.send_ui_response(
+            client_id,
+            UiLedgerExportResponse { entries }.tmb(context_id),
+        )
+    }
+
+    fn handle_ban_list(&self, client_id: u64, context_id: u64) {
+        let bans = self
+            .banned_dao
+            .ban_list()
+            .into_iter()
+            .map(|account| UiBannedWallet {
+                wallet: account.wallet.to_string(),
+                banned_at_opt: account.banned_at_opt.map(to_time_t),
+                reason: account
+                    .reason_opt
+                    .map(|reason| reason.as_str())
+                    .unwrap_or("unknown")
+                    .to_string(),
+                balance_wei_opt: account.balance_wei_opt.map(|balance| balance.to_string()),
+                note_opt: account.note_opt,
+            })
+            .collect();
+        self.send_ui_response(client_id, UiBanListResponse { bans }.tmb(context_id))
+    }
+
+    fn handle_ban(&self, msg: &UiBanRequest, client_id: u64, context_id: u64) {
+        let body = match Wallet::from_str(&msg.wallet) {
+            Ok(wallet) => {
+                let already_banned = self
+                    .banned_dao
+                    .ban_list()
+                    .iter()
+                    .any(|account| account.wallet == wallet);
+                if already_banned {
+                    if let Some(note) = msg.note_opt.as_ref() {
+                        self.banned_dao.annotate(&wallet, note);
+                    }
+                } else {
+                    let balance_wei_opt = self
+                        .receivable_dao
+                        .account_status(&wallet)
+                        .map(|account| account.balance_wei);
+                    self.banned_dao.ban(
+                        &wallet,
+                        BanDetails {
+                            timestamp: SystemTime::now(),
+                            reason: BanReason::Manual,
+                            balance_wei_opt,
+                            note_opt: msg.note_opt.clone(),
+                        },
+                    );
+                    info!(self.logger, "Wallet {} banned manually", wallet);
+                }
+                UiBanResponse {
+                    newly_banned: !already_banned,
+                }
+                .tmb(context_id)
+            }
+            Err(_) => Self::ban_list_error_body(
+                "ban",
+                context_id,
+                format!("Invalid wallet address '{}'", msg.wallet),
+            ),
+        };
+        self.send_ui_response(client_id, body)
+    }
+
+    fn handle_pardon(&self, msg: &UiPardonRequest, client_id: u64, context_id: u64) {
+        let body = match Wallet::from_str(&msg.wallet) {
+            Ok(wallet) if self.banned_dao.pardon(&wallet) => {
+                info!(self.logger, "Wallet {} pardoned", wallet);
+                UiPardonResponse {}.tmb(context_id)
+            }
+            Ok(wallet) => Self::ban_list_error_body(
+                "pardon",
+                context_id,
+                format!("Wallet {} is not banned", wallet),
+            ),
+            Err(_) => Self::ban_list_error_body(
+                "pardon",
+                context_id,
+                format!("Invalid wallet address '{}'", msg.wallet),
+            ),
+        };
+        self.send_ui_response(client_id, body)
+    }
+
+    fn handle_threshold_simulation(
+        &self,
+        msg: &UiThresholdSimulationRequest,
+        client_id: u64,
+        context_id: u64,
+    ) {
+        let payment_thresholds = match msg.payment_thresholds_opt.as_ref() {
+            Some(ui_thresholds) => threshold_simulation::from_ui_payment_thresholds(ui_thresholds),
+            None => *self.payment_thresholds,
+        };
+        let body =
+            match threshold_simulation::simulate_thresholds(&payment_thresholds, &msg.history) {
+                Ok(response) => response.tmb(context_id),
+                Err(message) => MessageBody {
+                    opcode: "thresholdSimulation".to_string(),
+                    path: MessagePath::Conversation(context_id),
+                    payload: Err((THRESHOLD_SIMULATION_ERROR, message)),
+                },
+            };
+        self.send_ui_response(client_id, body)
+    }
+
+    fn ban_list_error_body(opcode: &str, context_id: u64, message: String) -> MessageBody {
+        MessageBody {
+            opcode: opcode.to_string(),
+            path: MessagePath::Conversation(context_id),
+            payload: Err((BAN_LIST_ERROR, message)),
+        }
+    }
+
+    fn send_ui_response(&self, client_id: u64, body: MessageBody) {
+        self.ui_message_sub_opt
+            .as_ref()
+            .expect("UiGateway not bound")
+            .try_send(NodeToUiMessage {
+                target: ClientId(client_id),
+                body,
+            })
+            .expect("UiGateway is dead");
+    }
+
     fn compute_financials(&self, msg: &UiFinancialsRequest, context_id: u64) -> MessageBody {
         if let Err(message_body) = financials_entry_check(msg, context_id) {
             return message_body;
@@ -1036,6 +1318,8 @@ pub fn wei_to_gwei<T: TryFrom<S>, S: Display + Copy + Div<Output = S> + From<u32
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::accountant::db_access_objects::banned_dao::BannedAccount;
+    use crate::accountant::db_access_objects::ledger_dao::LedgerDaoError;
     use crate::accountant::db_access_objects::payable_dao::{
         PayableAccount, PayableDaoError, PayableDaoFactory,
     };
@@ -1048,17 +1332,19 @@ mod tests {
     use crate::accountant::scanners::mid_scan_msg_handling::payable_scanner::test_utils::BlockchainAgentMock;
     use crate::accountant::scanners::test_utils::protect_payables_in_test;
     use crate::accountant::scanners::BeginScanError;
+    use crate::accountant::scanners::{PayableScanner, PendingPayableScanner, ReceivableScanner};
     use crate::accountant::test_utils::DaoWithDestination::{
         ForAccountantBody, ForPayableScanner, ForPendingPayableScanner, ForReceivableScanner,
     };
     use crate::accountant::test_utils::{
-        bc_from_earning_wallet, bc_from_wallets, make_payable_account, make_payables,
-        BannedDaoFactoryMock, ConfigDaoFactoryMock, MessageIdGeneratorMock, NullScanner,
+        bc_from_earning_wallet, bc_from_wallets, make_custom_payment_thresholds,
+        make_payable_account, make_payables, BannedDaoFactoryMock, ConfigDaoFactoryMock,
+        LedgerDaoFactoryMock, LedgerDaoMock, MessageIdGeneratorMock, NullScanner,
         PayableDaoFactoryMock, PayableDaoMock, PayableScannerBuilder, PaymentAdjusterMock,
         PendingPayableDaoFactoryMock, PendingPayableDaoMock, ReceivableDaoFactoryMock,
         ReceivableDaoMock, ScannerMock,
     };
-    use crate::accountant::test_utils::{AccountantBuilder, BannedDaoMock};
+    use crate::accountant::test_utils::{make_receivable_account, AccountantBuilder, BannedDaoMock};
     use crate::accountant::Accountant;
     use crate::blockchain::blockchain_bridge::BlockchainBridge;
     use crate::blockchain::blockchain_interface::blockchain_interface_web3::HashAndAmount;
@@ -1075,6 +1361,7 @@ mod tests {
         DEFAULT_EARNING_WALLET, DEFAULT_PAYMENT_THRESHOLDS,
     };
     use crate::sub_lib::blockchain_bridge::OutboundPaymentsInstructions;
+    use crate::sub_lib::earning_wallet_shares::WalletShare;
     use crate::sub_lib::neighborhood::ConfigChange;
     use crate::sub_lib::neighborhood::{Hops, WalletPair};
     use crate::test_utils::persistent_configuration_mock::PersistentConfigurationMock;
@@ -1100,8 +1387,9 @@ mod tests {
     };
     use masq_lib::messages::TopRecordsOrdering::{Age, Balance};
     use masq_lib::messages::{
-        CustomQueries, RangeQuery, ScanType, TopRecordsConfig, UiFinancialStatistics,
-        UiMessageError, UiPayableAccount, UiReceivableAccount, UiScanRequest, UiScanResponse,
+        CustomQueries, RangeQuery, ScanType, TopRecordsConfig, UiDebtSnapshot,
+        UiFinancialStatistics, UiMessageError, UiPayableAccount, UiPaymentThresholds,
+        UiReceivableAccount, UiScanRequest, UiScanResponse, UiThresholdSimulationResponse,
     };
     use masq_lib::test_utils::logging::init_test_logging;
     use masq_lib::test_utils::logging::TestLogHandler;
@@ -1144,6 +1432,7 @@ mod tests {
         let pending_payable_dao_factory_params_arc = Arc::new(Mutex::new(vec![]));
         let receivable_dao_factory_params_arc = Arc::new(Mutex::new(vec![]));
         let banned_dao_factory_params_arc = Arc::new(Mutex::new(vec![]));
+        let ledger_dao_factory_params_arc = Arc::new(Mutex::new(vec![]));
         let config_dao_factory_params_arc = Arc::new(Mutex::new(vec![]));
         let payable_dao_factory = PayableDaoFactoryMock::new()
             .make_params(&payable_dao_factory_params_arc)
@@ -1161,7 +1450,13 @@ mod tests {
             .make_result(ReceivableDaoMock::new()); // For Receivable Scanner
         let banned_dao_factory = BannedDaoFactoryMock::new()
             .make_params(&banned_dao_factory_params_arc)
+            .make_result(BannedDaoMock::new()) // For Accountant
             .make_result(BannedDaoMock::new()); // For Receivable Scanner
+        let ledger_dao_factory = LedgerDaoFactoryMock::new()
+            .make_params(&ledger_dao_factory_params_arc)
+            .make_result(LedgerDaoMock::new()) // For Accountant
+            .make_result(LedgerDaoMock::new()) // For PendingPayable Scanner
+            .make_result(LedgerDaoMock::new()); // For Receivable Scanner
         let config_dao_factory = ConfigDaoFactoryMock::new()
             .make_params(&config_dao_factory_params_arc)
             .make_result(ConfigDaoMock::new()); // For receivable scanner
@@ -1173,6 +1468,7 @@ mod tests {
                 pending_payable_dao_factory: Box::new(pending_payable_dao_factory),
                 receivable_dao_factory: Box::new(receivable_dao_factory),
                 banned_dao_factory: Box::new(banned_dao_factory),
+                ledger_dao_factory: Box::new(ledger_dao_factory),
                 config_dao_factory: Box::new(config_dao_factory),
             },
         );
@@ -1189,7 +1485,11 @@ mod tests {
             *receivable_dao_factory_params_arc.lock().unwrap(),
             vec![(), ()]
         );
-        assert_eq!(*banned_dao_factory_params_arc.lock().unwrap(), vec![()]);
+        assert_eq!(*banned_dao_factory_params_arc.lock().unwrap(), vec![(), ()]);
+        assert_eq!(
+            *ledger_dao_factory_params_arc.lock().unwrap(),
+            vec![(), (), ()]
+        );
         assert_eq!(*config_dao_factory_params_arc.lock().unwrap(), vec![()]);
     }
 
@@ -1213,8 +1513,17 @@ mod tests {
                 .make_result(ReceivableDaoMock::new()) // For Accountant
                 .make_result(ReceivableDaoMock::new()), // For Scanner
         );
-        let banned_dao_factory =
-            Box::new(BannedDaoFactoryMock::new().make_result(BannedDaoMock::new()));
+        let banned_dao_factory = Box::new(
+            BannedDaoFactoryMock::new()
+                .make_result(BannedDaoMock::new()) // For Accountant
+                .make_result(BannedDaoMock::new()), // For Receivable Scanner
+        );
+        let ledger_dao_factory = Box::new(
+            LedgerDaoFactoryMock::new()
+                .make_result(LedgerDaoMock::new()) // For Accountant
+                .make_result(LedgerDaoMock::new()) // For PendingPayable Scanner
+                .make_result(LedgerDaoMock::new()), // For Receivable Scanner
+        );
         let config_dao_factory =
             Box::new(ConfigDaoFactoryMock::new().make_result(ConfigDaoMock::new()));
 
@@ -1225,6 +1534,7 @@ mod tests {
                 pending_payable_dao_factory,
                 receivable_dao_factory,
                 banned_dao_factory,
+                ledger_dao_factory,
                 config_dao_factory,
             },
         );
@@ -1312,6 +1622,85 @@ mod tests {
                 );
             },
         );
+        let new_payment_thresholds = PaymentThresholds {
+            debt_threshold_gwei: 123_456,
+            maturity_threshold_sec: 1_111,
+            payment_grace_period_sec: 2_222,
+            permanent_debt_allowed_gwei: 3_333,
+            threshold_interval_sec: 4_444,
+            unban_below_gwei: 5_555,
+        };
+        assert_handling_of_config_change_msg(
+            ConfigChangeMsg {
+                change: ConfigChange::UpdatePaymentThresholds(new_payment_thresholds),
+            },
+            |subject: &Accountant| {
+                assert_eq!(*subject.payment_thresholds, new_payment_thresholds);
+                let payable_scanner = subject
+                    .scanners
+                    .payable
+                    .as_any()
+                    .downcast_ref::<PayableScanner>()
+                    .unwrap();
+                assert_eq!(
+                    *payable_scanner.common.payment_thresholds,
+                    new_payment_thresholds
+                );
+                let pending_payable_scanner = subject
+                    .scanners
+                    .pending_payable
+                    .as_any()
+                    .downcast_ref::<PendingPayableScanner>()
+                    .unwrap();
+                assert_eq!(
+                    *pending_payable_scanner.common.payment_thresholds,
+                    new_payment_thresholds
+                );
+                let receivable_scanner = subject
+                    .scanners
+                    .receivable
+                    .as_any()
+                    .downcast_ref::<ReceivableScanner>()
+                    .unwrap();
+                assert_eq!(
+                    *receivable_scanner.common.payment_thresholds,
+                    new_payment_thresholds
+                );
+                let _ = TestLogHandler::new().exists_log_containing(
+                    "INFO: ConfigChange: Payment thresholds have been updated: \
+                    123456|1111|2222|3333|4444|5555",
+                );
+            },
+        );
+        let new_scan_intervals = ScanIntervals {
+            pending_payable_scan_interval: Duration::from_secs(77),
+            payable_scan_interval: Duration::from_secs(88),
+            receivable_scan_interval: Duration::from_secs(99),
+        };
+        assert_handling_of_config_change_msg(
+            ConfigChangeMsg {
+                change: ConfigChange::UpdateScanIntervals(new_scan_intervals),
+            },
+            |subject: &Accountant| {
+                let interval_of = |scan_type: ScanType| {
+                    subject
+                        .scan_schedulers
+                        .schedulers
+                        .get(&scan_type)
+                        .unwrap()
+                        .interval()
+                };
+                assert_eq!(interval_of(ScanType::Payables), Duration::from_secs(88));
+                assert_eq!(
+                    interval_of(ScanType::PendingPayables),
+                    Duration::from_secs(77)
+                );
+                assert_eq!(interval_of(ScanType::Receivables), Duration::from_secs(99));
+                let _ = TestLogHandler::new().exists_log_containing(
+                    "INFO: ConfigChange: Scan intervals have been updated: 77|88|99",
+                );
+            },
+        );
     }
 
     fn assert_handling_of_config_change_msg<A>(msg: ConfigChangeMsg, assertions: A)
@@ -1367,7 +1756,7 @@ mod tests {
         assert_eq!(
             blockchain_bridge_recording.get_record::<RetrieveTransactions>(0),
             &RetrieveTransactions {
-                recipient: make_wallet("earning_wallet"),
+                recipients: vec![make_wallet("earning_wallet")],
                 response_skeleton_opt: Some(ResponseSkeleton {
                     client_id: 1234,
                     context_id: 4321,
@@ -1433,6 +1822,7 @@ mod tests {
                 (DEFAULT_PAYMENT_THRESHOLDS.maturity_threshold_sec + 1) as u64,
             )),
             pending_payable_opt: None,
+            written_off: false,
         };
         let payable_dao =
             PayableDaoMock::new().non_pending_payables_result(vec![payable_account.clone()]);
@@ -2001,7 +2391,7 @@ mod tests {
         assert_eq!(
             retrieve_transactions_msg,
             &RetrieveTransactions {
-                recipient: earning_wallet.clone(),
+                recipients: vec![earning_wallet.clone()],
                 response_skeleton_opt: None,
             }
         );
@@ -2020,11 +2410,13 @@ mod tests {
             block_number: 7,
             from: make_wallet("wallet0"),
             wei_amount: 456,
+            transaction_hash: make_tx_hash(7),
         };
         let expected_receivable_2 = BlockchainTransaction {
             block_number: 13,
             from: make_wallet("wallet1"),
             wei_amount: 10000,
+            transaction_hash: make_tx_hash(13),
         };
         let transaction_id = ArbitraryIdStamp::new();
         let txn_inner_builder = TransactionInnerWrapperMockBuilder::default()
@@ -2153,7 +2545,7 @@ mod tests {
             .begin_scan_params(&begin_scan_params_arc)
             .begin_scan_result(Err(BeginScanError::NothingToProcess))
             .begin_scan_result(Ok(RetrieveTransactions {
-                recipient: make_wallet("some_recipient"),
+                recipients: vec![make_wallet("some_recipient")],
                 response_skeleton_opt: None,
             }))
             .stop_the_system_after_last_msg();
@@ -2529,6 +2921,7 @@ mod tests {
                     ),
                 ),
                 pending_payable_opt: None,
+                written_off: false,
             },
             // above balance intersection, to the left of minimum time (outside buffer zone)
             PayableAccount {
@@ -2540,6 +2933,7 @@ mod tests {
                     ),
                 ),
                 pending_payable_opt: None,
+                written_off: false,
             },
             // above minimum balance, to the right of minimum time (not in buffer zone, below the curve)
             PayableAccount {
@@ -2551,6 +2945,7 @@ mod tests {
                     ),
                 ),
                 pending_payable_opt: None,
+                written_off: false,
             },
         ];
         let payable_dao = PayableDaoMock::new()
@@ -2608,6 +3003,7 @@ mod tests {
                     ),
                 ),
                 pending_payable_opt: None,
+                written_off: false,
             },
             // slightly above the curve (balance intersection), to the right of minimum time
             PayableAccount {
@@ -2619,6 +3015,7 @@ mod tests {
                     ),
                 ),
                 pending_payable_opt: None,
+                written_off: false,
             },
         ];
         let payable_dao =
@@ -2678,6 +3075,7 @@ mod tests {
             balance_wei: gwei_to_wei(DEFAULT_PAYMENT_THRESHOLDS.debt_threshold_gwei + 1),
             last_paid_timestamp: from_time_t(last_paid_timestamp),
             pending_payable_opt: None,
+            written_off: false,
         };
         let payable_dao = payable_dao
             .non_pending_payables_result(vec![payable_account.clone()])
@@ -2810,14 +3208,18 @@ mod tests {
         let now = SystemTime::now();
         let bootstrapper_config = bc_from_earning_wallet(make_wallet("hi"));
         let more_money_receivable_parameters_arc = Arc::new(Mutex::new(vec![]));
+        let accumulate_charges_params_arc = Arc::new(Mutex::new(vec![]));
         let payable_dao_mock = PayableDaoMock::new().non_pending_payables_result(vec![]);
         let receivable_dao_mock = ReceivableDaoMock::new()
             .more_money_receivable_parameters(&more_money_receivable_parameters_arc)
             .more_money_receivable_result(Ok(()));
+        let ledger_dao_mock =
+            LedgerDaoMock::new().accumulate_charges_params(&accumulate_charges_params_arc);
         let subject = AccountantBuilder::default()
             .bootstrapper_config(bootstrapper_config)
             .payable_daos(vec![ForAccountantBody(payable_dao_mock)])
             .receivable_daos(vec![ForAccountantBody(receivable_dao_mock)])
+            .ledger_daos(vec![ForAccountantBody(ledger_dao_mock)])
             .build();
         let system = System::new("report_routing_service_message_is_received");
         let subject_addr: Addr<Accountant> = subject.start();
@@ -2845,6 +3247,17 @@ mod tests {
             more_money_receivable_parameters[0],
             (now, make_wallet("booga"), (1 * 42) + (1234 * 24))
         );
+        let accumulate_charges_params = accumulate_charges_params_arc.lock().unwrap();
+        assert_eq!(
+            *accumulate_charges_params,
+            vec![vec![LedgerEntry {
+                timestamp: now,
+                kind: LedgerEntryKind::RoutingServiceProvided,
+                wallet: make_wallet("booga"),
+                amount_wei: (1 * 42) + (1234 * 24),
+                transaction_hash_opt: None,
+            }]]
+        );
         TestLogHandler::new().exists_log_containing(&format!(
             "DEBUG: Accountant: Charging routing of 1234 bytes to wallet {}",
             paying_wallet
@@ -3083,14 +3496,18 @@ mod tests {
         init_test_logging();
         let config = make_bc_with_defaults();
         let more_money_payable_params_arc = Arc::new(Mutex::new(vec![]));
+        let accumulate_charges_params_arc = Arc::new(Mutex::new(vec![]));
         let payable_dao_mock = PayableDaoMock::new()
             .more_money_payable_params(more_money_payable_params_arc.clone())
             .more_money_payable_result(Ok(()))
             .more_money_payable_result(Ok(()))
             .more_money_payable_result(Ok(()));
+        let ledger_dao_mock =
+            LedgerDaoMock::new().accumulate_charges_params(&accumulate_charges_params_arc);
         let mut subject = AccountantBuilder::default()
             .bootstrapper_config(config)
             .payable_daos(vec![ForAccountantBody(payable_dao_mock)])
+            .ledger_daos(vec![ForAccountantBody(ledger_dao_mock)])
             .build();
         subject.message_id_generator = Box::new(MessageIdGeneratorMock::default().id_result(123));
         let system = System::new("report_services_consumed_message_is_received");
@@ -3152,6 +3569,31 @@ mod tests {
                 )
             ]
         );
+        let accumulate_charges_params = accumulate_charges_params_arc.lock().unwrap();
+        assert_eq!(
+            accumulate_charges_params
+                .iter()
+                .flatten()
+                .map(|entry| (entry.kind, &entry.wallet, entry.amount_wei))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    LedgerEntryKind::ExitServiceConsumed,
+                    &earning_wallet_exit,
+                    (1 * 120) + (1200 * 30)
+                ),
+                (
+                    LedgerEntryKind::RoutingServiceConsumed,
+                    &earning_wallet_routing_1,
+                    (1 * 42) + (3456 * 24)
+                ),
+                (
+                    LedgerEntryKind::RoutingServiceConsumed,
+                    &earning_wallet_routing_2,
+                    (1 * 52) + (3456 * 33)
+                ),
+            ]
+        );
         let test_log_handler = TestLogHandler::new();
 
         test_log_handler.exists_log_containing(&format!(
@@ -3196,6 +3638,107 @@ mod tests {
         more_money_payable_parameters_arc
     }
 
+    #[test]
+    fn wallets_seen_in_gossip_are_marked_as_seen_in_the_payable_table() {
+        let mark_seen_in_gossip_params_arc = Arc::new(Mutex::new(vec![]));
+        let record_earning_wallet_shares_params_arc = Arc::new(Mutex::new(vec![]));
+        let payable_dao = PayableDaoMock::new()
+            .mark_seen_in_gossip_params(&mark_seen_in_gossip_params_arc)
+            .mark_seen_in_gossip_result(Ok(()))
+            .record_earning_wallet_shares_params(&record_earning_wallet_shares_params_arc)
+            .record_earning_wallet_shares_result(Ok(()));
+        let subject = AccountantBuilder::default()
+            .bootstrapper_config(make_bc_with_defaults())
+            .payable_daos(vec![ForAccountantBody(payable_dao)])
+            .build();
+        let system = System::new("wallets_seen_in_gossip_are_marked_as_seen_in_the_payable_table");
+        let subject_addr: Addr<Accountant> = subject.start();
+        let timestamp = SystemTime::now();
+        let wallets = vec![make_wallet("abc"), make_wallet("def")];
+        let earning_wallet_shares = vec![
+            (
+                make_wallet("abc"),
+                EarningWalletShares::new(vec![WalletShare {
+                    wallet: make_wallet("def"),
+                    percentage: 10,
+                }])
+                .unwrap(),
+            ),
+            (make_wallet("ghi"), EarningWalletShares::default()),
+        ];
+
+        subject_addr
+            .try_send(WalletsSeenInGossip {
+                timestamp,
+                wallets: wallets.clone(),
+                earning_wallet_shares: earning_wallet_shares.clone(),
+            })
+            .unwrap();
+
+        System::current().stop();
+        system.run();
+        let mark_seen_in_gossip_params = mark_seen_in_gossip_params_arc.lock().unwrap();
+        assert_eq!(*mark_seen_in_gossip_params, vec![(wallets, timestamp)]);
+        let record_earning_wallet_shares_params =
+            record_earning_wallet_shares_params_arc.lock().unwrap();
+        assert_eq!(
+            *record_earning_wallet_shares_params,
+            vec![earning_wallet_shares]
+        );
+    }
+
+    #[test]
+    fn failure_to_mark_wallets_seen_in_gossip_is_logged() {
+        init_test_logging();
+        let test_name = "failure_to_mark_wallets_seen_in_gossip_is_logged";
+        let payable_dao = PayableDaoMock::new().mark_seen_in_gossip_result(Err(
+            PayableDaoError::RusqliteError("database is locked".to_string()),
+        ));
+        let mut subject = AccountantBuilder::default()
+            .bootstrapper_config(make_bc_with_defaults())
+            .payable_daos(vec![ForAccountantBody(payable_dao)])
+            .build();
+        subject.logger = Logger::new(test_name);
+
+        subject.handle_wallets_seen_in_gossip(WalletsSeenInGossip {
+            timestamp: SystemTime::now(),
+            wallets: vec![make_wallet("abc"), make_wallet("def")],
+            earning_wallet_shares: vec![],
+        });
+
+        TestLogHandler::new().exists_log_containing(&format!(
+            "ERROR: {test_name}: Failed to record 2 wallets seen in Gossip: \
+            RusqliteError(\"database is locked\")"
+        ));
+    }
+
+    #[test]
+    fn failure_to_record_earning_wallet_shares_seen_in_gossip_is_logged() {
+        init_test_logging();
+        let test_name = "failure_to_record_earning_wallet_shares_seen_in_gossip_is_logged";
+        let payable_dao = PayableDaoMock::new()
+            .mark_seen_in_gossip_result(Ok(()))
+            .record_earning_wallet_shares_result(Err(PayableDaoError::RusqliteError(
+                "database is locked".to_string(),
+            )));
+        let mut subject = AccountantBuilder::default()
+            .bootstrapper_config(make_bc_with_defaults())
+            .payable_daos(vec![ForAccountantBody(payable_dao)])
+            .build();
+        subject.logger = Logger::new(test_name);
+
+        subject.handle_wallets_seen_in_gossip(WalletsSeenInGossip {
+            timestamp: SystemTime::now(),
+            wallets: vec![make_wallet("abc")],
+            earning_wallet_shares: vec![(make_wallet("abc"), EarningWalletShares::default())],
+        });
+
+        TestLogHandler::new().exists_log_containing(&format!(
+            "ERROR: {test_name}: Failed to record earning wallet shares seen in Gossip: \
+            RusqliteError(\"database is locked\")"
+        ));
+    }
+
     #[test]
     fn routing_service_consumed_is_reported_for_our_consuming_wallet() {
         init_test_logging();
@@ -3279,6 +3822,42 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn exit_service_consumed_is_reported_for_one_of_our_earning_wallet_shares() {
+        init_test_logging();
+        let share_wallet =
+            make_wallet("exit_service_consumed_is_reported_for_one_of_our_earning_wallet_shares");
+        let mut config = bc_from_earning_wallet(make_wallet("own earning wallet"));
+        config.earning_wallet_shares = EarningWalletShares::new(vec![WalletShare {
+            wallet: share_wallet.clone(),
+            percentage: 30,
+        }])
+        .unwrap();
+        let report_message = ReportServicesConsumedMessage {
+            timestamp: SystemTime::now(),
+            exit: ExitServiceConsumed {
+                earning_wallet: share_wallet.clone(),
+                payload_size: 1234,
+                service_rate: 42,
+                byte_rate: 24,
+            },
+            routing_payload_size: 3333,
+            routing: vec![],
+        };
+
+        let more_money_payable_params_arc =
+            assert_that_we_do_not_charge_our_own_wallet_for_consumed_services(
+                config,
+                report_message,
+            );
+
+        assert!(more_money_payable_params_arc.lock().unwrap().is_empty());
+        TestLogHandler::new().exists_log_containing(&format!(
+            "WARN: Accountant: Declining to record a payable against our wallet {} for service we provided",
+            share_wallet
+        ));
+    }
+
     #[test]
     fn exit_service_consumed_is_reported_for_our_consuming_wallet() {
         init_test_logging();
@@ -3357,7 +3936,14 @@ mod tests {
             .receivable_daos(vec![ForAccountantBody(receivable_dao)])
             .build();
 
-        let _ = subject.record_service_provided(i64::MAX as u64, 1, SystemTime::now(), 2, &wallet);
+        let _ = subject.record_service_provided(
+            i64::MAX as u64,
+            1,
+            SystemTime::now(),
+            2,
+            &wallet,
+            LedgerEntryKind::ExitServiceProvided,
+        );
     }
 
     #[test]
@@ -3370,7 +3956,14 @@ mod tests {
             .receivable_daos(vec![ForAccountantBody(receivable_dao)])
             .build();
 
-        subject.record_service_provided(i64::MAX as u64, 1, SystemTime::now(), 2, &wallet);
+        subject.record_service_provided(
+            i64::MAX as u64,
+            1,
+            SystemTime::now(),
+            2,
+            &wallet,
+            LedgerEntryKind::ExitServiceProvided,
+        );
 
         TestLogHandler::new().exists_log_containing(&format!(
             "ERROR: Accountant: Overflow error recording service provided for {}: service rate {}, byte rate 1, payload size 2. Skipping",
@@ -3379,6 +3972,32 @@ mod tests {
         ));
     }
 
+    #[test]
+    #[should_panic(
+        expected = "Recording exit_provided for 0x000000000000000000000000000000626f6f6761 in the \
+    ledger but has hit fatal database error: InsertionFailed(\"disk I/O error\")"
+    )]
+    fn record_service_provided_panics_on_ledger_errors() {
+        let wallet = make_wallet("booga");
+        let receivable_dao = ReceivableDaoMock::new().more_money_receivable_result(Ok(()));
+        let ledger_dao = LedgerDaoMock::new().accumulate_charges_result(Err(
+            LedgerDaoError::InsertionFailed("disk I/O error".to_string()),
+        ));
+        let subject = AccountantBuilder::default()
+            .receivable_daos(vec![ForAccountantBody(receivable_dao)])
+            .ledger_daos(vec![ForAccountantBody(ledger_dao)])
+            .build();
+
+        subject.record_service_provided(
+            i64::MAX as u64,
+            1,
+            SystemTime::now(),
+            2,
+            &wallet,
+            LedgerEntryKind::ExitServiceProvided,
+        );
+    }
+
     #[test]
     fn record_service_consumed_handles_overflow() {
         init_test_logging();
@@ -3390,7 +4009,14 @@ mod tests {
             .build();
         let service_rate = i64::MAX as u64;
 
-        subject.record_service_consumed(service_rate, 1, SystemTime::now(), 2, &wallet);
+        subject.record_service_consumed(
+            service_rate,
+            1,
+            SystemTime::now(),
+            2,
+            &wallet,
+            LedgerEntryKind::ExitServiceConsumed,
+        );
 
         TestLogHandler::new().exists_log_containing(&format!(
             "ERROR: Accountant: Overflow error recording consumed services from {}: total charge {}, service rate {}, byte rate 1, payload size 2. Skipping",
@@ -3415,7 +4041,14 @@ mod tests {
             .payable_daos(vec![ForAccountantBody(payable_dao)])
             .build();
 
-        let _ = subject.record_service_consumed(i64::MAX as u64, 1, SystemTime::now(), 2, &wallet);
+        let _ = subject.record_service_consumed(
+            i64::MAX as u64,
+            1,
+            SystemTime::now(),
+            2,
+            &wallet,
+            LedgerEntryKind::ExitServiceConsumed,
+        );
     }
 
     #[test]
@@ -3557,12 +4190,14 @@ mod tests {
             balance_wei: payable_account_balance_1,
             last_paid_timestamp: past_payable_timestamp_1,
             pending_payable_opt: None,
+            written_off: false,
         };
         let account_2 = PayableAccount {
             wallet: wallet_account_2.clone(),
             balance_wei: payable_account_balance_2,
             last_paid_timestamp: past_payable_timestamp_2,
             pending_payable_opt: None,
+            written_off: false,
         };
         let pending_payable_scan_interval = 1000; // should be slightly less than 1/5 of the time until shutting the system
         let payable_dao_for_payable_scanner = PayableDaoMock::new()
@@ -3874,10 +4509,12 @@ mod tests {
         let hash_and_amount_1 = HashAndAmount {
             hash: hash_1,
             amount: amount_1,
+            recipient_address: make_wallet("creditor_1").address(),
         };
         let hash_and_amount_2 = HashAndAmount {
             hash: hash_2,
             amount: amount_2,
+            recipient_address: make_wallet("creditor_2").address(),
         };
         let init_params = vec![hash_and_amount_1, hash_and_amount_2];
         let init_fingerprints_msg = PendingPayableFingerprintSeeds {
@@ -3919,6 +4556,7 @@ mod tests {
         let hash_and_amount = HashAndAmount {
             hash: transaction_hash,
             amount,
+            recipient_address: make_wallet("creditor").address(),
         };
         let subject = AccountantBuilder::default()
             .pending_payable_daos(vec![ForAccountantBody(pending_payable_dao)])
@@ -4147,6 +4785,399 @@ mod tests {
         )
     }
 
+    #[test]
+    fn ledger_export_request_produces_ledger_export_response() {
+        let entries_params_arc = Arc::new(Mutex::new(vec![]));
+        let ledger_dao = LedgerDaoMock::new()
+            .entries_params(&entries_params_arc)
+            .entries_result(vec![
+                LedgerEntry {
+                    timestamp: from_time_t(1_700_000_100),
+                    kind: LedgerEntryKind::ExitServiceProvided,
+                    wallet: make_wallet("customer"),
+                    amount_wei: 123_456,
+                    transaction_hash_opt: None,
+                },
+                LedgerEntry {
+                    timestamp: from_time_t(1_700_000_200),
+                    kind: LedgerEntryKind::PaymentReceived,
+                    wallet: make_wallet("customer"),
+                    amount_wei: u128::MAX >> 1,
+                    transaction_hash_opt: Some(make_tx_hash(0x1a2b)),
+                },
+            ]);
+        let system = System::new("test");
+        let subject = AccountantBuilder::default()
+            .bootstrapper_config(make_bc_with_defaults())
+            .ledger_daos(vec![ForAccountantBody(ledger_dao)])
+            .build();
+        let (ui_gateway, _, ui_gateway_recording_arc) = make_recorder();
+        let subject_addr = subject.start();
+        let peer_actors = peer_actors_builder().ui_gateway(ui_gateway).build();
+        subject_addr.try_send(BindMessage { peer_actors }).unwrap();
+        let ui_message = NodeFromUiMessage {
+            client_id: 1234,
+            body: UiLedgerExportRequest {
+                start_timestamp_opt: Some(1_700_000_000),
+                end_timestamp_opt: None,
+            }
+            .tmb(2222),
+        };
+
+        subject_addr.try_send(ui_message).unwrap();
+
+        System::current().stop();
+        system.run();
+        let entries_params = entries_params_arc.lock().unwrap();
+        assert_eq!(
+            *entries_params,
+            vec![(Some(from_time_t(1_700_000_000)), None)]
+        );
+        let ui_gateway_recording = ui_gateway_recording_arc.lock().unwrap();
+        let response = ui_gateway_recording.get_record::<NodeToUiMessage>(0);
+        assert_eq!(response.target, ClientId(1234));
+        let (body, context_id) = UiLedgerExportResponse::fmb(response.body.clone()).unwrap();
+        assert_eq!(context_id, 2222);
+        assert_eq!(
+            body,
+            UiLedgerExportResponse {
+                entries: vec![
+                    UiLedgerEntry {
+                        timestamp: 1_700_000_100,
+                        kind: "exit_provided".to_string(),
+                        wallet: make_wallet("customer").to_string(),
+                        amount_wei: "123456".to_string(),
+                        transaction_hash_opt: None,
+                    },
+                    UiLedgerEntry {
+                        timestamp: 1_700_000_200,
+                        kind: "payment_received".to_string(),
+                        wallet: make_wallet("customer").to_string(),
+                        amount_wei: "170141183460469231731687303715884105727".to_string(),
+                        transaction_hash_opt: Some(
+                            "0x0000000000000000000000000000000000000000000000000000000000001a2b"
+                                .to_string()
+                        ),
+                    },
+                ]
+            }
+        )
+    }
+
+    fn run_ui_conversation(subject: Accountant, body: MessageBody) -> NodeToUiMessage {
+        let system = System::new("test");
+        let (ui_gateway, _, ui_gateway_recording_arc) = make_recorder();
+        let subject_addr = subject.start();
+        let peer_actors = peer_actors_builder().ui_gateway(ui_gateway).build();
+        subject_addr.try_send(BindMessage { peer_actors }).unwrap();
+
+        subject_addr
+            .try_send(NodeFromUiMessage {
+                client_id: 1234,
+                body,
+            })
+            .unwrap();
+
+        System::current().stop();
+        system.run();
+        let ui_gateway_recording = ui_gateway_recording_arc.lock().unwrap();
+        ui_gateway_recording
+            .get_record::<NodeToUiMessage>(0)
+            .clone()
+    }
+
+    #[test]
+    fn ban_list_request_produces_ban_list_response() {
+        let banned_dao = BannedDaoMock::new().ban_list_result(vec![
+            BannedAccount {
+                wallet: make_wallet("delinquent"),
+                banned_at_opt: Some(from_time_t(1_700_000_000)),
+                reason_opt: Some(BanReason::Delinquency),
+                balance_wei_opt: Some(i128::MAX),
+                note_opt: None,
+            },
+            BannedAccount {
+                wallet: make_wallet("legacy"),
+                banned_at_opt: None,
+                reason_opt: None,
+                balance_wei_opt: None,
+                note_opt: None,
+            },
+            BannedAccount {
+                wallet: make_wallet("spammer"),
+                banned_at_opt: Some(from_time_t(1_700_000_500)),
+                reason_opt: Some(BanReason::Manual),
+                balance_wei_opt: Some(-1_000),
+                note_opt: Some("Floods us with requests".to_string()),
+            },
+        ]);
+        let subject = AccountantBuilder::default()
+            .bootstrapper_config(make_bc_with_defaults())
+            .banned_daos(vec![ForAccountantBody(banned_dao)])
+            .build();
+
+        let response = run_ui_conversation(subject, UiBanListRequest {}.tmb(2222));
+
+        assert_eq!(response.target, ClientId(1234));
+        let (body, context_id) = UiBanListResponse::fmb(response.body).unwrap();
+        assert_eq!(context_id, 2222);
+        assert_eq!(
+            body,
+            UiBanListResponse {
+                bans: vec![
+                    UiBannedWallet {
+                        wallet: make_wallet("delinquent").to_string(),
+                        banned_at_opt: Some(1_700_000_000),
+                        reason: "delinquency".to_string(),
+                        balance_wei_opt: Some(i128::MAX.to_string()),
+                        note_opt: None,
+                    },
+                    UiBannedWallet {
+                        wallet: make_wallet("legacy").to_string(),
+                        banned_at_opt: None,
+                        reason: "unknown".to_string(),
+                        balance_wei_opt: None,
+                        note_opt: None,
+                    },
+                    UiBannedWallet {
+                        wallet: make_wallet("spammer").to_string(),
+                        banned_at_opt: Some(1_700_000_500),
+                        reason: "manual".to_string(),
+                        balance_wei_opt: Some("-1000".to_string()),
+                        note_opt: Some("Floods us with requests".to_string()),
+                    },
+                ]
+            }
+        )
+    }
+
+    #[test]
+    fn ban_request_bans_a_wallet_manually() {
+        init_test_logging();
+        let test_name = "ban_request_bans_a_wallet_manually";
+        let wallet = make_wallet("unwelcome");
+        let mut receivable_account = make_receivable_account(1234, true);
+        receivable_account.wallet = wallet.clone();
+        let account_status_params_arc = Arc::new(Mutex::new(vec![]));
+        let ban_params_arc = Arc::new(Mutex::new(vec![]));
+        let receivable_dao = ReceivableDaoMock::new()
+            .account_status_params(&account_status_params_arc)
+            .account_status_result(Some(receivable_account.clone()));
+        let banned_dao = BannedDaoMock::new()
+            .ban_list_result(vec![])
+            .ban_parameters(&ban_params_arc);
+        let subject = AccountantBuilder::default()
+            .bootstrapper_config(make_bc_with_defaults())
+            .logger(Logger::new(test_name))
+            .receivable_daos(vec![ForAccountantBody(receivable_dao)])
+            .banned_daos(vec![ForAccountantBody(banned_dao)])
+            .build();
+        let request = UiBanRequest {
+            wallet: wallet.to_string(),
+            note_opt: Some("Misbehaves".to_string()),
+        };
+        let before = SystemTime::now();
+
+        let response = run_ui_conversation(subject, request.tmb(3333));
+
+        let after = SystemTime::now();
+        let (body, context_id) = UiBanResponse::fmb(response.body).unwrap();
+        assert_eq!(context_id, 3333);
+        assert_eq!(body, UiBanResponse { newly_banned: true });
+        let account_status_params = account_status_params_arc.lock().unwrap();
+        assert_eq!(*account_status_params, vec![wallet.clone()]);
+        let mut ban_params = ban_params_arc.lock().unwrap();
+        let (banned_wallet, details) = ban_params.remove(0);
+        assert_eq!(banned_wallet, wallet);
+        assert!(before <= details.timestamp && details.timestamp <= after);
+        assert_eq!(details.reason, BanReason::Manual);
+        assert_eq!(
+            details.balance_wei_opt,
+            Some(receivable_account.balance_wei)
+        );
+        assert_eq!(details.note_opt, Some("Misbehaves".to_string()));
+        assert!(ban_params.is_empty());
+        TestLogHandler::new().exists_log_containing(&format!(
+            "INFO: {}: Wallet {} banned manually",
+            test_name, wallet
+        ));
+    }
+
+    #[test]
+    fn ban_request_annotates_a_wallet_that_is_banned_already() {
+        let wallet = make_wallet("old_offender");
+        let ban_params_arc = Arc::new(Mutex::new(vec![]));
+        let annotate_params_arc = Arc::new(Mutex::new(vec![]));
+        let banned_dao = BannedDaoMock::new()
+            .ban_list_result(vec![BannedAccount {
+                wallet: wallet.clone(),
+                banned_at_opt: None,
+                reason_opt: None,
+                balance_wei_opt: None,
+                note_opt: None,
+            }])
+            .ban_parameters(&ban_params_arc)
+            .annotate_parameters(&annotate_params_arc)
+            .annotate_result(true);
+        let subject = AccountantBuilder::default()
+            .bootstrapper_config(make_bc_with_defaults())
+            .banned_daos(vec![ForAccountantBody(banned_dao)])
+            .build();
+        let request = UiBanRequest {
+            wallet: wallet.to_string(),
+            note_opt: Some("Still around".to_string()),
+        };
+
+        let response = run_ui_conversation(subject, request.tmb(4444));
+
+        let (body, context_id) = UiBanResponse::fmb(response.body).unwrap();
+        assert_eq!(context_id, 4444);
+        assert_eq!(
+            body,
+            UiBanResponse {
+                newly_banned: false
+            }
+        );
+        assert!(ban_params_arc.lock().unwrap().is_empty());
+        let annotate_params = annotate_params_arc.lock().unwrap();
+        assert_eq!(*annotate_params, vec![(wallet, "Still around".to_string())]);
+    }
+
+    #[test]
+    fn ban_request_with_an_invalid_wallet_produces_an_error() {
+        let subject = AccountantBuilder::default()
+            .bootstrapper_config(make_bc_with_defaults())
+            .build();
+        let request = UiBanRequest {
+            wallet: "booga".to_string(),
+            note_opt: None,
+        };
+
+        let response = run_ui_conversation(subject, request.tmb(5555));
+
+        assert_eq!(
+            response.body,
+            MessageBody {
+                opcode: "ban".to_string(),
+                path: Conversation(5555),
+                payload: Err((BAN_LIST_ERROR, "Invalid wallet address 'booga'".to_string()))
+            }
+        );
+    }
+
+    #[test]
+    fn pardon_request_pardons_a_banned_wallet() {
+        init_test_logging();
+        let test_name = "pardon_request_pardons_a_banned_wallet";
+        let wallet = make_wallet("friendly_node");
+        let pardon_params_arc = Arc::new(Mutex::new(vec![]));
+        let banned_dao = BannedDaoMock::new()
+            .pardon_parameters(&pardon_params_arc)
+            .pardon_result(true);
+        let subject = AccountantBuilder::default()
+            .bootstrapper_config(make_bc_with_defaults())
+            .logger(Logger::new(test_name))
+            .banned_daos(vec![ForAccountantBody(banned_dao)])
+            .build();
+        let request = UiPardonRequest {
+            wallet: wallet.to_string(),
+        };
+
+        let response = run_ui_conversation(subject, request.tmb(6666));
+
+        let (body, context_id) = UiPardonResponse::fmb(response.body).unwrap();
+        assert_eq!(context_id, 6666);
+        assert_eq!(body, UiPardonResponse {});
+        let pardon_params = pardon_params_arc.lock().unwrap();
+        assert_eq!(*pardon_params, vec![wallet.clone()]);
+        TestLogHandler::new()
+            .exists_log_containing(&format!("INFO: {}: Wallet {} pardoned", test_name, wallet));
+    }
+
+    #[test]
+    fn pardon_request_for_a_wallet_that_is_not_banned_produces_an_error() {
+        let wallet = make_wallet("never_banned");
+        let banned_dao = BannedDaoMock::new().pardon_result(false);
+        let subject = AccountantBuilder::default()
+            .bootstrapper_config(make_bc_with_defaults())
+            .banned_daos(vec![ForAccountantBody(banned_dao)])
+            .build();
+        let request = UiPardonRequest {
+            wallet: wallet.to_string(),
+        };
+
+        let response = run_ui_conversation(subject, request.tmb(7777));
+
+        assert_eq!(
+            response.body,
+            MessageBody {
+                opcode: "pardon".to_string(),
+                path: Conversation(7777),
+                payload: Err((BAN_LIST_ERROR, format!("Wallet {} is not banned", wallet)))
+            }
+        );
+    }
+
+    #[test]
+    fn threshold_simulation_request_without_thresholds_simulates_the_current_ones() {
+        let payment_thresholds = make_custom_payment_thresholds();
+        let mut config = make_bc_with_defaults();
+        config.payment_thresholds_opt = Some(payment_thresholds);
+        let subject = AccountantBuilder::default()
+            .bootstrapper_config(config)
+            .build();
+        let history = vec![UiDebtSnapshot {
+            age_sec: 100_000,
+            balance_gwei: 999_999_999,
+        }];
+        let request = UiThresholdSimulationRequest {
+            payment_thresholds_opt: None,
+            history: history.clone(),
+        };
+
+        let response = run_ui_conversation(subject, request.tmb(3333));
+
+        let (body, context_id) = UiThresholdSimulationResponse::fmb(response.body).unwrap();
+        assert_eq!(context_id, 3333);
+        assert_eq!(
+            body,
+            threshold_simulation::simulate_thresholds(&payment_thresholds, &history).unwrap()
+        );
+        assert_eq!(body.steps[0].payment_due, true);
+    }
+
+    #[test]
+    fn threshold_simulation_request_with_inconsistent_thresholds_produces_an_error() {
+        let subject = AccountantBuilder::default()
+            .bootstrapper_config(make_bc_with_defaults())
+            .build();
+        let request = UiThresholdSimulationRequest {
+            payment_thresholds_opt: Some(UiPaymentThresholds {
+                threshold_interval_sec: 0,
+                debt_threshold_gwei: 1_000,
+                payment_grace_period_sec: 100,
+                maturity_threshold_sec: 100,
+                permanent_debt_allowed_gwei: 10,
+                unban_below_gwei: 10,
+            }),
+            history: vec![],
+        };
+
+        let response = run_ui_conversation(subject, request.tmb(4444));
+
+        assert_eq!(
+            response.body,
+            MessageBody {
+                opcode: "thresholdSimulation".to_string(),
+                path: Conversation(4444),
+                payload: Err((
+                    THRESHOLD_SIMULATION_ERROR,
+                    "thresholdIntervalSec must be greater than 0".to_string()
+                ))
+            }
+        );
+    }
+
     #[test]
     fn compute_financials_processes_defaulted_request() {
         let payable_dao = PayableDaoMock::new().total_result(u64::MAX as u128 + 123456);
@@ -4215,6 +5246,7 @@ mod tests {
             balance_wei: 58_568_686_005,
             last_paid_timestamp: SystemTime::now().sub(Duration::from_secs(5000)),
             pending_payable_opt: None,
+            written_off: false,
         }];
         let payable_dao = PayableDaoMock::new()
             .custom_query_params(&payable_custom_query_params_arc)
@@ -4260,7 +5292,8 @@ mod tests {
                         wallet: make_wallet("abcd123").to_string(),
                         age_s: extracted_payable_ages[0],
                         balance_gwei: 58,
-                        pending_payable_hash_opt: None
+                        pending_payable_hash_opt: None,
+                        written_off: false,
                     },]),
                     receivable_opt: Some(vec![UiReceivableAccount {
                         wallet: make_wallet("efe4848").to_string(),
@@ -4364,6 +5397,7 @@ mod tests {
             balance_wei: 5_686_860_056,
             last_paid_timestamp: SystemTime::now().sub(Duration::from_secs(7580)),
             pending_payable_opt: None,
+            written_off: false,
         }];
         let payable_dao = PayableDaoMock::new()
             .custom_query_params(&payable_custom_query_params_arc)
@@ -4426,7 +5460,8 @@ mod tests {
                         wallet: make_wallet("abcd123").to_string(),
                         age_s: extracted_payable_ages[0],
                         balance_gwei: 5,
-                        pending_payable_hash_opt: None
+                        pending_payable_hash_opt: None,
+                        written_off: false,
                     },]),
                     receivable_opt: Some(vec![
                         UiReceivableAccount {
@@ -4623,6 +5658,7 @@ mod tests {
             balance_wei: 8_686_005,
             last_paid_timestamp: SystemTime::now().sub(Duration::from_secs(5000)),
             pending_payable_opt: None,
+            written_off: false,
         }];
         let payable_dao =
             PayableDaoMock::new().custom_query_result(Some(payable_accounts_retrieved));
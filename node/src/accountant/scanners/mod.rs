@@ -4,6 +4,7 @@ pub mod mid_scan_msg_handling;
 pub mod scanners_utils;
 pub mod test_utils;
 
+use crate::accountant::db_access_objects::ledger_dao::{LedgerDao, LedgerEntry, LedgerEntryKind};
 use crate::accountant::db_access_objects::payable_dao::{PayableAccount, PayableDao};
 use crate::accountant::db_access_objects::pending_payable_dao::{PendingPayable, PendingPayableDao};
 use crate::accountant::db_access_objects::receivable_dao::ReceivableDao;
@@ -13,26 +14,27 @@ use crate::accountant::scanners::scanners_utils::payable_scanner_utils::PayableT
 };
 use crate::accountant::scanners::scanners_utils::payable_scanner_utils::{
     debugging_summary_after_error_separation, err_msg_for_failure_with_expected_but_missing_fingerprints,
-    investigate_debt_extremes, mark_pending_payable_fatal_error, payables_debug_summary,
-    separate_errors, separate_rowids_and_hashes, PayableThresholdsGauge,
+    investigate_debt_extremes, mark_pending_payable_fatal_error, payable_threshold_exceeded,
+    payables_debug_summary, separate_errors, separate_rowids_and_hashes, PayableThresholdsGauge,
     PayableThresholdsGaugeReal, PayableTransactingErrorEnum, PendingPayableMetadata,
 };
 use crate::accountant::scanners::scanners_utils::pending_payable_scanner_utils::{handle_none_receipt, handle_status_with_failure, handle_status_with_success, PendingPayableScanReport};
 use crate::accountant::scanners::scanners_utils::receivable_scanner_utils::balance_and_age;
 use crate::accountant::PendingPayableId;
 use crate::accountant::{
-    comma_joined_stringifiable, gwei_to_wei, Accountant, ReceivedPayments,
+    comma_joined_stringifiable, Accountant, ReceivedPayments,
     ReportTransactionReceipts, RequestTransactionReceipts, ResponseSkeleton, ScanForPayables,
     ScanForPendingPayables, ScanForReceivables, SentPayables,
 };
-use crate::accountant::db_access_objects::banned_dao::BannedDao;
+use crate::accountant::db_access_objects::banned_dao::{BanDetails, BanReason, BannedDao};
 use crate::blockchain::blockchain_bridge::{BlockMarker, PendingPayableFingerprint, RetrieveTransactions};
 use crate::sub_lib::accountant::{
-    DaoFactories, FinancialStatistics, PaymentThresholds, ScanIntervals,
+    DaoFactories, FinancialStatistics, PayableWriteOffPolicy, PaymentThresholds, ScanIntervals,
 };
 use crate::sub_lib::blockchain_bridge::{
     OutboundPaymentsInstructions,
 };
+use crate::sub_lib::earning_wallet_shares::EarningWalletShares;
 use crate::sub_lib::utils::{NotifyLaterHandle, NotifyLaterHandleReal};
 use crate::sub_lib::wallet::Wallet;
 use actix::{Context, Message};
@@ -43,7 +45,7 @@ use masq_lib::messages::{ScanType, ToMessageBody, UiScanResponse};
 use masq_lib::ui_gateway::{MessageTarget, NodeToUiMessage};
 use masq_lib::utils::ExpectValue;
 use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::rc::Rc;
 use std::time::{Duration, SystemTime};
 use time::format_description::parse;
@@ -68,17 +70,21 @@ impl Scanners {
         payment_thresholds: Rc<PaymentThresholds>,
         when_pending_too_long_sec: u64,
         financial_statistics: Rc<RefCell<FinancialStatistics>>,
+        earning_wallet_shares: EarningWalletShares,
+        write_off_policy: PayableWriteOffPolicy,
     ) -> Self {
         let payable = Box::new(PayableScanner::new(
             dao_factories.payable_dao_factory.make(),
             dao_factories.pending_payable_dao_factory.make(),
             Rc::clone(&payment_thresholds),
             Box::new(PaymentAdjusterReal::new()),
+            write_off_policy,
         ));
 
         let pending_payable = Box::new(PendingPayableScanner::new(
             dao_factories.payable_dao_factory.make(),
             dao_factories.pending_payable_dao_factory.make(),
+            dao_factories.ledger_dao_factory.make(),
             Rc::clone(&payment_thresholds),
             when_pending_too_long_sec,
             Rc::clone(&financial_statistics),
@@ -89,9 +95,11 @@ impl Scanners {
         let receivable = Box::new(ReceivableScanner::new(
             dao_factories.receivable_dao_factory.make(),
             dao_factories.banned_dao_factory.make(),
+            dao_factories.ledger_dao_factory.make(),
             Box::new(persistent_configuration),
             Rc::clone(&payment_thresholds),
             financial_statistics,
+            earning_wallet_shares,
         ));
 
         Scanners {
@@ -118,6 +126,7 @@ where
     fn scan_started_at(&self) -> Option<SystemTime>;
     fn mark_as_started(&mut self, timestamp: SystemTime);
     fn mark_as_ended(&mut self, logger: &Logger);
+    fn update_payment_thresholds(&mut self, payment_thresholds: Rc<PaymentThresholds>);
 
     as_any_ref_in_trait!();
     as_any_mut_in_trait!();
@@ -185,6 +194,7 @@ pub struct PayableScanner {
     pub pending_payable_dao: Box<dyn PendingPayableDao>,
     pub payable_threshold_gauge: Box<dyn PayableThresholdsGauge>,
     pub payment_adjuster: Box<dyn PaymentAdjuster>,
+    pub write_off_policy: PayableWriteOffPolicy,
 }
 
 impl Scanner<QualifiedPayablesMessage, SentPayables> for PayableScanner {
@@ -200,6 +210,7 @@ impl Scanner<QualifiedPayablesMessage, SentPayables> for PayableScanner {
         }
         self.mark_as_started(timestamp);
         info!(logger, "Scanning for payables");
+        self.write_off_stale_payables(timestamp, logger);
         let all_non_pending_payables = self.payable_dao.non_pending_payables();
 
         debug!(
@@ -217,6 +228,8 @@ impl Scanner<QualifiedPayablesMessage, SentPayables> for PayableScanner {
                 Err(BeginScanError::NothingToProcess)
             }
             false => {
+                let qualified_payables =
+                    self.split_among_earning_wallet_shares(qualified_payables, timestamp, logger);
                 info!(
                     logger,
                     "Chose {} qualified debts to pay",
@@ -257,6 +270,10 @@ impl Scanner<QualifiedPayablesMessage, SentPayables> for PayableScanner {
 
     time_marking_methods!(Payables);
 
+    fn update_payment_thresholds(&mut self, payment_thresholds: Rc<PaymentThresholds>) {
+        self.common.payment_thresholds = payment_thresholds
+    }
+
     as_any_ref_in_trait_impl!();
 }
 
@@ -302,6 +319,7 @@ impl PayableScanner {
         pending_payable_dao: Box<dyn PendingPayableDao>,
         payment_thresholds: Rc<PaymentThresholds>,
         payment_adjuster: Box<dyn PaymentAdjuster>,
+        write_off_policy: PayableWriteOffPolicy,
     ) -> Self {
         Self {
             common: ScannerCommon::new(payment_thresholds),
@@ -309,6 +327,76 @@ impl PayableScanner {
             pending_payable_dao,
             payable_threshold_gauge: Box::new(PayableThresholdsGaugeReal::default()),
             payment_adjuster,
+            write_off_policy,
+        }
+    }
+
+    fn write_off_stale_payables(&self, now: SystemTime, logger: &Logger) {
+        if !self.write_off_policy.is_enabled() {
+            return;
+        }
+        match self
+            .payable_dao
+            .write_off_stale_payables(&self.write_off_policy, now)
+        {
+            Ok(wallets) if wallets.is_empty() => (),
+            Ok(wallets) => info!(
+                logger,
+                "Wrote off {} stale payables owed to: {}",
+                wallets.len(),
+                comma_joined_stringifiable(&wallets, |wallet| wallet.to_string())
+            ),
+            Err(e) => error!(logger, "Failed to write off stale payables: {:?}", e),
+        }
+    }
+
+    // Debts are charged in full to a creditor's primary earning wallet; only once they're about
+    // to be paid do the creditor's share wallets get their portions, as debts of their own
+    fn split_among_earning_wallet_shares(
+        &self,
+        qualified_payables: Vec<PayableAccount>,
+        now: SystemTime,
+        logger: &Logger,
+    ) -> Vec<PayableAccount> {
+        let wallets = qualified_payables
+            .iter()
+            .map(|account| account.wallet.clone())
+            .collect_vec();
+        match self
+            .payable_dao
+            .split_among_earning_wallet_shares(&wallets, now)
+        {
+            Ok(split_payables) if split_payables.is_empty() => qualified_payables,
+            Ok(split_payables) => {
+                info!(
+                    logger,
+                    "Split debts among earning wallet shares; now paying: {}",
+                    comma_joined_stringifiable(&split_payables, |account| account
+                        .wallet
+                        .to_string())
+                );
+                qualified_payables
+                    .into_iter()
+                    .filter(|account| {
+                        !split_payables
+                            .iter()
+                            .any(|split| split.wallet == account.wallet)
+                    })
+                    .chain(split_payables)
+                    .collect()
+            }
+            Err(e) => {
+                error!(
+                    logger,
+                    "Failed to split debts among earning wallet shares: {:?}", e
+                );
+                // Some portions may have moved already, so the balances can't be trusted anymore
+                self.payable_dao
+                    .non_pending_payables()
+                    .into_iter()
+                    .filter(|account| wallets.contains(&account.wallet))
+                    .collect()
+            }
         }
     }
 
@@ -350,28 +438,12 @@ impl PayableScanner {
             .expect("Internal error")
             .as_secs();
 
-        if self.payable_threshold_gauge.is_innocent_age(
-            debt_age,
-            self.common.payment_thresholds.maturity_threshold_sec,
-        ) {
-            return None;
-        }
-
-        if self.payable_threshold_gauge.is_innocent_balance(
+        payable_threshold_exceeded(
+            self.payable_threshold_gauge.as_ref(),
+            &self.common.payment_thresholds,
             payable.balance_wei,
-            gwei_to_wei(self.common.payment_thresholds.permanent_debt_allowed_gwei),
-        ) {
-            return None;
-        }
-
-        let threshold = self
-            .payable_threshold_gauge
-            .calculate_payout_threshold_in_gwei(&self.common.payment_thresholds, debt_age);
-        if payable.balance_wei > threshold {
-            Some(threshold)
-        } else {
-            None
-        }
+            debt_age,
+        )
     }
 
     fn separate_existent_and_nonexistent_fingerprints<'a>(
@@ -382,10 +454,18 @@ impl PayableScanner {
             .iter()
             .map(|pending_payable| pending_payable.hash)
             .collect::<Vec<H256>>();
-        let mut sent_payables_hashmap = sent_payables
-            .iter()
-            .map(|payable| (payable.hash, &payable.recipient_wallet))
-            .collect::<HashMap<H256, &Wallet>>();
+        // A multisend transaction covers several payables under one hash; their fingerprints
+        // are told apart by the recipients recorded with them
+        let mut sent_payables_hashmap = sent_payables.iter().fold(
+            HashMap::<H256, VecDeque<&Wallet>>::new(),
+            |mut hashmap, payable| {
+                hashmap
+                    .entry(payable.hash)
+                    .or_default()
+                    .push_back(&payable.recipient_wallet);
+                hashmap
+            },
+        );
 
         let transaction_hashes = self.pending_payable_dao.fingerprints_rowids(&hashes);
         let mut hashes_from_db = transaction_hashes
@@ -406,12 +486,46 @@ impl PayableScanner {
             )
         }
 
+        let shared_hash_rowids = transaction_hashes
+            .rowid_results
+            .iter()
+            .filter(|(_, hash)| {
+                sent_payables_hashmap
+                    .get(hash)
+                    .map_or(false, |wallets| wallets.len() > 1)
+            })
+            .map(|(rowid, _)| *rowid)
+            .collect_vec();
+        let recipients = if shared_hash_rowids.is_empty() {
+            HashMap::new()
+        } else {
+            self.pending_payable_dao
+                .fingerprints_recipients(&shared_hash_rowids)
+        };
+
         let pending_payables_with_rowid = transaction_hashes
             .rowid_results
             .into_iter()
             .map(|(rowid, hash)| {
-                let wallet = sent_payables_hashmap
-                    .remove(&hash)
+                let wallets = sent_payables_hashmap
+                    .get_mut(&hash)
+                    .expect("expect transaction hash, but it disappear");
+                let position = match recipients.get(&rowid) {
+                    Some(recipient) => wallets
+                        .iter()
+                        .position(|wallet| *wallet == recipient)
+                        .unwrap_or_else(|| {
+                            panic!(
+                                "Fingerprint {} of transaction {:?} names recipient {} who \
+                                wasn't paid by it",
+                                rowid, hash, recipient
+                            )
+                        }),
+                    // Fingerprints older than the recorded recipients never shared a hash
+                    None => 0,
+                };
+                let wallet = wallets
+                    .remove(position)
                     .expect("expect transaction hash, but it disappear");
                 PendingPayableMetadata::new(wallet, hash, Some(rowid))
             })
@@ -419,11 +533,12 @@ impl PayableScanner {
         let pending_payables_without_rowid = transaction_hashes
             .no_rowid_results
             .into_iter()
-            .map(|hash| {
-                let wallet = sent_payables_hashmap
+            .flat_map(|hash| {
+                sent_payables_hashmap
                     .remove(&hash)
-                    .expect("expect transaction hash, but it disappear");
-                PendingPayableMetadata::new(wallet, hash, None)
+                    .expect("expect transaction hash, but it disappear")
+                    .into_iter()
+                    .map(move |wallet| PendingPayableMetadata::new(wallet, hash, None))
             })
             .collect_vec();
 
@@ -560,6 +675,7 @@ pub struct PendingPayableScanner {
     pub common: ScannerCommon,
     pub payable_dao: Box<dyn PayableDao>,
     pub pending_payable_dao: Box<dyn PendingPayableDao>,
+    pub ledger_dao: Box<dyn LedgerDao>,
     pub when_pending_too_long_sec: u64,
     pub financial_statistics: Rc<RefCell<FinancialStatistics>>,
 }
@@ -626,6 +742,10 @@ impl Scanner<RequestTransactionReceipts, ReportTransactionReceipts> for PendingP
 
     time_marking_methods!(PendingPayables);
 
+    fn update_payment_thresholds(&mut self, payment_thresholds: Rc<PaymentThresholds>) {
+        self.common.payment_thresholds = payment_thresholds
+    }
+
     as_any_ref_in_trait_impl!();
 }
 
@@ -633,6 +753,7 @@ impl PendingPayableScanner {
     pub fn new(
         payable_dao: Box<dyn PayableDao>,
         pending_payable_dao: Box<dyn PendingPayableDao>,
+        ledger_dao: Box<dyn LedgerDao>,
         payment_thresholds: Rc<PaymentThresholds>,
         when_pending_too_long_sec: u64,
         financial_statistics: Rc<RefCell<FinancialStatistics>>,
@@ -641,6 +762,7 @@ impl PendingPayableScanner {
             common: ScannerCommon::new(payment_thresholds),
             payable_dao,
             pending_payable_dao,
+            ledger_dao,
             when_pending_too_long_sec,
             financial_statistics,
         }
@@ -735,10 +857,24 @@ impl PendingPayableScanner {
         logger: &Logger,
     ) {
         fn serialize_hashes(fingerprints: &[PendingPayableFingerprint]) -> String {
-            comma_joined_stringifiable(fingerprints, |fgp| format!("{:?}", fgp.hash))
+            // Payables settled by one multisend transaction share its hash
+            let hashes = fingerprints
+                .iter()
+                .map(|fgp| fgp.hash)
+                .unique()
+                .collect_vec();
+            comma_joined_stringifiable(&hashes, |hash| format!("{:?}", hash))
         }
 
         if !fingerprints.is_empty() {
+            // The ledger finds the creditors through the payable records, which the confirmation detaches
+            if let Err(e) = self.ledger_dao.record_confirmed_payments(&fingerprints) {
+                panic!(
+                    "Unable to record confirmed pending payables {} in the ledger due to {:?}",
+                    serialize_hashes(&fingerprints),
+                    e
+                )
+            }
             if let Err(e) = self.payable_dao.transactions_confirmed(&fingerprints) {
                 panic!(
                     "Unable to cast confirmed pending payables {} into adjustment in the corresponding payable \
@@ -754,10 +890,12 @@ impl PendingPayableScanner {
                     panic!("Unable to delete payable fingerprints {} of verified transactions due to {:?}",
                            serialize_hashes(&fingerprints), e)
                 } else {
+                    let hashes = serialize_hashes(&fingerprints);
                     info!(
-                        logger,
+                        logger;
+                        "transactions" => hashes;
                         "Transactions {} completed their confirmation process succeeding",
-                        serialize_hashes(&fingerprints)
+                        hashes
                     )
                 }
             }
@@ -787,8 +925,10 @@ pub struct ReceivableScanner {
     pub common: ScannerCommon,
     pub receivable_dao: Box<dyn ReceivableDao>,
     pub banned_dao: Box<dyn BannedDao>,
+    pub ledger_dao: Box<dyn LedgerDao>,
     pub persistent_configuration: Box<dyn PersistentConfiguration>,
     pub financial_statistics: Rc<RefCell<FinancialStatistics>>,
+    pub earning_wallet_shares: EarningWalletShares,
 }
 
 impl Scanner<RetrieveTransactions, ReceivedPayments> for ReceivableScanner {
@@ -803,11 +943,20 @@ impl Scanner<RetrieveTransactions, ReceivedPayments> for ReceivableScanner {
             return Err(BeginScanError::ScanAlreadyRunning(timestamp));
         }
         self.mark_as_started(timestamp);
-        info!(logger, "Scanning for receivables to {}", earning_wallet);
+        let mut recipients = vec![earning_wallet];
+        recipients.extend(self.earning_wallet_shares.wallets());
+        info!(
+            logger,
+            "Scanning for receivables to {}",
+            recipients
+                .iter()
+                .map(|wallet| wallet.to_string())
+                .join(", ")
+        );
         self.scan_for_delinquencies(timestamp, logger);
 
         Ok(RetrieveTransactions {
-            recipient: earning_wallet,
+            recipients,
             response_skeleton_opt,
         })
     }
@@ -824,6 +973,10 @@ impl Scanner<RetrieveTransactions, ReceivedPayments> for ReceivableScanner {
 
     time_marking_methods!(Receivables);
 
+    fn update_payment_thresholds(&mut self, payment_thresholds: Rc<PaymentThresholds>) {
+        self.common.payment_thresholds = payment_thresholds
+    }
+
     as_any_ref_in_trait_impl!();
     as_any_mut_in_trait_impl!();
 }
@@ -832,16 +985,20 @@ impl ReceivableScanner {
     pub fn new(
         receivable_dao: Box<dyn ReceivableDao>,
         banned_dao: Box<dyn BannedDao>,
+        ledger_dao: Box<dyn LedgerDao>,
         persistent_configuration: Box<dyn PersistentConfiguration>,
         payment_thresholds: Rc<PaymentThresholds>,
         financial_statistics: Rc<RefCell<FinancialStatistics>>,
+        earning_wallet_shares: EarningWalletShares,
     ) -> Self {
         Self {
             common: ScannerCommon::new(payment_thresholds),
             receivable_dao,
             banned_dao,
+            ledger_dao,
             persistent_configuration,
             financial_statistics,
+            earning_wallet_shares,
         }
     }
 
@@ -873,6 +1030,23 @@ impl ReceivableScanner {
                 received_payments_msg.timestamp,
                 &received_payments_msg.transactions,
             );
+            let ledger_entries = received_payments_msg
+                .transactions
+                .iter()
+                .map(|transaction| LedgerEntry {
+                    timestamp: received_payments_msg.timestamp,
+                    kind: LedgerEntryKind::PaymentReceived,
+                    wallet: transaction.from.clone(),
+                    amount_wei: transaction.wei_amount,
+                    transaction_hash_opt: Some(transaction.transaction_hash),
+                })
+                .collect::<Vec<_>>();
+            if let Err(e) = self
+                .ledger_dao
+                .record_by_guest_transaction(&mut txn, &ledger_entries)
+            {
+                panic!("Recording received payments in the ledger failed: {:?}", e)
+            }
             let new_start_block = received_payments_msg.new_start_block;
             if let BlockMarker::Value(start_block_number) = new_start_block {
                 match self
@@ -916,7 +1090,15 @@ impl ReceivableScanner {
             .new_delinquencies(timestamp, self.common.payment_thresholds.as_ref())
             .into_iter()
             .for_each(|account| {
-                self.banned_dao.ban(&account.wallet);
+                self.banned_dao.ban(
+                    &account.wallet,
+                    BanDetails {
+                        timestamp,
+                        reason: BanReason::Delinquency,
+                        balance_wei_opt: Some(account.balance_wei),
+                        note_opt: None,
+                    },
+                );
                 let (balance_str_wei, age) = balance_and_age(timestamp, &account);
                 info!(
                     logger,
@@ -1064,10 +1246,15 @@ impl<T: Default + 'static> ScanScheduler for PeriodicalScanScheduler<T> {
 }
 #[cfg(test)]
 mod tests {
+    use crate::accountant::db_access_objects::banned_dao::{BanDetails, BanReason};
+    use crate::accountant::db_access_objects::ledger_dao::{
+        LedgerDaoError, LedgerEntry, LedgerEntryKind,
+    };
     use crate::accountant::db_access_objects::payable_dao::{PayableAccount, PayableDaoError};
     use crate::accountant::db_access_objects::pending_payable_dao::{
         PendingPayable, PendingPayableDaoError, TransactionHashes,
     };
+    use crate::accountant::db_access_objects::receivable_dao::ReceivableAccount;
     use crate::accountant::db_access_objects::utils::{from_time_t, to_time_t};
     use crate::accountant::scanners::mid_scan_msg_handling::payable_scanner::msgs::QualifiedPayablesMessage;
     use crate::accountant::scanners::scanners_utils::payable_scanner_utils::PendingPayableMetadata;
@@ -1080,10 +1267,10 @@ mod tests {
     use crate::accountant::test_utils::{
         make_custom_payment_thresholds, make_payable_account, make_payables,
         make_pending_payable_fingerprint, make_receivable_account, BannedDaoFactoryMock,
-        BannedDaoMock, ConfigDaoFactoryMock, PayableDaoFactoryMock, PayableDaoMock,
-        PayableScannerBuilder, PayableThresholdsGaugeMock, PendingPayableDaoFactoryMock,
-        PendingPayableDaoMock, PendingPayableScannerBuilder, ReceivableDaoFactoryMock,
-        ReceivableDaoMock, ReceivableScannerBuilder,
+        BannedDaoMock, ConfigDaoFactoryMock, LedgerDaoFactoryMock, LedgerDaoMock,
+        PayableDaoFactoryMock, PayableDaoMock, PayableScannerBuilder, PayableThresholdsGaugeMock,
+        PendingPayableDaoFactoryMock, PendingPayableDaoMock, PendingPayableScannerBuilder,
+        ReceivableDaoFactoryMock, ReceivableDaoMock, ReceivableScannerBuilder,
     };
     use crate::accountant::{gwei_to_wei, PendingPayableId, ReceivedPayments, ReportTransactionReceipts, RequestTransactionReceipts, SentPayables, DEFAULT_PENDING_TOO_LONG_SEC};
     use crate::blockchain::blockchain_bridge::{BlockMarker, PendingPayableFingerprint, RetrieveTransactions};
@@ -1097,9 +1284,10 @@ mod tests {
     use crate::db_config::mocks::ConfigDaoMock;
     use crate::db_config::persistent_configuration::{PersistentConfigError};
     use crate::sub_lib::accountant::{
-        DaoFactories, FinancialStatistics, PaymentThresholds, ScanIntervals,
-        DEFAULT_PAYMENT_THRESHOLDS,
+        DaoFactories, FinancialStatistics, PayableWriteOffPolicy, PaymentThresholds,
+        ScanIntervals, DEFAULT_PAYMENT_THRESHOLDS,
     };
+    use crate::sub_lib::earning_wallet_shares::{EarningWalletShares, WalletShare};
     use crate::test_utils::persistent_configuration_mock::PersistentConfigurationMock;
     use crate::test_utils::unshared_test_utils::arbitrary_id_stamp::ArbitraryIdStamp;
     use crate::test_utils::{make_paying_wallet, make_wallet};
@@ -1111,7 +1299,7 @@ mod tests {
     use regex::Regex;
     use rusqlite::{ffi, ErrorCode};
     use std::cell::RefCell;
-    use std::collections::HashSet;
+    use std::collections::{HashMap, HashSet};
     use std::ops::Sub;
     use std::panic::{catch_unwind, AssertUnwindSafe};
     use std::rc::Rc;
@@ -1132,6 +1320,9 @@ mod tests {
         let receivable_dao = ReceivableDaoMock::new();
         let receivable_dao_factory = ReceivableDaoFactoryMock::new().make_result(receivable_dao);
         let banned_dao_factory = BannedDaoFactoryMock::new().make_result(BannedDaoMock::new());
+        let ledger_dao_factory = LedgerDaoFactoryMock::new()
+            .make_result(LedgerDaoMock::new())
+            .make_result(LedgerDaoMock::new());
         let set_params_arc = Arc::new(Mutex::new(vec![]));
         let config_dao_mock = ConfigDaoMock::new()
             .set_params(&set_params_arc)
@@ -1145,6 +1336,11 @@ mod tests {
         let payment_thresholds = make_custom_payment_thresholds();
         let payment_thresholds_rc = Rc::new(payment_thresholds);
         let initial_rc_count = Rc::strong_count(&payment_thresholds_rc);
+        let write_off_policy = PayableWriteOffPolicy {
+            unseen_days: 60,
+            dust_threshold_gwei: 1_000,
+            dust_days: 20,
+        };
 
         let mut scanners = Scanners::new(
             DaoFactories {
@@ -1152,11 +1348,14 @@ mod tests {
                 pending_payable_dao_factory: Box::new(pending_payable_dao_factory),
                 receivable_dao_factory: Box::new(receivable_dao_factory),
                 banned_dao_factory: Box::new(banned_dao_factory),
+                ledger_dao_factory: Box::new(ledger_dao_factory),
                 config_dao_factory: Box::new(config_dao_factory),
             },
             Rc::clone(&payment_thresholds_rc),
             when_pending_too_long_sec,
             Rc::new(RefCell::new(financial_statistics.clone())),
+            EarningWalletShares::default(),
+            write_off_policy,
         );
 
         let payable_scanner = scanners
@@ -1179,6 +1378,7 @@ mod tests {
             &payment_thresholds
         );
         assert_eq!(payable_scanner.common.initiated_at_opt.is_some(), false);
+        assert_eq!(payable_scanner.write_off_policy, write_off_policy);
         assert_eq!(
             pending_payable_scanner.when_pending_too_long_sec,
             when_pending_too_long_sec
@@ -1269,6 +1469,184 @@ mod tests {
         ])
     }
 
+    #[test]
+    fn payable_scanner_writes_off_stale_payables_before_picking_qualified_ones() {
+        init_test_logging();
+        let test_name = "payable_scanner_writes_off_stale_payables_before_picking_qualified_ones";
+        let write_off_stale_payables_params_arc = Arc::new(Mutex::new(vec![]));
+        let consuming_wallet = make_paying_wallet(b"consuming wallet");
+        let now = SystemTime::now();
+        let (qualified_payable_accounts, _, all_non_pending_payables) =
+            make_payables(now, &PaymentThresholds::default());
+        let write_off_policy = PayableWriteOffPolicy {
+            unseen_days: 30,
+            dust_threshold_gwei: 0,
+            dust_days: 0,
+        };
+        let payable_dao = PayableDaoMock::new()
+            .write_off_stale_payables_params(&write_off_stale_payables_params_arc)
+            .write_off_stale_payables_result(Ok(vec![make_wallet("gone"), make_wallet("away")]))
+            .non_pending_payables_result(all_non_pending_payables);
+        let mut subject = PayableScannerBuilder::new()
+            .payable_dao(payable_dao)
+            .write_off_policy(write_off_policy)
+            .build();
+
+        let result = subject.begin_scan(consuming_wallet, now, None, &Logger::new(test_name));
+
+        assert_eq!(
+            result.unwrap().protected_qualified_payables,
+            protect_payables_in_test(qualified_payable_accounts)
+        );
+        let write_off_stale_payables_params = write_off_stale_payables_params_arc.lock().unwrap();
+        assert_eq!(
+            *write_off_stale_payables_params,
+            vec![(write_off_policy, now)]
+        );
+        TestLogHandler::new().exists_log_containing(&format!(
+            "INFO: {test_name}: Wrote off 2 stale payables owed to: {}, {}",
+            make_wallet("gone"),
+            make_wallet("away")
+        ));
+    }
+
+    #[test]
+    fn payable_scanner_goes_on_with_the_scan_if_writing_off_stale_payables_fails() {
+        init_test_logging();
+        let test_name = "payable_scanner_goes_on_with_the_scan_if_writing_off_stale_payables_fails";
+        let consuming_wallet = make_paying_wallet(b"consuming wallet");
+        let now = SystemTime::now();
+        let (qualified_payable_accounts, _, all_non_pending_payables) =
+            make_payables(now, &PaymentThresholds::default());
+        let payable_dao = PayableDaoMock::new()
+            .write_off_stale_payables_result(Err(PayableDaoError::RusqliteError(
+                "blah".to_string(),
+            )))
+            .non_pending_payables_result(all_non_pending_payables);
+        let mut subject = PayableScannerBuilder::new()
+            .payable_dao(payable_dao)
+            .write_off_policy(PayableWriteOffPolicy {
+                unseen_days: 0,
+                dust_threshold_gwei: 1_000,
+                dust_days: 10,
+            })
+            .build();
+
+        let result = subject.begin_scan(consuming_wallet, now, None, &Logger::new(test_name));
+
+        assert_eq!(
+            result.unwrap().protected_qualified_payables,
+            protect_payables_in_test(qualified_payable_accounts)
+        );
+        TestLogHandler::new().exists_log_containing(&format!(
+            "ERROR: {test_name}: Failed to write off stale payables: RusqliteError(\"blah\")"
+        ));
+    }
+
+    #[test]
+    fn payable_scanner_splits_qualified_debts_among_earning_wallet_shares() {
+        init_test_logging();
+        let test_name = "payable_scanner_splits_qualified_debts_among_earning_wallet_shares";
+        let split_among_earning_wallet_shares_params_arc = Arc::new(Mutex::new(vec![]));
+        let consuming_wallet = make_paying_wallet(b"consuming wallet");
+        let now = SystemTime::now();
+        let (qualified_payable_accounts, _, all_non_pending_payables) =
+            make_payables(now, &PaymentThresholds::default());
+        let mut sharing_account = qualified_payable_accounts[0].clone();
+        sharing_account.balance_wei = sharing_account.balance_wei / 4 * 3;
+        let share_account = PayableAccount {
+            wallet: make_wallet("share"),
+            balance_wei: qualified_payable_accounts[0].balance_wei / 4,
+            last_paid_timestamp: now,
+            pending_payable_opt: None,
+            written_off: false,
+        };
+        let payable_dao = PayableDaoMock::new()
+            .non_pending_payables_result(all_non_pending_payables)
+            .split_among_earning_wallet_shares_params(&split_among_earning_wallet_shares_params_arc)
+            .split_among_earning_wallet_shares_result(Ok(vec![
+                sharing_account.clone(),
+                share_account.clone(),
+            ]));
+        let mut subject = PayableScannerBuilder::new()
+            .payable_dao(payable_dao)
+            .build();
+
+        let result = subject.begin_scan(consuming_wallet, now, None, &Logger::new(test_name));
+
+        assert_eq!(
+            result.unwrap().protected_qualified_payables,
+            protect_payables_in_test(vec![
+                qualified_payable_accounts[1].clone(),
+                sharing_account.clone(),
+                share_account.clone()
+            ])
+        );
+        let split_among_earning_wallet_shares_params =
+            split_among_earning_wallet_shares_params_arc.lock().unwrap();
+        assert_eq!(
+            *split_among_earning_wallet_shares_params,
+            vec![(
+                qualified_payable_accounts
+                    .iter()
+                    .map(|account| account.wallet.clone())
+                    .collect(),
+                now
+            )]
+        );
+        TestLogHandler::new().assert_logs_match_in_order(vec![
+            &format!(
+                "INFO: {test_name}: Split debts among earning wallet shares; now paying: {}, {}",
+                sharing_account.wallet, share_account.wallet
+            ),
+            &format!("INFO: {test_name}: Chose 3 qualified debts to pay"),
+        ]);
+    }
+
+    #[test]
+    fn payable_scanner_pays_freshly_read_balances_if_splitting_debts_fails() {
+        init_test_logging();
+        let test_name = "payable_scanner_pays_freshly_read_balances_if_splitting_debts_fails";
+        let consuming_wallet = make_paying_wallet(b"consuming wallet");
+        let now = SystemTime::now();
+        let (qualified_payable_accounts, unqualified_payable_accounts, all_non_pending_payables) =
+            make_payables(now, &PaymentThresholds::default());
+        let mut sharing_account = qualified_payable_accounts[0].clone();
+        sharing_account.balance_wei = sharing_account.balance_wei / 4 * 3;
+        let share_account = PayableAccount {
+            wallet: make_wallet("share"),
+            balance_wei: qualified_payable_accounts[0].balance_wei / 4,
+            last_paid_timestamp: now,
+            pending_payable_opt: None,
+            written_off: false,
+        };
+        let payable_dao = PayableDaoMock::new()
+            .non_pending_payables_result(all_non_pending_payables)
+            .split_among_earning_wallet_shares_result(Err(PayableDaoError::RusqliteError(
+                "blah".to_string(),
+            )))
+            .non_pending_payables_result(vec![
+                unqualified_payable_accounts[0].clone(),
+                sharing_account.clone(),
+                share_account,
+                qualified_payable_accounts[1].clone(),
+            ]);
+        let mut subject = PayableScannerBuilder::new()
+            .payable_dao(payable_dao)
+            .build();
+
+        let result = subject.begin_scan(consuming_wallet, now, None, &Logger::new(test_name));
+
+        assert_eq!(
+            result.unwrap().protected_qualified_payables,
+            protect_payables_in_test(vec![sharing_account, qualified_payable_accounts[1].clone()])
+        );
+        TestLogHandler::new().exists_log_containing(&format!(
+            "ERROR: {test_name}: Failed to split debts among earning wallet shares: \
+            RusqliteError(\"blah\")"
+        ));
+    }
+
     #[test]
     fn payable_scanner_throws_error_when_a_scan_is_already_running() {
         let consuming_wallet = make_paying_wallet(b"consuming wallet");
@@ -1424,6 +1802,97 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn payable_scanner_marks_all_payables_covered_by_one_multisend_transaction() {
+        let fingerprints_recipients_params_arc = Arc::new(Mutex::new(vec![]));
+        let mark_pending_payables_params_arc = Arc::new(Mutex::new(vec![]));
+        let multisend_hash = make_tx_hash(0x7a7a);
+        let wallet_1 = make_wallet("first creditor");
+        let wallet_2 = make_wallet("second creditor");
+        let wallet_3 = make_wallet("third creditor");
+        let pending_payable_dao = PendingPayableDaoMock::default()
+            .fingerprints_rowids_result(TransactionHashes {
+                rowid_results: vec![
+                    (44, multisend_hash),
+                    (45, multisend_hash),
+                    (46, multisend_hash),
+                ],
+                no_rowid_results: vec![],
+            })
+            .fingerprints_recipients_params(&fingerprints_recipients_params_arc)
+            .fingerprints_recipients_result(HashMap::from([
+                (44, wallet_3.clone()),
+                (45, wallet_1.clone()),
+                (46, wallet_2.clone()),
+            ]));
+        let payable_dao = PayableDaoMock::new()
+            .mark_pending_payables_rowids_params(&mark_pending_payables_params_arc)
+            .mark_pending_payables_rowids_result(Ok(()));
+        let mut subject = PayableScannerBuilder::new()
+            .payable_dao(payable_dao)
+            .pending_payable_dao(pending_payable_dao)
+            .build();
+        let sent_payable = SentPayables {
+            payment_procedure_result: Ok(vec![
+                ProcessedPayableFallible::Correct(PendingPayable::new(
+                    wallet_1.clone(),
+                    multisend_hash,
+                )),
+                ProcessedPayableFallible::Correct(PendingPayable::new(
+                    wallet_2.clone(),
+                    multisend_hash,
+                )),
+                ProcessedPayableFallible::Correct(PendingPayable::new(
+                    wallet_3.clone(),
+                    multisend_hash,
+                )),
+            ]),
+            response_skeleton_opt: None,
+        };
+        subject.mark_as_started(SystemTime::now());
+
+        let message_opt = subject.finish_scan(sent_payable, &Logger::new("test"));
+
+        assert_eq!(message_opt, None);
+        let fingerprints_recipients_params = fingerprints_recipients_params_arc.lock().unwrap();
+        assert_eq!(*fingerprints_recipients_params, vec![vec![44, 45, 46]]);
+        let mark_pending_payables_params = mark_pending_payables_params_arc.lock().unwrap();
+        assert_eq!(
+            *mark_pending_payables_params,
+            vec![vec![(wallet_3, 44), (wallet_1, 45), (wallet_2, 46)]]
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Fingerprint 45 of transaction 0x0000000000000000000000000000000000000000000000000000000000007a7a names recipient 0x000000000000000000000000737472616e676572 who wasn't paid by it"
+    )]
+    fn payable_scanner_panics_on_a_multisend_fingerprint_naming_an_unpaid_recipient() {
+        let multisend_hash = make_tx_hash(0x7a7a);
+        let wallet_1 = make_wallet("first creditor");
+        let wallet_2 = make_wallet("second creditor");
+        let pending_payable_dao = PendingPayableDaoMock::default()
+            .fingerprints_rowids_result(TransactionHashes {
+                rowid_results: vec![(44, multisend_hash), (45, multisend_hash)],
+                no_rowid_results: vec![],
+            })
+            .fingerprints_recipients_result(HashMap::from([
+                (44, wallet_1.clone()),
+                (45, make_wallet("stranger")),
+            ]));
+        let subject = PayableScannerBuilder::new()
+            .pending_payable_dao(pending_payable_dao)
+            .build();
+        let pending_payables = vec![
+            PendingPayable::new(wallet_1, multisend_hash),
+            PendingPayable::new(wallet_2, multisend_hash),
+        ];
+
+        let _ = subject.separate_existent_and_nonexistent_fingerprints(
+            &pending_payables.iter().collect::<Vec<&PendingPayable>>(),
+        );
+    }
+
     #[test]
     fn entries_must_be_kept_consistent_and_aligned() {
         let wallet_1 = make_wallet("abc");
@@ -2044,6 +2513,7 @@ mod tests {
             balance_wei: balance,
             last_paid_timestamp,
             pending_payable_opt: None,
+            written_off: false,
         };
         let custom_payment_thresholds = PaymentThresholds {
             maturity_threshold_sec: 1111,
@@ -2109,6 +2579,7 @@ mod tests {
             balance_wei: debt,
             last_paid_timestamp: from_time_t(time),
             pending_payable_opt: None,
+            written_off: false,
         }];
         let subject = PayableScannerBuilder::new()
             .payment_thresholds(payment_thresholds)
@@ -2138,6 +2609,7 @@ mod tests {
             balance_wei: debt,
             last_paid_timestamp: from_time_t(time),
             pending_payable_opt: None,
+            written_off: false,
         };
         let subject = PayableScannerBuilder::new()
             .payment_thresholds(payment_thresholds)
@@ -2172,6 +2644,7 @@ mod tests {
                 to_time_t(now) - payment_thresholds.maturity_threshold_sec as i64 + 1,
             ),
             pending_payable_opt: None,
+            written_off: false,
         }];
         let subject = PayableScannerBuilder::new()
             .payment_thresholds(payment_thresholds)
@@ -2668,15 +3141,19 @@ mod tests {
         init_test_logging();
         let transactions_confirmed_params_arc = Arc::new(Mutex::new(vec![]));
         let delete_fingerprints_params_arc = Arc::new(Mutex::new(vec![]));
+        let record_confirmed_payments_params_arc = Arc::new(Mutex::new(vec![]));
         let payable_dao = PayableDaoMock::default()
             .transactions_confirmed_params(&transactions_confirmed_params_arc)
             .transactions_confirmed_result(Ok(()));
         let pending_payable_dao = PendingPayableDaoMock::default()
             .delete_fingerprints_params(&delete_fingerprints_params_arc)
             .delete_fingerprints_result(Ok(()));
+        let ledger_dao = LedgerDaoMock::new()
+            .record_confirmed_payments_params(&record_confirmed_payments_params_arc);
         let mut subject = PendingPayableScannerBuilder::new()
             .payable_dao(payable_dao)
             .pending_payable_dao(pending_payable_dao)
+            .ledger_dao(ledger_dao)
             .build();
         let rowid_1 = 2;
         let rowid_2 = 5;
@@ -2705,6 +3182,14 @@ mod tests {
             &Logger::new("confirm_transactions_works"),
         );
 
+        let record_confirmed_payments_params = record_confirmed_payments_params_arc.lock().unwrap();
+        assert_eq!(
+            *record_confirmed_payments_params,
+            vec![vec![
+                pending_payable_fingerprint_1.clone(),
+                pending_payable_fingerprint_2.clone()
+            ]]
+        );
         let confirm_transactions_params = transactions_confirmed_params_arc.lock().unwrap();
         assert_eq!(
             *confirm_transactions_params,
@@ -2732,6 +3217,24 @@ mod tests {
         );
     }
 
+    #[test]
+    #[should_panic(
+        expected = "Unable to record confirmed pending payables 0x0000000000000000000000000000000000000000000\
+    000000000000000000315 in the ledger due to InsertionFailed(\"no such table: ledger\")"
+    )]
+    fn confirm_transactions_panics_on_recording_in_the_ledger() {
+        let ledger_dao = LedgerDaoMock::new().record_confirmed_payments_result(Err(
+            LedgerDaoError::InsertionFailed("no such table: ledger".to_string()),
+        ));
+        let mut subject = PendingPayableScannerBuilder::new()
+            .ledger_dao(ledger_dao)
+            .build();
+        let mut fingerprint = make_pending_payable_fingerprint();
+        fingerprint.hash = make_tx_hash(0x315);
+
+        subject.confirm_transactions(vec![fingerprint], &Logger::new("test"));
+    }
+
     #[test]
     #[should_panic(
         expected = "Unable to cast confirmed pending payables 0x0000000000000000000000000000000000000000000\
@@ -2922,7 +3425,7 @@ mod tests {
         assert_eq!(
             result,
             Ok(RetrieveTransactions {
-                recipient: earning_wallet.clone(),
+                recipients: vec![earning_wallet.clone()],
                 response_skeleton_opt: None
             })
         );
@@ -2931,6 +3434,56 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn receivable_scanner_looks_for_payments_to_earning_wallet_shares_too() {
+        init_test_logging();
+        let test_name = "receivable_scanner_looks_for_payments_to_earning_wallet_shares_too";
+        let receivable_dao = ReceivableDaoMock::new()
+            .new_delinquencies_result(vec![])
+            .paid_delinquencies_result(vec![]);
+        let earning_wallet = make_wallet("earning");
+        let share_wallet_1 = make_wallet("share 1");
+        let share_wallet_2 = make_wallet("share 2");
+        let shares = EarningWalletShares::new(vec![
+            WalletShare {
+                wallet: share_wallet_1.clone(),
+                percentage: 10,
+            },
+            WalletShare {
+                wallet: share_wallet_2.clone(),
+                percentage: 20,
+            },
+        ])
+        .unwrap();
+        let mut receivable_scanner = ReceivableScannerBuilder::new()
+            .receivable_dao(receivable_dao)
+            .earning_wallet_shares(shares)
+            .build();
+
+        let result = receivable_scanner.begin_scan(
+            earning_wallet.clone(),
+            SystemTime::now(),
+            None,
+            &Logger::new(test_name),
+        );
+
+        assert_eq!(
+            result,
+            Ok(RetrieveTransactions {
+                recipients: vec![
+                    earning_wallet.clone(),
+                    share_wallet_1.clone(),
+                    share_wallet_2.clone()
+                ],
+                response_skeleton_opt: None
+            })
+        );
+        TestLogHandler::new().exists_log_containing(&format!(
+            "INFO: {test_name}: Scanning for receivables to {earning_wallet}, {share_wallet_1}, \
+            {share_wallet_2}"
+        ));
+    }
+
     #[test]
     fn receivable_scanner_throws_error_in_case_scan_is_already_running() {
         let now = SystemTime::now();
@@ -2991,7 +3544,7 @@ mod tests {
         assert_eq!(
             result,
             Ok(RetrieveTransactions {
-                recipient: earning_wallet,
+                recipients: vec![earning_wallet],
                 response_skeleton_opt: None
             })
         );
@@ -3004,8 +3557,20 @@ mod tests {
         assert_eq!(paid_delinquencies_parameters.len(), 1);
         assert_eq!(payment_thresholds, paid_delinquencies_parameters[0]);
         let ban_parameters = ban_parameters_arc.lock().unwrap();
-        assert!(ban_parameters.contains(&newly_banned_1.wallet));
-        assert!(ban_parameters.contains(&newly_banned_2.wallet));
+        let expected_ban_details = |account: &ReceivableAccount| BanDetails {
+            timestamp: now,
+            reason: BanReason::Delinquency,
+            balance_wei_opt: Some(account.balance_wei),
+            note_opt: None,
+        };
+        assert!(ban_parameters.contains(&(
+            newly_banned_1.wallet.clone(),
+            expected_ban_details(&newly_banned_1)
+        )));
+        assert!(ban_parameters.contains(&(
+            newly_banned_2.wallet.clone(),
+            expected_ban_details(&newly_banned_2)
+        )));
         assert_eq!(2, ban_parameters.len());
         let unban_parameters = unban_parameters_arc.lock().unwrap();
         assert!(unban_parameters.contains(&newly_unbanned_1.wallet));
@@ -3099,6 +3664,7 @@ mod tests {
         let more_money_received_params_arc = Arc::new(Mutex::new(vec![]));
         let set_start_block_from_txn_params_arc = Arc::new(Mutex::new(vec![]));
         let commit_params_arc = Arc::new(Mutex::new(vec![]));
+        let record_by_guest_transaction_params_arc = Arc::new(Mutex::new(vec![]));
         let transaction_id = ArbitraryIdStamp::new();
         let txn_inner_builder = TransactionInnerWrapperMockBuilder::default()
             .commit_params(&commit_params_arc)
@@ -3112,8 +3678,11 @@ mod tests {
         let receivable_dao = ReceivableDaoMock::new()
             .more_money_received_params(&more_money_received_params_arc)
             .more_money_received_result(transaction);
+        let ledger_dao = LedgerDaoMock::new()
+            .record_by_guest_transaction_params(&record_by_guest_transaction_params_arc);
         let mut subject = ReceivableScannerBuilder::new()
             .receivable_dao(receivable_dao)
+            .ledger_dao(ledger_dao)
             .persistent_configuration(persistent_config)
             .build();
         let mut financial_statistics = subject.financial_statistics.borrow().clone();
@@ -3124,11 +3693,13 @@ mod tests {
                 block_number: 4578910,
                 from: make_wallet("wallet_1"),
                 wei_amount: 45_780,
+                transaction_hash: make_tx_hash(1),
             },
             BlockchainTransaction {
                 block_number: 4569898,
                 from: make_wallet("wallet_2"),
                 wei_amount: 3_333_345,
+                transaction_hash: make_tx_hash(2),
             },
         ];
         let msg = ReceivedPayments {
@@ -3150,6 +3721,27 @@ mod tests {
         assert_eq!(total_paid_receivable, 2_222_123_123 + 45_780 + 3_333_345);
         let more_money_received_params = more_money_received_params_arc.lock().unwrap();
         assert_eq!(*more_money_received_params, vec![(now, receivables)]);
+        let record_by_guest_transaction_params =
+            record_by_guest_transaction_params_arc.lock().unwrap();
+        assert_eq!(
+            *record_by_guest_transaction_params,
+            vec![vec![
+                LedgerEntry {
+                    timestamp: now,
+                    kind: LedgerEntryKind::PaymentReceived,
+                    wallet: make_wallet("wallet_1"),
+                    amount_wei: 45_780,
+                    transaction_hash_opt: Some(make_tx_hash(1)),
+                },
+                LedgerEntry {
+                    timestamp: now,
+                    kind: LedgerEntryKind::PaymentReceived,
+                    wallet: make_wallet("wallet_2"),
+                    amount_wei: 3_333_345,
+                    transaction_hash_opt: Some(make_tx_hash(2)),
+                },
+            ]]
+        );
         let set_by_guest_transaction_params = set_start_block_from_txn_params_arc.lock().unwrap();
         assert_eq!(
             *set_by_guest_transaction_params,
@@ -3179,6 +3771,7 @@ mod tests {
             block_number: 4578910,
             from: make_wallet("wallet_1"),
             wei_amount: 45_780,
+            transaction_hash: make_tx_hash(3),
         }];
         let msg = ReceivedPayments {
             timestamp: SystemTime::now(),
@@ -3214,6 +3807,7 @@ mod tests {
             block_number: 4578910,
             from: make_wallet("abc"),
             wei_amount: 45_780,
+            transaction_hash: make_tx_hash(4),
         }];
         let msg = ReceivedPayments {
             timestamp: now,
@@ -3258,6 +3852,7 @@ mod tests {
             block_number: 4578910,
             from: make_wallet("abc"),
             wei_amount: 45_780,
+            transaction_hash: make_tx_hash(5),
         }];
         let msg = ReceivedPayments {
             timestamp: now,
@@ -6,7 +6,7 @@ pub mod payable_scanner_utils {
     use crate::accountant::scanners::scanners_utils::payable_scanner_utils::PayableTransactingErrorEnum::{
         LocallyCausedError, RemotelyCausedErrors,
     };
-    use crate::accountant::{comma_joined_stringifiable, SentPayables};
+    use crate::accountant::{comma_joined_stringifiable, gwei_to_wei, SentPayables};
     use crate::sub_lib::accountant::PaymentThresholds;
     use crate::sub_lib::wallet::Wallet;
     use itertools::Itertools;
@@ -274,6 +274,32 @@ pub mod payable_scanner_utils {
         ids_of_payments.into_iter().unzip()
     }
 
+    // Returns the threshold the balance exceeded if a debt of this size and age should be paid
+    pub fn payable_threshold_exceeded(
+        gauge: &dyn PayableThresholdsGauge,
+        payment_thresholds: &PaymentThresholds,
+        balance_wei: u128,
+        debt_age: u64,
+    ) -> Option<u128> {
+        if gauge.is_innocent_age(debt_age, payment_thresholds.maturity_threshold_sec) {
+            return None;
+        }
+
+        if gauge.is_innocent_balance(
+            balance_wei,
+            gwei_to_wei(payment_thresholds.permanent_debt_allowed_gwei),
+        ) {
+            return None;
+        }
+
+        let threshold = gauge.calculate_payout_threshold_in_gwei(payment_thresholds, debt_age);
+        if balance_wei > threshold {
+            Some(threshold)
+        } else {
+            None
+        }
+    }
+
     pub trait PayableThresholdsGauge {
         fn is_innocent_age(&self, age: u64, limit: u64) -> bool;
         fn is_innocent_balance(&self, balance: u128, limit: u128) -> bool;
@@ -425,7 +451,9 @@ pub mod pending_payable_scanner_utils {
 
 pub mod receivable_scanner_utils {
     use crate::accountant::db_access_objects::receivable_dao::ReceivableAccount;
-    use crate::accountant::wei_to_gwei;
+    use crate::accountant::db_access_objects::utils::ThresholdUtils;
+    use crate::accountant::{gwei_to_wei, wei_to_gwei};
+    use crate::sub_lib::accountant::PaymentThresholds;
     use std::time::{Duration, SystemTime};
     use thousands::Separable;
 
@@ -436,6 +464,26 @@ pub mod receivable_scanner_utils {
             .unwrap_or_else(|_| Duration::new(0, 0));
         (balance, age)
     }
+
+    // The balance a debt of this age must exceed to be delinquent, or None while the debt is too
+    // young to be delinquent at any balance. This is the condition ReceivableDaoReal applies in
+    // SQL when it looks for new delinquencies.
+    pub fn delinquency_threshold_opt(
+        payment_thresholds: &PaymentThresholds,
+        debt_age: u64,
+    ) -> Option<u128> {
+        let innocent_age =
+            payment_thresholds.maturity_threshold_sec + payment_thresholds.payment_grace_period_sec;
+        if debt_age <= innocent_age {
+            return None;
+        }
+        let sloped_threshold = gwei_to_wei::<i128, u64>(payment_thresholds.debt_threshold_gwei)
+            + ThresholdUtils::slope(payment_thresholds)
+                .saturating_mul((debt_age - innocent_age) as i128);
+        let permanent_debt_allowed =
+            gwei_to_wei::<i128, u64>(payment_thresholds.permanent_debt_allowed_gwei);
+        Some(sloped_threshold.max(permanent_debt_allowed) as u128)
+    }
 }
 
 #[cfg(test)]
@@ -476,6 +524,7 @@ mod tests {
                 balance_wei: same_amount_significance,
                 last_paid_timestamp: from_time_t(now_t - 5000),
                 pending_payable_opt: None,
+                written_off: false,
             },
             //this debt is more significant because beside being high in amount it's also older, so should be prioritized and picked
             PayableAccount {
@@ -483,6 +532,7 @@ mod tests {
                 balance_wei: same_amount_significance,
                 last_paid_timestamp: from_time_t(now_t - 10000),
                 pending_payable_opt: None,
+                written_off: false,
             },
             //similarly these two wallets have debts equally old but the second has a bigger balance and should be chosen
             PayableAccount {
@@ -490,12 +540,14 @@ mod tests {
                 balance_wei: 100,
                 last_paid_timestamp: same_age_significance,
                 pending_payable_opt: None,
+                written_off: false,
             },
             PayableAccount {
                 wallet: make_wallet("wallet2"),
                 balance_wei: 330,
                 last_paid_timestamp: same_age_significance,
                 pending_payable_opt: None,
+                written_off: false,
             },
         ];
 
@@ -635,6 +687,7 @@ mod tests {
                         ),
                     ),
                     pending_payable_opt: None,
+                    written_off: false,
                 },
                 10_000_000_001_152_000_u128,
             ),
@@ -648,6 +701,7 @@ mod tests {
                         ),
                     ),
                     pending_payable_opt: None,
+                    written_off: false,
                 },
                 999_978_993_055_555_580,
             ),
@@ -2,7 +2,12 @@
 
 #![cfg(test)]
 
-use crate::accountant::db_access_objects::banned_dao::{BannedDao, BannedDaoFactory};
+use crate::accountant::db_access_objects::banned_dao::{
+    BanDetails, BannedAccount, BannedDao, BannedDaoFactory,
+};
+use crate::accountant::db_access_objects::ledger_dao::{
+    LedgerDao, LedgerDaoError, LedgerDaoFactory, LedgerEntry,
+};
 use crate::accountant::db_access_objects::payable_dao::{
     PayableAccount, PayableDao, PayableDaoError, PayableDaoFactory,
 };
@@ -37,8 +42,9 @@ use crate::database::rusqlite_wrappers::TransactionSafeWrapper;
 use crate::db_config::config_dao::{ConfigDao, ConfigDaoFactory};
 use crate::db_config::mocks::ConfigDaoMock;
 use crate::sub_lib::accountant::{DaoFactories, FinancialStatistics};
-use crate::sub_lib::accountant::{MessageIdGenerator, PaymentThresholds};
+use crate::sub_lib::accountant::{MessageIdGenerator, PayableWriteOffPolicy, PaymentThresholds};
 use crate::sub_lib::blockchain_bridge::OutboundPaymentsInstructions;
+use crate::sub_lib::earning_wallet_shares::EarningWalletShares;
 use crate::sub_lib::utils::NotifyLaterHandle;
 use crate::sub_lib::wallet::Wallet;
 use crate::test_utils::make_wallet;
@@ -53,6 +59,7 @@ use masq_lib::ui_gateway::NodeToUiMessage;
 use rusqlite::{Connection, OpenFlags, Row};
 use std::any::type_name;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::path::Path;
 use std::rc::Rc;
@@ -92,6 +99,7 @@ pub fn make_payable_account_with_wallet_and_balance_and_timestamp_opt(
         balance_wei: balance,
         last_paid_timestamp: timestamp_opt.unwrap_or(SystemTime::now()),
         pending_payable_opt: None,
+        written_off: false,
     }
 }
 
@@ -103,6 +111,7 @@ pub struct AccountantBuilder {
     receivable_dao_factory_opt: Option<ReceivableDaoFactoryMock>,
     pending_payable_dao_factory_opt: Option<PendingPayableDaoFactoryMock>,
     banned_dao_factory_opt: Option<BannedDaoFactoryMock>,
+    ledger_dao_factory_opt: Option<LedgerDaoFactoryMock>,
     config_dao_factory_opt: Option<ConfigDaoFactoryMock>,
 }
 
@@ -116,6 +125,7 @@ impl Default for AccountantBuilder {
             receivable_dao_factory_opt: None,
             pending_payable_dao_factory_opt: None,
             banned_dao_factory_opt: None,
+            ledger_dao_factory_opt: None,
             config_dao_factory_opt: None,
         }
     }
@@ -263,6 +273,17 @@ const RECEIVABLE_DAOS_ACCOUNTANT_INITIALIZATION_ORDER: [DestinationMarker; 2] =
     DestinationMarker::ReceivableScanner,
 ];
 
+const BANNED_DAOS_ACCOUNTANT_INITIALIZATION_ORDER: [DestinationMarker; 2] = [
+    DestinationMarker::AccountantBody,
+    DestinationMarker::ReceivableScanner,
+];
+
+const LEDGER_DAOS_ACCOUNTANT_INITIALIZATION_ORDER: [DestinationMarker; 3] = [
+    DestinationMarker::AccountantBody,
+    DestinationMarker::PendingPayableScanner,
+    DestinationMarker::ReceivableScanner,
+];
+
 impl AccountantBuilder {
     pub fn bootstrapper_config(mut self, config: BootstrapperConfig) -> Self {
         self.config_opt = Some(config);
@@ -321,18 +342,32 @@ impl AccountantBuilder {
         )
     }
 
-    //TODO this method seems to be never used?
-    pub fn banned_dao(mut self, banned_dao: BannedDaoMock) -> Self {
-        match self.banned_dao_factory_opt {
-            None => {
-                self.banned_dao_factory_opt =
-                    Some(BannedDaoFactoryMock::new().make_result(banned_dao))
-            }
-            Some(banned_dao_factory) => {
-                self.banned_dao_factory_opt = Some(banned_dao_factory.make_result(banned_dao))
-            }
-        }
-        self
+    pub fn ledger_daos(
+        mut self,
+        specially_configured_daos: Vec<DaoWithDestination<LedgerDaoMock>>,
+    ) -> Self {
+        create_or_update_factory!(
+            specially_configured_daos,
+            LEDGER_DAOS_ACCOUNTANT_INITIALIZATION_ORDER,
+            ledger_dao_factory_opt,
+            LedgerDaoFactoryMock,
+            LedgerDao,
+            self
+        )
+    }
+
+    pub fn banned_daos(
+        mut self,
+        specially_configured_daos: Vec<DaoWithDestination<BannedDaoMock>>,
+    ) -> Self {
+        create_or_update_factory!(
+            specially_configured_daos,
+            BANNED_DAOS_ACCOUNTANT_INITIALIZATION_ORDER,
+            banned_dao_factory_opt,
+            BannedDaoFactoryMock,
+            BannedDao,
+            self
+        )
     }
 
     pub fn config_dao(mut self, config_dao: ConfigDaoMock) -> Self {
@@ -359,9 +394,17 @@ impl AccountantBuilder {
                 .make_result(PendingPayableDaoMock::new())
                 .make_result(PendingPayableDaoMock::new()),
         );
-        let banned_dao_factory = self
-            .banned_dao_factory_opt
-            .unwrap_or(BannedDaoFactoryMock::new().make_result(BannedDaoMock::new()));
+        let banned_dao_factory = self.banned_dao_factory_opt.unwrap_or(
+            BannedDaoFactoryMock::new()
+                .make_result(BannedDaoMock::new())
+                .make_result(BannedDaoMock::new()),
+        );
+        let ledger_dao_factory = self.ledger_dao_factory_opt.unwrap_or(
+            LedgerDaoFactoryMock::new()
+                .make_result(LedgerDaoMock::new())
+                .make_result(LedgerDaoMock::new())
+                .make_result(LedgerDaoMock::new()),
+        );
         let config_dao_factory = self
             .config_dao_factory_opt
             .unwrap_or(ConfigDaoFactoryMock::new().make_result(ConfigDaoMock::new()));
@@ -372,6 +415,7 @@ impl AccountantBuilder {
                 pending_payable_dao_factory: Box::new(pending_payable_dao_factory),
                 receivable_dao_factory: Box::new(receivable_dao_factory),
                 banned_dao_factory: Box::new(banned_dao_factory),
+                ledger_dao_factory: Box::new(ledger_dao_factory),
                 config_dao_factory: Box::new(config_dao_factory),
             },
         );
@@ -492,6 +536,40 @@ impl BannedDaoFactoryMock {
     }
 }
 
+pub struct LedgerDaoFactoryMock {
+    make_params: Arc<Mutex<Vec<()>>>,
+    make_results: RefCell<Vec<Box<dyn LedgerDao>>>,
+}
+
+impl LedgerDaoFactory for LedgerDaoFactoryMock {
+    fn make(&self) -> Box<dyn LedgerDao> {
+        if self.make_results.borrow().len() == 0 {
+            panic!("LedgerDao Missing.")
+        };
+        self.make_params.lock().unwrap().push(());
+        self.make_results.borrow_mut().remove(0)
+    }
+}
+
+impl LedgerDaoFactoryMock {
+    pub fn new() -> Self {
+        Self {
+            make_params: Arc::new(Mutex::new(vec![])),
+            make_results: RefCell::new(vec![]),
+        }
+    }
+
+    pub fn make_params(mut self, params: &Arc<Mutex<Vec<()>>>) -> Self {
+        self.make_params = params.clone();
+        self
+    }
+
+    pub fn make_result(self, result: LedgerDaoMock) -> Self {
+        self.make_results.borrow_mut().push(Box::new(result));
+        self
+    }
+}
+
 pub struct ConfigDaoFactoryMock {
     make_params: Arc<Mutex<Vec<()>>>,
     make_results: RefCell<Vec<Box<dyn ConfigDao>>>,
@@ -529,6 +607,15 @@ pub struct PayableDaoMock {
     more_money_payable_results: RefCell<Vec<Result<(), PayableDaoError>>>,
     non_pending_payables_params: Arc<Mutex<Vec<()>>>,
     non_pending_payables_results: RefCell<Vec<Vec<PayableAccount>>>,
+    write_off_stale_payables_params: Arc<Mutex<Vec<(PayableWriteOffPolicy, SystemTime)>>>,
+    write_off_stale_payables_results: RefCell<Vec<Result<Vec<Wallet>, PayableDaoError>>>,
+    mark_seen_in_gossip_params: Arc<Mutex<Vec<(Vec<Wallet>, SystemTime)>>>,
+    mark_seen_in_gossip_results: RefCell<Vec<Result<(), PayableDaoError>>>,
+    record_earning_wallet_shares_params: Arc<Mutex<Vec<Vec<(Wallet, EarningWalletShares)>>>>,
+    record_earning_wallet_shares_results: RefCell<Vec<Result<(), PayableDaoError>>>,
+    split_among_earning_wallet_shares_params: Arc<Mutex<Vec<(Vec<Wallet>, SystemTime)>>>,
+    split_among_earning_wallet_shares_results:
+        RefCell<Vec<Result<Vec<PayableAccount>, PayableDaoError>>>,
     mark_pending_payables_rowids_params: Arc<Mutex<Vec<Vec<(Wallet, u64)>>>>,
     mark_pending_payables_rowids_results: RefCell<Vec<Result<(), PayableDaoError>>>,
     transactions_confirmed_params: Arc<Mutex<Vec<Vec<PendingPayableFingerprint>>>>,
@@ -586,6 +673,53 @@ impl PayableDao for PayableDaoMock {
         self.non_pending_payables_results.borrow_mut().remove(0)
     }
 
+    fn write_off_stale_payables(
+        &self,
+        policy: &PayableWriteOffPolicy,
+        now: SystemTime,
+    ) -> Result<Vec<Wallet>, PayableDaoError> {
+        self.write_off_stale_payables_params
+            .lock()
+            .unwrap()
+            .push((*policy, now));
+        self.write_off_stale_payables_results.borrow_mut().remove(0)
+    }
+
+    fn mark_seen_in_gossip(
+        &self,
+        wallets: &[Wallet],
+        timestamp: SystemTime,
+    ) -> Result<(), PayableDaoError> {
+        self.mark_seen_in_gossip_params
+            .lock()
+            .unwrap()
+            .push((wallets.to_vec(), timestamp));
+        self.mark_seen_in_gossip_results.borrow_mut().remove(0)
+    }
+
+    fn record_earning_wallet_shares(
+        &self,
+        earning_wallet_shares: &[(Wallet, EarningWalletShares)],
+    ) -> Result<(), PayableDaoError> {
+        self.record_earning_wallet_shares_params
+            .lock()
+            .unwrap()
+            .push(earning_wallet_shares.to_vec());
+        Self::next_result(&self.record_earning_wallet_shares_results, Ok(()))
+    }
+
+    fn split_among_earning_wallet_shares(
+        &self,
+        wallets: &[Wallet],
+        now: SystemTime,
+    ) -> Result<Vec<PayableAccount>, PayableDaoError> {
+        self.split_among_earning_wallet_shares_params
+            .lock()
+            .unwrap()
+            .push((wallets.to_vec(), now));
+        Self::next_result(&self.split_among_earning_wallet_shares_results, Ok(vec![]))
+    }
+
     fn custom_query(&self, custom_query: CustomQuery<u64>) -> Option<Vec<PayableAccount>> {
         self.custom_query_params.lock().unwrap().push(custom_query);
         self.custom_query_result.borrow_mut().remove(0)
@@ -629,6 +763,70 @@ impl PayableDaoMock {
         self
     }
 
+    pub fn write_off_stale_payables_params(
+        mut self,
+        params: &Arc<Mutex<Vec<(PayableWriteOffPolicy, SystemTime)>>>,
+    ) -> Self {
+        self.write_off_stale_payables_params = params.clone();
+        self
+    }
+
+    pub fn write_off_stale_payables_result(
+        self,
+        result: Result<Vec<Wallet>, PayableDaoError>,
+    ) -> Self {
+        self.write_off_stale_payables_results
+            .borrow_mut()
+            .push(result);
+        self
+    }
+
+    pub fn mark_seen_in_gossip_params(
+        mut self,
+        params: &Arc<Mutex<Vec<(Vec<Wallet>, SystemTime)>>>,
+    ) -> Self {
+        self.mark_seen_in_gossip_params = params.clone();
+        self
+    }
+
+    pub fn mark_seen_in_gossip_result(self, result: Result<(), PayableDaoError>) -> Self {
+        self.mark_seen_in_gossip_results.borrow_mut().push(result);
+        self
+    }
+
+    pub fn record_earning_wallet_shares_params(
+        mut self,
+        params: &Arc<Mutex<Vec<Vec<(Wallet, EarningWalletShares)>>>>,
+    ) -> Self {
+        self.record_earning_wallet_shares_params = params.clone();
+        self
+    }
+
+    pub fn record_earning_wallet_shares_result(self, result: Result<(), PayableDaoError>) -> Self {
+        self.record_earning_wallet_shares_results
+            .borrow_mut()
+            .push(result);
+        self
+    }
+
+    pub fn split_among_earning_wallet_shares_params(
+        mut self,
+        params: &Arc<Mutex<Vec<(Vec<Wallet>, SystemTime)>>>,
+    ) -> Self {
+        self.split_among_earning_wallet_shares_params = params.clone();
+        self
+    }
+
+    pub fn split_among_earning_wallet_shares_result(
+        self,
+        result: Result<Vec<PayableAccount>, PayableDaoError>,
+    ) -> Self {
+        self.split_among_earning_wallet_shares_results
+            .borrow_mut()
+            .push(result);
+        self
+    }
+
     pub fn mark_pending_payables_rowids_params(
         mut self,
         params: &Arc<Mutex<Vec<Vec<(Wallet, u64)>>>>,
@@ -673,6 +871,17 @@ impl PayableDaoMock {
         self.total_results.borrow_mut().push(result);
         self
     }
+
+    // Most tests don't care about earning wallet shares, so these calls succeed doing nothing
+    // unless a result is supplied
+    fn next_result<T>(results: &RefCell<Vec<T>>, default: T) -> T {
+        let mut results = results.borrow_mut();
+        if results.is_empty() {
+            default
+        } else {
+            results.remove(0)
+        }
+    }
 }
 
 #[derive(Debug, Default)]
@@ -688,6 +897,8 @@ pub struct ReceivableDaoMock {
     custom_query_params: Arc<Mutex<Vec<CustomQuery<i64>>>>,
     custom_query_result: RefCell<Vec<Option<Vec<ReceivableAccount>>>>,
     total_results: RefCell<Vec<i128>>,
+    account_status_params: Arc<Mutex<Vec<Wallet>>>,
+    account_status_results: RefCell<Vec<Option<ReceivableAccount>>>,
 }
 
 impl ReceivableDao for ReceivableDaoMock {
@@ -745,9 +956,12 @@ impl ReceivableDao for ReceivableDaoMock {
         self.total_results.borrow_mut().remove(0)
     }
 
-    fn account_status(&self, _wallet: &Wallet) -> Option<ReceivableAccount> {
-        //test-only trait member
-        intentionally_blank!()
+    fn account_status(&self, wallet: &Wallet) -> Option<ReceivableAccount> {
+        self.account_status_params
+            .lock()
+            .unwrap()
+            .push(wallet.clone());
+        self.account_status_results.borrow_mut().remove(0)
     }
 }
 
@@ -822,47 +1036,72 @@ impl ReceivableDaoMock {
         self.total_results.borrow_mut().push(result);
         self
     }
+
+    pub fn account_status_params(mut self, params: &Arc<Mutex<Vec<Wallet>>>) -> Self {
+        self.account_status_params = params.clone();
+        self
+    }
+
+    pub fn account_status_result(self, result: Option<ReceivableAccount>) -> Self {
+        self.account_status_results.borrow_mut().push(result);
+        self
+    }
 }
 
 #[derive(Debug, Default)]
 pub struct BannedDaoMock {
     ban_list_parameters: Arc<Mutex<Vec<()>>>,
-    ban_list_results: RefCell<Vec<Vec<Wallet>>>,
-    ban_parameters: Arc<Mutex<Vec<Wallet>>>,
+    ban_list_results: RefCell<Vec<Vec<BannedAccount>>>,
+    ban_parameters: Arc<Mutex<Vec<(Wallet, BanDetails)>>>,
     unban_parameters: Arc<Mutex<Vec<Wallet>>>,
+    pardon_parameters: Arc<Mutex<Vec<Wallet>>>,
+    pardon_results: RefCell<Vec<bool>>,
+    annotate_parameters: Arc<Mutex<Vec<(Wallet, String)>>>,
+    annotate_results: RefCell<Vec<bool>>,
 }
 
 impl BannedDao for BannedDaoMock {
-    fn ban_list(&self) -> Vec<Wallet> {
+    fn ban_list(&self) -> Vec<BannedAccount> {
         self.ban_list_parameters.lock().unwrap().push(());
         self.ban_list_results.borrow_mut().remove(0)
     }
 
-    fn ban(&self, wallet: &Wallet) {
-        self.ban_parameters.lock().unwrap().push(wallet.clone());
+    fn ban(&self, wallet: &Wallet, details: BanDetails) {
+        self.ban_parameters
+            .lock()
+            .unwrap()
+            .push((wallet.clone(), details));
     }
 
     fn unban(&self, wallet: &Wallet) {
         self.unban_parameters.lock().unwrap().push(wallet.clone());
     }
+
+    fn pardon(&self, wallet: &Wallet) -> bool {
+        self.pardon_parameters.lock().unwrap().push(wallet.clone());
+        self.pardon_results.borrow_mut().remove(0)
+    }
+
+    fn annotate(&self, wallet: &Wallet, note: &str) -> bool {
+        self.annotate_parameters
+            .lock()
+            .unwrap()
+            .push((wallet.clone(), note.to_string()));
+        self.annotate_results.borrow_mut().remove(0)
+    }
 }
 
 impl BannedDaoMock {
     pub fn new() -> Self {
-        Self {
-            ban_list_parameters: Arc::new(Mutex::new(vec![])),
-            ban_list_results: RefCell::new(vec![]),
-            ban_parameters: Arc::new(Mutex::new(vec![])),
-            unban_parameters: Arc::new(Mutex::new(vec![])),
-        }
+        Self::default()
     }
 
-    pub fn ban_list_result(self, result: Vec<Wallet>) -> Self {
+    pub fn ban_list_result(self, result: Vec<BannedAccount>) -> Self {
         self.ban_list_results.borrow_mut().push(result);
         self
     }
 
-    pub fn ban_parameters(mut self, parameters: &Arc<Mutex<Vec<Wallet>>>) -> Self {
+    pub fn ban_parameters(mut self, parameters: &Arc<Mutex<Vec<(Wallet, BanDetails)>>>) -> Self {
         self.ban_parameters = parameters.clone();
         self
     }
@@ -871,6 +1110,168 @@ impl BannedDaoMock {
         self.unban_parameters = parameters.clone();
         self
     }
+
+    pub fn pardon_parameters(mut self, parameters: &Arc<Mutex<Vec<Wallet>>>) -> Self {
+        self.pardon_parameters = parameters.clone();
+        self
+    }
+
+    pub fn pardon_result(self, result: bool) -> Self {
+        self.pardon_results.borrow_mut().push(result);
+        self
+    }
+
+    pub fn annotate_parameters(mut self, parameters: &Arc<Mutex<Vec<(Wallet, String)>>>) -> Self {
+        self.annotate_parameters = parameters.clone();
+        self
+    }
+
+    pub fn annotate_result(self, result: bool) -> Self {
+        self.annotate_results.borrow_mut().push(result);
+        self
+    }
+}
+
+// Recording in the ledger succeeds unless a result says otherwise
+#[derive(Debug, Default)]
+pub struct LedgerDaoMock {
+    record_params: Arc<Mutex<Vec<Vec<LedgerEntry>>>>,
+    record_results: RefCell<Vec<Result<(), LedgerDaoError>>>,
+    accumulate_charges_params: Arc<Mutex<Vec<Vec<LedgerEntry>>>>,
+    accumulate_charges_results: RefCell<Vec<Result<(), LedgerDaoError>>>,
+    record_by_guest_transaction_params: Arc<Mutex<Vec<Vec<LedgerEntry>>>>,
+    record_by_guest_transaction_results: RefCell<Vec<Result<(), LedgerDaoError>>>,
+    record_confirmed_payments_params: Arc<Mutex<Vec<Vec<PendingPayableFingerprint>>>>,
+    record_confirmed_payments_results: RefCell<Vec<Result<(), LedgerDaoError>>>,
+    entries_params: Arc<Mutex<Vec<(Option<SystemTime>, Option<SystemTime>)>>>,
+    entries_results: RefCell<Vec<Vec<LedgerEntry>>>,
+}
+
+impl LedgerDao for LedgerDaoMock {
+    fn record(&self, entries: &[LedgerEntry]) -> Result<(), LedgerDaoError> {
+        self.record_params.lock().unwrap().push(entries.to_vec());
+        Self::next_result(&self.record_results)
+    }
+
+    fn accumulate_charges(&self, charges: &[LedgerEntry]) -> Result<(), LedgerDaoError> {
+        self.accumulate_charges_params
+            .lock()
+            .unwrap()
+            .push(charges.to_vec());
+        Self::next_result(&self.accumulate_charges_results)
+    }
+
+    fn record_by_guest_transaction(
+        &self,
+        _txn: &mut TransactionSafeWrapper,
+        entries: &[LedgerEntry],
+    ) -> Result<(), LedgerDaoError> {
+        self.record_by_guest_transaction_params
+            .lock()
+            .unwrap()
+            .push(entries.to_vec());
+        Self::next_result(&self.record_by_guest_transaction_results)
+    }
+
+    fn record_confirmed_payments(
+        &self,
+        confirmed_payables: &[PendingPayableFingerprint],
+    ) -> Result<(), LedgerDaoError> {
+        self.record_confirmed_payments_params
+            .lock()
+            .unwrap()
+            .push(confirmed_payables.to_vec());
+        Self::next_result(&self.record_confirmed_payments_results)
+    }
+
+    fn entries(
+        &self,
+        from_opt: Option<SystemTime>,
+        to_opt: Option<SystemTime>,
+    ) -> Vec<LedgerEntry> {
+        self.entries_params.lock().unwrap().push((from_opt, to_opt));
+        self.entries_results.borrow_mut().remove(0)
+    }
+}
+
+impl LedgerDaoMock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_params(mut self, params: &Arc<Mutex<Vec<Vec<LedgerEntry>>>>) -> Self {
+        self.record_params = params.clone();
+        self
+    }
+
+    pub fn record_result(self, result: Result<(), LedgerDaoError>) -> Self {
+        self.record_results.borrow_mut().push(result);
+        self
+    }
+
+    pub fn accumulate_charges_params(mut self, params: &Arc<Mutex<Vec<Vec<LedgerEntry>>>>) -> Self {
+        self.accumulate_charges_params = params.clone();
+        self
+    }
+
+    pub fn accumulate_charges_result(self, result: Result<(), LedgerDaoError>) -> Self {
+        self.accumulate_charges_results.borrow_mut().push(result);
+        self
+    }
+
+    pub fn record_by_guest_transaction_params(
+        mut self,
+        params: &Arc<Mutex<Vec<Vec<LedgerEntry>>>>,
+    ) -> Self {
+        self.record_by_guest_transaction_params = params.clone();
+        self
+    }
+
+    pub fn record_by_guest_transaction_result(self, result: Result<(), LedgerDaoError>) -> Self {
+        self.record_by_guest_transaction_results
+            .borrow_mut()
+            .push(result);
+        self
+    }
+
+    pub fn record_confirmed_payments_params(
+        mut self,
+        params: &Arc<Mutex<Vec<Vec<PendingPayableFingerprint>>>>,
+    ) -> Self {
+        self.record_confirmed_payments_params = params.clone();
+        self
+    }
+
+    pub fn record_confirmed_payments_result(self, result: Result<(), LedgerDaoError>) -> Self {
+        self.record_confirmed_payments_results
+            .borrow_mut()
+            .push(result);
+        self
+    }
+
+    pub fn entries_params(
+        mut self,
+        params: &Arc<Mutex<Vec<(Option<SystemTime>, Option<SystemTime>)>>>,
+    ) -> Self {
+        self.entries_params = params.clone();
+        self
+    }
+
+    pub fn entries_result(self, result: Vec<LedgerEntry>) -> Self {
+        self.entries_results.borrow_mut().push(result);
+        self
+    }
+
+    fn next_result(
+        results: &RefCell<Vec<Result<(), LedgerDaoError>>>,
+    ) -> Result<(), LedgerDaoError> {
+        let mut results = results.borrow_mut();
+        if results.is_empty() {
+            Ok(())
+        } else {
+            results.remove(0)
+        }
+    }
 }
 
 pub fn bc_from_earning_wallet(earning_wallet: Wallet) -> BootstrapperConfig {
@@ -890,6 +1291,8 @@ pub fn bc_from_wallets(consuming_wallet: Wallet, earning_wallet: Wallet) -> Boot
 pub struct PendingPayableDaoMock {
     fingerprints_rowids_params: Arc<Mutex<Vec<Vec<H256>>>>,
     fingerprints_rowids_results: RefCell<Vec<TransactionHashes>>,
+    fingerprints_recipients_params: Arc<Mutex<Vec<Vec<u64>>>>,
+    fingerprints_recipients_results: RefCell<Vec<HashMap<u64, Wallet>>>,
     delete_fingerprints_params: Arc<Mutex<Vec<Vec<u64>>>>,
     delete_fingerprints_results: RefCell<Vec<Result<(), PendingPayableDaoError>>>,
     insert_new_fingerprints_params: Arc<Mutex<Vec<(Vec<HashAndAmount>, SystemTime)>>>,
@@ -912,6 +1315,14 @@ impl PendingPayableDao for PendingPayableDaoMock {
         self.fingerprints_rowids_results.borrow_mut().remove(0)
     }
 
+    fn fingerprints_recipients(&self, ids: &[u64]) -> HashMap<u64, Wallet> {
+        self.fingerprints_recipients_params
+            .lock()
+            .unwrap()
+            .push(ids.to_vec());
+        self.fingerprints_recipients_results.borrow_mut().remove(0)
+    }
+
     fn return_all_errorless_fingerprints(&self) -> Vec<PendingPayableFingerprint> {
         self.return_all_errorless_fingerprints_params
             .lock()
@@ -980,6 +1391,18 @@ impl PendingPayableDaoMock {
         self
     }
 
+    pub fn fingerprints_recipients_params(mut self, params: &Arc<Mutex<Vec<Vec<u64>>>>) -> Self {
+        self.fingerprints_recipients_params = params.clone();
+        self
+    }
+
+    pub fn fingerprints_recipients_result(self, result: HashMap<u64, Wallet>) -> Self {
+        self.fingerprints_recipients_results
+            .borrow_mut()
+            .push(result);
+        self
+    }
+
     pub fn insert_fingerprints_params(
         mut self,
         params: &Arc<Mutex<Vec<(Vec<HashAndAmount>, SystemTime)>>>,
@@ -1090,6 +1513,7 @@ pub struct PayableScannerBuilder {
     pending_payable_dao: PendingPayableDaoMock,
     payment_thresholds: PaymentThresholds,
     payment_adjuster: PaymentAdjusterMock,
+    write_off_policy: PayableWriteOffPolicy,
 }
 
 impl PayableScannerBuilder {
@@ -1099,6 +1523,7 @@ impl PayableScannerBuilder {
             pending_payable_dao: PendingPayableDaoMock::new(),
             payment_thresholds: PaymentThresholds::default(),
             payment_adjuster: PaymentAdjusterMock::default(),
+            write_off_policy: PayableWriteOffPolicy::default(),
         }
     }
 
@@ -1128,12 +1553,18 @@ impl PayableScannerBuilder {
         self
     }
 
+    pub fn write_off_policy(mut self, write_off_policy: PayableWriteOffPolicy) -> Self {
+        self.write_off_policy = write_off_policy;
+        self
+    }
+
     pub fn build(self) -> PayableScanner {
         PayableScanner::new(
             Box::new(self.payable_dao),
             Box::new(self.pending_payable_dao),
             Rc::new(self.payment_thresholds),
             Box::new(self.payment_adjuster),
+            self.write_off_policy,
         )
     }
 }
@@ -1141,6 +1572,7 @@ impl PayableScannerBuilder {
 pub struct PendingPayableScannerBuilder {
     payable_dao: PayableDaoMock,
     pending_payable_dao: PendingPayableDaoMock,
+    ledger_dao: LedgerDaoMock,
     payment_thresholds: PaymentThresholds,
     when_pending_too_long_sec: u64,
     financial_statistics: FinancialStatistics,
@@ -1151,6 +1583,7 @@ impl PendingPayableScannerBuilder {
         Self {
             payable_dao: PayableDaoMock::new(),
             pending_payable_dao: PendingPayableDaoMock::new(),
+            ledger_dao: LedgerDaoMock::new(),
             payment_thresholds: PaymentThresholds::default(),
             when_pending_too_long_sec: DEFAULT_PENDING_TOO_LONG_SEC,
             financial_statistics: FinancialStatistics::default(),
@@ -1167,6 +1600,11 @@ impl PendingPayableScannerBuilder {
         self
     }
 
+    pub fn ledger_dao(mut self, ledger_dao: LedgerDaoMock) -> Self {
+        self.ledger_dao = ledger_dao;
+        self
+    }
+
     pub fn when_pending_too_long_sec(mut self, interval: u64) -> Self {
         self.when_pending_too_long_sec = interval;
         self
@@ -1176,6 +1614,7 @@ impl PendingPayableScannerBuilder {
         PendingPayableScanner::new(
             Box::new(self.payable_dao),
             Box::new(self.pending_payable_dao),
+            Box::new(self.ledger_dao),
             Rc::new(self.payment_thresholds),
             self.when_pending_too_long_sec,
             Rc::new(RefCell::new(self.financial_statistics)),
@@ -1186,9 +1625,11 @@ impl PendingPayableScannerBuilder {
 pub struct ReceivableScannerBuilder {
     receivable_dao: ReceivableDaoMock,
     banned_dao: BannedDaoMock,
+    ledger_dao: LedgerDaoMock,
     persistent_configuration: PersistentConfigurationMock,
     payment_thresholds: PaymentThresholds,
     financial_statistics: FinancialStatistics,
+    earning_wallet_shares: EarningWalletShares,
 }
 
 impl ReceivableScannerBuilder {
@@ -1196,9 +1637,11 @@ impl ReceivableScannerBuilder {
         Self {
             receivable_dao: ReceivableDaoMock::new(),
             banned_dao: BannedDaoMock::new(),
+            ledger_dao: LedgerDaoMock::new(),
             persistent_configuration: PersistentConfigurationMock::new(),
             payment_thresholds: PaymentThresholds::default(),
             financial_statistics: FinancialStatistics::default(),
+            earning_wallet_shares: EarningWalletShares::default(),
         }
     }
 
@@ -1220,18 +1663,30 @@ impl ReceivableScannerBuilder {
         self
     }
 
+    pub fn ledger_dao(mut self, ledger_dao: LedgerDaoMock) -> Self {
+        self.ledger_dao = ledger_dao;
+        self
+    }
+
     pub fn payment_thresholds(mut self, payment_thresholds: PaymentThresholds) -> Self {
         self.payment_thresholds = payment_thresholds;
         self
     }
 
+    pub fn earning_wallet_shares(mut self, earning_wallet_shares: EarningWalletShares) -> Self {
+        self.earning_wallet_shares = earning_wallet_shares;
+        self
+    }
+
     pub fn build(self) -> ReceivableScanner {
         ReceivableScanner::new(
             Box::new(self.receivable_dao),
             Box::new(self.banned_dao),
+            Box::new(self.ledger_dao),
             Box::new(self.persistent_configuration),
             Rc::new(self.payment_thresholds),
             Rc::new(RefCell::new(self.financial_statistics)),
+            self.earning_wallet_shares,
         )
     }
 }
@@ -1273,6 +1728,7 @@ pub fn make_payables(
             to_time_t(now) - payment_thresholds.maturity_threshold_sec as i64 + 1,
         ),
         pending_payable_opt: None,
+        written_off: false,
     }];
     let qualified_payable_accounts = vec![
         PayableAccount {
@@ -1284,6 +1740,7 @@ pub fn make_payables(
                 to_time_t(now) - payment_thresholds.maturity_threshold_sec as i64 - 1,
             ),
             pending_payable_opt: None,
+            written_off: false,
         },
         PayableAccount {
             wallet: make_wallet("wallet3"),
@@ -1294,6 +1751,7 @@ pub fn make_payables(
                 to_time_t(now) - payment_thresholds.maturity_threshold_sec as i64 - 100,
             ),
             pending_payable_opt: None,
+            written_off: false,
         },
     ];
 
@@ -1566,6 +2024,10 @@ where
         panic!("Called mark_as_ended() from NullScanner");
     }
 
+    fn update_payment_thresholds(&mut self, _payment_thresholds: Rc<PaymentThresholds>) {
+        panic!("Called update_payment_thresholds() from NullScanner");
+    }
+
     as_any_ref_in_trait_impl!();
 }
 
@@ -1635,6 +2097,10 @@ where
     fn mark_as_ended(&mut self, _logger: &Logger) {
         intentionally_blank!()
     }
+
+    fn update_payment_thresholds(&mut self, _payment_thresholds: Rc<PaymentThresholds>) {
+        intentionally_blank!()
+    }
 }
 
 impl<BeginMessage, EndMessage> Default for ScannerMock<BeginMessage, EndMessage> {
@@ -0,0 +1,314 @@
+// Copyright (c) 2024, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::accountant::db_access_objects::utils::ThresholdUtils;
+use crate::accountant::scanners::scanners_utils::payable_scanner_utils::{
+    payable_threshold_exceeded, PayableThresholdsGauge, PayableThresholdsGaugeReal,
+};
+use crate::accountant::scanners::scanners_utils::receivable_scanner_utils::delinquency_threshold_opt;
+use crate::accountant::{gwei_to_wei, wei_to_gwei};
+use crate::sub_lib::accountant::PaymentThresholds;
+use itertools::Itertools;
+use masq_lib::messages::{
+    UiDebtSnapshot, UiPaymentThresholds, UiThresholdCurvePoint, UiThresholdSimulationResponse,
+    UiThresholdSimulationStep,
+};
+
+// Longer periods than this make the threshold arithmetic overflow, and nobody needs them anyway
+const MAX_THRESHOLD_PERIOD_SEC: u64 = u32::MAX as u64;
+
+pub fn simulate_thresholds(
+    payment_thresholds: &PaymentThresholds,
+    history: &[UiDebtSnapshot],
+) -> Result<UiThresholdSimulationResponse, String> {
+    validate(payment_thresholds)?;
+    Ok(UiThresholdSimulationResponse {
+        payment_thresholds: to_ui_payment_thresholds(payment_thresholds),
+        curve: curve(payment_thresholds),
+        steps: steps(payment_thresholds, history),
+    })
+}
+
+pub fn from_ui_payment_thresholds(ui_thresholds: &UiPaymentThresholds) -> PaymentThresholds {
+    PaymentThresholds {
+        debt_threshold_gwei: ui_thresholds.debt_threshold_gwei,
+        maturity_threshold_sec: ui_thresholds.maturity_threshold_sec,
+        payment_grace_period_sec: ui_thresholds.payment_grace_period_sec,
+        permanent_debt_allowed_gwei: ui_thresholds.permanent_debt_allowed_gwei,
+        threshold_interval_sec: ui_thresholds.threshold_interval_sec,
+        unban_below_gwei: ui_thresholds.unban_below_gwei,
+    }
+}
+
+fn to_ui_payment_thresholds(payment_thresholds: &PaymentThresholds) -> UiPaymentThresholds {
+    UiPaymentThresholds {
+        threshold_interval_sec: payment_thresholds.threshold_interval_sec,
+        debt_threshold_gwei: payment_thresholds.debt_threshold_gwei,
+        payment_grace_period_sec: payment_thresholds.payment_grace_period_sec,
+        maturity_threshold_sec: payment_thresholds.maturity_threshold_sec,
+        permanent_debt_allowed_gwei: payment_thresholds.permanent_debt_allowed_gwei,
+        unban_below_gwei: payment_thresholds.unban_below_gwei,
+    }
+}
+
+fn validate(payment_thresholds: &PaymentThresholds) -> Result<(), String> {
+    let periods = [
+        (
+            "maturityThresholdSec",
+            payment_thresholds.maturity_threshold_sec,
+        ),
+        (
+            "paymentGracePeriodSec",
+            payment_thresholds.payment_grace_period_sec,
+        ),
+        (
+            "thresholdIntervalSec",
+            payment_thresholds.threshold_interval_sec,
+        ),
+    ];
+    if let Some((name, value)) = periods
+        .iter()
+        .find(|(_, value)| *value > MAX_THRESHOLD_PERIOD_SEC)
+    {
+        return Err(format!(
+            "{} ({}) must not be greater than {}",
+            name, value, MAX_THRESHOLD_PERIOD_SEC
+        ));
+    }
+    if payment_thresholds.threshold_interval_sec == 0 {
+        return Err("thresholdIntervalSec must be greater than 0".to_string());
+    }
+    if payment_thresholds.permanent_debt_allowed_gwei >= payment_thresholds.debt_threshold_gwei {
+        return Err(format!(
+            "permanentDebtAllowedGwei ({}) must be less than debtThresholdGwei ({})",
+            payment_thresholds.permanent_debt_allowed_gwei, payment_thresholds.debt_threshold_gwei
+        ));
+    }
+    if ThresholdUtils::slope(payment_thresholds) == 0 {
+        return Err(format!(
+            "thresholdIntervalSec ({}) is too long for the drop from debtThresholdGwei to \
+            permanentDebtAllowedGwei; the threshold would never fall",
+            payment_thresholds.threshold_interval_sec
+        ));
+    }
+    Ok(())
+}
+
+fn curve(payment_thresholds: &PaymentThresholds) -> Vec<UiThresholdCurvePoint> {
+    let maturity = payment_thresholds.maturity_threshold_sec;
+    let interval = payment_thresholds.threshold_interval_sec;
+    let payable_corner = maturity;
+    let delinquency_corner = maturity + payment_thresholds.payment_grace_period_sec;
+    let gauge = PayableThresholdsGaugeReal::default();
+    vec![
+        0,
+        payable_corner,
+        payable_corner + 1,
+        payable_corner + interval / 2,
+        payable_corner + interval,
+        delinquency_corner,
+        delinquency_corner + 1,
+        delinquency_corner + interval / 2,
+        delinquency_corner + interval,
+    ]
+    .into_iter()
+    .sorted()
+    .dedup()
+    .map(|age_sec| UiThresholdCurvePoint {
+        age_sec,
+        payable_threshold_gwei_opt: if age_sec <= maturity {
+            None
+        } else {
+            Some(wei_to_gwei(gauge.calculate_payout_threshold_in_gwei(
+                payment_thresholds,
+                age_sec,
+            )))
+        },
+        delinquency_threshold_gwei_opt: delinquency_threshold_opt(payment_thresholds, age_sec)
+            .map(wei_to_gwei),
+    })
+    .collect()
+}
+
+fn steps(
+    payment_thresholds: &PaymentThresholds,
+    history: &[UiDebtSnapshot],
+) -> Vec<UiThresholdSimulationStep> {
+    let gauge = PayableThresholdsGaugeReal::default();
+    let unban_below_wei: u128 = gwei_to_wei(payment_thresholds.unban_below_gwei);
+    let mut banned = false;
+    history
+        .iter()
+        .map(|snapshot| {
+            let balance_wei: u128 = gwei_to_wei(snapshot.balance_gwei);
+            let payment_due = payable_threshold_exceeded(
+                &gauge,
+                payment_thresholds,
+                balance_wei,
+                snapshot.age_sec,
+            )
+            .is_some();
+            let delinquent = matches!(
+                delinquency_threshold_opt(payment_thresholds, snapshot.age_sec),
+                Some(threshold) if balance_wei > threshold
+            );
+            let newly_banned = !banned && delinquent;
+            let newly_unbanned = banned && balance_wei <= unban_below_wei;
+            if newly_banned || newly_unbanned {
+                banned = !banned;
+            }
+            UiThresholdSimulationStep {
+                age_sec: snapshot.age_sec,
+                balance_gwei: snapshot.balance_gwei,
+                payment_due,
+                delinquent,
+                newly_banned,
+                newly_unbanned,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_thresholds() -> PaymentThresholds {
+        PaymentThresholds {
+            debt_threshold_gwei: 10_000,
+            maturity_threshold_sec: 1_000,
+            payment_grace_period_sec: 500,
+            permanent_debt_allowed_gwei: 2_000,
+            threshold_interval_sec: 8_000,
+            unban_below_gwei: 1_000,
+        }
+    }
+
+    fn snapshot(age_sec: u64, balance_gwei: u64) -> UiDebtSnapshot {
+        UiDebtSnapshot {
+            age_sec,
+            balance_gwei,
+        }
+    }
+
+    #[test]
+    fn constants_have_correct_values() {
+        assert_eq!(MAX_THRESHOLD_PERIOD_SEC, 4_294_967_295);
+    }
+
+    #[test]
+    fn curve_follows_both_slopes() {
+        let result = simulate_thresholds(&make_thresholds(), &[]).unwrap();
+
+        let point = |age_sec, payable_opt, delinquency_opt| UiThresholdCurvePoint {
+            age_sec,
+            payable_threshold_gwei_opt: payable_opt,
+            delinquency_threshold_gwei_opt: delinquency_opt,
+        };
+        assert_eq!(
+            result.curve,
+            vec![
+                point(0, None, None),
+                point(1_000, None, None),
+                point(1_001, Some(9_999), None),
+                point(1_500, Some(9_500), None),
+                point(1_501, Some(9_499), Some(9_999)),
+                point(5_000, Some(6_000), Some(6_500)),
+                point(5_500, Some(5_500), Some(6_000)),
+                point(9_000, Some(2_000), Some(2_500)),
+                point(9_500, Some(2_000), Some(2_000)),
+            ]
+        );
+        assert_eq!(result.steps, vec![]);
+        assert_eq!(
+            result.payment_thresholds,
+            to_ui_payment_thresholds(&make_thresholds())
+        );
+    }
+
+    #[test]
+    fn steps_show_payments_bans_and_unbans() {
+        let history = vec![
+            snapshot(900, 50_000),
+            snapshot(1_200, 9_000),
+            snapshot(1_400, 9_700),
+            snapshot(3_000, 8_600),
+            snapshot(4_000, 8_500),
+            snapshot(6_000, 1_500),
+            snapshot(7_000, 1_000),
+        ];
+
+        let result = simulate_thresholds(&make_thresholds(), &history).unwrap();
+
+        let step = |age_sec, balance_gwei, payment_due, delinquent, banned, unbanned| {
+            UiThresholdSimulationStep {
+                age_sec,
+                balance_gwei,
+                payment_due,
+                delinquent,
+                newly_banned: banned,
+                newly_unbanned: unbanned,
+            }
+        };
+        assert_eq!(
+            result.steps,
+            vec![
+                step(900, 50_000, false, false, false, false),
+                step(1_200, 9_000, false, false, false, false),
+                step(1_400, 9_700, true, false, false, false),
+                step(3_000, 8_600, true, true, true, false),
+                step(4_000, 8_500, true, true, false, false),
+                step(6_000, 1_500, false, false, false, false),
+                step(7_000, 1_000, false, false, false, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn inconsistent_thresholds_are_rejected() {
+        let with = |change: fn(&mut PaymentThresholds)| {
+            let mut thresholds = make_thresholds();
+            change(&mut thresholds);
+            simulate_thresholds(&thresholds, &[]).map(|_| ())
+        };
+
+        assert_eq!(
+            with(|t| t.threshold_interval_sec = 0),
+            Err("thresholdIntervalSec must be greater than 0".to_string())
+        );
+        assert_eq!(
+            with(|t| t.permanent_debt_allowed_gwei = 10_000),
+            Err(
+                "permanentDebtAllowedGwei (10000) must be less than debtThresholdGwei (10000)"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            with(|t| {
+                t.permanent_debt_allowed_gwei = 1;
+                t.debt_threshold_gwei = 2;
+                t.threshold_interval_sec = 2_000_000_000
+            }),
+            Err(
+                "thresholdIntervalSec (2000000000) is too long for the drop from debtThresholdGwei \
+                to permanentDebtAllowedGwei; the threshold would never fall"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            with(|t| t.maturity_threshold_sec = u64::MAX),
+            Err(format!(
+                "maturityThresholdSec ({}) must not be greater than 4294967295",
+                u64::MAX
+            ))
+        );
+    }
+
+    #[test]
+    fn ui_payment_thresholds_convert_both_ways() {
+        let thresholds = make_thresholds();
+
+        let result = from_ui_payment_thresholds(&to_ui_payment_thresholds(&thresholds));
+
+        assert_eq!(result, thresholds);
+    }
+}
@@ -56,6 +56,21 @@ pub fn app_config_dumper() -> App<'static, 'static> {
         .arg(real_user_arg())
 }
 
+pub fn app_backup_restorer() -> App<'static, 'static> {
+    app_head()
+        .arg(chain_arg())
+        .arg(
+            Arg::with_name("restore-backup")
+                .long("restore-backup")
+                .value_name("BACKUP-FILE")
+                .required(true)
+                .takes_value(true)
+                .help(RESTORE_BACKUP_HELP),
+        )
+        .arg(data_directory_arg(DATA_DIRECTORY_DAEMON_HELP.as_str()))
+        .arg(real_user_arg())
+}
+
 lazy_static! {
     static ref DAEMON_UI_PORT_HELP: String = format!(
         "The port at which user interfaces will connect to the Daemon. (This is NOT the port at which \
@@ -65,6 +80,10 @@ lazy_static! {
     );
 }
 
+const RESTORE_BACKUP_HELP: &str =
+    "Replace the MASQ Node database in the data directory with the given backup file. The Node \
+     must not be running. Backups made by a newer version of the Node are refused.";
+
 const DUMP_CONFIG_HELP: &str =
     "Dump the configuration of MASQ Node to stdout in JSON. Used chiefly by UIs.";
 
@@ -79,6 +98,11 @@ const NODE_HELP_TEXT: &str = indoc!(
 
         MASQNode --help --dump-config
 
+    If you want to replace the database with one of the copies the Node keeps in the
+    'backups' folder of its data directory, try:
+
+        MASQNode --help --restore-backup
+
     MASQ Node listens for connections from other Nodes using the computer's
     network interface. Configuring the internet router for port forwarding is a necessary
     step for Node users to permit network communication between Nodes.
@@ -138,6 +162,11 @@ mod tests {
             DUMP_CONFIG_HELP,
             "Dump the configuration of MASQ Node to stdout in JSON. Used chiefly by UIs."
         );
+        assert_eq!(
+            RESTORE_BACKUP_HELP,
+            "Replace the MASQ Node database in the data directory with the given backup file. \
+             The Node must not be running. Backups made by a newer version of the Node are refused."
+        );
         assert_eq!(
             NODE_HELP_TEXT,
             indoc!(
@@ -151,6 +180,11 @@ mod tests {
             
                       MASQNode --help --dump-config
             
+                  If you want to replace the database with one of the copies the Node keeps in the
+                  'backups' folder of its data directory, try:
+            
+                      MASQNode --help --restore-backup
+            
                   MASQ Node listens for connections from other Nodes using the computer's
                   network interface. Configuring the internet router for port forwarding is a necessary
                   step for Node users to permit network communication between Nodes.
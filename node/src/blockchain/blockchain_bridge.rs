@@ -39,6 +39,7 @@ use masq_lib::logger::Logger;
 use masq_lib::messages::ScanType;
 use masq_lib::ui_gateway::NodeFromUiMessage;
 use regex::Regex;
+use std::collections::HashMap;
 use std::path::Path;
 use std::string::ToString;
 use std::sync::{Arc, Mutex};
@@ -107,7 +108,7 @@ impl Handler<BindMessage> for BlockchainBridge {
 
 #[derive(Debug, PartialEq, Eq, Message, Clone)]
 pub struct RetrieveTransactions {
-    pub recipient: Wallet,
+    pub recipients: Vec<Wallet>,
     pub response_skeleton_opt: Option<ResponseSkeleton>,
 }
 
@@ -351,7 +352,7 @@ impl BlockchainBridge {
                 .retrieve_transactions(
                     start_block,
                     block_scan_range,
-                    msg.recipient.address(),
+                    msg.recipients.iter().map(|wallet| wallet.address()).collect(),
                 )
                 .map_err(move |e| {
                     if let Some(max_block_count) =
@@ -429,21 +430,32 @@ impl BlockchainBridge {
             .clone()
             .expect("Accountant is unbound");
 
+        // Payables settled by one multisend transaction share its hash; its receipt is fetched once
         let transaction_hashes = msg
             .pending_payable
             .iter()
             .map(|finger_print| finger_print.hash)
+            .unique()
             .collect::<Vec<Hash>>();
         Box::new(
             self.blockchain_interface
-                .process_transaction_receipts(transaction_hashes)
+                .process_transaction_receipts(transaction_hashes.clone())
                 .map_err(move |e| e.to_string())
                 .and_then(move |transaction_receipts_results| {
                     Self::log_status_of_tx_receipts(&logger, &transaction_receipts_results);
 
-                    let pairs = transaction_receipts_results
+                    let receipts_by_hash = transaction_hashes
                         .into_iter()
-                        .zip(msg.pending_payable.into_iter())
+                        .zip(transaction_receipts_results.into_iter())
+                        .collect::<HashMap<Hash, TransactionReceiptResult>>();
+                    let pairs = msg
+                        .pending_payable
+                        .into_iter()
+                        .flat_map(|fingerprint| {
+                            receipts_by_hash
+                                .get(&fingerprint.hash)
+                                .map(|receipt_result| (receipt_result.clone(), fingerprint))
+                        })
                         .collect_vec();
 
                     accountant_recipient
@@ -708,6 +720,7 @@ mod tests {
                     .checked_sub(Duration::from_secs(1000))
                     .unwrap(),
                 pending_payable_opt: None,
+                written_off: false,
             },
             PayableAccount {
                 wallet: wallet_2.clone(),
@@ -716,6 +729,7 @@ mod tests {
                     .checked_sub(Duration::from_secs(500))
                     .unwrap(),
                 pending_payable_opt: None,
+                written_off: false,
             },
         ];
         let mut subject = BlockchainBridge::new(
@@ -878,6 +892,7 @@ mod tests {
             balance_wei: 111_420_204,
             last_paid_timestamp: from_time_t(150_000_000),
             pending_payable_opt: None,
+            written_off: false,
         }];
         let agent_id_stamp = ArbitraryIdStamp::new();
         let agent = BlockchainAgentMock::default()
@@ -931,7 +946,8 @@ mod tests {
                     "36e9d7cdd657181317dd461192d537d9944c57a51ee950607de5a618b00e57a1"
                 )
                 .unwrap(),
-                amount: accounts[0].balance_wei
+                amount: accounts[0].balance_wei,
+                recipient_address: accounts[0].wallet.address(),
             }]
         );
         assert_eq!(accountant_recording.len(), 2);
@@ -968,6 +984,7 @@ mod tests {
             balance_wei: 111_420_204,
             last_paid_timestamp: from_time_t(150_000_000),
             pending_payable_opt: None,
+            written_off: false,
         }];
         let consuming_wallet = make_paying_wallet(b"consuming_wallet");
         let agent = BlockchainAgentMock::default()
@@ -1007,7 +1024,8 @@ mod tests {
                     "36e9d7cdd657181317dd461192d537d9944c57a51ee950607de5a618b00e57a1"
                 )
                 .unwrap(),
-                amount: accounts[0].balance_wei
+                amount: accounts[0].balance_wei,
+                recipient_address: accounts[0].wallet.address(),
             }]
         );
         assert_eq!(
@@ -1229,6 +1247,82 @@ mod tests {
         );
     }
 
+    #[test]
+    fn blockchain_bridge_asks_once_for_the_receipt_of_a_multisend_transaction() {
+        let (accountant, _, accountant_recording_arc) = make_recorder();
+        let accountant = accountant.system_stop_conditions(match_every_type_id!(ScanError));
+        let multisend_hash = make_tx_hash(0x5e5e);
+        let single_hash = make_tx_hash(0x6f6f);
+        let make_fingerprint = |rowid: u64, hash: H256, amount: u128| PendingPayableFingerprint {
+            rowid,
+            timestamp: from_time_t(200_000_000),
+            hash,
+            attempt: 1,
+            amount,
+            process_error: None,
+        };
+        let fingerprint_1 = make_fingerprint(11, multisend_hash, 1000);
+        let fingerprint_2 = make_fingerprint(12, single_hash, 2000);
+        let fingerprint_3 = make_fingerprint(13, multisend_hash, 3000);
+        let multisend_response = ReceiptResponseBuilder::default()
+            .status(U64::from(1))
+            .transaction_hash(multisend_hash)
+            .build();
+        let port = find_free_port();
+        // Only two receipts are to be requested for the three fingerprints
+        let _blockchain_client_server = MBCSBuilder::new(port)
+            .begin_batch()
+            .raw_response(multisend_response)
+            .raw_response(r#"{ "jsonrpc": "2.0", "id": 1, "result": null }"#.to_string())
+            .end_batch()
+            .start();
+        let blockchain_interface = make_blockchain_interface_web3(port);
+        let subject = BlockchainBridge::new(
+            Box::new(blockchain_interface),
+            Arc::new(Mutex::new(PersistentConfigurationMock::default())),
+            false,
+        );
+        let addr = subject.start();
+        let subject_subs = BlockchainBridge::make_subs_from(&addr);
+        let peer_actors = peer_actors_builder().accountant(accountant).build();
+        send_bind_message!(subject_subs, peer_actors);
+        let msg = RequestTransactionReceipts {
+            pending_payable: vec![
+                fingerprint_1.clone(),
+                fingerprint_2.clone(),
+                fingerprint_3.clone(),
+            ],
+            response_skeleton_opt: None,
+        };
+
+        let _ = addr.try_send(msg).unwrap();
+
+        let system = System::new("multisend transaction receipt");
+        system.run();
+        let accountant_recording = accountant_recording_arc.lock().unwrap();
+        let mut expected_receipt = TransactionReceipt::default();
+        expected_receipt.transaction_hash = multisend_hash;
+        expected_receipt.status = Some(U64::from(1));
+        let multisend_receipt = TransactionReceiptResult::RpcResponse(expected_receipt.into());
+        assert_eq!(
+            accountant_recording.get_record::<ReportTransactionReceipts>(0),
+            &ReportTransactionReceipts {
+                fingerprints_with_receipts: vec![
+                    (multisend_receipt.clone(), fingerprint_1),
+                    (
+                        TransactionReceiptResult::RpcResponse(TxReceipt {
+                            transaction_hash: single_hash,
+                            status: TxStatus::Pending
+                        }),
+                        fingerprint_2
+                    ),
+                    (multisend_receipt, fingerprint_3),
+                ],
+                response_skeleton_opt: None,
+            }
+        );
+    }
+
     #[test]
     fn blockchain_bridge_logs_error_from_retrieving_received_payments() {
         init_test_logging();
@@ -1255,7 +1349,7 @@ mod tests {
         subject.scan_error_subs_opt = Some(scan_error_recipient);
         subject.received_payments_subs_opt = Some(received_payments_subs);
         let msg = RetrieveTransactions {
-            recipient: make_wallet("blah"),
+            recipients: vec![make_wallet("blah")],
             response_skeleton_opt: None,
         };
         let subject_addr = subject.start();
@@ -1524,7 +1618,7 @@ mod tests {
         );
         subject.received_payments_subs_opt = Some(accountant.start().recipient());
         let retrieve_transactions = RetrieveTransactions {
-            recipient: earning_wallet.clone(),
+            recipients: vec![earning_wallet.clone()],
             response_skeleton_opt: Some(ResponseSkeleton {
                 client_id: 1234,
                 context_id: 4321,
@@ -1549,6 +1643,10 @@ mod tests {
                     from: make_wallet("first_wallet"),
                     // Paid amount read out from the field 'data' in the RPC
                     wei_amount: 42,
+                    transaction_hash: H256::from_str(
+                        "3dc91b98249fa9f2c5c37486a2427a3a7825be240c1c84961dfb3063d9c04d50",
+                    )
+                    .unwrap(),
                 },
                 BlockchainTransaction {
                     block_number: 6040060,
@@ -1556,6 +1654,10 @@ mod tests {
                     from: make_wallet("second_wallet"),
                     // Paid amount read out from the field 'data' in the RPC
                     wei_amount: 55,
+                    transaction_hash: H256::from_str(
+                        "788b1442414cb9c9a36dba2abe250763161a6f6395788a2e808f1b34e92beec1",
+                    )
+                    .unwrap(),
                 },
             ],
         };
@@ -1585,7 +1687,8 @@ mod tests {
         );
         let port = find_free_port();
         let _blockchain_client_server = MBCSBuilder::new(port)
-            .ok_response("0x845FED".to_string(), 0)
+            // Polygon wants 128 confirmations: the head is that far past block 0x845FED
+            .ok_response("0x84606D".to_string(), 0)
             .ok_response(
                 vec![LogObject {
                     removed: false,
@@ -1625,6 +1728,10 @@ mod tests {
                 block_number: 8675308u64,
                 from: some_wallet.clone(),
                 wei_amount: amount,
+                transaction_hash: H256::from_str(
+                    "2222222222222222222222222222222222222222222222222222222222222222",
+                )
+                .unwrap(),
             }],
         };
         let blockchain_interface = make_blockchain_interface_web3(port);
@@ -1641,7 +1748,7 @@ mod tests {
         let peer_actors = peer_actors_builder().accountant(accountant_addr).build();
         send_bind_message!(subject_subs, peer_actors);
         let retrieve_transactions = RetrieveTransactions {
-            recipient: recipient_wallet.clone(),
+            recipients: vec![recipient_wallet.clone()],
             response_skeleton_opt: Some(ResponseSkeleton {
                 client_id: 1234,
                 context_id: 4321,
@@ -1725,7 +1832,7 @@ mod tests {
         let peer_actors = peer_actors_builder().accountant(accountant_addr).build();
         send_bind_message!(subject_subs, peer_actors);
         let retrieve_transactions = RetrieveTransactions {
-            recipient: earning_wallet.clone(),
+            recipients: vec![earning_wallet.clone()],
             response_skeleton_opt: Some(ResponseSkeleton {
                 client_id: 1234,
                 context_id: 4321,
@@ -1747,6 +1854,10 @@ mod tests {
                 block_number: 2000,
                 from: earning_wallet.clone(),
                 wei_amount: amount,
+                transaction_hash: H256::from_str(
+                    "2222222222222222222222222222222222222222222222222222222222222222",
+                )
+                .unwrap(),
             }],
         };
         assert_eq!(
@@ -1809,7 +1920,7 @@ mod tests {
         let peer_actors = peer_actors_builder().accountant(accountant_addr).build();
         send_bind_message!(subject_subs, peer_actors);
         let retrieve_transactions = RetrieveTransactions {
-            recipient: earning_wallet.clone(),
+            recipients: vec![earning_wallet.clone()],
             response_skeleton_opt: Some(ResponseSkeleton {
                 client_id: 1234,
                 context_id: 4321,
@@ -1869,7 +1980,7 @@ mod tests {
         let peer_actors = peer_actors_builder().accountant(accountant).build();
         send_bind_message!(subject_subs, peer_actors);
         let retrieve_transactions = RetrieveTransactions {
-            recipient: earning_wallet.clone(),
+            recipients: vec![earning_wallet.clone()],
             response_skeleton_opt: Some(ResponseSkeleton {
                 client_id: 1234,
                 context_id: 4321,
@@ -1931,7 +2042,7 @@ mod tests {
         let peer_actors = peer_actors_builder().accountant(accountant).build();
         send_bind_message!(subject_subs, peer_actors);
         let retrieve_transactions = RetrieveTransactions {
-            recipient: earning_wallet.clone(),
+            recipients: vec![earning_wallet.clone()],
             response_skeleton_opt: Some(ResponseSkeleton {
                 client_id: 1234,
                 context_id: 4321,
@@ -1956,7 +2067,7 @@ mod tests {
             false,
         );
         let retrieve_transactions = RetrieveTransactions {
-            recipient: make_wallet("somewallet"),
+            recipients: vec![make_wallet("somewallet")],
             response_skeleton_opt: None,
         };
 
@@ -2011,7 +2122,7 @@ mod tests {
             .start_block_result(Ok(start_block))
             .max_block_count_result(Ok(None));
         let retrieve_transactions = RetrieveTransactions {
-            recipient: wallet.clone(),
+            recipients: vec![wallet.clone()],
             response_skeleton_opt: Some(ResponseSkeleton {
                 client_id: 1234,
                 context_id: 4321,
@@ -2043,7 +2154,7 @@ mod tests {
     #[test]
     fn handle_scan_future_handles_failure() {
         assert_handle_scan_future_handles_failure(RetrieveTransactions {
-            recipient: make_wallet("somewallet"),
+            recipients: vec![make_wallet("somewallet")],
             response_skeleton_opt: Some(ResponseSkeleton {
                 client_id: 1234,
                 context_id: 4321,
@@ -2051,7 +2162,7 @@ mod tests {
         });
 
         assert_handle_scan_future_handles_failure(RetrieveTransactions {
-            recipient: make_wallet("somewallet"),
+            recipients: vec![make_wallet("somewallet")],
             response_skeleton_opt: None,
         });
     }
@@ -88,6 +88,24 @@ impl LowBlockchainInt for LowBlockchainIntWeb3 {
         )
     }
 
+    fn get_service_fee_allowance(
+        &self,
+        owner: Address,
+        spender: Address,
+    ) -> Box<dyn Future<Item = U256, Error = BlockchainError>> {
+        Box::new(
+            self.contract
+                .query(
+                    "allowance",
+                    (owner, spender),
+                    None,
+                    Options::default(),
+                    None,
+                )
+                .map_err(|e| QueryFailed(e.to_string())),
+        )
+    }
+
     fn get_gas_price(&self) -> Box<dyn Future<Item = U256, Error = BlockchainError>> {
         Box::new(
             self.web3
@@ -361,6 +379,33 @@ mod tests {
         assert_eq!(result, U256::from(65_535));
     }
 
+    #[test]
+    fn get_service_fee_allowance_can_retrieve_the_allowance_of_a_spender() {
+        let port = find_free_port();
+        let _blockchain_client_server = MBCSBuilder::new(port)
+            .ok_response(
+                "0x00000000000000000000000000000000000000000000000000000000000F4240".to_string(),
+                0,
+            )
+            .start();
+        let subject = make_blockchain_interface_web3(port);
+
+        let result = subject
+            .lower_interface()
+            .get_service_fee_allowance(
+                Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc")
+                    .unwrap()
+                    .address(),
+                Wallet::from_str("0x5c1b5f2a0c52f3a8e1d9b0d9a36b8e3f7a9b6c5d")
+                    .unwrap()
+                    .address(),
+            )
+            .wait()
+            .unwrap();
+
+        assert_eq!(result, U256::from(1_000_000));
+    }
+
     #[test]
     fn get_token_balance_returns_error_for_unintelligible_response_to_token_balance() {
         let port = find_free_port();
@@ -11,8 +11,9 @@ use crate::blockchain::blockchain_interface::lower_level_interface::LowBlockchai
 use crate::blockchain::blockchain_interface::RetrievedBlockchainTransactions;
 use crate::blockchain::blockchain_interface::{BlockchainAgentBuildError, BlockchainInterface};
 use crate::sub_lib::wallet::Wallet;
-use futures::{Future};
+use futures::{future, Future};
 use indoc::indoc;
+use itertools::Itertools;
 use masq_lib::blockchains::chains::Chain;
 use masq_lib::logger::Logger;
 use std::convert::{From, TryInto};
@@ -43,6 +44,14 @@ const CONTRACT_ABI: &str = indoc!(
     "payable":false,
     "stateMutability":"nonpayable",
     "type":"function"
+    },{
+    "constant":true,
+    "inputs":[{"name":"owner","type":"address"},{"name":"spender","type":"address"}],
+    "name":"allowance",
+    "outputs":[{"name":"","type":"uint256"}],
+    "payable":false,
+    "stateMutability":"view",
+    "type":"function"
     }]"#
 );
 
@@ -53,6 +62,9 @@ pub const TRANSACTION_LITERAL: H256 = H256([
 
 pub const TRANSFER_METHOD_ID: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
 
+// disperseToken(address,address[],uint256[]) of a Disperse-compatible multisend contract
+pub const MULTISEND_METHOD_ID: [u8; 4] = [0xc7, 0x3a, 0x2d, 0x60];
+
 pub const REQUESTS_IN_PARALLEL: usize = 1;
 
 pub const FRESH_START_BLOCK: u64 = 0;
@@ -97,22 +109,27 @@ impl BlockchainInterface for BlockchainInterfaceWeb3 {
         &self,
         start_block_marker: BlockMarker,
         scan_range: BlockScanRange,
-        recipient: Address,
+        recipients: Vec<Address>,
     ) -> Box<dyn Future<Item = RetrievedBlockchainTransactions, Error = BlockchainError>> {
         let lower_level_interface = self.lower_interface();
         let logger = self.logger.clone();
         let contract_address = lower_level_interface.get_contract_address();
         let num_chain_id = self.chain.rec().num_chain_id;
+        let confirmation_depth = self.chain.rec().confirmation_depth;
         Box::new(
-            lower_level_interface.get_block_number().then(move |rpc_block_number_result| {
+            lower_level_interface.get_block_number().then(move |rpc_block_number_result| -> Box<dyn Future<Item = RetrievedBlockchainTransactions, Error = BlockchainError>> {
+                let confirmed_block_number_result = Self::confirmed_block_number(rpc_block_number_result, confirmation_depth);
+                if let Some(retrieved) = Self::unscannable_retrieval_opt(start_block_marker, &confirmed_block_number_result, confirmation_depth, &logger) {
+                    return Box::new(future::ok(retrieved));
+                }
                 let start_block_number = match start_block_marker {
-                    BlockMarker::Uninitialized => match rpc_block_number_result {
-                        Ok(latest_block) => { BlockNumber::Number(latest_block) }
+                    BlockMarker::Uninitialized => match confirmed_block_number_result {
+                        Ok(confirmed_block) => { BlockNumber::Number(confirmed_block) }
                         Err(_) => { BlockNumber::Latest }
                     },
                     BlockMarker::Value(number) => BlockNumber::Number(U64::from(number)),
                 };
-                let end_block_marker = Self::calculate_end_block_marker(start_block_marker, scan_range, rpc_block_number_result, &logger);
+                let end_block_marker = Self::calculate_end_block_marker(start_block_marker, scan_range, confirmed_block_number_result, &logger);
                 let end_block_number = match end_block_marker {
                     BlockMarker::Uninitialized => { BlockNumber::Latest }
                     BlockMarker::Value(number) => { BlockNumber::Number(U64::from(number)) }
@@ -122,7 +139,7 @@ impl BlockchainInterface for BlockchainInterfaceWeb3 {
                     "Retrieving transactions from start block: {:?} to end block: {:?} for: {} chain_id: {} contract: {:#x}",
                     start_block_number,
                     end_block_number,
-                    recipient,
+                    recipients.iter().map(|recipient| recipient.to_string()).join(", "),
                     num_chain_id,
                     contract_address
                 );
@@ -133,16 +150,17 @@ impl BlockchainInterface for BlockchainInterfaceWeb3 {
                     .topics(
                         Some(vec![TRANSACTION_LITERAL]),
                         None,
-                        Some(vec![recipient.into()]),
+                        Some(recipients.iter().map(|recipient| (*recipient).into()).collect()),
                         None,
                     )
                     .build();
-                lower_level_interface.get_transaction_logs(filter)
+                Box::new(lower_level_interface.get_transaction_logs(filter)
                     .then(move |logs_result| {
                         trace!(logger, "Transaction logs retrieval completed: {:?}", logs_result);
                         match Self::handle_transaction_logs(logs_result, &logger) {
                             Err(e) => Err(e),
                             Ok(transactions) => {
+                                let transactions = Self::drop_unconfirmed_transactions(transactions, end_block_marker, &logger);
                                 let new_start_block = Self::find_new_start_block(&transactions, start_block_marker, end_block_marker, &logger);
                                 Ok(RetrievedBlockchainTransactions {
                                     new_start_block,
@@ -150,7 +168,7 @@ impl BlockchainInterface for BlockchainInterfaceWeb3 {
                                 })
                             }
                         }
-                    })
+                    }))
             },
             )
         )
@@ -256,11 +274,18 @@ impl BlockchainInterface for BlockchainInterfaceWeb3 {
             .get_transaction_id(consuming_wallet.address());
         let gas_price_wei = agent.agreed_fee_per_computation_unit();
         let chain = agent.get_chain();
+        let usable_multisend_contract = self.usable_multisend_contract(
+            logger.clone(),
+            chain,
+            consuming_wallet.address(),
+            &affordable_accounts,
+        );
 
         Box::new(
             get_transaction_id
                 .map_err(PayableTransactionError::TransactionID)
-                .and_then(move |pending_nonce| {
+                .join(usable_multisend_contract)
+                .and_then(move |(pending_nonce, multisend_contract_opt)| {
                     send_payables_within_batch(
                         &logger,
                         chain,
@@ -268,6 +293,7 @@ impl BlockchainInterface for BlockchainInterfaceWeb3 {
                         consuming_wallet,
                         gas_price_wei,
                         pending_nonce,
+                        multisend_contract_opt,
                         fingerprints_recipient,
                         affordable_accounts,
                     )
@@ -280,6 +306,7 @@ impl BlockchainInterface for BlockchainInterfaceWeb3 {
 pub struct HashAndAmount {
     pub hash: H256,
     pub amount: u128,
+    pub recipient_address: Address,
 }
 
 impl BlockchainInterfaceWeb3 {
@@ -296,12 +323,56 @@ impl BlockchainInterfaceWeb3 {
     }
 
     pub fn web3_gas_limit_const_part(chain: Chain) -> u128 {
-        match chain {
-            Chain::EthMainnet | Chain::EthRopsten | Chain::Dev => 55_000,
-            Chain::PolyMainnet | Chain::PolyAmoy | Chain::BaseMainnet | Chain::BaseSepolia => {
-                70_000
-            }
-        }
+        chain.rec().gas_limit_const_part
+    }
+
+    // The multisend contract pulls the tokens by transferFrom, so it's used only when it has
+    // been approved to spend all that's due; otherwise every payable gets a transfer of its own
+    fn usable_multisend_contract(
+        &self,
+        logger: Logger,
+        chain: Chain,
+        consuming_wallet_address: Address,
+        accounts: &[PayableAccount],
+    ) -> Box<dyn Future<Item = Option<Address>, Error = PayableTransactionError>> {
+        let multisend_contract = match chain.multisend_contract_opt() {
+            Some(multisend_contract) if accounts.len() > 1 => multisend_contract,
+            _ => return Box::new(future::ok(None)),
+        };
+        let total_wei = accounts.iter().fold(U256::zero(), |so_far, account| {
+            so_far + U256::from(account.balance_wei)
+        });
+        Box::new(
+            self.lower_interface()
+                .get_service_fee_allowance(consuming_wallet_address, multisend_contract)
+                .then(move |result| {
+                    Ok(match result {
+                        Ok(allowance) if allowance >= total_wei => Some(multisend_contract),
+                        Ok(allowance) => {
+                            warning!(
+                                logger,
+                                "Multisend contract {:?} is allowed to spend only {} wei of the \
+                                consuming wallet's tokens, but {} wei is due; paying each creditor \
+                                separately",
+                                multisend_contract,
+                                allowance,
+                                total_wei
+                            );
+                            None
+                        }
+                        Err(e) => {
+                            warning!(
+                                logger,
+                                "Couldn't learn the allowance of multisend contract {:?}: {:?}; \
+                                paying each creditor separately",
+                                multisend_contract,
+                                e
+                            );
+                            None
+                        }
+                    })
+                }),
+        )
     }
 
     fn extract_transactions_from_logs(logs: Vec<Log>) -> Vec<BlockchainTransaction> {
@@ -314,6 +385,7 @@ impl BlockchainInterfaceWeb3 {
                         block_number: block_number.as_u64(),
                         from: Wallet::from(log.topics[1]),
                         wei_amount,
+                        transaction_hash: log.transaction_hash.unwrap_or_default(),
                     })
                 }
             })
@@ -395,6 +467,74 @@ impl BlockchainInterfaceWeb3 {
         }
     }
 
+    // Blocks closer to the head of the chain than the confirmation depth may still be reorganized
+    // away, so the scan stops short of them and picks them up again on a later pass
+    fn confirmed_block_number(
+        rpc_block_number_result: Result<U64, BlockchainError>,
+        confirmation_depth: u64,
+    ) -> Result<U64, BlockchainError> {
+        rpc_block_number_result
+            .map(|latest_block| U64::from(latest_block.as_u64().saturating_sub(confirmation_depth)))
+    }
+
+    fn unscannable_retrieval_opt(
+        start_block_marker: BlockMarker,
+        confirmed_block_number_result: &Result<U64, BlockchainError>,
+        confirmation_depth: u64,
+        logger: &Logger,
+    ) -> Option<RetrievedBlockchainTransactions> {
+        match (start_block_marker, confirmed_block_number_result) {
+            (_, Err(e)) if confirmation_depth > 0 => {
+                debug!(
+                    logger,
+                    "Cannot tell which transactions are {} blocks deep without the latest block number: {:?}; \
+                    will try again on the next scan",
+                    confirmation_depth,
+                    e
+                );
+            }
+            (BlockMarker::Value(start_block), Ok(confirmed_block))
+                if start_block > confirmed_block.as_u64() =>
+            {
+                debug!(
+                    logger,
+                    "No blocks from start block {} onward are confirmed yet; the chain is confirmed up to block {}",
+                    start_block,
+                    confirmed_block
+                );
+            }
+            _ => return None,
+        }
+        Some(RetrievedBlockchainTransactions {
+            new_start_block: start_block_marker,
+            transactions: vec![],
+        })
+    }
+
+    fn drop_unconfirmed_transactions(
+        transactions: Vec<BlockchainTransaction>,
+        end_block_marker: BlockMarker,
+        logger: &Logger,
+    ) -> Vec<BlockchainTransaction> {
+        let end_block_number = match end_block_marker {
+            BlockMarker::Value(end_block_number) => end_block_number,
+            BlockMarker::Uninitialized => return transactions,
+        };
+        let (confirmed, unconfirmed): (Vec<_>, Vec<_>) = transactions
+            .into_iter()
+            .partition(|tx| tx.block_number <= end_block_number);
+        if !unconfirmed.is_empty() {
+            debug!(
+                logger,
+                "Ignoring {} transaction(s) past the confirmed end block {}: {:?}",
+                unconfirmed.len(),
+                end_block_number,
+                unconfirmed
+            );
+        }
+        confirmed
+    }
+
     fn handle_transaction_logs(
         logs_result: Result<Vec<Log>, BlockchainError>,
         logger: &Logger,
@@ -434,17 +574,18 @@ mod tests {
     use super::*;
     use crate::accountant::scanners::mid_scan_msg_handling::payable_scanner::agent_web3::WEB3_MAXIMAL_GAS_LIMIT_MARGIN;
     use crate::blockchain::blockchain_interface::blockchain_interface_web3::{
-        BlockchainInterfaceWeb3, CONTRACT_ABI, REQUESTS_IN_PARALLEL, TRANSACTION_LITERAL,
-        TRANSFER_METHOD_ID,
+        BlockchainInterfaceWeb3, CONTRACT_ABI, MULTISEND_METHOD_ID, REQUESTS_IN_PARALLEL,
+        TRANSACTION_LITERAL, TRANSFER_METHOD_ID,
     };
     use crate::blockchain::blockchain_interface::data_structures::errors::BlockchainError::QueryFailed;
+    use crate::accountant::test_utils::make_payable_account;
     use crate::blockchain::blockchain_interface::data_structures::BlockchainTransaction;
     use crate::blockchain::blockchain_interface::{
         BlockchainAgentBuildError, BlockchainError, BlockchainInterface,
         RetrievedBlockchainTransactions,
     };
     use crate::blockchain::test_utils::{
-        all_chains, make_blockchain_interface_web3, ReceiptResponseBuilder,
+        all_chains, make_blockchain_interface_web3, make_tx_hash, ReceiptResponseBuilder,
     };
     use crate::sub_lib::blockchain_bridge::ConsumingWalletBalances;
     use crate::sub_lib::wallet::Wallet;
@@ -453,6 +594,7 @@ mod tests {
     use ethsign_crypto::Keccak256;
     use futures::Future;
     use masq_lib::blockchains::chains::Chain;
+    use masq_lib::blockchains::custom_chains::{register_custom_chain, CustomChainDefinition};
     use masq_lib::test_utils::logging::{init_test_logging, TestLogHandler};
     use masq_lib::test_utils::mock_blockchain_client_server::MBCSBuilder;
     use masq_lib::test_utils::utils::TEST_DEFAULT_CHAIN;
@@ -482,6 +624,14 @@ mod tests {
             "payable":false,
             "stateMutability":"nonpayable",
             "type":"function"
+            },{
+            "constant":true,
+            "inputs":[{"name":"owner","type":"address"},{"name":"spender","type":"address"}],
+            "name":"allowance",
+            "outputs":[{"name":"","type":"uint256"}],
+            "payable":false,
+            "stateMutability":"view",
+            "type":"function"
             }]"#
         );
         let transaction_literal_expected: H256 = H256 {
@@ -499,6 +649,11 @@ mod tests {
             TRANSFER_METHOD_ID,
             "transfer(address,uint256)".keccak256()[0..4],
         );
+        assert_eq!(MULTISEND_METHOD_ID, [0xc7, 0x3a, 0x2d, 0x60]);
+        assert_eq!(
+            MULTISEND_METHOD_ID,
+            "disperseToken(address,address[],uint256[])".keccak256()[0..4],
+        );
         assert_eq!(FRESH_START_BLOCK, 0);
     }
 
@@ -525,20 +680,22 @@ mod tests {
             block_response,
             expected_new_start_block,
             expected_log,
+            Chain::PolyMainnet,
             "all_values_are_present",
         );
 
         let start_block_marker = BlockMarker::Uninitialized;
         let scan_range = BlockScanRange::Range(1000);
         let block_response = "0xe2f432"; // 14_873_650
-        let expected_new_start_block = BlockMarker::Value(14_873_650 + 1);
-        let expected_log = "from start block: Number(14873650) to end block: Number(14873650)";
+        let expected_new_start_block = BlockMarker::Value(14_873_650 - 128 + 1);
+        let expected_log = "from start block: Number(14873522) to end block: Number(14873522)";
         assert_on_retrieves_transactions(
             start_block_marker,
             scan_range,
             block_response,
             expected_new_start_block,
             expected_log,
+            Chain::PolyMainnet,
             "start_block_is_missing",
         );
 
@@ -553,20 +710,22 @@ mod tests {
             block_response,
             expected_new_start_block,
             expected_log,
+            Chain::Dev,
             "start_block_is_missing",
         );
 
         let start_block_marker = BlockMarker::Value(42);
         let scan_range = BlockScanRange::NoLimit;
         let block_response = "0x7d0"; // 2_000
-        let expected_new_start_block = BlockMarker::Value(2_000 + 1);
-        let expected_log = "from start block: Number(42) to end block: Number(2000)";
+        let expected_new_start_block = BlockMarker::Value(2_000 - 128 + 1);
+        let expected_log = "from start block: Number(42) to end block: Number(1872)";
         assert_on_retrieves_transactions(
             start_block_marker,
             scan_range,
             block_response,
             expected_new_start_block,
             expected_log,
+            Chain::PolyMainnet,
             "scan_limit_is_missing",
         );
 
@@ -581,6 +740,7 @@ mod tests {
             block_response,
             expected_new_start_block,
             expected_log,
+            Chain::Dev,
             "scan_limit_and_blockchain_response_is_unavailable",
         );
     }
@@ -591,6 +751,7 @@ mod tests {
         block_response: &str,
         expected_new_start_block: BlockMarker,
         expected_log: &str,
+        chain: Chain,
         test_case: &str,
     ) {
         init_test_logging();
@@ -639,13 +800,14 @@ mod tests {
             )
             .start();
         let mut subject = make_blockchain_interface_web3(port);
+        subject.chain = chain;
         subject.logger = Logger::new(test_case);
 
         let result = subject
             .retrieve_transactions(
                 start_block_marker,
                 scan_range,
-                Wallet::from_str(&to).unwrap().address(),
+                vec![Wallet::from_str(&to).unwrap().address()],
             )
             .wait()
             .unwrap();
@@ -660,17 +822,29 @@ mod tests {
                         from: Wallet::from_str("0x3ab28ecedea6cdb6feed398e93ae8c7b316b1182")
                             .unwrap(),
                         wei_amount: 4_503_599_627_370_496u128,
+                        transaction_hash: H256::from_str(
+                            "955cec6ac4f832911ab894ce16aa22c3003f46deff3f7165b32700d2f5ff0681"
+                        )
+                        .unwrap(),
                     },
                     BlockchainTransaction {
                         block_number: 48,
                         from: Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc")
                             .unwrap(),
                         wei_amount: 4_503_599_627_370_496u128,
+                        transaction_hash: H256::from_str(
+                            "955cec6ac4f832911ab894ce16aa22c3003f46deff3f7165b32700d2f5ff0680"
+                        )
+                        .unwrap(),
                     },
                 ]
             }
         );
-        TestLogHandler::new().exists_log_containing(&format!("DEBUG: {test_case}: Retrieving transactions {expected_log} for: 0x3f69…72fc chain_id: 137 contract: 0xee9a352f6aac4af1a5b9f467f6a93e0ffbe9dd35"));
+        TestLogHandler::new().exists_log_containing(&format!(
+            "DEBUG: {test_case}: Retrieving transactions {expected_log} for: 0x3f69…72fc chain_id: {} contract: {:#x}",
+            chain.rec().num_chain_id,
+            chain.rec().contract
+        ));
     }
 
     #[test]
@@ -688,14 +862,14 @@ mod tests {
             .retrieve_transactions(
                 BlockMarker::Value(42),
                 BlockScanRange::NoLimit,
-                to_wallet.address(),
+                vec![to_wallet.address()],
             )
             .wait();
 
         assert_eq!(
             result,
             Ok(RetrievedBlockchainTransactions {
-                new_start_block: BlockMarker::Value(1543664),
+                new_start_block: BlockMarker::Value(1543663 - 128 + 1),
                 transactions: vec![]
             })
         );
@@ -723,9 +897,11 @@ mod tests {
             .retrieve_transactions(
                 BlockMarker::Value(42),
                 BlockScanRange::NoLimit,
-                Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc")
-                    .unwrap()
-                    .address(),
+                vec![
+                    Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc")
+                        .unwrap()
+                        .address(),
+                ],
             )
             .wait();
 
@@ -749,9 +925,11 @@ mod tests {
             .retrieve_transactions(
                 BlockMarker::Uninitialized,
                 BlockScanRange::NoLimit,
-                Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc")
-                    .unwrap()
-                    .address(),
+                vec![
+                    Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc")
+                        .unwrap()
+                        .address(),
+                ],
             )
             .wait();
 
@@ -773,7 +951,8 @@ mod tests {
         )
         .unwrap();
 
-        let end_block_nbr = BlockMarker::Value(1025u64);
+        // 1024 is the latest block; Base Sepolia wants ten confirmations
+        let end_block_nbr = BlockMarker::Value(1024u64 - 10 + 1);
         let subject =
             BlockchainInterfaceWeb3::new(transport, event_loop_handle, TEST_DEFAULT_CHAIN);
 
@@ -781,9 +960,11 @@ mod tests {
             .retrieve_transactions(
                 BlockMarker::Value(42),
                 BlockScanRange::Range(1000),
-                Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc")
-                    .unwrap()
-                    .address(),
+                vec![
+                    Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc")
+                        .unwrap()
+                        .address(),
+                ],
             )
             .wait();
 
@@ -808,7 +989,8 @@ mod tests {
             .ok_response("trash", 1)
             .raw_response(r#"{"jsonrpc":"2.0","id":2,"result":[{"address":"0xcd6c588e005032dd882cd43bf53a32129be81302","blockHash":"0x1a24b9169cbaec3f6effa1f600b70c7ab9e8e86db44062b49132a4415d26732a","data":"0x0000000000000000000000000000000000000000000000000010000000000000","logIndex":"0x0","removed":false,"topics":["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef","0x0000000000000000000000003f69f9efd4f2592fd70be8c32ecd9dce71c472fc","0x000000000000000000000000adc1853c7859369639eb414b6342b36288fe6092"],"transactionHash":"0x955cec6ac4f832911ab894ce16aa22c3003f46deff3f7165b32700d2f5ff0681","transactionIndex":"0x0"}]}"#.to_string())
             .start();
-        let subject = make_blockchain_interface_web3(port);
+        let mut subject = make_blockchain_interface_web3(port);
+        subject.chain = Chain::Dev;
         let start_block = 42u64;
         let fallback_number = start_block;
 
@@ -816,9 +998,11 @@ mod tests {
             .retrieve_transactions(
                 BlockMarker::Value(42),
                 BlockScanRange::NoLimit,
-                Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc")
-                    .unwrap()
-                    .address(),
+                vec![
+                    Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc")
+                        .unwrap()
+                        .address(),
+                ],
             )
             .wait();
 
@@ -832,6 +1016,141 @@ mod tests {
         );
     }
 
+    #[test]
+    fn retrieve_transactions_makes_no_progress_without_latest_block_number_if_confirmations_are_required(
+    ) {
+        init_test_logging();
+        let test_name = "retrieve_transactions_makes_no_progress_without_latest_block_number_if_confirmations_are_required";
+        let port = find_free_port();
+        let _blockchain_client_server = MBCSBuilder::new(port).ok_response("trash", 1).start();
+        let mut subject = make_blockchain_interface_web3(port);
+        subject.logger = Logger::new(test_name);
+
+        let result = subject
+            .retrieve_transactions(
+                BlockMarker::Value(42),
+                BlockScanRange::NoLimit,
+                vec![
+                    Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc")
+                        .unwrap()
+                        .address(),
+                ],
+            )
+            .wait();
+
+        assert_eq!(
+            result,
+            Ok(RetrievedBlockchainTransactions {
+                new_start_block: BlockMarker::Value(42),
+                transactions: vec![]
+            })
+        );
+        TestLogHandler::new().exists_log_containing(&format!(
+            "DEBUG: {test_name}: Cannot tell which transactions are 128 blocks deep without the latest block number:"
+        ));
+    }
+
+    #[test]
+    fn retrieve_transactions_waits_until_start_block_is_confirmed() {
+        init_test_logging();
+        let test_name = "retrieve_transactions_waits_until_start_block_is_confirmed";
+        let port = find_free_port();
+        let _blockchain_client_server = MBCSBuilder::new(port)
+            .ok_response("0x7d0", 1) // 2_000
+            .start();
+        let mut subject = make_blockchain_interface_web3(port);
+        subject.logger = Logger::new(test_name);
+
+        let result = subject
+            .retrieve_transactions(
+                BlockMarker::Value(1900),
+                BlockScanRange::Range(1000),
+                vec![
+                    Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc")
+                        .unwrap()
+                        .address(),
+                ],
+            )
+            .wait();
+
+        assert_eq!(
+            result,
+            Ok(RetrievedBlockchainTransactions {
+                new_start_block: BlockMarker::Value(1900),
+                transactions: vec![]
+            })
+        );
+        TestLogHandler::new().exists_log_containing(&format!(
+            "DEBUG: {test_name}: No blocks from start block 1900 onward are confirmed yet; the chain is confirmed up to block 1872"
+        ));
+    }
+
+    #[test]
+    fn retrieve_transactions_ignores_transactions_past_the_confirmed_end_block() {
+        init_test_logging();
+        let test_name = "retrieve_transactions_ignores_transactions_past_the_confirmed_end_block";
+        let port = find_free_port();
+        let _blockchain_client_server = MBCSBuilder::new(port)
+            .ok_response("0x7d0", 1) // 2_000
+            .raw_response(r#"{"jsonrpc":"2.0","id":2,"result":[{"address":"0xcd6c588e005032dd882cd43bf53a32129be81302","blockHash":"0x1a24b9169cbaec3f6effa1f600b70c7ab9e8e86db44062b49132a4415d26732a","blockNumber":"0x2e","data":"0x0000000000000000000000000000000000000000000000000010000000000000","logIndex":"0x0","removed":false,"topics":["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef","0x0000000000000000000000003ab28ecedea6cdb6feed398e93ae8c7b316b1182","0x000000000000000000000000adc1853c7859369639eb414b6342b36288fe6092"],"transactionHash":"0x955cec6ac4f832911ab894ce16aa22c3003f46deff3f7165b32700d2f5ff0681","transactionIndex":"0x0"},{"address":"0xcd6c588e005032dd882cd43bf53a32129be81302","blockHash":"0x1a24b9169cbaec3f6effa1f600b70c7ab9e8e86db44062b49132a4415d26732b","blockNumber":"0x7cf","data":"0x0000000000000000000000000000000000000000000000000010000000000000","logIndex":"0x0","removed":false,"topics":["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef","0x0000000000000000000000003f69f9efd4f2592fd70be8c32ecd9dce71c472fc","0x000000000000000000000000adc1853c7859369639eb414b6342b36288fe6092"],"transactionHash":"0x955cec6ac4f832911ab894ce16aa22c3003f46deff3f7165b32700d2f5ff0680","transactionIndex":"0x0"}]}"#.to_string())
+            .start();
+        let mut subject = make_blockchain_interface_web3(port);
+        subject.logger = Logger::new(test_name);
+
+        let result = subject
+            .retrieve_transactions(
+                BlockMarker::Value(42),
+                BlockScanRange::NoLimit,
+                vec![
+                    Wallet::from_str("0x3f69f9efd4f2592fd70be8c32ecd9dce71c472fc")
+                        .unwrap()
+                        .address(),
+                ],
+            )
+            .wait();
+
+        assert_eq!(
+            result,
+            Ok(RetrievedBlockchainTransactions {
+                new_start_block: BlockMarker::Value(1873),
+                transactions: vec![BlockchainTransaction {
+                    block_number: 46,
+                    from: Wallet::from_str("0x3ab28ecedea6cdb6feed398e93ae8c7b316b1182").unwrap(),
+                    wei_amount: 4_503_599_627_370_496u128,
+                    transaction_hash: H256::from_str(
+                        "955cec6ac4f832911ab894ce16aa22c3003f46deff3f7165b32700d2f5ff0681"
+                    )
+                    .unwrap(),
+                }]
+            })
+        );
+        TestLogHandler::new().exists_log_containing(&format!(
+            "DEBUG: {test_name}: Ignoring 1 transaction(s) past the confirmed end block 1872:"
+        ));
+    }
+
+    #[test]
+    fn confirmed_block_number_stays_the_confirmation_depth_behind_the_latest_block() {
+        type Subject = BlockchainInterfaceWeb3;
+
+        assert_eq!(
+            Subject::confirmed_block_number(Ok(U64::from(2000)), 128),
+            Ok(U64::from(1872))
+        );
+        assert_eq!(
+            Subject::confirmed_block_number(Ok(U64::from(2000)), 0),
+            Ok(U64::from(2000))
+        );
+        assert_eq!(
+            Subject::confirmed_block_number(Ok(U64::from(100)), 128),
+            Ok(U64::from(0))
+        );
+        assert_eq!(
+            Subject::confirmed_block_number(Err(BlockchainError::InvalidResponse), 128),
+            Err(BlockchainError::InvalidResponse)
+        );
+    }
+
     #[test]
     fn blockchain_interface_web3_can_build_blockchain_agent() {
         let port = find_free_port();
@@ -1111,6 +1430,166 @@ mod tests {
         assert_eq!(Subject::web3_gas_limit_const_part(Chain::Dev), 55_000);
     }
 
+    #[test]
+    fn web3_gas_limit_const_part_comes_from_custom_chain_definitions() {
+        let chain = register_custom_chain(CustomChainDefinition {
+            name: "gas-limit-devnet".to_string(),
+            chain_id: 31347,
+            contract: Default::default(),
+            contract_creation_block: 0,
+            confirmation_depth: 0,
+            gas_limit_const_part: 123_456,
+            multisend_contract: None,
+        })
+        .unwrap();
+
+        let result = BlockchainInterfaceWeb3::web3_gas_limit_const_part(chain);
+
+        assert_eq!(result, 123_456);
+    }
+
+    fn register_multisend_chain(name: &str, chain_id: u64, multisend_contract: Address) -> Chain {
+        register_custom_chain(CustomChainDefinition {
+            name: name.to_string(),
+            chain_id,
+            contract: Default::default(),
+            contract_creation_block: 0,
+            confirmation_depth: 0,
+            gas_limit_const_part: 55_000,
+            multisend_contract: Some(multisend_contract),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn usable_multisend_contract_is_the_one_allowed_to_spend_all_thats_due() {
+        let multisend_contract = Address::from_low_u64_be(0xd15e);
+        let chain = register_multisend_chain("allowance-devnet", 31352, multisend_contract);
+        let port = find_free_port();
+        // 3_000 wei is due; exactly that much is allowed
+        let _blockchain_client_server = MBCSBuilder::new(port)
+            .ok_response(
+                "0x0000000000000000000000000000000000000000000000000000000000000BB8".to_string(),
+                0,
+            )
+            .start();
+        let subject = make_blockchain_interface_web3(port);
+        let accounts = vec![make_payable_account(1), make_payable_account(2)]
+            .into_iter()
+            .map(|mut account| {
+                account.balance_wei = 1_500;
+                account
+            })
+            .collect::<Vec<_>>();
+
+        let result = subject
+            .usable_multisend_contract(
+                Logger::new("test"),
+                chain,
+                make_wallet("consuming").address(),
+                &accounts,
+            )
+            .wait();
+
+        assert_eq!(result, Ok(Some(multisend_contract)));
+    }
+
+    #[test]
+    fn usable_multisend_contract_is_refused_if_its_allowance_falls_short() {
+        init_test_logging();
+        let test_name = "usable_multisend_contract_is_refused_if_its_allowance_falls_short";
+        let multisend_contract = Address::from_low_u64_be(0xd15f);
+        let chain = register_multisend_chain("short-allowance-devnet", 31353, multisend_contract);
+        let port = find_free_port();
+        let _blockchain_client_server = MBCSBuilder::new(port)
+            .ok_response(
+                "0x0000000000000000000000000000000000000000000000000000000000000BB7".to_string(),
+                0,
+            )
+            .start();
+        let subject = make_blockchain_interface_web3(port);
+        let accounts = vec![make_payable_account(1), make_payable_account(2)]
+            .into_iter()
+            .map(|mut account| {
+                account.balance_wei = 1_500;
+                account
+            })
+            .collect::<Vec<_>>();
+
+        let result = subject
+            .usable_multisend_contract(
+                Logger::new(test_name),
+                chain,
+                make_wallet("consuming").address(),
+                &accounts,
+            )
+            .wait();
+
+        assert_eq!(result, Ok(None));
+        TestLogHandler::new().exists_log_containing(&format!(
+            "WARN: {}: Multisend contract {:?} is allowed to spend only 2999 wei of the consuming \
+            wallet's tokens, but 3000 wei is due; paying each creditor separately",
+            test_name, multisend_contract
+        ));
+    }
+
+    #[test]
+    fn usable_multisend_contract_is_refused_if_its_allowance_is_unknown() {
+        init_test_logging();
+        let test_name = "usable_multisend_contract_is_refused_if_its_allowance_is_unknown";
+        let multisend_contract = Address::from_low_u64_be(0xd160);
+        let chain = register_multisend_chain("unknown-allowance-devnet", 31354, multisend_contract);
+        let port = find_free_port();
+        let _blockchain_client_server = MBCSBuilder::new(port)
+            .ok_response("0xNOTAHEXNUMBER".to_string(), 0)
+            .start();
+        let subject = make_blockchain_interface_web3(port);
+        let accounts = vec![make_payable_account(1), make_payable_account(2)];
+
+        let result = subject
+            .usable_multisend_contract(
+                Logger::new(test_name),
+                chain,
+                make_wallet("consuming").address(),
+                &accounts,
+            )
+            .wait();
+
+        assert_eq!(result, Ok(None));
+        TestLogHandler::new().exists_log_containing(&format!(
+            "WARN: {}: Couldn't learn the allowance of multisend contract {:?}: QueryFailed(",
+            test_name, multisend_contract
+        ));
+    }
+
+    #[test]
+    fn usable_multisend_contract_is_not_wanted_for_a_single_payable_or_without_a_contract() {
+        let multisend_contract = Address::from_low_u64_be(0xd161);
+        let chain = register_multisend_chain("single-payable-devnet", 31355, multisend_contract);
+        // Nothing listens here; no allowance is to be asked for
+        let subject = make_blockchain_interface_web3(find_free_port());
+
+        let single = subject
+            .usable_multisend_contract(
+                Logger::new("test"),
+                chain,
+                make_wallet("consuming").address(),
+                &[make_payable_account(1)],
+            )
+            .wait();
+        let without_contract = subject
+            .usable_multisend_contract(
+                Logger::new("test"),
+                TEST_DEFAULT_CHAIN,
+                make_wallet("consuming").address(),
+                &[make_payable_account(1), make_payable_account(2)],
+            )
+            .wait();
+
+        assert_eq!(single, Ok(None));
+        assert_eq!(without_contract, Ok(None));
+    }
+
     #[test]
     fn calculate_end_block_marker_works() {
         let logger = Logger::new("calculate_end_block_marker_works");
@@ -1209,11 +1688,13 @@ mod tests {
                 block_number: 10,
                 from: make_wallet("wallet_1"),
                 wei_amount: 1000,
+                transaction_hash: make_tx_hash(10),
             },
             BlockchainTransaction {
                 block_number: 60,
                 from: make_wallet("wallet_1"),
                 wei_amount: 500,
+                transaction_hash: make_tx_hash(60),
             },
         ];
 
@@ -6,7 +6,7 @@ use crate::accountant::scanners::mid_scan_msg_handling::payable_scanner::agent_w
 use crate::accountant::scanners::mid_scan_msg_handling::payable_scanner::blockchain_agent::BlockchainAgent;
 use crate::blockchain::blockchain_bridge::PendingPayableFingerprintSeeds;
 use crate::blockchain::blockchain_interface::blockchain_interface_web3::{
-    BlockchainInterfaceWeb3, HashAndAmount, TRANSFER_METHOD_ID,
+    BlockchainInterfaceWeb3, HashAndAmount, MULTISEND_METHOD_ID, TRANSFER_METHOD_ID,
 };
 use crate::blockchain::blockchain_interface::data_structures::errors::PayableTransactionError;
 use crate::blockchain::blockchain_interface::data_structures::{
@@ -24,7 +24,7 @@ use std::iter::once;
 use std::time::SystemTime;
 use thousands::Separable;
 use web3::transports::{Batch, Http};
-use web3::types::{Bytes, SignedTransaction, TransactionParameters, U256};
+use web3::types::{Address, Bytes, SignedTransaction, TransactionParameters, H256, U256};
 use web3::Error as Web3Error;
 use web3::Web3;
 
@@ -59,27 +59,39 @@ pub fn merged_output_data(
     hashes_and_paid_amounts: Vec<HashAndAmount>,
     accounts: Vec<PayableAccount>,
 ) -> Vec<ProcessedPayableFallible> {
-    let iterator_with_all_data = responses
+    // There is one response per transaction, but a multisend transaction covers several
+    // payables in a row, all with the same hash
+    let mut responses = responses.into_iter();
+    let mut current_response_opt: Option<(H256, web3::transports::Result<Value>)> = None;
+    hashes_and_paid_amounts
         .into_iter()
-        .zip(hashes_and_paid_amounts.into_iter())
-        .zip(accounts.iter());
-    iterator_with_all_data
-        .map(
-            |((rpc_result, hash_and_amount), account)| match rpc_result {
-                Ok(_rpc_result) => {
-                    // TODO: GH-547: This rpc_result should be validated
-                    ProcessedPayableFallible::Correct(PendingPayable {
+        .zip(accounts.iter())
+        .flat_map(|(hash_and_amount, account)| {
+            let hash = hash_and_amount.hash;
+            if current_response_opt
+                .as_ref()
+                .map(|(current_hash, _)| *current_hash)
+                != Some(hash)
+            {
+                current_response_opt = responses.next().map(|response| (hash, response));
+            }
+            current_response_opt
+                .as_ref()
+                .map(|(_, rpc_result)| match rpc_result {
+                    Ok(_rpc_result) => {
+                        // TODO: GH-547: This rpc_result should be validated
+                        ProcessedPayableFallible::Correct(PendingPayable {
+                            recipient_wallet: account.wallet.clone(),
+                            hash,
+                        })
+                    }
+                    Err(rpc_error) => ProcessedPayableFallible::Failed(RpcPayableFailure {
+                        rpc_error: rpc_error.clone(),
                         recipient_wallet: account.wallet.clone(),
-                        hash: hash_and_amount.hash,
-                    })
-                }
-                Err(rpc_error) => ProcessedPayableFallible::Failed(RpcPayableFailure {
-                    rpc_error,
-                    recipient_wallet: account.wallet.clone(),
-                    hash: hash_and_amount.hash,
-                }),
-            },
-        )
+                        hash,
+                    }),
+                })
+        })
         .collect()
 }
 
@@ -126,6 +138,58 @@ pub fn sign_transaction_data(amount: u128, recipient_wallet: Wallet) -> [u8; 68]
 
 pub fn gas_limit(data: [u8; 68], chain: Chain) -> U256 {
     let base_gas_limit = BlockchainInterfaceWeb3::web3_gas_limit_const_part(chain);
+    gas_limit_with_data(base_gas_limit, &data)
+}
+
+pub fn multisend_transaction_data(token_contract: Address, accounts: &[PayableAccount]) -> Vec<u8> {
+    fn address_word(address: Address) -> [u8; 32] {
+        let mut word = [0u8; 32];
+        word[12..32].copy_from_slice(&address.0[..]);
+        word
+    }
+    fn number_word(number: U256) -> [u8; 32] {
+        let mut word = [0u8; 32];
+        number.to_big_endian(&mut word);
+        word
+    }
+
+    // The token address is followed by the offsets of the two arrays, which come right after
+    let recipients_offset = U256::from(3 * 32);
+    let values_offset = U256::from((3 + 1 + accounts.len()) * 32);
+    let count = U256::from(accounts.len());
+    let head = vec![
+        address_word(token_contract),
+        number_word(recipients_offset),
+        number_word(values_offset),
+        number_word(count),
+    ];
+    let recipients = accounts
+        .iter()
+        .map(|account| address_word(account.wallet.address()));
+    let values = accounts
+        .iter()
+        .map(|account| number_word(U256::from(account.balance_wei)));
+    MULTISEND_METHOD_ID
+        .iter()
+        .copied()
+        .chain(
+            head.into_iter()
+                .chain(recipients)
+                .chain(once(number_word(count)))
+                .chain(values)
+                .flatten(),
+        )
+        .collect()
+}
+
+pub fn multisend_gas_limit(data: &[u8], recipients_count: usize, chain: Chain) -> U256 {
+    // Each recipient is counted as if they were paid by a standalone transfer
+    let base_gas_limit =
+        BlockchainInterfaceWeb3::web3_gas_limit_const_part(chain) * recipients_count as u128;
+    gas_limit_with_data(base_gas_limit, data)
+}
+
+fn gas_limit_with_data(base_gas_limit: u128, data: &[u8]) -> U256 {
     ethereum_types::U256::try_from(data.iter().fold(base_gas_limit, |acc, v| {
         acc + if v == &0u8 { 4 } else { 68 }
     }))
@@ -160,6 +224,33 @@ pub fn sign_transaction(
     sign_transaction_locally(web3_batch, transaction_parameters, &key)
 }
 
+pub fn sign_multisend_transaction(
+    chain: Chain,
+    web3_batch: &Web3<Batch<Http>>,
+    multisend_contract: Address,
+    consuming_wallet: Wallet,
+    accounts: &[PayableAccount],
+    nonce: U256,
+    gas_price_in_wei: u128,
+) -> SignedTransaction {
+    let data = multisend_transaction_data(chain.rec().contract, accounts);
+    let gas_limit = multisend_gas_limit(&data, accounts.len(), chain);
+    let transaction_parameters = TransactionParameters {
+        nonce: Some(nonce),
+        to: Some(multisend_contract),
+        gas: gas_limit,
+        gas_price: Some(U256::from(gas_price_in_wei)),
+        value: ethereum_types::U256::zero(),
+        data: Bytes(data),
+        chain_id: Some(chain.rec().num_chain_id),
+    };
+    let key = consuming_wallet
+        .prepare_secp256k1_secret()
+        .expect("Consuming wallet doesn't contain a secret key");
+
+    sign_transaction_locally(web3_batch, transaction_parameters, &key)
+}
+
 pub fn sign_transaction_locally(
     web3_batch: &Web3<Batch<Http>>,
     transaction_parameters: TransactionParameters,
@@ -202,6 +293,7 @@ pub fn sign_and_append_payment(
     HashAndAmount {
         hash: signed_tx.transaction_hash,
         amount: recipient.balance_wei,
+        recipient_address: recipient.wallet.address(),
     }
 }
 
@@ -244,6 +336,52 @@ pub fn sign_and_append_multiple_payments(
     hash_and_amount_list
 }
 
+// The caller is to make sure the multisend contract has been approved a sufficient allowance
+#[allow(clippy::too_many_arguments)]
+pub fn sign_and_append_multisend_payment(
+    logger: &Logger,
+    chain: Chain,
+    web3_batch: &Web3<Batch<Http>>,
+    multisend_contract: Address,
+    consuming_wallet: Wallet,
+    gas_price_in_wei: u128,
+    pending_nonce: U256,
+    accounts: &[PayableAccount],
+) -> Vec<HashAndAmount> {
+    debug!(
+        logger,
+        "Preparing multisend payable future of {} wei to {} creditors through {:?} with nonce {}",
+        accounts
+            .iter()
+            .map(|payable| payable.balance_wei)
+            .sum::<u128>()
+            .separate_with_commas(),
+        accounts.len(),
+        multisend_contract,
+        pending_nonce
+    );
+
+    let signed_tx = sign_multisend_transaction(
+        chain,
+        web3_batch,
+        multisend_contract,
+        consuming_wallet,
+        accounts,
+        pending_nonce,
+        gas_price_in_wei,
+    );
+    append_signed_transaction_to_batch(web3_batch, signed_tx.raw_transaction);
+
+    accounts
+        .iter()
+        .map(|payable| HashAndAmount {
+            hash: signed_tx.transaction_hash,
+            amount: payable.balance_wei,
+            recipient_address: payable.wallet.address(),
+        })
+        .collect()
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn send_payables_within_batch(
     logger: &Logger,
@@ -252,6 +390,7 @@ pub fn send_payables_within_batch(
     consuming_wallet: Wallet,
     gas_price_in_wei: u128,
     pending_nonce: U256,
+    multisend_contract_opt: Option<Address>,
     new_fingerprints_recipient: Recipient<PendingPayableFingerprintSeeds>,
     accounts: Vec<PayableAccount>,
 ) -> Box<dyn Future<Item = Vec<ProcessedPayableFallible>, Error = PayableTransactionError> + 'static>
@@ -265,15 +404,27 @@ pub fn send_payables_within_batch(
             gas_price_in_wei
         );
 
-    let hashes_and_paid_amounts = sign_and_append_multiple_payments(
-        logger,
-        chain,
-        web3_batch,
-        consuming_wallet,
-        gas_price_in_wei,
-        pending_nonce,
-        &accounts,
-    );
+    let hashes_and_paid_amounts = match multisend_contract_opt {
+        Some(multisend_contract) => sign_and_append_multisend_payment(
+            logger,
+            chain,
+            web3_batch,
+            multisend_contract,
+            consuming_wallet,
+            gas_price_in_wei,
+            pending_nonce,
+            &accounts,
+        ),
+        _ => sign_and_append_multiple_payments(
+            logger,
+            chain,
+            web3_batch,
+            consuming_wallet,
+            gas_price_in_wei,
+            pending_nonce,
+            &accounts,
+        ),
+    };
 
     let timestamp = SystemTime::now();
     let hashes_and_paid_amounts_error = hashes_and_paid_amounts.clone();
@@ -409,7 +560,8 @@ mod tests {
                     "1931f78f7ce5b43ffae11a2c22f18765508a2b2d4810e84744f53b10f7072c7f"
                 )
                 .unwrap(),
-                amount: account.balance_wei
+                amount: account.balance_wei,
+                recipient_address: account.wallet.address(),
             }
         );
         assert_eq!(
@@ -436,7 +588,7 @@ mod tests {
         let consuming_wallet = make_paying_wallet(b"paying_wallet");
         let account_1 = make_payable_account(1);
         let account_2 = make_payable_account(2);
-        let accounts = vec![account_1, account_2];
+        let accounts = vec![account_1.clone(), account_2.clone()];
 
         let result = sign_and_append_multiple_payments(
             &logger,
@@ -456,14 +608,16 @@ mod tests {
                         "1931f78f7ce5b43ffae11a2c22f18765508a2b2d4810e84744f53b10f7072c7f"
                     )
                     .unwrap(),
-                    amount: 1000000000
+                    amount: 1000000000,
+                    recipient_address: account_1.wallet.address(),
                 },
                 HashAndAmount {
                     hash: H256::from_str(
                         "0d6daf751e62b89e79cac26d6376cf259d58e996cfccd63f3f43bb6408d1bae8"
                     )
                     .unwrap(),
-                    amount: 2000000000
+                    amount: 2000000000,
+                    recipient_address: account_2.wallet.address(),
                 }
             ]
         );
@@ -524,22 +678,26 @@ mod tests {
                 balance_wei: 2_345_678,
                 last_paid_timestamp: from_time_t(4500000),
                 pending_payable_opt: None,
+                written_off: false,
             },
             PayableAccount {
                 wallet: make_wallet("5656"),
                 balance_wei: 6_543_210,
                 last_paid_timestamp: from_time_t(333000),
                 pending_payable_opt: None,
+                written_off: false,
             },
         ];
         let fingerprint_inputs = vec![
             HashAndAmount {
                 hash: make_tx_hash(444),
                 amount: 2_345_678,
+                recipient_address: make_wallet("4567").address(),
             },
             HashAndAmount {
                 hash: make_tx_hash(333),
                 amount: 6_543_210,
+                recipient_address: make_wallet("5656").address(),
             },
         ];
         let responses = vec![
@@ -573,6 +731,113 @@ mod tests {
         )
     }
 
+    #[test]
+    fn output_by_joining_sources_spreads_a_multisend_response_over_all_its_payables() {
+        let accounts = vec![
+            make_payable_account(111),
+            make_payable_account(222),
+            make_payable_account(333),
+        ];
+        let fingerprint_inputs = vec![
+            HashAndAmount {
+                hash: make_tx_hash(555),
+                amount: accounts[0].balance_wei,
+                recipient_address: accounts[0].wallet.address(),
+            },
+            HashAndAmount {
+                hash: make_tx_hash(555),
+                amount: accounts[1].balance_wei,
+                recipient_address: accounts[1].wallet.address(),
+            },
+            HashAndAmount {
+                hash: make_tx_hash(666),
+                amount: accounts[2].balance_wei,
+                recipient_address: accounts[2].wallet.address(),
+            },
+        ];
+        let rpc_error = web3::Error::Rpc(Error {
+            code: ErrorCode::InternalError,
+            message: "Not today".to_string(),
+            data: None,
+        });
+        let responses = vec![
+            Err(rpc_error.clone()),
+            Ok(Value::String(String::from("blah"))),
+        ];
+
+        let result = merged_output_data(responses, fingerprint_inputs, accounts.to_vec());
+
+        assert_eq!(
+            result,
+            vec![
+                Failed(RpcPayableFailure {
+                    rpc_error: rpc_error.clone(),
+                    recipient_wallet: accounts[0].wallet.clone(),
+                    hash: make_tx_hash(555)
+                }),
+                Failed(RpcPayableFailure {
+                    rpc_error,
+                    recipient_wallet: accounts[1].wallet.clone(),
+                    hash: make_tx_hash(555)
+                }),
+                Correct(PendingPayable {
+                    recipient_wallet: accounts[2].wallet.clone(),
+                    hash: make_tx_hash(666)
+                }),
+            ]
+        )
+    }
+
+    #[test]
+    fn multisend_transaction_data_is_encoded_for_disperse_token() {
+        let token_contract = Address::from_low_u64_be(0xabcd);
+        let accounts = vec![
+            make_payable_account_with_wallet_and_balance_and_timestamp_opt(
+                Wallet::from(Address::from_low_u64_be(0x1111)),
+                1_000,
+                None,
+            ),
+            make_payable_account_with_wallet_and_balance_and_timestamp_opt(
+                Wallet::from(Address::from_low_u64_be(0x2222)),
+                2_000,
+                None,
+            ),
+        ];
+
+        let result = multisend_transaction_data(token_contract, &accounts);
+
+        let word = |value: u64| {
+            let mut word = [0u8; 32];
+            U256::from(value).to_big_endian(&mut word);
+            word.to_vec()
+        };
+        let expected = vec![
+            MULTISEND_METHOD_ID.to_vec(),
+            word(0xabcd),
+            word(3 * 32),
+            word(6 * 32),
+            word(2),
+            word(0x1111),
+            word(0x2222),
+            word(2),
+            word(1_000),
+            word(2_000),
+        ]
+        .concat();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn multisend_gas_limit_counts_every_recipient() {
+        let chain = Chain::BaseMainnet;
+        let data = [0u8, 1u8, 0u8, 2u8];
+
+        let result = multisend_gas_limit(&data, 3, chain);
+
+        let const_part = BlockchainInterfaceWeb3::web3_gas_limit_const_part(chain);
+        assert_eq!(result, U256::from(const_part * 3 + 4 + 68 + 4 + 68));
+    }
+
     fn execute_send_payables_test(
         test_name: &str,
         accounts: Vec<PayableAccount>,
@@ -603,6 +868,7 @@ mod tests {
             consuming_wallet.clone(),
             gas_price,
             pending_nonce,
+            None,
             new_fingerprints_recipient,
             accounts.clone(),
         )
@@ -779,6 +1045,80 @@ mod tests {
         );
     }
 
+    #[test]
+    fn send_payables_within_batch_settles_payables_in_one_multisend_transaction() {
+        init_test_logging();
+        let test_name = "send_payables_within_batch_settles_payables_in_one_multisend_transaction";
+        let multisend_contract = Address::from_low_u64_be(0xd15e);
+        let chain = DEFAULT_CHAIN;
+        let accounts = vec![
+            make_payable_account(1),
+            make_payable_account(2),
+            make_payable_account(3),
+        ];
+        let port = find_free_port();
+        let _blockchain_client_server = MBCSBuilder::new(port)
+            .begin_batch()
+            .ok_response("irrelevant_ok_rpc_response".to_string(), 7)
+            .end_batch()
+            .start();
+        let (_event_loop_handle, transport) = Http::with_max_parallel(
+            &format!("http://{}:{}", &Ipv4Addr::LOCALHOST, port),
+            REQUESTS_IN_PARALLEL,
+        )
+        .unwrap();
+        let web3_batch = Web3::new(Batch::new(transport));
+        let (accountant, _, accountant_recording) = make_recorder();
+        let new_fingerprints_recipient = accountant.start().recipient();
+        let system = System::new(test_name);
+
+        let result = send_payables_within_batch(
+            &Logger::new(test_name),
+            chain,
+            &web3_batch,
+            make_paying_wallet(b"consuming_wallet"),
+            1_000_000_000,
+            1.into(),
+            Some(multisend_contract),
+            new_fingerprints_recipient,
+            accounts.clone(),
+        )
+        .wait()
+        .unwrap();
+
+        System::current().stop();
+        system.run();
+        let accountant_recording = accountant_recording.lock().unwrap();
+        let seeds = accountant_recording.get_record::<PendingPayableFingerprintSeeds>(0);
+        let hash = seeds.hashes_and_balances[0].hash;
+        assert_eq!(
+            seeds.hashes_and_balances,
+            accounts
+                .iter()
+                .map(|account| HashAndAmount {
+                    hash,
+                    amount: account.balance_wei,
+                    recipient_address: account.wallet.address(),
+                })
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            result,
+            accounts
+                .iter()
+                .map(|account| Correct(PendingPayable {
+                    recipient_wallet: account.wallet.clone(),
+                    hash
+                }))
+                .collect::<Vec<_>>()
+        );
+        TestLogHandler::new().exists_log_containing(&format!(
+            "DEBUG: {test_name}: Preparing multisend payable future of 6,000,000,000 wei to 3 \
+            creditors through {:?} with nonce 1",
+            multisend_contract
+        ));
+    }
+
     #[test]
     fn advance_used_nonce_works() {
         let initial_nonce = U256::from(55);
@@ -15,6 +15,7 @@ pub struct BlockchainTransaction {
     pub block_number: u64,
     pub from: Wallet,
     pub wei_amount: u128,
+    pub transaction_hash: H256,
 }
 
 impl fmt::Display for BlockchainTransaction {
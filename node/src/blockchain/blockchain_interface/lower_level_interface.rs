@@ -22,6 +22,12 @@ pub trait LowBlockchainInt {
         address: Address,
     ) -> Box<dyn Future<Item = U256, Error = BlockchainError>>;
 
+    fn get_service_fee_allowance(
+        &self,
+        owner: Address,
+        spender: Address,
+    ) -> Box<dyn Future<Item = U256, Error = BlockchainError>>;
+
     fn get_gas_price(&self) -> Box<dyn Future<Item = U256, Error = BlockchainError>>;
 
     fn get_block_number(&self) -> Box<dyn Future<Item = U64, Error = BlockchainError>>;
@@ -30,7 +30,7 @@ pub trait BlockchainInterface {
         &self,
         start_block: BlockMarker,
         scan_range: BlockScanRange,
-        recipient: Address,
+        recipients: Vec<Address>,
     ) -> Box<dyn Future<Item = RetrievedBlockchainTransactions, Error = BlockchainError>>;
 
     fn build_blockchain_agent(
@@ -25,15 +25,17 @@ use crate::privilege_drop::{IdWrapper, IdWrapperReal};
 use crate::server_initializer::LoggerInitializerWrapper;
 use crate::stream_handler_pool::StreamHandlerPoolSubs;
 use crate::sub_lib::accountant;
-use crate::sub_lib::accountant::{PaymentThresholds, ScanIntervals};
+use crate::sub_lib::accountant::{PayableWriteOffPolicy, PaymentThresholds, ScanIntervals};
 use crate::sub_lib::blockchain_bridge::BlockchainBridgeConfig;
 use crate::sub_lib::cryptde::CryptDE;
 #[cfg(test)]
 use crate::sub_lib::cryptde_null::CryptDENull;
 use crate::sub_lib::cryptde_real::CryptDEReal;
+use crate::sub_lib::earning_wallet_shares::EarningWalletShares;
 use crate::sub_lib::neighborhood::NodeDescriptor;
-use crate::sub_lib::neighborhood::{NeighborhoodConfig, NeighborhoodMode};
+use crate::sub_lib::neighborhood::{NeighborhoodConfig, NeighborhoodMode, RouteDiversity};
 use crate::sub_lib::node_addr::NodeAddr;
+use crate::sub_lib::proxy_server::HeaderScrubbing;
 use crate::sub_lib::socket_server::ConfiguredByPrivilege;
 use crate::sub_lib::ui_gateway::UiGatewayConfig;
 use crate::sub_lib::utils::db_connection_launch_panic;
@@ -45,7 +47,7 @@ use masq_lib::blockchains::chains::Chain;
 use masq_lib::command::StdStreams;
 use masq_lib::constants::DEFAULT_UI_PORT;
 use masq_lib::crash_point::CrashPoint;
-use masq_lib::logger::Logger;
+use masq_lib::logger::{LogFormat, Logger};
 use masq_lib::multi_config::MultiConfig;
 use masq_lib::shared_schema::ConfiguratorError;
 use masq_lib::test_utils::utils::TEST_DEFAULT_CHAIN;
@@ -57,6 +59,7 @@ use std::fmt::{Debug, Display, Error, Formatter};
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::time::Duration;
 use std::vec::Vec;
 use tokio::prelude::stream::futures_unordered::FuturesUnordered;
 use tokio::prelude::Async;
@@ -348,6 +351,10 @@ impl Display for RealUser {
 pub struct BootstrapperConfig {
     // These fields can be set while privileged without penalty
     pub log_level: LevelFilter,
+    pub log_format: LogFormat,
+    pub key_rotation_interval_opt: Option<Duration>,
+    pub route_diversity: RouteDiversity,
+    pub header_scrubbing: HeaderScrubbing,
     pub dns_servers: Vec<SocketAddr>,
     pub scan_intervals_opt: Option<ScanIntervals>,
     pub suppress_initial_scans: bool,
@@ -363,12 +370,14 @@ pub struct BootstrapperConfig {
     pub mapping_protocol_opt: Option<AutomapProtocol>,
     pub real_user: RealUser,
     pub payment_thresholds_opt: Option<PaymentThresholds>,
+    pub payable_write_off_policy: PayableWriteOffPolicy,
 
     // These fields must be set without privilege: otherwise the database will be created as root
     pub db_password_opt: Option<String>,
     pub clandestine_port_opt: Option<u16>,
     pub consuming_wallet_opt: Option<Wallet>,
     pub earning_wallet: Wallet,
+    pub earning_wallet_shares: EarningWalletShares,
     pub neighborhood_config: NeighborhoodConfig,
 }
 
@@ -383,6 +392,10 @@ impl BootstrapperConfig {
         BootstrapperConfig {
             // These fields can be set while privileged without penalty
             log_level: LevelFilter::Off,
+            log_format: LogFormat::Text,
+            key_rotation_interval_opt: None,
+            route_diversity: RouteDiversity::default(),
+            header_scrubbing: HeaderScrubbing::default(),
             dns_servers: vec![],
             scan_intervals_opt: None,
             suppress_initial_scans: false,
@@ -408,11 +421,13 @@ impl BootstrapperConfig {
             mapping_protocol_opt: None,
             real_user: RealUser::new(None, None, None),
             payment_thresholds_opt: Default::default(),
+            payable_write_off_policy: PayableWriteOffPolicy::default(),
 
             // These fields must be set without privilege: otherwise the database will be created as root
             db_password_opt: None,
             clandestine_port_opt: None,
             earning_wallet: accountant::DEFAULT_EARNING_WALLET.clone(),
+            earning_wallet_shares: EarningWalletShares::default(),
             consuming_wallet_opt: None,
             neighborhood_config: NeighborhoodConfig {
                 mode: NeighborhoodMode::ZeroHop,
@@ -430,12 +445,14 @@ impl BootstrapperConfig {
         self.clandestine_port_opt = unprivileged.clandestine_port_opt;
         self.neighborhood_config = unprivileged.neighborhood_config;
         self.earning_wallet = unprivileged.earning_wallet;
+        self.earning_wallet_shares = unprivileged.earning_wallet_shares;
         self.consuming_wallet_opt = unprivileged.consuming_wallet_opt;
         self.cryptde_pair = unprivileged.cryptde_pair;
         self.db_password_opt = unprivileged.db_password_opt;
         self.scan_intervals_opt = unprivileged.scan_intervals_opt;
         self.suppress_initial_scans = unprivileged.suppress_initial_scans;
         self.payment_thresholds_opt = unprivileged.payment_thresholds_opt;
+        self.payable_write_off_policy = unprivileged.payable_write_off_policy;
         self.when_pending_too_long_sec = unprivileged.when_pending_too_long_sec;
     }
 
@@ -489,6 +506,7 @@ impl ConfiguredByPrivilege for Bootstrapper {
             self.config.data_directory.clone(),
             &self.config.real_user,
             self.config.log_level,
+            self.config.log_format,
             None,
         );
         self.listener_handlers =
@@ -693,10 +711,11 @@ mod tests {
     use crate::server_initializer::LoggerInitializerWrapper;
     use crate::stream_handler_pool::StreamHandlerPoolSubs;
     use crate::stream_messages::AddStreamMsg;
-    use crate::sub_lib::accountant::ScanIntervals;
+    use crate::sub_lib::accountant::{PayableWriteOffPolicy, ScanIntervals};
     use crate::sub_lib::cryptde::PublicKey;
     use crate::sub_lib::cryptde::{CryptDE, PlainData};
     use crate::sub_lib::cryptde_null::CryptDENull;
+    use crate::sub_lib::earning_wallet_shares::EarningWalletShares;
     use crate::sub_lib::neighborhood::{
         NeighborhoodConfig, NeighborhoodMode, NodeDescriptor, DEFAULT_RATE_PACK,
     };
@@ -1129,6 +1148,7 @@ mod tests {
                 data_dir,
                 RealUser::new(Some(123), Some(456), Some("/home/booga".into())),
                 LevelFilter::Warn,
+                LogFormat::Text,
                 None,
             )]
         )
@@ -1205,6 +1225,8 @@ mod tests {
             min_hops: MIN_HOPS_FOR_TEST,
         };
         let earning_wallet = make_wallet("earning wallet");
+        let earning_wallet_shares =
+            EarningWalletShares::from_str(&format!("{}:25", make_wallet("share wallet"))).unwrap();
         let consuming_wallet_opt = Some(make_wallet("consuming wallet"));
         let db_password_opt = Some("password".to_string());
         unprivileged_config.blockchain_bridge_config.gas_price = gas_price;
@@ -1214,11 +1236,18 @@ mod tests {
         unprivileged_config.clandestine_port_opt = clandestine_port_opt;
         unprivileged_config.neighborhood_config = neighborhood_config.clone();
         unprivileged_config.earning_wallet = earning_wallet.clone();
+        unprivileged_config.earning_wallet_shares = earning_wallet_shares.clone();
         unprivileged_config.consuming_wallet_opt = consuming_wallet_opt.clone();
         unprivileged_config.db_password_opt = db_password_opt.clone();
         unprivileged_config.scan_intervals_opt = Some(ScanIntervals::default());
         unprivileged_config.suppress_initial_scans = false;
         unprivileged_config.when_pending_too_long_sec = DEFAULT_PENDING_TOO_LONG_SEC;
+        let payable_write_off_policy = PayableWriteOffPolicy {
+            unseen_days: 45,
+            dust_threshold_gwei: 2_000,
+            dust_days: 15,
+        };
+        unprivileged_config.payable_write_off_policy = payable_write_off_policy;
 
         privileged_config.merge_unprivileged(unprivileged_config);
 
@@ -1236,6 +1265,10 @@ mod tests {
         assert_eq!(privileged_config.clandestine_port_opt, clandestine_port_opt);
         assert_eq!(privileged_config.neighborhood_config, neighborhood_config);
         assert_eq!(privileged_config.earning_wallet, earning_wallet);
+        assert_eq!(
+            privileged_config.earning_wallet_shares,
+            earning_wallet_shares
+        );
         assert_eq!(privileged_config.consuming_wallet_opt, consuming_wallet_opt);
         assert_eq!(privileged_config.db_password_opt, db_password_opt);
         assert_eq!(
@@ -1247,6 +1280,10 @@ mod tests {
             privileged_config.when_pending_too_long_sec,
             DEFAULT_PENDING_TOO_LONG_SEC
         );
+        assert_eq!(
+            privileged_config.payable_write_off_policy,
+            payable_write_off_policy
+        );
         //some values from the privileged config
         assert_eq!(privileged_config.log_level, Off);
         assert_eq!(
@@ -16,6 +16,7 @@ use crossbeam_channel::{unbounded, Receiver, Sender};
 use flexi_logger::LevelFilter;
 use itertools::Itertools;
 use masq_lib::command::StdStreams;
+use masq_lib::logger::LogFormat;
 use masq_lib::shared_schema::ConfiguratorError;
 use std::collections::HashMap;
 
@@ -140,6 +141,7 @@ impl DaemonInitializerReal {
             real_data_dir.join("MASQ"),
             &real_user,
             LevelFilter::Trace,
+            LogFormat::Text,
             Some("daemon"),
         );
         DaemonInitializerReal {
@@ -1,13 +1,15 @@
 // Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 
 use crate::apps::app_config_dumper;
-use crate::blockchain::bip39::Bip39;
 use crate::bootstrapper::RealUser;
 use crate::database::db_initializer::DbInitializationConfig;
 use crate::database::db_initializer::{
     DbInitializer, DbInitializerReal, InitializationError, DATABASE_FILE,
 };
 use crate::db_config::config_dao::{ConfigDao, ConfigDaoReal, ConfigDaoRecord};
+use crate::db_config::db_encryption_layer::DbEncryptionLayer;
+use crate::db_config::db_kdf::{DerivedKeyCache, KdfParameters};
+use crate::db_config::secure_config_layer::EXAMPLE_ENCRYPTED;
 use crate::db_config::typed_config_layer::{decode_bytes, encode_bytes};
 use crate::node_configurator::{
     data_directory_from_context, real_user_data_directory_path_and_chain, DirsWrapper,
@@ -67,16 +69,31 @@ fn configuration_to_json(
     cryptde: &dyn CryptDE,
 ) -> String {
     let mut map = Map::new();
+    let kdf_parameters_opt = configuration
+        .iter()
+        .find(|record| record.name == EXAMPLE_ENCRYPTED)
+        .and_then(|record| record.value_opt.as_ref())
+        .and_then(|value| KdfParameters::from_crypt_value(value));
+    let key_cache = DerivedKeyCache::default();
     configuration.into_iter().for_each(|record| {
         let json_name = record.name.to_mixed_case();
         let value_opt = match (&record.value_opt, record.encrypted, &password_opt) {
             (None, _, _) => None,
             (Some(value), false, _) => Some(value.to_string()),
             (Some(value), true, None) => Some(value.to_string()),
-            (Some(value), true, Some(password)) => match Bip39::decrypt_bytes(value, password) {
-                Ok(decrypted_value) => Some(translate_bytes(&json_name, decrypted_value, cryptde)),
-                Err(_) => Some(value.to_string()),
-            },
+            (Some(value), true, Some(password)) => {
+                match DbEncryptionLayer::decrypt_bytes(
+                    value,
+                    password,
+                    &kdf_parameters_opt,
+                    &key_cache,
+                ) {
+                    Ok(decrypted_value) => {
+                        Some(translate_bytes(&json_name, decrypted_value, cryptde))
+                    }
+                    Err(_) => Some(value.to_string()),
+                }
+            }
         };
         let json_value = match value_opt {
             Some(s) => json!(s),
@@ -158,7 +175,6 @@ fn distill_args(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::blockchain::bip39::Bip39;
     use crate::bootstrapper::CryptDEPair;
     use crate::database::db_initializer::ExternalData;
     use crate::database::rusqlite_wrappers::ConnectionWrapperReal;
@@ -501,7 +517,13 @@ mod tests {
         assert_value("schemaVersion", &CURRENT_SCHEMA_VERSION.to_string(), &map);
         assert_null("startBlock", &map);
         let expected_ee_entry = dao.get("example_encrypted").unwrap().value_opt.unwrap();
-        let expected_ee_decrypted = Bip39::decrypt_bytes(&expected_ee_entry, "password").unwrap();
+        let expected_ee_decrypted = DbEncryptionLayer::decrypt_bytes(
+            &expected_ee_entry,
+            "password",
+            &None,
+            &DerivedKeyCache::default(),
+        )
+        .unwrap();
         let expected_ee_string = encode_bytes(Some(expected_ee_decrypted)).unwrap().unwrap();
         assert_value("exampleEncrypted", &expected_ee_string, &map);
         assert_value(
@@ -692,7 +714,17 @@ mod tests {
             Value::String(s) => s,
             x => panic!("Expected JSON string; found {:?}", x),
         };
-        let decrypted_value_bytes = Bip39::decrypt_bytes(encrypted_value, password).unwrap();
+        let kdf_parameters_opt = match map.get("exampleEncrypted") {
+            Some(Value::String(s)) => KdfParameters::from_crypt_value(s),
+            _ => None,
+        };
+        let decrypted_value_bytes = DbEncryptionLayer::decrypt_bytes(
+            encrypted_value,
+            password,
+            &kdf_parameters_opt,
+            &DerivedKeyCache::default(),
+        )
+        .unwrap();
         let actual_value: String = decrypted_value_bytes.as_slice().to_hex();
         assert_eq!(actual_value.to_uppercase(), expected_value.to_uppercase());
     }
@@ -0,0 +1,545 @@
+// Copyright (c) 2024, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::apps::app_backup_restorer;
+use crate::bootstrapper::RealUser;
+use crate::database::db_initializer::DATABASE_FILE;
+use crate::database::db_migrations::db_migrator::DbMigratorReal;
+use crate::database::rusqlite_wrappers::{ConnectionWrapper, ConnectionWrapperReal};
+use crate::node_configurator::{
+    data_directory_from_context, real_user_data_directory_path_and_chain, DirsWrapper,
+};
+use crate::privilege_drop::{PrivilegeDropper, PrivilegeDropperReal};
+use crate::run_modes_factories::{RestoreBackupRunner, RunModeResult};
+use crate::sub_lib::utils::make_new_multi_config;
+use clap::value_t;
+use masq_lib::command::StdStreams;
+use masq_lib::constants::CURRENT_SCHEMA_VERSION;
+use masq_lib::multi_config::{CommandLineVcl, EnvironmentVcl, VirtualCommandLine};
+use masq_lib::shared_schema::ConfiguratorError;
+use rusqlite::types::Type;
+use rusqlite::{Connection, OpenFlags};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const BACKUP_DIRECTORY: &str = "backups";
+pub const BACKUPS_TO_KEEP: usize = 5;
+const BACKUP_FILE_PREFIX: &str = "node-data-";
+const BACKUP_FILE_SUFFIX: &str = ".db";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupRecord {
+    pub path: PathBuf,
+    pub timestamp: i64,
+    pub schema_version: usize,
+    pub removed: Vec<PathBuf>,
+}
+
+pub trait DatabaseBackup {
+    fn backup(&self) -> Result<BackupRecord, String>;
+}
+
+pub struct DatabaseBackupReal {
+    conn: Box<dyn ConnectionWrapper>,
+    backup_directory: PathBuf,
+}
+
+impl DatabaseBackup for DatabaseBackupReal {
+    fn backup(&self) -> Result<BackupRecord, String> {
+        fs::create_dir_all(&self.backup_directory).map_err(|e| {
+            format!(
+                "Could not create backup directory {}: {}",
+                self.backup_directory.display(),
+                e
+            )
+        })?;
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("System clock is before the epoch")
+            .as_secs() as i64;
+        let partial_path = self
+            .backup_directory
+            .join(format!("{}{}.partial", BACKUP_FILE_PREFIX, timestamp));
+        let schema_version = match self.copy_and_stamp(&partial_path) {
+            Ok(version) => version,
+            Err(e) => {
+                let _ = fs::remove_file(&partial_path);
+                return Err(e);
+            }
+        };
+        let path = self
+            .backup_directory
+            .join(backup_file_name(timestamp, schema_version));
+        fs::rename(&partial_path, &path)
+            .map_err(|e| format!("Could not finish backup {}: {}", path.display(), e))?;
+        let removed = rotate_backups(&self.backup_directory, BACKUPS_TO_KEEP)?;
+        Ok(BackupRecord {
+            path,
+            timestamp,
+            schema_version,
+            removed,
+        })
+    }
+}
+
+impl DatabaseBackupReal {
+    pub fn new(conn: Box<dyn ConnectionWrapper>, data_directory: &Path) -> Self {
+        Self {
+            conn,
+            backup_directory: data_directory.join(BACKUP_DIRECTORY),
+        }
+    }
+
+    fn copy_and_stamp(&self, partial_path: &Path) -> Result<usize, String> {
+        self.conn.backup_to(partial_path).map_err(|e| {
+            format!(
+                "Database backup to {} failed: {}",
+                partial_path.display(),
+                e
+            )
+        })?;
+        stamp_schema_version_header(partial_path).map_err(|e| {
+            format!(
+                "Could not write schema version header to {}: {}",
+                partial_path.display(),
+                e
+            )
+        })
+    }
+}
+
+// The schema version is copied from the config table into SQLite's own 'user_version' header
+// field, so that the version of a backup can be checked without trusting its tables.
+fn stamp_schema_version_header(path: &Path) -> rusqlite::Result<usize> {
+    let conn = Connection::open(path)?;
+    let schema_version = conn
+        .query_row(
+            "select value from config where name = 'schema_version'",
+            [],
+            |row| row.get::<_, String>(0),
+        )?
+        .parse::<usize>()
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, Type::Text, Box::new(e)))?;
+    conn.pragma_update(None, "user_version", schema_version as i64)?;
+    Ok(schema_version)
+}
+
+pub fn read_schema_version_header(path: &Path) -> Result<usize, String> {
+    Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .and_then(|conn| conn.pragma_query_value(None, "user_version", |row| row.get::<_, i64>(0)))
+        .map(|version| version as usize)
+        .map_err(|e| {
+            format!(
+                "Could not read {} as a database backup: {}",
+                path.display(),
+                e
+            )
+        })
+}
+
+pub fn restore_backup(backup_path: &Path, data_directory: &Path) -> Result<usize, String> {
+    let schema_version = read_schema_version_header(backup_path)?;
+    DbMigratorReal::check_backup_schema_version(schema_version, CURRENT_SCHEMA_VERSION)?;
+    let source = Connection::open_with_flags(backup_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Could not open {}: {}", backup_path.display(), e))?;
+    fs::create_dir_all(data_directory).map_err(|e| {
+        format!(
+            "Could not create data directory {}: {}",
+            data_directory.display(),
+            e
+        )
+    })?;
+    let database_path = data_directory.join(DATABASE_FILE);
+    ConnectionWrapperReal::new(source)
+        .backup_to(&database_path)
+        .map_err(|e| format!("Restoring into {} failed: {}", database_path.display(), e))?;
+    Ok(schema_version)
+}
+
+fn backup_file_name(timestamp: i64, schema_version: usize) -> String {
+    format!(
+        "{}{}-v{}{}",
+        BACKUP_FILE_PREFIX, timestamp, schema_version, BACKUP_FILE_SUFFIX
+    )
+}
+
+fn backup_timestamp_from_file_name(file_name: &str) -> Option<i64> {
+    let body = file_name
+        .strip_prefix(BACKUP_FILE_PREFIX)?
+        .strip_suffix(BACKUP_FILE_SUFFIX)?;
+    let (timestamp, version) = body.split_once("-v")?;
+    version.parse::<usize>().ok()?;
+    timestamp.parse::<i64>().ok()
+}
+
+fn rotate_backups(backup_directory: &Path, keep: usize) -> Result<Vec<PathBuf>, String> {
+    let entries = fs::read_dir(backup_directory).map_err(|e| {
+        format!(
+            "Could not list backup directory {}: {}",
+            backup_directory.display(),
+            e
+        )
+    })?;
+    let mut backups = entries
+        .flatten()
+        .filter_map(|entry| {
+            let timestamp = backup_timestamp_from_file_name(entry.file_name().to_str()?)?;
+            Some((timestamp, entry.path()))
+        })
+        .collect::<Vec<(i64, PathBuf)>>();
+    backups.sort_by(|a, b| b.cmp(a));
+    backups
+        .into_iter()
+        .skip(keep)
+        .map(|(_, path)| {
+            fs::remove_file(&path)
+                .map(|_| path.clone())
+                .map_err(|e| format!("Could not remove old backup {}: {}", path.display(), e))
+        })
+        .collect()
+}
+
+pub struct RestoreBackupRunnerReal {
+    pub(crate) dirs_wrapper: Box<dyn DirsWrapper>,
+}
+
+impl RestoreBackupRunner for RestoreBackupRunnerReal {
+    fn go(&self, streams: &mut StdStreams, args: &[String]) -> RunModeResult {
+        let (real_user, data_directory, backup_path) =
+            distill_args(self.dirs_wrapper.as_ref(), args)?;
+        PrivilegeDropperReal::new().drop_privileges(&real_user);
+        match restore_backup(&backup_path, &data_directory) {
+            Ok(schema_version) => {
+                short_writeln!(
+                    streams.stdout,
+                    "Restored {} (database schema version {}) into {}",
+                    backup_path.display(),
+                    schema_version,
+                    data_directory.join(DATABASE_FILE).display()
+                );
+                Ok(())
+            }
+            Err(e) => Err(ConfiguratorError::required("restore-backup", &e)),
+        }
+    }
+
+    as_any_ref_in_trait_impl!();
+}
+
+fn distill_args(
+    dirs_wrapper: &dyn DirsWrapper,
+    args: &[String],
+) -> Result<(RealUser, PathBuf, PathBuf), ConfiguratorError> {
+    let app = app_backup_restorer();
+    let vcls: Vec<Box<dyn VirtualCommandLine>> = vec![
+        Box::new(CommandLineVcl::new(args.to_vec())),
+        Box::new(EnvironmentVcl::new(&app)),
+    ];
+    let multi_config = make_new_multi_config(&app, vcls)?;
+    let (real_user, data_directory_path, chain) =
+        real_user_data_directory_path_and_chain(dirs_wrapper, &multi_config);
+    let directory = match data_directory_path {
+        Some(data_dir) => data_dir,
+        None => data_directory_from_context(dirs_wrapper, &real_user, chain),
+    };
+    let backup_path = value_m!(multi_config, "restore-backup", PathBuf)
+        .expect("restore-backup is a required parameter");
+    Ok((real_user, directory, backup_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::db_initializer::{
+        DbInitializationConfig, DbInitializer, DbInitializerReal,
+    };
+    use crate::database::test_utils::ConnectionWrapperMock;
+    use crate::node_configurator::DirsWrapperReal;
+    use crate::test_utils::ArgsBuilder;
+    use masq_lib::test_utils::fake_stream_holder::FakeStreamHolder;
+    use masq_lib::test_utils::utils::ensure_node_home_directory_exists;
+    use std::sync::{Arc, Mutex};
+
+    fn make_database(data_directory: &Path) -> Box<dyn ConnectionWrapper> {
+        DbInitializerReal::default()
+            .initialize(data_directory, DbInitializationConfig::test_default())
+            .unwrap()
+    }
+
+    fn count_banned(database_path: &Path) -> i64 {
+        Connection::open(database_path)
+            .unwrap()
+            .query_row("select count(*) from banned", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn constants_have_correct_values() {
+        assert_eq!(BACKUP_DIRECTORY, "backups");
+        assert_eq!(BACKUPS_TO_KEEP, 5);
+    }
+
+    #[test]
+    fn backup_copies_the_database_and_stamps_the_schema_version() {
+        let data_dir = ensure_node_home_directory_exists(
+            "db_backup",
+            "backup_copies_the_database_and_stamps_the_schema_version",
+        );
+        let conn = make_database(&data_dir);
+        conn.prepare(
+            "insert into banned (wallet_address) \
+             values ('0x0000000000000000000000000000000000000001')",
+        )
+        .unwrap()
+        .execute([])
+        .unwrap();
+        let subject = DatabaseBackupReal::new(conn, &data_dir);
+
+        let result = subject.backup().unwrap();
+
+        assert_eq!(result.schema_version, CURRENT_SCHEMA_VERSION);
+        assert_eq!(result.removed, Vec::<PathBuf>::new());
+        assert_eq!(
+            result.path,
+            data_dir.join(BACKUP_DIRECTORY).join(format!(
+                "node-data-{}-v{}.db",
+                result.timestamp, CURRENT_SCHEMA_VERSION
+            ))
+        );
+        assert_eq!(
+            read_schema_version_header(&result.path),
+            Ok(CURRENT_SCHEMA_VERSION)
+        );
+        assert_eq!(count_banned(&result.path), 1);
+    }
+
+    #[test]
+    fn backup_rotates_old_copies_and_leaves_other_files_alone() {
+        let data_dir = ensure_node_home_directory_exists(
+            "db_backup",
+            "backup_rotates_old_copies_and_leaves_other_files_alone",
+        );
+        let backup_dir = data_dir.join(BACKUP_DIRECTORY);
+        fs::create_dir_all(&backup_dir).unwrap();
+        [100, 200, 300, 400, 500, 600].iter().for_each(|timestamp| {
+            fs::write(backup_dir.join(backup_file_name(*timestamp, 12)), b"old").unwrap()
+        });
+        fs::write(backup_dir.join("notes.txt"), b"keep me").unwrap();
+        let subject = DatabaseBackupReal::new(make_database(&data_dir), &data_dir);
+
+        let result = subject.backup().unwrap();
+
+        assert_eq!(
+            result.removed,
+            vec![
+                backup_dir.join("node-data-200-v12.db"),
+                backup_dir.join("node-data-100-v12.db"),
+            ]
+        );
+        let mut remaining = fs::read_dir(&backup_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect::<Vec<String>>();
+        remaining.sort();
+        let mut expected = vec![
+            "node-data-300-v12.db".to_string(),
+            "node-data-400-v12.db".to_string(),
+            "node-data-500-v12.db".to_string(),
+            "node-data-600-v12.db".to_string(),
+            "notes.txt".to_string(),
+            result
+                .path
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .to_string(),
+        ];
+        expected.sort();
+        assert_eq!(remaining, expected);
+    }
+
+    #[test]
+    fn backup_reports_a_failed_copy_and_leaves_nothing_behind() {
+        let data_dir = ensure_node_home_directory_exists(
+            "db_backup",
+            "backup_reports_a_failed_copy_and_leaves_nothing_behind",
+        );
+        let backup_to_params_arc = Arc::new(Mutex::new(vec![]));
+        let conn = ConnectionWrapperMock::default()
+            .backup_to_params(&backup_to_params_arc)
+            .backup_to_result(Err(rusqlite::Error::InvalidQuery));
+        let subject = DatabaseBackupReal::new(Box::new(conn), &data_dir);
+
+        let result = subject.backup();
+
+        let backup_to_params = backup_to_params_arc.lock().unwrap();
+        assert_eq!(backup_to_params.len(), 1);
+        assert_eq!(
+            result,
+            Err(format!(
+                "Database backup to {} failed: Query is not read-only",
+                backup_to_params[0].display()
+            ))
+        );
+        let leftovers = fs::read_dir(data_dir.join(BACKUP_DIRECTORY))
+            .unwrap()
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[test]
+    fn backup_file_names_are_recognized() {
+        assert_eq!(
+            backup_timestamp_from_file_name("node-data-1700000000-v13.db"),
+            Some(1700000000)
+        );
+        assert_eq!(
+            backup_timestamp_from_file_name("node-data-1700000000.partial"),
+            None
+        );
+        assert_eq!(backup_timestamp_from_file_name("node-data.db"), None);
+        assert_eq!(
+            backup_timestamp_from_file_name("node-data-abc-v13.db"),
+            None
+        );
+    }
+
+    #[test]
+    fn restore_backup_replaces_the_database() {
+        let home_dir =
+            ensure_node_home_directory_exists("db_backup", "restore_backup_replaces_the_database");
+        let data_dir = home_dir.join("data");
+        let conn = make_database(&data_dir);
+        let backup = DatabaseBackupReal::new(conn, &home_dir).backup().unwrap();
+        let live_conn = make_database(&data_dir);
+        live_conn
+            .prepare(
+                "insert into banned (wallet_address) \
+                 values ('0x0000000000000000000000000000000000000002')",
+            )
+            .unwrap()
+            .execute([])
+            .unwrap();
+        drop(live_conn);
+        assert_eq!(count_banned(&data_dir.join(DATABASE_FILE)), 1);
+
+        let result = restore_backup(&backup.path, &data_dir);
+
+        assert_eq!(result, Ok(CURRENT_SCHEMA_VERSION));
+        assert_eq!(count_banned(&data_dir.join(DATABASE_FILE)), 0);
+    }
+
+    #[test]
+    fn restore_backup_refuses_a_backup_newer_than_the_binary() {
+        let home_dir = ensure_node_home_directory_exists(
+            "db_backup",
+            "restore_backup_refuses_a_backup_newer_than_the_binary",
+        );
+        let backup_path = home_dir.join("future.db");
+        Connection::open(&backup_path)
+            .unwrap()
+            .pragma_update(None, "user_version", (CURRENT_SCHEMA_VERSION + 1) as i64)
+            .unwrap();
+        let data_dir = home_dir.join("data");
+
+        let result = restore_backup(&backup_path, &data_dir);
+
+        assert_eq!(
+            result,
+            Err(format!(
+                "The backup has database schema version {}, but this Node only understands \
+                 versions up to {}; restore it with a newer Node",
+                CURRENT_SCHEMA_VERSION + 1,
+                CURRENT_SCHEMA_VERSION
+            ))
+        );
+        assert!(!data_dir.join(DATABASE_FILE).exists());
+    }
+
+    #[test]
+    fn restore_backup_refuses_a_database_without_a_header() {
+        let home_dir = ensure_node_home_directory_exists(
+            "db_backup",
+            "restore_backup_refuses_a_database_without_a_header",
+        );
+        let data_dir = home_dir.join("data");
+        drop(make_database(&home_dir));
+
+        let result = restore_backup(&home_dir.join(DATABASE_FILE), &data_dir);
+
+        assert_eq!(
+            result,
+            Err(
+                "The file carries no schema version header; it was not made by a Node backup"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn restore_backup_runner_reports_errors_as_configuration_errors() {
+        let home_dir = ensure_node_home_directory_exists(
+            "db_backup",
+            "restore_backup_runner_reports_errors_as_configuration_errors",
+        );
+        let missing_path = home_dir.join("missing.db");
+        let args_vec: Vec<String> = ArgsBuilder::new()
+            .param("--data-directory", home_dir.join("data").to_str().unwrap())
+            .param("--real-user", "123::")
+            .param("--restore-backup", missing_path.to_str().unwrap())
+            .into();
+        let subject = RestoreBackupRunnerReal {
+            dirs_wrapper: Box::new(DirsWrapperReal::default()),
+        };
+        let mut holder = FakeStreamHolder::new();
+
+        let result = subject.go(&mut holder.streams(), args_vec.as_slice());
+
+        let error = result.unwrap_err();
+        assert_eq!(error.param_errors.len(), 1);
+        assert_eq!(error.param_errors[0].parameter, "restore-backup");
+        assert!(error.param_errors[0].reason.starts_with(&format!(
+            "Could not read {} as a database backup: ",
+            missing_path.display()
+        )));
+        assert_eq!(holder.stdout.get_string(), "");
+    }
+
+    #[test]
+    fn restore_backup_runner_restores_and_reports() {
+        let home_dir = ensure_node_home_directory_exists(
+            "db_backup",
+            "restore_backup_runner_restores_and_reports",
+        );
+        let data_dir = home_dir.join("data");
+        let backup = DatabaseBackupReal::new(make_database(&home_dir), &home_dir)
+            .backup()
+            .unwrap();
+        let args_vec: Vec<String> = ArgsBuilder::new()
+            .param("--data-directory", data_dir.to_str().unwrap())
+            .param("--real-user", "123::")
+            .param("--restore-backup", backup.path.to_str().unwrap())
+            .into();
+        let subject = RestoreBackupRunnerReal {
+            dirs_wrapper: Box::new(DirsWrapperReal::default()),
+        };
+        let mut holder = FakeStreamHolder::new();
+
+        let result = subject.go(&mut holder.streams(), args_vec.as_slice());
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            holder.stdout.get_string(),
+            format!(
+                "Restored {} (database schema version {}) into {}\n",
+                backup.path.display(),
+                CURRENT_SCHEMA_VERSION,
+                data_dir.join(DATABASE_FILE).display()
+            )
+        );
+        assert_eq!(
+            read_schema_version_header(&data_dir.join(DATABASE_FILE)),
+            Ok(CURRENT_SCHEMA_VERSION)
+        );
+    }
+}
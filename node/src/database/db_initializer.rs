@@ -138,6 +138,7 @@ impl DbInitializerReal {
         Self::create_pending_payable_table(conn);
         Self::create_receivable_table(conn);
         Self::create_banned_table(conn);
+        Self::create_ledger_table(conn);
     }
 
     pub fn create_config_table(conn: &Connection) {
@@ -189,6 +190,13 @@ impl DbInitializerReal {
             false,
             "earning wallet address",
         );
+        Self::set_config_value(
+            conn,
+            "earning_wallet_shares",
+            None,
+            false,
+            "wallets sharing the earnings, with their percentages",
+        );
         Self::set_config_value(
             conn,
             "neighborhood_mode",
@@ -275,13 +283,14 @@ impl DbInitializerReal {
                     amount_low_b integer not null,
                     payable_timestamp integer not null,
                     attempt integer not null,
-                    process_error text null
+                    process_error text null,
+                    recipient_wallet text null
             )",
             [],
         )
         .expect("Can't create pending_payable table");
         conn.execute(
-            "CREATE UNIQUE INDEX pending_payable_hash_idx ON pending_payable (transaction_hash)",
+            "CREATE INDEX pending_payable_hash_idx ON pending_payable (transaction_hash)",
             [],
         )
         .expect("Can't create transaction hash index in pending payments");
@@ -294,7 +303,10 @@ impl DbInitializerReal {
                     balance_high_b integer not null,
                     balance_low_b integer not null,
                     last_paid_timestamp integer not null,
-                    pending_payable_rowid integer null
+                    pending_payable_rowid integer null,
+                    earning_wallet_shares text null,
+                    last_seen_timestamp integer null,
+                    written_off_timestamp integer null
             ) strict",
             [],
         )
@@ -316,12 +328,41 @@ impl DbInitializerReal {
 
     pub fn create_banned_table(conn: &Connection) {
         conn.execute(
-            "create table banned ( wallet_address text primary key )",
+            "create table banned (
+                    wallet_address text primary key,
+                    banned_at integer null,
+                    reason text null,
+                    balance_high_b integer null,
+                    balance_low_b integer null,
+                    note text null,
+                    pardoned integer not null default 0
+            )",
             [],
         )
         .expect("Can't create banned table");
     }
 
+    pub fn create_ledger_table(conn: &Connection) {
+        conn.execute(
+            "create table ledger (
+                    rowid integer primary key,
+                    timestamp integer not null,
+                    kind text not null,
+                    wallet_address text not null,
+                    amount_high_b integer not null,
+                    amount_low_b integer not null,
+                    transaction_hash text null
+            ) strict",
+            [],
+        )
+        .expect("Can't create ledger table");
+        conn.execute(
+            "CREATE INDEX ledger_timestamp_idx ON ledger (timestamp)",
+            [],
+        )
+        .expect("Can't create timestamp index in ledger");
+    }
+
     fn extra_configuration(
         conn: &Connection,
         init_config: &DbInitializationConfig,
@@ -661,7 +702,7 @@ mod tests {
     #[test]
     fn constants_have_correct_values() {
         assert_eq!(DATABASE_FILE, "node-data.db");
-        assert_eq!(CURRENT_SCHEMA_VERSION, 11);
+        assert_eq!(CURRENT_SCHEMA_VERSION, 16);
     }
 
     #[test]
@@ -701,7 +742,7 @@ mod tests {
             .initialize(&home_dir, DbInitializationConfig::test_default())
             .unwrap();
 
-        let mut stmt = conn.prepare("select rowid, transaction_hash, amount_high_b, amount_low_b, payable_timestamp, attempt, process_error from pending_payable").unwrap();
+        let mut stmt = conn.prepare("select rowid, transaction_hash, amount_high_b, amount_low_b, payable_timestamp, attempt, process_error, recipient_wallet from pending_payable").unwrap();
         let mut payable_contents = stmt.query_map([], |_| Ok(42)).unwrap();
         assert!(payable_contents.next().is_none());
         let expected_key_words: &[&[&str]] = &[
@@ -712,6 +753,7 @@ mod tests {
             &["payable_timestamp", "integer", "not", "null"],
             &["attempt", "integer", "not", "null"],
             &["process_error", "text", "null"],
+            &["recipient_wallet", "text", "null"],
         ];
         assert_create_table_stm_contains_all_parts(&*conn, "pending_payable", expected_key_words);
         let expected_key_words: &[&[&str]] = &[&["transaction_hash"]];
@@ -734,7 +776,7 @@ mod tests {
             .initialize(&home_dir, DbInitializationConfig::test_default())
             .unwrap();
 
-        let mut stmt = conn.prepare ("select wallet_address, balance_high_b, balance_low_b, last_paid_timestamp, pending_payable_rowid from payable").unwrap ();
+        let mut stmt = conn.prepare ("select wallet_address, balance_high_b, balance_low_b, last_paid_timestamp, pending_payable_rowid, earning_wallet_shares, last_seen_timestamp, written_off_timestamp from payable").unwrap ();
         let mut payable_contents = stmt.query_map([], |_| Ok(42)).unwrap();
         assert!(payable_contents.next().is_none());
         assert_table_created_as_strict(&*conn, "payable");
@@ -744,6 +786,9 @@ mod tests {
             &["balance_low_b", "integer", "not", "null"],
             &["last_paid_timestamp", "integer", "not", "null"],
             &["pending_payable_rowid", "integer", "null"],
+            &["earning_wallet_shares", "text", "null"],
+            &["last_seen_timestamp", "integer", "null"],
+            &["written_off_timestamp", "integer", "null"],
         ];
         assert_create_table_stm_contains_all_parts(&*conn, "payable", expected_key_words);
         assert_no_index_exists_for_table(conn.as_ref(), "payable")
@@ -793,11 +838,55 @@ mod tests {
         let mut stmt = conn.prepare("select wallet_address from banned").unwrap();
         let mut banned_contents = stmt.query_map([], |_| Ok(42)).unwrap();
         assert!(banned_contents.next().is_none());
-        let expected_key_words: &[&[&str]] = &[&["wallet_address", "text", "primary", "key"]];
+        let expected_key_words: &[&[&str]] = &[
+            &["wallet_address", "text", "primary", "key"],
+            &["banned_at", "integer", "null"],
+            &["reason", "text", "null"],
+            &["balance_high_b", "integer", "null"],
+            &["balance_low_b", "integer", "null"],
+            &["note", "text", "null"],
+            &["pardoned", "integer", "not", "null", "default", "0"],
+        ];
         assert_create_table_stm_contains_all_parts(conn.as_ref(), "banned", expected_key_words);
         assert_no_index_exists_for_table(conn.as_ref(), "banned")
     }
 
+    #[test]
+    fn db_initialize_creates_ledger_table() {
+        let home_dir = ensure_node_home_directory_does_not_exist(
+            "db_initializer",
+            "db_initialize_creates_ledger_table",
+        );
+        let subject = DbInitializerReal::default();
+
+        let conn = subject
+            .initialize(&home_dir, DbInitializationConfig::test_default())
+            .unwrap();
+
+        let mut stmt = conn
+            .prepare("select rowid, timestamp, kind, wallet_address, amount_high_b, amount_low_b, transaction_hash from ledger")
+            .unwrap();
+        let mut ledger_contents = stmt.query_map([], |_| Ok(())).unwrap();
+        assert!(ledger_contents.next().is_none());
+        assert_table_created_as_strict(&*conn, "ledger");
+        let expected_key_words: &[&[&str]] = &[
+            &["rowid", "integer", "primary", "key"],
+            &["timestamp", "integer", "not", "null"],
+            &["kind", "text", "not", "null"],
+            &["wallet_address", "text", "not", "null"],
+            &["amount_high_b", "integer", "not", "null"],
+            &["amount_low_b", "integer", "not", "null"],
+            &["transaction_hash", "text", "null"],
+        ];
+        assert_create_table_stm_contains_all_parts(conn.as_ref(), "ledger", expected_key_words);
+        let expected_key_words: &[&[&str]] = &[&["timestamp"]];
+        assert_index_stm_is_coupled_with_right_parameter(
+            conn.as_ref(),
+            "ledger_timestamp_idx",
+            expected_key_words,
+        )
+    }
+
     #[test]
     #[should_panic(expected = "The database undoubtedly exists, but: unable to open database file")]
     fn double_check_the_result_of_db_migration_panics_if_cannot_reestablish_the_connection_to_the_database(
@@ -928,6 +1017,7 @@ mod tests {
         assert!(clandestine_port < 10000);
         verify(&mut config_vec, "consuming_wallet_private_key", None, true);
         verify(&mut config_vec, "earning_wallet_address", None, false);
+        verify(&mut config_vec, "earning_wallet_shares", None, false);
         verify(&mut config_vec, EXAMPLE_ENCRYPTED, None, true);
         verify(
             &mut config_vec,
@@ -1006,6 +1096,7 @@ mod tests {
                 "pending_payable".to_string(),
                 "receivable".to_string(),
                 "banned".to_string(),
+                "ledger".to_string(),
             ]),
         );
         let config_map = extract_configurations(&conn);
@@ -1041,6 +1132,7 @@ mod tests {
         assert!(clandestine_port < 10000);
         verify(&mut config_vec, "consuming_wallet_private_key", None, true);
         verify(&mut config_vec, "earning_wallet_address", None, false);
+        verify(&mut config_vec, "earning_wallet_shares", None, false);
         verify(&mut config_vec, EXAMPLE_ENCRYPTED, None, true);
         verify(
             &mut config_vec,
@@ -3,6 +3,11 @@
 use crate::database::db_initializer::ExternalData;
 use crate::database::db_migrations::migrations::migration_0_to_1::Migrate_0_to_1;
 use crate::database::db_migrations::migrations::migration_10_to_11::Migrate_10_to_11;
+use crate::database::db_migrations::migrations::migration_11_to_12::Migrate_11_to_12;
+use crate::database::db_migrations::migrations::migration_12_to_13::Migrate_12_to_13;
+use crate::database::db_migrations::migrations::migration_13_to_14::Migrate_13_to_14;
+use crate::database::db_migrations::migrations::migration_14_to_15::Migrate_14_to_15;
+use crate::database::db_migrations::migrations::migration_15_to_16::Migrate_15_to_16;
 use crate::database::db_migrations::migrations::migration_1_to_2::Migrate_1_to_2;
 use crate::database::db_migrations::migrations::migration_2_to_3::Migrate_2_to_3;
 use crate::database::db_migrations::migrations::migration_3_to_4::Migrate_3_to_4;
@@ -82,6 +87,11 @@ impl DbMigratorReal {
             &Migrate_8_to_9,
             &Migrate_9_to_10,
             &Migrate_10_to_11,
+            &Migrate_11_to_12,
+            &Migrate_12_to_13,
+            &Migrate_13_to_14,
+            &Migrate_14_to_15,
+            &Migrate_15_to_16,
         ]
     }
 
@@ -141,6 +151,28 @@ impl DbMigratorReal {
         Ok(())
     }
 
+    // A backup from an older schema is fine to restore: the DbInitializer migrates it the next
+    // time the Node starts. One from a newer schema would be unreadable by this binary.
+    pub fn check_backup_schema_version(
+        backup_version: usize,
+        binary_version: usize,
+    ) -> Result<(), String> {
+        if backup_version == 0 {
+            Err(
+                "The file carries no schema version header; it was not made by a Node backup"
+                    .to_string(),
+            )
+        } else if backup_version > binary_version {
+            Err(format!(
+                "The backup has database schema version {}, but this Node only understands versions \
+                 up to {}; restore it with a newer Node",
+                backup_version, binary_version
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
     fn select_migrations_to_process<'a>(
         obsolete_schema: usize,
         list_of_migrations: &'a [&'a (dyn DatabaseMigration + 'a)],
@@ -444,6 +476,42 @@ mod tests {
         assert!(two_numbers_are_sequential(result, CURRENT_SCHEMA_VERSION))
     }
 
+    #[test]
+    fn check_backup_schema_version_accepts_current_and_older_versions() {
+        let current = DbMigratorReal::check_backup_schema_version(13, 13);
+        let older = DbMigratorReal::check_backup_schema_version(4, 13);
+
+        assert_eq!(current, Ok(()));
+        assert_eq!(older, Ok(()));
+    }
+
+    #[test]
+    fn check_backup_schema_version_refuses_a_newer_version() {
+        let result = DbMigratorReal::check_backup_schema_version(14, 13);
+
+        assert_eq!(
+            result,
+            Err(
+                "The backup has database schema version 14, but this Node only understands \
+                 versions up to 13; restore it with a newer Node"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn check_backup_schema_version_refuses_a_missing_header() {
+        let result = DbMigratorReal::check_backup_schema_version(0, 13);
+
+        assert_eq!(
+            result,
+            Err(
+                "The file carries no schema version header; it was not made by a Node backup"
+                    .to_string()
+            )
+        );
+    }
+
     #[test]
     fn migrate_semi_automated_returns_an_error_from_update_schema_version() {
         let update_schema_version_params_arc = Arc::new(Mutex::new(vec![]));
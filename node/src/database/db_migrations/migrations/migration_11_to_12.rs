@@ -0,0 +1,97 @@
+// Copyright (c) 2024, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::database::db_migrations::db_migrator::DatabaseMigration;
+use crate::database::db_migrations::migrator_utils::DBMigDeclarator;
+
+#[allow(non_camel_case_types)]
+pub struct Migrate_11_to_12;
+
+impl DatabaseMigration for Migrate_11_to_12 {
+    fn migrate<'a>(
+        &self,
+        declaration_utils: Box<dyn DBMigDeclarator + 'a>,
+    ) -> rusqlite::Result<()> {
+        declaration_utils.execute_upon_transaction(&[
+            &"create table ledger (
+                    rowid integer primary key,
+                    timestamp integer not null,
+                    kind text not null,
+                    wallet_address text not null,
+                    amount_high_b integer not null,
+                    amount_low_b integer not null,
+                    transaction_hash text null
+            ) strict",
+            &"create index ledger_timestamp_idx on ledger (timestamp)",
+        ])
+    }
+
+    fn old_version(&self) -> usize {
+        11
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::db_initializer::{
+        DbInitializationConfig, DbInitializer, DbInitializerReal, DATABASE_FILE,
+    };
+    use crate::test_utils::database_utils::{
+        assert_create_table_stm_contains_all_parts,
+        assert_index_stm_is_coupled_with_right_parameter, assert_table_created_as_strict,
+        assert_table_does_not_exist, bring_db_0_back_to_life_and_return_connection,
+        make_external_data, retrieve_config_row,
+    };
+    use masq_lib::test_utils::utils::ensure_node_home_directory_exists;
+    use std::fs::create_dir_all;
+
+    #[test]
+    fn migration_from_11_to_12_is_properly_set() {
+        let dir_path = ensure_node_home_directory_exists(
+            "db_migrations",
+            "migration_from_11_to_12_is_properly_set",
+        );
+        create_dir_all(&dir_path).unwrap();
+        let db_path = dir_path.join(DATABASE_FILE);
+        let _ = bring_db_0_back_to_life_and_return_connection(&db_path);
+        let subject = DbInitializerReal::default();
+        let connection = subject
+            .initialize_to_version(
+                &dir_path,
+                11,
+                DbInitializationConfig::create_or_migrate(make_external_data()),
+            )
+            .unwrap();
+        assert_table_does_not_exist(connection.as_ref(), "ledger");
+
+        let result = subject.initialize_to_version(
+            &dir_path,
+            12,
+            DbInitializationConfig::create_or_migrate(make_external_data()),
+        );
+
+        let connection = result.unwrap();
+        let expected_key_words: &[&[&str]] = &[
+            &["rowid", "integer", "primary", "key"],
+            &["timestamp", "integer", "not", "null"],
+            &["kind", "text", "not", "null"],
+            &["wallet_address", "text", "not", "null"],
+            &["amount_high_b", "integer", "not", "null"],
+            &["amount_low_b", "integer", "not", "null"],
+            &["transaction_hash", "text", "null"],
+        ];
+        assert_table_created_as_strict(connection.as_ref(), "ledger");
+        assert_create_table_stm_contains_all_parts(
+            connection.as_ref(),
+            "ledger",
+            expected_key_words,
+        );
+        assert_index_stm_is_coupled_with_right_parameter(
+            connection.as_ref(),
+            "ledger_timestamp_idx",
+            &[&["timestamp"]],
+        );
+        let (cs_value, cs_encrypted) = retrieve_config_row(connection.as_ref(), "schema_version");
+        assert_eq!(cs_value, Some("12".to_string()));
+        assert_eq!(cs_encrypted, false)
+    }
+}
@@ -0,0 +1,104 @@
+// Copyright (c) 2024, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::database::db_migrations::db_migrator::DatabaseMigration;
+use crate::database::db_migrations::migrator_utils::DBMigDeclarator;
+
+#[allow(non_camel_case_types)]
+pub struct Migrate_12_to_13;
+
+impl DatabaseMigration for Migrate_12_to_13 {
+    fn migrate<'a>(
+        &self,
+        declaration_utils: Box<dyn DBMigDeclarator + 'a>,
+    ) -> rusqlite::Result<()> {
+        declaration_utils.execute_upon_transaction(&[
+            &"alter table banned add banned_at integer null",
+            &"alter table banned add reason text null",
+            &"alter table banned add balance_high_b integer null",
+            &"alter table banned add balance_low_b integer null",
+            &"alter table banned add note text null",
+            &"alter table banned add pardoned integer not null default 0",
+        ])
+    }
+
+    fn old_version(&self) -> usize {
+        12
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::db_initializer::{
+        DbInitializationConfig, DbInitializer, DbInitializerReal, DATABASE_FILE,
+    };
+    use crate::test_utils::database_utils::{
+        assert_create_table_stm_contains_all_parts, bring_db_0_back_to_life_and_return_connection,
+        make_external_data, retrieve_config_row,
+    };
+    use masq_lib::test_utils::utils::ensure_node_home_directory_exists;
+    use rusqlite::ToSql;
+    use std::fs::create_dir_all;
+
+    #[test]
+    fn migration_from_12_to_13_is_properly_set() {
+        let dir_path = ensure_node_home_directory_exists(
+            "db_migrations",
+            "migration_from_12_to_13_is_properly_set",
+        );
+        create_dir_all(&dir_path).unwrap();
+        let db_path = dir_path.join(DATABASE_FILE);
+        let _ = bring_db_0_back_to_life_and_return_connection(&db_path);
+        let subject = DbInitializerReal::default();
+        let connection = subject
+            .initialize_to_version(
+                &dir_path,
+                12,
+                DbInitializationConfig::create_or_migrate(make_external_data()),
+            )
+            .unwrap();
+        let wallet_address = "0x0000000000000000000000000000000000626f6f";
+        let params: &[&dyn ToSql] = &[&wallet_address];
+        connection
+            .prepare("insert into banned (wallet_address) values (?)")
+            .unwrap()
+            .execute(params)
+            .unwrap();
+
+        let result = subject.initialize_to_version(
+            &dir_path,
+            13,
+            DbInitializationConfig::create_or_migrate(make_external_data()),
+        );
+
+        let connection = result.unwrap();
+        let expected_key_words: &[&[&str]] = &[
+            &["wallet_address", "text", "primary", "key"],
+            &["banned_at", "integer", "null"],
+            &["reason", "text", "null"],
+            &["balance_high_b", "integer", "null"],
+            &["balance_low_b", "integer", "null"],
+            &["note", "text", "null"],
+            &["pardoned", "integer", "not", "null", "default", "0"],
+        ];
+        assert_create_table_stm_contains_all_parts(
+            connection.as_ref(),
+            "banned",
+            expected_key_words,
+        );
+        let (reason, pardoned) = connection
+            .prepare("select reason, pardoned from banned where wallet_address = ?")
+            .unwrap()
+            .query_row(params, |row| {
+                Ok((
+                    row.get::<usize, Option<String>>(0)?,
+                    row.get::<usize, i64>(1)?,
+                ))
+            })
+            .unwrap();
+        assert_eq!(reason, None);
+        assert_eq!(pardoned, 0);
+        let (cs_value, cs_encrypted) = retrieve_config_row(connection.as_ref(), "schema_version");
+        assert_eq!(cs_value, Some("13".to_string()));
+        assert_eq!(cs_encrypted, false)
+    }
+}
@@ -0,0 +1,85 @@
+// Copyright (c) 2024, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::database::db_migrations::db_migrator::DatabaseMigration;
+use crate::database::db_migrations::migrator_utils::DBMigDeclarator;
+
+#[allow(non_camel_case_types)]
+pub struct Migrate_13_to_14;
+
+impl DatabaseMigration for Migrate_13_to_14 {
+    fn migrate<'a>(
+        &self,
+        declaration_utils: Box<dyn DBMigDeclarator + 'a>,
+    ) -> rusqlite::Result<()> {
+        // Creditors' shares are learned from Gossip, so existing rows start without any
+        declaration_utils.execute_upon_transaction(&[
+            &"INSERT INTO config (name, value, encrypted) VALUES ('earning_wallet_shares', null, 0)",
+            &"alter table payable add column earning_wallet_shares text null",
+        ])
+    }
+
+    fn old_version(&self) -> usize {
+        13
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::db_initializer::{
+        DbInitializationConfig, DbInitializer, DbInitializerReal, DATABASE_FILE,
+    };
+    use crate::test_utils::database_utils::{
+        assert_create_table_stm_contains_all_parts, bring_db_0_back_to_life_and_return_connection,
+        make_external_data, retrieve_config_row,
+    };
+    use masq_lib::test_utils::utils::ensure_node_home_directory_exists;
+
+    #[test]
+    fn migration_from_13_to_14_is_properly_set() {
+        let dir_path = ensure_node_home_directory_exists(
+            "db_migrations",
+            "migration_from_13_to_14_is_properly_set",
+        );
+        let db_path = dir_path.join(DATABASE_FILE);
+        let _ = bring_db_0_back_to_life_and_return_connection(&db_path);
+        let subject = DbInitializerReal::default();
+        {
+            subject
+                .initialize_to_version(
+                    &dir_path,
+                    13,
+                    DbInitializationConfig::create_or_migrate(make_external_data()),
+                )
+                .unwrap();
+        }
+
+        let result = subject.initialize_to_version(
+            &dir_path,
+            14,
+            DbInitializationConfig::create_or_migrate(make_external_data()),
+        );
+
+        let connection = result.unwrap();
+        let (shares_value, shares_encrypted) =
+            retrieve_config_row(connection.as_ref(), "earning_wallet_shares");
+        assert_eq!(shares_value, None);
+        assert_eq!(shares_encrypted, false);
+        let expected_key_words: &[&[&str]] = &[
+            &["wallet_address", "text", "primary", "key"],
+            &["balance_high_b", "integer", "not", "null"],
+            &["balance_low_b", "integer", "not", "null"],
+            &["last_paid_timestamp", "integer", "not", "null"],
+            &["pending_payable_rowid", "integer", "null"],
+            &["earning_wallet_shares", "text", "null"],
+        ];
+        assert_create_table_stm_contains_all_parts(
+            connection.as_ref(),
+            "payable",
+            expected_key_words,
+        );
+        let (schv_value, schv_encrypted) =
+            retrieve_config_row(connection.as_ref(), "schema_version");
+        assert_eq!(schv_value, Some("14".to_string()));
+        assert_eq!(schv_encrypted, false);
+    }
+}
@@ -0,0 +1,91 @@
+// Copyright (c) 2024, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::database::db_migrations::db_migrator::DatabaseMigration;
+use crate::database::db_migrations::migrator_utils::DBMigDeclarator;
+
+#[allow(non_camel_case_types)]
+pub struct Migrate_14_to_15;
+
+impl DatabaseMigration for Migrate_14_to_15 {
+    fn migrate<'a>(
+        &self,
+        declaration_utils: Box<dyn DBMigDeclarator + 'a>,
+    ) -> rusqlite::Result<()> {
+        // A multisend transaction pays several creditors, each of whom gets a fingerprint
+        // of their own, all carrying the same transaction hash; the recipient tells them apart
+        declaration_utils.execute_upon_transaction(&[
+            &"drop index pending_payable_hash_idx",
+            &"create index pending_payable_hash_idx on pending_payable (transaction_hash)",
+            &"alter table pending_payable add column recipient_wallet text null",
+        ])
+    }
+
+    fn old_version(&self) -> usize {
+        14
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::db_initializer::{
+        DbInitializationConfig, DbInitializer, DbInitializerReal, DATABASE_FILE,
+    };
+    use crate::test_utils::database_utils::{
+        assert_index_stm_is_coupled_with_right_parameter,
+        bring_db_0_back_to_life_and_return_connection, make_external_data, retrieve_config_row,
+    };
+    use masq_lib::test_utils::utils::ensure_node_home_directory_exists;
+
+    #[test]
+    fn migration_from_14_to_15_is_properly_set() {
+        let dir_path = ensure_node_home_directory_exists(
+            "db_migrations",
+            "migration_from_14_to_15_is_properly_set",
+        );
+        let db_path = dir_path.join(DATABASE_FILE);
+        let _ = bring_db_0_back_to_life_and_return_connection(&db_path);
+        let subject = DbInitializerReal::default();
+        let insert_sql = "insert into pending_payable (transaction_hash, amount_high_b, \
+            amount_low_b, payable_timestamp, attempt, process_error) values ('0x1234', 0, 500, \
+            1000, 1, null)";
+        {
+            let connection = subject
+                .initialize_to_version(
+                    &dir_path,
+                    14,
+                    DbInitializationConfig::create_or_migrate(make_external_data()),
+                )
+                .unwrap();
+            connection.prepare(insert_sql).unwrap().execute([]).unwrap();
+            assert!(connection.prepare(insert_sql).unwrap().execute([]).is_err());
+        }
+
+        let result = subject.initialize_to_version(
+            &dir_path,
+            15,
+            DbInitializationConfig::create_or_migrate(make_external_data()),
+        );
+
+        let connection = result.unwrap();
+        assert_eq!(connection.prepare(insert_sql).unwrap().execute([]), Ok(1));
+        let insert_with_recipient_sql = "insert into pending_payable (transaction_hash, \
+            amount_high_b, amount_low_b, payable_timestamp, attempt, process_error, \
+            recipient_wallet) values ('0x1234', 0, 700, 1000, 1, null, '0x0000000000000000000000000000000000001234')";
+        assert_eq!(
+            connection
+                .prepare(insert_with_recipient_sql)
+                .unwrap()
+                .execute([]),
+            Ok(1)
+        );
+        assert_index_stm_is_coupled_with_right_parameter(
+            connection.as_ref(),
+            "pending_payable_hash_idx",
+            &[&["transaction_hash"]],
+        );
+        let (schv_value, schv_encrypted) =
+            retrieve_config_row(connection.as_ref(), "schema_version");
+        assert_eq!(schv_value, Some("15".to_string()));
+        assert_eq!(schv_encrypted, false);
+    }
+}
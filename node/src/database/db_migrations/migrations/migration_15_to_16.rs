@@ -0,0 +1,112 @@
+// Copyright (c) 2024, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::database::db_migrations::db_migrator::DatabaseMigration;
+use crate::database::db_migrations::migrator_utils::DBMigDeclarator;
+
+#[allow(non_camel_case_types)]
+pub struct Migrate_15_to_16;
+
+impl DatabaseMigration for Migrate_15_to_16 {
+    fn migrate<'a>(
+        &self,
+        declaration_utils: Box<dyn DBMigDeclarator + 'a>,
+    ) -> rusqlite::Result<()> {
+        // Creditors known before the upgrade are deemed seen right now, otherwise the first
+        // payable scan would write off every debt that hasn't been paid for a while
+        declaration_utils.execute_upon_transaction(&[
+            &"alter table payable add column last_seen_timestamp integer null",
+            &"alter table payable add column written_off_timestamp integer null",
+            &"update payable set last_seen_timestamp = cast(strftime('%s', 'now') as integer)",
+        ])
+    }
+
+    fn old_version(&self) -> usize {
+        15
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::db_initializer::{
+        DbInitializationConfig, DbInitializer, DbInitializerReal, DATABASE_FILE,
+    };
+    use crate::test_utils::database_utils::{
+        assert_create_table_stm_contains_all_parts, bring_db_0_back_to_life_and_return_connection,
+        make_external_data, retrieve_config_row,
+    };
+    use masq_lib::test_utils::utils::ensure_node_home_directory_exists;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn migration_from_15_to_16_is_properly_set() {
+        let dir_path = ensure_node_home_directory_exists(
+            "db_migrations",
+            "migration_from_15_to_16_is_properly_set",
+        );
+        let db_path = dir_path.join(DATABASE_FILE);
+        let _ = bring_db_0_back_to_life_and_return_connection(&db_path);
+        let subject = DbInitializerReal::default();
+        {
+            let connection = subject
+                .initialize_to_version(
+                    &dir_path,
+                    15,
+                    DbInitializationConfig::create_or_migrate(make_external_data()),
+                )
+                .unwrap();
+            connection
+                .prepare(
+                    "insert into payable (wallet_address, balance_high_b, balance_low_b, \
+                    last_paid_timestamp, pending_payable_rowid) values ('0x1234', 0, 500, 1000, null)",
+                )
+                .unwrap()
+                .execute([])
+                .unwrap();
+        }
+        let before = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+
+        let result = subject.initialize_to_version(
+            &dir_path,
+            16,
+            DbInitializationConfig::create_or_migrate(make_external_data()),
+        );
+
+        let after = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        let connection = result.unwrap();
+        let (last_seen, written_off): (i64, Option<i64>) = connection
+            .prepare(
+                "select last_seen_timestamp, written_off_timestamp from payable \
+                where wallet_address = '0x1234'",
+            )
+            .unwrap()
+            .query_row([], |row| Ok((row.get(0).unwrap(), row.get(1).unwrap())))
+            .unwrap();
+        assert!(before <= last_seen && last_seen <= after);
+        assert_eq!(written_off, None);
+        let expected_key_words: &[&[&str]] = &[
+            &["wallet_address", "text", "primary", "key"],
+            &["balance_high_b", "integer", "not", "null"],
+            &["balance_low_b", "integer", "not", "null"],
+            &["last_paid_timestamp", "integer", "not", "null"],
+            &["pending_payable_rowid", "integer", "null"],
+            &["earning_wallet_shares", "text", "null"],
+            &["last_seen_timestamp", "integer", "null"],
+            &["written_off_timestamp", "integer", "null"],
+        ];
+        assert_create_table_stm_contains_all_parts(
+            connection.as_ref(),
+            "payable",
+            expected_key_words,
+        );
+        let (schv_value, schv_encrypted) =
+            retrieve_config_row(connection.as_ref(), "schema_version");
+        assert_eq!(schv_value, Some("16".to_string()));
+        assert_eq!(schv_encrypted, false);
+    }
+}
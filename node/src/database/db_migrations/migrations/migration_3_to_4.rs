@@ -4,6 +4,7 @@ use crate::blockchain::bip39::Bip39;
 use crate::database::db_migrations::db_migrator::DatabaseMigration;
 use crate::database::db_migrations::migrator_utils::DBMigDeclarator;
 use crate::db_config::db_encryption_layer::DbEncryptionLayer;
+use crate::db_config::db_kdf::DerivedKeyCache;
 use crate::db_config::typed_config_layer::decode_bytes;
 use crate::sub_lib::cryptde::PlainData;
 use itertools::Itertools;
@@ -26,13 +27,23 @@ impl Migrate_3_to_4 {
         ) {
             (Some(consuming_path), Some(example_encrypted), Some(seed_encrypted)) => {
                 let password_opt = utils.db_password();
-                if !DbEncryptionLayer::password_matches(&password_opt, &Some(example_encrypted)) {
+                let key_cache = DerivedKeyCache::default();
+                if !DbEncryptionLayer::password_matches(
+                    &password_opt,
+                    &Some(example_encrypted),
+                    &key_cache,
+                ) {
                     panic!("Migrating Database from 3 to 4: bad password");
                 }
-                let seed_encoded =
-                    DbEncryptionLayer::decrypt_value(&Some(seed_encrypted), &password_opt, "seed")
-                        .expect("Internal error")
-                        .expect("Internal error");
+                let seed_encoded = DbEncryptionLayer::decrypt_value(
+                    &Some(seed_encrypted),
+                    &password_opt,
+                    &None,
+                    &key_cache,
+                    "seed",
+                )
+                .expect("Internal error")
+                .expect("Internal error");
                 let seed_data = decode_bytes(Some(seed_encoded))
                     .expect("Internal error")
                     .expect("Internal error");
@@ -118,6 +129,7 @@ mod tests {
     use crate::database::db_migrations::migrations::migration_3_to_4::Migrate_3_to_4;
     use crate::database::db_migrations::test_utils::DBMigDeclaratorMock;
     use crate::db_config::db_encryption_layer::DbEncryptionLayer;
+    use crate::db_config::db_kdf::DerivedKeyCache;
     use crate::db_config::typed_config_layer::encode_bytes;
     use crate::sub_lib::cryptde::PlainData;
     use crate::test_utils::database_utils::{
@@ -157,10 +169,15 @@ mod tests {
                     .secret();
             let seed_plain = PlainData::new(seed.as_bytes());
             let seed_encoded = encode_bytes(Some(seed_plain)).unwrap().unwrap();
-            let seed_encrypted =
-                DbEncryptionLayer::encrypt_value(&Some(seed_encoded), password_opt, "seed")
-                    .unwrap()
-                    .unwrap();
+            let seed_encrypted = DbEncryptionLayer::encrypt_value(
+                &Some(seed_encoded),
+                password_opt,
+                &None,
+                &DerivedKeyCache::default(),
+                "seed",
+            )
+            .unwrap()
+            .unwrap();
             let mut example_data = [0u8; 32];
             rand::thread_rng().fill(&mut example_data);
             let example_encrypted =
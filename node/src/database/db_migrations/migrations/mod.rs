@@ -2,6 +2,11 @@
 
 pub mod migration_0_to_1;
 pub mod migration_10_to_11;
+pub mod migration_11_to_12;
+pub mod migration_12_to_13;
+pub mod migration_13_to_14;
+pub mod migration_14_to_15;
+pub mod migration_15_to_16;
 pub mod migration_1_to_2;
 pub mod migration_2_to_3;
 pub mod migration_3_to_4;
@@ -3,6 +3,7 @@
 #![cfg(test)]
 pub mod transaction_wrapper_mock;
 
+use crate::database::db_backup::{BackupRecord, DatabaseBackup};
 use crate::database::db_initializer::DbInitializationConfig;
 use crate::database::db_initializer::{DbInitializer, InitializationError};
 use crate::database::rusqlite_wrappers::{ConnectionWrapper, TransactionSafeWrapper};
@@ -17,6 +18,8 @@ pub struct ConnectionWrapperMock<'conn> {
     prepare_params: Arc<Mutex<Vec<String>>>,
     prepare_results: RefCell<Vec<Result<Statement<'conn>, Error>>>,
     transaction_results: RefCell<Vec<Result<TransactionSafeWrapper<'conn>, Error>>>,
+    backup_to_params: Arc<Mutex<Vec<PathBuf>>>,
+    backup_to_results: RefCell<Vec<Result<(), Error>>>,
 }
 
 // We don't know better how to deal with the third-party code for `Statement` that inherits
@@ -44,6 +47,16 @@ impl<'conn> ConnectionWrapperMock<'conn> {
         self.transaction_results.borrow_mut().push(result);
         self
     }
+
+    pub fn backup_to_params(mut self, params: &Arc<Mutex<Vec<PathBuf>>>) -> Self {
+        self.backup_to_params = params.clone();
+        self
+    }
+
+    pub fn backup_to_result(self, result: Result<(), Error>) -> Self {
+        self.backup_to_results.borrow_mut().push(result);
+        self
+    }
 }
 
 impl ConnectionWrapper for ConnectionWrapperMock<'_> {
@@ -58,6 +71,14 @@ impl ConnectionWrapper for ConnectionWrapperMock<'_> {
     fn transaction(&mut self) -> Result<TransactionSafeWrapper, Error> {
         self.transaction_results.borrow_mut().remove(0)
     }
+
+    fn backup_to(&self, destination: &Path) -> Result<(), Error> {
+        self.backup_to_params
+            .lock()
+            .unwrap()
+            .push(destination.to_path_buf());
+        self.backup_to_results.borrow_mut().remove(0)
+    }
 }
 
 #[derive(Default)]
@@ -114,3 +135,32 @@ impl DbInitializerMock {
         self
     }
 }
+
+#[derive(Default)]
+pub struct DatabaseBackupMock {
+    backup_params: Arc<Mutex<Vec<()>>>,
+    backup_results: RefCell<Vec<Result<BackupRecord, String>>>,
+}
+
+impl DatabaseBackup for DatabaseBackupMock {
+    fn backup(&self) -> Result<BackupRecord, String> {
+        self.backup_params.lock().unwrap().push(());
+        self.backup_results.borrow_mut().remove(0)
+    }
+}
+
+impl DatabaseBackupMock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn backup_params(mut self, params: &Arc<Mutex<Vec<()>>>) -> Self {
+        self.backup_params = params.clone();
+        self
+    }
+
+    pub fn backup_result(self, result: Result<BackupRecord, String>) -> Self {
+        self.backup_results.borrow_mut().push(result);
+        self
+    }
+}
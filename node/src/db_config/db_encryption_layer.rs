@@ -1,7 +1,9 @@
 // Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 
 use crate::blockchain::bip39::{Bip39, Bip39Error};
+use crate::db_config::db_kdf::{DerivedKeyCache, KdfParameters};
 use crate::db_config::secure_config_layer::SecureConfigLayerError;
+use crate::sub_lib::cryptde::PlainData;
 
 pub struct DbEncryptionLayer {}
 
@@ -9,13 +11,14 @@ impl DbEncryptionLayer {
     pub fn password_matches(
         db_password_opt: &Option<String>,
         example_encrypted_opt: &Option<String>,
+        key_cache: &DerivedKeyCache,
     ) -> bool {
         match (db_password_opt, example_encrypted_opt) {
             (None, None) => true,
             (None, Some(_)) => false,
             (Some(_), None) => false,
             (Some(db_password), Some(encrypted_example)) => {
-                match Bip39::decrypt_bytes(encrypted_example, db_password) {
+                match Self::decrypt_bytes(encrypted_example, db_password, &None, key_cache) {
                     Ok(_) => true,
                     Err(Bip39Error::DecryptionFailure(_)) => false,
                     Err(e) => panic!(
@@ -30,19 +33,23 @@ impl DbEncryptionLayer {
     pub fn decrypt_value(
         crypt_value_opt: &Option<String>,
         db_password_opt: &Option<String>,
+        kdf_parameters_opt: &Option<KdfParameters>,
+        key_cache: &DerivedKeyCache,
         name: &str,
     ) -> Result<Option<String>, SecureConfigLayerError> {
         match (crypt_value_opt, db_password_opt) {
-            (Some(value), Some(password)) => match Bip39::decrypt_bytes(value, password) {
-                Err(_) => Err(SecureConfigLayerError::PasswordError),
-                Ok(plain_data) => match String::from_utf8(plain_data.into()) {
-                    Err(_) => panic!(
-                        "Database is corrupt: contains a non-UTF-8 value for '{}'",
-                        name
-                    ),
-                    Ok(plain_value) => Ok(Some(plain_value)),
-                },
-            },
+            (Some(value), Some(password)) => {
+                match Self::decrypt_bytes(value, password, kdf_parameters_opt, key_cache) {
+                    Err(_) => Err(SecureConfigLayerError::PasswordError),
+                    Ok(plain_data) => match String::from_utf8(plain_data.into()) {
+                        Err(_) => panic!(
+                            "Database is corrupt: contains a non-UTF-8 value for '{}'",
+                            name
+                        ),
+                        Ok(plain_value) => Ok(Some(plain_value)),
+                    },
+                }
+            }
             (Some(_), None) => Err(SecureConfigLayerError::PasswordError),
             (None, _) => Ok(None),
         }
@@ -51,11 +58,18 @@ impl DbEncryptionLayer {
     pub fn encrypt_value(
         plain_value_opt: &Option<String>,
         db_password_opt: &Option<String>,
+        kdf_parameters_opt: &Option<KdfParameters>,
+        key_cache: &DerivedKeyCache,
         name: &str,
     ) -> Result<Option<String>, SecureConfigLayerError> {
         match (plain_value_opt, db_password_opt) {
             (Some(plain_value), Some(password)) => {
-                match Bip39::encrypt_bytes(&plain_value.as_bytes(), password) {
+                match Self::encrypt_bytes(
+                    &plain_value.as_bytes(),
+                    password,
+                    kdf_parameters_opt,
+                    key_cache,
+                ) {
                     Err(_) => panic!("Encryption of '{}' for {} failed", plain_value, name),
                     Ok(crypt_data) => Ok(Some(crypt_data)),
                 }
@@ -68,17 +82,52 @@ impl DbEncryptionLayer {
     pub fn reencrypt_value(
         crypt_value: &str,
         old_password: &str,
+        old_kdf_parameters_opt: &Option<KdfParameters>,
         new_password: &str,
+        new_kdf_parameters: &KdfParameters,
+        key_cache: &DerivedKeyCache,
         name: &str,
     ) -> String {
-        let decrypted_value = match Bip39::decrypt_bytes(crypt_value, old_password) {
-            Ok(plain_data) => plain_data,
-            Err(_) => panic!(
-                "Database is corrupt: configuration value '{}' cannot be decrypted",
-                name
-            ),
-        };
-        Bip39::encrypt_bytes(&decrypted_value, new_password).expect("Encryption failed")
+        let decrypted_value =
+            match Self::decrypt_bytes(crypt_value, old_password, old_kdf_parameters_opt, key_cache)
+            {
+                Ok(plain_data) => plain_data,
+                Err(_) => panic!(
+                    "Database is corrupt: configuration value '{}' cannot be decrypted",
+                    name
+                ),
+            };
+        new_kdf_parameters
+            .encrypt_bytes(&decrypted_value, new_password, key_cache)
+            .expect("Encryption failed")
+    }
+
+    // Values written before the database had KDF parameters stay in the legacy format until the
+    // next time the password is supplied; see SecureConfigLayer::upgrade_encryption().
+    pub fn encrypt_bytes(
+        data: &dyn AsRef<[u8]>,
+        db_password: &str,
+        kdf_parameters_opt: &Option<KdfParameters>,
+        key_cache: &DerivedKeyCache,
+    ) -> Result<String, Bip39Error> {
+        match kdf_parameters_opt {
+            Some(kdf_parameters) => kdf_parameters.encrypt_bytes(data, db_password, key_cache),
+            None => Bip39::encrypt_bytes(data, db_password),
+        }
+    }
+
+    // kdf_parameters_opt holds the database's parameters, from its password example.
+    pub fn decrypt_bytes(
+        crypt_value: &str,
+        db_password: &str,
+        kdf_parameters_opt: &Option<KdfParameters>,
+        key_cache: &DerivedKeyCache,
+    ) -> Result<PlainData, Bip39Error> {
+        if KdfParameters::is_kdf_value(crypt_value) {
+            KdfParameters::decrypt_bytes(crypt_value, db_password, kdf_parameters_opt, key_cache)
+        } else {
+            Bip39::decrypt_bytes(crypt_value, db_password)
+        }
     }
 
     // These methods were extracted from SecureConfigLayer and are covered by the tests there.
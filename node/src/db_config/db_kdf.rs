@@ -0,0 +1,487 @@
+// Copyright (c) 2024, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::blockchain::bip39::Bip39Error;
+use crate::sub_lib::cryptde::PlainData;
+use rustc_hex::{FromHex, ToHex};
+use sodiumoxide::crypto::hash::sha256;
+use sodiumoxide::crypto::pwhash::argon2id13;
+use sodiumoxide::crypto::secretbox;
+use std::sync::Mutex;
+
+pub const KDF_ALGORITHM: &str = "argon2id";
+// These are libsodium's "interactive" limits: a derivation takes a fraction of a second and 64MiB
+pub const DEFAULT_KDF_OPS_LIMIT: usize = 2;
+pub const DEFAULT_KDF_MEM_LIMIT_KIB: usize = 65536;
+// Limits read back from the database are held between libsodium's Argon2id minimums and its
+// "sensitive" limits, so a tampered value can't make the Node grind for hours or exhaust memory.
+pub const MIN_KDF_OPS_LIMIT: usize = 1;
+pub const MAX_KDF_OPS_LIMIT: usize = 4;
+pub const MIN_KDF_MEM_LIMIT_KIB: usize = 8;
+pub const MAX_KDF_MEM_LIMIT_KIB: usize = 1048576;
+// A password change decrypts with the old key and encrypts with the new one, record by record.
+const DERIVED_KEY_CACHE_SIZE: usize = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DbEncryptionScheme {
+    NoPassword,
+    Legacy,
+    Kdf(KdfParameters),
+}
+
+impl DbEncryptionScheme {
+    pub fn kdf_parameters_opt(self) -> Option<KdfParameters> {
+        match self {
+            DbEncryptionScheme::Kdf(kdf_parameters) => Some(kdf_parameters),
+            _ => None,
+        }
+    }
+}
+
+// Derived keys are expensive, so each SecureConfigLayer remembers the last few it derived instead
+// of recomputing one for every value it reads or writes.
+#[derive(Default)]
+pub struct DerivedKeyCache {
+    entries: Mutex<Vec<(KdfParameters, sha256::Digest, secretbox::Key)>>,
+}
+
+impl DerivedKeyCache {
+    fn get(&self, parameters: &KdfParameters, digest: &sha256::Digest) -> Option<secretbox::Key> {
+        self.entries
+            .lock()
+            .expect("Derived-key cache is poisoned")
+            .iter()
+            .find(|(p, d, _)| p == parameters && d == digest)
+            .map(|(_, _, key)| key.clone())
+    }
+
+    fn put(&self, parameters: &KdfParameters, digest: sha256::Digest, key: secretbox::Key) {
+        let mut entries = self.entries.lock().expect("Derived-key cache is poisoned");
+        if entries.len() >= DERIVED_KEY_CACHE_SIZE {
+            entries.remove(0);
+        }
+        entries.push((parameters.clone(), digest, key));
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KdfParameters {
+    pub ops_limit: usize,
+    pub mem_limit_kib: usize,
+    pub salt: Vec<u8>,
+}
+
+// A database has one set of parameters, and they're written only into its password example:
+// "argon2id:<ops>:<mem KiB>:<salt>:<payload>". Every other value is just "argon2id:<payload>".
+impl KdfParameters {
+    pub fn generate() -> Self {
+        Self {
+            ops_limit: DEFAULT_KDF_OPS_LIMIT,
+            mem_limit_kib: DEFAULT_KDF_MEM_LIMIT_KIB,
+            salt: argon2id13::gen_salt().0.to_vec(),
+        }
+    }
+
+    pub fn from_crypt_value(crypt_value: &str) -> Option<Self> {
+        Self::parse(crypt_value)
+            .ok()
+            .and_then(|(parameters_opt, _)| parameters_opt)
+    }
+
+    pub fn is_kdf_value(crypt_value: &str) -> bool {
+        crypt_value.starts_with(&format!("{}:", KDF_ALGORITHM))
+    }
+
+    pub fn is_up_to_date(&self) -> bool {
+        self.ops_limit >= DEFAULT_KDF_OPS_LIMIT && self.mem_limit_kib >= DEFAULT_KDF_MEM_LIMIT_KIB
+    }
+
+    pub fn encrypt_example(
+        &self,
+        data: &dyn AsRef<[u8]>,
+        db_password: &str,
+        key_cache: &DerivedKeyCache,
+    ) -> Result<String, Bip39Error> {
+        Ok(format!(
+            "{}:{}:{}:{}:{}",
+            KDF_ALGORITHM,
+            self.ops_limit,
+            self.mem_limit_kib,
+            self.salt.to_hex::<String>(),
+            self.seal(data, db_password, key_cache)?
+        ))
+    }
+
+    pub fn encrypt_bytes(
+        &self,
+        data: &dyn AsRef<[u8]>,
+        db_password: &str,
+        key_cache: &DerivedKeyCache,
+    ) -> Result<String, Bip39Error> {
+        Ok(format!(
+            "{}:{}",
+            KDF_ALGORITHM,
+            self.seal(data, db_password, key_cache)?
+        ))
+    }
+
+    // Parameters written into the value itself take precedence over the database's.
+    pub fn decrypt_bytes(
+        crypt_value: &str,
+        db_password: &str,
+        kdf_parameters_opt: &Option<KdfParameters>,
+        key_cache: &DerivedKeyCache,
+    ) -> Result<PlainData, Bip39Error> {
+        let (embedded_parameters_opt, payload) = Self::parse(crypt_value)?;
+        let parameters = match embedded_parameters_opt
+            .as_ref()
+            .or(kdf_parameters_opt.as_ref())
+        {
+            Some(parameters) => parameters,
+            None => {
+                return Err(Bip39Error::DeserializationFailure(
+                    "Database has no KDF parameters to decrypt with".to_string(),
+                ))
+            }
+        };
+        if payload.len() <= secretbox::NONCEBYTES {
+            return Err(Bip39Error::DeserializationFailure(format!(
+                "Encrypted payload is only {} bytes long",
+                payload.len()
+            )));
+        }
+        let nonce = secretbox::Nonce::from_slice(&payload[0..secretbox::NONCEBYTES])
+            .expect("Nonce length was checked");
+        let key = parameters.derive_key(db_password, key_cache)?;
+        match secretbox::open(&payload[secretbox::NONCEBYTES..], &nonce, &key) {
+            Ok(plain_data) => Ok(PlainData::new(&plain_data)),
+            Err(()) => Err(Bip39Error::DecryptionFailure(
+                "Wrong password or tampered data".to_string(),
+            )),
+        }
+    }
+
+    fn seal(
+        &self,
+        data: &dyn AsRef<[u8]>,
+        db_password: &str,
+        key_cache: &DerivedKeyCache,
+    ) -> Result<String, Bip39Error> {
+        let key = self.derive_key(db_password, key_cache)?;
+        let nonce = secretbox::gen_nonce();
+        let mut payload = nonce.0.to_vec();
+        payload.extend(secretbox::seal(data.as_ref(), &nonce, &key));
+        Ok(payload.to_hex::<String>())
+    }
+
+    fn derive_key(
+        &self,
+        db_password: &str,
+        key_cache: &DerivedKeyCache,
+    ) -> Result<secretbox::Key, Bip39Error> {
+        let password_digest = sha256::hash(db_password.as_bytes());
+        if let Some(key) = key_cache.get(self, &password_digest) {
+            return Ok(key);
+        }
+        let salt = match argon2id13::Salt::from_slice(&self.salt) {
+            Some(salt) => salt,
+            None => {
+                return Err(Bip39Error::DeserializationFailure(format!(
+                    "Salt must be {} bytes long, not {}",
+                    argon2id13::SALTBYTES,
+                    self.salt.len()
+                )))
+            }
+        };
+        let mem_limit_bytes = match self.mem_limit_kib.checked_mul(1024) {
+            Some(bytes) => bytes,
+            None => {
+                return Err(Bip39Error::EncryptionFailure(format!(
+                    "Memory limit of {}KiB is too large",
+                    self.mem_limit_kib
+                )))
+            }
+        };
+        let mut key_bytes = [0u8; secretbox::KEYBYTES];
+        if argon2id13::derive_key(
+            &mut key_bytes,
+            db_password.as_bytes(),
+            &salt,
+            argon2id13::OpsLimit(self.ops_limit),
+            argon2id13::MemLimit(mem_limit_bytes),
+        )
+        .is_err()
+        {
+            return Err(Bip39Error::EncryptionFailure(format!(
+                "Key derivation failed with {:?}",
+                self
+            )));
+        }
+        let key = secretbox::Key(key_bytes);
+        key_cache.put(self, password_digest, key.clone());
+        Ok(key)
+    }
+
+    fn parse(crypt_value: &str) -> Result<(Option<KdfParameters>, Vec<u8>), Bip39Error> {
+        let malformed = || {
+            Bip39Error::DeserializationFailure(format!(
+                "Not a valid {} value: '{}'",
+                KDF_ALGORITHM, crypt_value
+            ))
+        };
+        let pieces = crypt_value.split(':').collect::<Vec<&str>>();
+        if pieces[0] != KDF_ALGORITHM {
+            return Err(malformed());
+        }
+        let payload_from = |piece: &str| piece.from_hex::<Vec<u8>>().map_err(|_| malformed());
+        match pieces.len() {
+            2 => Ok((None, payload_from(pieces[1])?)),
+            5 => {
+                let ops_limit = pieces[1].parse::<usize>().map_err(|_| malformed())?;
+                let mem_limit_kib = pieces[2].parse::<usize>().map_err(|_| malformed())?;
+                let salt = pieces[3].from_hex::<Vec<u8>>().map_err(|_| malformed())?;
+                Ok((
+                    Some(KdfParameters {
+                        ops_limit: ops_limit.clamp(MIN_KDF_OPS_LIMIT, MAX_KDF_OPS_LIMIT),
+                        mem_limit_kib: mem_limit_kib
+                            .clamp(MIN_KDF_MEM_LIMIT_KIB, MAX_KDF_MEM_LIMIT_KIB),
+                        salt,
+                    }),
+                    payload_from(pieces[4])?,
+                ))
+            }
+            _ => Err(malformed()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constants_have_correct_values() {
+        assert_eq!(KDF_ALGORITHM, "argon2id");
+        assert_eq!(DEFAULT_KDF_OPS_LIMIT, 2);
+        assert_eq!(DEFAULT_KDF_MEM_LIMIT_KIB, 65536);
+        assert_eq!(MIN_KDF_OPS_LIMIT, 1);
+        assert_eq!(MAX_KDF_OPS_LIMIT, 4);
+        assert_eq!(MIN_KDF_MEM_LIMIT_KIB, 8);
+        assert_eq!(MAX_KDF_MEM_LIMIT_KIB, 1048576);
+        assert_eq!(DERIVED_KEY_CACHE_SIZE, 2);
+    }
+
+    #[test]
+    fn generate_uses_defaults_and_a_fresh_salt() {
+        let first = KdfParameters::generate();
+        let second = KdfParameters::generate();
+
+        assert_eq!(first.ops_limit, DEFAULT_KDF_OPS_LIMIT);
+        assert_eq!(first.mem_limit_kib, DEFAULT_KDF_MEM_LIMIT_KIB);
+        assert_eq!(first.salt.len(), argon2id13::SALTBYTES);
+        assert_ne!(first.salt, second.salt);
+        assert_eq!(first.is_up_to_date(), true);
+    }
+
+    #[test]
+    fn weaker_parameters_are_not_up_to_date() {
+        let parameters = KdfParameters {
+            ops_limit: 1,
+            mem_limit_kib: 8,
+            salt: vec![0; 16],
+        };
+
+        assert_eq!(parameters.is_up_to_date(), false);
+    }
+
+    #[test]
+    fn example_round_trips_and_carries_the_parameters() {
+        let parameters = KdfParameters::generate();
+        let key_cache = DerivedKeyCache::default();
+
+        let crypt_value = parameters
+            .encrypt_example(b"booga", "password", &key_cache)
+            .unwrap();
+
+        assert_eq!(KdfParameters::is_kdf_value(&crypt_value), true);
+        assert_eq!(
+            KdfParameters::from_crypt_value(&crypt_value),
+            Some(parameters)
+        );
+        assert_eq!(
+            KdfParameters::decrypt_bytes(&crypt_value, "password", &None, &key_cache),
+            Ok(PlainData::new(b"booga"))
+        );
+    }
+
+    #[test]
+    fn other_values_leave_the_parameters_to_the_database() {
+        let parameters = KdfParameters::generate();
+        let key_cache = DerivedKeyCache::default();
+
+        let crypt_value = parameters
+            .encrypt_bytes(b"booga", "password", &key_cache)
+            .unwrap();
+
+        assert_eq!(KdfParameters::is_kdf_value(&crypt_value), true);
+        assert_eq!(crypt_value.split(':').count(), 2);
+        assert_eq!(KdfParameters::from_crypt_value(&crypt_value), None);
+        assert_eq!(
+            KdfParameters::decrypt_bytes(
+                &crypt_value,
+                "password",
+                &Some(parameters),
+                &DerivedKeyCache::default()
+            ),
+            Ok(PlainData::new(b"booga"))
+        );
+        assert_eq!(
+            KdfParameters::decrypt_bytes(&crypt_value, "password", &None, &key_cache),
+            Err(Bip39Error::DeserializationFailure(
+                "Database has no KDF parameters to decrypt with".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn same_plaintext_encrypts_differently_each_time() {
+        let parameters = KdfParameters::generate();
+        let key_cache = DerivedKeyCache::default();
+
+        let first = parameters
+            .encrypt_bytes(b"booga", "password", &key_cache)
+            .unwrap();
+        let second = parameters
+            .encrypt_bytes(b"booga", "password", &key_cache)
+            .unwrap();
+
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn decryption_with_wrong_password_fails() {
+        let key_cache = DerivedKeyCache::default();
+        let crypt_value = KdfParameters::generate()
+            .encrypt_example(b"booga", "password", &key_cache)
+            .unwrap();
+
+        let result = KdfParameters::decrypt_bytes(&crypt_value, "bad password", &None, &key_cache);
+
+        assert_eq!(
+            result,
+            Err(Bip39Error::DecryptionFailure(
+                "Wrong password or tampered data".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn legacy_values_are_not_recognized() {
+        let legacy = crate::blockchain::bip39::Bip39::encrypt_bytes(b"booga", "password").unwrap();
+
+        assert_eq!(KdfParameters::is_kdf_value(&legacy), false);
+        assert_eq!(KdfParameters::from_crypt_value(&legacy), None);
+    }
+
+    #[test]
+    fn malformed_values_are_rejected() {
+        let result = KdfParameters::decrypt_bytes(
+            "argon2id:2:booga:00:00",
+            "password",
+            &None,
+            &DerivedKeyCache::default(),
+        );
+
+        assert_eq!(
+            result,
+            Err(Bip39Error::DeserializationFailure(
+                "Not a valid argon2id value: 'argon2id:2:booga:00:00'".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn limits_read_from_the_database_are_clamped() {
+        let salt = "00112233445566778899aabbccddeeff";
+
+        let too_high = KdfParameters::from_crypt_value(&format!(
+            "argon2id:1000000:{}:{}:00",
+            usize::MAX,
+            salt
+        ))
+        .unwrap();
+        let too_low =
+            KdfParameters::from_crypt_value(&format!("argon2id:0:0:{}:00", salt)).unwrap();
+
+        assert_eq!(too_high.ops_limit, MAX_KDF_OPS_LIMIT);
+        assert_eq!(too_high.mem_limit_kib, MAX_KDF_MEM_LIMIT_KIB);
+        assert_eq!(too_low.ops_limit, MIN_KDF_OPS_LIMIT);
+        assert_eq!(too_low.mem_limit_kib, MIN_KDF_MEM_LIMIT_KIB);
+    }
+
+    #[test]
+    fn memory_limit_that_overflows_is_rejected() {
+        let parameters = KdfParameters {
+            ops_limit: DEFAULT_KDF_OPS_LIMIT,
+            mem_limit_kib: usize::MAX,
+            salt: argon2id13::gen_salt().0.to_vec(),
+        };
+
+        let result = parameters.encrypt_bytes(b"booga", "password", &DerivedKeyCache::default());
+
+        assert_eq!(
+            result,
+            Err(Bip39Error::EncryptionFailure(format!(
+                "Memory limit of {}KiB is too large",
+                usize::MAX
+            )))
+        );
+    }
+
+    #[test]
+    fn short_salt_is_rejected() {
+        let parameters = KdfParameters {
+            ops_limit: DEFAULT_KDF_OPS_LIMIT,
+            mem_limit_kib: DEFAULT_KDF_MEM_LIMIT_KIB,
+            salt: vec![1, 2, 3],
+        };
+
+        let result = parameters.encrypt_bytes(b"booga", "password", &DerivedKeyCache::default());
+
+        assert_eq!(
+            result,
+            Err(Bip39Error::DeserializationFailure(
+                "Salt must be 16 bytes long, not 3".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn derived_keys_are_remembered_only_by_the_cache_that_derived_them() {
+        let parameters = KdfParameters::generate();
+        let digest = sha256::hash(b"password");
+        let key_cache = DerivedKeyCache::default();
+        let other_key_cache = DerivedKeyCache::default();
+
+        let key = parameters.derive_key("password", &key_cache).unwrap();
+
+        assert_eq!(key_cache.get(&parameters, &digest), Some(key));
+        assert_eq!(other_key_cache.get(&parameters, &digest), None);
+    }
+
+    #[test]
+    fn derived_key_cache_forgets_the_oldest_key_when_full() {
+        let first = KdfParameters::generate();
+        let second = KdfParameters::generate();
+        let third = KdfParameters::generate();
+        let digest = sha256::hash(b"password");
+        let subject = DerivedKeyCache::default();
+
+        subject.put(&first, digest, secretbox::gen_key());
+        subject.put(&second, digest, secretbox::gen_key());
+        subject.put(&third, digest, secretbox::gen_key());
+
+        assert_eq!(subject.get(&first, &digest), None);
+        assert!(subject.get(&second, &digest).is_some());
+        assert!(subject.get(&third, &digest).is_some());
+    }
+}
@@ -2,9 +2,11 @@
 
 use crate::arbitrary_id_stamp_in_trait;
 use crate::blockchain::bip32::Bip32EncryptionKeyProvider;
-use crate::blockchain::bip39::{Bip39, Bip39Error};
+use crate::blockchain::bip39::Bip39Error;
 use crate::database::rusqlite_wrappers::{ConnectionWrapper, TransactionSafeWrapper};
 use crate::db_config::config_dao::{ConfigDao, ConfigDaoError, ConfigDaoReal, ConfigDaoRecord};
+use crate::db_config::db_encryption_layer::DbEncryptionLayer;
+use crate::db_config::db_kdf::{DbEncryptionScheme, DerivedKeyCache, KdfParameters};
 use crate::db_config::secure_config_layer::{SecureConfigLayer, SecureConfigLayerError};
 use crate::db_config::typed_config_layer::{
     decode_bytes, decode_combined_params, decode_u64, encode_bytes, encode_u64,
@@ -14,6 +16,7 @@ use crate::sub_lib::accountant::{PaymentThresholds, ScanIntervals};
 use crate::sub_lib::cryptde::{CryptDE, PlainData};
 use crate::sub_lib::cryptde_null::CryptDENull;
 use crate::sub_lib::cryptde_real::CryptDEReal;
+use crate::sub_lib::earning_wallet_shares::EarningWalletShares;
 use crate::sub_lib::neighborhood::{Hops, NodeDescriptor, RatePack};
 use crate::sub_lib::wallet::Wallet;
 use masq_lib::blockchains::chains::Chain;
@@ -98,6 +101,9 @@ pub trait PersistentConfiguration {
         old_password_opt: Option<String>,
         new_password: &str,
     ) -> Result<(), PersistentConfigError>;
+    // Re-encrypts legacy or outdated values under fresh KDF parameters; true if it did anything
+    fn upgrade_encryption(&mut self, db_password: &str) -> Result<bool, PersistentConfigError>;
+    fn encryption_scheme(&self) -> Result<DbEncryptionScheme, PersistentConfigError>;
     // WARNING: Actors should get consuming-wallet information from their startup config, not from here
     fn consuming_wallet(&self, db_password: &str) -> Result<Option<Wallet>, PersistentConfigError>;
     // WARNING: Actors should get consuming-wallet information from their startup config, not from here
@@ -118,6 +124,12 @@ pub trait PersistentConfiguration {
     fn earning_wallet(&self) -> Result<Option<Wallet>, PersistentConfigError>;
     // WARNING: Actors should get earning-wallet information from their startup config, not from here
     fn earning_wallet_address(&self) -> Result<Option<String>, PersistentConfigError>;
+    // WARNING: Actors should get earning-wallet information from their startup config, not from here
+    fn earning_wallet_shares(&self) -> Result<EarningWalletShares, PersistentConfigError>;
+    fn set_earning_wallet_shares(
+        &mut self,
+        shares: &EarningWalletShares,
+    ) -> Result<(), PersistentConfigError>;
     fn gas_price(&self) -> Result<u64, PersistentConfigError>;
     fn set_gas_price(&mut self, gas_price: u64) -> Result<(), PersistentConfigError>;
     fn mapping_protocol(&self) -> Result<Option<AutomapProtocol>, PersistentConfigError>;
@@ -226,6 +238,14 @@ impl PersistentConfiguration for PersistentConfigurationReal {
             .change_password(old_password_opt, new_password, &mut self.dao)?)
     }
 
+    fn upgrade_encryption(&mut self, db_password: &str) -> Result<bool, PersistentConfigError> {
+        Ok(self.scl.upgrade_encryption(db_password, &self.dao)?)
+    }
+
+    fn encryption_scheme(&self) -> Result<DbEncryptionScheme, PersistentConfigError> {
+        Ok(self.scl.encryption_scheme(&self.dao)?)
+    }
+
     fn consuming_wallet(&self, db_password: &str) -> Result<Option<Wallet>, PersistentConfigError> {
         self.consuming_wallet_private_key(db_password)
             .map(|key_opt| {
@@ -253,7 +273,17 @@ impl PersistentConfiguration for PersistentConfigurationReal {
     ) -> Result<Option<String>, PersistentConfigError> {
         let encrypted_value_opt = self.get_record("consuming_wallet_private_key")?.value_opt;
         if let Some(encrypted_value) = encrypted_value_opt {
-            match Bip39::decrypt_bytes(&encrypted_value, db_password) {
+            let kdf_parameters_opt = if KdfParameters::is_kdf_value(&encrypted_value) {
+                self.encryption_scheme()?.kdf_parameters_opt()
+            } else {
+                None
+            };
+            match DbEncryptionLayer::decrypt_bytes(
+                &encrypted_value,
+                db_password,
+                &kdf_parameters_opt,
+                self.scl.key_cache(),
+            ) {
                 Ok(decrypted_bytes) => Ok(Some(decrypted_bytes.as_slice().to_hex())),
                 Err(Bip39Error::DecryptionFailure(_)) => Err(PersistentConfigError::PasswordError),
                 Err(e) => panic!(
@@ -382,6 +412,26 @@ impl PersistentConfiguration for PersistentConfigurationReal {
         Ok(self.get("earning_wallet_address")?)
     }
 
+    fn earning_wallet_shares(&self) -> Result<EarningWalletShares, PersistentConfigError> {
+        match self.get("earning_wallet_shares")? {
+            None => Ok(EarningWalletShares::default()),
+            Some(shares) => EarningWalletShares::from_str(&shares)
+                .map_err(PersistentConfigError::UninterpretableValue),
+        }
+    }
+
+    fn set_earning_wallet_shares(
+        &mut self,
+        shares: &EarningWalletShares,
+    ) -> Result<(), PersistentConfigError> {
+        let value_opt = if shares.is_empty() {
+            None
+        } else {
+            Some(shares.to_string())
+        };
+        Ok(self.dao.set("earning_wallet_shares", value_opt)?)
+    }
+
     fn gas_price(&self) -> Result<u64, PersistentConfigError> {
         match decode_u64(self.get("gas_price")?) {
             Ok(val) => {
@@ -534,13 +584,18 @@ impl PersistentConfiguration for PersistentConfigurationReal {
                 }
             }
         }
-        let encrypted_consuming_wallet_private_key =
-            Self::encrypt_private_key(consuming_wallet_private_key, db_password)?;
         if !Self::validate_wallet_address(earning_wallet_address) {
             return Err(PersistentConfigError::BadAddressFormat(
                 earning_wallet_address.to_string(),
             ));
         }
+        let kdf_parameters_opt = self.encryption_scheme()?.kdf_parameters_opt();
+        let encrypted_consuming_wallet_private_key = Self::encrypt_private_key(
+            consuming_wallet_private_key,
+            db_password,
+            &kdf_parameters_opt,
+            self.scl.key_cache(),
+        )?;
         self.dao.set(
             "consuming_wallet_private_key",
             Some(encrypted_consuming_wallet_private_key),
@@ -603,13 +658,20 @@ impl PersistentConfigurationReal {
     fn encrypt_private_key(
         private_key: &str,
         db_password: &str,
+        kdf_parameters_opt: &Option<KdfParameters>,
+        key_cache: &DerivedKeyCache,
     ) -> Result<String, PersistentConfigError> {
         let private_key_bytes = match private_key.from_hex::<Vec<u8>>() {
             Ok(bytes) => bytes,
             Err(_) => return Err(PersistentConfigError::BadHexFormat(private_key.to_string())),
         };
-        Bip39::encrypt_bytes(&private_key_bytes, db_password)
-            .map_err(|e| panic!("Failure to encrypt consuming private key: {:?}", e))
+        DbEncryptionLayer::encrypt_bytes(
+            &private_key_bytes,
+            db_password,
+            kdf_parameters_opt,
+            key_cache,
+        )
+        .map_err(|e| panic!("Failure to encrypt consuming private key: {:?}", e))
     }
 
     fn validate_wallet_address(address: &str) -> bool {
@@ -683,10 +745,10 @@ mod tests {
     };
     use crate::database::test_utils::transaction_wrapper_mock::TransactionInnerWrapperMockBuilder;
     use crate::db_config::config_dao::ConfigDaoRecord;
-    use crate::db_config::db_encryption_layer::DbEncryptionLayer;
     use crate::db_config::mocks::ConfigDaoMock;
     use crate::db_config::secure_config_layer::EXAMPLE_ENCRYPTED;
     use crate::sub_lib::cryptde_real::CryptDEReal;
+    use crate::test_utils::make_wallet;
     use crate::test_utils::unshared_test_utils::arbitrary_id_stamp::ArbitraryIdStamp;
     use bip39::{Language, MnemonicType};
     use itertools::Itertools;
@@ -881,6 +943,91 @@ mod tests {
         assert_eq!(*get_string_params, [EXAMPLE_ENCRYPTED.to_string()]);
     }
 
+    #[test]
+    fn upgrade_encryption_delegates_properly() {
+        let encrypted_example = KdfParameters::generate()
+            .encrypt_example(b"example", "password", &DerivedKeyCache::default())
+            .unwrap();
+        let get_params_arc = Arc::new(Mutex::new(vec![]));
+        let config_dao = ConfigDaoMock::new()
+            .get_params(&get_params_arc)
+            .get_result(Ok(ConfigDaoRecord::new(
+                EXAMPLE_ENCRYPTED,
+                Some(&encrypted_example),
+                true,
+            )));
+        let mut subject = PersistentConfigurationReal::new(Box::new(config_dao));
+
+        let result = subject.upgrade_encryption("password");
+
+        assert_eq!(result, Ok(false));
+        let get_params = get_params_arc.lock().unwrap();
+        assert_eq!(*get_params, [EXAMPLE_ENCRYPTED.to_string()]);
+    }
+
+    #[test]
+    fn upgrade_encryption_reencrypts_a_legacy_database_for_real() {
+        let home_dir = ensure_node_home_directory_exists(
+            "persistent_configuration",
+            "upgrade_encryption_reencrypts_a_legacy_database_for_real",
+        );
+        let conn = DbInitializerReal::default()
+            .initialize(&home_dir, DbInitializationConfig::test_default())
+            .unwrap();
+        let mut subject = PersistentConfigurationReal::from(conn);
+        let legacy_example = Bip39::encrypt_bytes(b"example", "password").unwrap();
+        let legacy_cryptde = Bip39::encrypt_bytes(b"booga", "password").unwrap();
+        subject
+            .dao
+            .set(EXAMPLE_ENCRYPTED, Some(legacy_example))
+            .unwrap();
+        subject
+            .dao
+            .set("last_cryptde", Some(legacy_cryptde))
+            .unwrap();
+        assert_eq!(subject.encryption_scheme(), Ok(DbEncryptionScheme::Legacy));
+
+        let first_result = subject.upgrade_encryption("password");
+        let second_result = subject.upgrade_encryption("password");
+
+        assert_eq!(first_result, Ok(true));
+        assert_eq!(second_result, Ok(false));
+        let kdf_parameters = match subject.encryption_scheme() {
+            Ok(DbEncryptionScheme::Kdf(kdf_parameters)) => kdf_parameters,
+            x => panic!("Expected Kdf(_), got {:?}", x),
+        };
+        let cryptde_crypt = subject.get("last_cryptde").unwrap().unwrap();
+        assert_eq!(KdfParameters::is_kdf_value(&cryptde_crypt), true);
+        assert_eq!(
+            DbEncryptionLayer::decrypt_bytes(
+                &cryptde_crypt,
+                "password",
+                &Some(kdf_parameters),
+                &DerivedKeyCache::default()
+            ),
+            Ok(PlainData::new(b"booga"))
+        );
+        assert_eq!(
+            subject.check_password(Some("password".to_string())),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn encryption_scheme_delegates_properly() {
+        let get_params_arc = Arc::new(Mutex::new(vec![]));
+        let config_dao = ConfigDaoMock::new()
+            .get_params(&get_params_arc)
+            .get_result(Ok(ConfigDaoRecord::new(EXAMPLE_ENCRYPTED, None, true)));
+        let subject = PersistentConfigurationReal::new(Box::new(config_dao));
+
+        let result = subject.encryption_scheme();
+
+        assert_eq!(result, Ok(DbEncryptionScheme::NoPassword));
+        let get_params = get_params_arc.lock().unwrap();
+        assert_eq!(*get_params, [EXAMPLE_ENCRYPTED.to_string()]);
+    }
+
     #[test]
     #[should_panic(
         expected = "ever-supplied value missing: clandestine_port; database is corrupt!"
@@ -1074,6 +1221,8 @@ mod tests {
         let cryptde_crypt = DbEncryptionLayer::encrypt_value(
             &Some(cryptde_string),
             &Some(db_password.clone()),
+            &None,
+            &DerivedKeyCache::default(),
             "last_cryptde",
         )
         .unwrap()
@@ -1115,6 +1264,8 @@ mod tests {
         let cryptde_crypt = DbEncryptionLayer::encrypt_value(
             &Some(cryptde_string),
             &Some(db_password.clone()),
+            &None,
+            &DerivedKeyCache::default(),
             "last_cryptde",
         )
         .unwrap()
@@ -1154,6 +1305,8 @@ mod tests {
         let example_encrypted = DbEncryptionLayer::encrypt_value(
             &Some("Example plaintext".to_string()),
             &Some(db_password.clone()),
+            &None,
+            &DerivedKeyCache::default(),
             EXAMPLE_ENCRYPTED,
         )
         .unwrap()
@@ -1180,6 +1333,8 @@ mod tests {
         let example_encrypted = DbEncryptionLayer::encrypt_value(
             &Some("Example plaintext".to_string()),
             &Some(db_password.clone()),
+            &None,
+            &DerivedKeyCache::default(),
             EXAMPLE_ENCRYPTED,
         )
         .unwrap()
@@ -1208,6 +1363,8 @@ mod tests {
         let cryptde_crypt = DbEncryptionLayer::encrypt_value(
             &Some(cryptde_string),
             &Some("good_password".to_string()),
+            &None,
+            &DerivedKeyCache::default(),
             "last_cryptde",
         )
         .unwrap()
@@ -1239,6 +1396,8 @@ mod tests {
         let example_encrypted = DbEncryptionLayer::encrypt_value(
             &Some("Example plaintext".to_string()),
             &Some(db_password.clone()),
+            &None,
+            &DerivedKeyCache::default(),
             EXAMPLE_ENCRYPTED,
         )
         .unwrap()
@@ -1275,6 +1434,8 @@ mod tests {
         let cryptde_crypt = DbEncryptionLayer::encrypt_value(
             &Some(cryptde_string),
             &Some(db_password.clone()),
+            &None,
+            &DerivedKeyCache::default(),
             "last_cryptde",
         )
         .unwrap()
@@ -1304,6 +1465,8 @@ mod tests {
         let plain_text = DbEncryptionLayer::decrypt_value(
             &crypt_text_opt,
             &Some(db_password.clone()),
+            &None,
+            &DerivedKeyCache::default(),
             "last_cryptde",
         )
         .unwrap()
@@ -1398,6 +1561,56 @@ mod tests {
         );
     }
 
+    #[test]
+    fn consuming_wallet_private_key_takes_kdf_parameters_from_the_example() {
+        let get_params_arc = Arc::new(Mutex::new(vec![]));
+        let kdf_parameters = KdfParameters::generate();
+        let key_cache = DerivedKeyCache::default();
+        let example_encrypted = kdf_parameters
+            .encrypt_example(b"example", "password", &key_cache)
+            .unwrap();
+        let consuming_private_key =
+            "0123456789ABCDEF0123456789ABCDEF0123456789ABCDEF0123456789ABCDEF";
+        let consuming_private_key_encrypted = kdf_parameters
+            .encrypt_bytes(
+                &consuming_private_key.from_hex::<Vec<u8>>().unwrap(),
+                "password",
+                &key_cache,
+            )
+            .unwrap();
+        let config_dao = ConfigDaoMock::new()
+            .get_params(&get_params_arc)
+            .get_result(Ok(ConfigDaoRecord::new(
+                "consuming_wallet_private_key",
+                Some(&consuming_private_key_encrypted),
+                true,
+            )))
+            .get_result(Ok(ConfigDaoRecord::new(
+                EXAMPLE_ENCRYPTED,
+                Some(&example_encrypted),
+                true,
+            )));
+        let subject = PersistentConfigurationReal::new(Box::new(config_dao));
+
+        let result = subject
+            .consuming_wallet_private_key("password")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            result.to_uppercase(),
+            consuming_private_key.to_string().to_uppercase()
+        );
+        let get_params = get_params_arc.lock().unwrap();
+        assert_eq!(
+            *get_params,
+            vec![
+                "consuming_wallet_private_key".to_string(),
+                EXAMPLE_ENCRYPTED.to_string()
+            ]
+        );
+    }
+
     #[test]
     fn consuming_wallet() {
         let example_encrypted = Bip39::encrypt_bytes(
@@ -1462,6 +1675,88 @@ mod tests {
         assert_eq!(*get_params, vec!["earning_wallet_address".to_string()]);
     }
 
+    #[test]
+    fn earning_wallet_shares_are_empty_if_never_set() {
+        let get_params_arc = Arc::new(Mutex::new(vec![]));
+        let config_dao = ConfigDaoMock::new()
+            .get_params(&get_params_arc)
+            .get_result(Ok(ConfigDaoRecord::new(
+                "earning_wallet_shares",
+                None,
+                false,
+            )));
+        let subject = PersistentConfigurationReal::new(Box::new(config_dao));
+
+        let result = subject.earning_wallet_shares();
+
+        assert_eq!(result, Ok(EarningWalletShares::default()));
+        let get_params = get_params_arc.lock().unwrap();
+        assert_eq!(*get_params, vec!["earning_wallet_shares".to_string()]);
+    }
+
+    #[test]
+    fn earning_wallet_shares_are_parsed() {
+        let shares_str = format!("{}:30,{}:20", make_wallet("first"), make_wallet("second"));
+        let config_dao = ConfigDaoMock::new().get_result(Ok(ConfigDaoRecord::new(
+            "earning_wallet_shares",
+            Some(&shares_str),
+            false,
+        )));
+        let subject = PersistentConfigurationReal::new(Box::new(config_dao));
+
+        let result = subject.earning_wallet_shares();
+
+        assert_eq!(
+            result,
+            Ok(EarningWalletShares::from_str(&shares_str).unwrap())
+        );
+    }
+
+    #[test]
+    fn earning_wallet_shares_complains_about_garbage() {
+        let config_dao = ConfigDaoMock::new().get_result(Ok(ConfigDaoRecord::new(
+            "earning_wallet_shares",
+            Some("booga"),
+            false,
+        )));
+        let subject = PersistentConfigurationReal::new(Box::new(config_dao));
+
+        let result = subject.earning_wallet_shares();
+
+        assert_eq!(
+            result,
+            Err(PersistentConfigError::UninterpretableValue(
+                "Share 'booga' is not <address>:<percentage>".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn set_earning_wallet_shares_stores_shares_or_clears_them() {
+        let set_params_arc = Arc::new(Mutex::new(vec![]));
+        let config_dao = ConfigDaoMock::new()
+            .set_params(&set_params_arc)
+            .set_result(Ok(()))
+            .set_result(Ok(()));
+        let mut subject = PersistentConfigurationReal::new(Box::new(config_dao));
+        let shares_str = format!("{}:30", make_wallet("first"));
+        let shares = EarningWalletShares::from_str(&shares_str).unwrap();
+
+        let some_result = subject.set_earning_wallet_shares(&shares);
+        let none_result = subject.set_earning_wallet_shares(&EarningWalletShares::default());
+
+        assert_eq!(some_result, Ok(()));
+        assert_eq!(none_result, Ok(()));
+        let set_params = set_params_arc.lock().unwrap();
+        assert_eq!(
+            *set_params,
+            vec![
+                ("earning_wallet_shares".to_string(), Some(shares_str)),
+                ("earning_wallet_shares".to_string(), None)
+            ]
+        );
+    }
+
     #[test]
     fn earning_wallet_if_address_is_missing() {
         let get_params_arc = Arc::new(Mutex::new(vec![]));
@@ -1611,6 +1906,7 @@ mod tests {
             vec![
                 "consuming_wallet_private_key".to_string(),
                 "earning_wallet_address".to_string(),
+                EXAMPLE_ENCRYPTED.to_string(),
             ]
         );
         let set_params = set_params_arc.lock().unwrap();
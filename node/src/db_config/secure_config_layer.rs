@@ -1,8 +1,8 @@
 // Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 
-use crate::blockchain::bip39::Bip39;
 use crate::db_config::config_dao::{ConfigDao, ConfigDaoError, ConfigDaoRecord};
 use crate::db_config::db_encryption_layer::DbEncryptionLayer;
+use crate::db_config::db_kdf::{DbEncryptionScheme, DerivedKeyCache, KdfParameters};
 use rand::Rng;
 
 pub const EXAMPLE_ENCRYPTED: &str = "example_encrypted";
@@ -25,7 +25,9 @@ impl From<ConfigDaoError> for SecureConfigLayerError {
     }
 }
 
-pub struct SecureConfigLayer {}
+pub struct SecureConfigLayer {
+    key_cache: DerivedKeyCache,
+}
 
 impl Default for SecureConfigLayer {
     fn default() -> Self {
@@ -35,7 +37,13 @@ impl Default for SecureConfigLayer {
 
 impl SecureConfigLayer {
     pub fn new() -> SecureConfigLayer {
-        Self {}
+        Self {
+            key_cache: DerivedKeyCache::default(),
+        }
+    }
+
+    pub fn key_cache(&self) -> &DerivedKeyCache {
+        &self.key_cache
     }
 
     #[allow(clippy::borrowed_box)]
@@ -56,14 +64,66 @@ impl SecureConfigLayer {
         new_password: &str,
         dao: &'b mut Box<T>,
     ) -> Result<(), SecureConfigLayerError> {
-        if !self.check_password(old_password_opt.clone(), dao)? {
+        let example_record = dao.get(EXAMPLE_ENCRYPTED)?;
+        let old_kdf_parameters_opt = Self::kdf_parameters_of(&example_record);
+        if !self.password_matches_example(old_password_opt.clone(), example_record)? {
             return Err(SecureConfigLayerError::PasswordError);
         }
-        self.reencrypt_records(old_password_opt, new_password, dao)?;
-        self.install_example_for_password(new_password, dao)?;
+        let kdf_parameters = KdfParameters::generate();
+        self.reencrypt_records(
+            old_password_opt,
+            &old_kdf_parameters_opt,
+            new_password,
+            &kdf_parameters,
+            dao,
+        )?;
+        self.install_example_for_password(new_password, &kdf_parameters, dao)?;
         Ok(())
     }
 
+    // Re-encrypts a database written with the legacy scheme (or with KDF limits weaker than the
+    // current defaults) under fresh KDF parameters. Returns true if anything was re-encrypted.
+    #[allow(clippy::borrowed_box)]
+    pub fn upgrade_encryption<T: ConfigDao + ?Sized>(
+        &self,
+        db_password: &str,
+        dao: &Box<T>,
+    ) -> Result<bool, SecureConfigLayerError> {
+        let example_record = dao.get(EXAMPLE_ENCRYPTED)?;
+        let kdf_parameters_opt = Self::kdf_parameters_of(&example_record);
+        if !self.password_matches_example(Some(db_password.to_string()), example_record)? {
+            return Err(SecureConfigLayerError::PasswordError);
+        }
+        match &kdf_parameters_opt {
+            Some(kdf_parameters) if kdf_parameters.is_up_to_date() => Ok(false),
+            _ => {
+                let kdf_parameters = KdfParameters::generate();
+                self.reencrypt_records(
+                    Some(db_password.to_string()),
+                    &kdf_parameters_opt,
+                    db_password,
+                    &kdf_parameters,
+                    dao,
+                )?;
+                self.install_example_for_password(db_password, &kdf_parameters, dao)?;
+                Ok(true)
+            }
+        }
+    }
+
+    #[allow(clippy::borrowed_box)]
+    pub fn encryption_scheme<T: ConfigDao + ?Sized>(
+        &self,
+        dao: &Box<T>,
+    ) -> Result<DbEncryptionScheme, SecureConfigLayerError> {
+        let example_record = dao.get(EXAMPLE_ENCRYPTED)?;
+        Ok(match Self::kdf_parameters_of(&example_record) {
+            Some(kdf_parameters) => DbEncryptionScheme::Kdf(kdf_parameters),
+            None if example_record.value_opt.is_none() => DbEncryptionScheme::NoPassword,
+            None => DbEncryptionScheme::Legacy,
+        })
+    }
+
     #[allow(clippy::borrowed_box)]
     pub fn encrypt<T: ConfigDao + ?Sized>(
         &self,
@@ -72,14 +132,22 @@ impl SecureConfigLayer {
         password_opt: Option<String>,
         dao: &Box<T>,
     ) -> Result<Option<String>, SecureConfigLayerError> {
-        if !self.check_password(password_opt.clone(), dao)? {
+        let example_record = dao.get(EXAMPLE_ENCRYPTED)?;
+        let kdf_parameters_opt = Self::kdf_parameters_of(&example_record);
+        if !self.password_matches_example(password_opt.clone(), example_record)? {
             return Err(SecureConfigLayerError::PasswordError);
         }
         let record = dao.get(name)?;
         if !record.encrypted {
             Ok(plain_value_opt)
         } else {
-            DbEncryptionLayer::encrypt_value(&plain_value_opt, &password_opt, &record.name)
+            DbEncryptionLayer::encrypt_value(
+                &plain_value_opt,
+                &password_opt,
+                &kdf_parameters_opt,
+                &self.key_cache,
+                &record.name,
+            )
         }
     }
 
@@ -90,13 +158,21 @@ impl SecureConfigLayer {
         password_opt: Option<String>,
         dao: &Box<T>,
     ) -> Result<Option<String>, SecureConfigLayerError> {
-        if !self.check_password(password_opt.clone(), dao)? {
+        let example_record = dao.get(EXAMPLE_ENCRYPTED)?;
+        let kdf_parameters_opt = Self::kdf_parameters_of(&example_record);
+        if !self.password_matches_example(password_opt.clone(), example_record)? {
             return Err(SecureConfigLayerError::PasswordError);
         }
         if !record.encrypted {
             Ok(record.value_opt)
         } else {
-            DbEncryptionLayer::decrypt_value(&record.value_opt, &password_opt, &record.name)
+            DbEncryptionLayer::decrypt_value(
+                &record.value_opt,
+                &password_opt,
+                &kdf_parameters_opt,
+                &self.key_cache,
+                &record.name,
+            )
         }
     }
 
@@ -111,14 +187,24 @@ impl SecureConfigLayer {
         Ok(DbEncryptionLayer::password_matches(
             &db_password_opt,
             &example_record.value_opt,
+            &self.key_cache,
         ))
     }
 
+    fn kdf_parameters_of(example_record: &ConfigDaoRecord) -> Option<KdfParameters> {
+        example_record
+            .value_opt
+            .as_ref()
+            .and_then(|value| KdfParameters::from_crypt_value(value))
+    }
+
     #[allow(clippy::borrowed_box)]
     fn reencrypt_records<T: ConfigDao + ?Sized>(
         &self,
         old_password_opt: Option<String>,
+        old_kdf_parameters_opt: &Option<KdfParameters>,
         new_password: &str,
+        new_kdf_parameters: &KdfParameters,
         dao: &Box<T>,
     ) -> Result<(), SecureConfigLayerError> {
         let existing_records = dao.get_all()?;
@@ -129,7 +215,13 @@ impl SecureConfigLayer {
             .fold(init, |so_far, record| match so_far {
                 Err(e) => Err(e),
                 Ok(records) => {
-                    match Self::reencrypt_record(record, old_password_opt.clone(), new_password) {
+                    match self.reencrypt_record(
+                        record,
+                        old_password_opt.clone(),
+                        old_kdf_parameters_opt,
+                        new_password,
+                        new_kdf_parameters,
+                    ) {
                         Err(e) => Err(e),
                         Ok(new_record) => Ok(append(records, new_record)),
                     }
@@ -163,9 +255,12 @@ impl SecureConfigLayer {
     }
 
     fn reencrypt_record(
+        &self,
         old_record: ConfigDaoRecord,
         old_password_opt: Option<String>,
+        old_kdf_parameters_opt: &Option<KdfParameters>,
         new_password: &str,
+        new_kdf_parameters: &KdfParameters,
     ) -> Result<ConfigDaoRecord, SecureConfigLayerError> {
         match (old_record.encrypted, &old_record.value_opt, &old_password_opt) {
             (false, _, _) => Ok(old_record),
@@ -173,7 +268,8 @@ impl SecureConfigLayer {
             (true, Some(_), None) => panic! ("Database is corrupt: configuration value '{}' is encrypted, but database has no password", old_record.name),
             (true, Some(value), Some(old_password)) => {
                 let reencrypted_value = DbEncryptionLayer::reencrypt_value(value,
-                    old_password, new_password, &old_record.name);
+                    old_password, old_kdf_parameters_opt, new_password, new_kdf_parameters,
+                    &self.key_cache, &old_record.name);
                 Ok(ConfigDaoRecord::new(&old_record.name, Some(&reencrypted_value), old_record.encrypted))
             },
         }
@@ -183,12 +279,14 @@ impl SecureConfigLayer {
     fn install_example_for_password<T: ConfigDao + ?Sized>(
         &self,
         new_password: &str,
+        kdf_parameters: &KdfParameters,
         dao: &Box<T>,
     ) -> Result<(), SecureConfigLayerError> {
         let mut example_data = [0u8; 32];
         rand::thread_rng().fill(&mut example_data);
-        let example_encrypted =
-            Bip39::encrypt_bytes(&example_data, new_password).expect("Encryption failed");
+        let example_encrypted = kdf_parameters
+            .encrypt_example(&example_data, new_password, &self.key_cache)
+            .expect("Encryption failed");
         dao.set(EXAMPLE_ENCRYPTED, Some(example_encrypted))
             .map_err(SecureConfigLayerError::from)
     }
@@ -412,8 +510,14 @@ mod tests {
         assert_eq!(set_params[1], ("encrypted_value_key".to_string(), None));
         assert_eq!(set_params[2], ("missing_value_key".to_string(), None));
         assert_eq!(set_params[3].0, EXAMPLE_ENCRYPTED.to_string());
-        let encrypted_example = set_params[3].1.clone();
-        match Bip39::decrypt_bytes(&encrypted_example.unwrap(), "password") {
+        let encrypted_example = set_params[3].1.clone().unwrap();
+        assert_eq!(KdfParameters::is_kdf_value(&encrypted_example), true);
+        match DbEncryptionLayer::decrypt_bytes(
+            &encrypted_example,
+            "password",
+            &None,
+            &DerivedKeyCache::default(),
+        ) {
             Ok(_) => (),
             x => panic!("Expected Ok(_), got {:?}", x),
         };
@@ -475,14 +579,32 @@ mod tests {
             )
         );
         assert_eq!(set_params[1].0, "encrypted_value_key".to_string());
-        assert_eq!(
-            Bip39::decrypt_bytes(&set_params[1].1.as_ref().unwrap(), "new_password").unwrap(),
-            PlainData::new(unencrypted_value)
-        );
+        let new_encrypted_value = set_params[1].1.as_ref().unwrap();
         assert_eq!(set_params[2], ("missing_encrypted_key".to_string(), None));
         assert_eq!(set_params[3], ("missing_unencrypted_key".to_string(), None));
         assert_eq!(set_params[4].0, EXAMPLE_ENCRYPTED.to_string());
-        let _ = Bip39::decrypt_bytes(&set_params[4].1.as_ref().unwrap(), "new_password").unwrap();
+        let new_encrypted_example = set_params[4].1.as_ref().unwrap();
+        let kdf_parameters_opt = KdfParameters::from_crypt_value(new_encrypted_example);
+        assert_eq!(kdf_parameters_opt.is_some(), true);
+        assert_eq!(KdfParameters::from_crypt_value(new_encrypted_value), None);
+        let key_cache = DerivedKeyCache::default();
+        let _ = DbEncryptionLayer::decrypt_bytes(
+            new_encrypted_example,
+            "new_password",
+            &None,
+            &key_cache,
+        )
+        .unwrap();
+        assert_eq!(
+            DbEncryptionLayer::decrypt_bytes(
+                new_encrypted_value,
+                "new_password",
+                &kdf_parameters_opt,
+                &key_cache
+            )
+            .unwrap(),
+            PlainData::new(unencrypted_value)
+        );
     }
 
     #[test]
@@ -521,7 +643,9 @@ mod tests {
 
         let _ = subject.reencrypt_records(
             Some("old_password".to_string()),
+            &None,
             "new_password",
+            &KdfParameters::generate(),
             &Box::new(dao),
         );
     }
@@ -554,7 +678,9 @@ mod tests {
 
         let result = subject.reencrypt_records(
             Some("old_password".to_string()),
+            &None,
             "new_password",
+            &KdfParameters::generate(),
             &Box::new(dao),
         );
 
@@ -569,8 +695,15 @@ mod tests {
         let record = ConfigDaoRecord::new("name", Some("value"), true);
         let old_password_opt = None;
         let new_password = "irrelevant";
+        let subject = SecureConfigLayer::new();
 
-        let _ = SecureConfigLayer::reencrypt_record(record, old_password_opt, new_password);
+        let _ = subject.reencrypt_record(
+            record,
+            old_password_opt,
+            &None,
+            new_password,
+            &KdfParameters::generate(),
+        );
     }
 
     #[test]
@@ -944,6 +1077,190 @@ mod tests {
         );
     }
 
+    #[test]
+    fn encrypt_uses_the_kdf_parameters_of_the_database() {
+        let kdf_parameters = KdfParameters::generate();
+        let encrypted_example = kdf_parameters
+            .encrypt_example(
+                b"Aside from that, Mrs. Lincoln, how was the play?",
+                "password",
+                &DerivedKeyCache::default(),
+            )
+            .unwrap();
+        let dao = Box::new(
+            ConfigDaoMock::new()
+                .get_result(Ok(ConfigDaoRecord::new(
+                    EXAMPLE_ENCRYPTED,
+                    Some(&encrypted_example),
+                    true,
+                )))
+                .get_result(Ok(ConfigDaoRecord::new(
+                    "attribute_name",
+                    Some("irrelevant"),
+                    true,
+                ))),
+        );
+        let subject = SecureConfigLayer::new();
+
+        let result = subject
+            .encrypt(
+                "attribute_name",
+                Some("attribute_value".to_string()),
+                Some("password".to_string()),
+                &dao,
+            )
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(KdfParameters::is_kdf_value(&result), true);
+        assert_eq!(KdfParameters::from_crypt_value(&result), None);
+        assert_eq!(
+            DbEncryptionLayer::decrypt_bytes(
+                &result,
+                "password",
+                &Some(kdf_parameters),
+                &DerivedKeyCache::default()
+            )
+            .unwrap(),
+            PlainData::new(b"attribute_value")
+        );
+    }
+
+    #[test]
+    fn upgrade_encryption_reencrypts_a_legacy_database() {
+        let get_params_arc = Arc::new(Mutex::new(vec![]));
+        let set_params_arc = Arc::new(Mutex::new(vec![]));
+        let example = "Aside from that, Mrs. Lincoln, how was the play?".as_bytes();
+        let encrypted_example = Bip39::encrypt_bytes(&example, "password").unwrap();
+        let unencrypted_value = "These are the times that try men's souls.".as_bytes();
+        let old_encrypted_value = Bip39::encrypt_bytes(&unencrypted_value, "password").unwrap();
+        let dao = Box::new(
+            ConfigDaoMock::new()
+                .get_params(&get_params_arc)
+                .get_result(Ok(ConfigDaoRecord::new(
+                    EXAMPLE_ENCRYPTED,
+                    Some(&encrypted_example),
+                    true,
+                )))
+                .get_all_result(Ok(vec![
+                    ConfigDaoRecord::new(EXAMPLE_ENCRYPTED, Some(&encrypted_example), true),
+                    ConfigDaoRecord::new("unencrypted_value_key", Some("unencrypted_value"), false),
+                    ConfigDaoRecord::new("encrypted_value_key", Some(&old_encrypted_value), true),
+                ]))
+                .set_params(&set_params_arc)
+                .set_result(Ok(()))
+                .set_result(Ok(()))
+                .set_result(Ok(())),
+        );
+        let subject = SecureConfigLayer::new();
+
+        let result = subject.upgrade_encryption("password", &dao);
+
+        assert_eq!(result, Ok(true));
+        let get_params = get_params_arc.lock().unwrap();
+        assert_eq!(*get_params, vec![EXAMPLE_ENCRYPTED]);
+        let set_params = set_params_arc.lock().unwrap();
+        assert_eq!(set_params.len(), 3);
+        assert_eq!(
+            set_params[0],
+            (
+                "unencrypted_value_key".to_string(),
+                Some("unencrypted_value".to_string())
+            )
+        );
+        assert_eq!(set_params[1].0, "encrypted_value_key".to_string());
+        let new_encrypted_value = set_params[1].1.as_ref().unwrap();
+        assert_eq!(set_params[2].0, EXAMPLE_ENCRYPTED.to_string());
+        let new_encrypted_example = set_params[2].1.as_ref().unwrap();
+        let kdf_parameters = KdfParameters::from_crypt_value(new_encrypted_example).unwrap();
+        assert_eq!(kdf_parameters.is_up_to_date(), true);
+        assert_eq!(KdfParameters::is_kdf_value(new_encrypted_value), true);
+        assert_eq!(
+            KdfParameters::decrypt_bytes(
+                new_encrypted_value,
+                "password",
+                &Some(kdf_parameters),
+                subject.key_cache()
+            )
+            .unwrap(),
+            PlainData::new(unencrypted_value)
+        );
+    }
+
+    #[test]
+    fn upgrade_encryption_leaves_an_up_to_date_database_alone() {
+        let encrypted_example = KdfParameters::generate()
+            .encrypt_example(
+                b"Aside from that, Mrs. Lincoln, how was the play?",
+                "password",
+                &DerivedKeyCache::default(),
+            )
+            .unwrap();
+        let dao = Box::new(ConfigDaoMock::new().get_result(Ok(ConfigDaoRecord::new(
+            EXAMPLE_ENCRYPTED,
+            Some(&encrypted_example),
+            true,
+        ))));
+        let subject = SecureConfigLayer::new();
+
+        let result = subject.upgrade_encryption("password", &dao);
+
+        assert_eq!(result, Ok(false));
+    }
+
+    #[test]
+    fn upgrade_encryption_balks_at_the_wrong_password() {
+        let example = "Aside from that, Mrs. Lincoln, how was the play?".as_bytes();
+        let encrypted_example = Bip39::encrypt_bytes(&example, "password").unwrap();
+        let dao = Box::new(ConfigDaoMock::new().get_result(Ok(ConfigDaoRecord::new(
+            EXAMPLE_ENCRYPTED,
+            Some(&encrypted_example),
+            true,
+        ))));
+        let subject = SecureConfigLayer::new();
+
+        let result = subject.upgrade_encryption("bad password", &dao);
+
+        assert_eq!(result, Err(SecureConfigLayerError::PasswordError));
+    }
+
+    #[test]
+    fn encryption_scheme_is_read_from_the_example_value() {
+        let kdf_parameters = KdfParameters::generate();
+        let encrypted_example = kdf_parameters
+            .encrypt_example(b"example", "password", &DerivedKeyCache::default())
+            .unwrap();
+        let legacy_example = Bip39::encrypt_bytes(b"example", "password").unwrap();
+        let dao = Box::new(
+            ConfigDaoMock::new()
+                .get_result(Ok(ConfigDaoRecord::new(
+                    EXAMPLE_ENCRYPTED,
+                    Some(&encrypted_example),
+                    true,
+                )))
+                .get_result(Ok(ConfigDaoRecord::new(
+                    EXAMPLE_ENCRYPTED,
+                    Some(&legacy_example),
+                    true,
+                )))
+                .get_result(Ok(ConfigDaoRecord::new(EXAMPLE_ENCRYPTED, None, true))),
+        );
+        let subject = SecureConfigLayer::new();
+
+        assert_eq!(
+            subject.encryption_scheme(&dao),
+            Ok(DbEncryptionScheme::Kdf(kdf_parameters))
+        );
+        assert_eq!(
+            subject.encryption_scheme(&dao),
+            Ok(DbEncryptionScheme::Legacy)
+        );
+        assert_eq!(
+            subject.encryption_scheme(&dao),
+            Ok(DbEncryptionScheme::NoPassword)
+        );
+    }
+
     #[test]
     fn encrypt_works_when_database_is_unencrypted_and_value_is_encrypted_and_present_without_password(
     ) {
@@ -6,7 +6,7 @@ use crate::sub_lib::dispatcher::{DispatcherSubs, StreamShutdownMsg};
 use crate::sub_lib::neighborhood::NodeDescriptor;
 use crate::sub_lib::node_addr::NodeAddr;
 use crate::sub_lib::peer_actors::{BindMessage, NewPublicIp};
-use crate::sub_lib::stream_handler_pool::TransmitDataMsg;
+use crate::sub_lib::stream_handler_pool::{BanClandestinePeerMsg, TransmitDataMsg};
 use crate::sub_lib::utils::{handle_ui_crash_request, NODE_MAILBOX_CAPACITY};
 use actix::Actor;
 use actix::Addr;
@@ -39,6 +39,7 @@ pub struct Dispatcher {
     crashable: bool,
     node_descriptor: NodeDescriptor,
     to_stream: Option<Recipient<TransmitDataMsg>>,
+    ban_clandestine_peer_sub_opt: Option<Recipient<BanClandestinePeerMsg>>,
     cryptde_pair: CryptDEPair,
     logger: Logger,
 }
@@ -68,6 +69,7 @@ impl Handler<PoolBindMessage> for Dispatcher {
 
     fn handle(&mut self, msg: PoolBindMessage, _ctx: &mut Self::Context) {
         self.to_stream = Some(msg.stream_handler_pool_subs.transmit_sub);
+        self.ban_clandestine_peer_sub_opt = Some(msg.stream_handler_pool_subs.ban_sub);
     }
 }
 
@@ -111,6 +113,22 @@ impl Handler<TransmitDataMsg> for Dispatcher {
     }
 }
 
+impl Handler<BanClandestinePeerMsg> for Dispatcher {
+    type Result = ();
+
+    fn handle(&mut self, msg: BanClandestinePeerMsg, _ctx: &mut Self::Context) {
+        debug!(
+            self.logger,
+            "Relaying {:?} ban of {} to StreamHandlerPool", msg.duration, msg.peer_ip
+        );
+        self.ban_clandestine_peer_sub_opt
+            .as_ref()
+            .expect("StreamHandlerPool unbound in Dispatcher")
+            .try_send(msg)
+            .expect("StreamHandlerPool is dead");
+    }
+}
+
 impl Handler<StreamShutdownMsg> for Dispatcher {
     type Result = ();
 
@@ -164,6 +182,7 @@ impl Dispatcher {
             crashable,
             node_descriptor,
             to_stream: None,
+            ban_clandestine_peer_sub_opt: None,
             cryptde_pair,
             logger: Logger::new("Dispatcher"),
         }
@@ -177,6 +196,7 @@ impl Dispatcher {
             stream_shutdown_sub: addr.clone().recipient::<StreamShutdownMsg>(),
             ui_sub: addr.clone().recipient::<NodeFromUiMessage>(),
             new_ip_sub: addr.clone().recipient::<NewPublicIp>(),
+            ban_clandestine_peer_sub: addr.clone().recipient::<BanClandestinePeerMsg>(),
         }
     }
 
@@ -241,7 +261,7 @@ mod tests {
     use std::net::{IpAddr, Ipv4Addr, SocketAddr};
     use std::str::FromStr;
     use std::thread;
-    use std::time::SystemTime;
+    use std::time::{Duration, SystemTime};
 
     lazy_static! {
         static ref CRYPTDE_PAIR: CryptDEPair = CryptDEPair::null();
@@ -460,6 +480,36 @@ mod tests {
         assert_eq!(recording.len(), 1);
     }
 
+    #[test]
+    fn forwards_clandestine_peer_bans_to_stream_handler_pool() {
+        let system = System::new("test");
+        let subject = Dispatcher::new(NODE_DESCRIPTOR.clone(), CRYPTDE_PAIR.clone(), false);
+        let subject_addr = subject.start();
+        let (stream_handler_pool, _, recording_arc) = make_recorder();
+        let peer_actors = peer_actors_builder().build();
+        subject_addr
+            .try_send(PoolBindMessage {
+                dispatcher_subs: peer_actors.dispatcher.clone(),
+                stream_handler_pool_subs: make_stream_handler_pool_subs_from_recorder(
+                    &stream_handler_pool.start(),
+                ),
+                neighborhood_subs: peer_actors.neighborhood.clone(),
+            })
+            .unwrap();
+        let ban = BanClandestinePeerMsg {
+            peer_ip: IpAddr::from_str("1.2.3.4").unwrap(),
+            duration: Duration::from_secs(300),
+        };
+
+        subject_addr.try_send(ban.clone()).unwrap();
+
+        System::current().stop_with_code(0);
+        system.run();
+        let recording = recording_arc.lock().unwrap();
+        assert_eq!(recording.get_record::<BanClandestinePeerMsg>(0), &ban);
+        assert_eq!(recording.len(), 1);
+    }
+
     #[test]
     fn handle_stream_shutdown_msg_routes_non_clandestine_to_proxy_server() {
         let system = System::new("test");
@@ -14,6 +14,7 @@ use std::time::SystemTime;
 
 pub struct ConsumingService {
     cryptde: Box<dyn CryptDE>,
+    session_cryptdes: Vec<Box<dyn CryptDE>>,
     to_dispatcher: Recipient<TransmitDataMsg>,
     to_hopper: Recipient<InboundClientData>,
     logger: Logger,
@@ -27,12 +28,17 @@ impl ConsumingService {
     ) -> Self {
         Self {
             cryptde,
+            session_cryptdes: vec![],
             to_dispatcher,
             to_hopper,
             logger: Logger::new("ConsumingService"),
         }
     }
 
+    pub fn set_session_cryptdes(&mut self, session_cryptdes: Vec<Box<dyn CryptDE>>) {
+        self.session_cryptdes = session_cryptdes;
+    }
+
     pub fn consume_no_lookup(&self, incipient_cores_package: NoLookupIncipientCoresPackage) {
         debug!(
             self.logger,
@@ -76,16 +82,26 @@ impl ConsumingService {
             "Instructed to send IncipientCoresPackage with {}-byte payload",
             incipient_cores_package.payload.len()
         );
-        match LiveCoresPackage::from_incipient(incipient_cores_package, self.cryptde.borrow()) {
+        // At an exit Node, the top hop of the Route back is encrypted to a session key
+        let top_hop_cryptde = match incipient_cores_package
+            .route
+            .top_hop_cryptde(self.cryptde.borrow(), &self.session_cryptdes)
+        {
+            Ok(cryptde) => cryptde,
+            Err(e) => {
+                error!(self.logger, "Couldn't decode top hop: {:?}", e);
+                return;
+            }
+        };
+        match LiveCoresPackage::from_incipient(incipient_cores_package, top_hop_cryptde) {
             Ok((live_package, next_hop)) => {
-                let encrypted_package =
-                    match encodex(self.cryptde.as_ref(), &next_hop.public_key, &live_package) {
-                        Ok(p) => p,
-                        Err(e) => {
-                            error!(self.logger, "Couldn't encode package: {:?}", e);
-                            return;
-                        }
-                    };
+                let encrypted_package = match live_package.seal(self.cryptde.as_ref(), &next_hop) {
+                    Ok(p) => p,
+                    Err(e) => {
+                        error!(self.logger, "Couldn't encode package: {:?}", e);
+                        return;
+                    }
+                };
                 if &next_hop.public_key == self.cryptde.public_key() {
                     self.zero_hop(encrypted_package);
                 } else {
@@ -138,8 +154,11 @@ mod tests {
     use super::*;
     use crate::bootstrapper::CryptDEPair;
     use crate::node_test_utils::check_timestamp;
-    use crate::sub_lib::cryptde::PublicKey;
+    use crate::sub_lib::cryptde::{decodex, PublicKey};
+    use crate::sub_lib::cryptde_null::CryptDENull;
+    use crate::sub_lib::cryptde_real::CryptDEReal;
     use crate::sub_lib::dispatcher::{Component, InboundClientData};
+    use crate::sub_lib::hopper::MessageType;
     use crate::sub_lib::node_addr::NodeAddr;
     use crate::sub_lib::route::Route;
     use crate::sub_lib::route::RouteSegment;
@@ -151,6 +170,7 @@ mod tests {
     use masq_lib::test_utils::logging::init_test_logging;
     use masq_lib::test_utils::logging::TestLogHandler;
     use masq_lib::test_utils::utils::TEST_DEFAULT_CHAIN;
+    use std::collections::HashMap;
     use std::net::{IpAddr, Ipv4Addr};
     use std::str::FromStr;
     use std::time::SystemTime;
@@ -273,6 +293,130 @@ mod tests {
         );
     }
 
+    #[test]
+    fn consume_shifts_a_top_hop_encrypted_to_a_session_key() {
+        let main_cryptde = CryptDEReal::new(TEST_DEFAULT_CHAIN);
+        let session_cryptde = CryptDEReal::new(TEST_DEFAULT_CHAIN);
+        let origin_cryptde = CryptDEReal::new(TEST_DEFAULT_CHAIN);
+        let destination_cryptde = CryptDEReal::new(TEST_DEFAULT_CHAIN);
+        let (dispatcher, _, dispatcher_recording_arc) = make_recorder();
+        let mut route = Route::one_way(
+            RouteSegment::new(
+                vec![
+                    origin_cryptde.public_key(),
+                    main_cryptde.public_key(),
+                    destination_cryptde.public_key(),
+                ],
+                Component::Neighborhood,
+            )
+            .with_session_keys(HashMap::from([(
+                main_cryptde.public_key().clone(),
+                session_cryptde.public_key().clone(),
+            )])),
+            &origin_cryptde,
+            None,
+            None,
+        )
+        .unwrap();
+        route.shift(&origin_cryptde).unwrap();
+        let incipient_cores_package = IncipientCoresPackage::new(
+            &main_cryptde,
+            route,
+            make_meaningless_message_type(),
+            destination_cryptde.public_key(),
+        )
+        .unwrap();
+        let system = System::new("consume_shifts_a_top_hop_encrypted_to_a_session_key");
+        let peer_actors = peer_actors_builder().dispatcher(dispatcher).build();
+        let mut subject = ConsumingService::new(
+            main_cryptde.dup(),
+            peer_actors.dispatcher.from_dispatcher_client,
+            peer_actors.hopper.from_dispatcher,
+        );
+        subject.set_session_cryptdes(vec![session_cryptde.dup()]);
+
+        subject.consume(incipient_cores_package);
+
+        System::current().stop();
+        system.run();
+        let dispatcher_recording = dispatcher_recording_arc.lock().unwrap();
+        let record = dispatcher_recording.get_record::<TransmitDataMsg>(0);
+        assert_eq!(
+            record.endpoint,
+            Endpoint::Key(destination_cryptde.public_key().clone())
+        );
+        let lcp: LiveCoresPackage =
+            decodex(&destination_cryptde, &CryptData::new(&record.data)).unwrap();
+        assert_eq!(
+            lcp.route.next_hop(&destination_cryptde).unwrap().component,
+            Component::Neighborhood
+        );
+    }
+
+    #[test]
+    fn consume_seals_the_package_to_the_session_key_of_the_next_node() {
+        let main_cryptde = CryptDENull::new(TEST_DEFAULT_CHAIN);
+        let destination_cryptde = CryptDENull::new(TEST_DEFAULT_CHAIN);
+        let destination_session_cryptde = CryptDENull::new(TEST_DEFAULT_CHAIN);
+        let (dispatcher, _, dispatcher_recording_arc) = make_recorder();
+        let route = Route::one_way(
+            RouteSegment::new(
+                vec![main_cryptde.public_key(), destination_cryptde.public_key()],
+                Component::ProxyClient,
+            )
+            .with_session_keys(HashMap::from([(
+                destination_cryptde.public_key().clone(),
+                destination_session_cryptde.public_key().clone(),
+            )])),
+            &main_cryptde,
+            None,
+            None,
+        )
+        .unwrap();
+        let incipient_cores_package = IncipientCoresPackage::new(
+            &main_cryptde,
+            route,
+            make_meaningless_message_type(),
+            destination_session_cryptde.public_key(),
+        )
+        .unwrap();
+        let system = System::new("consume_seals_the_package_to_the_session_key_of_the_next_node");
+        let peer_actors = peer_actors_builder().dispatcher(dispatcher).build();
+        let subject = ConsumingService::new(
+            main_cryptde.dup(),
+            peer_actors.dispatcher.from_dispatcher_client,
+            peer_actors.hopper.from_dispatcher,
+        );
+
+        subject.consume(incipient_cores_package);
+
+        System::current().stop();
+        system.run();
+        let dispatcher_recording = dispatcher_recording_arc.lock().unwrap();
+        let record = dispatcher_recording.get_record::<TransmitDataMsg>(0);
+        assert_eq!(
+            record.endpoint,
+            Endpoint::Key(destination_cryptde.public_key().clone())
+        );
+        let lcp = LiveCoresPackage::open(
+            &CryptData::new(&record.data),
+            &destination_cryptde,
+            &[destination_session_cryptde.dup()],
+        )
+        .unwrap();
+        assert_eq!(
+            lcp.route
+                .top_hop_cryptde(&destination_cryptde, &[destination_session_cryptde.dup()])
+                .unwrap()
+                .public_key(),
+            destination_session_cryptde.public_key()
+        );
+        assert_eq!(
+            decodex::<MessageType>(&destination_session_cryptde, &lcp.payload).unwrap(),
+            make_meaningless_message_type()
+        );
+    }
+
     #[test]
     fn consume_sends_zero_hop_incipient_directly_to_hopper() {
         let cryptde = CRYPTDE_PAIR.main.as_ref();
@@ -339,7 +483,10 @@ mod tests {
         subject.consume(
             IncipientCoresPackage::new(
                 CRYPTDE_PAIR.main.as_ref(),
-                Route { hops: vec![] },
+                Route {
+                    hops: vec![],
+                    top_hop_key_id_opt: None,
+                },
                 make_meaningless_message_type(),
                 &PublicKey::new(&[1, 2]),
             )
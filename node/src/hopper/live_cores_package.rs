@@ -1,16 +1,29 @@
 // Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 
 use crate::sub_lib::cryptde::PublicKey;
-use crate::sub_lib::cryptde::{decodex, CryptDE};
-use crate::sub_lib::cryptde::{CodexError, CryptData};
+use crate::sub_lib::cryptde::{decodex, encodex, CryptDE};
+use crate::sub_lib::cryptde::{CodexError, CryptData, SerdeCborError};
 use crate::sub_lib::data_version::DataVersion;
 use crate::sub_lib::hop::LiveHop;
 use crate::sub_lib::hopper::IncipientCoresPackage;
+use crate::sub_lib::hopper::{find_session_cryptde, session_key_id};
 use crate::sub_lib::hopper::{ExpiredCoresPackage, MessageType, NoLookupIncipientCoresPackage};
-use crate::sub_lib::route::Route;
+use crate::sub_lib::route::{Route, RouteError};
+use crate::sub_lib::versioned_data::VersionedData;
+use masq_lib::constants::LIVE_CORES_PACKAGE_CURRENT_VERSION;
 use serde_derive::{Deserialize, Serialize};
+use std::convert::TryFrom;
 use std::net::SocketAddr;
 
+// A package sealed to one of the receiving Node's session keys travels in this frame, which
+// names the key. Nodes that publish no session key never get one, so the sealed package can be
+// versioned data: only Nodes that understand versioned LiveCoresPackages ever see it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionSealedPackage {
+    pub key_id: u64,
+    pub sealed: CryptData,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct LiveCoresPackage {
     pub version: DataVersion,
@@ -20,7 +33,11 @@ pub struct LiveCoresPackage {
 
 impl LiveCoresPackage {
     pub fn version() -> DataVersion {
-        DataVersion::new(0, 0).expect("Internal Error")
+        DataVersion::new(
+            LIVE_CORES_PACKAGE_CURRENT_VERSION.major,
+            LIVE_CORES_PACKAGE_CURRENT_VERSION.minor,
+        )
+        .expect("Internal Error")
     }
 
     pub fn new(route: Route, payload: CryptData) -> LiveCoresPackage {
@@ -33,13 +50,54 @@ impl LiveCoresPackage {
 
     pub fn into_next_live(
         mut self,
-        cryptde: &dyn CryptDE, // must be the main CryptDE of the Node to which the top hop is encrypted
+        cryptde: &dyn CryptDE, // must be the CryptDE to which the top hop is encrypted
     ) -> Result<(LiveHop, LiveCoresPackage), CodexError> {
         let next_hop = self.route.shift(cryptde)?;
         let next_live = LiveCoresPackage::new(self.route, self.payload);
         Ok((next_hop, next_live))
     }
 
+    // Encrypts this package for the Node at next_hop: to the session key the hop names, if any,
+    // and to the Node's static key otherwise.
+    pub fn seal(&self, cryptde: &dyn CryptDE, next_hop: &LiveHop) -> Result<CryptData, CodexError> {
+        match &next_hop.session_key_opt {
+            None => encodex(cryptde, &next_hop.public_key, self),
+            Some(session_key) => {
+                let frame = SessionSealedPackage {
+                    key_id: session_key_id(session_key),
+                    sealed: encodex(cryptde, session_key, &VersionedData::from(self.clone()))?,
+                };
+                match serde_cbor::ser::to_vec(&frame) {
+                    Ok(serialized) => Ok(CryptData::new(&serialized)),
+                    Err(e) => Err(CodexError::SerializationError(SerdeCborError {
+                        delegate: e,
+                    })),
+                }
+            }
+        }
+    }
+
+    // Reverses seal() on the receiving Node.
+    pub fn open(
+        data: &CryptData,
+        main_cryptde: &dyn CryptDE,
+        session_cryptdes: &[Box<dyn CryptDE>],
+    ) -> Result<LiveCoresPackage, CodexError> {
+        match serde_cbor::de::from_slice::<SessionSealedPackage>(data.as_slice()) {
+            Ok(frame) => match find_session_cryptde(session_cryptdes, frame.key_id) {
+                Some(session_cryptde) => {
+                    let versioned =
+                        decodex::<VersionedData<LiveCoresPackage>>(session_cryptde, &frame.sealed)?;
+                    LiveCoresPackage::try_from(versioned).map_err(CodexError::MigrationError)
+                }
+                None => Err(CodexError::RoutingError(RouteError::UnknownSessionKey(
+                    frame.key_id,
+                ))),
+            },
+            Err(_) => decodex::<LiveCoresPackage>(main_cryptde, data),
+        }
+    }
+
     pub fn from_no_lookup_incipient(
         no_lookup_incipient: NoLookupIncipientCoresPackage,
         cryptde: &dyn CryptDE, // must be the CryptDE of the Node the package is about to leave
@@ -59,7 +117,7 @@ impl LiveCoresPackage {
 
     pub fn from_incipient(
         incipient: IncipientCoresPackage,
-        cryptde: &dyn CryptDE, // must be the main CryptDE of the Node to which the top hop is encrypted
+        cryptde: &dyn CryptDE, // must be the CryptDE to which the top hop is encrypted
     ) -> Result<(LiveCoresPackage, LiveHop), String> {
         let mut route = incipient.route.clone();
         let next_hop = match route.shift(cryptde) {
@@ -72,10 +130,10 @@ impl LiveCoresPackage {
     pub fn to_expired(
         &self,
         immediate_neighbor_addr: SocketAddr,
-        main_cryptde: &dyn CryptDE, // Must be the main CryptDE of the Node for which the package (not necessarily the payload) is intended.
-        payload_cryptde: &dyn CryptDE, // Must be the main or alias CryptDE of the Node for which the payload is intended.
+        top_hop_cryptde: &dyn CryptDE, // Must be the CryptDE to which the top hop is encrypted.
+        payload_cryptde: &dyn CryptDE, // Must be the CryptDE of the Node for which the payload is intended.
     ) -> Result<ExpiredCoresPackage<MessageType>, CodexError> {
-        let top_hop = self.route.next_hop(main_cryptde)?;
+        let top_hop = self.route.next_hop(top_hop_cryptde)?;
         decodex::<MessageType>(payload_cryptde, &self.payload).map(|decoded_payload| {
             ExpiredCoresPackage::new(
                 immediate_neighbor_addr,
@@ -188,7 +246,13 @@ mod tests {
 
     #[test]
     fn to_next_live_complains_about_bad_input() {
-        let subject = LiveCoresPackage::new(Route { hops: vec![] }, CryptData::new(&[]));
+        let subject = LiveCoresPackage::new(
+            Route {
+                hops: vec![],
+                top_hop_key_id_opt: None,
+            },
+            CryptData::new(&[]),
+        );
 
         let result = subject.into_next_live(CRYPTDE_PAIR.main.as_ref());
 
@@ -264,7 +328,8 @@ mod tests {
             LiveHop {
                 public_key: key34.clone(),
                 payer: Some(paying_wallet.as_payer(&key12, &contract_address)),
-                component: Component::Hopper
+                component: Component::Hopper,
+                session_key_opt: None,
             },
             next_stop
         );
@@ -279,7 +344,10 @@ mod tests {
         let cryptde = CRYPTDE_PAIR.main.as_ref();
         let incipient = IncipientCoresPackage::new(
             cryptde,
-            Route { hops: vec![] },
+            Route {
+                hops: vec![],
+                top_hop_key_id_opt: None,
+            },
             make_meaningless_message_type(),
             &PublicKey::new(&[3, 4]),
         )
@@ -389,7 +457,10 @@ mod tests {
     #[test]
     fn to_expired_complains_about_bad_route() {
         let subject = LiveCoresPackage::new(
-            Route { hops: vec![] },
+            Route {
+                hops: vec![],
+                top_hop_key_id_opt: None,
+            },
             CryptData::new(CRYPTDE_PAIR.main.as_ref().private_key().as_slice()),
         );
 
@@ -405,6 +476,84 @@ mod tests {
         );
     }
 
+    #[test]
+    fn version_is_the_current_live_cores_package_version() {
+        let result = LiveCoresPackage::version();
+
+        assert_eq!(result, DataVersion::new(0, 1).unwrap());
+    }
+
+    #[test]
+    fn package_sealed_to_a_static_key_is_opened_with_the_main_cryptde() {
+        let main_cryptde = CryptDENull::new(TEST_DEFAULT_CHAIN);
+        let session_cryptdes: Vec<Box<dyn CryptDE>> =
+            vec![Box::new(CryptDENull::new(TEST_DEFAULT_CHAIN))];
+        let subject = LiveCoresPackage::new(
+            make_meaningless_route(&CRYPTDE_PAIR),
+            CryptData::new(&[1, 2, 3, 4]),
+        );
+        let next_hop = LiveHop::new(main_cryptde.public_key(), None, Component::Hopper);
+
+        let sealed = subject.seal(&main_cryptde, &next_hop).unwrap();
+
+        assert_eq!(
+            sealed,
+            encodex(&main_cryptde, main_cryptde.public_key(), &subject).unwrap()
+        );
+        let result = LiveCoresPackage::open(&sealed, &main_cryptde, &session_cryptdes);
+        assert_eq!(result, Ok(subject));
+    }
+
+    #[test]
+    fn package_sealed_to_a_session_key_is_opened_with_the_session_cryptde_it_names() {
+        let main_cryptde = CryptDENull::new(TEST_DEFAULT_CHAIN);
+        let session_cryptdes: Vec<Box<dyn CryptDE>> = vec![
+            Box::new(CryptDENull::new(TEST_DEFAULT_CHAIN)),
+            Box::new(CryptDENull::new(TEST_DEFAULT_CHAIN)),
+        ];
+        let session_key = session_cryptdes[1].public_key().clone();
+        let subject = LiveCoresPackage::new(
+            make_meaningless_route(&CRYPTDE_PAIR),
+            CryptData::new(&[1, 2, 3, 4]),
+        );
+        let mut next_hop = LiveHop::new(main_cryptde.public_key(), None, Component::Hopper);
+        next_hop.session_key_opt = Some(session_key.clone());
+
+        let sealed = subject.seal(&main_cryptde, &next_hop).unwrap();
+
+        let frame = serde_cbor::de::from_slice::<SessionSealedPackage>(sealed.as_slice()).unwrap();
+        assert_eq!(frame.key_id, session_key_id(&session_key));
+        let versioned: VersionedData<LiveCoresPackage> =
+            decodex(session_cryptdes[1].as_ref(), &frame.sealed).unwrap();
+        assert_eq!(LiveCoresPackage::try_from(versioned), Ok(subject.clone()));
+        let result = LiveCoresPackage::open(&sealed, &main_cryptde, &session_cryptdes);
+        assert_eq!(result, Ok(subject));
+    }
+
+    #[test]
+    fn open_complains_about_a_package_sealed_to_an_unknown_session_key() {
+        let main_cryptde = CryptDENull::new(TEST_DEFAULT_CHAIN);
+        let session_cryptdes: Vec<Box<dyn CryptDE>> =
+            vec![Box::new(CryptDENull::new(TEST_DEFAULT_CHAIN))];
+        let retired_session_key = PublicKey::new(&[9, 8, 7, 6, 5, 4, 3, 2, 1]);
+        let subject = LiveCoresPackage::new(
+            make_meaningless_route(&CRYPTDE_PAIR),
+            CryptData::new(&[1, 2, 3, 4]),
+        );
+        let mut next_hop = LiveHop::new(main_cryptde.public_key(), None, Component::Hopper);
+        next_hop.session_key_opt = Some(retired_session_key);
+        let sealed = subject.seal(&main_cryptde, &next_hop).unwrap();
+
+        let result = LiveCoresPackage::open(&sealed, &main_cryptde, &session_cryptdes);
+
+        assert_eq!(
+            result,
+            Err(CodexError::RoutingError(RouteError::UnknownSessionKey(
+                0x0908070605040302
+            )))
+        );
+    }
+
     #[test]
     fn live_cores_package_serialization_deserialization() {
         let original = LiveCoresPackage::new(
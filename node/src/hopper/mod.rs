@@ -6,20 +6,32 @@ mod routing_service;
 
 use crate::bootstrapper::CryptDEPair;
 use crate::hopper::routing_service::RoutingServiceSubs;
+use crate::sub_lib::cryptde::CryptDE;
+use crate::sub_lib::cryptde_real::CryptDEReal;
 use crate::sub_lib::dispatcher::InboundClientData;
 use crate::sub_lib::hopper::HopperSubs;
 use crate::sub_lib::hopper::IncipientCoresPackage;
-use crate::sub_lib::hopper::{HopperConfig, NoLookupIncipientCoresPackage};
-use crate::sub_lib::peer_actors::BindMessage;
-use crate::sub_lib::utils::{handle_ui_crash_request, NODE_MAILBOX_CAPACITY};
+use crate::sub_lib::hopper::{
+    HopperConfig, NoLookupIncipientCoresPackage, ScheduledSessionKeyRotation,
+    SESSION_KEY_LIFETIME_SECS,
+};
+use crate::sub_lib::neighborhood::{
+    retired_key_is_still_honored, ConfigChange, ConfigChangeMsg, MainCryptDE,
+};
+use crate::sub_lib::peer_actors::{BindMessage, PeerActors};
+use crate::sub_lib::utils::{
+    handle_ui_crash_request, NotifyLaterHandle, NotifyLaterHandleReal, NODE_MAILBOX_CAPACITY,
+};
 use actix::Actor;
 use actix::Addr;
 use actix::Context;
 use actix::Handler;
 use consuming_service::ConsumingService;
+use masq_lib::blockchains::chains::Chain;
 use masq_lib::logger::Logger;
 use masq_lib::ui_gateway::NodeFromUiMessage;
 use routing_service::RoutingService;
+use std::time::{Duration, SystemTime};
 
 pub const CRASH_KEY: &str = "HOPPER";
 
@@ -27,6 +39,17 @@ pub struct Hopper {
     cryptde_pair: CryptDEPair,
     consuming_service: Option<ConsumingService>,
     routing_service: Option<RoutingService>,
+    // After a key rotation, CORES packages encrypted for the old key are still routed with it
+    // until the grace period is over.
+    retired_routing_service_opt: Option<(RoutingService, SystemTime)>,
+    // Other Nodes encrypt the hops of their Routes through this Node to its current session key.
+    // The one before is kept for a lifetime in case of Routes built before the rotation; after
+    // that it's destroyed, and with it the ability to decrypt hops recorded back then.
+    session_cryptde_opt: Option<Box<dyn CryptDE>>,
+    previous_session_cryptde_opt: Option<Box<dyn CryptDE>>,
+    session_key_chain_opt: Option<Chain>,
+    session_key_rotation_scheduler: Box<dyn NotifyLaterHandle<ScheduledSessionKeyRotation, Hopper>>,
+    peer_actors_opt: Option<PeerActors>,
     per_routing_service: u64,
     per_routing_byte: u64,
     is_decentralized: bool,
@@ -43,25 +66,35 @@ impl Handler<BindMessage> for Hopper {
 
     fn handle(&mut self, msg: BindMessage, ctx: &mut Self::Context) -> Self::Result {
         ctx.set_mailbox_capacity(NODE_MAILBOX_CAPACITY);
-        self.consuming_service = Some(ConsumingService::new(
-            self.cryptde_pair.main.dup(),
-            msg.peer_actors.dispatcher.from_dispatcher_client.clone(),
-            msg.peer_actors.hopper.from_dispatcher.clone(),
-        ));
-        self.routing_service = Some(RoutingService::new(
-            self.cryptde_pair.clone(),
-            RoutingServiceSubs {
-                proxy_client_subs_opt: msg.peer_actors.proxy_client_opt,
-                proxy_server_subs: msg.peer_actors.proxy_server,
-                neighborhood_subs: msg.peer_actors.neighborhood,
-                hopper_subs: msg.peer_actors.hopper,
-                to_dispatcher: msg.peer_actors.dispatcher.from_dispatcher_client,
-                to_accountant_routing: msg.peer_actors.accountant.report_routing_service_provided,
-            },
-            self.per_routing_service,
-            self.per_routing_byte,
-            self.is_decentralized,
-        ));
+        self.make_services(msg.peer_actors.clone());
+        self.peer_actors_opt = Some(msg.peer_actors);
+        if self.session_key_chain_opt.is_some() {
+            self.rotate_session_key();
+            self.schedule_session_key_rotation(ctx);
+        }
+    }
+}
+
+impl Handler<ScheduledSessionKeyRotation> for Hopper {
+    type Result = ();
+
+    fn handle(
+        &mut self,
+        _msg: ScheduledSessionKeyRotation,
+        ctx: &mut Self::Context,
+    ) -> Self::Result {
+        self.rotate_session_key();
+        self.schedule_session_key_rotation(ctx);
+    }
+}
+
+impl Handler<ConfigChangeMsg> for Hopper {
+    type Result = ();
+
+    fn handle(&mut self, msg: ConfigChangeMsg, _ctx: &mut Self::Context) -> Self::Result {
+        if let ConfigChange::UpdateMainCryptDE(MainCryptDE(new_cryptde)) = msg.change {
+            self.handle_new_main_cryptde(new_cryptde)
+        }
     }
 }
 
@@ -99,10 +132,29 @@ impl Handler<InboundClientData> for Hopper {
     type Result = ();
 
     fn handle(&mut self, msg: InboundClientData, _ctx: &mut Self::Context) -> Self::Result {
-        self.routing_service
+        let grace_period_is_over = self
+            .retired_routing_service_opt
+            .as_ref()
+            .map_or(false, |(_, retired_at)| {
+                !retired_key_is_still_honored(*retired_at)
+            });
+        if grace_period_is_over {
+            debug!(
+                self.logger,
+                "Grace period for the retired key is over; no longer routing for it"
+            );
+            self.retired_routing_service_opt = None;
+        }
+        let routing_service = self
+            .routing_service
             .as_ref()
-            .expect("Hopper unbound: no RoutingService")
-            .route(msg);
+            .expect("Hopper unbound: no RoutingService");
+        match self.retired_routing_service_opt.as_ref() {
+            Some((retired_routing_service, _)) if !routing_service.can_decode(&msg) => {
+                retired_routing_service.route(msg)
+            }
+            _ => routing_service.route(msg),
+        }
     }
 }
 
@@ -120,6 +172,12 @@ impl Hopper {
             cryptde_pair: config.cryptde_pair,
             consuming_service: None,
             routing_service: None,
+            retired_routing_service_opt: None,
+            session_cryptde_opt: None,
+            previous_session_cryptde_opt: None,
+            session_key_chain_opt: config.session_key_chain_opt,
+            session_key_rotation_scheduler: Box::new(NotifyLaterHandleReal::new()),
+            peer_actors_opt: None,
             crashable: config.crashable,
             per_routing_service: config.per_routing_service,
             per_routing_byte: config.per_routing_byte,
@@ -134,31 +192,131 @@ impl Hopper {
             from_hopper_client: recipient!(addr, IncipientCoresPackage),
             from_hopper_client_no_lookup: recipient!(addr, NoLookupIncipientCoresPackage),
             from_dispatcher: recipient!(addr, InboundClientData),
+            config_change_msg_sub: recipient!(addr, ConfigChangeMsg),
             node_from_ui: recipient!(addr, NodeFromUiMessage),
         }
     }
+
+    fn make_services(&mut self, peer_actors: PeerActors) {
+        self.consuming_service = Some(ConsumingService::new(
+            self.cryptde_pair.main.dup(),
+            peer_actors.dispatcher.from_dispatcher_client.clone(),
+            peer_actors.hopper.from_dispatcher.clone(),
+        ));
+        self.routing_service = Some(RoutingService::new(
+            self.cryptde_pair.clone(),
+            RoutingServiceSubs {
+                proxy_client_subs_opt: peer_actors.proxy_client_opt,
+                proxy_server_subs: peer_actors.proxy_server,
+                neighborhood_subs: peer_actors.neighborhood,
+                hopper_subs: peer_actors.hopper,
+                to_dispatcher: peer_actors.dispatcher.from_dispatcher_client,
+                to_accountant_routing: peer_actors.accountant.report_routing_service_provided,
+            },
+            self.per_routing_service,
+            self.per_routing_byte,
+            self.is_decentralized,
+        ));
+        self.hand_out_session_cryptdes();
+    }
+
+    fn schedule_session_key_rotation(&self, ctx: &mut Context<Self>) {
+        let _ = self.session_key_rotation_scheduler.notify_later(
+            ScheduledSessionKeyRotation {},
+            Duration::from_secs(SESSION_KEY_LIFETIME_SECS),
+            ctx,
+        );
+    }
+
+    fn rotate_session_key(&mut self) {
+        let (chain, peer_actors) = match (self.session_key_chain_opt, &self.peer_actors_opt) {
+            (Some(chain), Some(peer_actors)) => (chain, peer_actors),
+            _ => return,
+        };
+        let new_session_cryptde: Box<dyn CryptDE> = Box::new(CryptDEReal::new(chain));
+        let new_session_key = new_session_cryptde.public_key().clone();
+        peer_actors
+            .neighborhood
+            .config_change_msg_sub
+            .try_send(ConfigChangeMsg {
+                change: ConfigChange::UpdateSessionKey(new_session_key.clone()),
+            })
+            .expect("Neighborhood is dead");
+        self.previous_session_cryptde_opt = self.session_cryptde_opt.replace(new_session_cryptde);
+        self.hand_out_session_cryptdes();
+        debug!(
+            self.logger,
+            "Hops through this Node are now to be encrypted to session key {}", new_session_key
+        );
+    }
+
+    fn hand_out_session_cryptdes(&mut self) {
+        let session_cryptdes: Vec<Box<dyn CryptDE>> = self
+            .session_cryptde_opt
+            .iter()
+            .chain(self.previous_session_cryptde_opt.iter())
+            .map(|session_cryptde| session_cryptde.dup())
+            .collect();
+        if let Some(consuming_service) = self.consuming_service.as_mut() {
+            consuming_service.set_session_cryptdes(
+                session_cryptdes
+                    .iter()
+                    .map(|session_cryptde| session_cryptde.dup())
+                    .collect(),
+            );
+        }
+        if let Some(routing_service) = self.routing_service.as_mut() {
+            routing_service.set_session_cryptdes(session_cryptdes);
+        }
+    }
+
+    fn handle_new_main_cryptde(&mut self, new_cryptde: Box<dyn CryptDE>) {
+        self.cryptde_pair.main = new_cryptde;
+        let peer_actors = match self.peer_actors_opt.as_ref() {
+            Some(peer_actors) => peer_actors.clone(),
+            None => return,
+        };
+        let retired_routing_service_opt = self.routing_service.take();
+        self.make_services(peer_actors);
+        self.retired_routing_service_opt =
+            retired_routing_service_opt.map(|routing_service| (routing_service, SystemTime::now()));
+        info!(
+            self.logger,
+            "Now routing with key {}; the previous key is honored for a grace period",
+            self.cryptde_pair.main.public_key()
+        );
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::live_cores_package::LiveCoresPackage;
     use super::*;
-    use crate::sub_lib::cryptde::PlainData;
+    use crate::accountant::db_access_objects::banned_dao::BAN_CACHE;
     use crate::sub_lib::cryptde::PublicKey;
+    use crate::sub_lib::cryptde::{encodex, PlainData};
+    use crate::sub_lib::cryptde_real::CryptDEReal;
     use crate::sub_lib::dispatcher::Component;
-    use crate::sub_lib::hopper::IncipientCoresPackage;
+    use crate::sub_lib::hopper::{ExpiredCoresPackage, IncipientCoresPackage, MessageType};
+    use crate::sub_lib::proxy_server::ClientRequestPayload_0v1;
     use crate::sub_lib::route::Route;
     use crate::sub_lib::route::RouteSegment;
-    use crate::test_utils::unshared_test_utils::prove_that_crash_request_handler_is_hooked_up;
+    use crate::test_utils::recorder::{make_recorder, peer_actors_builder};
+    use crate::test_utils::unshared_test_utils::notify_handlers::NotifyLaterHandleMock;
+    use crate::test_utils::unshared_test_utils::{
+        make_request_payload, prove_that_crash_request_handler_is_hooked_up,
+    };
     use crate::test_utils::{
         make_meaningless_message_type, make_paying_wallet, route_to_proxy_client,
     };
     use actix::Actor;
     use actix::System;
     use lazy_static::lazy_static;
+    use masq_lib::test_utils::environment_guard::EnvironmentGuard;
     use masq_lib::test_utils::utils::TEST_DEFAULT_CHAIN;
     use std::net::SocketAddr;
     use std::str::FromStr;
+    use std::sync::{Arc, Mutex};
     use std::time::SystemTime;
 
     lazy_static! {
@@ -205,6 +363,7 @@ mod tests {
             per_routing_service: 100,
             per_routing_byte: 200,
             is_decentralized: false,
+            session_key_chain_opt: None,
             crashable: false,
         });
         let subject_addr = subject.start();
@@ -244,6 +403,7 @@ mod tests {
             per_routing_service: 100,
             per_routing_byte: 200,
             is_decentralized: false,
+            session_key_chain_opt: None,
             crashable: false,
         });
         let subject_addr = subject.start();
@@ -254,6 +414,196 @@ mod tests {
         system.run();
     }
 
+    fn make_ibcd_for_proxy_client(cryptde: &dyn CryptDE) -> InboundClientData {
+        let route = route_to_proxy_client(&cryptde.public_key(), cryptde);
+        let payload = encodex::<MessageType>(
+            cryptde,
+            &cryptde.public_key(),
+            &make_request_payload(0, cryptde).into(),
+        )
+        .unwrap();
+        let live_package = LiveCoresPackage::new(route, payload);
+        let live_data = PlainData::new(&serde_cbor::ser::to_vec(&live_package).unwrap()[..]);
+        InboundClientData {
+            timestamp: SystemTime::now(),
+            client_addr: SocketAddr::from_str("1.2.3.4:5678").unwrap(),
+            reception_port: None,
+            last_data: false,
+            is_clandestine: false,
+            sequence_number: None,
+            data: cryptde
+                .encode(&cryptde.public_key(), &live_data)
+                .unwrap()
+                .into(),
+        }
+    }
+
+    #[test]
+    fn packages_for_the_retired_key_are_still_routed_after_a_key_rotation() {
+        let _eg = EnvironmentGuard::new();
+        BAN_CACHE.clear();
+        // CryptDENull panics when asked to decrypt with the wrong key, so these need real keys
+        let retired_cryptde = CryptDEReal::new(TEST_DEFAULT_CHAIN);
+        let new_cryptde = CryptDEReal::new(TEST_DEFAULT_CHAIN);
+        let ibcd_for_retired_key = make_ibcd_for_proxy_client(&retired_cryptde);
+        let ibcd_for_new_key = make_ibcd_for_proxy_client(&new_cryptde);
+        let (proxy_client, _, proxy_client_recording_arc) = make_recorder();
+        let system =
+            System::new("packages_for_the_retired_key_are_still_routed_after_a_key_rotation");
+        let subject = Hopper::new(HopperConfig {
+            cryptde_pair: CryptDEPair::new(retired_cryptde.dup(), CRYPTDE_PAIR.alias.dup()),
+            per_routing_service: 100,
+            per_routing_byte: 200,
+            is_decentralized: false,
+            session_key_chain_opt: None,
+            crashable: false,
+        });
+        let subject_addr = subject.start();
+        let peer_actors = peer_actors_builder().proxy_client(proxy_client).build();
+        subject_addr.try_send(BindMessage { peer_actors }).unwrap();
+        subject_addr
+            .try_send(ConfigChangeMsg {
+                change: ConfigChange::UpdateMainCryptDE(MainCryptDE(new_cryptde.dup())),
+            })
+            .unwrap();
+
+        subject_addr.try_send(ibcd_for_retired_key).unwrap();
+        subject_addr.try_send(ibcd_for_new_key).unwrap();
+
+        System::current().stop_with_code(0);
+        system.run();
+        let proxy_client_recording = proxy_client_recording_arc.lock().unwrap();
+        assert_eq!(proxy_client_recording.len(), 2);
+        let first =
+            proxy_client_recording.get_record::<ExpiredCoresPackage<ClientRequestPayload_0v1>>(0);
+        assert_eq!(first.payload, make_request_payload(0, &retired_cryptde));
+        let second =
+            proxy_client_recording.get_record::<ExpiredCoresPackage<ClientRequestPayload_0v1>>(1);
+        assert_eq!(second.payload, make_request_payload(0, &new_cryptde));
+    }
+
+    fn session_key_in(msg: &ConfigChangeMsg) -> PublicKey {
+        match &msg.change {
+            ConfigChange::UpdateSessionKey(session_key) => session_key.clone(),
+            other => panic!("Expected UpdateSessionKey, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn binding_announces_a_session_key_and_schedules_its_rotation() {
+        let system = System::new("binding_announces_a_session_key_and_schedules_its_rotation");
+        let notify_later_params_arc = Arc::new(Mutex::new(vec![]));
+        let (neighborhood, _, neighborhood_recording_arc) = make_recorder();
+        let mut subject = Hopper::new(HopperConfig {
+            cryptde_pair: CRYPTDE_PAIR.clone(),
+            per_routing_service: 100,
+            per_routing_byte: 200,
+            is_decentralized: false,
+            session_key_chain_opt: Some(TEST_DEFAULT_CHAIN),
+            crashable: false,
+        });
+        subject.session_key_rotation_scheduler = Box::new(
+            NotifyLaterHandleMock::default().notify_later_params(&notify_later_params_arc),
+        );
+        let subject_addr = subject.start();
+        let peer_actors = peer_actors_builder().neighborhood(neighborhood).build();
+
+        subject_addr.try_send(BindMessage { peer_actors }).unwrap();
+        subject_addr
+            .try_send(ScheduledSessionKeyRotation {})
+            .unwrap();
+
+        System::current().stop();
+        system.run();
+        let neighborhood_recording = neighborhood_recording_arc.lock().unwrap();
+        assert_eq!(neighborhood_recording.len(), 2);
+        let first_session_key =
+            session_key_in(neighborhood_recording.get_record::<ConfigChangeMsg>(0));
+        let second_session_key =
+            session_key_in(neighborhood_recording.get_record::<ConfigChangeMsg>(1));
+        assert_ne!(first_session_key, second_session_key);
+        assert_ne!(&first_session_key, CRYPTDE_PAIR.main.public_key());
+        let notify_later_params = notify_later_params_arc.lock().unwrap();
+        assert_eq!(
+            *notify_later_params,
+            vec![
+                (
+                    ScheduledSessionKeyRotation {},
+                    Duration::from_secs(SESSION_KEY_LIFETIME_SECS)
+                ),
+                (
+                    ScheduledSessionKeyRotation {},
+                    Duration::from_secs(SESSION_KEY_LIFETIME_SECS)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn session_keys_are_not_used_without_a_chain_to_make_them_for() {
+        let system = System::new("session_keys_are_not_used_without_a_chain_to_make_them_for");
+        let notify_later_params_arc = Arc::new(Mutex::new(vec![]));
+        let (neighborhood, _, neighborhood_recording_arc) = make_recorder();
+        let mut subject = Hopper::new(HopperConfig {
+            cryptde_pair: CRYPTDE_PAIR.clone(),
+            per_routing_service: 100,
+            per_routing_byte: 200,
+            is_decentralized: false,
+            session_key_chain_opt: None,
+            crashable: false,
+        });
+        subject.session_key_rotation_scheduler = Box::new(
+            NotifyLaterHandleMock::default().notify_later_params(&notify_later_params_arc),
+        );
+        let subject_addr = subject.start();
+        let peer_actors = peer_actors_builder().neighborhood(neighborhood).build();
+
+        subject_addr.try_send(BindMessage { peer_actors }).unwrap();
+
+        System::current().stop();
+        system.run();
+        let neighborhood_recording = neighborhood_recording_arc.lock().unwrap();
+        assert_eq!(neighborhood_recording.len(), 0);
+        let notify_later_params = notify_later_params_arc.lock().unwrap();
+        assert_eq!(*notify_later_params, vec![]);
+    }
+
+    #[test]
+    fn a_session_key_is_kept_for_one_rotation_and_then_destroyed() {
+        let system = System::new("a_session_key_is_kept_for_one_rotation_and_then_destroyed");
+        let (neighborhood, _, neighborhood_recording_arc) = make_recorder();
+        let mut subject = Hopper::new(HopperConfig {
+            cryptde_pair: CRYPTDE_PAIR.clone(),
+            per_routing_service: 100,
+            per_routing_byte: 200,
+            is_decentralized: false,
+            session_key_chain_opt: Some(TEST_DEFAULT_CHAIN),
+            crashable: false,
+        });
+        subject.peer_actors_opt = Some(peer_actors_builder().neighborhood(neighborhood).build());
+
+        subject.rotate_session_key();
+        subject.rotate_session_key();
+        subject.rotate_session_key();
+
+        System::current().stop();
+        system.run();
+        let neighborhood_recording = neighborhood_recording_arc.lock().unwrap();
+        let session_keys = (0..3)
+            .map(|index| {
+                session_key_in(neighborhood_recording.get_record::<ConfigChangeMsg>(index))
+            })
+            .collect::<Vec<PublicKey>>();
+        assert_eq!(
+            subject.session_cryptde_opt.unwrap().public_key(),
+            &session_keys[2]
+        );
+        assert_eq!(
+            subject.previous_session_cryptde_opt.unwrap().public_key(),
+            &session_keys[1]
+        );
+    }
+
     #[test]
     #[should_panic(
         expected = "panic message (processed with: node_lib::sub_lib::utils::crash_request_analyzer)"
@@ -264,6 +614,7 @@ mod tests {
             per_routing_service: 100,
             per_routing_byte: 200,
             is_decentralized: false,
+            session_key_chain_opt: None,
             crashable: true,
         });
 
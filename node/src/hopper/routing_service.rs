@@ -4,7 +4,7 @@ use crate::blockchain::payer::Payer;
 use crate::bootstrapper::CryptDEPair;
 use crate::neighborhood::gossip::Gossip_0v1;
 use crate::sub_lib::accountant::ReportRoutingServiceProvidedMessage;
-use crate::sub_lib::cryptde::{decodex, encodex, CryptData, CryptdecError};
+use crate::sub_lib::cryptde::{CodexError, CryptDE, CryptData, CryptdecError};
 use crate::sub_lib::dispatcher::{Component, Endpoint, InboundClientData};
 use crate::sub_lib::hop::LiveHop;
 use crate::sub_lib::hopper::{ExpiredCoresPackage, HopperSubs, MessageType};
@@ -16,7 +16,6 @@ use crate::sub_lib::proxy_server::{ClientRequestPayload_0v1, ProxyServerSubs};
 use crate::sub_lib::stream_handler_pool::TransmitDataMsg;
 use actix::Recipient;
 use masq_lib::logger::Logger;
-use std::borrow::Borrow;
 use std::convert::TryFrom;
 use std::net::SocketAddr;
 use std::time::SystemTime;
@@ -32,6 +31,7 @@ pub struct RoutingServiceSubs {
 
 pub struct RoutingService {
     cryptde_pair: CryptDEPair,
+    session_cryptdes: Vec<Box<dyn CryptDE>>,
     routing_service_subs: RoutingServiceSubs,
     per_routing_service: u64,
     per_routing_byte: u64,
@@ -49,6 +49,7 @@ impl RoutingService {
     ) -> RoutingService {
         RoutingService {
             cryptde_pair,
+            session_cryptdes: vec![],
             routing_service_subs,
             per_routing_service,
             per_routing_byte,
@@ -57,6 +58,19 @@ impl RoutingService {
         }
     }
 
+    pub fn set_session_cryptdes(&mut self, session_cryptdes: Vec<Box<dyn CryptDE>>) {
+        self.session_cryptdes = session_cryptdes;
+    }
+
+    pub fn can_decode(&self, ibcd: &InboundClientData) -> bool {
+        LiveCoresPackage::open(
+            &CryptData::new(&ibcd.data[..]),
+            self.cryptde_pair.main.as_ref(),
+            &self.session_cryptdes,
+        )
+        .is_ok()
+    }
+
     pub fn route(&self, ibcd: InboundClientData) {
         let data_size = ibcd.data.len();
         debug!(
@@ -69,9 +83,10 @@ impl RoutingService {
         let last_data = ibcd.last_data;
         let ibcd_but_data = ibcd.clone_but_data();
 
-        let live_package = match decodex::<LiveCoresPackage>(
-            self.cryptde_pair.main.as_ref(),
+        let live_package = match LiveCoresPackage::open(
             &CryptData::new(&ibcd.data[..]),
+            self.cryptde_pair.main.as_ref(),
+            &self.session_cryptdes,
         ) {
             Ok(lcp) => lcp,
             Err(e) => {
@@ -86,7 +101,10 @@ impl RoutingService {
             }
         };
 
-        let next_hop = match live_package.route.next_hop(self.cryptde_pair.main.borrow()) {
+        let next_hop = match self
+            .top_hop_cryptde(&live_package)
+            .and_then(|top_hop_cryptde| live_package.route.next_hop(top_hop_cryptde))
+        {
             Ok(hop) => hop,
             Err(e) => {
                 error!(
@@ -127,6 +145,12 @@ impl RoutingService {
         }
     }
 
+    fn top_hop_cryptde(&self, live_package: &LiveCoresPackage) -> Result<&dyn CryptDE, CodexError> {
+        live_package
+            .route
+            .top_hop_cryptde(self.cryptde_pair.main.as_ref(), &self.session_cryptdes)
+    }
+
     fn is_destined_for_here(&self, next_hop: &LiveHop) -> bool {
         &next_hop.public_key == self.cryptde_pair.main.public_key()
     }
@@ -170,19 +194,19 @@ impl RoutingService {
         live_package: LiveCoresPackage,
         ibcd_but_data: &InboundClientData,
     ) {
-        let (_, next_lcp) = match live_package.into_next_live(self.cryptde_pair.main.as_ref()) {
+        let (next_hop, next_lcp) = match self
+            .top_hop_cryptde(&live_package)
+            .and_then(|top_hop_cryptde| live_package.into_next_live(top_hop_cryptde))
+        {
             Ok(x) => x,
             Err(e) => {
                 error!(self.logger, "bad zero-hop route: {:?}", e);
                 return;
             }
         };
-        let payload = encodex(
-            self.cryptde_pair.main.as_ref(),
-            self.cryptde_pair.main.public_key(),
-            &next_lcp,
-        )
-        .expect("Encryption of LiveCoresPackage failed");
+        let payload = next_lcp
+            .seal(self.cryptde_pair.main.as_ref(), &next_hop)
+            .expect("Encryption of LiveCoresPackage failed");
         let inbound_client_data = InboundClientData {
             timestamp: ibcd_but_data.timestamp,
             client_addr: ibcd_but_data.client_addr,
@@ -226,13 +250,29 @@ impl RoutingService {
         component: Component,
     ) -> Option<ExpiredCoresPackage<MessageType>> {
         let data_len = live_package.payload.len();
-        let (payload_cryptde, cryptde_name) = match component {
-            Component::ProxyServer => (self.cryptde_pair.alias.as_ref(), "alias"),
-            _ => (self.cryptde_pair.main.as_ref(), "main"),
+        let top_hop_cryptde = match self.top_hop_cryptde(&live_package) {
+            Ok(cryptde) => cryptde,
+            Err(e) => {
+                error!(
+                    self.logger,
+                    "Couldn't expire CORES package with {}-byte payload to {:?}: {:?}",
+                    data_len,
+                    component,
+                    e
+                );
+                return None;
+            }
         };
+        // Payloads for a Node's other components travel under the same key as its hop
+        let (payload_cryptde, cryptde_name) =
+            match (component, live_package.route.top_hop_key_id_opt) {
+                (Component::ProxyServer, _) => (self.cryptde_pair.alias.as_ref(), "alias"),
+                (_, None) => (top_hop_cryptde, "main"),
+                (_, Some(_)) => (top_hop_cryptde, "session"),
+            };
         let expired_package = match live_package.to_expired(
             immediate_neighbor_addr,
-            self.cryptde_pair.main.as_ref(),
+            top_hop_cryptde,
             payload_cryptde,
         ) {
             Ok(pkg) => pkg,
@@ -467,30 +507,29 @@ impl RoutingService {
         live_package: LiveCoresPackage,
         last_data: bool,
     ) -> Result<TransmitDataMsg, CryptdecError> {
-        let (next_hop, next_live_package) =
-            match live_package.into_next_live(self.cryptde_pair.main.borrow()) {
-                Err(e) => {
-                    let msg = format!(
-                        "Couldn't get next hop and outgoing LCP from incoming LCP: {:?}",
-                        e
-                    );
-                    error!(self.logger, "{}", &msg);
-                    return Err(CryptdecError::OtherError(msg));
-                }
-                Ok(p) => p,
-            };
-        let next_live_package_enc = match encodex(
-            self.cryptde_pair.main.as_ref(),
-            &next_hop.public_key,
-            &next_live_package,
-        ) {
-            Ok(nlpe) => nlpe,
+        let (next_hop, next_live_package) = match self
+            .top_hop_cryptde(&live_package)
+            .and_then(|top_hop_cryptde| live_package.into_next_live(top_hop_cryptde))
+        {
             Err(e) => {
-                let msg = format!("Couldn't serialize or encrypt outgoing LCP: {:?}", e);
+                let msg = format!(
+                    "Couldn't get next hop and outgoing LCP from incoming LCP: {:?}",
+                    e
+                );
                 error!(self.logger, "{}", &msg);
                 return Err(CryptdecError::OtherError(msg));
             }
+            Ok(p) => p,
         };
+        let next_live_package_enc =
+            match next_live_package.seal(self.cryptde_pair.main.as_ref(), &next_hop) {
+                Ok(nlpe) => nlpe,
+                Err(e) => {
+                    let msg = format!("Couldn't serialize or encrypt outgoing LCP: {:?}", e);
+                    error!(self.logger, "{}", &msg);
+                    return Err(CryptdecError::OtherError(msg));
+                }
+            };
         Ok(TransmitDataMsg {
             endpoint: Endpoint::Key(next_hop.public_key),
             last_data,
@@ -510,7 +549,9 @@ mod tests {
     use crate::sub_lib::cryptde::{encodex, CryptDE, PlainData, PublicKey};
     use crate::sub_lib::cryptde_null::CryptDENull;
     use crate::sub_lib::cryptde_real::CryptDEReal;
-    use crate::sub_lib::hopper::{IncipientCoresPackage, MessageType, MessageType::ClientRequest};
+    use crate::sub_lib::hopper::{
+        session_key_id, IncipientCoresPackage, MessageType, MessageType::ClientRequest,
+    };
     use crate::sub_lib::neighborhood::GossipFailure_0v1;
     use crate::sub_lib::peer_actors::PeerActors;
     use crate::sub_lib::proxy_client::{ClientResponsePayload_0v1, DnsResolveFailure_0v1};
@@ -523,8 +564,8 @@ mod tests {
     use crate::test_utils::recorder::{make_recorder, peer_actors_builder};
     use crate::test_utils::unshared_test_utils::{make_request_payload, make_response_payload};
     use crate::test_utils::{
-        make_meaningless_message_type, make_paying_wallet, rate_pack_routing,
-        rate_pack_routing_byte, route_from_proxy_client, route_to_proxy_client,
+        make_meaningless_message_type, make_meaningless_route, make_paying_wallet,
+        rate_pack_routing, rate_pack_routing_byte, route_from_proxy_client, route_to_proxy_client,
         route_to_proxy_server,
     };
     use actix::System;
@@ -532,6 +573,7 @@ mod tests {
     use masq_lib::test_utils::environment_guard::EnvironmentGuard;
     use masq_lib::test_utils::logging::{init_test_logging, TestLogHandler};
     use masq_lib::test_utils::utils::TEST_DEFAULT_CHAIN;
+    use std::collections::HashMap;
     use std::net::SocketAddr;
     use std::str::FromStr;
     use std::time::SystemTime;
@@ -817,6 +859,76 @@ mod tests {
         assert_eq!(record.payload_len, expected_ecp.payload_len);
     }
 
+    #[test]
+    fn routes_live_message_sealed_to_a_session_key() {
+        let _eg = EnvironmentGuard::new();
+        BAN_CACHE.clear();
+        let main_cryptde = CryptDEReal::new(TEST_DEFAULT_CHAIN);
+        let session_cryptde = CryptDEReal::new(TEST_DEFAULT_CHAIN);
+        let origin_cryptde = CryptDEReal::new(TEST_DEFAULT_CHAIN);
+        let (proxy_client, _, proxy_client_recording_arc) = make_recorder();
+        let mut route = Route::one_way(
+            RouteSegment::new(
+                vec![origin_cryptde.public_key(), main_cryptde.public_key()],
+                Component::ProxyClient,
+            )
+            .with_session_keys(HashMap::from([(
+                main_cryptde.public_key().clone(),
+                session_cryptde.public_key().clone(),
+            )])),
+            &origin_cryptde,
+            None,
+            None,
+        )
+        .unwrap();
+        let next_hop = route.shift(&origin_cryptde).unwrap();
+        let payload = make_request_payload(0, &main_cryptde);
+        let lcp = LiveCoresPackage::new(
+            route,
+            encodex::<MessageType>(
+                &origin_cryptde,
+                session_cryptde.public_key(),
+                &payload.clone().into(),
+            )
+            .unwrap(),
+        );
+        let data_enc = lcp.seal(&origin_cryptde, &next_hop).unwrap();
+        let inbound_client_data = InboundClientData {
+            timestamp: SystemTime::now(),
+            client_addr: SocketAddr::from_str("1.2.3.4:5678").unwrap(),
+            reception_port: None,
+            sequence_number: None,
+            last_data: true,
+            is_clandestine: false,
+            data: data_enc.into(),
+        };
+        let system = System::new("routes_live_message_sealed_to_a_session_key");
+        let peer_actors = peer_actors_builder().proxy_client(proxy_client).build();
+        let mut subject = RoutingService::new(
+            CryptDEPair::new(
+                main_cryptde.dup(),
+                Box::new(CryptDEReal::new(TEST_DEFAULT_CHAIN)),
+            ),
+            make_routing_service_subs(peer_actors),
+            0,
+            0,
+            false,
+        );
+        subject.set_session_cryptdes(vec![
+            Box::new(CryptDEReal::new(TEST_DEFAULT_CHAIN)),
+            session_cryptde.dup(),
+        ]);
+
+        subject.route(inbound_client_data);
+
+        System::current().stop();
+        system.run();
+        let proxy_client_recording = proxy_client_recording_arc.lock().unwrap();
+        let record =
+            proxy_client_recording.get_record::<ExpiredCoresPackage<ClientRequestPayload_0v1>>(0);
+        assert_eq!(record.payload, payload);
+    }
+
     #[test]
     fn complains_about_live_message_for_nonexistent_proxy_client() {
         let _eg = EnvironmentGuard::new();
@@ -1186,6 +1298,127 @@ mod tests {
         )
     }
 
+    #[test]
+    fn passes_on_inbound_client_data_sealed_to_the_session_key_of_the_next_node() {
+        let _eg = EnvironmentGuard::new();
+        BAN_CACHE.clear();
+        let main_cryptde = CRYPTDE_PAIR.main.as_ref();
+        let session_cryptde = CryptDENull::new(TEST_DEFAULT_CHAIN);
+        let origin_cryptde = CryptDENull::new(TEST_DEFAULT_CHAIN);
+        let next_key = PublicKey::new(&[65, 65, 65]);
+        let next_session_cryptde = CryptDENull::new(TEST_DEFAULT_CHAIN);
+        let paying_wallet = make_paying_wallet(b"wallet");
+        let (dispatcher, _, dispatcher_recording_arc) = make_recorder();
+        let mut route = Route::one_way(
+            RouteSegment::new(
+                vec![
+                    origin_cryptde.public_key(),
+                    main_cryptde.public_key(),
+                    &next_key,
+                ],
+                Component::Neighborhood,
+            )
+            .with_session_keys(HashMap::from([
+                (
+                    main_cryptde.public_key().clone(),
+                    session_cryptde.public_key().clone(),
+                ),
+                (next_key.clone(), next_session_cryptde.public_key().clone()),
+            ])),
+            &origin_cryptde,
+            Some(paying_wallet),
+            Some(TEST_DEFAULT_CHAIN.rec().contract),
+        )
+        .unwrap();
+        let next_hop = route.shift(&origin_cryptde).unwrap();
+        let lcp = LiveCoresPackage::new(route, CryptData::new(b"abcd"));
+        let inbound_client_data = InboundClientData {
+            timestamp: SystemTime::now(),
+            client_addr: SocketAddr::from_str("1.2.3.4:5678").unwrap(),
+            reception_port: None,
+            last_data: true,
+            is_clandestine: true,
+            sequence_number: None,
+            data: lcp.seal(&origin_cryptde, &next_hop).unwrap().into(),
+        };
+        let system =
+            System::new("passes_on_inbound_client_data_sealed_to_the_session_key_of_the_next_node");
+        let peer_actors = peer_actors_builder().dispatcher(dispatcher).build();
+        let mut subject = RoutingService::new(
+            CRYPTDE_PAIR.clone(),
+            make_routing_service_subs(peer_actors),
+            rate_pack_routing(103),
+            rate_pack_routing_byte(103),
+            false,
+        );
+        subject.set_session_cryptdes(vec![session_cryptde.dup()]);
+
+        subject.route(inbound_client_data);
+
+        System::current().stop();
+        system.run();
+        let dispatcher_recording = dispatcher_recording_arc.lock().unwrap();
+        let record = dispatcher_recording.get_record::<TransmitDataMsg>(0);
+        assert_eq!(record.endpoint, Endpoint::Key(next_key));
+        let next_lcp = LiveCoresPackage::open(
+            &CryptData::new(&record.data),
+            main_cryptde,
+            &[next_session_cryptde.dup()],
+        )
+        .unwrap();
+        assert_eq!(
+            next_lcp.route.top_hop_key_id_opt,
+            Some(session_key_id(next_session_cryptde.public_key()))
+        );
+        assert_eq!(
+            next_lcp
+                .route
+                .next_hop(&next_session_cryptde)
+                .unwrap()
+                .component,
+            Component::Neighborhood
+        );
+    }
+
+    #[test]
+    fn route_logs_and_ignores_package_sealed_to_an_unknown_session_key() {
+        let _eg = EnvironmentGuard::new();
+        init_test_logging();
+        BAN_CACHE.clear();
+        let main_cryptde = CRYPTDE_PAIR.main.as_ref();
+        let mut next_hop = LiveHop::new(main_cryptde.public_key(), None, Component::Hopper);
+        next_hop.session_key_opt = Some(PublicKey::new(&[1, 2, 3, 4, 5, 6, 7, 8]));
+        let lcp = LiveCoresPackage::new(
+            make_meaningless_route(&CRYPTDE_PAIR),
+            CryptData::new(b"abcd"),
+        );
+        let inbound_client_data = InboundClientData {
+            timestamp: SystemTime::now(),
+            client_addr: SocketAddr::from_str("1.2.3.4:5678").unwrap(),
+            reception_port: None,
+            last_data: true,
+            is_clandestine: true,
+            sequence_number: None,
+            data: lcp.seal(main_cryptde, &next_hop).unwrap().into(),
+        };
+        let peer_actors = peer_actors_builder().build();
+        let subject = RoutingService::new(
+            CRYPTDE_PAIR.clone(),
+            make_routing_service_subs(peer_actors),
+            0,
+            0,
+            false,
+        );
+
+        assert_eq!(subject.can_decode(&inbound_client_data), false);
+        subject.route(inbound_client_data);
+
+        TestLogHandler::new().exists_log_containing(&format!(
+            "ERROR: RoutingService: Couldn't decode CORES package in {}-byte buffer from 1.2.3.4:5678: RoutingError(UnknownSessionKey(72623859790382856))",
+            lcp.seal(main_cryptde, &next_hop).unwrap().len()
+        ));
+    }
+
     #[test]
     fn reprocesses_inbound_client_data_meant_for_this_node_and_destined_for_hopper() {
         let _eg = EnvironmentGuard::new();
@@ -1384,7 +1617,10 @@ mod tests {
                 Err(e) => panic!("Couldn't encode hop: {:?}", e),
             })
             .collect();
-        let route = Route { hops };
+        let route = Route {
+            hops,
+            top_hop_key_id_opt: None,
+        };
         let icp = IncipientCoresPackage::new(main_cryptde, route, payload, public_key).unwrap();
         let (lcp, _) = LiveCoresPackage::from_incipient(icp, main_cryptde).unwrap();
         let data_ser = PlainData::new(&serde_cbor::ser::to_vec(&lcp).unwrap()[..]);
@@ -1487,7 +1723,10 @@ mod tests {
             })
             .collect();
 
-        let route = Route { hops };
+        let route = Route {
+            hops,
+            top_hop_key_id_opt: None,
+        };
 
         let lcp = LiveCoresPackage::new(
             route,
@@ -1733,7 +1972,13 @@ mod tests {
     fn route_logs_and_ignores_invalid_live_cores_package() {
         init_test_logging();
         let main_cryptde = CRYPTDE_PAIR.main.as_ref();
-        let lcp = LiveCoresPackage::new(Route { hops: vec![] }, CryptData::new(&[]));
+        let lcp = LiveCoresPackage::new(
+            Route {
+                hops: vec![],
+                top_hop_key_id_opt: None,
+            },
+            CryptData::new(&[]),
+        );
         let data_ser = PlainData::new(&serde_cbor::ser::to_vec(&lcp).unwrap()[..]);
         let data_enc = main_cryptde
             .encode(&main_cryptde.public_key(), &data_ser)
@@ -1804,7 +2049,13 @@ mod tests {
             200,
             false,
         );
-        let lcp = LiveCoresPackage::new(Route { hops: vec![] }, CryptData::new(&[]));
+        let lcp = LiveCoresPackage::new(
+            Route {
+                hops: vec![],
+                top_hop_key_id_opt: None,
+            },
+            CryptData::new(&[]),
+        );
         let ibcd = InboundClientData {
             timestamp: SystemTime::now(),
             client_addr: SocketAddr::from_str("1.2.3.4:5678").unwrap(),
@@ -1972,7 +2223,10 @@ mod tests {
         let expired_package = ExpiredCoresPackage::new(
             SocketAddr::from_str("1.2.3.4:1234").unwrap(),
             None,
-            Route { hops: vec![] },
+            Route {
+                hops: vec![],
+                top_hop_key_id_opt: None,
+            },
             MessageType::Gossip(VersionedData::test_new(dv!(0, 0), vec![])),
             0,
         );
@@ -2011,7 +2265,10 @@ mod tests {
         let expired_package = ExpiredCoresPackage::new(
             SocketAddr::from_str("1.2.3.4:1234").unwrap(),
             None,
-            Route { hops: vec![] },
+            Route {
+                hops: vec![],
+                top_hop_key_id_opt: None,
+            },
             MessageType::ClientRequest(VersionedData::test_new(dv!(0, 0), vec![])),
             0,
         );
@@ -2050,7 +2307,10 @@ mod tests {
         let expired_package = ExpiredCoresPackage::new(
             SocketAddr::from_str("1.2.3.4:1234").unwrap(),
             None,
-            Route { hops: vec![] },
+            Route {
+                hops: vec![],
+                top_hop_key_id_opt: None,
+            },
             MessageType::ClientResponse(VersionedData::test_new(dv!(0, 0), vec![])),
             0,
         );
@@ -2089,7 +2349,10 @@ mod tests {
         let expired_package = ExpiredCoresPackage::new(
             SocketAddr::from_str("1.2.3.4:1234").unwrap(),
             None,
-            Route { hops: vec![] },
+            Route {
+                hops: vec![],
+                top_hop_key_id_opt: None,
+            },
             MessageType::DnsResolveFailed(VersionedData::test_new(dv!(0, 0), vec![])),
             0,
         );
@@ -2128,7 +2391,10 @@ mod tests {
         let expired_package = ExpiredCoresPackage::new(
             SocketAddr::from_str("1.2.3.4:1234").unwrap(),
             None,
-            Route { hops: vec![] },
+            Route {
+                hops: vec![],
+                top_hop_key_id_opt: None,
+            },
             MessageType::GossipFailure(VersionedData::test_new(dv!(0, 0), vec![])),
             0,
         );
@@ -1335,6 +1335,44 @@ impl GossipAcceptor for GossipAcceptorReal {
         agrs: Vec<AccessibleGossipRecord>,
         gossip_source: SocketAddr,
         neighborhood_metadata: NeighborhoodMetadata,
+    ) -> GossipAcceptanceResult {
+        database.expire_retired_keys();
+        let keys_handed_over = self.apply_key_handovers(database, &agrs);
+        let record_count = agrs.len();
+        let agrs = self.drop_records_under_retired_keys(database, agrs);
+        if agrs.is_empty() && record_count > 0 {
+            return GossipAcceptanceResult::Ignored;
+        }
+        let agrs = Self::translate_retired_keys(database, agrs);
+        match self.delegate(database, agrs, gossip_source, neighborhood_metadata) {
+            GossipAcceptanceResult::Ignored if keys_handed_over => GossipAcceptanceResult::Accepted,
+            result => result,
+        }
+    }
+}
+
+impl GossipAcceptorReal {
+    pub fn new(cryptde: Box<dyn CryptDE>) -> GossipAcceptorReal {
+        let logger = Logger::new("GossipAcceptor");
+        GossipAcceptorReal {
+            gossip_handlers: vec![
+                Box::new(DebutHandler::new(logger.clone())),
+                Box::new(PassHandler::new()),
+                Box::new(IntroductionHandler::new(logger.clone())),
+                Box::new(StandardGossipHandler::new(logger.clone())),
+                Box::new(RejectHandler::new()),
+            ],
+            cryptde,
+            logger,
+        }
+    }
+
+    fn delegate(
+        &self,
+        database: &mut NeighborhoodDatabase,
+        agrs: Vec<AccessibleGossipRecord>,
+        gossip_source: SocketAddr,
+        neighborhood_metadata: NeighborhoodMetadata,
     ) -> GossipAcceptanceResult {
         let (qualification, handler_ref) = self
             .gossip_handlers
@@ -1363,22 +1401,93 @@ impl GossipAcceptor for GossipAcceptorReal {
             Qualification::Malformed(reason) => GossipAcceptanceResult::Ban(reason),
         }
     }
-}
 
-impl GossipAcceptorReal {
-    pub fn new(cryptde: Box<dyn CryptDE>) -> GossipAcceptorReal {
-        let logger = Logger::new("GossipAcceptor");
-        GossipAcceptorReal {
-            gossip_handlers: vec![
-                Box::new(DebutHandler::new(logger.clone())),
-                Box::new(PassHandler::new()),
-                Box::new(IntroductionHandler::new(logger.clone())),
-                Box::new(StandardGossipHandler::new(logger.clone())),
-                Box::new(RejectHandler::new()),
-            ],
-            cryptde,
-            logger,
+    // A Node that has rotated its key pair Gossips a record under its new key, carrying its old
+    // key's signature over the new one. If we know the old key, the Node keeps its place in our
+    // database, neighborships included, under the new key. Accounting follows wallets rather
+    // than keys, so it carries over by itself.
+    fn apply_key_handovers(
+        &self,
+        database: &mut NeighborhoodDatabase,
+        agrs: &[AccessibleGossipRecord],
+    ) -> bool {
+        let mut keys_handed_over = false;
+        for agr in agrs {
+            let key_handover = match &agr.inner.key_handover_opt {
+                Some(key_handover) => key_handover,
+                None => continue,
+            };
+            let previous_key = &key_handover.previous_public_key;
+            let new_key = &agr.inner.public_key;
+            if previous_key == database.root_key()
+                || database.node_by_key(previous_key).is_none()
+                || database.node_by_key(new_key).is_some()
+            {
+                continue;
+            }
+            if !key_handover.is_valid(self.cryptde.as_ref(), new_key) {
+                warning!(
+                    self.logger,
+                    "Ignoring key handover from {} to {}: the signature doesn't match",
+                    previous_key,
+                    new_key
+                );
+                continue;
+            }
+            match database.rekey_node(previous_key, new_key) {
+                Ok(()) => {
+                    info!(
+                        self.logger,
+                        "Node {} has rotated its key; it is now {}", previous_key, new_key
+                    );
+                    keys_handed_over = true;
+                }
+                Err(e) => warning!(
+                    self.logger,
+                    "Couldn't hand over {} to {}: {:?}",
+                    previous_key,
+                    new_key,
+                    e
+                ),
+            }
         }
+        keys_handed_over
+    }
+
+    fn drop_records_under_retired_keys(
+        &self,
+        database: &NeighborhoodDatabase,
+        agrs: Vec<AccessibleGossipRecord>,
+    ) -> Vec<AccessibleGossipRecord> {
+        let (retired, current): (Vec<_>, Vec<_>) = agrs
+            .into_iter()
+            .partition(|agr| database.successor_key(&agr.inner.public_key).is_some());
+        retired.iter().for_each(|agr| {
+            debug!(
+                self.logger,
+                "Ignoring record under retired key {}", agr.inner.public_key
+            )
+        });
+        current
+    }
+
+    // Only the database's picture of the neighborships changes; the signed Gossip keeps
+    // referring to the retired keys until the Nodes that signed it Gossip again.
+    fn translate_retired_keys(
+        database: &NeighborhoodDatabase,
+        agrs: Vec<AccessibleGossipRecord>,
+    ) -> Vec<AccessibleGossipRecord> {
+        agrs.into_iter()
+            .map(|mut agr| {
+                agr.inner.neighbors = agr
+                    .inner
+                    .neighbors
+                    .into_iter()
+                    .map(|key| database.successor_key(&key).cloned().unwrap_or(key))
+                    .collect();
+                agr
+            })
+            .collect()
     }
 
     fn make_debut_triple(
@@ -1420,7 +1529,7 @@ mod tests {
     use crate::bootstrapper::CryptDEPair;
     use crate::neighborhood::gossip_producer::GossipProducer;
     use crate::neighborhood::gossip_producer::GossipProducerReal;
-    use crate::neighborhood::node_record::NodeRecord;
+    use crate::neighborhood::node_record::{KeyHandover, NodeRecord};
     use crate::neighborhood::{
         FallbackPreference, UserExitPreferences, COUNTRY_UNDESIRABILITY_FACTOR,
         UNREACHABLE_COUNTRY_PENALTY,
@@ -4146,6 +4255,160 @@ mod tests {
         assert_eq!(dest_db.node_by_key(disconnected_node.public_key()), None);
     }
 
+    fn make_rotated_source_db(
+        src_root: &NodeRecord,
+        dest_root: &NodeRecord,
+        new_key: &PublicKey,
+        signing_key: &PublicKey,
+    ) -> NeighborhoodDatabase {
+        let old_key = src_root.public_key();
+        let mut src_db = db_from_node(src_root);
+        src_db.add_node(dest_root.clone()).unwrap();
+        src_db.add_arbitrary_full_neighbor(old_key, dest_root.public_key());
+        src_db.rekey_node(old_key, new_key).unwrap();
+        let mut key_handover =
+            KeyHandover::new(&CryptDENull::from(signing_key, TEST_DEFAULT_CHAIN), new_key);
+        key_handover.previous_public_key = old_key.clone();
+        src_db.root_mut().set_key_handover(Some(key_handover));
+        src_db.root_mut().increment_version();
+        src_db.resign_node(new_key);
+        src_db
+    }
+
+    #[test]
+    fn key_handover_moves_a_known_node_to_its_new_key() {
+        let src_root = make_node_record(1234, true);
+        let dest_root = make_node_record(2345, true);
+        let old_key = src_root.public_key().clone();
+        let new_key = PublicKey::new(&[9, 8, 7, 6]);
+        let mut dest_db = db_from_node(&dest_root);
+        dest_db.add_node(src_root.clone()).unwrap();
+        dest_db.add_arbitrary_full_neighbor(dest_root.public_key(), &old_key);
+        let src_db = make_rotated_source_db(&src_root, &dest_root, &new_key, &old_key);
+        let gossip = GossipBuilder::new(&src_db).node(&new_key, true).build();
+        let subject = make_subject(CRYPTDE_PAIR.main.as_ref());
+
+        let result = subject.handle(
+            &mut dest_db,
+            gossip.try_into().unwrap(),
+            src_root.node_addr_opt().unwrap().into(),
+            make_default_neighborhood_metadata(),
+        );
+
+        assert_eq!(result, GossipAcceptanceResult::Accepted);
+        assert_eq!(dest_db.node_by_key(&old_key), None);
+        assert_eq!(
+            dest_db.node_by_key(&new_key).unwrap().inner,
+            src_db.root().inner
+        );
+        assert!(dest_db.has_full_neighbor(dest_root.public_key(), &new_key));
+        assert_eq!(dest_db.successor_key(&old_key), Some(&new_key));
+    }
+
+    #[test]
+    fn key_handover_signed_by_the_wrong_key_is_ignored() {
+        init_test_logging();
+        let src_root = make_node_record(1234, true);
+        let dest_root = make_node_record(2345, true);
+        let old_key = src_root.public_key().clone();
+        let new_key = PublicKey::new(&[9, 8, 7, 6]);
+        let mut dest_db = db_from_node(&dest_root);
+        dest_db.add_node(src_root.clone()).unwrap();
+        dest_db.add_arbitrary_full_neighbor(dest_root.public_key(), &old_key);
+        let impostor_key = PublicKey::new(&[4, 3, 2, 1]);
+        let src_db = make_rotated_source_db(&src_root, &dest_root, &new_key, &impostor_key);
+        let gossip = GossipBuilder::new(&src_db).node(&new_key, true).build();
+        let subject = make_subject(CRYPTDE_PAIR.main.as_ref());
+
+        let _ = subject.handle(
+            &mut dest_db,
+            gossip.try_into().unwrap(),
+            src_root.node_addr_opt().unwrap().into(),
+            make_default_neighborhood_metadata(),
+        );
+
+        assert!(dest_db.node_by_key(&old_key).is_some());
+        assert!(dest_db.has_full_neighbor(dest_root.public_key(), &old_key));
+        assert_eq!(dest_db.successor_key(&old_key), None);
+        TestLogHandler::new().exists_log_containing(
+            "WARN: GossipAcceptor: Ignoring key handover from AQIDBA to CQgHBg: the signature \
+             doesn't match",
+        );
+    }
+
+    #[test]
+    fn records_under_retired_keys_are_ignored() {
+        let src_root = make_node_record(1234, true);
+        let dest_root = make_node_record(2345, true);
+        let old_key = src_root.public_key().clone();
+        let new_key = PublicKey::new(&[9, 8, 7, 6]);
+        let mut dest_db = db_from_node(&dest_root);
+        dest_db.add_node(src_root.clone()).unwrap();
+        dest_db.add_arbitrary_full_neighbor(dest_root.public_key(), &old_key);
+        dest_db.rekey_node(&old_key, &new_key).unwrap();
+        let mut src_db = db_from_node(&src_root);
+        src_db.add_node(dest_root.clone()).unwrap();
+        src_db.add_arbitrary_full_neighbor(&old_key, dest_root.public_key());
+        src_db.root_mut().increment_version();
+        src_db.resign_node(&old_key);
+        let gossip = GossipBuilder::new(&src_db).node(&old_key, true).build();
+        let subject = make_subject(CRYPTDE_PAIR.main.as_ref());
+        let original_dest_db = dest_db.clone();
+
+        let result = subject.handle(
+            &mut dest_db,
+            gossip.try_into().unwrap(),
+            src_root.node_addr_opt().unwrap().into(),
+            make_default_neighborhood_metadata(),
+        );
+
+        assert_eq!(result, GossipAcceptanceResult::Ignored);
+        assert_eq!(dest_db.node_by_key(&old_key), None);
+        assert_eq!(
+            dest_db.node_by_key(&new_key),
+            original_dest_db.node_by_key(&new_key)
+        );
+    }
+
+    #[test]
+    fn references_to_retired_keys_are_translated_to_their_successors() {
+        let src_root = make_node_record(1234, true);
+        let dest_root = make_node_record(2345, true);
+        let rotated_node = make_node_record(3456, true);
+        let old_key = rotated_node.public_key().clone();
+        let new_key = PublicKey::new(&[9, 8, 7, 6]);
+        let mut dest_db = db_from_node(&dest_root);
+        dest_db.add_node(src_root.clone()).unwrap();
+        dest_db.add_node(rotated_node.clone()).unwrap();
+        dest_db.add_arbitrary_full_neighbor(dest_root.public_key(), src_root.public_key());
+        dest_db.add_arbitrary_full_neighbor(src_root.public_key(), &old_key);
+        dest_db.rekey_node(&old_key, &new_key).unwrap();
+        let mut src_db = db_from_node(&src_root);
+        src_db.add_node(dest_root.clone()).unwrap();
+        src_db.add_node(rotated_node.clone()).unwrap();
+        src_db.add_arbitrary_full_neighbor(src_root.public_key(), dest_root.public_key());
+        src_db.add_arbitrary_full_neighbor(src_root.public_key(), &old_key);
+        src_db.root_mut().increment_version();
+        src_db.resign_node(src_root.public_key());
+        let gossip = GossipBuilder::new(&src_db)
+            .node(src_root.public_key(), true)
+            .build();
+        let subject = make_subject(CRYPTDE_PAIR.main.as_ref());
+
+        let result = subject.handle(
+            &mut dest_db,
+            gossip.try_into().unwrap(),
+            src_root.node_addr_opt().unwrap().into(),
+            make_default_neighborhood_metadata(),
+        );
+
+        assert_eq!(result, GossipAcceptanceResult::Accepted);
+        let src_record = dest_db.node_by_key(src_root.public_key()).unwrap();
+        assert_eq!(src_record.version(), src_db.root().version());
+        assert!(src_record.has_half_neighbor(&new_key));
+        assert!(!src_record.has_half_neighbor(&old_key));
+    }
+
     #[test]
     fn standard_gossip_with_current_and_obsolete_versions_doesnt_change_anything() {
         let dest_root = make_node_record(1234, true);
@@ -0,0 +1,565 @@
+// Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+use crate::sub_lib::stream_handler_pool::clandestine_peer_id;
+use masq_lib::messages::UiGossipRejections;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const PEER_GOSSIP_BURST: u32 = 20;
+pub const PEER_GOSSIP_PER_SECOND: f64 = 2.0;
+pub const GLOBAL_GOSSIP_BURST: u32 = 200;
+pub const GLOBAL_GOSSIP_PER_SECOND: f64 = 50.0;
+pub const BAN_PENALTY_THRESHOLD: u32 = 100;
+pub const PENALTY_DECAY_PER_MINUTE: u32 = 10;
+pub const FIRST_BAN_DURATION: Duration = Duration::from_secs(5 * 60);
+pub const MAX_BAN_DURATION: Duration = Duration::from_secs(24 * 60 * 60);
+pub const NEW_PEERS_BETWEEN_SWEEPS: usize = 1000;
+pub const MAX_PEERS_TRACKED: usize = 10000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GossipRejection {
+    RateLimited,
+    Overloaded,
+    WhileBanned,
+    Malformed,
+    BadSignature,
+    Malefactor,
+}
+
+impl GossipRejection {
+    // Being over the Node-wide limit isn't any one peer's fault, and a banned peer has already
+    // been punished; everything else brings the peer closer to a ban.
+    fn penalty(self) -> u32 {
+        match self {
+            GossipRejection::Overloaded | GossipRejection::WhileBanned => 0,
+            GossipRejection::RateLimited => 5,
+            GossipRejection::Malformed
+            | GossipRejection::BadSignature
+            | GossipRejection::Malefactor => 50,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_second: f64,
+    refilled_at: SystemTime,
+}
+
+impl TokenBucket {
+    pub fn new(capacity: u32, refill_per_second: f64, now: SystemTime) -> Self {
+        Self {
+            capacity: capacity as f64,
+            tokens: capacity as f64,
+            refill_per_second,
+            refilled_at: now,
+        }
+    }
+
+    pub fn try_take(&mut self, now: SystemTime) -> bool {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn is_full(&mut self, now: SystemTime) -> bool {
+        self.refill(now);
+        self.tokens >= self.capacity
+    }
+
+    fn refill(&mut self, now: SystemTime) {
+        if let Ok(elapsed) = now.duration_since(self.refilled_at) {
+            self.tokens =
+                (self.tokens + elapsed.as_secs_f64() * self.refill_per_second).min(self.capacity);
+            self.refilled_at = now;
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct RejectionCounts {
+    rate_limited: u64,
+    overloaded: u64,
+    while_banned: u64,
+    malformed: u64,
+    bad_signature: u64,
+    malefactor: u64,
+}
+
+impl RejectionCounts {
+    fn count(&mut self, rejection: GossipRejection) {
+        let counter = match rejection {
+            GossipRejection::RateLimited => &mut self.rate_limited,
+            GossipRejection::Overloaded => &mut self.overloaded,
+            GossipRejection::WhileBanned => &mut self.while_banned,
+            GossipRejection::Malformed => &mut self.malformed,
+            GossipRejection::BadSignature => &mut self.bad_signature,
+            GossipRejection::Malefactor => &mut self.malefactor,
+        };
+        *counter += 1;
+    }
+
+    fn is_empty(&self) -> bool {
+        self == &RejectionCounts::default()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct PeerGossipRecord {
+    bucket: TokenBucket,
+    penalty: u32,
+    penalty_decayed_at: SystemTime,
+    ban_count: u32,
+    banned_until_opt: Option<SystemTime>,
+    rejections: RejectionCounts,
+    last_heard_from: SystemTime,
+}
+
+impl PeerGossipRecord {
+    fn new(now: SystemTime) -> Self {
+        Self {
+            bucket: TokenBucket::new(PEER_GOSSIP_BURST, PEER_GOSSIP_PER_SECOND, now),
+            penalty: 0,
+            penalty_decayed_at: now,
+            ban_count: 0,
+            banned_until_opt: None,
+            rejections: RejectionCounts::default(),
+            last_heard_from: now,
+        }
+    }
+
+    fn is_banned(&self, now: SystemTime) -> bool {
+        matches!(self.banned_until_opt, Some(banned_until) if banned_until > now)
+    }
+
+    fn decay_penalty(&mut self, now: SystemTime) {
+        let minutes = now
+            .duration_since(self.penalty_decayed_at)
+            .unwrap_or_default()
+            .as_secs()
+            / 60;
+        if minutes > 0 {
+            let decay = PENALTY_DECAY_PER_MINUTE.saturating_mul(minutes as u32);
+            self.penalty = self.penalty.saturating_sub(decay);
+            self.penalty_decayed_at += Duration::from_secs(minutes * 60);
+        }
+    }
+
+    // Every ban lasts twice as long as the one before it, up to a limit
+    fn next_ban_duration(&self) -> Duration {
+        FIRST_BAN_DURATION
+            .checked_mul(2u32.saturating_pow(self.ban_count))
+            .unwrap_or(MAX_BAN_DURATION)
+            .min(MAX_BAN_DURATION)
+    }
+}
+
+// Keeps a single peer from flooding the Neighborhood with Gossip, keeps all peers together from
+// flooding it, and bans peers whose Gossip keeps getting rejected. Peers are identified by IP
+// address (or IPv6 /64), because the port of an inbound clandestine connection changes every time.
+pub struct GossipRateLimiter {
+    peers: HashMap<IpAddr, PeerGossipRecord>,
+    new_peers_since_sweep: usize,
+    global_bucket: TokenBucket,
+}
+
+impl GossipRateLimiter {
+    pub fn new(now: SystemTime) -> Self {
+        Self {
+            peers: HashMap::new(),
+            new_peers_since_sweep: 0,
+            global_bucket: TokenBucket::new(GLOBAL_GOSSIP_BURST, GLOBAL_GOSSIP_PER_SECOND, now),
+        }
+    }
+
+    // Decides whether Gossip from the peer may be processed at all. It's cheap, so it belongs
+    // before deserialization and signature checks.
+    pub fn admit(&mut self, peer_ip: IpAddr, now: SystemTime) -> Result<(), GossipRejection> {
+        let record = self.record_mut(peer_ip, now);
+        if record.is_banned(now) {
+            Err(GossipRejection::WhileBanned)
+        } else if !record.bucket.try_take(now) {
+            Err(GossipRejection::RateLimited)
+        } else if !self.global_bucket.try_take(now) {
+            Err(GossipRejection::Overloaded)
+        } else {
+            Ok(())
+        }
+    }
+
+    // Counts the rejection against the peer. Returns the duration of the ban if this rejection
+    // was the one that got the peer banned.
+    pub fn record_rejection(
+        &mut self,
+        peer_ip: IpAddr,
+        rejection: GossipRejection,
+        now: SystemTime,
+    ) -> Option<Duration> {
+        let record = self.record_mut(peer_ip, now);
+        record.rejections.count(rejection);
+        if record.is_banned(now) {
+            return None;
+        }
+        record.decay_penalty(now);
+        record.penalty = record.penalty.saturating_add(rejection.penalty());
+        if record.penalty < BAN_PENALTY_THRESHOLD {
+            return None;
+        }
+        let duration = record.next_ban_duration();
+        record.penalty = 0;
+        record.penalty_decayed_at = now;
+        record.ban_count += 1;
+        record.banned_until_opt = Some(now + duration);
+        Some(duration)
+    }
+
+    pub fn ui_rejections(&self, now: SystemTime) -> Vec<UiGossipRejections> {
+        let mut peers = self
+            .peers
+            .iter()
+            .filter(|(_, record)| !record.rejections.is_empty())
+            .collect::<Vec<_>>();
+        peers.sort_by_key(|(peer_id, _)| **peer_id);
+        peers
+            .into_iter()
+            .map(|(peer_id, record)| UiGossipRejections {
+                peer_ip: match peer_id {
+                    IpAddr::V4(_) => peer_id.to_string(),
+                    IpAddr::V6(_) => format!("{}/64", peer_id),
+                },
+                rate_limited: record.rejections.rate_limited,
+                overloaded: record.rejections.overloaded,
+                while_banned: record.rejections.while_banned,
+                malformed: record.rejections.malformed,
+                bad_signature: record.rejections.bad_signature,
+                malefactor: record.rejections.malefactor,
+                ban_count: record.ban_count,
+                banned_until_opt: record
+                    .banned_until_opt
+                    .filter(|_| record.is_banned(now))
+                    .and_then(|banned_until| banned_until.duration_since(UNIX_EPOCH).ok())
+                    .map(|since_epoch| since_epoch.as_secs()),
+            })
+            .collect()
+    }
+
+    fn record_mut(&mut self, peer_ip: IpAddr, now: SystemTime) -> &mut PeerGossipRecord {
+        let peer_id = clandestine_peer_id(peer_ip);
+        if !self.peers.contains_key(&peer_id) {
+            if self.new_peers_since_sweep >= NEW_PEERS_BETWEEN_SWEEPS {
+                self.sweep(now);
+            }
+            self.new_peers_since_sweep += 1;
+        }
+        let record = self
+            .peers
+            .entry(peer_id)
+            .or_insert_with(|| PeerGossipRecord::new(now));
+        record.last_heard_from = now;
+        record
+    }
+
+    // Sweeping looks at every peer, so it waits until enough new peers have shown up to be worth
+    // it, rather than slowing down every admission during a flood of Gossip.
+    fn sweep(&mut self, now: SystemTime) {
+        self.new_peers_since_sweep = 0;
+        self.forget_idle_peers(now);
+        self.forget_oldest_peers(now);
+    }
+
+    // A peer that has never been rejected and has nothing left to recover from carries no
+    // information worth keeping
+    fn forget_idle_peers(&mut self, now: SystemTime) {
+        self.peers
+            .retain(|_, record| !record.rejections.is_empty() || !record.bucket.is_full(now));
+    }
+
+    // Rejection counts are worth keeping too, but not for an unlimited number of peers: the peers
+    // heard from least recently go first, leaving room for the new peers before the next sweep.
+    // A peer still serving a ban stays, so that changing addresses can't be used to push it out
+    // early.
+    fn forget_oldest_peers(&mut self, now: SystemTime) {
+        let excess = self
+            .peers
+            .len()
+            .saturating_sub(MAX_PEERS_TRACKED - NEW_PEERS_BETWEEN_SWEEPS);
+        if excess == 0 {
+            return;
+        }
+        let mut evictable = self
+            .peers
+            .iter()
+            .filter(|(_, record)| !record.is_banned(now))
+            .map(|(peer_id, record)| (record.last_heard_from, *peer_id))
+            .collect::<Vec<_>>();
+        evictable.sort();
+        evictable.into_iter().take(excess).for_each(|(_, peer_id)| {
+            self.peers.remove(&peer_id);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn peer(ip: &str) -> IpAddr {
+        IpAddr::from_str(ip).unwrap()
+    }
+
+    #[test]
+    fn constants_have_correct_values() {
+        assert_eq!(PEER_GOSSIP_BURST, 20);
+        assert_eq!(PEER_GOSSIP_PER_SECOND, 2.0);
+        assert_eq!(GLOBAL_GOSSIP_BURST, 200);
+        assert_eq!(GLOBAL_GOSSIP_PER_SECOND, 50.0);
+        assert_eq!(BAN_PENALTY_THRESHOLD, 100);
+        assert_eq!(PENALTY_DECAY_PER_MINUTE, 10);
+        assert_eq!(FIRST_BAN_DURATION, Duration::from_secs(300));
+        assert_eq!(MAX_BAN_DURATION, Duration::from_secs(86400));
+        assert_eq!(NEW_PEERS_BETWEEN_SWEEPS, 1000);
+        assert_eq!(MAX_PEERS_TRACKED, 10000);
+    }
+
+    #[test]
+    fn token_bucket_allows_a_burst_and_then_refills_over_time() {
+        let now = SystemTime::now();
+        let mut subject = TokenBucket::new(3, 2.0, now);
+
+        let burst = (0..4).map(|_| subject.try_take(now)).collect::<Vec<_>>();
+        let after_quarter_second = subject.try_take(now + Duration::from_millis(250));
+        let after_half_second = subject.try_take(now + Duration::from_millis(500));
+        let after_an_hour = (0..4)
+            .map(|_| subject.try_take(now + Duration::from_secs(3600)))
+            .collect::<Vec<_>>();
+
+        assert_eq!(burst, vec![true, true, true, false]);
+        assert_eq!(after_quarter_second, false);
+        assert_eq!(after_half_second, true);
+        assert_eq!(after_an_hour, vec![true, true, true, false]);
+    }
+
+    #[test]
+    fn admits_a_peer_until_it_exceeds_its_own_rate() {
+        let now = SystemTime::now();
+        let mut subject = GossipRateLimiter::new(now);
+
+        let results = (0..=PEER_GOSSIP_BURST)
+            .map(|_| subject.admit(peer("1.2.3.4"), now))
+            .collect::<Vec<_>>();
+        let other_peer_result = subject.admit(peer("2.3.4.5"), now);
+
+        assert!(results[..PEER_GOSSIP_BURST as usize]
+            .iter()
+            .all(|result| result.is_ok()));
+        assert_eq!(
+            results[PEER_GOSSIP_BURST as usize],
+            Err(GossipRejection::RateLimited)
+        );
+        assert_eq!(other_peer_result, Ok(()));
+    }
+
+    #[test]
+    fn ipv6_peers_in_the_same_slash_64_share_a_bucket_and_a_ban() {
+        let now = SystemTime::now();
+        let mut subject = GossipRateLimiter::new(now);
+        (0..PEER_GOSSIP_BURST).for_each(|_| subject.admit(peer("2001:db8::1"), now).unwrap());
+
+        let rate_limited = subject.admit(peer("2001:db8::ffff:2"), now);
+        subject.record_rejection(peer("2001:db8::3"), GossipRejection::Malformed, now);
+        let ban = subject.record_rejection(peer("2001:db8::4"), GossipRejection::Malformed, now);
+        let later = now + Duration::from_secs(60);
+        let while_banned = subject.admit(peer("2001:db8::5"), later);
+        let other_slash_64 = subject.admit(peer("2001:db8:0:1::1"), later);
+
+        assert_eq!(rate_limited, Err(GossipRejection::RateLimited));
+        assert_eq!(ban, Some(FIRST_BAN_DURATION));
+        assert_eq!(while_banned, Err(GossipRejection::WhileBanned));
+        assert_eq!(other_slash_64, Ok(()));
+        let rejections = subject.ui_rejections(later);
+        assert_eq!(rejections.len(), 1);
+        assert_eq!(rejections[0].peer_ip, "2001:db8::/64");
+        assert_eq!(rejections[0].malformed, 2);
+        assert_eq!(rejections[0].ban_count, 1);
+    }
+
+    #[test]
+    fn refuses_everybody_when_all_peers_together_exceed_the_global_rate() {
+        let now = SystemTime::now();
+        let mut subject = GossipRateLimiter::new(now);
+        (0..GLOBAL_GOSSIP_BURST).for_each(|index| {
+            let peer_ip = IpAddr::from([10, 0, (index / 256) as u8, (index % 256) as u8]);
+            subject.admit(peer_ip, now).unwrap();
+        });
+
+        let result = subject.admit(peer("1.2.3.4"), now);
+
+        assert_eq!(result, Err(GossipRejection::Overloaded));
+    }
+
+    #[test]
+    fn repeated_abuse_leads_to_escalating_bans() {
+        let now = SystemTime::now();
+        let peer_ip = peer("1.2.3.4");
+        let mut subject = GossipRateLimiter::new(now);
+
+        let first_strike = subject.record_rejection(peer_ip, GossipRejection::Malformed, now);
+        let first_ban = subject.record_rejection(peer_ip, GossipRejection::BadSignature, now);
+        let during_ban = subject.admit(peer_ip, now + Duration::from_secs(299));
+        let after_ban = subject.admit(peer_ip, now + Duration::from_secs(300));
+        let later = now + Duration::from_secs(300);
+        subject.record_rejection(peer_ip, GossipRejection::Malefactor, later);
+        let second_ban = subject.record_rejection(peer_ip, GossipRejection::Malefactor, later);
+
+        assert_eq!(first_strike, None);
+        assert_eq!(first_ban, Some(Duration::from_secs(300)));
+        assert_eq!(during_ban, Err(GossipRejection::WhileBanned));
+        assert_eq!(after_ban, Ok(()));
+        assert_eq!(second_ban, Some(Duration::from_secs(600)));
+    }
+
+    #[test]
+    fn ban_duration_is_capped() {
+        let mut record = PeerGossipRecord::new(SystemTime::now());
+        record.ban_count = 40;
+
+        let result = record.next_ban_duration();
+
+        assert_eq!(result, MAX_BAN_DURATION);
+    }
+
+    #[test]
+    fn penalties_decay_over_time() {
+        let now = SystemTime::now();
+        let peer_ip = peer("1.2.3.4");
+        let mut subject = GossipRateLimiter::new(now);
+        subject.record_rejection(peer_ip, GossipRejection::Malformed, now);
+
+        let result = subject.record_rejection(
+            peer_ip,
+            GossipRejection::Malformed,
+            now + Duration::from_secs(60),
+        );
+
+        assert_eq!(result, None);
+        assert_eq!(subject.peers.get(&peer_ip).unwrap().penalty, 90);
+    }
+
+    #[test]
+    fn rejections_that_are_not_the_peers_fault_carry_no_penalty() {
+        let now = SystemTime::now();
+        let peer_ip = peer("1.2.3.4");
+        let mut subject = GossipRateLimiter::new(now);
+
+        let results = (0..100)
+            .map(|_| subject.record_rejection(peer_ip, GossipRejection::Overloaded, now))
+            .collect::<Vec<_>>();
+
+        assert!(results.iter().all(|result| result.is_none()));
+        assert_eq!(subject.peers.get(&peer_ip).unwrap().penalty, 0);
+    }
+
+    #[test]
+    fn reports_rejection_counts_for_rejected_peers_only() {
+        let now = SystemTime::now();
+        let mut subject = GossipRateLimiter::new(now);
+        subject.admit(peer("3.3.3.3"), now).unwrap();
+        subject.record_rejection(peer("2.2.2.2"), GossipRejection::RateLimited, now);
+        subject.record_rejection(peer("2.2.2.2"), GossipRejection::RateLimited, now);
+        subject.record_rejection(peer("1.1.1.1"), GossipRejection::Malformed, now);
+        subject.record_rejection(peer("1.1.1.1"), GossipRejection::Malefactor, now);
+        subject.record_rejection(peer("1.1.1.1"), GossipRejection::WhileBanned, now);
+        let banned_until = (now + FIRST_BAN_DURATION)
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let result = subject.ui_rejections(now);
+
+        assert_eq!(
+            result,
+            vec![
+                UiGossipRejections {
+                    peer_ip: "1.1.1.1".to_string(),
+                    rate_limited: 0,
+                    overloaded: 0,
+                    while_banned: 1,
+                    malformed: 1,
+                    bad_signature: 0,
+                    malefactor: 1,
+                    ban_count: 1,
+                    banned_until_opt: Some(banned_until),
+                },
+                UiGossipRejections {
+                    peer_ip: "2.2.2.2".to_string(),
+                    rate_limited: 2,
+                    overloaded: 0,
+                    while_banned: 0,
+                    malformed: 0,
+                    bad_signature: 0,
+                    malefactor: 0,
+                    ban_count: 0,
+                    banned_until_opt: None,
+                },
+            ]
+        );
+        let after_ban = subject.ui_rejections(now + FIRST_BAN_DURATION);
+        assert_eq!(after_ban[0].banned_until_opt, None);
+    }
+
+    #[test]
+    fn forgets_idle_peers_only_once_enough_new_peers_have_shown_up() {
+        let now = SystemTime::now();
+        let later = now + Duration::from_secs(10);
+        let mut subject = GossipRateLimiter::new(now);
+        (0..NEW_PEERS_BETWEEN_SWEEPS).for_each(|index| {
+            let peer_ip = IpAddr::from([10, 0, (index / 256) as u8, (index % 256) as u8]);
+            let _ = subject.admit(peer_ip, now);
+        });
+        subject.record_rejection(peer("10.0.0.1"), GossipRejection::RateLimited, now);
+
+        subject.admit(peer("10.0.0.2"), later).unwrap();
+        let before_sweep = subject.peers.len();
+        subject.admit(peer("1.2.3.4"), later).unwrap();
+
+        assert_eq!(before_sweep, NEW_PEERS_BETWEEN_SWEEPS);
+        assert_eq!(subject.peers.len(), 2);
+        assert!(subject.peers.contains_key(&peer("10.0.0.1")));
+        assert!(subject.peers.contains_key(&peer("1.2.3.4")));
+        assert_eq!(subject.new_peers_since_sweep, 1);
+    }
+
+    #[test]
+    fn forgets_the_oldest_peers_whose_bans_are_over_once_too_many_are_tracked() {
+        let now = SystemTime::now();
+        let mut subject = GossipRateLimiter::new(now);
+        let ip_of = |index: usize| IpAddr::from([10, 0, (index / 256) as u8, (index % 256) as u8]);
+        (0..MAX_PEERS_TRACKED).for_each(|index| {
+            let heard_at = now + Duration::from_millis(index as u64);
+            subject.record_rejection(ip_of(index), GossipRejection::RateLimited, heard_at);
+        });
+        subject.record_rejection(ip_of(0), GossipRejection::Malformed, now);
+        subject.record_rejection(ip_of(0), GossipRejection::Malformed, now);
+
+        let _ = subject.admit(peer("1.2.3.4"), now + Duration::from_secs(60));
+
+        assert_eq!(
+            subject.peers.len(),
+            MAX_PEERS_TRACKED - NEW_PEERS_BETWEEN_SWEEPS + 1
+        );
+        assert!(subject.peers.contains_key(&ip_of(0)));
+        assert!(!subject.peers.contains_key(&ip_of(1)));
+        assert!(!subject.peers.contains_key(&ip_of(NEW_PEERS_BETWEEN_SWEEPS)));
+        assert!(subject
+            .peers
+            .contains_key(&ip_of(NEW_PEERS_BETWEEN_SWEEPS + 1)));
+        assert!(subject.peers.contains_key(&peer("1.2.3.4")));
+    }
+}
@@ -7,13 +7,14 @@ use crate::neighborhood::node_location::get_node_location;
 use crate::neighborhood::node_record::{NodeRecord, NodeRecordError, NodeRecordInputs};
 use crate::sub_lib::cryptde::CryptDE;
 use crate::sub_lib::cryptde::PublicKey;
-use crate::sub_lib::neighborhood::NeighborhoodMode;
+use crate::sub_lib::neighborhood::{NeighborhoodMode, RETIRED_KEY_GRACE_PERIOD_SECS};
 use crate::sub_lib::node_addr::NodeAddr;
 use crate::sub_lib::utils::time_t_timestamp;
 use crate::sub_lib::wallet::Wallet;
 use itertools::Itertools;
 use masq_lib::logger::Logger;
 use masq_lib::utils::ExpectValue;
+use std::collections::hash_map::Entry;
 use std::collections::HashSet;
 use std::collections::{BTreeSet, HashMap};
 use std::fmt::Debug;
@@ -31,6 +32,8 @@ pub struct NeighborhoodDatabase {
     this_node: PublicKey,
     by_public_key: HashMap<PublicKey, NodeRecord>,
     by_ip_addr: HashMap<IpAddr, PublicKey>,
+    // Keys given up in key rotations, with the keys that replaced them and when
+    retired_keys: HashMap<PublicKey, (PublicKey, u32)>,
     logger: Logger,
 }
 
@@ -50,6 +53,7 @@ impl NeighborhoodDatabase {
             this_node: cryptde.public_key().clone(),
             by_public_key: HashMap::new(),
             by_ip_addr: HashMap::new(),
+            retired_keys: HashMap::new(),
             logger: Logger::new("NeighborhoodDatabase"),
         };
         let location_opt = match neighborhood_mode.node_addr_opt() {
@@ -256,10 +260,42 @@ impl NeighborhoodDatabase {
     }
 
     pub fn to_dot_graph(&self) -> String {
-        let renderables = self.to_dot_renderables();
+        let renderables = self.to_dot_renderables(&self.keys());
         render_dot_graph(renderables)
     }
 
+    // Renders only the selected Nodes, and only those of their edges that lead to other selected
+    // Nodes or to Nodes that are not in the database at all
+    pub fn to_dot_graph_of(&self, selected: &HashSet<&PublicKey>) -> String {
+        let renderables = self.to_dot_renderables(selected);
+        render_dot_graph(renderables)
+    }
+
+    // Number of hops along half-neighbor edges from the root to each Node it can reach
+    pub fn distances_from_root(&self) -> HashMap<PublicKey, u32> {
+        let mut distances: HashMap<PublicKey, u32> = HashMap::new();
+        let mut frontier: Vec<PublicKey> = vec![self.root().public_key().clone()];
+        distances.insert(self.root().public_key().clone(), 0);
+        let mut distance = 0;
+        while !frontier.is_empty() {
+            distance += 1;
+            let mut next_frontier = vec![];
+            for node in frontier.iter().flat_map(|k| self.node_by_key(k)) {
+                for key in node.half_neighbor_keys() {
+                    if self.node_by_key(key).is_none() {
+                        continue;
+                    }
+                    if let Entry::Vacant(entry) = distances.entry(key.clone()) {
+                        entry.insert(distance);
+                        next_frontier.push(key.clone());
+                    }
+                }
+            }
+            frontier = next_frontier;
+        }
+        distances
+    }
+
     pub fn referenced_node_keys(&self) -> BTreeSet<PublicKey> {
         let mut keys: BTreeSet<PublicKey> = self
             .keys()
@@ -272,6 +308,72 @@ impl NeighborhoodDatabase {
         keys
     }
 
+    // Moves a Node, with its neighborships, from the key pair it has just given up to its new one.
+    // Neighborships recorded by other Nodes are moved too, until those Nodes Gossip about it
+    // themselves.
+    pub fn rekey_node(
+        &mut self,
+        old_key: &PublicKey,
+        new_key: &PublicKey,
+    ) -> Result<(), NeighborhoodDatabaseError> {
+        self.check_for_collision(new_key)?;
+        let mut node_record = match self.by_public_key.remove(old_key) {
+            Some(node_record) => node_record,
+            None => return Err(NodeKeyNotFound(old_key.clone())),
+        };
+        node_record.inner.public_key = new_key.clone();
+        self.add_arbitrary_node(node_record);
+        self.nodes_mut().into_iter().for_each(|node_record| {
+            if node_record.inner.neighbors.remove(old_key) {
+                node_record.inner.neighbors.insert(new_key.clone());
+            }
+        });
+        if &self.this_node == old_key {
+            self.this_node = new_key.clone();
+        }
+        self.retired_keys
+            .values_mut()
+            .filter(|(successor, _)| successor == old_key)
+            .for_each(|(successor, _)| *successor = new_key.clone());
+        self.retired_keys
+            .insert(old_key.clone(), (new_key.clone(), time_t_timestamp()));
+        Ok(())
+    }
+
+    // The key that replaced a retired key, if it was retired less than the grace period ago
+    pub fn successor_key(&self, retired_key: &PublicKey) -> Option<&PublicKey> {
+        self.retired_keys
+            .get(retired_key)
+            .map(|(successor, _)| successor)
+    }
+
+    pub fn expire_retired_keys(&mut self) {
+        let deadline = time_t_timestamp() - RETIRED_KEY_GRACE_PERIOD_SECS;
+        let expired: BTreeSet<PublicKey> = self
+            .retired_keys
+            .iter()
+            .filter(|(_, (_, retired_at))| *retired_at <= deadline)
+            .map(|(key, _)| key.clone())
+            .collect();
+        if expired.is_empty() {
+            return;
+        }
+        expired.iter().for_each(|key| {
+            self.retired_keys.remove(key);
+        });
+        let root_handover_expired = match self.root().key_handover_opt() {
+            Some(key_handover) => expired.contains(&key_handover.previous_public_key),
+            None => false,
+        };
+        if root_handover_expired {
+            self.root_mut().set_key_handover(None);
+        }
+        debug!(
+            self.logger,
+            "No longer honoring retired keys: {:?}", expired
+        );
+    }
+
     pub fn new_public_ip(&mut self, public_ip: IpAddr) {
         let record = self.root_mut();
         let public_key = record.public_key().clone();
@@ -283,24 +385,27 @@ impl NeighborhoodDatabase {
         self.by_ip_addr.insert(public_ip, public_key);
     }
 
-    fn to_dot_renderables(&self) -> Vec<Box<dyn DotRenderable>> {
+    fn to_dot_renderables(&self, selected: &HashSet<&PublicKey>) -> Vec<Box<dyn DotRenderable>> {
         let mut mentioned: HashSet<PublicKey> = HashSet::new();
         let mut present: HashSet<PublicKey> = HashSet::new();
         let mut node_renderables: Vec<NodeRenderable> = vec![];
         let mut edge_renderables: Vec<EdgeRenderable> = vec![];
-        self.keys()
-            .into_iter()
-            .map(|k| self.node_by_key(k).expect("Node magically disappeared"))
+        selected
+            .iter()
+            .flat_map(|k| self.node_by_key(k))
             .for_each(|nr| {
                 present.insert(nr.public_key().clone());
                 let public_key = nr.public_key();
-                nr.half_neighbor_keys().into_iter().for_each(|k| {
-                    mentioned.insert(k.clone());
-                    edge_renderables.push(EdgeRenderable {
-                        from: public_key.clone(),
-                        to: k.clone(),
-                    })
-                });
+                nr.half_neighbor_keys()
+                    .into_iter()
+                    .filter(|k| selected.contains(k) || self.node_by_key(k).is_none())
+                    .for_each(|k| {
+                        mentioned.insert(k.clone());
+                        edge_renderables.push(EdgeRenderable {
+                            from: public_key.clone(),
+                            to: k.clone(),
+                        })
+                    });
                 let country_code = match &nr.inner.country_code_opt {
                     Some(cc) => cc.clone(),
                     None => "ZZ".to_string(),
@@ -385,12 +490,14 @@ pub enum NeighborhoodDatabaseError {
 mod tests {
     use super::*;
     use crate::neighborhood::node_location::NodeLocation;
+    use crate::neighborhood::node_record::KeyHandover;
+    use crate::sub_lib::cryptde::CryptData;
     use crate::sub_lib::cryptde_null::CryptDENull;
     use crate::sub_lib::utils::time_t_timestamp;
-    use crate::test_utils::assert_string_contains;
     use crate::test_utils::neighborhood_test_utils::{
         db_from_node, make_node_record, make_node_record_cc, make_segmented_ip, make_segments,
     };
+    use crate::test_utils::{assert_string_contains, vec_to_set};
     use masq_lib::constants::DEFAULT_CHAIN;
     use masq_lib::test_utils::utils::TEST_DEFAULT_CHAIN;
     use std::iter::FromIterator;
@@ -856,6 +963,62 @@ mod tests {
         assert_string_contains(&result, "\"BAUGBw\" -> \"AQIDBA\";");
     }
 
+    #[test]
+    fn selected_nodes_can_be_pretty_printed_to_dot_format() {
+        let this_node = make_node_record_cc(1234, true, "AU"); // AQIDBA
+        let node_one = make_node_record_cc(2345, true, "FR"); // AgMEBQ
+        let node_two = make_node_record_cc(3456, true, "CN"); // AwQFBg
+        let absent_key = PublicKey::new(&[9, 9, 9, 9]); // CQkJCQ
+        let mut subject = db_from_node(&this_node);
+        subject.add_node(node_one.clone()).unwrap();
+        subject.add_node(node_two.clone()).unwrap();
+        subject.add_arbitrary_full_neighbor(this_node.public_key(), node_one.public_key());
+        subject.add_arbitrary_full_neighbor(node_one.public_key(), node_two.public_key());
+        subject.add_arbitrary_half_neighbor(this_node.public_key(), &absent_key);
+        let selected: HashSet<&PublicKey> = vec![this_node.public_key(), node_one.public_key()]
+            .into_iter()
+            .collect();
+
+        let result = subject.to_dot_graph_of(&selected);
+
+        assert_eq!(result.matches("->").count(), 3);
+        assert_string_contains(&result, "\"AQIDBA\" -> \"AgMEBQ\";");
+        assert_string_contains(&result, "\"AgMEBQ\" -> \"AQIDBA\";");
+        assert_string_contains(&result, "\"AQIDBA\" -> \"CQkJCQ\";");
+        assert_string_contains(&result, "\"CQkJCQ\" [label=\"CQkJCQ\"] [shape=none];");
+        assert_eq!(result.contains("AwQFBg"), false);
+    }
+
+    #[test]
+    fn distances_from_root_follow_half_neighbor_edges_out_of_the_root() {
+        let this_node = make_node_record(1234, true);
+        let one_hop = make_node_record(2345, true);
+        let two_hops = make_node_record(3456, true);
+        let unreachable = make_node_record(4567, true);
+        let mut subject = db_from_node(&this_node);
+        subject.add_node(one_hop.clone()).unwrap();
+        subject.add_node(two_hops.clone()).unwrap();
+        subject.add_node(unreachable.clone()).unwrap();
+        subject.add_arbitrary_full_neighbor(this_node.public_key(), one_hop.public_key());
+        subject.add_arbitrary_half_neighbor(one_hop.public_key(), two_hops.public_key());
+        subject.add_arbitrary_half_neighbor(two_hops.public_key(), this_node.public_key());
+        subject.add_arbitrary_half_neighbor(unreachable.public_key(), this_node.public_key());
+        subject.add_arbitrary_half_neighbor(this_node.public_key(), &PublicKey::new(&[9, 9]));
+
+        let result = subject.distances_from_root();
+
+        assert_eq!(
+            result,
+            vec![
+                (this_node.public_key().clone(), 0),
+                (one_hop.public_key().clone(), 1),
+                (two_hops.public_key().clone(), 2),
+            ]
+            .into_iter()
+            .collect::<HashMap<PublicKey, u32>>()
+        );
+    }
+
     #[test]
     fn new_public_ip_replaces_ip_address_and_nothing_else() {
         let this_node = make_node_record(1234, true);
@@ -1016,4 +1179,137 @@ mod tests {
         assert!(subject.node_by_key(referenced_by_dead).is_some());
         assert!(subject.node_by_key(dead).is_none());
     }
+
+    #[test]
+    fn rekey_node_moves_a_node_and_its_neighborships_to_the_new_key() {
+        let root_node = make_node_record(1234, true);
+        let rekeyed_node = make_node_record(2345, true);
+        let mut subject: NeighborhoodDatabase = db_from_node(&root_node);
+        let old_key = subject.add_node(rekeyed_node.clone()).unwrap();
+        let other_key = subject.add_node(make_node_record(3456, false)).unwrap();
+        subject.add_arbitrary_full_neighbor(root_node.public_key(), &old_key);
+        subject.add_arbitrary_half_neighbor(&other_key, &old_key);
+        subject.add_arbitrary_half_neighbor(&old_key, &other_key);
+        let new_key = PublicKey::new(&[9, 8, 7, 6]);
+        let before = time_t_timestamp();
+
+        let result = subject.rekey_node(&old_key, &new_key);
+
+        let after = time_t_timestamp();
+        assert_eq!(result, Ok(()));
+        assert_eq!(subject.node_by_key(&old_key), None);
+        let moved = subject.node_by_key(&new_key).unwrap();
+        assert_eq!(moved.public_key(), &new_key);
+        assert_eq!(moved.earning_wallet(), rekeyed_node.earning_wallet());
+        assert_eq!(
+            moved.half_neighbor_keys(),
+            vec_to_set(vec![root_node.public_key(), &other_key])
+        );
+        assert_eq!(
+            subject
+                .node_by_ip(&rekeyed_node.node_addr_opt().unwrap().ip_addr())
+                .unwrap()
+                .public_key(),
+            &new_key
+        );
+        assert!(subject.has_full_neighbor(root_node.public_key(), &new_key));
+        assert!(subject.has_half_neighbor(&other_key, &new_key));
+        assert!(!subject.has_half_neighbor(&other_key, &old_key));
+        assert_eq!(subject.successor_key(&old_key), Some(&new_key));
+        let (_, retired_at) = subject.retired_keys.get(&old_key).unwrap();
+        assert!(before <= *retired_at && *retired_at <= after);
+    }
+
+    #[test]
+    fn rekey_node_can_rekey_the_root() {
+        let root_node = make_node_record(1234, true);
+        let mut subject: NeighborhoodDatabase = db_from_node(&root_node);
+        let new_key = PublicKey::new(&[9, 8, 7, 6]);
+
+        let result = subject.rekey_node(root_node.public_key(), &new_key);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(subject.root_key(), &new_key);
+        assert_eq!(subject.root().public_key(), &new_key);
+    }
+
+    #[test]
+    fn rekey_node_keeps_earlier_retired_keys_pointing_at_the_latest_key() {
+        let root_node = make_node_record(1234, true);
+        let mut subject: NeighborhoodDatabase = db_from_node(&root_node);
+        let first_key = subject.add_node(make_node_record(2345, true)).unwrap();
+        let second_key = PublicKey::new(&[9, 8, 7, 6]);
+        let third_key = PublicKey::new(&[5, 4, 3, 2]);
+        subject.rekey_node(&first_key, &second_key).unwrap();
+
+        subject.rekey_node(&second_key, &third_key).unwrap();
+
+        assert_eq!(subject.successor_key(&first_key), Some(&third_key));
+        assert_eq!(subject.successor_key(&second_key), Some(&third_key));
+        assert_eq!(subject.successor_key(&third_key), None);
+    }
+
+    #[test]
+    fn rekey_node_complains_about_unknown_and_colliding_keys() {
+        let root_node = make_node_record(1234, true);
+        let mut subject: NeighborhoodDatabase = db_from_node(&root_node);
+        let existing_key = subject.add_node(make_node_record(2345, true)).unwrap();
+        let unknown_key = PublicKey::new(&[9, 8, 7, 6]);
+
+        let unknown_result = subject.rekey_node(&unknown_key, &PublicKey::new(&[5, 4, 3, 2]));
+        let collision_result = subject.rekey_node(root_node.public_key(), &existing_key);
+
+        assert_eq!(unknown_result, Err(NodeKeyNotFound(unknown_key)));
+        assert_eq!(
+            collision_result,
+            Err(NeighborhoodDatabaseError::NodeKeyCollision(existing_key))
+        );
+        assert_eq!(subject.root_key(), root_node.public_key());
+    }
+
+    #[test]
+    fn expire_retired_keys_forgets_old_retirements_and_the_root_handover() {
+        let root_node = make_node_record(1234, true);
+        let mut subject: NeighborhoodDatabase = db_from_node(&root_node);
+        let old_root_key = root_node.public_key().clone();
+        let new_root_key = PublicKey::new(&[9, 8, 7, 6]);
+        let recent_key = subject.add_node(make_node_record(2345, true)).unwrap();
+        let recent_successor = PublicKey::new(&[5, 4, 3, 2]);
+        subject.rekey_node(&old_root_key, &new_root_key).unwrap();
+        subject.root_mut().set_key_handover(Some(KeyHandover {
+            previous_public_key: old_root_key.clone(),
+            signature: CryptData::new(b"signature"),
+        }));
+        subject.rekey_node(&recent_key, &recent_successor).unwrap();
+        subject.retired_keys.get_mut(&old_root_key).unwrap().1 =
+            time_t_timestamp() - RETIRED_KEY_GRACE_PERIOD_SECS - 1;
+
+        subject.expire_retired_keys();
+
+        assert_eq!(subject.successor_key(&old_root_key), None);
+        assert_eq!(subject.successor_key(&recent_key), Some(&recent_successor));
+        assert_eq!(subject.root().key_handover_opt(), None);
+    }
+
+    #[test]
+    fn expire_retired_keys_leaves_a_recent_root_handover_alone() {
+        let root_node = make_node_record(1234, true);
+        let mut subject: NeighborhoodDatabase = db_from_node(&root_node);
+        let old_root_key = root_node.public_key().clone();
+        let key_handover = KeyHandover {
+            previous_public_key: old_root_key.clone(),
+            signature: CryptData::new(b"signature"),
+        };
+        subject
+            .rekey_node(&old_root_key, &PublicKey::new(&[9, 8, 7, 6]))
+            .unwrap();
+        subject
+            .root_mut()
+            .set_key_handover(Some(key_handover.clone()));
+
+        subject.expire_retired_keys();
+
+        assert!(subject.successor_key(&old_root_key).is_some());
+        assert_eq!(subject.root().key_handover_opt(), Some(&key_handover));
+    }
 }
@@ -1,8 +1,13 @@
 // Copyright (c) 2024, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 
 use ip_country_lib;
-use ip_country_lib::country_finder::{CountryCodeFinder, COUNTRY_CODE_FINDER};
+use ip_country_lib::country_data_file::{CountryDataFile, COUNTRY_DATA_FILE_NAME};
+use ip_country_lib::country_finder::{
+    active_country_data, find_country_code, install_country_data, uninstall_country_data,
+    ActiveCountryData,
+};
 use std::net::IpAddr;
+use std::path::{Path, PathBuf};
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct NodeLocation {
@@ -12,18 +17,65 @@ pub struct NodeLocation {
 pub fn get_node_location(ip_opt: Option<IpAddr>) -> Option<NodeLocation> {
     match ip_opt {
         Some(ip_addr) => {
-            let country_opt = CountryCodeFinder::find_country(&COUNTRY_CODE_FINDER, ip_addr);
-            country_opt.map(|country| NodeLocation {
-                country_code: country.iso3166.clone(),
-            })
+            find_country_code(ip_addr).map(|country_code| NodeLocation { country_code })
         }
         None => None,
     }
 }
 
+// Country data can be refreshed by dropping a file made with `ip_country --data-file` into the
+// data directory; without one, the data compiled into the Node is used.
+pub trait CountryDataLoader: Send {
+    fn load(&self) -> Result<ActiveCountryData, String>;
+    fn active(&self) -> ActiveCountryData;
+    fn data_file_path(&self) -> PathBuf;
+}
+
+pub struct CountryDataLoaderReal {
+    data_file_path: PathBuf,
+}
+
+impl CountryDataLoader for CountryDataLoaderReal {
+    // A missing file means the compiled-in data; an unusable one leaves the active data in place
+    fn load(&self) -> Result<ActiveCountryData, String> {
+        match CountryDataFile::read(&self.data_file_path)? {
+            Some(data_file) => install_country_data(data_file).map_err(|e| {
+                format!(
+                    "{} is not a usable country database: {}",
+                    self.data_file_path.display(),
+                    e
+                )
+            }),
+            None => Ok(uninstall_country_data()),
+        }
+    }
+
+    fn active(&self) -> ActiveCountryData {
+        active_country_data()
+    }
+
+    fn data_file_path(&self) -> PathBuf {
+        self.data_file_path.clone()
+    }
+}
+
+impl CountryDataLoaderReal {
+    pub fn new(data_directory: &Path) -> Self {
+        Self {
+            data_file_path: data_directory.join(COUNTRY_DATA_FILE_NAME),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::neighborhood::node_location::get_node_location;
+    use crate::neighborhood::node_location::{
+        get_node_location, CountryDataLoader, CountryDataLoaderReal,
+    };
+    use ip_country_lib::country_data_file::{CountryData, CountryDataFile, COUNTRY_DATA_FILE_NAME};
+    use ip_country_lib::dbip_country;
+    use masq_lib::test_utils::utils::ensure_node_home_directory_exists;
+    use std::fs;
     use std::net::{IpAddr, Ipv4Addr};
 
     #[test]
@@ -32,4 +84,57 @@ mod tests {
 
         assert!(node_location.is_some());
     }
+
+    // The data file holds the compiled-in blocks, so lookups elsewhere don't change while it's loaded
+    #[test]
+    fn country_data_loader_real_loads_data_file_and_falls_back_to_compiled_in_data() {
+        let data_dir = ensure_node_home_directory_exists(
+            "node_location",
+            "country_data_loader_real_loads_data_file_and_falls_back_to_compiled_in_data",
+        );
+        let data_file_path = data_dir.join(COUNTRY_DATA_FILE_NAME);
+        let data_file = CountryDataFile {
+            version: "2024-06".to_string(),
+            countries: dbip_country::COUNTRIES.clone(),
+            ipv4: CountryData {
+                data: dbip_country::ipv4_country_data(),
+                block_count: dbip_country::ipv4_country_block_count(),
+            },
+            ipv6: CountryData {
+                data: dbip_country::ipv6_country_data(),
+                block_count: dbip_country::ipv6_country_block_count(),
+            },
+        };
+        let subject = CountryDataLoaderReal::new(&data_dir);
+
+        fs::write(&data_file_path, data_file.to_bytes()).unwrap();
+        let loaded_result = subject.load();
+        let loaded_active = subject.active();
+        fs::write(&data_file_path, b"booga").unwrap();
+        let bad_result = subject.load();
+        let bad_active = subject.active();
+        fs::remove_file(&data_file_path).unwrap();
+        let missing_result = subject.load();
+
+        assert_eq!(subject.data_file_path(), data_file_path);
+        assert_eq!(
+            loaded_result.unwrap().version_opt,
+            Some("2024-06".to_string())
+        );
+        assert_eq!(loaded_active.version_opt, Some("2024-06".to_string()));
+        assert_eq!(
+            bad_result,
+            Err(format!(
+                "{} is not a usable country database: it doesn't start with the country-database signature",
+                data_file_path.display()
+            ))
+        );
+        assert_eq!(bad_active.version_opt, Some("2024-06".to_string()));
+        let missing_active = missing_result.unwrap();
+        assert_eq!(missing_active.version_opt, None);
+        assert_eq!(
+            missing_active.ipv4_block_count,
+            dbip_country::ipv4_country_block_count()
+        );
+    }
 }
@@ -6,6 +6,7 @@ use crate::neighborhood::gossip::{
 use crate::neighborhood::neighborhood_database::{NeighborhoodDatabase, NeighborhoodDatabaseError};
 use crate::neighborhood::node_location::{get_node_location, NodeLocation};
 use crate::sub_lib::cryptde::{CryptDE, CryptData, PlainData, PublicKey};
+use crate::sub_lib::earning_wallet_shares::EarningWalletShares;
 use crate::sub_lib::neighborhood::{NodeDescriptor, RatePack};
 use crate::sub_lib::node_addr::NodeAddr;
 use crate::sub_lib::utils::time_t_timestamp;
@@ -28,6 +29,14 @@ pub struct NodeRecordInner_0v1 {
     pub routes_data: bool,
     pub version: u32,
     pub country_code_opt: Option<String>,
+    #[serde(default, skip_serializing_if = "EarningWalletShares::is_empty")]
+    pub earning_wallet_shares: EarningWalletShares,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_handover_opt: Option<KeyHandover>,
+    // Short-lived key that the hops of Routes through this Node should be encrypted to instead
+    // of its public key. Nodes that don't know about it keep using the public key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_key_opt: Option<PublicKey>,
 }
 
 impl TryFrom<GossipNodeRecord> for NodeRecordInner_0v1 {
@@ -54,6 +63,35 @@ pub enum NodeRecordError {
     SelfNeighborAttempt(PublicKey),
 }
 
+// Published by a Node that has just rotated its key pair. The whole NodeRecord is signed with the
+// new key as usual; this adds the old key's signature over the new public key, so that Nodes that
+// knew the old key can tell that the new one belongs to the same Node.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct KeyHandover {
+    pub previous_public_key: PublicKey,
+    pub signature: CryptData,
+}
+
+impl KeyHandover {
+    pub fn new(previous_cryptde: &dyn CryptDE, new_public_key: &PublicKey) -> KeyHandover {
+        KeyHandover {
+            previous_public_key: previous_cryptde.public_key().clone(),
+            signature: previous_cryptde
+                .sign(&PlainData::from(new_public_key.as_slice()))
+                .expect("Couldn't sign new public key"),
+        }
+    }
+
+    pub fn is_valid(&self, cryptde: &dyn CryptDE, new_public_key: &PublicKey) -> bool {
+        self.previous_public_key != *new_public_key
+            && cryptde.verify_signature(
+                &PlainData::from(new_public_key.as_slice()),
+                &self.signature,
+                &self.previous_public_key,
+            )
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct NodeRecord {
     pub inner: NodeRecordInner_0v1,
@@ -93,6 +131,9 @@ impl NodeRecord {
                 neighbors: BTreeSet::new(),
                 version: node_record_inputs.version,
                 country_code_opt: country_opt,
+                earning_wallet_shares: EarningWalletShares::default(),
+                key_handover_opt: None,
+                session_key_opt: None,
             },
             signed_gossip: PlainData::new(&[]),
             signature: CryptData::new(&[]),
@@ -256,10 +297,87 @@ impl NodeRecord {
         }
     }
 
+    pub fn earning_wallet_shares(&self) -> &EarningWalletShares {
+        &self.inner.earning_wallet_shares
+    }
+
+    pub fn set_earning_wallet_shares(
+        &mut self,
+        earning_wallet_shares: EarningWalletShares,
+    ) -> bool {
+        if self.inner.earning_wallet_shares == earning_wallet_shares {
+            false
+        } else {
+            self.inner.earning_wallet_shares = earning_wallet_shares;
+            true
+        }
+    }
+
+    pub fn key_handover_opt(&self) -> Option<&KeyHandover> {
+        self.inner.key_handover_opt.as_ref()
+    }
+
+    pub fn set_key_handover(&mut self, key_handover_opt: Option<KeyHandover>) {
+        self.inner.key_handover_opt = key_handover_opt;
+    }
+
+    pub fn session_key_opt(&self) -> Option<&PublicKey> {
+        self.inner.session_key_opt.as_ref()
+    }
+
+    pub fn set_session_key(&mut self, session_key: PublicKey) -> bool {
+        if self.inner.session_key_opt.as_ref() == Some(&session_key) {
+            false
+        } else {
+            self.inner.session_key_opt = Some(session_key);
+            true
+        }
+    }
+
     pub fn rate_pack(&self) -> &RatePack {
         &self.inner.rate_pack
     }
 
+    pub fn set_rate_pack(&mut self, rate_pack: RatePack) -> bool {
+        if self.inner.rate_pack == rate_pack {
+            false
+        } else {
+            self.inner.rate_pack = rate_pack;
+            true
+        }
+    }
+
+    // The declared country is only trusted as far as the country data agrees with it
+    pub fn verified_country_code_opt(&self) -> Option<&String> {
+        match self.metadata.country_mismatch_opt {
+            Some(_) => None,
+            None => self.inner.country_code_opt.as_ref(),
+        }
+    }
+
+    // Returns true if the verdict changed
+    pub fn verify_country(&mut self) -> bool {
+        let observed_location_opt = get_node_location(
+            self.metadata
+                .node_addr_opt
+                .as_ref()
+                .map(|node_addr| node_addr.ip_addr()),
+        );
+        let country_mismatch_opt = match (&self.inner.country_code_opt, &observed_location_opt) {
+            (Some(declared), Some(observed)) if declared != &observed.country_code => {
+                Some(CountryMismatch {
+                    declared: declared.clone(),
+                    observed: observed.country_code.clone(),
+                })
+            }
+            _ => None,
+        };
+        self.metadata.node_location_opt = observed_location_opt;
+        let changed = self.metadata.country_mismatch_opt != country_mismatch_opt;
+        self.metadata.country_mismatch_opt = country_mismatch_opt;
+        changed
+    }
+
     pub fn update(&mut self, agr: AccessibleGossipRecord) -> Result<(), String> {
         if &agr.inner.public_key != self.public_key() {
             return Err(format!(
@@ -268,7 +386,8 @@ impl NodeRecord {
                 agr.inner.public_key
             ));
         }
-        if &agr.inner.rate_pack != self.rate_pack() {
+        // A Node may change its rate pack at runtime, but only in a new version of its record
+        if &agr.inner.rate_pack != self.rate_pack() && agr.inner.version <= self.version() {
             return Err(format!(
                 "Updating a NodeRecord must not change its rate pack: {} -> {}",
                 self.rate_pack(),
@@ -295,7 +414,6 @@ impl NodeRecord {
     }
 }
 
-//TODO #479 check constructed metadata contains proper country_code
 impl From<AccessibleGossipRecord> for NodeRecord {
     fn from(agr: AccessibleGossipRecord) -> Self {
         let ip_add_opt = agr
@@ -353,8 +471,9 @@ pub struct NodeRecordMetadata {
     // to false, we do not consider the undesirability of countries other than those selected for exit.
     // Therefore, we use a value of 0 for exit nodes in countries that are not considered for exit.
     pub country_undesirability: u32,
+    // Set when the country a full neighbor declares disagrees with the one its observed IP maps to
+    pub country_mismatch_opt: Option<CountryMismatch>,
     //TODO introduce scores for latency #582 and reliability #583
-    //TODO #479 introduce check for node_location_opt, to verify full neighbors country code (we know his IP, so we can verify it)
 }
 
 impl NodeRecordMetadata {
@@ -365,16 +484,24 @@ impl NodeRecordMetadata {
             unreachable_hosts: Default::default(),
             node_location_opt: None,
             country_undesirability: 0u32,
+            country_mismatch_opt: None,
         }
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CountryMismatch {
+    pub declared: String,
+    pub observed: String,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::bootstrapper::CryptDEPair;
     use crate::neighborhood::gossip::GossipBuilder;
     use crate::sub_lib::cryptde_null::CryptDENull;
+    use crate::sub_lib::earning_wallet_shares::WalletShare;
     use crate::sub_lib::neighborhood::ZERO_RATE_PACK;
     use crate::test_utils::make_wallet;
     use crate::test_utils::neighborhood_test_utils::{db_from_node, make_node_record};
@@ -427,6 +554,59 @@ mod tests {
         assert_eq!(actual_node_record, expected_node_record);
     }
 
+    #[test]
+    fn verify_country_accepts_a_declared_country_that_matches_the_observed_one() {
+        let mut subject = make_node_record(1234, true);
+        let derived_cc = subject.inner.country_code_opt.clone().unwrap();
+
+        let result = subject.verify_country();
+
+        assert_eq!(result, false);
+        assert_eq!(subject.metadata.country_mismatch_opt, None);
+        assert_eq!(subject.verified_country_code_opt(), Some(&derived_cc));
+    }
+
+    #[test]
+    fn verify_country_flags_and_then_clears_a_mismatch() {
+        let mut subject = make_node_record(1234, true);
+        let derived_cc = subject.inner.country_code_opt.clone().unwrap();
+        let declared_cc = if derived_cc == "ZW" { "ZM" } else { "ZW" }.to_string();
+        subject.inner.country_code_opt = Some(declared_cc.clone());
+
+        let flagged = subject.verify_country();
+        let flagged_again = subject.verify_country();
+
+        assert_eq!(flagged, true);
+        assert_eq!(flagged_again, false);
+        assert_eq!(
+            subject.metadata.country_mismatch_opt,
+            Some(CountryMismatch {
+                declared: declared_cc,
+                observed: derived_cc.clone(),
+            })
+        );
+        assert_eq!(subject.verified_country_code_opt(), None);
+        subject.inner.country_code_opt = Some(derived_cc.clone());
+
+        let cleared = subject.verify_country();
+
+        assert_eq!(cleared, true);
+        assert_eq!(subject.metadata.country_mismatch_opt, None);
+        assert_eq!(subject.verified_country_code_opt(), Some(&derived_cc));
+    }
+
+    #[test]
+    fn verify_country_does_not_flag_a_node_without_an_observed_address() {
+        let mut subject = make_node_record(1234, false);
+        subject.inner.country_code_opt = Some("ZW".to_string());
+
+        let result = subject.verify_country();
+
+        assert_eq!(result, false);
+        assert_eq!(subject.metadata.country_mismatch_opt, None);
+        assert_eq!(subject.verified_country_code_opt(), Some(&"ZW".to_string()));
+    }
+
     #[test]
     fn set_node_addr_works_once_but_not_twice() {
         let mut subject = make_node_record(1234, false);
@@ -830,6 +1010,110 @@ mod tests {
         );
     }
 
+    #[test]
+    fn set_earning_wallet_shares_reports_whether_the_shares_changed() {
+        let mut this_node = make_node_record(1234, true);
+        let shares = EarningWalletShares::new(vec![WalletShare {
+            wallet: make_wallet("share"),
+            percentage: 25,
+        }])
+        .unwrap();
+        assert!(this_node.earning_wallet_shares().is_empty());
+
+        assert!(this_node.set_earning_wallet_shares(shares.clone()));
+        assert!(!this_node.set_earning_wallet_shares(shares.clone()));
+
+        assert_eq!(this_node.earning_wallet_shares(), &shares);
+    }
+
+    #[test]
+    fn earning_wallet_shares_survive_gossip_serialization() {
+        let mut this_node = make_node_record(1234, true);
+        let shares = EarningWalletShares::new(vec![WalletShare {
+            wallet: make_wallet("share"),
+            percentage: 25,
+        }])
+        .unwrap();
+        this_node.set_earning_wallet_shares(shares.clone());
+        this_node.regenerate_signed_gossip(CRYPTDE_PAIR.main.as_ref());
+        let gnr = GossipNodeRecord::from(this_node);
+
+        let result = NodeRecordInner_0v1::try_from(gnr).unwrap();
+
+        assert_eq!(result.earning_wallet_shares, shares);
+    }
+
+    #[test]
+    fn key_handover_is_valid_only_for_the_key_it_hands_over_to() {
+        let previous_cryptde =
+            CryptDENull::from(&PublicKey::new(&[1, 2, 3, 4]), TEST_DEFAULT_CHAIN);
+        let new_public_key = PublicKey::new(&[5, 6, 7, 8]);
+        let other_public_key = PublicKey::new(&[9, 10, 11, 12]);
+
+        let subject = KeyHandover::new(&previous_cryptde, &new_public_key);
+
+        let cryptde = CRYPTDE_PAIR.main.as_ref();
+        assert_eq!(&subject.previous_public_key, previous_cryptde.public_key());
+        assert_eq!(subject.is_valid(cryptde, &new_public_key), true);
+        assert_eq!(subject.is_valid(cryptde, &other_public_key), false);
+    }
+
+    #[test]
+    fn key_handover_is_invalid_when_signed_by_some_other_key() {
+        let previous_cryptde =
+            CryptDENull::from(&PublicKey::new(&[1, 2, 3, 4]), TEST_DEFAULT_CHAIN);
+        let impostor_cryptde =
+            CryptDENull::from(&PublicKey::new(&[4, 3, 2, 1]), TEST_DEFAULT_CHAIN);
+        let new_public_key = PublicKey::new(&[5, 6, 7, 8]);
+        let mut subject = KeyHandover::new(&impostor_cryptde, &new_public_key);
+        subject.previous_public_key = previous_cryptde.public_key().clone();
+
+        let result = subject.is_valid(CRYPTDE_PAIR.main.as_ref(), &new_public_key);
+
+        assert_eq!(result, false);
+    }
+
+    #[test]
+    fn key_handover_survives_gossip_serialization() {
+        let previous_cryptde =
+            CryptDENull::from(&PublicKey::new(&[9, 8, 7, 6]), TEST_DEFAULT_CHAIN);
+        let mut this_node = make_node_record(1234, true);
+        let key_handover = KeyHandover::new(&previous_cryptde, this_node.public_key());
+        this_node.set_key_handover(Some(key_handover.clone()));
+        this_node.regenerate_signed_gossip(CRYPTDE_PAIR.main.as_ref());
+        let gnr = GossipNodeRecord::from(this_node);
+
+        let result = NodeRecordInner_0v1::try_from(gnr).unwrap();
+
+        assert_eq!(result.key_handover_opt, Some(key_handover));
+    }
+
+    #[test]
+    fn set_session_key_reports_whether_the_key_changed() {
+        let mut subject = make_node_record(1234, true);
+        let session_key = PublicKey::new(&[5, 6, 7, 8]);
+
+        let first_result = subject.set_session_key(session_key.clone());
+        let second_result = subject.set_session_key(session_key.clone());
+
+        assert_eq!(first_result, true);
+        assert_eq!(second_result, false);
+        assert_eq!(subject.session_key_opt(), Some(&session_key));
+    }
+
+    #[test]
+    fn session_key_survives_gossip_serialization() {
+        let mut this_node = make_node_record(1234, true);
+        let session_key = PublicKey::new(&[5, 6, 7, 8]);
+        this_node.set_session_key(session_key.clone());
+        this_node.regenerate_signed_gossip(CRYPTDE_PAIR.main.as_ref());
+        let gnr = GossipNodeRecord::from(this_node);
+
+        let result = NodeRecordInner_0v1::try_from(gnr).unwrap();
+
+        assert_eq!(result.session_key_opt, Some(session_key));
+    }
+
     #[test]
     fn update_works_when_immutable_characteristics_dont_change() {
         let mut subject = make_node_record(1234, true);
@@ -950,6 +1234,35 @@ mod tests {
         )
     }
 
+    #[test]
+    fn update_accepts_rate_pack_change_in_newer_version() {
+        let mut subject = make_node_record(1234, true);
+        let mut modified = subject.clone();
+        modified.inner.rate_pack = ZERO_RATE_PACK.clone();
+        modified.increment_version();
+        modified.resign();
+        let agr = AccessibleGossipRecord::from(&modified);
+
+        let result = subject.update(agr);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(subject.rate_pack(), &ZERO_RATE_PACK.clone());
+        assert_eq!(subject.version(), modified.version());
+    }
+
+    #[test]
+    fn set_rate_pack_reports_whether_anything_changed() {
+        let mut subject = make_node_record(1234, true);
+        let original_rate_pack = *subject.rate_pack();
+
+        let same_result = subject.set_rate_pack(original_rate_pack);
+        let different_result = subject.set_rate_pack(ZERO_RATE_PACK.clone());
+
+        assert_eq!(same_result, false);
+        assert_eq!(different_result, true);
+        assert_eq!(subject.rate_pack(), &ZERO_RATE_PACK.clone());
+    }
+
     #[test]
     fn from_gnr_to_nri_when_gossip_is_corrupt() {
         let corrupt_gnr = GossipNodeRecord {
@@ -3,15 +3,20 @@
 use crate::neighborhood::overall_connection_status::ConnectionStageErrors::{
     NoGossipResponseReceived, PassLoopFound, TcpConnectionFailed,
 };
+use crate::sub_lib::cryptde::CryptDE;
 use crate::sub_lib::neighborhood::{
     ConnectionProgressEvent, ConnectionProgressMessage, NodeDescriptor,
 };
 use actix::Recipient;
 use masq_lib::logger::Logger;
-use masq_lib::messages::{ToMessageBody, UiConnectionChangeBroadcast, UiConnectionStage};
+use masq_lib::messages::{
+    ToMessageBody, UiConnectionChangeBroadcast, UiConnectionStage, UiNeighborConnectionFailure,
+    UiNeighborConnectionProgress, UiNeighborConnectionStage,
+};
 use masq_lib::ui_gateway::{MessageTarget, NodeToUiMessage};
 use std::net::IpAddr;
 use std::string::String;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub enum ConnectionStageErrors {
@@ -20,6 +25,16 @@ pub enum ConnectionStageErrors {
     PassLoopFound,
 }
 
+impl From<&ConnectionStageErrors> for UiNeighborConnectionFailure {
+    fn from(error: &ConnectionStageErrors) -> UiNeighborConnectionFailure {
+        match error {
+            TcpConnectionFailed => UiNeighborConnectionFailure::TcpConnectionFailed,
+            NoGossipResponseReceived => UiNeighborConnectionFailure::NoGossipResponseReceived,
+            PassLoopFound => UiNeighborConnectionFailure::PassLoopFound,
+        }
+    }
+}
+
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub enum ConnectionStage {
     StageZero,
@@ -46,6 +61,8 @@ pub struct ConnectionProgress {
     pub initial_node_descriptor: NodeDescriptor,
     pub current_peer_addr: IpAddr,
     pub connection_stage: ConnectionStage,
+    // When connection_stage or current_peer_addr last changed; None if neither has changed yet
+    pub stage_changed_at_opt: Option<SystemTime>,
 }
 
 impl ConnectionProgress {
@@ -64,6 +81,32 @@ impl ConnectionProgress {
             initial_node_descriptor: node_descriptor,
             current_peer_addr: peer_addr,
             connection_stage: ConnectionStage::StageZero,
+            stage_changed_at_opt: None,
+        }
+    }
+
+    pub fn ui_progress(&self, cryptde: &dyn CryptDE) -> UiNeighborConnectionProgress {
+        let (stage, failure_opt) = match &self.connection_stage {
+            ConnectionStage::StageZero => (UiNeighborConnectionStage::StageZero, None),
+            ConnectionStage::TcpConnectionEstablished => {
+                (UiNeighborConnectionStage::TcpConnectionEstablished, None)
+            }
+            ConnectionStage::NeighborshipEstablished => {
+                (UiNeighborConnectionStage::NeighborshipEstablished, None)
+            }
+            ConnectionStage::Failed(error) => {
+                (UiNeighborConnectionStage::Failed, Some(error.into()))
+            }
+        };
+        UiNeighborConnectionProgress {
+            node_descriptor: self.initial_node_descriptor.to_string(cryptde),
+            current_peer_addr: self.current_peer_addr.to_string(),
+            stage,
+            failure_opt,
+            stage_changed_at_opt: self
+                .stage_changed_at_opt
+                .and_then(|changed_at| changed_at.duration_since(UNIX_EPOCH).ok())
+                .map(|duration| duration.as_secs()),
         }
     }
 
@@ -90,6 +133,7 @@ impl ConnectionProgress {
         );
 
         self.connection_stage = connection_stage;
+        self.stage_changed_at_opt = Some(SystemTime::now());
     }
 
     pub fn handle_pass_gossip(&mut self, logger: &Logger, new_pass_target: IpAddr) {
@@ -118,6 +162,7 @@ impl ConnectionProgress {
 
         self.connection_stage = ConnectionStage::StageZero;
         self.current_peer_addr = new_pass_target;
+        self.stage_changed_at_opt = Some(SystemTime::now());
     }
 }
 
@@ -278,7 +323,9 @@ impl OverallConnectionStatus {
             self.stage = new_stage;
             OverallConnectionStatus::send_message_to_ui(self.stage.into(), node_to_ui_recipient);
             debug!(
-                logger,
+                logger;
+                "previous_stage" => format!("{:?}", prev_stage),
+                "new_stage" => format!("{:?}", new_stage);
                 "The stage of OverallConnectionStatus has been changed \
                 from {:?} to {:?}. A message to the UI was also sent.",
                 prev_stage,
@@ -313,6 +360,19 @@ impl OverallConnectionStatus {
         self.progress.is_empty()
     }
 
+    // Returns false if the descriptor was already being tracked
+    pub fn add(&mut self, node_descriptor: NodeDescriptor) -> bool {
+        if self
+            .iter_initial_node_descriptors()
+            .any(|existing| existing == &node_descriptor)
+        {
+            false
+        } else {
+            self.progress.push(ConnectionProgress::new(node_descriptor));
+            true
+        }
+    }
+
     pub fn remove(&mut self, index: usize) -> NodeDescriptor {
         let removed_connection_progress = self.progress.remove(index);
         removed_connection_progress.initial_node_descriptor
@@ -334,6 +394,7 @@ mod tests {
         PassLoopFound, TcpConnectionFailed,
     };
     use crate::neighborhood::PublicKey;
+    use crate::sub_lib::cryptde_null::CryptDENull;
     use crate::test_utils::neighborhood_test_utils::{make_ip, make_node, make_node_descriptor};
     use crate::test_utils::unshared_test_utils::make_node_to_ui_recipient;
     use actix::System;
@@ -341,6 +402,7 @@ mod tests {
     use masq_lib::messages::{ToMessageBody, UiConnectionChangeBroadcast, UiConnectionStage};
     use masq_lib::test_utils::logging::{init_test_logging, TestLogHandler};
     use masq_lib::ui_gateway::MessageTarget;
+    use std::time::Duration;
 
     #[test]
     #[should_panic(
@@ -374,7 +436,8 @@ mod tests {
             ConnectionProgress {
                 initial_node_descriptor,
                 current_peer_addr: pass_target,
-                connection_stage: ConnectionStage::StageZero
+                connection_stage: ConnectionStage::StageZero,
+                stage_changed_at_opt: subject.stage_changed_at_opt
             }
         );
         TestLogHandler::new().assert_logs_contain_in_order(vec![
@@ -410,7 +473,8 @@ mod tests {
             ConnectionProgress {
                 initial_node_descriptor,
                 current_peer_addr: pass_target,
-                connection_stage: ConnectionStage::StageZero
+                connection_stage: ConnectionStage::StageZero,
+                stage_changed_at_opt: subject.stage_changed_at_opt
             }
         );
         TestLogHandler::new().exists_log_containing(&format!(
@@ -584,6 +648,29 @@ mod tests {
         assert_eq!(result.next(), None);
     }
 
+    #[test]
+    fn add_tracks_a_new_descriptor_only_once() {
+        let node_desc_1 = make_node_descriptor(make_ip(1));
+        let node_desc_2 = make_node_descriptor(make_ip(2));
+        let mut subject = OverallConnectionStatus::new(vec![node_desc_1.clone()]);
+
+        let first_result = subject.add(node_desc_2.clone());
+        let second_result = subject.add(node_desc_2.clone());
+        let third_result = subject.add(node_desc_1.clone());
+
+        assert_eq!(first_result, true);
+        assert_eq!(second_result, false);
+        assert_eq!(third_result, false);
+        assert_eq!(
+            subject.iter_initial_node_descriptors().collect::<Vec<_>>(),
+            vec![&node_desc_1, &node_desc_2]
+        );
+        assert_eq!(
+            subject.progress[1],
+            ConnectionProgress::new(node_desc_2.clone())
+        );
+    }
+
     #[test]
     fn remove_deletes_descriptor_s_progress_and_returns_node_descriptor() {
         let node_desc_1 = make_node_descriptor(make_ip(1));
@@ -617,7 +704,8 @@ mod tests {
                 progress: vec![ConnectionProgress {
                     initial_node_descriptor: node_descriptor,
                     current_peer_addr: node_ip_addr,
-                    connection_stage: ConnectionStage::TcpConnectionEstablished
+                    connection_stage: ConnectionStage::TcpConnectionEstablished,
+                    stage_changed_at_opt: subject.progress[0].stage_changed_at_opt
                 }],
             }
         );
@@ -649,7 +737,8 @@ mod tests {
                 progress: vec![ConnectionProgress {
                     initial_node_descriptor: node_descriptor,
                     current_peer_addr: node_ip_addr,
-                    connection_stage: ConnectionStage::Failed(TcpConnectionFailed)
+                    connection_stage: ConnectionStage::Failed(TcpConnectionFailed),
+                    stage_changed_at_opt: subject.progress[0].stage_changed_at_opt
                 }],
             }
         )
@@ -679,7 +768,8 @@ mod tests {
             &mut ConnectionProgress {
                 initial_node_descriptor: node_descriptor,
                 current_peer_addr: node_ip_addr,
-                connection_stage: ConnectionStage::NeighborshipEstablished
+                connection_stage: ConnectionStage::NeighborshipEstablished,
+                stage_changed_at_opt: connection_progress.stage_changed_at_opt
             }
         )
     }
@@ -707,7 +797,8 @@ mod tests {
             &mut ConnectionProgress {
                 initial_node_descriptor: node_descriptor,
                 current_peer_addr: node_ip_addr,
-                connection_stage: ConnectionStage::NeighborshipEstablished
+                connection_stage: ConnectionStage::NeighborshipEstablished,
+                stage_changed_at_opt: connection_progress.stage_changed_at_opt
             }
         )
     }
@@ -740,7 +831,8 @@ mod tests {
                 progress: vec![ConnectionProgress {
                     initial_node_descriptor: node_descriptor,
                     current_peer_addr: pass_target,
-                    connection_stage: ConnectionStage::StageZero
+                    connection_stage: ConnectionStage::StageZero,
+                    stage_changed_at_opt: subject.progress[0].stage_changed_at_opt
                 }],
             }
         )
@@ -772,7 +864,8 @@ mod tests {
                 progress: vec![ConnectionProgress {
                     initial_node_descriptor: node_descriptor,
                     current_peer_addr: node_ip_addr,
-                    connection_stage: ConnectionStage::Failed(PassLoopFound)
+                    connection_stage: ConnectionStage::Failed(PassLoopFound),
+                    stage_changed_at_opt: subject.progress[0].stage_changed_at_opt
                 }],
             }
         )
@@ -805,12 +898,83 @@ mod tests {
                 progress: vec![ConnectionProgress {
                     initial_node_descriptor: node_descriptor,
                     current_peer_addr: node_ip_addr,
-                    connection_stage: ConnectionStage::Failed(NoGossipResponseReceived)
+                    connection_stage: ConnectionStage::Failed(NoGossipResponseReceived),
+                    stage_changed_at_opt: subject.progress[0].stage_changed_at_opt
                 }],
             }
         )
     }
 
+    #[test]
+    fn stage_changes_are_timestamped() {
+        let logger = Logger::new("stage_changes_are_timestamped");
+        let mut subject = ConnectionProgress::new(make_node_descriptor(make_ip(1)));
+        let initial_timestamp_opt = subject.stage_changed_at_opt;
+
+        let before_update = SystemTime::now();
+        subject.update_stage(&logger, ConnectionStage::TcpConnectionEstablished);
+        let after_update = SystemTime::now();
+        let update_timestamp = subject.stage_changed_at_opt.unwrap();
+        subject.handle_pass_gossip(&logger, make_ip(2));
+        let after_pass = SystemTime::now();
+        let pass_timestamp = subject.stage_changed_at_opt.unwrap();
+
+        assert_eq!(initial_timestamp_opt, None);
+        assert!(before_update <= update_timestamp && update_timestamp <= after_update);
+        assert!(update_timestamp <= pass_timestamp && pass_timestamp <= after_pass);
+    }
+
+    #[test]
+    fn connection_progress_can_be_described_for_the_ui() {
+        let cryptde = CryptDENull::new(Chain::EthRopsten);
+        let node_descriptor = make_node_descriptor(make_ip(1));
+        let mut failed = ConnectionProgress::new(node_descriptor.clone());
+        failed.current_peer_addr = make_ip(2);
+        failed.connection_stage = ConnectionStage::Failed(NoGossipResponseReceived);
+        failed.stage_changed_at_opt = Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+        let untouched = ConnectionProgress::new(node_descriptor.clone());
+
+        let failed_result = failed.ui_progress(&cryptde);
+        let untouched_result = untouched.ui_progress(&cryptde);
+
+        assert_eq!(
+            failed_result,
+            UiNeighborConnectionProgress {
+                node_descriptor: node_descriptor.to_string(&cryptde),
+                current_peer_addr: "1.1.1.2".to_string(),
+                stage: UiNeighborConnectionStage::Failed,
+                failure_opt: Some(UiNeighborConnectionFailure::NoGossipResponseReceived),
+                stage_changed_at_opt: Some(1_700_000_000),
+            }
+        );
+        assert_eq!(
+            untouched_result,
+            UiNeighborConnectionProgress {
+                node_descriptor: node_descriptor.to_string(&cryptde),
+                current_peer_addr: "1.1.1.1".to_string(),
+                stage: UiNeighborConnectionStage::StageZero,
+                failure_opt: None,
+                stage_changed_at_opt: None,
+            }
+        );
+    }
+
+    #[test]
+    fn connection_stage_errors_are_converted_for_the_ui() {
+        assert_eq!(
+            UiNeighborConnectionFailure::from(&TcpConnectionFailed),
+            UiNeighborConnectionFailure::TcpConnectionFailed
+        );
+        assert_eq!(
+            UiNeighborConnectionFailure::from(&NoGossipResponseReceived),
+            UiNeighborConnectionFailure::NoGossipResponseReceived
+        );
+        assert_eq!(
+            UiNeighborConnectionFailure::from(&PassLoopFound),
+            UiNeighborConnectionFailure::PassLoopFound
+        );
+    }
+
     #[test]
     fn connection_stage_can_be_converted_to_number() {
         assert_eq!(usize::try_from(&ConnectionStage::StageZero), Ok(0));
@@ -15,7 +15,8 @@ use crate::sub_lib::utils::{db_connection_launch_panic, make_new_multi_config};
 use clap::{value_t, App};
 use core::option::Option;
 use dirs::{data_local_dir, home_dir};
-use masq_lib::blockchains::chains::Chain;
+use masq_lib::blockchains::chains::{chain_from_chain_identifier_opt, Chain};
+use masq_lib::blockchains::custom_chains::load_custom_chains;
 use masq_lib::constants::DEFAULT_CHAIN;
 use masq_lib::multi_config::{
     merge, CommandLineVcl, EnvironmentVcl, MultiConfig, VirtualCommandLine,
@@ -25,6 +26,7 @@ use masq_lib::utils::{add_masq_and_chain_directories, localhost};
 use std::env::current_dir;
 use std::net::{SocketAddr, TcpListener};
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 pub trait NodeConfigurator<T> {
     fn configure(&self, multi_config: &MultiConfig) -> Result<T, ConfiguratorError>;
@@ -232,6 +234,7 @@ pub fn determine_user_specific_data(
         Box::new(EnvironmentVcl::new(app)),
         Box::new(CommandLineVcl::new(args.to_vec())),
     );
+    load_custom_chains_from_args(dirs_wrapper, orientation_args.as_ref())?;
     /* We create multiconfig to retrieve chain, real-user, data-directory and config file, to establish ConfigVcl */
     let first_multi_config =
         make_new_multi_config(app, vec![orientation_args]).expect("expected MultiConfig");
@@ -241,6 +244,40 @@ pub fn determine_user_specific_data(
     Ok(initialization_data)
 }
 
+// Clap can only recognize a custom chain that is already loaded, so its definition has to be found
+// in the data directory before anything is parsed properly
+fn load_custom_chains_from_args(
+    dirs_wrapper: &dyn DirsWrapper,
+    orientation_args: &dyn VirtualCommandLine,
+) -> Result<(), ConfiguratorError> {
+    let value_opt = |name: &str| {
+        orientation_args
+            .vcl_args()
+            .into_iter()
+            .find(|vcl_arg| vcl_arg.name() == name)
+            .and_then(|vcl_arg| vcl_arg.to_args().get(1).cloned())
+    };
+    let chain_name = match value_opt("--chain") {
+        Some(chain_name) if chain_from_chain_identifier_opt(&chain_name).is_none() => chain_name,
+        _ => return Ok(()),
+    };
+    let data_directory = match value_opt("--data-directory") {
+        Some(data_directory) => replace_tilde(PathBuf::from(data_directory), dirs_wrapper),
+        None => {
+            let real_user = value_opt("--real-user")
+                .and_then(|triple| RealUser::from_str(&triple).ok())
+                .unwrap_or_else(|| RealUser::new(None, None, None))
+                .populate(dirs_wrapper);
+            local_data_dir_from_context(dirs_wrapper, &real_user)
+                .join("MASQ")
+                .join(&chain_name)
+        }
+    };
+    load_custom_chains(&data_directory)
+        .map(|_| ())
+        .map_err(|e| ConfiguratorError::required("chain", &e))
+}
+
 pub fn initialize_database(
     data_directory: &Path,
     migrator_config: DbInitializationConfig,
@@ -293,6 +330,11 @@ pub fn data_directory_from_context(
     real_user: &RealUser,
     chain: Chain,
 ) -> PathBuf {
+    let homedir = local_data_dir_from_context(dirs_wrapper, real_user);
+    add_masq_and_chain_directories(chain, &homedir)
+}
+
+fn local_data_dir_from_context(dirs_wrapper: &dyn DirsWrapper, real_user: &RealUser) -> PathBuf {
     let right_home_dir = real_user
         .home_dir_opt
         .as_ref()
@@ -306,8 +348,7 @@ pub fn data_directory_from_context(
     let adjusted_local_data_dir: &Path = wrong_local_data_dir
         .strip_prefix(wrong_home_dir)
         .expect("std lib failed");
-    let homedir = right_home_dir.join(adjusted_local_data_dir);
-    add_masq_and_chain_directories(chain, &homedir)
+    right_home_dir.join(adjusted_local_data_dir)
 }
 
 pub fn port_is_busy(port: u16) -> bool {
@@ -351,12 +392,130 @@ mod tests {
     use super::*;
     use crate::node_test_utils::DirsWrapperMock;
     use crate::test_utils::ArgsBuilder;
-    use masq_lib::shared_schema::{config_file_arg, data_directory_arg, DATA_DIRECTORY_HELP};
+    use masq_lib::constants::CUSTOM_CHAINS_FILE_NAME;
+    use masq_lib::shared_schema::{
+        chain_arg, config_file_arg, data_directory_arg, real_user_arg, DATA_DIRECTORY_HELP,
+    };
     use masq_lib::test_utils::environment_guard::EnvironmentGuard;
     use masq_lib::test_utils::utils::ensure_node_home_directory_exists;
     use masq_lib::utils::find_free_port;
+    use std::fs;
     use std::net::{SocketAddr, TcpListener};
 
+    fn custom_chain_app() -> App<'static, 'static> {
+        App::new("test")
+            .arg(chain_arg())
+            .arg(data_directory_arg(DATA_DIRECTORY_HELP))
+            .arg(real_user_arg())
+    }
+
+    fn write_custom_chain(data_directory: &Path, name: &str, chain_id: u64) {
+        fs::create_dir_all(data_directory).unwrap();
+        fs::write(
+            data_directory.join(CUSTOM_CHAINS_FILE_NAME),
+            format!(
+                "[[chain]]\n\
+                name = \"{}\"\n\
+                chain_id = {}\n\
+                contract = \"0x4242424242424242424242424242424242424242\"\n\
+                contract_creation_block = 0\n\
+                confirmation_depth = 1\n\
+                gas_limit_const_part = 55000\n",
+                name, chain_id
+            ),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn determine_user_specific_data_loads_custom_chains_from_the_data_directory() {
+        let _guard = EnvironmentGuard::new();
+        let data_directory = ensure_node_home_directory_exists(
+            "node_configurator",
+            "determine_user_specific_data_loads_custom_chains_from_the_data_directory",
+        );
+        write_custom_chain(&data_directory, "explicit-devnet", 31348);
+        let args_vec: Vec<String> = ArgsBuilder::new()
+            .param("--chain", "explicit-devnet")
+            .param("--data-directory", data_directory.to_str().unwrap())
+            .into();
+
+        let user_specific_data = determine_user_specific_data(
+            &DirsWrapperReal::default(),
+            &custom_chain_app(),
+            args_vec.as_slice(),
+        )
+        .unwrap();
+
+        assert_eq!(user_specific_data.chain.item, Chain::Custom(31348));
+        assert_eq!(user_specific_data.chain.user_specified, true);
+        assert_eq!(user_specific_data.data_directory.item, data_directory);
+    }
+
+    #[test]
+    fn determine_user_specific_data_finds_custom_chains_in_the_default_data_directory() {
+        let _guard = EnvironmentGuard::new();
+        let home_dir = ensure_node_home_directory_exists(
+            "node_configurator",
+            "determine_user_specific_data_finds_custom_chains_in_the_default_data_directory",
+        );
+        let local_data_dir = home_dir.join(".local").join("share");
+        let expected_data_directory = local_data_dir.join("MASQ").join("implicit-devnet");
+        write_custom_chain(&expected_data_directory, "implicit-devnet", 31349);
+        let dirs_wrapper = DirsWrapperMock::new()
+            .home_dir_result(Some(home_dir.clone()))
+            .data_dir_result(Some(local_data_dir));
+        let args_vec: Vec<String> = ArgsBuilder::new()
+            .param("--chain", "implicit-devnet")
+            .param(
+                "--real-user",
+                &format!("123:456:{}", home_dir.to_str().unwrap()),
+            )
+            .into();
+
+        let user_specific_data =
+            determine_user_specific_data(&dirs_wrapper, &custom_chain_app(), args_vec.as_slice())
+                .unwrap();
+
+        assert_eq!(user_specific_data.chain.item, Chain::Custom(31349));
+        assert_eq!(
+            user_specific_data.data_directory.item,
+            expected_data_directory
+        );
+    }
+
+    #[test]
+    fn determine_user_specific_data_reports_a_bad_custom_chains_file() {
+        let _guard = EnvironmentGuard::new();
+        let data_directory = ensure_node_home_directory_exists(
+            "node_configurator",
+            "determine_user_specific_data_reports_a_bad_custom_chains_file",
+        );
+        let path = data_directory.join(CUSTOM_CHAINS_FILE_NAME);
+        fs::write(&path, "[[chain]]\nname = \"broken-devnet\"\n").unwrap();
+        let args_vec: Vec<String> = ArgsBuilder::new()
+            .param("--chain", "broken-devnet")
+            .param("--data-directory", data_directory.to_str().unwrap())
+            .into();
+
+        let result = determine_user_specific_data(
+            &DirsWrapperReal::default(),
+            &custom_chain_app(),
+            args_vec.as_slice(),
+        );
+
+        let error = result.err().unwrap();
+        assert_eq!(error.param_errors.len(), 1);
+        assert_eq!(error.param_errors[0].parameter, "chain".to_string());
+        assert!(
+            error.param_errors[0]
+                .reason
+                .starts_with(&format!("Couldn't parse {}: ", path.display())),
+            "{:?}",
+            error
+        );
+    }
+
     fn determine_config_file_path_app() -> App<'static, 'static> {
         App::new("test")
             .arg(data_directory_arg(DATA_DIRECTORY_HELP))
@@ -4,7 +4,7 @@ use crate::bootstrapper::{BootstrapperConfig, CryptDEPair};
 use crate::node_configurator::{initialize_database, DirsWrapper, FieldPair, NodeConfigurator};
 use crate::node_configurator::{ConfigInitializationData, DirsWrapperReal};
 use masq_lib::crash_point::CrashPoint;
-use masq_lib::logger::Logger;
+use masq_lib::logger::{LogFormat, Logger};
 use masq_lib::multi_config::{MultiConfig, VirtualCommandLine};
 use masq_lib::shared_schema::ConfiguratorError;
 use masq_lib::utils::NeighborhoodModeLight;
@@ -17,6 +17,7 @@ use log::LevelFilter;
 use crate::apps::app_node;
 use crate::bootstrapper::PortConfiguration;
 use crate::database::db_initializer::{DbInitializationConfig, ExternalData};
+use crate::db_config::db_kdf::KDF_ALGORITHM;
 use crate::db_config::persistent_configuration::PersistentConfiguration;
 use crate::http_request_start_finder::HttpRequestDiscriminatorFactory;
 use crate::node_configurator::unprivileged_parse_args_configuration::{
@@ -29,12 +30,15 @@ use crate::node_configurator::{
 use crate::sub_lib::cryptde::{CryptDE, PublicKey};
 use crate::sub_lib::cryptde_null::CryptDENull;
 use crate::sub_lib::cryptde_real::CryptDEReal;
+use crate::sub_lib::neighborhood::{RouteDiversity, RouteDiversityMode};
+use crate::sub_lib::proxy_server::HeaderScrubbing;
 use crate::sub_lib::utils::make_new_multi_config;
 use crate::tls_discriminator_factory::TlsDiscriminatorFactory;
 use masq_lib::blockchains::chains::Chain;
 use masq_lib::constants::{DEFAULT_UI_PORT, HTTP_PORT, TLS_PORT};
 use masq_lib::multi_config::{CommandLineVcl, ConfigFileVcl, EnvironmentVcl};
 use std::str::FromStr;
+use std::time::Duration;
 
 pub struct NodeConfiguratorStandardPrivileged {
     dirs_wrapper: Box<dyn DirsWrapper>,
@@ -94,6 +98,11 @@ impl NodeConfigurator<BootstrapperConfig> for NodeConfiguratorStandardUnprivileg
             &self.logger,
         )?;
         configure_database(&unprivileged_config, persistent_config.as_mut())?;
+        upgrade_database_encryption(
+            &unprivileged_config.db_password_opt,
+            persistent_config.as_mut(),
+            &self.logger,
+        )?;
         let cryptde_pair = if multi_config.occurrences_of("fake-public-key") == 0 {
             configure_cryptdes(
                 persistent_config.as_mut(),
@@ -302,6 +311,18 @@ pub fn privileged_parse_args(
 
     privileged_config.log_level =
         value_m!(multi_config, "log-level", LevelFilter).unwrap_or(LevelFilter::Warn);
+    privileged_config.log_format =
+        value_m!(multi_config, "log-format", LogFormat).unwrap_or(LogFormat::Text);
+    privileged_config.key_rotation_interval_opt =
+        value_m!(multi_config, "key-rotation-interval", u64)
+            .map(|hours| Duration::from_secs(hours * 60 * 60));
+    privileged_config.route_diversity = RouteDiversity {
+        mode: value_m!(multi_config, "route-diversity", RouteDiversityMode)
+            .unwrap_or(RouteDiversityMode::Subnet),
+        min_distinct_prefixes: value_m!(multi_config, "min-route-prefixes", usize).unwrap_or(0),
+    };
+    privileged_config.header_scrubbing =
+        value_m!(multi_config, "scrub-headers", HeaderScrubbing).unwrap_or_default();
 
     privileged_config.ui_gateway_config.ui_port =
         value_m!(multi_config, "ui-port", u16).unwrap_or(DEFAULT_UI_PORT);
@@ -344,6 +365,25 @@ fn configure_database(
     Ok(())
 }
 
+fn upgrade_database_encryption(
+    db_password_opt: &Option<String>,
+    persistent_config: &mut dyn PersistentConfiguration,
+    logger: &Logger,
+) -> Result<(), ConfiguratorError> {
+    if let Some(db_password) = db_password_opt {
+        match persistent_config.upgrade_encryption(db_password) {
+            Ok(true) => info!(
+                logger,
+                "Re-encrypted the secrets in the database under fresh {} key-derivation parameters",
+                KDF_ALGORITHM
+            ),
+            Ok(false) => (),
+            Err(pce) => return Err(pce.into_configurator_error("db-password")),
+        }
+    }
+    Ok(())
+}
+
 fn configure_cryptdes(
     persistent_config: &mut dyn PersistentConfiguration,
     db_password_opt: &Option<String>,
@@ -426,6 +466,7 @@ mod tests {
     use masq_lib::multi_config::VirtualCommandLine;
     use masq_lib::shared_schema::ParamError;
     use masq_lib::test_utils::environment_guard::{ClapGuard, EnvironmentGuard};
+    use masq_lib::test_utils::logging::{init_test_logging, TestLogHandler};
     use masq_lib::test_utils::utils::{ensure_node_home_directory_exists, TEST_DEFAULT_CHAIN};
     use masq_lib::utils::running_test;
     use rustc_hex::FromHex;
@@ -643,6 +684,62 @@ mod tests {
         )
     }
 
+    #[test]
+    fn upgrade_database_encryption_does_nothing_without_a_password() {
+        let upgrade_encryption_params_arc = Arc::new(Mutex::new(vec![]));
+        let mut persistent_config = PersistentConfigurationMock::new()
+            .upgrade_encryption_params(&upgrade_encryption_params_arc);
+
+        let result =
+            upgrade_database_encryption(&None, &mut persistent_config, &Logger::new("test"));
+
+        assert_eq!(result, Ok(()));
+        let upgrade_encryption_params = upgrade_encryption_params_arc.lock().unwrap();
+        assert_eq!(upgrade_encryption_params.len(), 0);
+    }
+
+    #[test]
+    fn upgrade_database_encryption_reports_an_upgrade() {
+        init_test_logging();
+        let test_name = "upgrade_database_encryption_reports_an_upgrade";
+        let upgrade_encryption_params_arc = Arc::new(Mutex::new(vec![]));
+        let mut persistent_config = PersistentConfigurationMock::new()
+            .upgrade_encryption_params(&upgrade_encryption_params_arc)
+            .upgrade_encryption_result(Ok(true));
+
+        let result = upgrade_database_encryption(
+            &Some("password".to_string()),
+            &mut persistent_config,
+            &Logger::new(test_name),
+        );
+
+        assert_eq!(result, Ok(()));
+        let upgrade_encryption_params = upgrade_encryption_params_arc.lock().unwrap();
+        assert_eq!(*upgrade_encryption_params, vec!["password".to_string()]);
+        TestLogHandler::new().exists_log_containing(&format!(
+            "INFO: {}: Re-encrypted the secrets in the database under fresh argon2id \
+             key-derivation parameters",
+            test_name
+        ));
+    }
+
+    #[test]
+    fn upgrade_database_encryption_handles_error() {
+        let mut persistent_config = PersistentConfigurationMock::new()
+            .upgrade_encryption_result(Err(PersistentConfigError::PasswordError));
+
+        let result = upgrade_database_encryption(
+            &Some("password".to_string()),
+            &mut persistent_config,
+            &Logger::new("test"),
+        );
+
+        assert_eq!(
+            result,
+            Err(PersistentConfigError::PasswordError.into_configurator_error("db-password"))
+        );
+    }
+
     #[test]
     fn configure_cryptdes_handles_missing_password_with_uninitialized_cryptdes() {
         let cryptde_params_arc = Arc::new(Mutex::new(vec![]));
@@ -841,6 +938,11 @@ mod tests {
             .param("--data-directory", home_dir.to_str().unwrap())
             .param("--blockchain-service-url", "http://127.0.0.1:8545")
             .param("--log-level", "trace")
+            .param("--log-format", "json")
+            .param("--key-rotation-interval", "24")
+            .param("--route-diversity", "country")
+            .param("--min-route-prefixes", "3")
+            .param("--scrub-headers", "Referer,User-Agent")
             .param("--db-password", "secret-db-password")
             .param(
                 "--earning-wallet",
@@ -871,6 +973,25 @@ mod tests {
             ),
         );
         assert_eq!(config.ui_gateway_config.ui_port, 5335);
+        assert_eq!(config.log_level, LevelFilter::Trace);
+        assert_eq!(config.log_format, LogFormat::Json);
+        assert_eq!(
+            config.key_rotation_interval_opt,
+            Some(Duration::from_secs(24 * 60 * 60))
+        );
+        assert_eq!(
+            config.route_diversity,
+            RouteDiversity {
+                mode: RouteDiversityMode::Country,
+                min_distinct_prefixes: 3,
+            }
+        );
+        assert_eq!(
+            config.header_scrubbing,
+            HeaderScrubbing {
+                header_names: vec!["referer".to_string(), "user-agent".to_string()]
+            }
+        );
         assert_eq!(
             config.neighborhood_config,
             NeighborhoodConfig {
@@ -963,6 +1084,15 @@ mod tests {
         );
         assert_eq!(config.crash_point, CrashPoint::None);
         assert_eq!(config.ui_gateway_config.ui_port, DEFAULT_UI_PORT);
+        assert_eq!(config.key_rotation_interval_opt, None);
+        assert_eq!(
+            config.route_diversity,
+            RouteDiversity {
+                mode: RouteDiversityMode::Subnet,
+                min_distinct_prefixes: 0,
+            }
+        );
+        assert_eq!(config.header_scrubbing, HeaderScrubbing::default());
         assert_eq!(
             config.real_user,
             RealUser::new(None, None, None).populate(&DirsWrapperReal::default())
@@ -4,10 +4,13 @@ use crate::accountant::DEFAULT_PENDING_TOO_LONG_SEC;
 use crate::blockchain::bip32::Bip32EncryptionKeyProvider;
 use crate::bootstrapper::BootstrapperConfig;
 use crate::db_config::persistent_configuration::{PersistentConfigError, PersistentConfiguration};
-use crate::sub_lib::accountant::{PaymentThresholds, ScanIntervals, DEFAULT_EARNING_WALLET};
+use crate::sub_lib::accountant::{
+    PayableWriteOffPolicy, PaymentThresholds, ScanIntervals, DEFAULT_EARNING_WALLET,
+};
 use crate::sub_lib::cryptde::CryptDE;
 use crate::sub_lib::cryptde_null::CryptDENull;
 use crate::sub_lib::cryptde_real::CryptDEReal;
+use crate::sub_lib::earning_wallet_shares::EarningWalletShares;
 use crate::sub_lib::neighborhood::{
     Hops, NeighborhoodConfig, NeighborhoodMode, NodeDescriptor, RatePack,
 };
@@ -75,6 +78,7 @@ pub trait UnprivilegedParseArgsConfiguration {
             compute_mapping_protocol_opt(multi_config, persistent_config, logger);
         let mnc_result = {
             get_wallets(multi_config, persistent_config, unprivileged_config)?;
+            configure_earning_wallet_shares(multi_config, persistent_config, unprivileged_config)?;
             make_neighborhood_config(self, multi_config, persistent_config, unprivileged_config)
         };
 
@@ -218,6 +222,43 @@ fn wallet_params_are_equal(a: &str, b: &str) -> bool {
     a.to_uppercase() == b.to_uppercase()
 }
 
+fn configure_earning_wallet_shares(
+    multi_config: &MultiConfig,
+    persistent_config: &mut dyn PersistentConfiguration,
+    config: &mut BootstrapperConfig,
+) -> Result<(), ConfiguratorError> {
+    let persistent_shares = persistent_config
+        .earning_wallet_shares()
+        .map_err(|e| e.into_configurator_error("earning-wallet-shares"))?;
+    let shares_specified = multi_config.occurrences_of("earning-wallet-shares") > 0;
+    let shares = match value_m!(multi_config, "earning-wallet-shares", String) {
+        Some(shares_str) => EarningWalletShares::from_str(&shares_str)
+            .map_err(|e| ConfiguratorError::required("earning-wallet-shares", &e))?,
+        None if shares_specified => EarningWalletShares::default(),
+        None => persistent_shares.clone(),
+    };
+    if shares
+        .wallets()
+        .iter()
+        .any(|wallet| wallet.address() == config.earning_wallet.address())
+    {
+        return Err(ConfiguratorError::required(
+            "earning-wallet-shares",
+            &format!(
+                "Earning wallet {} cannot also take a share",
+                config.earning_wallet
+            ),
+        ));
+    }
+    if shares != persistent_shares {
+        persistent_config
+            .set_earning_wallet_shares(&shares)
+            .map_err(|e| e.into_configurator_error("earning-wallet-shares"))?;
+    }
+    config.earning_wallet_shares = shares;
+    Ok(())
+}
+
 pub fn make_neighborhood_config<T: UnprivilegedParseArgsConfiguration + ?Sized>(
     parse_args_configurator: &T,
     multi_config: &MultiConfig,
@@ -404,7 +445,7 @@ fn convert_ci_configs(
     }
 }
 
-fn validate_descriptors_from_user(
+pub fn validate_descriptors_from_user(
     descriptors: Vec<String>,
     cryptde_for_key_len: Box<dyn CryptDE>,
     desired_native_chain: Chain,
@@ -525,10 +566,16 @@ fn configure_accountant_config(
     )?;
     let suppress_initial_scans =
         value_m!(multi_config, "scans", String).unwrap_or_else(|| "on".to_string()) == *"off";
+    let payable_write_off_policy = match value_m!(multi_config, "payable-write-off", String) {
+        Some(policy_str) => PayableWriteOffPolicy::try_from(policy_str.as_str())
+            .map_err(|e| ConfiguratorError::required("payable-write-off", &e))?,
+        None => PayableWriteOffPolicy::default(),
+    };
 
     config.payment_thresholds_opt = Some(payment_thresholds);
     config.scan_intervals_opt = Some(scan_intervals);
     config.suppress_initial_scans = suppress_initial_scans;
+    config.payable_write_off_policy = payable_write_off_policy;
     config.when_pending_too_long_sec = DEFAULT_PENDING_TOO_LONG_SEC;
     Ok(())
 }
@@ -636,7 +683,7 @@ mod tests {
         make_persistent_config_real_with_config_dao_null, make_simplified_multi_config,
         ACCOUNTANT_CONFIG_PARAMS, MAPPING_PROTOCOL, RATE_PACK, ZERO,
     };
-    use crate::test_utils::ArgsBuilder;
+    use crate::test_utils::{make_wallet, ArgsBuilder};
     use lazy_static::lazy_static;
     use masq_lib::constants::DEFAULT_GAS_PRICE;
     use masq_lib::multi_config::{CommandLineVcl, NameValueVclArg, VclArg, VirtualCommandLine};
@@ -2421,6 +2468,115 @@ mod tests {
         assert_eq!(result, expected_rate_pack)
     }
 
+    #[test]
+    fn configure_earning_wallet_shares_uses_saved_shares_if_nothing_supplied() {
+        let multi_config = make_new_multi_config(
+            &app_node(),
+            vec![Box::new(CommandLineVcl::new(ArgsBuilder::new().into()))],
+        )
+        .unwrap();
+        let shares =
+            EarningWalletShares::from_str(&format!("{}:30", make_wallet("share"))).unwrap();
+        let mut persistent_config =
+            PersistentConfigurationMock::new().earning_wallet_shares_result(Ok(shares.clone()));
+        let mut config = BootstrapperConfig::new();
+
+        let result =
+            configure_earning_wallet_shares(&multi_config, &mut persistent_config, &mut config);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(config.earning_wallet_shares, shares);
+        // No .set_earning_wallet_shares result: if it is called, the mock panics and fails this test
+    }
+
+    #[test]
+    fn configure_earning_wallet_shares_saves_supplied_shares_if_different() {
+        let shares_str = format!(
+            "{}:30,{}:20",
+            make_wallet("first share"),
+            make_wallet("second share")
+        );
+        let multi_config = make_new_multi_config(
+            &app_node(),
+            vec![Box::new(CommandLineVcl::new(
+                ArgsBuilder::new()
+                    .param("--earning-wallet-shares", &shares_str)
+                    .into(),
+            ))],
+        )
+        .unwrap();
+        let set_earning_wallet_shares_params_arc = Arc::new(Mutex::new(vec![]));
+        let mut persistent_config = PersistentConfigurationMock::new()
+            .earning_wallet_shares_result(Ok(EarningWalletShares::default()))
+            .set_earning_wallet_shares_params(&set_earning_wallet_shares_params_arc)
+            .set_earning_wallet_shares_result(Ok(()));
+        let mut config = BootstrapperConfig::new();
+
+        let result =
+            configure_earning_wallet_shares(&multi_config, &mut persistent_config, &mut config);
+
+        assert_eq!(result, Ok(()));
+        let expected_shares = EarningWalletShares::from_str(&shares_str).unwrap();
+        assert_eq!(config.earning_wallet_shares, expected_shares);
+        let set_earning_wallet_shares_params = set_earning_wallet_shares_params_arc.lock().unwrap();
+        assert_eq!(*set_earning_wallet_shares_params, vec![expected_shares]);
+    }
+
+    #[test]
+    fn configure_earning_wallet_shares_blanks_database_if_command_line_with_missing_value() {
+        let multi_config = make_simplified_multi_config(["--earning-wallet-shares"]);
+        let set_earning_wallet_shares_params_arc = Arc::new(Mutex::new(vec![]));
+        let mut persistent_config = PersistentConfigurationMock::new()
+            .earning_wallet_shares_result(Ok(EarningWalletShares::from_str(&format!(
+                "{}:30",
+                make_wallet("share")
+            ))
+            .unwrap()))
+            .set_earning_wallet_shares_params(&set_earning_wallet_shares_params_arc)
+            .set_earning_wallet_shares_result(Ok(()));
+        let mut config = BootstrapperConfig::new();
+
+        let result =
+            configure_earning_wallet_shares(&multi_config, &mut persistent_config, &mut config);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(config.earning_wallet_shares, EarningWalletShares::default());
+        let set_earning_wallet_shares_params = set_earning_wallet_shares_params_arc.lock().unwrap();
+        assert_eq!(
+            *set_earning_wallet_shares_params,
+            vec![EarningWalletShares::default()]
+        );
+    }
+
+    #[test]
+    fn configure_earning_wallet_shares_rejects_a_share_for_the_earning_wallet() {
+        let earning_wallet = make_wallet("earning");
+        let multi_config = make_new_multi_config(
+            &app_node(),
+            vec![Box::new(CommandLineVcl::new(
+                ArgsBuilder::new()
+                    .param("--earning-wallet-shares", &format!("{}:30", earning_wallet))
+                    .into(),
+            ))],
+        )
+        .unwrap();
+        let mut persistent_config = PersistentConfigurationMock::new()
+            .earning_wallet_shares_result(Ok(EarningWalletShares::default()));
+        let mut config = BootstrapperConfig::new();
+        config.earning_wallet = earning_wallet.clone();
+
+        let result =
+            configure_earning_wallet_shares(&multi_config, &mut persistent_config, &mut config);
+
+        assert_eq!(
+            result,
+            Err(ConfiguratorError::required(
+                "earning-wallet-shares",
+                &format!("Earning wallet {} cannot also take a share", earning_wallet)
+            ))
+        );
+    }
+
     #[test]
     fn compute_mapping_protocol_returns_saved_value_if_nothing_supplied() {
         let multi_config = make_new_multi_config(
@@ -2625,6 +2781,56 @@ mod tests {
             .unwrap();
 
         assert_eq!(bootstrapper_config.suppress_initial_scans, false);
+        assert_eq!(
+            bootstrapper_config.payable_write_off_policy,
+            PayableWriteOffPolicy::default()
+        );
+    }
+
+    #[test]
+    fn configure_accountant_config_handles_payable_write_off() {
+        let multi_config = make_simplified_multi_config(["--payable-write-off", "90|5000|30"]);
+        let mut bootstrapper_config = BootstrapperConfig::new();
+        let mut persistent_config =
+            configure_default_persistent_config(ACCOUNTANT_CONFIG_PARAMS | MAPPING_PROTOCOL);
+
+        let result = configure_accountant_config(
+            &multi_config,
+            &mut bootstrapper_config,
+            &mut persistent_config,
+        );
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            bootstrapper_config.payable_write_off_policy,
+            PayableWriteOffPolicy {
+                unseen_days: 90,
+                dust_threshold_gwei: 5000,
+                dust_days: 30
+            }
+        );
+    }
+
+    #[test]
+    fn configure_accountant_config_rejects_payable_write_off_with_wrong_number_of_values() {
+        let multi_config = make_simplified_multi_config(["--payable-write-off", "90|5000"]);
+        let mut bootstrapper_config = BootstrapperConfig::new();
+        let mut persistent_config =
+            configure_default_persistent_config(ACCOUNTANT_CONFIG_PARAMS | MAPPING_PROTOCOL);
+
+        let result = configure_accountant_config(
+            &multi_config,
+            &mut bootstrapper_config,
+            &mut persistent_config,
+        );
+
+        assert_eq!(
+            result,
+            Err(ConfiguratorError::required(
+                "payable-write-off",
+                "Wrong number of values: expected 3 but 2 supplied"
+            ))
+        );
     }
 
     fn make_persistent_config(
@@ -2657,6 +2863,7 @@ mod tests {
             .consuming_wallet_private_key_result(Ok(consuming_wallet_private_key_opt))
             .earning_wallet_address_result(Ok(earning_wallet_address_opt.map(to_string)))
             .earning_wallet_result(Ok(earning_wallet_opt))
+            .earning_wallet_shares_result(Ok(EarningWalletShares::default()))
             .gas_price_result(Ok(gas_price))
             .past_neighbors_result(past_neighbors_result)
             .mapping_protocol_result(Ok(Some(AutomapProtocol::Pcp)))
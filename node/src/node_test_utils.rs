@@ -13,6 +13,7 @@ use crate::stream_handler_pool::StreamHandlerPoolSubs;
 use crate::stream_messages::*;
 use crate::sub_lib::framer::FramedChunk;
 use crate::sub_lib::framer::Framer;
+use crate::sub_lib::stream_handler_pool::BanClandestinePeerMsg;
 use crate::sub_lib::stream_handler_pool::DispatcherNodeQueryResponse;
 use crate::sub_lib::stream_handler_pool::TransmitDataMsg;
 use crate::sub_lib::utils::MessageScheduler;
@@ -301,6 +302,7 @@ pub fn make_stream_handler_pool_subs_from_recorder(addr: &Addr<Recorder>) -> Str
             addr,
             MessageScheduler<DispatcherNodeQueryResponse>
         ),
+        ban_sub: recipient!(addr, BanClandestinePeerMsg),
     }
 }
 
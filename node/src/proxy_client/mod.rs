@@ -15,9 +15,10 @@ use crate::proxy_client::stream_handler_pool::StreamHandlerPool;
 use crate::proxy_client::stream_handler_pool::StreamHandlerPoolFactory;
 use crate::proxy_client::stream_handler_pool::StreamHandlerPoolFactoryReal;
 use crate::sub_lib::accountant::ReportExitServiceProvidedMessage;
-use crate::sub_lib::cryptde::PublicKey;
+use crate::sub_lib::cryptde::{CryptDE, PublicKey};
 use crate::sub_lib::hopper::MessageType;
 use crate::sub_lib::hopper::{ExpiredCoresPackage, IncipientCoresPackage};
+use crate::sub_lib::neighborhood::{ConfigChange, ConfigChangeMsg, MainCryptDE, RatePack};
 use crate::sub_lib::peer_actors::BindMessage;
 use crate::sub_lib::proxy_client::InboundServerData;
 use crate::sub_lib::proxy_client::ProxyClientConfig;
@@ -208,6 +209,20 @@ impl Handler<DnsResolveFailure_0v1> for ProxyClient {
     }
 }
 
+impl Handler<ConfigChangeMsg> for ProxyClient {
+    type Result = ();
+
+    fn handle(&mut self, msg: ConfigChangeMsg, _ctx: &mut Self::Context) -> Self::Result {
+        match msg.change {
+            ConfigChange::UpdateRatePack(rate_pack) => self.handle_new_rate_pack(rate_pack),
+            ConfigChange::UpdateMainCryptDE(MainCryptDE(new_cryptde)) => {
+                self.handle_new_main_cryptde(new_cryptde)
+            }
+            _ => (),
+        }
+    }
+}
+
 impl Handler<NodeFromUiMessage> for ProxyClient {
     type Result = ();
 
@@ -244,10 +259,34 @@ impl ProxyClient {
             from_hopper: recipient!(addr, ExpiredCoresPackage<ClientRequestPayload_0v1>),
             inbound_server_data: recipient!(addr, InboundServerData),
             dns_resolve_failed: recipient!(addr, DnsResolveFailure_0v1),
+            config_change_msg_sub: recipient!(addr, ConfigChangeMsg),
             node_from_ui: recipient!(addr, NodeFromUiMessage),
         }
     }
 
+    fn handle_new_main_cryptde(&mut self, new_cryptde: Box<dyn CryptDE>) {
+        self.cryptde_pair.main = new_cryptde;
+        info!(
+            self.logger,
+            "Responses are now sent with main key {}",
+            self.cryptde_pair.main.public_key()
+        );
+    }
+
+    fn handle_new_rate_pack(&mut self, rate_pack: RatePack) {
+        self.exit_service_rate = rate_pack.exit_service_rate;
+        self.exit_byte_rate = rate_pack.exit_byte_rate;
+        if let Some(pool) = self.pool.as_ref() {
+            pool.update_exit_rates(self.exit_service_rate, self.exit_byte_rate)
+        }
+        info!(
+            self.logger,
+            "Exit rates have been updated: {} per service, {} per byte",
+            self.exit_service_rate,
+            self.exit_byte_rate
+        );
+    }
+
     fn send_response_to_hopper(
         &self,
         msg: InboundServerData,
@@ -341,6 +380,7 @@ mod tests {
     use crate::sub_lib::accountant::ReportExitServiceProvidedMessage;
     use crate::sub_lib::cryptde::PublicKey;
     use crate::sub_lib::cryptde::{CryptDE, CryptData};
+    use crate::sub_lib::cryptde_null::CryptDENull;
     use crate::sub_lib::dispatcher::Component;
     use crate::sub_lib::hopper::MessageType;
     use crate::sub_lib::proxy_client::ClientResponsePayload_0v1;
@@ -360,6 +400,7 @@ mod tests {
     use lazy_static::lazy_static;
     use masq_lib::blockchains::chains::Chain;
     use masq_lib::test_utils::logging::{init_test_logging, TestLogHandler};
+    use masq_lib::test_utils::utils::TEST_DEFAULT_CHAIN;
     use std::cell::RefCell;
     use std::net::SocketAddr;
     use std::net::{IpAddr, SocketAddrV4};
@@ -384,6 +425,7 @@ mod tests {
 
     pub struct StreamHandlerPoolMock {
         process_package_parameters: Arc<Mutex<Vec<(ClientRequestPayload_0v1, Option<Wallet>)>>>,
+        update_exit_rates_params: Arc<Mutex<Vec<(u64, u64)>>>,
     }
 
     impl StreamHandlerPool for StreamHandlerPoolMock {
@@ -397,15 +439,28 @@ mod tests {
                 .unwrap()
                 .push((payload, paying_wallet));
         }
+
+        fn update_exit_rates(&self, exit_service_rate: u64, exit_byte_rate: u64) {
+            self.update_exit_rates_params
+                .lock()
+                .unwrap()
+                .push((exit_service_rate, exit_byte_rate));
+        }
     }
 
     impl StreamHandlerPoolMock {
         pub fn new() -> StreamHandlerPoolMock {
             StreamHandlerPoolMock {
                 process_package_parameters: Arc::new(Mutex::new(vec![])),
+                update_exit_rates_params: Arc::new(Mutex::new(vec![])),
             }
         }
 
+        pub fn update_exit_rates_params(mut self, params: &Arc<Mutex<Vec<(u64, u64)>>>) -> Self {
+            self.update_exit_rates_params = params.clone();
+            self
+        }
+
         pub fn process_package_parameters(
             self,
             parameters: &mut Arc<Mutex<Vec<(ClientRequestPayload_0v1, Option<Wallet>)>>>,
@@ -603,6 +658,81 @@ mod tests {
         assert_eq!(resolver_wrapper_new_parameters.is_empty(), true);
     }
 
+    #[test]
+    fn new_rate_pack_updates_exit_rates() {
+        init_test_logging();
+        let system = System::new("new_rate_pack_updates_exit_rates");
+        let update_exit_rates_params_arc = Arc::new(Mutex::new(vec![]));
+        let pool =
+            StreamHandlerPoolMock::new().update_exit_rates_params(&update_exit_rates_params_arc);
+        let pool_factory = StreamHandlerPoolFactoryMock::new().make_result(Box::new(pool));
+        let mut subject = ProxyClient::new(ProxyClientConfig {
+            cryptde_pair: CRYPTDE_PAIR.clone(),
+            dns_servers: dnss(),
+            exit_service_rate: 100,
+            exit_byte_rate: 200,
+            is_decentralized: true,
+            crashable: false,
+        });
+        subject.resolver_wrapper_factory = Box::new(
+            ResolverWrapperFactoryMock::new().new_result(Box::new(ResolverWrapperMock::new())),
+        );
+        subject.stream_handler_pool_factory = Box::new(pool_factory);
+        let subject_addr: Addr<ProxyClient> = subject.start();
+        subject_addr
+            .try_send(BindMessage {
+                peer_actors: peer_actors_builder().build(),
+            })
+            .unwrap();
+
+        subject_addr
+            .try_send(ConfigChangeMsg {
+                change: ConfigChange::UpdateRatePack(RatePack {
+                    routing_byte_rate: 1,
+                    routing_service_rate: 2,
+                    exit_byte_rate: 3,
+                    exit_service_rate: 4,
+                }),
+            })
+            .unwrap();
+
+        System::current().stop();
+        system.run();
+        let update_exit_rates_params = update_exit_rates_params_arc.lock().unwrap();
+        assert_eq!(*update_exit_rates_params, vec![(4, 3)]);
+        TestLogHandler::new().exists_log_containing(
+            "INFO: ProxyClient: Exit rates have been updated: 4 per service, 3 per byte",
+        );
+    }
+
+    #[test]
+    fn new_main_cryptde_is_used_from_then_on() {
+        init_test_logging();
+        let system = System::new("new_main_cryptde_is_used_from_then_on");
+        let subject = ProxyClient::new(ProxyClientConfig {
+            cryptde_pair: CRYPTDE_PAIR.clone(),
+            dns_servers: dnss(),
+            exit_service_rate: 100,
+            exit_byte_rate: 200,
+            is_decentralized: true,
+            crashable: false,
+        });
+        let subject_addr: Addr<ProxyClient> = subject.start();
+        let new_cryptde = CryptDENull::from(&PublicKey::new(&[1, 2, 3, 4]), TEST_DEFAULT_CHAIN);
+
+        subject_addr
+            .try_send(ConfigChangeMsg {
+                change: ConfigChange::UpdateMainCryptDE(MainCryptDE(Box::new(new_cryptde))),
+            })
+            .unwrap();
+
+        System::current().stop();
+        system.run();
+        TestLogHandler::new().exists_log_containing(
+            "INFO: ProxyClient: Responses are now sent with main key AQIDBA",
+        );
+    }
+
     #[test]
     #[should_panic(expected = "StreamHandlerPool unbound")]
     fn panics_if_unbound() {
@@ -1201,6 +1331,7 @@ mod tests {
         let pool_factory = StreamHandlerPoolFactoryMock::new().make_result(Box::new(pool));
         let old_return_route = Route {
             hops: vec![CryptData::new(&[1, 2, 3, 4])],
+            top_hop_key_id_opt: None,
         };
         let new_return_route = make_meaningless_route(&CRYPTDE_PAIR);
         let originator_public_key = PublicKey::new(&[4, 3, 2, 1]);
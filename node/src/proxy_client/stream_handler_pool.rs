@@ -33,6 +33,7 @@ use trust_dns_resolver::lookup_ip::LookupIp;
 // which, unlike this, is an actor.
 pub trait StreamHandlerPool {
     fn process_package(&self, payload: ClientRequestPayload_0v1, paying_wallet_opt: Option<Wallet>);
+    fn update_exit_rates(&self, exit_service_rate: u64, exit_byte_rate: u64);
 }
 
 #[derive(Debug)]
@@ -67,6 +68,12 @@ impl StreamHandlerPool for StreamHandlerPoolReal {
         self.do_housekeeping();
         Self::process_package(payload, paying_wallet_opt, self.inner.clone())
     }
+
+    fn update_exit_rates(&self, exit_service_rate: u64, exit_byte_rate: u64) {
+        let mut inner = self.inner.lock().expect("Stream handler pool is poisoned");
+        inner.exit_service_rate = exit_service_rate;
+        inner.exit_byte_rate = exit_byte_rate;
+    }
 }
 
 type StreamEstablisherResult =
@@ -754,6 +761,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn update_exit_rates_replaces_the_rates_charged_for_exit_service() {
+        let _system = System::new("update_exit_rates_replaces_the_rates_charged_for_exit_service");
+        let peer_actors = peer_actors_builder().build();
+        let subject = StreamHandlerPoolReal::new(
+            Box::new(ResolverWrapperMock::new()),
+            CRYPTDE_PAIR.main.as_ref(),
+            peer_actors.accountant.report_exit_service_provided.clone(),
+            peer_actors.proxy_client_opt.unwrap().clone(),
+            100,
+            200,
+        );
+
+        subject.update_exit_rates(300, 400);
+
+        let inner = subject.inner.lock().unwrap();
+        assert_eq!(inner.exit_service_rate, 300);
+        assert_eq!(inner.exit_byte_rate, 400);
+    }
+
     #[test]
     fn non_terminal_payload_can_be_sent_over_existing_connection() {
         let cryptde = CRYPTDE_PAIR.main.as_ref();
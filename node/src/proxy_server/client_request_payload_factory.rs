@@ -1,9 +1,10 @@
 // Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+use crate::proxy_server::http_header_scrubber::HttpHeaderScrubber;
 use crate::proxy_server::protocol_pack::from_ibcd;
 use crate::sub_lib::cryptde::CryptDE;
 use crate::sub_lib::cryptde::PlainData;
 use crate::sub_lib::dispatcher::InboundClientData;
-use crate::sub_lib::proxy_server::ClientRequestPayload_0v1;
+use crate::sub_lib::proxy_server::{ClientRequestPayload_0v1, HeaderScrubbing, ProxyProtocol};
 use crate::sub_lib::sequence_buffer::SequencedPacket;
 use crate::sub_lib::stream_key::StreamKey;
 use masq_lib::logger::Logger;
@@ -19,7 +20,9 @@ pub trait ClientRequestPayloadFactory {
 }
 
 #[derive(Default)]
-pub struct ClientRequestPayloadFactoryReal {}
+pub struct ClientRequestPayloadFactoryReal {
+    header_scrubber: HttpHeaderScrubber,
+}
 
 impl ClientRequestPayloadFactory for ClientRequestPayloadFactoryReal {
     fn make(
@@ -47,10 +50,25 @@ impl ClientRequestPayloadFactory for ClientRequestPayloadFactoryReal {
             Some(host) => (Some(host.name), host.port),
             None => (None, protocol_pack.standard_port()),
         };
+        let request_data = match protocol_pack.proxy_protocol() {
+            ProxyProtocol::HTTP => self.header_scrubber.scrub(&ibcd.data),
+            ProxyProtocol::TLS => None,
+        }
+        .map(|scrubbed_data| {
+            debug!(
+                logger,
+                "Scrubbed identifying headers from {}-byte request for {:?}: {} bytes remain",
+                ibcd.data.len(),
+                target_hostname_opt,
+                scrubbed_data.len()
+            );
+            scrubbed_data
+        })
+        .unwrap_or_else(|| ibcd.data.clone());
         Some(ClientRequestPayload_0v1 {
             stream_key,
             sequenced_packet: SequencedPacket {
-                data: ibcd.data.clone(),
+                data: request_data,
                 sequence_number,
                 last_data: ibcd.last_data,
             },
@@ -66,15 +84,20 @@ impl ClientRequestPayloadFactoryReal {
     pub fn new() -> Self {
         Self::default()
     }
+
+    pub fn with_header_scrubbing(header_scrubbing: HeaderScrubbing) -> Self {
+        Self {
+            header_scrubber: HttpHeaderScrubber::new(header_scrubbing),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::bootstrapper::CryptDEPair;
-    use crate::sub_lib::proxy_server::ProxyProtocol;
     use lazy_static::lazy_static;
-    use masq_lib::constants::HTTP_PORT;
+    use masq_lib::constants::{HTTP_PORT, TLS_PORT};
     use masq_lib::test_utils::logging::init_test_logging;
     use masq_lib::test_utils::logging::TestLogHandler;
     use std::net::SocketAddr;
@@ -340,6 +363,76 @@ mod tests {
         assert_eq!(result.sequenced_packet.sequence_number, 1);
     }
 
+    #[test]
+    fn scrubs_identifying_headers_from_http_requests_when_asked_to() {
+        init_test_logging();
+        let test_name = "scrubs_identifying_headers_from_http_requests_when_asked_to";
+        let ibcd = InboundClientData {
+            timestamp: SystemTime::now(),
+            client_addr: SocketAddr::from_str("1.2.3.4:5678").unwrap(),
+            reception_port: Some(HTTP_PORT),
+            sequence_number: Some(1),
+            last_data: false,
+            is_clandestine: false,
+            data: b"GET /fleebs.html HTTP/1.1\r\nHost: borkoed.com\r\nVia: 1.1 proxy\r\n\r\n"
+                .to_vec(),
+        };
+        let cryptde = CRYPTDE_PAIR.main.as_ref();
+        let logger = Logger::new(test_name);
+        let subject = ClientRequestPayloadFactoryReal::with_header_scrubbing(
+            HeaderScrubbing::from_str("on").unwrap(),
+        );
+
+        let result = subject
+            .make(
+                &ibcd,
+                StreamKey::make_meaningless_stream_key(),
+                cryptde,
+                &logger,
+            )
+            .unwrap();
+
+        assert_eq!(
+            result.sequenced_packet.data,
+            b"GET /fleebs.html HTTP/1.1\r\nHost: borkoed.com\r\n\r\n".to_vec()
+        );
+        assert_eq!(result.target_hostname, Some("borkoed.com".to_string()));
+        TestLogHandler::new().exists_log_containing(&format!(
+            "DEBUG: {test_name}: Scrubbed identifying headers from 64-byte request for \
+             Some(\"borkoed.com\"): 48 bytes remain"
+        ));
+    }
+
+    #[test]
+    fn leaves_tls_records_alone_even_when_scrubbing_headers() {
+        let data = b"GET / HTTP/1.1\r\nVia: 1.1 proxy\r\n\r\n".to_vec();
+        let ibcd = InboundClientData {
+            timestamp: SystemTime::now(),
+            client_addr: SocketAddr::from_str("1.2.3.4:5678").unwrap(),
+            reception_port: Some(TLS_PORT),
+            sequence_number: Some(1),
+            last_data: false,
+            is_clandestine: false,
+            data: data.clone(),
+        };
+        let cryptde = CRYPTDE_PAIR.main.as_ref();
+        let logger = Logger::new("test");
+        let subject = ClientRequestPayloadFactoryReal::with_header_scrubbing(
+            HeaderScrubbing::from_str("on").unwrap(),
+        );
+
+        let result = subject
+            .make(
+                &ibcd,
+                StreamKey::make_meaningless_stream_key(),
+                cryptde,
+                &logger,
+            )
+            .unwrap();
+
+        assert_eq!(result.sequenced_packet.data, data);
+    }
+
     #[test]
     fn makes_no_payload_if_sequence_number_is_unknown() {
         init_test_logging();
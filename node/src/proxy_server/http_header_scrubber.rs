@@ -0,0 +1,281 @@
+// Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+use crate::sub_lib::proxy_server::HeaderScrubbing;
+use masq_lib::utils::index_of;
+
+// Enough for servers to send ordinary pages, but nothing about the browser or the machine it runs on
+pub const GENERIC_USER_AGENT: &str = "Mozilla/5.0";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HttpHeaderScrubber {
+    header_scrubbing: HeaderScrubbing,
+}
+
+impl HttpHeaderScrubber {
+    pub fn new(header_scrubbing: HeaderScrubbing) -> Self {
+        Self { header_scrubbing }
+    }
+
+    // Only the head of a request is rewritten. The HttpPacketFramer hands over the chunks of a
+    // chunked body as frames of their own, and an unchunked body follows the head untouched, so
+    // bodies and the Content-Length that describes them survive byte for byte. Returns None if
+    // the data isn't the head of a request or there was nothing to scrub.
+    pub fn scrub(&self, data: &[u8]) -> Option<Vec<u8>> {
+        if self.header_scrubbing.is_off() {
+            return None;
+        }
+        let body_start = index_of(data, b"\r\n\r\n")? + 4;
+        let head = std::str::from_utf8(&data[..(body_start - 4)]).ok()?;
+        let mut lines = head.split("\r\n");
+        let request_line = lines.next()?;
+        if !Self::is_request_line(request_line) {
+            return None;
+        }
+        let headers = lines
+            .map(|line| {
+                line.split_once(':')
+                    .filter(|(name, _)| !name.is_empty() && !name.starts_with([' ', '\t']))
+                    .map(|(name, value)| (line, name, value.trim()))
+            })
+            .collect::<Option<Vec<(&str, &str, &str)>>>()?;
+        let cross_site = Self::is_cross_site(request_line, &headers);
+        let mut scrubbed_lines = vec![request_line.to_string()];
+        let mut scrubbed_count = 0;
+        for (line, name, value) in headers {
+            let scrubbed_line_opt = match name.to_lowercase().as_str() {
+                _ if !self.header_scrubbing.scrubs(name) => Some(line.to_string()),
+                "referer" => Self::origin_of(value).map(|origin| format!("{}: {}", name, origin)),
+                "user-agent" => Some(format!("{}: {}", name, GENERIC_USER_AGENT)),
+                "cookie" if !cross_site => Some(line.to_string()),
+                _ => None,
+            };
+            if scrubbed_line_opt.as_deref() != Some(line) {
+                scrubbed_count += 1;
+            }
+            scrubbed_lines.extend(scrubbed_line_opt);
+        }
+        if scrubbed_count == 0 {
+            return None;
+        }
+        let mut scrubbed = scrubbed_lines.join("\r\n").into_bytes();
+        scrubbed.extend_from_slice(b"\r\n\r\n");
+        scrubbed.extend_from_slice(&data[body_start..]);
+        Some(scrubbed)
+    }
+
+    fn is_request_line(line: &str) -> bool {
+        let parts = line.split(' ').collect::<Vec<&str>>();
+        parts.len() == 3 && !parts[0].is_empty() && parts[2].starts_with("HTTP/")
+    }
+
+    // A request is made on behalf of some other site if the page that made it came from a host
+    // that is neither the target host nor one of its parent or child domains
+    fn is_cross_site(request_line: &str, headers: &[(&str, &str, &str)]) -> bool {
+        let header_value_opt = |wanted: &str| {
+            headers
+                .iter()
+                .find(|(_, name, _)| name.eq_ignore_ascii_case(wanted))
+                .map(|(_, _, value)| *value)
+        };
+        let target_host_opt = header_value_opt("host").map(Self::hostname_of).or_else(|| {
+            request_line
+                .split(' ')
+                .nth(1)
+                .and_then(Self::authority_of)
+                .map(Self::hostname_of)
+        });
+        let requesting_host_opt = header_value_opt("origin")
+            .filter(|origin| *origin != "null")
+            .or_else(|| header_value_opt("referer"))
+            .and_then(Self::authority_of)
+            .map(Self::hostname_of);
+        match (target_host_opt, requesting_host_opt) {
+            (Some(target_host), Some(requesting_host)) => {
+                target_host != requesting_host
+                    && !target_host.ends_with(&format!(".{}", requesting_host))
+                    && !requesting_host.ends_with(&format!(".{}", target_host))
+            }
+            _ => false,
+        }
+    }
+
+    fn origin_of(url: &str) -> Option<String> {
+        let (scheme, _) = url.split_once("://")?;
+        Self::authority_of(url).map(|authority| format!("{}://{}/", scheme, authority))
+    }
+
+    fn authority_of(url: &str) -> Option<&str> {
+        let (scheme, rest) = url.split_once("://")?;
+        let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+        if scheme.is_empty() || authority.is_empty() {
+            None
+        } else {
+            Some(authority)
+        }
+    }
+
+    fn hostname_of(authority: &str) -> String {
+        let host_and_port = authority.rsplit('@').next().unwrap_or(authority);
+        let hostname = match host_and_port.strip_prefix('[') {
+            Some(bracketed) => bracketed.split(']').next().unwrap_or(bracketed),
+            None => host_and_port.split(':').next().unwrap_or(host_and_port),
+        };
+        hostname.to_lowercase()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn scrub(header_scrubbing: &str, request: &str) -> Option<String> {
+        let subject = HttpHeaderScrubber::new(HeaderScrubbing::from_str(header_scrubbing).unwrap());
+
+        subject
+            .scrub(request.as_bytes())
+            .map(|scrubbed| String::from_utf8(scrubbed).unwrap())
+    }
+
+    #[test]
+    fn constants_have_correct_values() {
+        assert_eq!(GENERIC_USER_AGENT, "Mozilla/5.0");
+    }
+
+    #[test]
+    fn does_nothing_when_scrubbing_is_off() {
+        let request = "GET / HTTP/1.1\r\nHost: example.com\r\nVia: 1.1 proxy\r\n\r\n";
+
+        let result = scrub("off", request);
+
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn strips_and_normalizes_identifying_headers_but_leaves_the_rest_alone() {
+        let request = "GET /page.html HTTP/1.1\r\n\
+            Host: www.example.com\r\n\
+            X-Forwarded-For: 192.168.0.5\r\n\
+            Via: 1.1 corporate-proxy\r\n\
+            Referer: https://www.example.com/private/path?user=bob\r\n\
+            User-Agent: Mozilla/5.0 (X11; Linux x86_64; rv:128.0) Gecko/20100101 Firefox/128.0\r\n\
+            Accept: text/html\r\n\
+            Cookie: session=1234\r\n\
+            \r\n";
+
+        let result = scrub("on", request);
+
+        assert_eq!(
+            result,
+            Some(
+                "GET /page.html HTTP/1.1\r\n\
+                Host: www.example.com\r\n\
+                Referer: https://www.example.com/\r\n\
+                User-Agent: Mozilla/5.0\r\n\
+                Accept: text/html\r\n\
+                Cookie: session=1234\r\n\
+                \r\n"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn strips_cookies_from_requests_made_on_behalf_of_another_site() {
+        let request = "GET http://tracker.com/pixel.gif HTTP/1.1\r\n\
+            Referer: https://news.com/story\r\n\
+            Cookie: id=5678\r\n\
+            \r\n";
+
+        let result = scrub("cookie", request);
+
+        assert_eq!(
+            result,
+            Some(
+                "GET http://tracker.com/pixel.gif HTTP/1.1\r\n\
+                Referer: https://news.com/story\r\n\
+                \r\n"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn keeps_cookies_for_requests_from_the_same_site_or_its_subdomains() {
+        vec![
+            "Origin: https://example.com\r\n",
+            "Referer: https://www.example.com:8443/page\r\n",
+            "",
+        ]
+        .into_iter()
+        .for_each(|requester| {
+            let request = format!(
+                "POST /login HTTP/1.1\r\nHost: example.com:80\r\n{}Cookie: id=5678\r\n\r\n",
+                requester
+            );
+
+            let result = scrub("cookie", &request);
+
+            assert_eq!(result, None, "{}", requester);
+        });
+    }
+
+    #[test]
+    fn scrubs_only_the_head_so_the_body_and_its_length_survive() {
+        let request = "POST /form HTTP/1.1\r\n\
+            Host: example.com\r\n\
+            Content-Length: 17\r\n\
+            X-Client-Id: abc\r\n\
+            \r\n\
+            X-Client-Id: abc\n";
+
+        let result = scrub("x-client-id", request);
+
+        assert_eq!(
+            result,
+            Some(
+                "POST /form HTTP/1.1\r\n\
+                Host: example.com\r\n\
+                Content-Length: 17\r\n\
+                \r\n\
+                X-Client-Id: abc\n"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn leaves_chunks_of_a_chunked_body_alone() {
+        let chunk = "1A\r\nVia: not really a header\r\n\r\n";
+
+        let result = scrub("on", chunk);
+
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn leaves_incomplete_or_malformed_heads_alone() {
+        vec![
+            "GET / HTTP/1.1\r\nVia: 1.1 proxy\r\n",
+            "GET / HTTP/1.1\r\nVia: 1.1 proxy\r\n folded continuation\r\n\r\n",
+            "GET / HTTP/1.1\r\nVia 1.1 proxy\r\n\r\n",
+        ]
+        .into_iter()
+        .for_each(|request| {
+            let result = scrub("on", request);
+
+            assert_eq!(result, None, "{:?}", request);
+        });
+    }
+
+    #[test]
+    fn drops_a_referer_that_has_no_origin() {
+        let request = "GET / HTTP/1.1\r\nHost: example.com\r\nReferer: /relative/path\r\n\r\n";
+
+        let result = scrub("referer", request);
+
+        assert_eq!(
+            result,
+            Some("GET / HTTP/1.1\r\nHost: example.com\r\n\r\n".to_string())
+        );
+    }
+}
@@ -1,6 +1,7 @@
 // Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 
 pub mod client_request_payload_factory;
+pub mod http_header_scrubber;
 pub mod http_protocol_pack;
 pub mod protocol_pack;
 pub mod server_impersonator_http;
@@ -25,6 +26,9 @@ use crate::sub_lib::dispatcher::InboundClientData;
 use crate::sub_lib::dispatcher::{Endpoint, StreamShutdownMsg};
 use crate::sub_lib::hopper::{ExpiredCoresPackage, IncipientCoresPackage};
 use crate::sub_lib::neighborhood::RouteQueryResponse;
+use crate::sub_lib::neighborhood::{
+    retired_key_is_still_honored, ConfigChange, ConfigChangeMsg, MainCryptDE,
+};
 use crate::sub_lib::neighborhood::{ExpectedService, UpdateNodeRecordMetadataMessage};
 use crate::sub_lib::neighborhood::{ExpectedServices, RatePack};
 use crate::sub_lib::neighborhood::{NRMetadataChange, RouteQueryMessage};
@@ -33,7 +37,7 @@ use crate::sub_lib::proxy_client::{ClientResponsePayload_0v1, DnsResolveFailure_
 use crate::sub_lib::proxy_server::ProxyServerSubs;
 use crate::sub_lib::proxy_server::{AddReturnRouteMessage, StreamKeyPurge};
 use crate::sub_lib::proxy_server::{
-    AddRouteResultMessage, ClientRequestPayload_0v1, ProxyProtocol,
+    AddRouteResultMessage, ClientRequestPayload_0v1, HeaderScrubbing, ProxyProtocol,
 };
 use crate::sub_lib::route::Route;
 use crate::sub_lib::stream_handler_pool::TransmitDataMsg;
@@ -87,6 +91,7 @@ pub struct ProxyServer {
     is_decentralized: bool,
     consuming_wallet_balance: Option<i64>,
     cryptde_pair: CryptDEPair,
+    retired_main_cryptde_opt: Option<(Box<dyn CryptDE>, SystemTime)>,
     crashable: bool,
     logger: Logger,
     route_ids_to_return_routes: TtlHashMap<u32, AddReturnRouteMessage>,
@@ -229,6 +234,17 @@ impl Handler<NodeFromUiMessage> for ProxyServer {
     }
 }
 
+impl Handler<ConfigChangeMsg> for ProxyServer {
+    type Result = ();
+
+    fn handle(&mut self, msg: ConfigChangeMsg, _ctx: &mut Self::Context) -> Self::Result {
+        if let ConfigChange::UpdateMainCryptDE(MainCryptDE(new_cryptde)) = msg.change {
+            let retired_cryptde = std::mem::replace(&mut self.cryptde_pair.main, new_cryptde);
+            self.retired_main_cryptde_opt = Some((retired_cryptde, SystemTime::now()));
+        }
+    }
+}
+
 impl Handler<StreamKeyPurge> for ProxyServer {
     type Result = ();
 
@@ -268,6 +284,7 @@ impl ProxyServer {
             is_decentralized,
             consuming_wallet_balance,
             cryptde_pair,
+            retired_main_cryptde_opt: None,
             crashable,
             logger: Logger::new("ProxyServer"),
             route_ids_to_return_routes: TtlHashMap::new(RETURN_ROUTE_TTL),
@@ -278,6 +295,12 @@ impl ProxyServer {
         }
     }
 
+    pub fn scrub_headers(&mut self, header_scrubbing: HeaderScrubbing) {
+        self.client_request_payload_factory = Box::new(
+            ClientRequestPayloadFactoryReal::with_header_scrubbing(header_scrubbing),
+        );
+    }
+
     pub fn make_subs_from(addr: &Addr<ProxyServer>) -> ProxyServerSubs {
         ProxyServerSubs {
             bind: recipient!(addr, BindMessage),
@@ -289,6 +312,7 @@ impl ProxyServer {
             node_from_ui: recipient!(addr, NodeFromUiMessage),
             route_result_sub: recipient!(addr, AddRouteResultMessage),
             schedule_stream_key_purge: recipient!(addr, MessageScheduler<StreamKeyPurge>),
+            config_change_msg_sub: recipient!(addr, ConfigChangeMsg),
         }
     }
 
@@ -739,7 +763,12 @@ impl ProxyServer {
                 add_return_route_sub
                     .try_send(return_route_info)
                     .expect("ProxyServer is dead");
-                ProxyServer::transmit_to_hopper(args, route_query_response.route, over)
+                ProxyServer::transmit_to_hopper(
+                    args,
+                    route_query_response.route,
+                    over,
+                    route_query_response.payload_session_key_opt,
+                )
             }
             _ => panic!("Expected RoundTrip ExpectedServices but got OneWay"),
         }
@@ -811,6 +840,7 @@ impl ProxyServer {
         args: TransmitToHopperArgs,
         route: Route,
         expected_services: Vec<ExpectedService>,
+        payload_session_key_opt: Option<PublicKey>,
     ) -> Result<(), String> {
         let logger = args.logger;
         let destination_key_opt = if args.is_decentralized {
@@ -840,11 +870,16 @@ impl ProxyServer {
                 let payload = args.payload;
                 let payload_size = payload.sequenced_packet.data.len();
                 let stream_key = payload.stream_key;
+                // The exit Node decrypts the payload with the key its hop was encrypted to
+                let payload_encryption_key = match payload_session_key_opt {
+                    Some(session_key) if args.is_decentralized => session_key,
+                    _ => payload_destination_key,
+                };
                 let pkg = IncipientCoresPackage::new(
                     args.main_cryptde.as_ref(),
                     route,
                     payload.into(),
-                    &payload_destination_key,
+                    &payload_encryption_key,
                 )
                 .expect("Key magically disappeared");
                 if args.is_decentralized {
@@ -929,11 +964,12 @@ impl ProxyServer {
         remaining_route: &Route,
         source: &str,
     ) -> Option<Rc<AddReturnRouteMessage>> {
+        let cryptde = self.return_route_cryptde(remaining_route);
         let mut mut_remaining_route = remaining_route.clone();
         mut_remaining_route
-            .shift(self.cryptde_pair.main.as_ref())
+            .shift(cryptde)
             .expect("Internal error: remaining route in ProxyServer with no hops");
-        let return_route_id = match mut_remaining_route.id(self.cryptde_pair.main.as_ref()) {
+        let return_route_id = match mut_remaining_route.id(cryptde) {
             Ok(rri) => rri,
             Err(e) => {
                 error!(self.logger, "Can't report services consumed: {}", e);
@@ -949,6 +985,21 @@ impl ProxyServer {
         }
     }
 
+    // Responses to requests sent before a key rotation come back on return routes encrypted for
+    // the retired key; during the grace period they're still decrypted with it.
+    fn return_route_cryptde(&self, remaining_route: &Route) -> &dyn CryptDE {
+        let main_cryptde = self.cryptde_pair.main.as_ref();
+        match self.retired_main_cryptde_opt.as_ref() {
+            Some((retired_cryptde, retired_at))
+                if retired_key_is_still_honored(*retired_at)
+                    && remaining_route.clone().shift(main_cryptde).is_err() =>
+            {
+                retired_cryptde.as_ref()
+            }
+            _ => main_cryptde,
+        }
+    }
+
     fn report_response_services_consumed(
         &self,
         return_route_info: &AddReturnRouteMessage,
@@ -1364,10 +1415,12 @@ mod tests {
     use crate::sub_lib::cryptde::{decodex, CryptData};
     use crate::sub_lib::cryptde::{encodex, PlainData};
     use crate::sub_lib::cryptde_null::CryptDENull;
+    use crate::sub_lib::cryptde_real::CryptDEReal;
     use crate::sub_lib::dispatcher::Component;
     use crate::sub_lib::hop::LiveHop;
     use crate::sub_lib::hopper::MessageType;
     use crate::sub_lib::neighborhood::ExpectedServices;
+    use crate::sub_lib::neighborhood::RETIRED_KEY_GRACE_PERIOD_SECS;
     use crate::sub_lib::neighborhood::{ExpectedService, DEFAULT_RATE_PACK};
     use crate::sub_lib::proxy_client::{ClientResponsePayload_0v1, DnsResolveFailure_0v1};
     use crate::sub_lib::proxy_server::ClientRequestPayload_0v1;
@@ -1558,6 +1611,7 @@ mod tests {
             .unwrap();
         Route {
             hops: vec![cover_hop, id_hop],
+            top_hop_key_id_opt: None,
         }
     }
 
@@ -1569,6 +1623,7 @@ mod tests {
                 public_key: cryptde.public_key().clone(),
                 payer: None,
                 component: Component::ProxyServer,
+                session_key_opt: None,
             },
         )
         .unwrap()
@@ -1636,12 +1691,16 @@ mod tests {
         let (neighborhood_mock, _, neighborhood_recording_arc) = make_recorder();
         let destination_key = PublicKey::from(&b"our destination"[..]);
         let neighborhood_mock = neighborhood_mock.route_query_response(Some(RouteQueryResponse {
-            route: Route { hops: vec![] },
+            route: Route {
+                hops: vec![],
+                top_hop_key_id_opt: None,
+            },
             expected_services: ExpectedServices::RoundTrip(
                 vec![make_exit_service_from_key(destination_key.clone())],
                 vec![],
                 1234,
             ),
+            payload_session_key_opt: None,
         }));
         let (proxy_server_mock, _, proxy_server_recording_arc) = make_recorder();
         let socket_addr = SocketAddr::from_str("1.2.3.4:5678").unwrap();
@@ -1657,7 +1716,10 @@ mod tests {
             data: expected_data.clone(),
         };
         let expected_http_request = PlainData::new(http_request);
-        let route = Route { hops: vec![] };
+        let route = Route {
+            hops: vec![],
+            top_hop_key_id_opt: None,
+        };
         let expected_payload = ClientRequestPayload_0v1 {
             stream_key: stream_key.clone(),
             sequenced_packet: SequencedPacket {
@@ -1739,14 +1801,21 @@ mod tests {
         let (neighborhood_mock, _, neighborhood_recording_arc) = make_recorder();
         let destination_key = PublicKey::from(&b"our destination"[..]);
         let neighborhood_mock = neighborhood_mock.route_query_response(Some(RouteQueryResponse {
-            route: Route { hops: vec![] },
+            route: Route {
+                hops: vec![],
+                top_hop_key_id_opt: None,
+            },
             expected_services: ExpectedServices::RoundTrip(
                 vec![make_exit_service_from_key(destination_key.clone())],
                 vec![],
                 1234,
             ),
+            payload_session_key_opt: None,
         }));
-        let route = Route { hops: vec![] };
+        let route = Route {
+            hops: vec![],
+            top_hop_key_id_opt: None,
+        };
         let (dispatcher_mock, _, dispatcher_recording_arc) = make_recorder();
         let socket_addr = SocketAddr::from_str("1.2.3.4:5678").unwrap();
         let stream_key = StreamKey::make_meaningless_stream_key();
@@ -2353,12 +2422,16 @@ mod tests {
         let hopper_awaiter = hopper_mock.get_awaiter();
         let destination_key = PublicKey::from(&b"our destination"[..]);
         let neighborhood_mock = Recorder::new().route_query_response(Some(RouteQueryResponse {
-            route: Route { hops: vec![] },
+            route: Route {
+                hops: vec![],
+                top_hop_key_id_opt: None,
+            },
             expected_services: ExpectedServices::RoundTrip(
                 vec![make_exit_service_from_key(destination_key.clone())],
                 vec![],
                 1234,
             ),
+            payload_session_key_opt: None,
         }));
         let socket_addr = SocketAddr::from_str("1.2.3.4:5678").unwrap();
         let stream_key = StreamKey::make_meaningless_stream_key();
@@ -2373,7 +2446,10 @@ mod tests {
             data: expected_data.clone(),
         };
         let expected_http_request = PlainData::new(http_request);
-        let route = Route { hops: vec![] };
+        let route = Route {
+            hops: vec![],
+            top_hop_key_id_opt: None,
+        };
         let expected_payload = ClientRequestPayload_0v1 {
             stream_key: stream_key.clone(),
             sequenced_packet: SequencedPacket {
@@ -2478,6 +2554,7 @@ mod tests {
                 ],
                 1234,
             ),
+            payload_session_key_opt: None,
         }));
         let socket_addr = SocketAddr::from_str("1.2.3.4:5678").unwrap();
         let stream_key = StreamKey::make_meaningless_stream_key();
@@ -2556,12 +2633,16 @@ mod tests {
             DEFAULT_RATE_PACK,
         );
         let route_query_response = Some(RouteQueryResponse {
-            route: Route { hops: vec![] },
+            route: Route {
+                hops: vec![],
+                top_hop_key_id_opt: None,
+            },
             expected_services: ExpectedServices::RoundTrip(
                 vec![expected_service.clone()],
                 vec![expected_service],
                 123,
             ),
+            payload_session_key_opt: None,
         });
         let (neighborhood_mock, _, _) = make_recorder();
         let neighborhood_mock =
@@ -2720,12 +2801,16 @@ mod tests {
         let http_request = b"GET /index.html HTTP/1.1\r\nHost: nowhere.com\r\n\r\n";
         let destination_key = PublicKey::from(&b"our destination"[..]);
         let route_query_response = RouteQueryResponse {
-            route: Route { hops: vec![] },
+            route: Route {
+                hops: vec![],
+                top_hop_key_id_opt: None,
+            },
             expected_services: ExpectedServices::RoundTrip(
                 vec![make_exit_service_from_key(destination_key.clone())],
                 vec![],
                 1234,
             ),
+            payload_session_key_opt: None,
         };
         let (hopper_mock, hopper_awaiter, hopper_recording_arc) = make_recorder();
         let socket_addr = SocketAddr::from_str("1.2.3.4:5678").unwrap();
@@ -2754,7 +2839,10 @@ mod tests {
         };
         let expected_pkg = IncipientCoresPackage::new(
             main_cryptde,
-            Route { hops: vec![] },
+            Route {
+                hops: vec![],
+                top_hop_key_id_opt: None,
+            },
             expected_payload.into(),
             &destination_key,
         )
@@ -2844,6 +2932,7 @@ mod tests {
                 ],
                 0,
             ),
+            payload_session_key_opt: None,
         };
         let source_addr = SocketAddr::from_str("1.2.3.4:5678").unwrap();
         let stream_key = StreamKey::make_meaningless_stream_key();
@@ -2935,6 +3024,7 @@ mod tests {
                 vec![ExpectedService::Nothing],
                 0,
             ),
+            payload_session_key_opt: None,
         };
         let source_addr = SocketAddr::from_str("1.2.3.4:5678").unwrap();
         let stream_key = StreamKey::make_meaningless_stream_key();
@@ -3000,6 +3090,72 @@ mod tests {
         assert_eq!(result, Ok(()));
     }
 
+    #[test]
+    fn try_transmit_to_hopper_encrypts_payload_to_the_session_key_of_the_exit_node() {
+        let cryptde = CRYPTDE_PAIR.main.as_ref();
+        let exit_cryptde = CryptDENull::from(&PublicKey::new(b"exit"), TEST_DEFAULT_CHAIN);
+        let session_cryptde =
+            CryptDENull::from(&PublicKey::new(b"exit session"), TEST_DEFAULT_CHAIN);
+        let (hopper_mock, _, hopper_recording_arc) = make_recorder();
+        let route_query_response = RouteQueryResponse {
+            route: make_meaningless_route(&CRYPTDE_PAIR),
+            expected_services: ExpectedServices::RoundTrip(
+                vec![ExpectedService::Exit(
+                    exit_cryptde.public_key().clone(),
+                    make_wallet("exit wallet"),
+                    rate_pack(3),
+                )],
+                vec![ExpectedService::Nothing],
+                0,
+            ),
+            payload_session_key_opt: Some(session_cryptde.public_key().clone()),
+        };
+        let system = System::new(
+            "try_transmit_to_hopper_encrypts_payload_to_the_session_key_of_the_exit_node",
+        );
+        let peer_actors = peer_actors_builder().hopper(hopper_mock).build();
+        let payload = ClientRequestPayload_0v1 {
+            stream_key: StreamKey::make_meaningless_stream_key(),
+            sequenced_packet: SequencedPacket::new(b"request".to_vec(), 0, false),
+            target_hostname: Some("nowhere.com".to_string()),
+            target_port: HTTP_PORT,
+            protocol: ProxyProtocol::HTTP,
+            originator_public_key: PublicKey::new(b"originator_public_key"),
+        };
+        let args = TransmitToHopperArgs {
+            main_cryptde: cryptde.dup(),
+            payload: payload.clone(),
+            client_addr: SocketAddr::from_str("1.2.3.4:5678").unwrap(),
+            timestamp: SystemTime::now(),
+            is_decentralized: true,
+            logger: Logger::new("test"),
+            hopper_sub: peer_actors.hopper.from_hopper_client,
+            dispatcher_sub: peer_actors.dispatcher.from_dispatcher_client,
+            accountant_sub: peer_actors.accountant.report_services_consumed,
+            retire_stream_key_sub_opt: None,
+        };
+
+        let result = ProxyServer::try_transmit_to_hopper(
+            args,
+            peer_actors.proxy_server.add_return_route,
+            route_query_response,
+        );
+
+        System::current().stop();
+        system.run();
+        assert_eq!(result, Ok(()));
+        let hopper_recording = hopper_recording_arc.lock().unwrap();
+        let record = hopper_recording.get_record::<IncipientCoresPackage>(0);
+        let decrypted = decodex::<MessageType>(&session_cryptde, &record.payload).unwrap();
+        assert_eq!(
+            decrypted,
+            MessageType::ClientRequest(VersionedData::new(
+                &crate::sub_lib::migrations::client_request_payload::MIGRATIONS,
+                &payload,
+            ))
+        );
+    }
+
     #[test]
     fn proxy_server_logs_messages_when_routing_services_are_not_requested() {
         init_test_logging();
@@ -3223,6 +3379,7 @@ mod tests {
                     rate_pack(103),
                 ),
             ]),
+            payload_session_key_opt: None,
         };
         let payload = ClientRequestPayload_0v1 {
             stream_key: StreamKey::make_meaningless_stream_key(),
@@ -3310,6 +3467,7 @@ mod tests {
             )
             .unwrap(),
             expected_services: ExpectedServices::RoundTrip(vec![], vec![], 1234),
+            payload_session_key_opt: None,
         };
         let neighborhood_mock = neighborhood_mock.route_query_response(Some(route_query_response));
         let dispatcher = Recorder::new();
@@ -3402,12 +3560,16 @@ mod tests {
         let hopper_awaiter = hopper_mock.get_awaiter();
         let destination_key = PublicKey::from(&b"our destination"[..]);
         let neighborhood_mock = Recorder::new().route_query_response(Some(RouteQueryResponse {
-            route: Route { hops: vec![] },
+            route: Route {
+                hops: vec![],
+                top_hop_key_id_opt: None,
+            },
             expected_services: ExpectedServices::RoundTrip(
                 vec![make_exit_service_from_key(destination_key.clone())],
                 vec![],
                 1234,
             ),
+            payload_session_key_opt: None,
         }));
         let stream_key = StreamKey::make_meaningless_stream_key();
         let socket_addr = SocketAddr::from_str("1.2.3.4:5678").unwrap();
@@ -3422,7 +3584,10 @@ mod tests {
             data: expected_data.clone(),
         };
         let expected_tls_request = PlainData::new(tls_request);
-        let route = Route { hops: vec![] };
+        let route = Route {
+            hops: vec![],
+            top_hop_key_id_opt: None,
+        };
         let expected_payload = ClientRequestPayload_0v1 {
             stream_key: stream_key.clone(),
             sequenced_packet: SequencedPacket {
@@ -3487,12 +3652,16 @@ mod tests {
         let hopper_awaiter = hopper_mock.get_awaiter();
         let destination_key = PublicKey::from(&b"our destination"[..]);
         let neighborhood_mock = Recorder::new().route_query_response(Some(RouteQueryResponse {
-            route: Route { hops: vec![] },
+            route: Route {
+                hops: vec![],
+                top_hop_key_id_opt: None,
+            },
             expected_services: ExpectedServices::RoundTrip(
                 vec![make_exit_service_from_key(destination_key.clone())],
                 vec![],
                 1234,
             ),
+            payload_session_key_opt: None,
         }));
         let stream_key = StreamKey::make_meaningless_stream_key();
         let socket_addr = SocketAddr::from_str("1.2.3.4:5678").unwrap();
@@ -3507,7 +3676,10 @@ mod tests {
             data: expected_data.clone(),
         };
         let expected_tls_request = PlainData::new(tls_request);
-        let route = Route { hops: vec![] };
+        let route = Route {
+            hops: vec![],
+            top_hop_key_id_opt: None,
+        };
         let expected_payload = ClientRequestPayload_0v1 {
             stream_key: stream_key.clone(),
             sequenced_packet: SequencedPacket {
@@ -3571,12 +3743,16 @@ mod tests {
         let hopper_awaiter = hopper_mock.get_awaiter();
         let destination_key = PublicKey::from(&b"our destination"[..]);
         let neighborhood_mock = Recorder::new().route_query_response(Some(RouteQueryResponse {
-            route: Route { hops: vec![] },
+            route: Route {
+                hops: vec![],
+                top_hop_key_id_opt: None,
+            },
             expected_services: ExpectedServices::RoundTrip(
                 vec![make_exit_service_from_key(destination_key.clone())],
                 vec![],
                 1234,
             ),
+            payload_session_key_opt: None,
         }));
         let client_addr = SocketAddr::from_str("1.2.3.4:5678").unwrap();
         let stream_key = StreamKey::make_meaningful_stream_key(test_name);
@@ -3591,7 +3767,10 @@ mod tests {
             data: expected_data.clone(),
         };
         let expected_tls_request = PlainData::new(tls_request);
-        let route = Route { hops: vec![] };
+        let route = Route {
+            hops: vec![],
+            top_hop_key_id_opt: None,
+        };
         let expected_payload = ClientRequestPayload_0v1 {
             stream_key: stream_key.clone(),
             sequenced_packet: SequencedPacket {
@@ -3785,6 +3964,96 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn proxy_server_accepts_responses_on_return_routes_built_for_its_retired_key() {
+        let system = System::new(
+            "proxy_server_accepts_responses_on_return_routes_built_for_its_retired_key",
+        );
+        let (dispatcher, _, dispatcher_recording_arc) = make_recorder();
+        // CryptDENull panics when asked to decrypt with the wrong key, so these need real keys
+        let retired_cryptde = CryptDEReal::new(TEST_DEFAULT_CHAIN);
+        let new_cryptde = CryptDEReal::new(TEST_DEFAULT_CHAIN);
+        let mut subject = ProxyServer::new(
+            CryptDEPair::new(retired_cryptde.dup(), CRYPTDE_PAIR.alias.dup()),
+            true,
+            Some(STANDARD_CONSUMING_WALLET_BALANCE),
+            false,
+            false,
+        );
+        let socket_addr = SocketAddr::from_str("1.2.3.4:5678").unwrap();
+        let stream_key = StreamKey::make_meaningless_stream_key();
+        subject
+            .keys_and_addrs
+            .insert(stream_key.clone(), socket_addr.clone());
+        subject.route_ids_to_return_routes.insert(
+            1234,
+            AddReturnRouteMessage {
+                return_route_id: 1234,
+                expected_services: vec![ExpectedService::Nothing],
+                protocol: ProxyProtocol::TLS,
+                hostname_opt: None,
+            },
+        );
+        let subject_addr: Addr<ProxyServer> = subject.start();
+        let expired_cores_package = ExpiredCoresPackage::new(
+            SocketAddr::from_str("1.2.3.4:1234").unwrap(),
+            Some(make_wallet("consuming")),
+            return_route_with_id(&retired_cryptde, 1234),
+            ClientResponsePayload_0v1 {
+                stream_key,
+                sequenced_packet: SequencedPacket {
+                    data: b"16 bytes of data".to_vec(),
+                    sequence_number: 12345678,
+                    last_data: false,
+                },
+            },
+            0,
+        );
+        let peer_actors = peer_actors_builder().dispatcher(dispatcher).build();
+        subject_addr.try_send(BindMessage { peer_actors }).unwrap();
+        subject_addr
+            .try_send(ConfigChangeMsg {
+                change: ConfigChange::UpdateMainCryptDE(MainCryptDE(Box::new(new_cryptde))),
+            })
+            .unwrap();
+
+        subject_addr.try_send(expired_cores_package).unwrap();
+
+        System::current().stop();
+        system.run();
+        let dispatcher_recording = dispatcher_recording_arc.lock().unwrap();
+        let transmit_data_msg = dispatcher_recording.get_record::<TransmitDataMsg>(0);
+        assert_eq!(transmit_data_msg.endpoint, Endpoint::Socket(socket_addr));
+        assert_eq!(transmit_data_msg.data, b"16 bytes of data".to_vec());
+    }
+
+    #[test]
+    fn return_routes_for_the_retired_key_are_not_decrypted_after_the_grace_period() {
+        let retired_cryptde = CryptDEReal::new(TEST_DEFAULT_CHAIN);
+        let new_cryptde = CryptDEReal::new(TEST_DEFAULT_CHAIN);
+        let new_public_key = new_cryptde.public_key().clone();
+        let mut subject = ProxyServer::new(
+            CryptDEPair::new(Box::new(new_cryptde), CRYPTDE_PAIR.alias.dup()),
+            true,
+            Some(STANDARD_CONSUMING_WALLET_BALANCE),
+            false,
+            false,
+        );
+        let grace_period = Duration::from_secs(RETIRED_KEY_GRACE_PERIOD_SECS as u64);
+        subject.retired_main_cryptde_opt = Some((
+            retired_cryptde.dup(),
+            SystemTime::now() - grace_period - Duration::from_secs(1),
+        ));
+        let remaining_route = return_route_with_id(&retired_cryptde, 1234);
+
+        let result = subject.return_route_cryptde(&remaining_route);
+
+        assert_eq!(result.public_key(), &new_public_key);
+        subject.retired_main_cryptde_opt = Some((retired_cryptde.dup(), SystemTime::now()));
+        let result = subject.return_route_cryptde(&remaining_route);
+        assert_eq!(result.public_key(), retired_cryptde.public_key());
+    }
+
     #[test]
     #[should_panic(expected = "time calculation error")]
     fn log_straggling_packet_panics_if_timestamp_is_wrong() {
@@ -3822,8 +4091,12 @@ mod tests {
         subject.stream_key_routes.insert(
             stream_key.clone(),
             RouteQueryResponse {
-                route: Route { hops: vec![] },
+                route: Route {
+                    hops: vec![],
+                    top_hop_key_id_opt: None,
+                },
                 expected_services: ExpectedServices::RoundTrip(vec![], vec![], 1234),
+                payload_session_key_opt: None,
             },
         );
         subject
@@ -3929,8 +4202,12 @@ mod tests {
         subject.stream_key_routes.insert(
             stream_key.clone(),
             RouteQueryResponse {
-                route: Route { hops: vec![] },
+                route: Route {
+                    hops: vec![],
+                    top_hop_key_id_opt: None,
+                },
                 expected_services: ExpectedServices::RoundTrip(vec![], vec![], 1234),
+                payload_session_key_opt: None,
             },
         );
         subject
@@ -4031,8 +4308,12 @@ mod tests {
         subject.stream_key_routes.insert(
             stream_key.clone(),
             RouteQueryResponse {
-                route: Route { hops: vec![] },
+                route: Route {
+                    hops: vec![],
+                    top_hop_key_id_opt: None,
+                },
                 expected_services: ExpectedServices::RoundTrip(vec![], vec![], 1234),
+                payload_session_key_opt: None,
             },
         );
         subject
@@ -5052,8 +5333,12 @@ mod tests {
         subject.stream_key_routes.insert(
             stream_key.clone(),
             RouteQueryResponse {
-                route: Route { hops: vec![] },
+                route: Route {
+                    hops: vec![],
+                    top_hop_key_id_opt: None,
+                },
                 expected_services: ExpectedServices::OneWay(vec![]),
+                payload_session_key_opt: None,
             },
         );
         subject.route_ids_to_return_routes.insert(
@@ -5187,6 +5472,7 @@ mod tests {
                 expected_services.clone(),
                 1234,
             ),
+            payload_session_key_opt: None,
         };
         let neighborhood_mock = neighborhood_mock
             .system_stop_conditions(match_every_type_id!(RouteQueryMessage))
@@ -5355,6 +5641,7 @@ mod tests {
                 expected_services.clone(),
                 1234,
             ),
+            payload_session_key_opt: None,
         };
         let neighborhood_mock = neighborhood_mock
             .system_stop_conditions(match_every_type_id!(
@@ -5619,6 +5906,7 @@ mod tests {
             Some(make_wallet("irrelevant")),
             Route {
                 hops: vec![make_cover_hop(cryptde), CryptData::new(&[0])],
+                top_hop_key_id_opt: None,
             },
             client_response_payload,
             0,
@@ -5708,8 +5996,12 @@ mod tests {
         subject.stream_key_routes.insert(
             unaffected_stream_key,
             RouteQueryResponse {
-                route: Route { hops: vec![] },
+                route: Route {
+                    hops: vec![],
+                    top_hop_key_id_opt: None,
+                },
                 expected_services: ExpectedServices::RoundTrip(vec![], vec![], 1234),
+                payload_session_key_opt: None,
             },
         );
         subject
@@ -5760,8 +6052,12 @@ mod tests {
         subject.stream_key_routes.insert(
             unaffected_stream_key,
             RouteQueryResponse {
-                route: Route { hops: vec![] },
+                route: Route {
+                    hops: vec![],
+                    top_hop_key_id_opt: None,
+                },
                 expected_services: ExpectedServices::RoundTrip(vec![], vec![], 1234),
+                payload_session_key_opt: None,
             },
         );
         let affected_route = Route::round_trip(
@@ -5799,6 +6095,7 @@ mod tests {
                     vec![],
                     1234,
                 ),
+                payload_session_key_opt: None,
             },
         );
         subject
@@ -5890,8 +6187,12 @@ mod tests {
         subject.stream_key_routes.insert(
             unaffected_stream_key,
             RouteQueryResponse {
-                route: Route { hops: vec![] },
+                route: Route {
+                    hops: vec![],
+                    top_hop_key_id_opt: None,
+                },
                 expected_services: ExpectedServices::RoundTrip(vec![], vec![], 1234),
+                payload_session_key_opt: None,
             },
         );
         let affected_route = Route::round_trip(
@@ -5929,6 +6230,7 @@ mod tests {
                     vec![],
                     1234,
                 ),
+                payload_session_key_opt: None,
             },
         );
         subject.logger = Logger::new(test_name);
@@ -6033,8 +6335,12 @@ mod tests {
         subject.stream_key_routes.insert(
             stream_key,
             RouteQueryResponse {
-                route: Route { hops: vec![] },
+                route: Route {
+                    hops: vec![],
+                    top_hop_key_id_opt: None,
+                },
                 expected_services: ExpectedServices::RoundTrip(vec![], vec![], 0),
+                payload_session_key_opt: None,
             },
         );
         subject
@@ -6201,12 +6507,16 @@ mod tests {
         let (neighborhood_mock, _, _) = make_recorder();
         let destination_key = PublicKey::from(&b"our destination"[..]);
         let neighborhood_mock = neighborhood_mock.route_query_response(Some(RouteQueryResponse {
-            route: Route { hops: vec![] },
+            route: Route {
+                hops: vec![],
+                top_hop_key_id_opt: None,
+            },
             expected_services: ExpectedServices::RoundTrip(
                 vec![make_exit_service_from_key(destination_key.clone())],
                 vec![],
                 1234,
             ),
+            payload_session_key_opt: None,
         }));
         let socket_addr = SocketAddr::from_str("1.2.3.4:5678").unwrap();
         let stream_key = StreamKey::make_meaningless_stream_key();
@@ -6267,12 +6577,16 @@ mod tests {
         let (neighborhood_mock, _, _) = make_recorder();
         let destination_key = PublicKey::from(&b"our destination"[..]);
         let neighborhood_mock = neighborhood_mock.route_query_response(Some(RouteQueryResponse {
-            route: Route { hops: vec![] },
+            route: Route {
+                hops: vec![],
+                top_hop_key_id_opt: None,
+            },
             expected_services: ExpectedServices::RoundTrip(
                 vec![make_exit_service_from_key(destination_key.clone())],
                 vec![],
                 1234,
             ),
+            payload_session_key_opt: None,
         }));
         let socket_addr = SocketAddr::from_str("1.2.3.4:5678").unwrap();
         let stream_key = StreamKey::make_meaningless_stream_key();
@@ -1,11 +1,12 @@
 // Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 
-use crate::apps::{app_config_dumper, app_daemon, app_node};
+use crate::apps::{app_backup_restorer, app_config_dumper, app_daemon, app_node};
 use crate::privilege_drop::{PrivilegeDropper, PrivilegeDropperReal};
 use crate::run_modes::Leaving::{ExitCode, Not};
 use crate::run_modes_factories::{
     DaemonInitializerFactory, DaemonInitializerFactoryReal, DumpConfigRunnerFactory,
-    DumpConfigRunnerFactoryReal, ServerInitializerFactory, ServerInitializerFactoryReal,
+    DumpConfigRunnerFactoryReal, RestoreBackupRunnerFactory, RestoreBackupRunnerFactoryReal,
+    ServerInitializerFactory, ServerInitializerFactoryReal,
 };
 use actix::System;
 use clap::Error;
@@ -18,6 +19,7 @@ use ProgramEntering::{Enter, Leave};
 #[derive(Debug, PartialEq, Eq)]
 enum Mode {
     DumpConfig,
+    RestoreBackup,
     Initialization,
     Service,
 }
@@ -49,6 +51,7 @@ impl RunModes {
 
         match match mode {
             Mode::DumpConfig => self.runner.dump_config(args, streams),
+            Mode::RestoreBackup => self.runner.restore_backup(args, streams),
             Mode::Initialization => self.runner.run_daemon(args, streams),
             Mode::Service => self.runner.run_node(args, streams),
         } {
@@ -87,6 +90,7 @@ impl RunModes {
             true => mode,
         } {
             Mode::DumpConfig => app_config_dumper(),
+            Mode::RestoreBackup => app_backup_restorer(),
             Mode::Initialization => app_daemon(),
             Mode::Service => app_node(),
         }
@@ -195,6 +199,8 @@ impl RunModes {
     fn determine_mode_and_priv_req(&self, args: &[String]) -> (Mode, bool) {
         if args.contains(&"--dump-config".to_string()) {
             (Mode::DumpConfig, false)
+        } else if args.contains(&"--restore-backup".to_string()) {
+            (Mode::RestoreBackup, false)
         } else if args.contains(&"--initialization".to_string()) {
             (Mode::Initialization, true)
         } else {
@@ -223,11 +229,17 @@ trait Runner {
     fn run_node(&self, args: &[String], streams: &mut StdStreams<'_>) -> Result<(), RunnerError>;
     fn dump_config(&self, args: &[String], streams: &mut StdStreams<'_>)
         -> Result<(), RunnerError>;
+    fn restore_backup(
+        &self,
+        args: &[String],
+        streams: &mut StdStreams<'_>,
+    ) -> Result<(), RunnerError>;
     fn run_daemon(&self, args: &[String], streams: &mut StdStreams<'_>) -> Result<(), RunnerError>;
 }
 
 struct RunnerReal {
     dump_config_runner_factory: Box<dyn DumpConfigRunnerFactory>,
+    restore_backup_runner_factory: Box<dyn RestoreBackupRunnerFactory>,
     server_initializer_factory: Box<dyn ServerInitializerFactory>,
     daemon_initializer_factory: Box<dyn DaemonInitializerFactory>,
 }
@@ -257,6 +269,17 @@ impl Runner for RunnerReal {
             .map_err(RunnerError::Configurator)
     }
 
+    fn restore_backup(
+        &self,
+        args: &[String],
+        streams: &mut StdStreams<'_>,
+    ) -> Result<(), RunnerError> {
+        self.restore_backup_runner_factory
+            .make()
+            .go(streams, args)
+            .map_err(RunnerError::Configurator)
+    }
+
     fn run_daemon(&self, args: &[String], streams: &mut StdStreams<'_>) -> Result<(), RunnerError> {
         let mut initializer = self.daemon_initializer_factory.make(args)?;
         initializer.go(streams, args)?;
@@ -268,6 +291,7 @@ impl RunnerReal {
     pub fn new() -> Self {
         Self {
             dump_config_runner_factory: Box::new(DumpConfigRunnerFactoryReal),
+            restore_backup_runner_factory: Box::new(RestoreBackupRunnerFactoryReal),
             server_initializer_factory: Box::new(ServerInitializerFactoryReal),
             daemon_initializer_factory: Box::new(DaemonInitializerFactoryReal::default()),
         }
@@ -285,7 +309,8 @@ mod tests {
     use super::*;
     use crate::run_modes_factories::mocks::{
         DaemonInitializerFactoryMock, DaemonInitializerMock, DumpConfigRunnerFactoryMock,
-        DumpConfigRunnerMock, ServerInitializerFactoryMock, ServerInitializerMock,
+        DumpConfigRunnerMock, RestoreBackupRunnerFactoryMock, RestoreBackupRunnerMock,
+        ServerInitializerFactoryMock, ServerInitializerMock,
     };
     use crate::server_initializer::test_utils::PrivilegeDropperMock;
     use masq_lib::test_utils::fake_stream_holder::FakeStreamHolder;
@@ -301,6 +326,8 @@ mod tests {
         run_node_results: RefCell<Vec<Result<(), RunnerError>>>,
         dump_config_params: Arc<Mutex<Vec<Vec<String>>>>,
         dump_config_results: RefCell<Vec<Result<(), RunnerError>>>,
+        restore_backup_params: Arc<Mutex<Vec<Vec<String>>>>,
+        restore_backup_results: RefCell<Vec<Result<(), RunnerError>>>,
         run_daemon_params: Arc<Mutex<Vec<Vec<String>>>>,
         run_daemon_results: RefCell<Vec<Result<(), RunnerError>>>,
     }
@@ -324,6 +351,18 @@ mod tests {
             self.dump_config_results.borrow_mut().remove(0)
         }
 
+        fn restore_backup(
+            &self,
+            args: &[String],
+            _streams: &mut StdStreams<'_>,
+        ) -> Result<(), RunnerError> {
+            self.restore_backup_params
+                .lock()
+                .unwrap()
+                .push(args.to_vec());
+            self.restore_backup_results.borrow_mut().remove(0)
+        }
+
         fn run_daemon(
             &self,
             args: &[String],
@@ -342,6 +381,8 @@ mod tests {
                 run_node_results: RefCell::new(vec![]),
                 dump_config_params: Arc::new(Mutex::new(vec![])),
                 dump_config_results: RefCell::new(vec![]),
+                restore_backup_params: Arc::new(Mutex::new(vec![])),
+                restore_backup_results: RefCell::new(vec![]),
                 run_daemon_params: Arc::new(Mutex::new(vec![])),
                 run_daemon_results: RefCell::new(vec![]),
             }
@@ -367,6 +408,16 @@ mod tests {
             self
         }
 
+        pub fn restore_backup_params(mut self, params: &Arc<Mutex<Vec<Vec<String>>>>) -> Self {
+            self.restore_backup_params = params.clone();
+            self
+        }
+
+        pub fn restore_backup_result(self, result: Result<(), RunnerError>) -> Self {
+            self.restore_backup_results.borrow_mut().push(result);
+            self
+        }
+
         pub fn run_daemon_params(mut self, params: &Arc<Mutex<Vec<Vec<String>>>>) -> Self {
             self.run_daemon_params = params.clone();
             self
@@ -384,6 +435,26 @@ mod tests {
         check_mode(arg, Mode::DumpConfig, false);
     }
 
+    #[test]
+    fn restore_backup() {
+        let args = slice_of_strs_to_vec_of_strings(&["--restore-backup", "backup.db"]);
+        check_mode(args, Mode::RestoreBackup, false);
+    }
+
+    #[test]
+    fn restore_backup_beats_initialization_but_not_dump_config() {
+        check_mode(
+            slice_of_strs_to_vec_of_strings(&["--initialization", "--restore-backup", "b.db"]),
+            Mode::RestoreBackup,
+            false,
+        );
+        check_mode(
+            slice_of_strs_to_vec_of_strings(&["--restore-backup", "b.db", "--dump-config"]),
+            Mode::DumpConfig,
+            false,
+        );
+    }
+
     #[test]
     fn initialization() {
         let arg = vec!["--initialization".to_string()];
@@ -659,6 +730,59 @@ parm2 - msg2\n"
         assert_eq!(*dump_config_params[0], args)
     }
 
+    #[test]
+    fn restore_backup_hands_in_an_error_from_the_restore_backup_runner() {
+        let restore_backup_params_arc = Arc::new(Mutex::new(vec![]));
+        let mut subject = RunModes::new();
+        let mut runner = RunnerReal::new();
+        runner.restore_backup_runner_factory = Box::new(
+            RestoreBackupRunnerFactoryMock::default().make_result(Box::new(
+                RestoreBackupRunnerMock::default()
+                    .restore_backup_result(Err(ConfiguratorError::required(
+                        "restore-backup",
+                        "too-new",
+                    )))
+                    .restore_backup_params(&restore_backup_params_arc),
+            )),
+        );
+        subject.runner = Box::new(runner);
+        let mut holder = FakeStreamHolder::new();
+        let args = slice_of_strs_to_vec_of_strings(&["program", "--restore-backup", "b.db"]);
+
+        let result = subject.runner.restore_backup(&args, &mut holder.streams());
+
+        assert_eq!(
+            result,
+            Err(RunnerError::Configurator(ConfiguratorError::required(
+                "restore-backup",
+                "too-new"
+            )))
+        );
+        let restore_backup_params = restore_backup_params_arc.lock().unwrap();
+        assert_eq!(*restore_backup_params, vec![args])
+    }
+
+    #[test]
+    fn go_dispatches_restore_backup_mode_to_the_runner() {
+        let mut subject = RunModes::new();
+        let runner_params_arc = Arc::new(Mutex::new(vec![]));
+        subject.runner = Box::new(
+            RunnerMock::new()
+                .restore_backup_params(&runner_params_arc)
+                .restore_backup_result(Ok(())),
+        );
+        subject.privilege_dropper =
+            Box::new(PrivilegeDropperMock::new().expect_privilege_result(true));
+        let mut holder = FakeStreamHolder::new();
+        let args = slice_of_strs_to_vec_of_strings(&["--restore-backup", "b.db"]);
+
+        let result = subject.go(&args, &mut holder.streams());
+
+        assert_eq!(result, 0);
+        let params = runner_params_arc.lock().unwrap();
+        assert_eq!(*params, vec![args]);
+    }
+
     #[test]
     fn daemon_and_node_modes_complain_without_privilege() {
         let mut subject = RunModes::new();
@@ -5,6 +5,7 @@ use crate::daemon::daemon_initializer::{
 };
 use crate::daemon::{ChannelFactory, ChannelFactoryReal};
 use crate::database::config_dumper::DumpConfigRunnerReal;
+use crate::database::db_backup::RestoreBackupRunnerReal;
 use crate::node_configurator::node_configurator_initialization::{
     InitializationConfig, NodeConfiguratorInitializationReal,
 };
@@ -20,6 +21,7 @@ use std::cell::RefCell;
 pub type RunModeResult = Result<(), ConfiguratorError>;
 
 pub struct DumpConfigRunnerFactoryReal;
+pub struct RestoreBackupRunnerFactoryReal;
 pub struct ServerInitializerFactoryReal;
 pub struct DaemonInitializerFactoryReal {
     configurator: RefCell<Option<Box<dyn NodeConfigurator<InitializationConfig>>>>,
@@ -57,6 +59,9 @@ impl DaemonInitializerFactoryReal {
 pub trait DumpConfigRunnerFactory {
     fn make(&self) -> Box<dyn DumpConfigRunner>;
 }
+pub trait RestoreBackupRunnerFactory {
+    fn make(&self) -> Box<dyn RestoreBackupRunner>;
+}
 pub trait ServerInitializerFactory {
     fn make(&self) -> Box<dyn ServerInitializer<Item = (), Error = ()>>;
 }
@@ -69,6 +74,11 @@ pub trait DumpConfigRunner {
     as_any_ref_in_trait!();
 }
 
+pub trait RestoreBackupRunner {
+    fn go(&self, streams: &mut StdStreams, args: &[String]) -> RunModeResult;
+    as_any_ref_in_trait!();
+}
+
 pub trait ServerInitializer: futures::Future {
     fn go(&mut self, streams: &mut StdStreams, args: &[String]) -> RunModeResult;
     as_any_ref_in_trait!();
@@ -87,6 +97,14 @@ impl DumpConfigRunnerFactory for DumpConfigRunnerFactoryReal {
     }
 }
 
+impl RestoreBackupRunnerFactory for RestoreBackupRunnerFactoryReal {
+    fn make(&self) -> Box<dyn RestoreBackupRunner> {
+        Box::new(RestoreBackupRunnerReal {
+            dirs_wrapper: Box::new(DirsWrapperReal::default()),
+        })
+    }
+}
+
 impl ServerInitializerFactory for ServerInitializerFactoryReal {
     fn make(&self) -> Box<dyn ServerInitializer<Item = (), Error = ()>> {
         Box::new(ServerInitializerReal::default())
@@ -131,14 +149,15 @@ pub struct DIClusteredParams {
 #[cfg(test)]
 mod tests {
     use crate::database::config_dumper::DumpConfigRunnerReal;
+    use crate::database::db_backup::RestoreBackupRunnerReal;
     use crate::node_configurator::node_configurator_initialization::NodeConfiguratorInitializationReal;
     use crate::run_modes_factories::mocks::{
         test_clustered_params, NodeConfiguratorInitializationMock,
     };
     use crate::run_modes_factories::{
         DIClusteredParams, DaemonInitializerFactory, DaemonInitializerFactoryReal,
-        DumpConfigRunnerFactory, DumpConfigRunnerFactoryReal, ServerInitializerFactory,
-        ServerInitializerFactoryReal,
+        DumpConfigRunnerFactory, DumpConfigRunnerFactoryReal, RestoreBackupRunnerFactory,
+        RestoreBackupRunnerFactoryReal, ServerInitializerFactory, ServerInitializerFactoryReal,
     };
     use crate::server_initializer::ServerInitializerReal;
     use masq_lib::shared_schema::ConfiguratorError;
@@ -157,6 +176,17 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn make_for_restore_backup_runner_factory_produces_a_proper_object() {
+        let subject = RestoreBackupRunnerFactoryReal;
+        let result = subject.make();
+
+        let _ = result
+            .as_any()
+            .downcast_ref::<RestoreBackupRunnerReal>()
+            .unwrap();
+    }
+
     #[test]
     fn make_for_server_initializer_factory_produces_a_proper_object() {
         let subject = ServerInitializerFactoryReal;
@@ -240,7 +270,8 @@ pub mod mocks {
     use crate::node_configurator::NodeConfigurator;
     use crate::run_modes_factories::{
         DIClusteredParams, DaemonInitializer, DaemonInitializerFactory, DumpConfigRunner,
-        DumpConfigRunnerFactory, RunModeResult, ServerInitializer, ServerInitializerFactory,
+        DumpConfigRunnerFactory, RestoreBackupRunner, RestoreBackupRunnerFactory, RunModeResult,
+        ServerInitializer, ServerInitializerFactory,
     };
     use crate::server_initializer::test_utils::LoggerInitializerWrapperMock;
     use crate::server_initializer::tests::{
@@ -284,6 +315,24 @@ pub mod mocks {
         }
     }
 
+    #[derive(Default)]
+    pub struct RestoreBackupRunnerFactoryMock {
+        make_results: RefCell<Vec<Box<RestoreBackupRunnerMock>>>,
+    }
+
+    impl RestoreBackupRunnerFactoryMock {
+        pub fn make_result(self, result: Box<RestoreBackupRunnerMock>) -> Self {
+            self.make_results.borrow_mut().push(result);
+            self
+        }
+    }
+
+    impl RestoreBackupRunnerFactory for RestoreBackupRunnerFactoryMock {
+        fn make(&self) -> Box<dyn RestoreBackupRunner> {
+            self.make_results.borrow_mut().remove(0)
+        }
+    }
+
     #[derive(Default)]
     pub struct ServerInitializerFactoryMock {
         make_results: RefCell<Vec<Box<ServerInitializerMock>>>,
@@ -380,6 +429,34 @@ pub mod mocks {
         }
     }
 
+    #[derive(Default)]
+    pub struct RestoreBackupRunnerMock {
+        restore_backup_params: Arc<Mutex<Vec<Vec<String>>>>,
+        restore_backup_results: RefCell<Vec<Result<(), ConfiguratorError>>>,
+    }
+
+    impl RestoreBackupRunner for RestoreBackupRunnerMock {
+        fn go(&self, _streams: &mut StdStreams, args: &[String]) -> Result<(), ConfiguratorError> {
+            self.restore_backup_params
+                .lock()
+                .unwrap()
+                .push(args.to_vec());
+            self.restore_backup_results.borrow_mut().remove(0)
+        }
+    }
+
+    impl RestoreBackupRunnerMock {
+        pub fn restore_backup_result(self, result: Result<(), ConfiguratorError>) -> Self {
+            self.restore_backup_results.borrow_mut().push(result);
+            self
+        }
+
+        pub fn restore_backup_params(mut self, params_arc: &Arc<Mutex<Vec<Vec<String>>>>) -> Self {
+            self.restore_backup_params = params_arc.clone();
+            self
+        }
+    }
+
     #[derive(Default)]
     pub struct ServerInitializerMock {
         go_result: RefCell<Vec<Result<(), ConfiguratorError>>>,
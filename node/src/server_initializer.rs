@@ -17,7 +17,7 @@ use lazy_static::lazy_static;
 use log::{log, Level};
 use masq_lib::command::StdStreams;
 use masq_lib::logger;
-use masq_lib::logger::{real_format_function, POINTER_TO_FORMAT_FUNCTION};
+use masq_lib::logger::{LogFormat, POINTER_TO_FORMAT_FUNCTION};
 use masq_lib::shared_schema::ConfiguratorError;
 use std::any::Any;
 use std::io;
@@ -132,6 +132,7 @@ pub trait LoggerInitializerWrapper {
         file_path: PathBuf,
         real_user: &RealUser,
         log_level: LevelFilter,
+        log_format: LogFormat,
         discriminant_opt: Option<&str>,
     );
 }
@@ -144,6 +145,7 @@ impl LoggerInitializerWrapper for LoggerInitializerWrapperReal {
         file_path: PathBuf,
         real_user: &RealUser,
         log_level: LevelFilter,
+        log_format: LogFormat,
         discriminant_opt: Option<&str>,
     ) {
         let mut logger = Logger::with(
@@ -183,11 +185,15 @@ impl LoggerInitializerWrapper for LoggerInitializerWrapperReal {
         }));
 
         // Info level is not shown within the log
-        log!(Level::Info, "{}", logger::Logger::log_file_heading());
+        let heading = match log_format {
+            LogFormat::Text => logger::Logger::log_file_heading(),
+            LogFormat::Json => logger::Logger::json_log_file_heading(OffsetDateTime::now_utc()),
+        };
+        log!(Level::Info, "{}", heading);
 
         unsafe {
             // This resets the format function after specialized formatting for the log heading is used.
-            POINTER_TO_FORMAT_FUNCTION = real_format_function;
+            POINTER_TO_FORMAT_FUNCTION = log_format.format_function();
         }
     }
 }
@@ -277,6 +283,7 @@ pub mod test_utils {
     use crate::privilege_drop::PrivilegeDropper;
     use crate::server_initializer::LoggerInitializerWrapper;
     use log::LevelFilter;
+    use masq_lib::logger::LogFormat;
     use std::cell::RefCell;
     use std::path::{Path, PathBuf};
     use std::sync::{Arc, Mutex};
@@ -344,21 +351,25 @@ pub mod test_utils {
     }
 
     pub struct LoggerInitializerWrapperMock {
-        init_parameters: Arc<Mutex<Vec<(PathBuf, RealUser, LevelFilter, Option<String>)>>>,
+        init_parameters: Arc<Mutex<Vec<InitParameters>>>,
     }
 
+    pub type InitParameters = (PathBuf, RealUser, LevelFilter, LogFormat, Option<String>);
+
     impl LoggerInitializerWrapper for LoggerInitializerWrapperMock {
         fn init(
             &mut self,
             file_path: PathBuf,
             real_user: &RealUser,
             log_level: LevelFilter,
+            log_format: LogFormat,
             name_segment: Option<&str>,
         ) {
             self.init_parameters.lock().unwrap().push((
                 file_path,
                 real_user.clone(),
                 log_level,
+                log_format,
                 match name_segment {
                     Some(s) => Some(s.to_string()),
                     None => None,
@@ -374,10 +385,7 @@ pub mod test_utils {
             }
         }
 
-        pub fn init_parameters(
-            mut self,
-            parameters: &Arc<Mutex<Vec<(PathBuf, RealUser, LevelFilter, Option<String>)>>>,
-        ) -> Self {
+        pub fn init_parameters(mut self, parameters: &Arc<Mutex<Vec<InitParameters>>>) -> Self {
             self.init_parameters = parameters.clone();
             self
         }
@@ -26,6 +26,8 @@ use crate::sub_lib::sequence_buffer::SequencedPacket;
 use crate::sub_lib::stream_connector::ConnectionInfo;
 use crate::sub_lib::stream_connector::StreamConnector;
 use crate::sub_lib::stream_connector::StreamConnectorReal;
+use crate::sub_lib::stream_handler_pool::clandestine_peer_id;
+use crate::sub_lib::stream_handler_pool::BanClandestinePeerMsg;
 use crate::sub_lib::stream_handler_pool::DispatcherNodeQueryResponse;
 use crate::sub_lib::stream_handler_pool::TransmitDataMsg;
 use crate::sub_lib::tokio_wrappers::ReadHalfWrapper;
@@ -42,8 +44,8 @@ use masq_lib::utils::localhost;
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 use std::io;
-use std::net::SocketAddr;
-use std::time::Duration;
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, SystemTime};
 use tokio::prelude::Future;
 
 // IMPORTANT: Nothing at or below the level of StreamHandlerPool should know about StreamKeys.
@@ -62,6 +64,7 @@ pub struct StreamHandlerPoolSubs {
     pub node_query_response: Recipient<DispatcherNodeQueryResponse>,
     pub node_from_ui_sub: Recipient<NodeFromUiMessage>,
     pub scheduled_node_query_response_sub: Recipient<MessageScheduler<DispatcherNodeQueryResponse>>,
+    pub ban_sub: Recipient<BanClandestinePeerMsg>,
 }
 
 impl Clone for StreamHandlerPoolSubs {
@@ -74,6 +77,7 @@ impl Clone for StreamHandlerPoolSubs {
             node_query_response: self.node_query_response.clone(),
             node_from_ui_sub: self.node_from_ui_sub.clone(),
             scheduled_node_query_response_sub: self.scheduled_node_query_response_sub.clone(),
+            ban_sub: self.ban_sub.clone(),
         }
     }
 }
@@ -109,6 +113,7 @@ impl Display for StreamWriterKey {
 // It is used to store streams for both neighbors and browser.
 pub struct StreamHandlerPool {
     stream_writers: HashMap<StreamWriterKey, Option<Box<dyn SenderWrapper<SequencedPacket>>>>,
+    banned_peers: HashMap<IpAddr, SystemTime>,
     dispatcher_subs_opt: Option<DispatcherSubs>,
     self_subs_opt: Option<StreamHandlerPoolSubs>,
     ask_neighborhood_opt: Option<Recipient<DispatcherNodeQueryMessage>>,
@@ -150,6 +155,14 @@ impl Handler<TransmitDataMsg> for StreamHandlerPool {
     }
 }
 
+impl Handler<BanClandestinePeerMsg> for StreamHandlerPool {
+    type Result = ();
+
+    fn handle(&mut self, msg: BanClandestinePeerMsg, _ctx: &mut Self::Context) {
+        self.handle_ban_clandestine_peer_msg(msg, SystemTime::now())
+    }
+}
+
 impl Handler<DispatcherNodeQueryResponse> for StreamHandlerPool {
     type Result = ();
     fn handle(&mut self, msg: DispatcherNodeQueryResponse, _ctx: &mut Self::Context) {
@@ -197,6 +210,7 @@ impl StreamHandlerPool {
     ) -> StreamHandlerPool {
         StreamHandlerPool {
             stream_writers: HashMap::new(),
+            banned_peers: HashMap::new(),
             dispatcher_subs_opt: None,
             self_subs_opt: None,
             ask_neighborhood_opt: None,
@@ -223,6 +237,7 @@ impl StreamHandlerPool {
                 pool_addr,
                 MessageScheduler<DispatcherNodeQueryResponse>
             ),
+            ban_sub: recipient!(pool_addr, BanClandestinePeerMsg),
         }
     }
 
@@ -351,6 +366,14 @@ impl StreamHandlerPool {
         msg: AddStreamMsg,
     ) -> <Self as Handler<AddStreamMsg>>::Result {
         let port_config = msg.port_configuration.clone();
+        let peer_addr = msg.connection_info.peer_addr;
+        if port_config.is_clandestine && self.is_banned(peer_addr.ip(), SystemTime::now()) {
+            debug!(
+                self.logger,
+                "Refusing clandestine stream with banned peer {}", peer_addr
+            );
+            return;
+        }
         self.set_up_stream_writer(
             msg.connection_info.writer,
             msg.connection_info.peer_addr,
@@ -365,6 +388,35 @@ impl StreamHandlerPool {
         );
     }
 
+    fn handle_ban_clandestine_peer_msg(&mut self, msg: BanClandestinePeerMsg, now: SystemTime) {
+        warning!(
+            self.logger,
+            "Banning clandestine connections with {} for {:?}",
+            msg.peer_ip,
+            msg.duration
+        );
+        // Peers that never come back would otherwise keep their expired bans forever
+        self.banned_peers.retain(|_, expiration| *expiration > now);
+        let peer_id = clandestine_peer_id(msg.peer_ip);
+        self.banned_peers.insert(peer_id, now + msg.duration);
+        // Dropping the writer's channel shuts the stream down; the reader follows when the
+        // peer notices the connection is gone.
+        self.stream_writers
+            .retain(|key, _| clandestine_peer_id(key.socket_addr.ip()) != peer_id);
+    }
+
+    fn is_banned(&mut self, peer_ip: IpAddr, now: SystemTime) -> bool {
+        let peer_id = clandestine_peer_id(peer_ip);
+        match self.banned_peers.get(&peer_id) {
+            Some(expiration) if *expiration > now => true,
+            Some(_) => {
+                self.banned_peers.remove(&peer_id);
+                false
+            }
+            None => false,
+        }
+    }
+
     fn handle_remove_stream_msg(&mut self, msg: RemoveStreamMsg) {
         let stream_writer_key = StreamWriterKey::from(msg.peer_addr);
         debug!(
@@ -442,6 +494,16 @@ impl StreamHandlerPool {
 
         // TODO: Picking the first port is a temporary hack. This problem should go away with clandestine ports.
         let peer_addr = SocketAddr::new(node_addr.ip_addr(), node_addr.ports()[0]);
+        let is_clandestine = msg.context.sequence_number.is_none();
+        if is_clandestine && self.is_banned(peer_addr.ip(), SystemTime::now()) {
+            debug!(
+                self.logger,
+                "Dropping {} bytes for banned peer {}",
+                msg.context.data.len(),
+                peer_addr
+            );
+            return;
+        }
 
         let sw_key = StreamWriterKey::from(peer_addr);
 
@@ -2264,6 +2326,159 @@ mod tests {
         );
     }
 
+    #[test]
+    fn ban_drops_stream_writer_and_refuses_new_clandestine_streams_from_banned_peer() {
+        init_test_logging();
+        let peer_ip = IpAddr::from_str("1.2.3.5").unwrap();
+        let existing_peer_addr = SocketAddr::new(peer_ip, 6789);
+        let mut subject = StreamHandlerPool::new(vec![], false);
+        subject.stream_writers.insert(
+            StreamWriterKey::from(existing_peer_addr),
+            Some(Box::new(SenderWrapperMock::new(existing_peer_addr))),
+        );
+
+        subject.handle_ban_clandestine_peer_msg(
+            BanClandestinePeerMsg {
+                peer_ip,
+                duration: Duration::from_secs(300),
+            },
+            SystemTime::now(),
+        );
+        subject.handle_add_stream_msg(AddStreamMsg::new(
+            ConnectionInfo {
+                reader: Box::new(ReadHalfWrapperMock::new()),
+                writer: Box::new(WriteHalfWrapperMock::new()),
+                local_addr: SocketAddr::from_str("1.2.3.4:7777").unwrap(),
+                peer_addr: SocketAddr::new(peer_ip, 5432),
+            },
+            None,
+            PortConfiguration::new(vec![], true),
+        ));
+
+        assert!(subject.stream_writers.is_empty());
+        let tlh = TestLogHandler::new();
+        tlh.exists_log_containing(
+            "WARN: Dispatcher: Banning clandestine connections with 1.2.3.5 for 300s",
+        );
+        tlh.exists_log_containing(
+            "DEBUG: Dispatcher: Refusing clandestine stream with banned peer 1.2.3.5:5432",
+        );
+    }
+
+    #[test]
+    fn clandestine_data_for_banned_peer_is_dropped() {
+        init_test_logging();
+        let peer_addr = SocketAddr::from_str("1.2.3.6:6789").unwrap();
+        let mut subject = StreamHandlerPool::new(vec![], false);
+        subject.handle_ban_clandestine_peer_msg(
+            BanClandestinePeerMsg {
+                peer_ip: peer_addr.ip(),
+                duration: Duration::from_secs(300),
+            },
+            SystemTime::now(),
+        );
+
+        subject.handle_dispatcher_node_query_response(DispatcherNodeQueryResponse {
+            result: Some(NodeQueryResponseMetadata::new(
+                PublicKey::new(&b"banned"[..]),
+                Some(NodeAddr::new(&peer_addr.ip(), &[peer_addr.port()])),
+                ZERO_RATE_PACK,
+            )),
+            context: TransmitDataMsg {
+                endpoint: Endpoint::Socket(peer_addr),
+                last_data: false,
+                sequence_number: None,
+                data: b"hello".to_vec(),
+            },
+        });
+
+        assert!(subject.stream_writers.is_empty());
+        TestLogHandler::new().exists_log_containing(
+            "DEBUG: Dispatcher: Dropping 5 bytes for banned peer 1.2.3.6:6789",
+        );
+    }
+
+    #[test]
+    fn bans_expire() {
+        let peer_ip = IpAddr::from_str("1.2.3.7").unwrap();
+        let banned_at = SystemTime::now();
+        let mut subject = StreamHandlerPool::new(vec![], false);
+        subject.handle_ban_clandestine_peer_msg(
+            BanClandestinePeerMsg {
+                peer_ip,
+                duration: Duration::from_secs(300),
+            },
+            banned_at,
+        );
+
+        let before_expiration = subject.is_banned(peer_ip, banned_at + Duration::from_secs(299));
+        let after_expiration = subject.is_banned(peer_ip, banned_at + Duration::from_secs(300));
+
+        assert_eq!(before_expiration, true);
+        assert_eq!(after_expiration, false);
+        assert!(subject.banned_peers.is_empty());
+    }
+
+    #[test]
+    fn new_ban_forgets_expired_bans_of_peers_that_never_came_back() {
+        let banned_at = SystemTime::now();
+        let mut subject = StreamHandlerPool::new(vec![], false);
+        subject.handle_ban_clandestine_peer_msg(
+            BanClandestinePeerMsg {
+                peer_ip: IpAddr::from_str("1.2.3.8").unwrap(),
+                duration: Duration::from_secs(300),
+            },
+            banned_at,
+        );
+
+        subject.handle_ban_clandestine_peer_msg(
+            BanClandestinePeerMsg {
+                peer_ip: IpAddr::from_str("1.2.3.9").unwrap(),
+                duration: Duration::from_secs(300),
+            },
+            banned_at + Duration::from_secs(300),
+        );
+
+        assert_eq!(
+            subject.banned_peers.keys().collect::<Vec<_>>(),
+            vec![&IpAddr::from_str("1.2.3.9").unwrap()]
+        );
+    }
+
+    #[test]
+    fn ban_of_ipv6_peer_covers_its_whole_slash_64() {
+        let banned_at = SystemTime::now();
+        let same_slash_64_addr = SocketAddr::from_str("[2001:db8:1:2::9]:6789").unwrap();
+        let other_slash_64_addr = SocketAddr::from_str("[2001:db8:1:3::9]:6789").unwrap();
+        let mut subject = StreamHandlerPool::new(vec![], false);
+        vec![same_slash_64_addr, other_slash_64_addr]
+            .into_iter()
+            .for_each(|peer_addr| {
+                subject.stream_writers.insert(
+                    StreamWriterKey::from(peer_addr),
+                    Some(Box::new(SenderWrapperMock::new(peer_addr))),
+                );
+            });
+
+        subject.handle_ban_clandestine_peer_msg(
+            BanClandestinePeerMsg {
+                peer_ip: IpAddr::from_str("2001:db8:1:2::1").unwrap(),
+                duration: Duration::from_secs(300),
+            },
+            banned_at,
+        );
+
+        assert_eq!(
+            subject.stream_writers.keys().collect::<Vec<_>>(),
+            vec![&StreamWriterKey::from(other_slash_64_addr)]
+        );
+        let is_banned = |subject: &mut StreamHandlerPool, ip: &str| {
+            subject.is_banned(IpAddr::from_str(ip).unwrap(), banned_at)
+        };
+        assert_eq!(is_banned(&mut subject, "2001:db8:1:2:ffff::1"), true);
+        assert_eq!(is_banned(&mut subject, "2001:db8:1:3::1"), false);
+    }
+
     #[test]
     #[should_panic(
         expected = "panic message (processed with: node_lib::sub_lib::utils::crash_request_analyzer)"
@@ -1,5 +1,6 @@
 // Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 use crate::accountant::db_access_objects::banned_dao::BannedDaoFactory;
+use crate::accountant::db_access_objects::ledger_dao::LedgerDaoFactory;
 use crate::accountant::db_access_objects::payable_dao::PayableDaoFactory;
 use crate::accountant::db_access_objects::pending_payable_dao::PendingPayableDaoFactory;
 use crate::accountant::db_access_objects::receivable_dao::ReceivableDaoFactory;
@@ -11,6 +12,7 @@ use crate::accountant::{
 use crate::actor_system_factory::SubsFactory;
 use crate::blockchain::blockchain_bridge::PendingPayableFingerprintSeeds;
 use crate::db_config::config_dao::ConfigDaoFactory;
+use crate::sub_lib::earning_wallet_shares::EarningWalletShares;
 use crate::sub_lib::neighborhood::ConfigChangeMsg;
 use crate::sub_lib::peer_actors::{BindMessage, StartMessage};
 use crate::sub_lib::wallet::Wallet;
@@ -42,6 +44,11 @@ lazy_static! {
         payable_scan_interval: Duration::from_secs(600),
         receivable_scan_interval: Duration::from_secs(600)
     };
+    pub static ref DEFAULT_PAYABLE_WRITE_OFF_POLICY: PayableWriteOffPolicy = PayableWriteOffPolicy {
+        unseen_days: 0,
+        dust_threshold_gwei: 0,
+        dust_days: 0
+    };
 }
 
 //please, alphabetical order
@@ -74,6 +81,7 @@ pub struct DaoFactories {
     pub pending_payable_dao_factory: Box<dyn PendingPayableDaoFactory>,
     pub receivable_dao_factory: Box<dyn ReceivableDaoFactory>,
     pub banned_dao_factory: Box<dyn BannedDaoFactory>,
+    pub ledger_dao_factory: Box<dyn LedgerDaoFactory>,
     pub config_dao_factory: Box<dyn ConfigDaoFactory>,
 }
 
@@ -90,6 +98,34 @@ impl Default for ScanIntervals {
     }
 }
 
+// A zero in either rule switches that rule off
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct PayableWriteOffPolicy {
+    pub unseen_days: u64,
+    pub dust_threshold_gwei: u64,
+    pub dust_days: u64,
+}
+
+impl Default for PayableWriteOffPolicy {
+    fn default() -> Self {
+        *DEFAULT_PAYABLE_WRITE_OFF_POLICY
+    }
+}
+
+impl PayableWriteOffPolicy {
+    pub fn unseen_rule_enabled(&self) -> bool {
+        self.unseen_days > 0
+    }
+
+    pub fn dust_rule_enabled(&self) -> bool {
+        self.dust_threshold_gwei > 0 && self.dust_days > 0
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.unseen_rule_enabled() || self.dust_rule_enabled()
+    }
+}
+
 #[derive(Clone, PartialEq, Eq)]
 pub struct AccountantSubs {
     pub bind: Recipient<BindMessage>,
@@ -98,6 +134,7 @@ pub struct AccountantSubs {
     pub report_routing_service_provided: Recipient<ReportRoutingServiceProvidedMessage>,
     pub report_exit_service_provided: Recipient<ReportExitServiceProvidedMessage>,
     pub report_services_consumed: Recipient<ReportServicesConsumedMessage>,
+    pub report_wallets_seen_in_gossip: Recipient<WalletsSeenInGossip>,
     pub report_payable_payments_setup: Recipient<BlockchainAgentWithContextMessage>,
     pub report_inbound_payments: Recipient<ReceivedPayments>,
     pub init_pending_payable_fingerprints: Recipient<PendingPayableFingerprintSeeds>,
@@ -148,6 +185,13 @@ pub struct ReportServicesConsumedMessage {
     pub routing: Vec<RoutingServiceConsumed>,
 }
 
+#[derive(Clone, PartialEq, Eq, Debug, Message)]
+pub struct WalletsSeenInGossip {
+    pub timestamp: SystemTime,
+    pub wallets: Vec<Wallet>,
+    pub earning_wallet_shares: Vec<(Wallet, EarningWalletShares)>,
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct RoutingServiceConsumed {
     pub earning_wallet: Wallet,
@@ -196,8 +240,9 @@ mod tests {
     use crate::accountant::test_utils::AccountantBuilder;
     use crate::accountant::{checked_conversion, Accountant};
     use crate::sub_lib::accountant::{
-        AccountantSubsFactoryReal, MessageIdGenerator, MessageIdGeneratorReal, PaymentThresholds,
-        ScanIntervals, SubsFactory, DEFAULT_EARNING_WALLET, DEFAULT_PAYMENT_THRESHOLDS,
+        AccountantSubsFactoryReal, MessageIdGenerator, MessageIdGeneratorReal,
+        PayableWriteOffPolicy, PaymentThresholds, ScanIntervals, SubsFactory,
+        DEFAULT_EARNING_WALLET, DEFAULT_PAYABLE_WRITE_OFF_POLICY, DEFAULT_PAYMENT_THRESHOLDS,
         DEFAULT_SCAN_INTERVALS, MSG_ID_INCREMENTER, TEMPORARY_CONSUMING_WALLET,
     };
     use crate::sub_lib::wallet::Wallet;
@@ -235,8 +280,17 @@ mod tests {
             payable_scan_interval: Duration::from_secs(600),
             receivable_scan_interval: Duration::from_secs(600),
         };
+        let payable_write_off_policy_expected = PayableWriteOffPolicy {
+            unseen_days: 0,
+            dust_threshold_gwei: 0,
+            dust_days: 0,
+        };
         assert_eq!(*DEFAULT_SCAN_INTERVALS, scan_intervals_expected);
         assert_eq!(*DEFAULT_PAYMENT_THRESHOLDS, payment_thresholds_expected);
+        assert_eq!(
+            *DEFAULT_PAYABLE_WRITE_OFF_POLICY,
+            payable_write_off_policy_expected
+        );
         assert_eq!(*DEFAULT_EARNING_WALLET, default_earning_wallet_expected);
         assert_eq!(
             *TEMPORARY_CONSUMING_WALLET,
@@ -244,6 +298,36 @@ mod tests {
         )
     }
 
+    #[test]
+    fn payable_write_off_policy_is_disabled_by_default() {
+        let subject = PayableWriteOffPolicy::default();
+
+        assert_eq!(subject.unseen_rule_enabled(), false);
+        assert_eq!(subject.dust_rule_enabled(), false);
+        assert_eq!(subject.is_enabled(), false)
+    }
+
+    #[test]
+    fn payable_write_off_policy_rules_are_enabled_independently() {
+        let unseen_only = PayableWriteOffPolicy {
+            unseen_days: 30,
+            dust_threshold_gwei: 0,
+            dust_days: 10,
+        };
+        let dust_only = PayableWriteOffPolicy {
+            unseen_days: 0,
+            dust_threshold_gwei: 1000,
+            dust_days: 10,
+        };
+
+        assert_eq!(unseen_only.unseen_rule_enabled(), true);
+        assert_eq!(unseen_only.dust_rule_enabled(), false);
+        assert_eq!(unseen_only.is_enabled(), true);
+        assert_eq!(dust_only.unseen_rule_enabled(), false);
+        assert_eq!(dust_only.dust_rule_enabled(), true);
+        assert_eq!(dust_only.is_enabled(), true)
+    }
+
     #[test]
     fn accountant_subs_debug() {
         let addr = Recorder::new().start();
@@ -1,6 +1,6 @@
 // Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 
-use crate::sub_lib::accountant::{PaymentThresholds, ScanIntervals};
+use crate::sub_lib::accountant::{PayableWriteOffPolicy, PaymentThresholds, ScanIntervals};
 use crate::sub_lib::combined_parameters::CombinedParamsDataTypes::U64;
 use crate::sub_lib::combined_parameters::InitializationState::{Initialized, Uninitialized};
 use crate::sub_lib::neighborhood::RatePack;
@@ -96,6 +96,7 @@ enum CombinedParams {
     RatePack(InitializationState<RatePack>),
     PaymentThresholds(InitializationState<PaymentThresholds>),
     ScanIntervals(InitializationState<ScanIntervals>),
+    PayableWriteOffPolicy(InitializationState<PayableWriteOffPolicy>),
 }
 
 #[derive(Debug)]
@@ -182,6 +183,15 @@ impl CombinedParams {
                     "receivable_scan_interval"
                 )))
             }
+            Self::PayableWriteOffPolicy(Uninitialized) => {
+                Self::PayableWriteOffPolicy(Initialized(initiate_struct!(
+                    PayableWriteOffPolicy,
+                    &parsed_values,
+                    "unseen_days",
+                    "dust_threshold_gwei",
+                    "dust_days"
+                )))
+            }
             _ => panic!(
                 "should be called only on uninitialized object, not: {:?}",
                 self
@@ -212,6 +222,11 @@ impl From<&CombinedParams> for &[(&str, CombinedParamsDataTypes)] {
                 ("payable_scan_interval", U64),
                 ("receivable_scan_interval", U64),
             ],
+            CombinedParams::PayableWriteOffPolicy(Uninitialized) => &[
+                ("unseen_days", U64),
+                ("dust_threshold_gwei", U64),
+                ("dust_days", U64),
+            ],
             _ => panic!(
                 "should be called only on uninitialized object, not: {:?}",
                 params
@@ -298,6 +313,28 @@ impl TryFrom<&str> for RatePack {
     }
 }
 
+impl Display for PayableWriteOffPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}|{}|{}",
+            self.unseen_days, self.dust_threshold_gwei, self.dust_days
+        )
+    }
+}
+
+impl TryFrom<&str> for PayableWriteOffPolicy {
+    type Error = String;
+
+    fn try_from(parameters: &str) -> Result<Self, String> {
+        match CombinedParams::PayableWriteOffPolicy(Uninitialized).parse(parameters) {
+            Ok(CombinedParams::PayableWriteOffPolicy(Initialized(policy))) => Ok(policy),
+            Err(e) => Err(e),
+            _ => unreachable(),
+        }
+    }
+}
+
 fn unreachable() -> ! {
     unreachable!("technically shouldn't be possible")
 }
@@ -418,6 +455,16 @@ mod tests {
                 ("unban_below_gwei", U64)
             ]
         );
+        let payable_write_off_policy: &[(&str, CombinedParamsDataTypes)] =
+            (&CombinedParams::PayableWriteOffPolicy(Uninitialized)).into();
+        assert_eq!(
+            payable_write_off_policy,
+            &[
+                ("unseen_days", U64),
+                ("dust_threshold_gwei", U64),
+                ("dust_days", U64)
+            ]
+        );
     }
 
     #[test]
@@ -611,4 +658,33 @@ mod tests {
 
         assert_eq!(result, "5000010|120|123|20000|30020|111".to_string());
     }
+
+    #[test]
+    fn payable_write_off_policy_from_combined_params() {
+        let policy_str = "90|1000|30";
+
+        let result = PayableWriteOffPolicy::try_from(policy_str).unwrap();
+
+        assert_eq!(
+            result,
+            PayableWriteOffPolicy {
+                unseen_days: 90,
+                dust_threshold_gwei: 1000,
+                dust_days: 30
+            }
+        )
+    }
+
+    #[test]
+    fn payable_write_off_policy_to_combined_params() {
+        let policy = PayableWriteOffPolicy {
+            unseen_days: 60,
+            dust_threshold_gwei: 5000,
+            dust_days: 14,
+        };
+
+        let result = policy.to_string();
+
+        assert_eq!(result, "60|5000|14".to_string());
+    }
 }
@@ -1,15 +1,20 @@
 // Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 
-use crate::sub_lib::peer_actors::BindMessage;
+use crate::sub_lib::peer_actors::{BindMessage, NewPublicIp};
+use actix::Message;
 use actix::Recipient;
+use automap_lib::control_layer::automap_control::{AutomapControl, AutomapStatus};
 use masq_lib::ui_gateway::NodeFromUiMessage;
+use masq_lib::utils::AutomapProtocol;
 use std::fmt;
 use std::fmt::{Debug, Formatter};
+use std::net::IpAddr;
 
 #[derive(Clone, PartialEq, Eq)]
 pub struct ConfiguratorSubs {
     pub bind: Recipient<BindMessage>,
     pub node_from_ui_sub: Recipient<NodeFromUiMessage>,
+    pub automap_control_sub: Recipient<AutomapControlMessage>,
 }
 
 impl Debug for ConfiguratorSubs {
@@ -18,6 +23,48 @@ impl Debug for ConfiguratorSubs {
     }
 }
 
+// Once startup has mapped the Node's ports, the Configurator takes custody of the
+// AutomapControl so that the UI can inspect and adjust the mapping. The recipients are the
+// ones startup told about the public IP; they hear about it again if a switch changes it.
+#[derive(Message)]
+pub struct AutomapControlMessage {
+    pub automap_control: Box<dyn AutomapControl>,
+    pub new_ip_recipients: Vec<Recipient<NewPublicIp>>,
+}
+
+impl Debug for AutomapControlMessage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "AutomapControlMessage")
+    }
+}
+
+// The Configurator's automap thread sends this back once it has carried out a UI request
+// against the router
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub struct AutomapActionResult {
+    pub client_id: u64,
+    pub context_id: u64,
+    pub result: Result<AutomapOutcome, (u64, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutomapOutcome {
+    pub status: AutomapStatus,
+    // The protocol a switch settled on, to be remembered for the next startup
+    pub switched_protocol_opt: Option<AutomapProtocol>,
+    // Present only if the action left the Node with a different public IP than before
+    pub new_public_ip_opt: Option<IpAddr>,
+}
+
+// The Configurator sends this to itself to take the periodic database backup
+#[derive(Message, Debug, Default, Clone, PartialEq, Eq)]
+pub struct ScheduledDatabaseBackup {}
+
+// The Configurator sends this to itself to rotate the Node's key pair when --key-rotation-interval
+// is specified
+#[derive(Message, Debug, Default, Clone, PartialEq, Eq)]
+pub struct ScheduledKeyRotation {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -31,6 +78,7 @@ mod tests {
         let subject = ConfiguratorSubs {
             bind: recipient!(recorder, BindMessage),
             node_from_ui_sub: recipient!(recorder, NodeFromUiMessage),
+            automap_control_sub: recipient!(recorder, AutomapControlMessage),
         };
 
         assert_eq!(format!("{:?}", subject), "ConfiguratorSubs");
@@ -1,5 +1,6 @@
 // Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 use crate::sub_lib::route::RouteError;
+use crate::sub_lib::versioned_data::MigrationError;
 use ethsign_crypto::Keccak256;
 use masq_lib::blockchains::chains::Chain;
 use rustc_hex::{FromHex, ToHex};
@@ -590,6 +591,7 @@ pub enum CodexError {
     EncryptionError(CryptdecError),
     DecryptionError(CryptdecError),
     RoutingError(RouteError),
+    MigrationError(MigrationError),
 }
 
 pub fn encodex<T>(
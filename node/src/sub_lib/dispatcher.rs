@@ -3,7 +3,7 @@ use crate::proxy_server::http_protocol_pack::HttpProtocolPack;
 use crate::stream_messages::RemovedStreamType;
 use crate::sub_lib::cryptde::PublicKey;
 use crate::sub_lib::peer_actors::{BindMessage, NewPublicIp};
-use crate::sub_lib::stream_handler_pool::TransmitDataMsg;
+use crate::sub_lib::stream_handler_pool::{BanClandestinePeerMsg, TransmitDataMsg};
 use actix::Message;
 use actix::Recipient;
 use masq_lib::ui_gateway::NodeFromUiMessage;
@@ -167,6 +167,7 @@ pub struct DispatcherSubs {
     pub stream_shutdown_sub: Recipient<StreamShutdownMsg>,
     pub ui_sub: Recipient<NodeFromUiMessage>,
     pub new_ip_sub: Recipient<NewPublicIp>,
+    pub ban_clandestine_peer_sub: Recipient<BanClandestinePeerMsg>,
 }
 
 impl Debug for DispatcherSubs {
@@ -184,6 +185,7 @@ impl Clone for DispatcherSubs {
             stream_shutdown_sub: self.stream_shutdown_sub.clone(),
             ui_sub: self.ui_sub.clone(),
             new_ip_sub: self.new_ip_sub.clone(),
+            ban_clandestine_peer_sub: self.ban_clandestine_peer_sub.clone(),
         }
     }
 }
@@ -207,6 +209,7 @@ mod tests {
             stream_shutdown_sub: recipient!(addr, StreamShutdownMsg),
             ui_sub: recipient!(addr, NodeFromUiMessage),
             new_ip_sub: recipient!(addr, NewPublicIp),
+            ban_clandestine_peer_sub: recipient!(addr, BanClandestinePeerMsg),
         };
 
         assert_eq!(format!("{:?}", subject), "DispatcherSubs");
@@ -0,0 +1,223 @@
+// Copyright (c) 2024, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::sub_lib::wallet::Wallet;
+use serde_derive::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletShare {
+    pub wallet: Wallet,
+    pub percentage: u8,
+}
+
+// Wallets that take a percentage of a Node's earnings away from its primary earning wallet, which
+// keeps whatever the shares leave over.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EarningWalletShares(Vec<WalletShare>);
+
+impl EarningWalletShares {
+    pub fn new(shares: Vec<WalletShare>) -> Result<Self, String> {
+        let mut total = 0u32;
+        for (index, share) in shares.iter().enumerate() {
+            if share.percentage == 0 {
+                return Err(format!("Share for {} must not be 0%", share.wallet));
+            }
+            if shares[..index]
+                .iter()
+                .any(|other| other.wallet.address() == share.wallet.address())
+            {
+                return Err(format!("Wallet {} appears more than once", share.wallet));
+            }
+            total += share.percentage as u32;
+        }
+        if total >= 100 {
+            return Err(format!(
+                "Shares total {}%, leaving nothing for the earning wallet",
+                total
+            ));
+        }
+        Ok(Self(shares))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn shares(&self) -> &[WalletShare] {
+        &self.0
+    }
+
+    pub fn wallets(&self) -> Vec<Wallet> {
+        self.0.iter().map(|share| share.wallet.clone()).collect()
+    }
+
+    // The portion of a debt that each share wallet is owed, rounded down; whatever isn't claimed
+    // stays with the primary wallet. Shares whose portion rounds down to nothing are left out.
+    pub fn split(&self, amount: u128) -> Vec<(Wallet, u128)> {
+        self.0
+            .iter()
+            .map(|share| {
+                let percentage = share.percentage as u128;
+                let portion = amount / 100 * percentage + amount % 100 * percentage / 100;
+                (share.wallet.clone(), portion)
+            })
+            .filter(|(_, portion)| *portion > 0)
+            .collect()
+    }
+
+    fn parse_share(share: &str) -> Result<WalletShare, String> {
+        let (address, percentage) = share
+            .split_once(':')
+            .ok_or_else(|| format!("Share '{}' is not <address>:<percentage>", share))?;
+        let wallet = Wallet::from_str(address)
+            .map_err(|_| format!("Invalid wallet address '{}'", address))?;
+        let percentage = percentage
+            .parse::<u8>()
+            .map_err(|_| format!("Invalid percentage '{}'", percentage))?;
+        Ok(WalletShare { wallet, percentage })
+    }
+}
+
+impl FromStr for EarningWalletShares {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        let shares = s
+            .split(',')
+            .map(|share| Self::parse_share(share.trim()))
+            .collect::<Result<Vec<WalletShare>, String>>()?;
+        Self::new(shares)
+    }
+}
+
+impl Display for EarningWalletShares {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let shares = self
+            .0
+            .iter()
+            .map(|share| format!("{}:{}", share.wallet, share.percentage))
+            .collect::<Vec<String>>();
+        write!(f, "{}", shares.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::make_wallet;
+
+    fn make_shares() -> EarningWalletShares {
+        EarningWalletShares::new(vec![
+            WalletShare {
+                wallet: make_wallet("first"),
+                percentage: 30,
+            },
+            WalletShare {
+                wallet: make_wallet("second"),
+                percentage: 20,
+            },
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn shares_round_trip_through_strings() {
+        let subject = make_shares();
+
+        let string = subject.to_string();
+
+        assert_eq!(
+            string,
+            format!("{}:30,{}:20", make_wallet("first"), make_wallet("second"))
+        );
+        assert_eq!(EarningWalletShares::from_str(&string), Ok(subject));
+    }
+
+    #[test]
+    fn empty_string_means_no_shares() {
+        let result = EarningWalletShares::from_str("");
+
+        assert_eq!(result, Ok(EarningWalletShares::default()));
+        assert_eq!(EarningWalletShares::default().to_string(), "");
+    }
+
+    #[test]
+    fn invalid_shares_are_rejected() {
+        let first = make_wallet("first");
+        let second = make_wallet("second");
+        vec![
+            (
+                format!("{}", first),
+                format!("Share '{}' is not <address>:<percentage>", first),
+            ),
+            (
+                "booga:30".to_string(),
+                "Invalid wallet address 'booga'".to_string(),
+            ),
+            (
+                format!("{}:lots", first),
+                "Invalid percentage 'lots'".to_string(),
+            ),
+            (
+                format!("{}:0", first),
+                format!("Share for {} must not be 0%", first),
+            ),
+            (
+                format!("{}:10,{}:20", first, first),
+                format!("Wallet {} appears more than once", first),
+            ),
+            (
+                format!("{}:60,{}:40", first, second),
+                "Shares total 100%, leaving nothing for the earning wallet".to_string(),
+            ),
+        ]
+        .into_iter()
+        .for_each(|(input, expected_error)| {
+            assert_eq!(
+                EarningWalletShares::from_str(&input),
+                Err(expected_error),
+                "{}",
+                input
+            )
+        });
+    }
+
+    #[test]
+    fn split_gives_each_share_its_percentage_rounded_down() {
+        let subject = make_shares();
+
+        assert_eq!(
+            subject.split(1_000_001),
+            vec![
+                (make_wallet("first"), 300_000),
+                (make_wallet("second"), 200_000)
+            ]
+        );
+        assert_eq!(
+            subject.split(u128::MAX),
+            vec![
+                (
+                    make_wallet("first"),
+                    102_084_710_076_281_539_039_012_382_229_530_463_436
+                ),
+                (
+                    make_wallet("second"),
+                    68_056_473_384_187_692_692_674_921_486_353_642_291
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn split_leaves_out_shares_that_round_down_to_nothing() {
+        let subject = make_shares();
+
+        assert_eq!(subject.split(4), vec![(make_wallet("first"), 1)]);
+        assert_eq!(subject.split(3), vec![]);
+        assert_eq!(EarningWalletShares::default().split(1_000_000), vec![]);
+    }
+}
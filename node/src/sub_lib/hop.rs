@@ -16,6 +16,10 @@ pub struct LiveHop {
     pub public_key: PublicKey,
     pub payer: Option<Payer>,
     pub component: Component,
+    // Session key of the Node at public_key, to which that Node's hop and the package carrying
+    // it are encrypted; None means its static key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_key_opt: Option<PublicKey>,
 }
 
 impl LiveHop {
@@ -24,6 +28,7 @@ impl LiveHop {
             public_key: key.clone(),
             payer,
             component,
+            session_key_opt: None,
         }
     }
 
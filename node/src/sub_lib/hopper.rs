@@ -6,7 +6,7 @@ use crate::sub_lib::cryptde::CryptDE;
 use crate::sub_lib::cryptde::CryptData;
 use crate::sub_lib::cryptde::PublicKey;
 use crate::sub_lib::dispatcher::InboundClientData;
-use crate::sub_lib::neighborhood::GossipFailure_0v1;
+use crate::sub_lib::neighborhood::{ConfigChangeMsg, GossipFailure_0v1};
 use crate::sub_lib::node_addr::NodeAddr;
 use crate::sub_lib::peer_actors::BindMessage;
 use crate::sub_lib::proxy_client::{ClientResponsePayload_0v1, DnsResolveFailure_0v1};
@@ -16,6 +16,7 @@ use crate::sub_lib::versioned_data::VersionedData;
 use crate::sub_lib::wallet::Wallet;
 use actix::Message;
 use actix::Recipient;
+use masq_lib::blockchains::chains::Chain;
 use masq_lib::ui_gateway::NodeFromUiMessage;
 use serde_derive::{Deserialize, Serialize};
 use std::fmt::Debug;
@@ -143,21 +144,55 @@ impl<T> ExpiredCoresPackage<T> {
     }
 }
 
+// A session key is handed out for this long, and its secret half is kept for as long again so
+// that Routes built with it just before a rotation still work; then it's destroyed.
+pub const SESSION_KEY_LIFETIME_SECS: u64 = 600;
+
+// Hops and packages encrypted to a session key carry this id of it, so that the receiving Node
+// knows which of its keys to decrypt them with. The first eight bytes of a random key are plenty
+// to tell it from the only other session key the Node still has.
+pub fn session_key_id(session_key: &PublicKey) -> u64 {
+    let mut id_bytes = [0u8; 8];
+    session_key
+        .as_slice()
+        .iter()
+        .zip(id_bytes.iter_mut())
+        .for_each(|(key_byte, id_byte)| *id_byte = *key_byte);
+    u64::from_be_bytes(id_bytes)
+}
+
+pub fn find_session_cryptde(
+    session_cryptdes: &[Box<dyn CryptDE>],
+    key_id: u64,
+) -> Option<&dyn CryptDE> {
+    session_cryptdes
+        .iter()
+        .map(|session_cryptde| session_cryptde.as_ref())
+        .find(|session_cryptde| session_key_id(session_cryptde.public_key()) == key_id)
+}
+
 #[derive(Clone)]
 pub struct HopperConfig {
     pub cryptde_pair: CryptDEPair,
     pub per_routing_service: u64,
     pub per_routing_byte: u64,
     pub is_decentralized: bool,
+    // Session keys are real key pairs, so they're used only when the main key is one too
+    pub session_key_chain_opt: Option<Chain>,
     pub crashable: bool,
 }
 
+// The Hopper sends this to itself to replace its session key
+#[derive(Message, Debug, Default, Clone, PartialEq, Eq)]
+pub struct ScheduledSessionKeyRotation {}
+
 #[derive(Clone, PartialEq, Eq)]
 pub struct HopperSubs {
     pub bind: Recipient<BindMessage>,
     pub from_hopper_client: Recipient<IncipientCoresPackage>,
     pub from_hopper_client_no_lookup: Recipient<NoLookupIncipientCoresPackage>,
     pub from_dispatcher: Recipient<InboundClientData>,
+    pub config_change_msg_sub: Recipient<ConfigChangeMsg>,
     pub node_from_ui: Recipient<NodeFromUiMessage>,
 }
 
@@ -171,6 +206,7 @@ impl Debug for HopperSubs {
 mod tests {
     use super::*;
     use crate::sub_lib::cryptde::PlainData;
+    use crate::sub_lib::cryptde_null::CryptDENull;
     use crate::sub_lib::dispatcher::Component;
     use crate::sub_lib::route::RouteSegment;
     use crate::test_utils::recorder::Recorder;
@@ -185,6 +221,45 @@ mod tests {
         static ref CRYPTDE_PAIR: CryptDEPair = CryptDEPair::null();
     }
 
+    #[test]
+    fn constants_have_correct_values() {
+        assert_eq!(SESSION_KEY_LIFETIME_SECS, 600);
+    }
+
+    #[test]
+    fn session_key_id_is_made_of_the_first_eight_bytes_of_the_key() {
+        let long_key = PublicKey::new(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+        let short_key = PublicKey::new(&[1, 2, 3]);
+
+        let long_result = session_key_id(&long_key);
+        let short_result = session_key_id(&short_key);
+
+        assert_eq!(long_result, 0x0102030405060708);
+        assert_eq!(short_result, 0x0102030000000000);
+    }
+
+    #[test]
+    fn find_session_cryptde_looks_session_keys_up_by_id() {
+        let first_cryptde = CryptDENull::new(TEST_DEFAULT_CHAIN);
+        let second_cryptde = CryptDENull::new(TEST_DEFAULT_CHAIN);
+        let session_cryptdes = vec![first_cryptde.dup(), second_cryptde.dup()];
+
+        let found = find_session_cryptde(
+            &session_cryptdes,
+            session_key_id(second_cryptde.public_key()),
+        );
+        let not_found = find_session_cryptde(
+            &session_cryptdes,
+            !session_key_id(second_cryptde.public_key()),
+        );
+
+        assert_eq!(
+            found.map(|cryptde| cryptde.public_key()),
+            Some(second_cryptde.public_key())
+        );
+        assert_eq!(not_found.is_none(), true);
+    }
+
     #[test]
     fn hopper_subs_debug() {
         let recorder = Recorder::new().start();
@@ -194,6 +269,7 @@ mod tests {
             from_hopper_client: recipient!(recorder, IncipientCoresPackage),
             from_hopper_client_no_lookup: recipient!(recorder, NoLookupIncipientCoresPackage),
             from_dispatcher: recipient!(recorder, InboundClientData),
+            config_change_msg_sub: recipient!(recorder, ConfigChangeMsg),
             node_from_ui: recipient!(recorder, NodeFromUiMessage),
         };
 
@@ -277,7 +353,10 @@ mod tests {
         let cryptde = CRYPTDE_PAIR.main.as_ref();
         let result = IncipientCoresPackage::new(
             cryptde,
-            Route { hops: vec![] },
+            Route {
+                hops: vec![],
+                top_hop_key_id_opt: None,
+            },
             make_meaningless_message_type(),
             &PublicKey::new(&[]),
         );
@@ -0,0 +1,171 @@
+// Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
+
+use crate::hopper::live_cores_package::LiveCoresPackage;
+use crate::sub_lib::cryptde::CryptData;
+use crate::sub_lib::data_version::DataVersion;
+use crate::sub_lib::migrations::utils::value_to_type;
+use crate::sub_lib::route::Route;
+use crate::sub_lib::versioned_data::Migrations;
+use crate::sub_lib::versioned_data::{MigrationError, StepError, VersionedData};
+use lazy_static::lazy_static;
+use serde_cbor::Value;
+use std::convert::TryFrom;
+
+lazy_static! {
+    pub static ref MIGRATIONS: Migrations = {
+        let current_version = masq_lib::constants::LIVE_CORES_PACKAGE_CURRENT_VERSION;
+        let mut migrations = Migrations::new(current_version);
+
+        migrate_value!(dv!(0, 1), LiveCoresPackage, LiveCoresPackageMF_0v1, {|value: serde_cbor::Value| {
+            LiveCoresPackage::try_from (&value)
+        }});
+        migrations.add_step (masq_lib::data_version::FUTURE_VERSION, dv!(0, 1), Box::new (LiveCoresPackageMF_0v1{}));
+
+        // add more steps here
+
+        migrations
+    };
+}
+
+impl From<LiveCoresPackage> for VersionedData<LiveCoresPackage> {
+    fn from(data: LiveCoresPackage) -> Self {
+        VersionedData::new(&MIGRATIONS, &data)
+    }
+}
+
+impl TryFrom<VersionedData<LiveCoresPackage>> for LiveCoresPackage {
+    type Error = MigrationError;
+
+    fn try_from(vd: VersionedData<LiveCoresPackage>) -> Result<Self, Self::Error> {
+        vd.extract(&MIGRATIONS)
+    }
+}
+
+impl TryFrom<&Value> for LiveCoresPackage {
+    type Error = StepError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Map(map) => {
+                let mut version_opt: Option<DataVersion> = None;
+                let mut route_opt: Option<Route> = None;
+                let mut payload_opt: Option<CryptData> = None;
+                map.iter().for_each(|(k, v)| {
+                    if let Value::Text(field_name) = k {
+                        match field_name.as_str() {
+                            "version" => version_opt = value_to_type::<DataVersion>(v),
+                            "route" => route_opt = value_to_type::<Route>(v),
+                            "payload" => payload_opt = value_to_type::<CryptData>(v),
+                            _ => (),
+                        }
+                    }
+                });
+                match (version_opt, route_opt, payload_opt) {
+                    (Some(version), Some(route), Some(payload)) => Ok(LiveCoresPackage {
+                        version,
+                        route,
+                        payload,
+                    }),
+                    (version_opt, route_opt, payload_opt) => {
+                        let missing_fields = [
+                            ("version", version_opt.is_none()),
+                            ("route", route_opt.is_none()),
+                            ("payload", payload_opt.is_none()),
+                        ]
+                        .iter()
+                        .filter(|(_, missing)| *missing)
+                        .map(|(name, _)| *name)
+                        .collect::<Vec<&str>>();
+                        Err(StepError::SemanticError(format!(
+                            "Missing fields: {:?}",
+                            missing_fields
+                        )))
+                    }
+                }
+            }
+            _ => Err(StepError::SemanticError(format!(
+                "Expected Value::Map; found {:?}",
+                value
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bootstrapper::CryptDEPair;
+    use crate::test_utils::make_meaningless_route;
+    use lazy_static::lazy_static;
+    use masq_lib::data_version::DataVersion as MigrationVersion;
+    use serde_derive::{Deserialize, Serialize};
+
+    lazy_static! {
+        static ref CRYPTDE_PAIR: CryptDEPair = CryptDEPair::null();
+    }
+
+    #[test]
+    fn can_migrate_from_the_future() {
+        #[derive(Serialize, Deserialize)]
+        struct ExampleFutureLCP {
+            pub version: DataVersion,
+            pub route: Route,
+            pub payload: CryptData,
+            pub another_field: String,
+            pub yet_another_field: u64,
+        }
+        let expected = LiveCoresPackage::new(
+            make_meaningless_route(&CRYPTDE_PAIR),
+            CryptData::new(&[1, 2, 3, 4]),
+        );
+        let future_lcp = ExampleFutureLCP {
+            version: expected.version,
+            route: expected.route.clone(),
+            payload: expected.payload.clone(),
+            another_field: "These are the times that try men's souls".to_string(),
+            yet_another_field: 42,
+        };
+        let future_migrations = Migrations::new(MigrationVersion::new(4095, 4095));
+        let serialized =
+            serde_cbor::ser::to_vec(&VersionedData::new(&future_migrations, &future_lcp)).unwrap();
+        let future_vd =
+            serde_cbor::de::from_slice::<VersionedData<LiveCoresPackage>>(&serialized).unwrap();
+
+        let actual = LiveCoresPackage::try_from(future_vd);
+
+        assert_eq!(actual, Ok(expected));
+    }
+
+    #[test]
+    fn cannot_migrate_from_value_without_all_fields() {
+        let mut map = std::collections::BTreeMap::new();
+        map.insert(
+            Value::Text("payload".to_string()),
+            Value::Bytes(vec![1, 2, 3]),
+        );
+        let value = Value::Map(map);
+
+        let result = LiveCoresPackage::try_from(&value);
+
+        assert_eq!(
+            result,
+            Err(StepError::SemanticError(
+                "Missing fields: [\"version\", \"route\"]".to_string()
+            ))
+        )
+    }
+
+    #[test]
+    fn cannot_migrate_from_value_other_than_map() {
+        let value = Value::Bool(true);
+
+        let result = LiveCoresPackage::try_from(&value);
+
+        assert_eq!(
+            result,
+            Err(StepError::SemanticError(
+                "Expected Value::Map; found Bool(true)".to_string()
+            ))
+        )
+    }
+}
@@ -5,5 +5,6 @@ pub mod client_response_payload;
 pub mod dns_resolve_failure;
 pub mod gossip;
 pub mod gossip_failure;
+pub mod live_cores_package;
 pub mod node_record_inner;
 pub mod utils;
@@ -1,7 +1,8 @@
 // Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 
-use crate::neighborhood::node_record::NodeRecordInner_0v1;
+use crate::neighborhood::node_record::{KeyHandover, NodeRecordInner_0v1};
 use crate::sub_lib::cryptde::PublicKey;
+use crate::sub_lib::earning_wallet_shares::EarningWalletShares;
 use crate::sub_lib::migrations::utils::value_to_type;
 use crate::sub_lib::neighborhood::RatePack;
 use crate::sub_lib::versioned_data::{MigrationError, Migrations, StepError, VersionedData};
@@ -56,12 +57,18 @@ impl TryFrom<&Value> for NodeRecordInner_0v1 {
                 let mut routes_data_opt: Option<bool> = None;
                 let mut version_opt: Option<u32> = None;
                 let mut country_code_opt: Option<String> = None;
+                let mut earning_wallet_shares_opt: Option<EarningWalletShares> = None;
+                let mut key_handover_opt: Option<KeyHandover> = None;
+                let mut session_key_opt: Option<PublicKey> = None;
                 map.keys().for_each(|k| {
                     let v = map.get(k).expect("Disappeared");
                     match (k, v) {
                         (Value::Text(field_name), Value::Map(_)) => match field_name.as_str() {
                             "earning_wallet" => earning_wallet_opt = value_to_type::<Wallet>(v),
                             "rate_pack" => rate_pack_opt = value_to_type::<RatePack>(v),
+                            "key_handover_opt" => {
+                                key_handover_opt = value_to_type::<KeyHandover>(v)
+                            }
                             _ => (),
                         },
                         (Value::Text(field_name), Value::Array(field_value)) => {
@@ -69,12 +76,19 @@ impl TryFrom<&Value> for NodeRecordInner_0v1 {
                                 "neighbors" => {
                                     neighbors_opt = Self::public_keys_to_btree_set(field_value)
                                 }
+                                "earning_wallet_shares" => {
+                                    earning_wallet_shares_opt =
+                                        value_to_type::<EarningWalletShares>(v)
+                                }
                                 _ => (),
                             }
                         }
                         (Value::Text(field_name), Value::Bytes(field_value)) => {
                             match field_name.as_str() {
                                 "public_key" => public_key_opt = Some(PublicKey::new(field_value)),
+                                "session_key_opt" => {
+                                    session_key_opt = Some(PublicKey::new(field_value))
+                                }
                                 _ => (),
                             }
                         }
@@ -140,6 +154,9 @@ impl TryFrom<&Value> for NodeRecordInner_0v1 {
                     routes_data: routes_data_opt.expect("public_key disappeared"),
                     version: version_opt.expect("public_key disappeared"),
                     country_code_opt,
+                    earning_wallet_shares: earning_wallet_shares_opt.unwrap_or_default(),
+                    key_handover_opt,
+                    session_key_opt,
                 })
             }
             _ => Err(StepError::SemanticError(format!(
@@ -183,6 +200,7 @@ mod tests {
             pub routes_data: bool,
             pub version: u32,
             pub country_code: Option<String>,
+            pub session_key_opt: Option<PublicKey>,
             pub another_field: String,
             pub yet_another_field: u64,
         }
@@ -197,6 +215,9 @@ mod tests {
             routes_data: true,
             version: 42,
             country_code_opt: Some("AU".to_string()),
+            earning_wallet_shares: EarningWalletShares::default(),
+            key_handover_opt: None,
+            session_key_opt: Some(PublicKey::new(&[9, 8, 7, 6])),
         };
         let future_nri = ExampleFutureNRI {
             public_key: expected_nri.public_key.clone(),
@@ -207,6 +228,7 @@ mod tests {
             routes_data: expected_nri.routes_data,
             version: expected_nri.version,
             country_code: expected_nri.country_code_opt.clone(),
+            session_key_opt: expected_nri.session_key_opt.clone(),
             another_field: "These are the times that try men's souls".to_string(),
             yet_another_field: 1234567890,
         };
@@ -17,6 +17,7 @@ pub mod cryptde_null;
 pub mod cryptde_real;
 pub mod data_version;
 pub mod dispatcher;
+pub mod earning_wallet_shares;
 pub mod framer;
 pub mod framer_utils;
 pub mod hop;
@@ -36,10 +36,16 @@ impl Debug for PeerActors {
 pub type ConfigChangeSubs = Vec<Recipient<ConfigChangeMsg>>;
 impl PeerActors {
     pub fn config_change_subs(&self) -> ConfigChangeSubs {
-        vec![
+        let mut subs = vec![
             self.accountant.config_change_msg_sub.clone(),
             self.neighborhood.config_change_msg_sub.clone(),
-        ]
+            self.hopper.config_change_msg_sub.clone(),
+            self.proxy_server.config_change_msg_sub.clone(),
+        ];
+        if let Some(proxy_client) = self.proxy_client_opt.as_ref() {
+            subs.push(proxy_client.config_change_msg_sub.clone())
+        }
+        subs
     }
 }
 
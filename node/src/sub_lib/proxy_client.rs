@@ -1,6 +1,7 @@
 // Copyright (c) 2019, MASQ (https://masq.ai) and/or its affiliates. All rights reserved.
 use crate::bootstrapper::CryptDEPair;
 use crate::sub_lib::hopper::{ExpiredCoresPackage, MessageType};
+use crate::sub_lib::neighborhood::ConfigChangeMsg;
 use crate::sub_lib::peer_actors::BindMessage;
 use crate::sub_lib::proxy_server::ClientRequestPayload_0v1;
 use crate::sub_lib::sequence_buffer::SequencedPacket;
@@ -72,6 +73,7 @@ pub struct ProxyClientSubs {
     pub from_hopper: Recipient<ExpiredCoresPackage<ClientRequestPayload_0v1>>,
     pub inbound_server_data: Recipient<InboundServerData>,
     pub dns_resolve_failed: Recipient<DnsResolveFailure_0v1>,
+    pub config_change_msg_sub: Recipient<ConfigChangeMsg>,
     pub node_from_ui: Recipient<NodeFromUiMessage>,
 }
 
@@ -138,6 +140,7 @@ mod tests {
             from_hopper: recipient!(recorder, ExpiredCoresPackage<ClientRequestPayload_0v1>),
             inbound_server_data: recipient!(recorder, InboundServerData),
             dns_resolve_failed: recipient!(recorder, DnsResolveFailure_0v1),
+            config_change_msg_sub: recipient!(recorder, ConfigChangeMsg),
             node_from_ui: recipient!(recorder, NodeFromUiMessage),
         };
 
@@ -4,7 +4,7 @@ use crate::sub_lib::data_version::DataVersion;
 use crate::sub_lib::dispatcher::InboundClientData;
 use crate::sub_lib::dispatcher::StreamShutdownMsg;
 use crate::sub_lib::hopper::{ExpiredCoresPackage, MessageType};
-use crate::sub_lib::neighborhood::{ExpectedService, RouteQueryResponse};
+use crate::sub_lib::neighborhood::{ConfigChangeMsg, ExpectedService, RouteQueryResponse};
 use crate::sub_lib::peer_actors::BindMessage;
 use crate::sub_lib::proxy_client::{ClientResponsePayload_0v1, DnsResolveFailure_0v1};
 use crate::sub_lib::sequence_buffer::SequencedPacket;
@@ -13,11 +13,24 @@ use crate::sub_lib::utils::MessageScheduler;
 use crate::sub_lib::versioned_data::VersionedData;
 use actix::Message;
 use actix::Recipient;
+use masq_lib::constants::UNSCRUBBABLE_HTTP_HEADERS;
 use masq_lib::ui_gateway::NodeFromUiMessage;
 use serde_derive::{Deserialize, Serialize};
-use std::fmt::Debug;
+use std::fmt;
+use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
 
 pub const DEFAULT_MINIMUM_HOP_COUNT: usize = 3;
+// What 'on' means for --scrub-headers
+pub const STANDARD_SCRUBBED_HEADERS: &[&str] = &[
+    "x-forwarded-for",
+    "forwarded",
+    "via",
+    "x-real-ip",
+    "referer",
+    "user-agent",
+    "cookie",
+];
 
 #[allow(clippy::upper_case_acronyms)]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
@@ -55,6 +68,58 @@ impl ClientRequestPayload_0v1 {
     }
 }
 
+// The identifying headers to scrub from plain HTTP requests; names are kept in lower case
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HeaderScrubbing {
+    pub header_names: Vec<String>,
+}
+
+impl HeaderScrubbing {
+    pub fn is_off(&self) -> bool {
+        self.header_names.is_empty()
+    }
+
+    pub fn scrubs(&self, header_name: &str) -> bool {
+        self.header_names
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(header_name))
+    }
+}
+
+impl FromStr for HeaderScrubbing {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let header_names = match value.to_lowercase().as_str() {
+            "off" => vec![],
+            "on" => STANDARD_SCRUBBED_HEADERS
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            lower_case_value => lower_case_value
+                .split(',')
+                .map(|name| name.trim().to_string())
+                .collect(),
+        };
+        match header_names
+            .iter()
+            .find(|name| name.is_empty() || UNSCRUBBABLE_HTTP_HEADERS.contains(&name.as_str()))
+        {
+            Some(_) => Err(format!("Can't scrub headers '{}'", value)),
+            None => Ok(HeaderScrubbing { header_names }),
+        }
+    }
+}
+
+impl Display for HeaderScrubbing {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.is_off() {
+            true => write!(f, "off"),
+            false => write!(f, "{}", self.header_names.join(",")),
+        }
+    }
+}
+
 #[derive(Message, Debug, PartialEq, Eq)]
 pub struct AddReturnRouteMessage {
     pub return_route_id: u32,
@@ -86,6 +151,7 @@ pub struct ProxyServerSubs {
     pub node_from_ui: Recipient<NodeFromUiMessage>,
     pub route_result_sub: Recipient<AddRouteResultMessage>,
     pub schedule_stream_key_purge: Recipient<MessageScheduler<StreamKeyPurge>>,
+    pub config_change_msg_sub: Recipient<ConfigChangeMsg>,
 }
 
 impl Debug for ProxyServerSubs {
@@ -118,8 +184,52 @@ mod tests {
             node_from_ui: recipient!(recorder, NodeFromUiMessage),
             route_result_sub: recipient!(recorder, AddRouteResultMessage),
             schedule_stream_key_purge: recipient!(recorder, MessageScheduler<StreamKeyPurge>),
+            config_change_msg_sub: recipient!(recorder, ConfigChangeMsg),
         };
 
         assert_eq!(format!("{:?}", subject), "ProxyServerSubs");
     }
+
+    #[test]
+    fn header_scrubbing_can_be_parsed_from_on_off_or_a_list_of_names() {
+        assert_eq!(
+            HeaderScrubbing::from_str("OFF"),
+            Ok(HeaderScrubbing::default())
+        );
+        assert_eq!(
+            HeaderScrubbing::from_str("on").unwrap().header_names,
+            STANDARD_SCRUBBED_HEADERS
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<String>>()
+        );
+        assert_eq!(
+            HeaderScrubbing::from_str("Referer, X-Client-Id"),
+            Ok(HeaderScrubbing {
+                header_names: vec!["referer".to_string(), "x-client-id".to_string()]
+            })
+        );
+        assert_eq!(
+            HeaderScrubbing::from_str("Referer,,Via"),
+            Err("Can't scrub headers 'Referer,,Via'".to_string())
+        );
+        assert_eq!(
+            HeaderScrubbing::from_str("Referer,Host"),
+            Err("Can't scrub headers 'Referer,Host'".to_string())
+        );
+    }
+
+    #[test]
+    fn header_scrubbing_knows_which_headers_it_scrubs_and_displays_them() {
+        let off = HeaderScrubbing::default();
+        let subject = HeaderScrubbing::from_str("referer,via").unwrap();
+
+        assert_eq!(off.is_off(), true);
+        assert_eq!(off.to_string(), "off".to_string());
+        assert_eq!(subject.is_off(), false);
+        assert_eq!(subject.scrubs("Referer"), true);
+        assert_eq!(subject.scrubs("VIA"), true);
+        assert_eq!(subject.scrubs("Cookie"), false);
+        assert_eq!(subject.to_string(), "referer,via".to_string());
+    }
 }
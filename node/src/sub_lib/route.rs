@@ -6,17 +6,22 @@ use crate::sub_lib::cryptde::PublicKey;
 use crate::sub_lib::cryptde::{decodex, CodexError};
 use crate::sub_lib::dispatcher::Component;
 use crate::sub_lib::hop::LiveHop;
+use crate::sub_lib::hopper::{find_session_cryptde, session_key_id};
 use crate::sub_lib::wallet::Wallet;
 use ethereum_types::Address;
 use itertools::Itertools;
 use serde_derive::{Deserialize, Serialize};
 use std::cmp::min;
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::iter;
 
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub struct Route {
     pub hops: Vec<CryptData>,
+    // Id of the session key the top hop is encrypted to; None means the Node's static key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_hop_key_id_opt: Option<u64>,
 }
 
 impl Route {
@@ -90,6 +95,22 @@ impl Route {
         }
     }
 
+    // The top hop is encrypted either to one of the Node's session keys or to its main key; this
+    // picks the CryptDE named by top_hop_key_id_opt, so that it can be handed to next_hop() or
+    // shift().
+    pub fn top_hop_cryptde<'a>(
+        &self,
+        main_cryptde: &'a dyn CryptDE,
+        session_cryptdes: &'a [Box<dyn CryptDE>],
+    ) -> Result<&'a dyn CryptDE, CodexError> {
+        match self.top_hop_key_id_opt {
+            None => Ok(main_cryptde),
+            Some(key_id) => find_session_cryptde(session_cryptdes, key_id).ok_or(
+                CodexError::RoutingError(RouteError::UnknownSessionKey(key_id)),
+            ),
+        }
+    }
+
     pub fn shift(&mut self, cryptde: &dyn CryptDE) -> Result<LiveHop, CodexError> {
         if self.hops.is_empty() {
             return Err(CodexError::RoutingError(RouteError::EmptyRoute));
@@ -101,6 +122,7 @@ impl Route {
         let mut garbage_can: Vec<u8> = iter::repeat(0u8).take(top_hop_len).collect();
         cryptde.random(&mut garbage_can[..]);
         self.hops.push(CryptData::new(&garbage_can[..]));
+        self.top_hop_key_id_opt = next_hop.session_key_opt.as_ref().map(session_key_id);
 
         Ok(next_hop)
     }
@@ -157,6 +179,10 @@ impl Route {
         }
         let over_component = over.recipient;
         let over_keys = over.keys.iter();
+        let mut session_keys = over.session_keys.clone();
+        if let Some(back) = back.as_ref() {
+            session_keys.extend(back.session_keys.clone());
+        }
 
         let mut hops = Route::over_segment(
             back.is_none(),
@@ -177,6 +203,7 @@ impl Route {
         Route::hops_to_route(
             hops[0..].to_vec(),
             &over.keys[0],
+            &session_keys,
             return_route_id_opt,
             cryptde,
         )
@@ -293,26 +320,62 @@ impl Route {
         None
     }
 
+    // The top hop never leaves the originating Node, so it stays encrypted to that Node's own
+    // key. Every other hop is encrypted to the session key of the Node that will take it off
+    // the Route, if session_key_for_hop() says so, and to its static key otherwise. The hop
+    // before it carries that session key, so that the Node before it can seal the package to it.
     fn hops_to_route(
-        hops: Vec<LiveHop>,
+        mut hops: Vec<LiveHop>,
         top_hop_key: &PublicKey,
+        session_keys: &HashMap<PublicKey, PublicKey>,
         return_route_id_opt: Option<u32>,
         cryptde: &dyn CryptDE,
     ) -> Result<Route, CodexError> {
+        let hop_keys: Vec<PublicKey> = iter::once(top_hop_key.clone())
+            .chain(hops.iter().map(|hop| hop.public_key.clone()))
+            .take(hops.len())
+            .collect();
+        let hop_session_keys: Vec<Option<PublicKey>> = (0..hops.len())
+            .map(|index| Self::session_key_for_hop(&hop_keys, index, session_keys).cloned())
+            .collect();
+        hops.iter_mut()
+            .zip(hop_session_keys.iter().skip(1))
+            .for_each(|(hop, session_key_opt)| hop.session_key_opt = session_key_opt.clone());
         let mut hops_enc: Vec<CryptData> = Vec::new();
-        let mut hop_key = top_hop_key;
-        for data_hop in &hops {
-            hops_enc.push(match data_hop.encode(hop_key, cryptde) {
+        for (index, data_hop) in hops.iter().enumerate() {
+            let encryption_key = hop_session_keys[index].as_ref().unwrap_or(&hop_keys[index]);
+            hops_enc.push(match data_hop.encode(encryption_key, cryptde) {
                 Ok(crypt_data) => crypt_data,
                 Err(e) => return Err(e),
             });
-            hop_key = &data_hop.public_key;
         }
         if let Some(return_route_id) = return_route_id_opt {
             let return_route_id_enc = Self::encrypt_return_route_id(return_route_id, cryptde);
             hops_enc.push(return_route_id_enc);
         }
-        Ok(Route { hops: hops_enc })
+        Ok(Route {
+            hops: hops_enc,
+            top_hop_key_id_opt: None,
+        })
+    }
+
+    // A Node only gets traffic under its session key if the Node before it knows to seal the
+    // package to that key: either because that's us (index 1) or because it has published a
+    // session key itself, which Nodes too old to do so never have.
+    fn session_key_for_hop<'a>(
+        hop_keys: &[PublicKey],
+        index: usize,
+        session_keys: &'a HashMap<PublicKey, PublicKey>,
+    ) -> Option<&'a PublicKey> {
+        if index == 0 || index >= hop_keys.len() {
+            return None;
+        }
+        let session_key = session_keys.get(&hop_keys[index])?;
+        if index == 1 || session_keys.contains_key(&hop_keys[index - 1]) {
+            Some(session_key)
+        } else {
+            None
+        }
     }
 
     fn encrypt_return_route_id(return_route_id: u32, cryptde: &dyn CryptDE) -> CryptData {
@@ -324,6 +387,8 @@ impl Route {
 pub struct RouteSegment {
     pub keys: Vec<PublicKey>,
     pub recipient: Component,
+    // Session keys published by Nodes in the segment, by their static keys
+    pub session_keys: HashMap<PublicKey, PublicKey>,
 }
 
 impl Debug for RouteSegment {
@@ -338,8 +403,21 @@ impl RouteSegment {
         RouteSegment {
             keys: keys.iter().map(|k| (*k).clone()).collect(),
             recipient,
+            session_keys: HashMap::new(),
         }
     }
+
+    pub fn with_session_keys(mut self, session_keys: HashMap<PublicKey, PublicKey>) -> Self {
+        self.session_keys = session_keys;
+        self
+    }
+
+    // The session key, if any, that the last Node in the segment will receive its package
+    // under; a payload for that Node should be encrypted to it as well.
+    pub fn destination_session_key_opt(&self) -> Option<PublicKey> {
+        let last_index = self.keys.len().checked_sub(1)?;
+        Route::session_key_for_hop(&self.keys, last_index, &self.session_keys).cloned()
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -348,6 +426,7 @@ pub enum RouteError {
     EmptyRoute,
     TooFewKeysInRouteSegment,
     DisjointRouteSegments,
+    UnknownSessionKey(u64),
 }
 
 #[cfg(test)]
@@ -371,6 +450,7 @@ mod tests {
 
         let subject = Route {
             hops: vec![Route::encrypt_return_route_id(42, cryptde)],
+            top_hop_key_id_opt: None,
         };
 
         assert_eq!(subject.id(cryptde), Ok(42));
@@ -380,7 +460,10 @@ mod tests {
     fn id_returns_empty_route_error_when_the_route_is_empty() {
         let cryptde = CRYPTDE_PAIR.main.as_ref();
 
-        let subject = Route { hops: vec![] };
+        let subject = Route {
+            hops: vec![],
+            top_hop_key_id_opt: None,
+        };
 
         assert_eq!(
             subject.id(cryptde),
@@ -395,6 +478,7 @@ mod tests {
         let cryptde2 = CryptDENull::from(&PublicKey::new(b"key b"), TEST_DEFAULT_CHAIN);
         let subject = Route {
             hops: vec![Route::encrypt_return_route_id(42, &cryptde1)],
+            top_hop_key_id_opt: None,
         };
 
         let _ = subject.id(&cryptde2);
@@ -645,6 +729,285 @@ mod tests {
         );
     }
 
+    #[test]
+    fn construct_encrypts_all_but_the_top_hop_to_session_keys_where_there_are_any() {
+        let a_key = PublicKey::new(&[65, 65, 65]);
+        let b_key = PublicKey::new(&[66, 66, 66]);
+        let c_key = PublicKey::new(&[67, 67, 67]);
+        let a_session_key = PublicKey::new(&[97, 97, 97]);
+        let b_session_key = PublicKey::new(&[98, 98, 98]);
+        let cryptde = CRYPTDE_PAIR.main.as_ref();
+        let paying_wallet = make_paying_wallet(b"wallet");
+        let contract_address = TEST_DEFAULT_CHAIN.rec().contract;
+        let session_keys = HashMap::from([
+            (a_key.clone(), a_session_key.clone()),
+            (b_key.clone(), b_session_key.clone()),
+        ]);
+
+        let subject = Route::one_way(
+            RouteSegment::new(vec![&a_key, &b_key, &c_key], Component::Neighborhood)
+                .with_session_keys(session_keys),
+            cryptde,
+            Some(paying_wallet.clone()),
+            Some(contract_address.clone()),
+        )
+        .unwrap();
+
+        let empty_public_key = PublicKey::new(b"");
+        let mut first_hop = LiveHop::new(
+            &b_key,
+            Some(paying_wallet.as_payer(&a_key, &contract_address)),
+            Component::Hopper,
+        );
+        first_hop.session_key_opt = Some(b_session_key.clone());
+        assert_eq!(
+            subject.hops,
+            vec!(
+                first_hop.encode(&a_key, cryptde).unwrap(),
+                LiveHop::new(
+                    &c_key,
+                    Some(paying_wallet.as_payer(&b_key, &contract_address)),
+                    Component::Hopper
+                )
+                .encode(&b_session_key, cryptde)
+                .unwrap(),
+                LiveHop::new(
+                    &empty_public_key,
+                    Some(paying_wallet.as_payer(&c_key, &contract_address)),
+                    Component::Neighborhood,
+                )
+                .encode(&c_key, cryptde)
+                .unwrap(),
+            )
+        );
+    }
+
+    #[test]
+    fn construct_uses_no_session_key_for_a_node_whose_predecessor_has_none() {
+        let a_key = PublicKey::new(&[65, 65, 65]);
+        let b_key = PublicKey::new(&[66, 66, 66]);
+        let c_key = PublicKey::new(&[67, 67, 67]);
+        let d_key = PublicKey::new(&[68, 68, 68]);
+        let c_session_key = PublicKey::new(&[99, 99, 99]);
+        let d_session_key = PublicKey::new(&[100, 100, 100]);
+        let cryptde = CRYPTDE_PAIR.main.as_ref();
+        let session_keys = HashMap::from([
+            (c_key.clone(), c_session_key.clone()),
+            (d_key.clone(), d_session_key.clone()),
+        ]);
+
+        let subject = Route::one_way(
+            RouteSegment::new(
+                vec![&a_key, &b_key, &c_key, &d_key],
+                Component::Neighborhood,
+            )
+            .with_session_keys(session_keys),
+            cryptde,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let mut third_hop = LiveHop::new(&d_key, None, Component::Hopper);
+        third_hop.session_key_opt = Some(d_session_key.clone());
+        assert_eq!(
+            subject.hops,
+            vec!(
+                LiveHop::new(&b_key, None, Component::Hopper)
+                    .encode(&a_key, cryptde)
+                    .unwrap(),
+                LiveHop::new(&c_key, None, Component::Hopper)
+                    .encode(&b_key, cryptde)
+                    .unwrap(),
+                third_hop.encode(&c_key, cryptde).unwrap(),
+                LiveHop::new(&PublicKey::new(b""), None, Component::Neighborhood)
+                    .encode(&d_session_key, cryptde)
+                    .unwrap(),
+            )
+        );
+    }
+
+    #[test]
+    fn destination_session_key_opt_follows_the_rules_for_hops() {
+        let a_key = PublicKey::new(&[65, 65, 65]);
+        let b_key = PublicKey::new(&[66, 66, 66]);
+        let c_key = PublicKey::new(&[67, 67, 67]);
+        let b_session_key = PublicKey::new(&[98, 98, 98]);
+        let c_session_key = PublicKey::new(&[99, 99, 99]);
+        let segment = |keys: Vec<&PublicKey>, session_keys: Vec<(&PublicKey, &PublicKey)>| {
+            RouteSegment::new(keys, Component::ProxyClient).with_session_keys(
+                session_keys
+                    .into_iter()
+                    .map(|(key, session_key)| (key.clone(), session_key.clone()))
+                    .collect(),
+            )
+        };
+
+        let one_hop = segment(vec![&a_key, &b_key], vec![(&b_key, &b_session_key)]);
+        let two_hop = segment(
+            vec![&a_key, &b_key, &c_key],
+            vec![(&b_key, &b_session_key), (&c_key, &c_session_key)],
+        );
+        let two_hop_old_relay =
+            segment(vec![&a_key, &b_key, &c_key], vec![(&c_key, &c_session_key)]);
+        let no_session_key = segment(vec![&a_key, &b_key], vec![]);
+        let zero_hop = segment(vec![&a_key], vec![(&a_key, &b_session_key)]);
+        let empty = segment(vec![], vec![]);
+
+        assert_eq!(one_hop.destination_session_key_opt(), Some(b_session_key));
+        assert_eq!(two_hop.destination_session_key_opt(), Some(c_session_key));
+        assert_eq!(two_hop_old_relay.destination_session_key_opt(), None);
+        assert_eq!(no_session_key.destination_session_key_opt(), None);
+        assert_eq!(zero_hop.destination_session_key_opt(), None);
+        assert_eq!(empty.destination_session_key_opt(), None);
+    }
+
+    #[test]
+    fn shift_names_the_session_key_the_new_top_hop_is_encrypted_to() {
+        let origin_cryptde = CryptDENull::new(TEST_DEFAULT_CHAIN);
+        let relay_key = PublicKey::new(&[66, 66, 66]);
+        let relay_session_key = PublicKey::new(&[1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        let exit_key = PublicKey::new(&[67, 67, 67]);
+        let mut subject = Route::one_way(
+            RouteSegment::new(
+                vec![origin_cryptde.public_key(), &relay_key, &exit_key],
+                Component::Neighborhood,
+            )
+            .with_session_keys(HashMap::from([(
+                relay_key.clone(),
+                relay_session_key.clone(),
+            )])),
+            &origin_cryptde,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(subject.top_hop_key_id_opt, None);
+
+        let next_hop = subject.shift(&origin_cryptde).unwrap();
+
+        assert_eq!(next_hop.session_key_opt, Some(relay_session_key.clone()));
+        assert_eq!(subject.top_hop_key_id_opt, Some(0x0102030405060708));
+        let relay_session_cryptde = CryptDENull::from(&relay_session_key, TEST_DEFAULT_CHAIN);
+        let next_hop = subject.shift(&relay_session_cryptde).unwrap();
+        assert_eq!(next_hop.public_key, exit_key);
+        assert_eq!(subject.top_hop_key_id_opt, None);
+    }
+
+    #[test]
+    fn top_hop_cryptde_finds_the_session_cryptde_named_by_the_top_hop_key_id() {
+        let main_cryptde = CryptDENull::new(TEST_DEFAULT_CHAIN);
+        let session_cryptdes: Vec<Box<dyn CryptDE>> = vec![
+            Box::new(CryptDENull::new(TEST_DEFAULT_CHAIN)),
+            Box::new(CryptDENull::new(TEST_DEFAULT_CHAIN)),
+        ];
+        let origin_cryptde = CryptDENull::new(TEST_DEFAULT_CHAIN);
+        let session_keys = HashMap::from([(
+            main_cryptde.public_key().clone(),
+            session_cryptdes[1].public_key().clone(),
+        )]);
+        let mut subject = Route::one_way(
+            RouteSegment::new(
+                vec![origin_cryptde.public_key(), main_cryptde.public_key()],
+                Component::Neighborhood,
+            )
+            .with_session_keys(session_keys),
+            &origin_cryptde,
+            None,
+            None,
+        )
+        .unwrap();
+        subject.shift(&origin_cryptde).unwrap();
+
+        let result = subject
+            .top_hop_cryptde(&main_cryptde, &session_cryptdes)
+            .unwrap();
+
+        assert_eq!(result.public_key(), session_cryptdes[1].public_key());
+        assert_eq!(
+            subject.next_hop(result).unwrap().component,
+            Component::Neighborhood
+        );
+    }
+
+    #[test]
+    fn top_hop_cryptde_uses_the_main_cryptde_when_there_is_no_key_id() {
+        let main_cryptde = CryptDENull::new(TEST_DEFAULT_CHAIN);
+        let session_cryptdes: Vec<Box<dyn CryptDE>> =
+            vec![Box::new(CryptDENull::new(TEST_DEFAULT_CHAIN))];
+        let other_key = CryptDENull::new(TEST_DEFAULT_CHAIN).public_key().clone();
+        let subject = Route::single_hop(&other_key, &main_cryptde).unwrap();
+
+        let result = subject
+            .top_hop_cryptde(&main_cryptde, &session_cryptdes)
+            .unwrap();
+
+        assert_eq!(result.public_key(), main_cryptde.public_key());
+    }
+
+    #[test]
+    fn top_hop_cryptde_complains_about_an_unknown_key_id() {
+        let main_cryptde = CryptDENull::new(TEST_DEFAULT_CHAIN);
+        let session_cryptdes: Vec<Box<dyn CryptDE>> =
+            vec![Box::new(CryptDENull::new(TEST_DEFAULT_CHAIN))];
+        let mut subject = Route::single_hop(main_cryptde.public_key(), &main_cryptde).unwrap();
+        subject.top_hop_key_id_opt = Some(1234);
+
+        let result = subject.top_hop_cryptde(&main_cryptde, &session_cryptdes);
+
+        assert_eq!(
+            result.err(),
+            Some(CodexError::RoutingError(RouteError::UnknownSessionKey(
+                1234
+            )))
+        );
+    }
+
+    #[test]
+    fn route_and_hop_from_nodes_without_session_keys_still_deserialize() {
+        #[derive(Serialize)]
+        struct OldLiveHop {
+            public_key: PublicKey,
+            payer: Option<crate::blockchain::payer::Payer>,
+            component: Component,
+        }
+        #[derive(Serialize)]
+        struct OldRoute {
+            hops: Vec<CryptData>,
+        }
+        let old_hop = OldLiveHop {
+            public_key: PublicKey::new(&[1, 2, 3]),
+            payer: None,
+            component: Component::ProxyClient,
+        };
+        let old_route = OldRoute {
+            hops: vec![CryptData::new(&[4, 5, 6])],
+        };
+
+        let hop =
+            serde_cbor::de::from_slice::<LiveHop>(&serde_cbor::ser::to_vec(&old_hop).unwrap())
+                .unwrap();
+        let route =
+            serde_cbor::de::from_slice::<Route>(&serde_cbor::ser::to_vec(&old_route).unwrap())
+                .unwrap();
+
+        assert_eq!(
+            hop,
+            LiveHop::new(&PublicKey::new(&[1, 2, 3]), None, Component::ProxyClient)
+        );
+        assert_eq!(
+            route,
+            Route {
+                hops: vec![CryptData::new(&[4, 5, 6])],
+                top_hop_key_id_opt: None,
+            }
+        );
+        assert_eq!(
+            serde_cbor::ser::to_vec(&route).unwrap(),
+            serde_cbor::ser::to_vec(&old_route).unwrap()
+        );
+    }
+
     #[test]
     fn next_hop_decodes_top_hop() {
         let cryptde = CRYPTDE_PAIR.main.as_ref();
@@ -755,7 +1118,10 @@ mod tests {
     #[test]
     fn empty_route_says_none_when_asked_for_next_hop() {
         let cryptde = CRYPTDE_PAIR.main.as_ref();
-        let subject = Route { hops: Vec::new() };
+        let subject = Route {
+            hops: Vec::new(),
+            top_hop_key_id_opt: None,
+        };
 
         let result = subject.next_hop(cryptde).err().unwrap();
 
@@ -765,7 +1131,10 @@ mod tests {
     #[test]
     fn shift_says_none_when_asked_for_next_hop_on_empty_route() {
         let cryptde = CRYPTDE_PAIR.main.as_ref();
-        let mut subject = Route { hops: Vec::new() };
+        let mut subject = Route {
+            hops: Vec::new(),
+            top_hop_key_id_opt: None,
+        };
 
         let result = subject.shift(cryptde).err().unwrap();
 
@@ -819,9 +1188,9 @@ mod tests {
             result,
             String::from(
                 r#"
-Encrypted with 0x01020304: LiveHop { public_key: 0x02030405, payer: Some(Payer { wallet: Wallet { kind: Address(0x71d0fc7d1c570b1ed786382b551a09391c91e33d) }, proof: Signature { v: 0, r: "3e3a92d7284c2c2ff7119e9f7a7e183b062a335a598e965a47c36a2f288b6f8d", s: "3e3a92d7284c2c2ff7119e9f7a7e183b062a335a598e965a47c36a2f288b6f8d" } }), component: Hopper }
-Encrypted with 0x02030405: LiveHop { public_key: 0x03040506, payer: Some(Payer { wallet: Wallet { kind: Address(0x71d0fc7d1c570b1ed786382b551a09391c91e33d) }, proof: Signature { v: 0, r: "4324a40295bb36ef2b927fb24250fe42397a57b861ea152bbbe4f84150d4ff5a", s: "4324a40295bb36ef2b927fb24250fe42397a57b861ea152bbbe4f84150d4ff5a" } }), component: Hopper }
-Encrypted with 0x03040506: LiveHop { public_key: 0x, payer: Some(Payer { wallet: Wallet { kind: Address(0x71d0fc7d1c570b1ed786382b551a09391c91e33d) }, proof: Signature { v: 1, r: "8649b8f6db6232cb1e4f1f04786ad4ef33488c968e64bec74ecd893d6d05c1b9", s: "8649b8f6db6232cb1e4f1f04786ad4ef33488c968e64bec74ecd893d6d05c1b9" } }), component: Neighborhood }
+Encrypted with 0x01020304: LiveHop { public_key: 0x02030405, payer: Some(Payer { wallet: Wallet { kind: Address(0x71d0fc7d1c570b1ed786382b551a09391c91e33d) }, proof: Signature { v: 0, r: "3e3a92d7284c2c2ff7119e9f7a7e183b062a335a598e965a47c36a2f288b6f8d", s: "3e3a92d7284c2c2ff7119e9f7a7e183b062a335a598e965a47c36a2f288b6f8d" } }), component: Hopper, session_key_opt: None }
+Encrypted with 0x02030405: LiveHop { public_key: 0x03040506, payer: Some(Payer { wallet: Wallet { kind: Address(0x71d0fc7d1c570b1ed786382b551a09391c91e33d) }, proof: Signature { v: 0, r: "4324a40295bb36ef2b927fb24250fe42397a57b861ea152bbbe4f84150d4ff5a", s: "4324a40295bb36ef2b927fb24250fe42397a57b861ea152bbbe4f84150d4ff5a" } }), component: Hopper, session_key_opt: None }
+Encrypted with 0x03040506: LiveHop { public_key: 0x, payer: Some(Payer { wallet: Wallet { kind: Address(0x71d0fc7d1c570b1ed786382b551a09391c91e33d) }, proof: Signature { v: 1, r: "8649b8f6db6232cb1e4f1f04786ad4ef33488c968e64bec74ecd893d6d05c1b9", s: "8649b8f6db6232cb1e4f1f04786ad4ef33488c968e64bec74ecd893d6d05c1b9" } }), component: Neighborhood, session_key_opt: None }
 "#
             )
         );
@@ -856,11 +1225,11 @@ Encrypted with 0x03040506: LiveHop { public_key: 0x, payer: Some(Payer { wallet:
             result,
             String::from(
                 r#"
-Encrypted with 0x01020304: LiveHop { public_key: 0x02030405, payer: Some(Payer { wallet: Wallet { kind: Address(0x71d0fc7d1c570b1ed786382b551a09391c91e33d) }, proof: Signature { v: 0, r: "3e3a92d7284c2c2ff7119e9f7a7e183b062a335a598e965a47c36a2f288b6f8d", s: "3e3a92d7284c2c2ff7119e9f7a7e183b062a335a598e965a47c36a2f288b6f8d" } }), component: Hopper }
-Encrypted with 0x02030405: LiveHop { public_key: 0x03040506, payer: Some(Payer { wallet: Wallet { kind: Address(0x71d0fc7d1c570b1ed786382b551a09391c91e33d) }, proof: Signature { v: 0, r: "4324a40295bb36ef2b927fb24250fe42397a57b861ea152bbbe4f84150d4ff5a", s: "4324a40295bb36ef2b927fb24250fe42397a57b861ea152bbbe4f84150d4ff5a" } }), component: Hopper }
-Encrypted with 0x03040506: LiveHop { public_key: 0x02030405, payer: Some(Payer { wallet: Wallet { kind: Address(0x71d0fc7d1c570b1ed786382b551a09391c91e33d) }, proof: Signature { v: 1, r: "8649b8f6db6232cb1e4f1f04786ad4ef33488c968e64bec74ecd893d6d05c1b9", s: "8649b8f6db6232cb1e4f1f04786ad4ef33488c968e64bec74ecd893d6d05c1b9" } }), component: ProxyClient }
-Encrypted with 0x02030405: LiveHop { public_key: 0x01020304, payer: Some(Payer { wallet: Wallet { kind: Address(0x71d0fc7d1c570b1ed786382b551a09391c91e33d) }, proof: Signature { v: 0, r: "4324a40295bb36ef2b927fb24250fe42397a57b861ea152bbbe4f84150d4ff5a", s: "4324a40295bb36ef2b927fb24250fe42397a57b861ea152bbbe4f84150d4ff5a" } }), component: Hopper }
-Encrypted with 0x01020304: LiveHop { public_key: 0x, payer: Some(Payer { wallet: Wallet { kind: Address(0x71d0fc7d1c570b1ed786382b551a09391c91e33d) }, proof: Signature { v: 0, r: "3e3a92d7284c2c2ff7119e9f7a7e183b062a335a598e965a47c36a2f288b6f8d", s: "3e3a92d7284c2c2ff7119e9f7a7e183b062a335a598e965a47c36a2f288b6f8d" } }), component: ProxyServer }
+Encrypted with 0x01020304: LiveHop { public_key: 0x02030405, payer: Some(Payer { wallet: Wallet { kind: Address(0x71d0fc7d1c570b1ed786382b551a09391c91e33d) }, proof: Signature { v: 0, r: "3e3a92d7284c2c2ff7119e9f7a7e183b062a335a598e965a47c36a2f288b6f8d", s: "3e3a92d7284c2c2ff7119e9f7a7e183b062a335a598e965a47c36a2f288b6f8d" } }), component: Hopper, session_key_opt: None }
+Encrypted with 0x02030405: LiveHop { public_key: 0x03040506, payer: Some(Payer { wallet: Wallet { kind: Address(0x71d0fc7d1c570b1ed786382b551a09391c91e33d) }, proof: Signature { v: 0, r: "4324a40295bb36ef2b927fb24250fe42397a57b861ea152bbbe4f84150d4ff5a", s: "4324a40295bb36ef2b927fb24250fe42397a57b861ea152bbbe4f84150d4ff5a" } }), component: Hopper, session_key_opt: None }
+Encrypted with 0x03040506: LiveHop { public_key: 0x02030405, payer: Some(Payer { wallet: Wallet { kind: Address(0x71d0fc7d1c570b1ed786382b551a09391c91e33d) }, proof: Signature { v: 1, r: "8649b8f6db6232cb1e4f1f04786ad4ef33488c968e64bec74ecd893d6d05c1b9", s: "8649b8f6db6232cb1e4f1f04786ad4ef33488c968e64bec74ecd893d6d05c1b9" } }), component: ProxyClient, session_key_opt: None }
+Encrypted with 0x02030405: LiveHop { public_key: 0x01020304, payer: Some(Payer { wallet: Wallet { kind: Address(0x71d0fc7d1c570b1ed786382b551a09391c91e33d) }, proof: Signature { v: 0, r: "4324a40295bb36ef2b927fb24250fe42397a57b861ea152bbbe4f84150d4ff5a", s: "4324a40295bb36ef2b927fb24250fe42397a57b861ea152bbbe4f84150d4ff5a" } }), component: Hopper, session_key_opt: None }
+Encrypted with 0x01020304: LiveHop { public_key: 0x, payer: Some(Payer { wallet: Wallet { kind: Address(0x71d0fc7d1c570b1ed786382b551a09391c91e33d) }, proof: Signature { v: 0, r: "3e3a92d7284c2c2ff7119e9f7a7e183b062a335a598e965a47c36a2f288b6f8d", s: "3e3a92d7284c2c2ff7119e9f7a7e183b062a335a598e965a47c36a2f288b6f8d" } }), component: ProxyServer, session_key_opt: None }
 Encrypted with 0x01020304: Return Route ID: 1234
 "#
             )
@@ -869,7 +1238,10 @@ Encrypted with 0x01020304: Return Route ID: 1234
 
     #[test]
     fn to_string_works_with_zero_length_data() {
-        let subject = Route { hops: vec![] };
+        let subject = Route {
+            hops: vec![],
+            top_hop_key_id_opt: None,
+        };
 
         let result = subject.to_string(vec![]);
 
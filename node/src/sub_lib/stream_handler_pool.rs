@@ -2,6 +2,8 @@
 use crate::sub_lib::dispatcher::Endpoint;
 use crate::sub_lib::neighborhood::NodeQueryResponseMetadata;
 use actix::Message;
+use std::net::{IpAddr, Ipv6Addr};
+use std::time::Duration;
 
 // This message can be sent either to a neighboring Node or to the client, but not to the server.
 #[derive(PartialEq, Eq, Debug, Message, Clone)]
@@ -12,8 +14,48 @@ pub struct TransmitDataMsg {
     pub data: Vec<u8>,
 }
 
+// Sent by the Neighborhood when a peer has abused Gossip badly enough to be shut out for a while.
+// The StreamHandlerPool drops its clandestine streams to and from that IP address and refuses
+// new ones until the ban expires.
+#[derive(PartialEq, Eq, Debug, Message, Clone)]
+pub struct BanClandestinePeerMsg {
+    pub peer_ip: IpAddr,
+    pub duration: Duration,
+}
+
+// An IPv6 host usually has a whole /64 to pick its addresses from, so it's the /64 that identifies
+// an IPv6 peer for rate limits and bans, not whichever address the peer happens to be using.
+pub fn clandestine_peer_id(peer_ip: IpAddr) -> IpAddr {
+    match peer_ip {
+        IpAddr::V4(_) => peer_ip,
+        IpAddr::V6(ipv6_addr) => match ipv6_addr.to_ipv4_mapped() {
+            Some(ipv4_addr) => IpAddr::V4(ipv4_addr),
+            None => IpAddr::V6(Ipv6Addr::from(
+                u128::from(ipv6_addr) & 0xFFFF_FFFF_FFFF_FFFF_0000_0000_0000_0000,
+            )),
+        },
+    }
+}
+
 #[derive(Message, Clone, PartialEq, Eq)]
 pub struct DispatcherNodeQueryResponse {
     pub result: Option<NodeQueryResponseMetadata>,
     pub context: TransmitDataMsg,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn clandestine_peer_id_is_the_ipv4_address_or_the_ipv6_slash_64() {
+        let id = |ip: &str| clandestine_peer_id(IpAddr::from_str(ip).unwrap()).to_string();
+
+        assert_eq!(id("1.2.3.4"), "1.2.3.4");
+        assert_eq!(id("2001:db8:1:2:3:4:5:6"), "2001:db8:1:2::");
+        assert_eq!(id("2001:db8:1:2:ffff::1"), "2001:db8:1:2::");
+        assert_eq!(id("2001:db8:1:3::1"), "2001:db8:1:3::");
+        assert_eq!(id("::ffff:1.2.3.4"), "1.2.3.4");
+    }
+}
@@ -2,10 +2,10 @@
 
 use crate::actor_system_factory::AutomapControlFactory;
 use automap_lib::comm_layer::AutomapError;
-use automap_lib::control_layer::automap_control::{AutomapControl, ChangeHandler};
+use automap_lib::control_layer::automap_control::{AutomapControl, AutomapStatus, ChangeHandler};
 use masq_lib::utils::AutomapProtocol;
 use std::cell::RefCell;
-use std::net::IpAddr;
+use std::net::{IpAddr, Ipv6Addr};
 use std::sync::{Arc, Mutex};
 
 #[allow(clippy::type_complexity)]
@@ -61,8 +61,14 @@ pub struct AutomapControlMock {
     get_public_ip_results: RefCell<Vec<Result<IpAddr, AutomapError>>>,
     add_mapping_params: Arc<Mutex<Vec<u16>>>,
     add_mapping_results: RefCell<Vec<Result<(), AutomapError>>>,
+    add_pinhole_params: Arc<Mutex<Vec<(Ipv6Addr, u16)>>>,
+    add_pinhole_results: RefCell<Vec<Result<(), AutomapError>>>,
     delete_mappings_results: RefCell<Vec<Result<(), AutomapError>>>,
+    remap_results: RefCell<Vec<Result<(), AutomapError>>>,
+    switch_protocol_params: Arc<Mutex<Vec<AutomapProtocol>>>,
+    switch_protocol_results: RefCell<Vec<Result<(), AutomapError>>>,
     get_mapping_protocol_results: RefCell<Vec<Option<AutomapProtocol>>>,
+    get_status_results: RefCell<Vec<AutomapStatus>>,
 }
 
 impl AutomapControl for AutomapControlMock {
@@ -75,13 +81,34 @@ impl AutomapControl for AutomapControlMock {
         self.add_mapping_results.borrow_mut().remove(0)
     }
 
+    fn add_pinhole(&mut self, local_ip: Ipv6Addr, hole_port: u16) -> Result<(), AutomapError> {
+        self.add_pinhole_params
+            .lock()
+            .unwrap()
+            .push((local_ip, hole_port));
+        self.add_pinhole_results.borrow_mut().remove(0)
+    }
+
     fn delete_mappings(&mut self) -> Result<(), AutomapError> {
         self.delete_mappings_results.borrow_mut().remove(0)
     }
 
+    fn remap(&mut self) -> Result<(), AutomapError> {
+        self.remap_results.borrow_mut().remove(0)
+    }
+
+    fn switch_protocol(&mut self, protocol: AutomapProtocol) -> Result<(), AutomapError> {
+        self.switch_protocol_params.lock().unwrap().push(protocol);
+        self.switch_protocol_results.borrow_mut().remove(0)
+    }
+
     fn get_mapping_protocol(&self) -> Option<AutomapProtocol> {
         self.get_mapping_protocol_results.borrow_mut().remove(0)
     }
+
+    fn get_status(&self) -> AutomapStatus {
+        self.get_status_results.borrow_mut().remove(0)
+    }
 }
 
 impl Default for AutomapControlMock {
@@ -96,8 +123,14 @@ impl AutomapControlMock {
             get_public_ip_results: RefCell::new(vec![]),
             add_mapping_params: Arc::new(Mutex::new(vec![])),
             add_mapping_results: RefCell::new(vec![]),
+            add_pinhole_params: Arc::new(Mutex::new(vec![])),
+            add_pinhole_results: RefCell::new(vec![]),
             delete_mappings_results: RefCell::new(vec![]),
+            remap_results: RefCell::new(vec![]),
+            switch_protocol_params: Arc::new(Mutex::new(vec![])),
+            switch_protocol_results: RefCell::new(vec![]),
             get_mapping_protocol_results: RefCell::new(vec![]),
+            get_status_results: RefCell::new(vec![]),
         }
     }
 
@@ -116,13 +149,43 @@ impl AutomapControlMock {
         self
     }
 
+    pub fn add_pinhole_params(mut self, params: &Arc<Mutex<Vec<(Ipv6Addr, u16)>>>) -> Self {
+        self.add_pinhole_params = params.clone();
+        self
+    }
+
+    pub fn add_pinhole_result(self, result: Result<(), AutomapError>) -> Self {
+        self.add_pinhole_results.borrow_mut().push(result);
+        self
+    }
+
     pub fn delete_mappings_result(self, result: Result<(), AutomapError>) -> Self {
         self.delete_mappings_results.borrow_mut().push(result);
         self
     }
 
+    pub fn remap_result(self, result: Result<(), AutomapError>) -> Self {
+        self.remap_results.borrow_mut().push(result);
+        self
+    }
+
+    pub fn switch_protocol_params(mut self, params: &Arc<Mutex<Vec<AutomapProtocol>>>) -> Self {
+        self.switch_protocol_params = params.clone();
+        self
+    }
+
+    pub fn switch_protocol_result(self, result: Result<(), AutomapError>) -> Self {
+        self.switch_protocol_results.borrow_mut().push(result);
+        self
+    }
+
     pub fn get_mapping_protocol_result(self, result: Option<AutomapProtocol>) -> Self {
         self.get_mapping_protocol_results.borrow_mut().push(result);
         self
     }
+
+    pub fn get_status_result(self, result: AutomapStatus) -> Self {
+        self.get_status_results.borrow_mut().push(result);
+        self
+    }
 }
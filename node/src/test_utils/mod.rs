@@ -230,6 +230,7 @@ pub fn zero_hop_route_response(
             vec![ExpectedService::Nothing, ExpectedService::Nothing],
             0,
         ),
+        payload_session_key_opt: None,
     }
 }
 
@@ -507,6 +508,7 @@ pub mod unshared_test_utils {
     use crate::node_test_utils::DirsWrapperMock;
     use crate::sub_lib::accountant::{PaymentThresholds, ScanIntervals};
     use crate::sub_lib::cryptde::CryptDE;
+    use crate::sub_lib::earning_wallet_shares::EarningWalletShares;
     use crate::sub_lib::neighborhood::{ConnectionProgressMessage, DEFAULT_RATE_PACK};
     use crate::sub_lib::proxy_client::ClientResponsePayload_0v1;
     use crate::sub_lib::proxy_server::{ClientRequestPayload_0v1, ProxyProtocol};
@@ -640,6 +642,7 @@ pub mod unshared_test_utils {
         persistent_config_mock
             .earning_wallet_address_result(Ok(None))
             .earning_wallet_result(Ok(None))
+            .earning_wallet_shares_result(Ok(EarningWalletShares::default()))
             .consuming_wallet_private_key_result(Ok(None))
             .consuming_wallet_result(Ok(None))
             .past_neighbors_result(Ok(None))
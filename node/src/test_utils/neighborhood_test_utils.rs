@@ -4,7 +4,7 @@ use crate::neighborhood::gossip::{
     AccessibleGossipRecord, GossipBuilder, GossipNodeRecord, Gossip_0v1,
 };
 use crate::neighborhood::neighborhood_database::NeighborhoodDatabase;
-use crate::neighborhood::node_location::NodeLocation;
+use crate::neighborhood::node_location::{CountryDataLoader, NodeLocation};
 use crate::neighborhood::node_record::{NodeRecord, NodeRecordInner_0v1, NodeRecordInputs};
 use crate::neighborhood::{Neighborhood, DEFAULT_MIN_HOPS};
 use crate::sub_lib::cryptde::PublicKey;
@@ -15,12 +15,15 @@ use crate::sub_lib::node_addr::NodeAddr;
 use crate::sub_lib::wallet::Wallet;
 use crate::test_utils::*;
 use ethereum_types::H160;
-use ip_country_lib::country_finder::COUNTRY_CODE_FINDER;
+use ip_country_lib::country_finder::{ActiveCountryData, COUNTRY_CODE_FINDER};
 use masq_lib::blockchains::chains::Chain;
 use masq_lib::test_utils::utils::TEST_DEFAULT_CHAIN;
+use std::cell::RefCell;
 use std::convert::TryFrom;
 use std::net::IpAddr;
 use std::net::Ipv4Addr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 
 pub const MIN_HOPS_FOR_TEST: Hops = DEFAULT_MIN_HOPS;
 pub const DB_PATCH_SIZE_FOR_TEST: u8 = DEFAULT_MIN_HOPS as u8;
@@ -390,3 +393,52 @@ pub fn cryptdes_from_node_records(nodes: &[NodeRecord]) -> Vec<CryptDENull> {
         .map(|node| CryptDENull::from(node.public_key(), TEST_DEFAULT_CHAIN))
         .collect::<Vec<CryptDENull>>()
 }
+
+#[derive(Default)]
+pub struct CountryDataLoaderMock {
+    load_params: Arc<Mutex<Vec<()>>>,
+    load_results: RefCell<Vec<Result<ActiveCountryData, String>>>,
+    active_results: RefCell<Vec<ActiveCountryData>>,
+    data_file_path_results: RefCell<Vec<PathBuf>>,
+}
+
+impl CountryDataLoader for CountryDataLoaderMock {
+    fn load(&self) -> Result<ActiveCountryData, String> {
+        self.load_params.lock().unwrap().push(());
+        self.load_results.borrow_mut().remove(0)
+    }
+
+    fn active(&self) -> ActiveCountryData {
+        self.active_results.borrow_mut().remove(0)
+    }
+
+    fn data_file_path(&self) -> PathBuf {
+        self.data_file_path_results.borrow_mut().remove(0)
+    }
+}
+
+impl CountryDataLoaderMock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load_params(mut self, params: &Arc<Mutex<Vec<()>>>) -> Self {
+        self.load_params = params.clone();
+        self
+    }
+
+    pub fn load_result(self, result: Result<ActiveCountryData, String>) -> Self {
+        self.load_results.borrow_mut().push(result);
+        self
+    }
+
+    pub fn active_result(self, result: ActiveCountryData) -> Self {
+        self.active_results.borrow_mut().push(result);
+        self
+    }
+
+    pub fn data_file_path_result(self, result: PathBuf) -> Self {
+        self.data_file_path_results.borrow_mut().push(result);
+        self
+    }
+}
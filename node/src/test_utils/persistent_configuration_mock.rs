@@ -3,9 +3,11 @@
 #![cfg(test)]
 
 use crate::database::rusqlite_wrappers::TransactionSafeWrapper;
+use crate::db_config::db_kdf::DbEncryptionScheme;
 use crate::db_config::persistent_configuration::{PersistentConfigError, PersistentConfiguration};
 use crate::sub_lib::accountant::{PaymentThresholds, ScanIntervals};
 use crate::sub_lib::cryptde::CryptDE;
+use crate::sub_lib::earning_wallet_shares::EarningWalletShares;
 use crate::sub_lib::neighborhood::{Hops, NodeDescriptor, RatePack};
 use crate::sub_lib::wallet::Wallet;
 use crate::test_utils::unshared_test_utils::arbitrary_id_stamp::ArbitraryIdStamp;
@@ -29,6 +31,9 @@ pub struct PersistentConfigurationMock {
     check_password_results: RefCell<Vec<Result<bool, PersistentConfigError>>>,
     change_password_params: Arc<Mutex<Vec<(Option<String>, String)>>>,
     change_password_results: RefCell<Vec<Result<(), PersistentConfigError>>>,
+    upgrade_encryption_params: Arc<Mutex<Vec<String>>>,
+    upgrade_encryption_results: RefCell<Vec<Result<bool, PersistentConfigError>>>,
+    encryption_scheme_results: RefCell<Vec<Result<DbEncryptionScheme, PersistentConfigError>>>,
     clandestine_port_results: RefCell<Vec<Result<u16, PersistentConfigError>>>,
     set_clandestine_port_params: Arc<Mutex<Vec<u16>>>,
     set_clandestine_port_results: RefCell<Vec<Result<(), PersistentConfigError>>>,
@@ -46,6 +51,9 @@ pub struct PersistentConfigurationMock {
         RefCell<Vec<Result<Option<String>, PersistentConfigError>>>,
     earning_wallet_results: RefCell<Vec<Result<Option<Wallet>, PersistentConfigError>>>,
     earning_wallet_address_results: RefCell<Vec<Result<Option<String>, PersistentConfigError>>>,
+    earning_wallet_shares_results: RefCell<Vec<Result<EarningWalletShares, PersistentConfigError>>>,
+    set_earning_wallet_shares_params: Arc<Mutex<Vec<EarningWalletShares>>>,
+    set_earning_wallet_shares_results: RefCell<Vec<Result<(), PersistentConfigError>>>,
     set_wallet_info_params: Arc<Mutex<Vec<(String, String, String)>>>,
     set_wallet_info_results: RefCell<Vec<Result<(), PersistentConfigError>>>,
     mapping_protocol_results: RefCell<Vec<Result<Option<AutomapProtocol>, PersistentConfigError>>>,
@@ -97,6 +105,9 @@ impl Clone for PersistentConfigurationMock {
             check_password_results: self.check_password_results.clone(),
             change_password_params: self.change_password_params.clone(),
             change_password_results: self.change_password_results.clone(),
+            upgrade_encryption_params: self.upgrade_encryption_params.clone(),
+            upgrade_encryption_results: self.upgrade_encryption_results.clone(),
+            encryption_scheme_results: self.encryption_scheme_results.clone(),
             clandestine_port_results: self.clandestine_port_results.clone(),
             set_clandestine_port_params: self.set_clandestine_port_params.clone(),
             set_clandestine_port_results: self.set_clandestine_port_results.clone(),
@@ -123,6 +134,9 @@ impl Clone for PersistentConfigurationMock {
             consuming_wallet_private_key_results: self.consuming_wallet_private_key_results.clone(),
             earning_wallet_results: self.earning_wallet_results.clone(),
             earning_wallet_address_results: self.earning_wallet_address_results.clone(),
+            earning_wallet_shares_results: self.earning_wallet_shares_results.clone(),
+            set_earning_wallet_shares_params: self.set_earning_wallet_shares_params.clone(),
+            set_earning_wallet_shares_results: self.set_earning_wallet_shares_results.clone(),
             set_wallet_info_params: self.set_wallet_info_params.clone(),
             set_wallet_info_results: self.set_wallet_info_results.clone(),
             mapping_protocol_results: self.mapping_protocol_results.clone(),
@@ -209,6 +223,18 @@ impl PersistentConfiguration for PersistentConfigurationMock {
         self.change_password_results.borrow_mut().remove(0)
     }
 
+    fn upgrade_encryption(&mut self, db_password: &str) -> Result<bool, PersistentConfigError> {
+        self.upgrade_encryption_params
+            .lock()
+            .unwrap()
+            .push(db_password.to_string());
+        self.upgrade_encryption_results.borrow_mut().remove(0)
+    }
+
+    fn encryption_scheme(&self) -> Result<DbEncryptionScheme, PersistentConfigError> {
+        self.encryption_scheme_results.borrow_mut().remove(0)
+    }
+
     fn consuming_wallet(&self, db_password: &str) -> Result<Option<Wallet>, PersistentConfigError> {
         self.consuming_wallet_params
             .lock()
@@ -268,6 +294,23 @@ impl PersistentConfiguration for PersistentConfigurationMock {
         Self::result_from(&self.earning_wallet_address_results)
     }
 
+    fn earning_wallet_shares(&self) -> Result<EarningWalletShares, PersistentConfigError> {
+        Self::result_from(&self.earning_wallet_shares_results)
+    }
+
+    fn set_earning_wallet_shares(
+        &mut self,
+        shares: &EarningWalletShares,
+    ) -> Result<(), PersistentConfigError> {
+        self.set_earning_wallet_shares_params
+            .lock()
+            .unwrap()
+            .push(shares.clone());
+        self.set_earning_wallet_shares_results
+            .borrow_mut()
+            .remove(0)
+    }
+
     fn gas_price(&self) -> Result<u64, PersistentConfigError> {
         Self::result_from(&self.gas_price_results)
     }
@@ -478,6 +521,24 @@ impl PersistentConfigurationMock {
         self
     }
 
+    pub fn upgrade_encryption_params(mut self, params: &Arc<Mutex<Vec<String>>>) -> Self {
+        self.upgrade_encryption_params = params.clone();
+        self
+    }
+
+    pub fn upgrade_encryption_result(self, result: Result<bool, PersistentConfigError>) -> Self {
+        self.upgrade_encryption_results.borrow_mut().push(result);
+        self
+    }
+
+    pub fn encryption_scheme_result(
+        self,
+        result: Result<DbEncryptionScheme, PersistentConfigError>,
+    ) -> Self {
+        self.encryption_scheme_results.borrow_mut().push(result);
+        self
+    }
+
     pub fn check_password_params(mut self, params: &Arc<Mutex<Vec<Option<String>>>>) -> Self {
         self.check_password_params = params.clone();
         self
@@ -673,6 +734,32 @@ impl PersistentConfigurationMock {
         self
     }
 
+    pub fn earning_wallet_shares_result(
+        self,
+        result: Result<EarningWalletShares, PersistentConfigError>,
+    ) -> Self {
+        self.earning_wallet_shares_results.borrow_mut().push(result);
+        self
+    }
+
+    pub fn set_earning_wallet_shares_params(
+        mut self,
+        params: &Arc<Mutex<Vec<EarningWalletShares>>>,
+    ) -> Self {
+        self.set_earning_wallet_shares_params = params.clone();
+        self
+    }
+
+    pub fn set_earning_wallet_shares_result(
+        self,
+        result: Result<(), PersistentConfigError>,
+    ) -> Self {
+        self.set_earning_wallet_shares_results
+            .borrow_mut()
+            .push(result);
+        self
+    }
+
     pub fn start_block_params(mut self, params: &Arc<Mutex<Vec<()>>>) -> Self {
         self.start_block_params = params.clone();
         self
@@ -18,6 +18,7 @@ use crate::sub_lib::accountant::AccountantSubs;
 use crate::sub_lib::accountant::ReportExitServiceProvidedMessage;
 use crate::sub_lib::accountant::ReportRoutingServiceProvidedMessage;
 use crate::sub_lib::accountant::ReportServicesConsumedMessage;
+use crate::sub_lib::accountant::WalletsSeenInGossip;
 use crate::sub_lib::blockchain_bridge::BlockchainBridgeSubs;
 use crate::sub_lib::blockchain_bridge::OutboundPaymentsInstructions;
 use crate::sub_lib::dispatcher::InboundClientData;
@@ -28,7 +29,7 @@ use crate::sub_lib::hopper::{HopperSubs, MessageType};
 use crate::sub_lib::neighborhood::NeighborhoodSubs;
 use crate::sub_lib::neighborhood::{ConfigChangeMsg, ConnectionProgressMessage};
 
-use crate::sub_lib::configurator::ConfiguratorSubs;
+use crate::sub_lib::configurator::{AutomapControlMessage, ConfiguratorSubs};
 use crate::sub_lib::neighborhood::NodeQueryResponseMetadata;
 use crate::sub_lib::neighborhood::RemoveNeighborMessage;
 use crate::sub_lib::neighborhood::RouteQueryMessage;
@@ -43,6 +44,7 @@ use crate::sub_lib::proxy_server::{
     AddReturnRouteMessage, ClientRequestPayload_0v1, StreamKeyPurge,
 };
 use crate::sub_lib::proxy_server::{AddRouteResultMessage, ProxyServerSubs};
+use crate::sub_lib::stream_handler_pool::BanClandestinePeerMsg;
 use crate::sub_lib::stream_handler_pool::DispatcherNodeQueryResponse;
 use crate::sub_lib::stream_handler_pool::TransmitDataMsg;
 use crate::sub_lib::ui_gateway::UiGatewaySubs;
@@ -126,6 +128,8 @@ macro_rules! recorder_message_handler_t_p {
 recorder_message_handler_t_m_p!(AddReturnRouteMessage);
 recorder_message_handler_t_m_p!(AddRouteResultMessage);
 recorder_message_handler_t_p!(AddStreamMsg);
+recorder_message_handler_t_p!(AutomapControlMessage);
+recorder_message_handler_t_m_p!(BanClandestinePeerMsg);
 recorder_message_handler_t_m_p!(BindMessage);
 recorder_message_handler_t_p!(BlockchainAgentWithContextMessage);
 recorder_message_handler_t_m_p!(ConfigChangeMsg);
@@ -170,6 +174,7 @@ recorder_message_handler_t_m_p!(StartMessage);
 recorder_message_handler_t_m_p!(StreamShutdownMsg);
 recorder_message_handler_t_m_p!(TransmitDataMsg);
 recorder_message_handler_t_m_p!(UpdateNodeRecordMetadataMessage);
+recorder_message_handler_t_m_p!(WalletsSeenInGossip);
 
 impl<M> Handler<MessageScheduler<M>> for Recorder
 where
@@ -403,6 +408,7 @@ pub fn make_proxy_server_subs_from_recorder(addr: &Addr<Recorder>) -> ProxyServe
         node_from_ui: recipient!(addr, NodeFromUiMessage),
         route_result_sub: recipient!(addr, AddRouteResultMessage),
         schedule_stream_key_purge: recipient!(addr, MessageScheduler<StreamKeyPurge>),
+        config_change_msg_sub: recipient!(addr, ConfigChangeMsg),
     }
 }
 
@@ -414,6 +420,7 @@ pub fn make_dispatcher_subs_from_recorder(addr: &Addr<Recorder>) -> DispatcherSu
         stream_shutdown_sub: recipient!(addr, StreamShutdownMsg),
         ui_sub: recipient!(addr, NodeFromUiMessage),
         new_ip_sub: recipient!(addr, NewPublicIp),
+        ban_clandestine_peer_sub: recipient!(addr, BanClandestinePeerMsg),
     }
 }
 
@@ -423,6 +430,7 @@ pub fn make_hopper_subs_from_recorder(addr: &Addr<Recorder>) -> HopperSubs {
         from_hopper_client: recipient!(addr, IncipientCoresPackage),
         from_hopper_client_no_lookup: recipient!(addr, NoLookupIncipientCoresPackage),
         from_dispatcher: recipient!(addr, InboundClientData),
+        config_change_msg_sub: recipient!(addr, ConfigChangeMsg),
         node_from_ui: recipient!(addr, NodeFromUiMessage),
     }
 }
@@ -433,6 +441,7 @@ pub fn make_proxy_client_subs_from_recorder(addr: &Addr<Recorder>) -> ProxyClien
         from_hopper: recipient!(addr, ExpiredCoresPackage<ClientRequestPayload_0v1>),
         inbound_server_data: recipient!(addr, InboundServerData),
         dns_resolve_failed: recipient!(addr, DnsResolveFailure_0v1),
+        config_change_msg_sub: recipient!(addr, ConfigChangeMsg),
         node_from_ui: recipient!(addr, NodeFromUiMessage),
     }
 }
@@ -463,6 +472,7 @@ pub fn make_accountant_subs_from_recorder(addr: &Addr<Recorder>) -> AccountantSu
         report_routing_service_provided: recipient!(addr, ReportRoutingServiceProvidedMessage),
         report_exit_service_provided: recipient!(addr, ReportExitServiceProvidedMessage),
         report_services_consumed: recipient!(addr, ReportServicesConsumedMessage),
+        report_wallets_seen_in_gossip: recipient!(addr, WalletsSeenInGossip),
         report_payable_payments_setup: recipient!(addr, BlockchainAgentWithContextMessage),
         report_inbound_payments: recipient!(addr, ReceivedPayments),
         init_pending_payable_fingerprints: recipient!(addr, PendingPayableFingerprintSeeds),
@@ -496,6 +506,7 @@ pub fn make_configurator_subs_from_recorder(addr: &Addr<Recorder>) -> Configurat
     ConfiguratorSubs {
         bind: recipient!(addr, BindMessage),
         node_from_ui_sub: recipient!(addr, NodeFromUiMessage),
+        automap_control_sub: recipient!(addr, AutomapControlMessage),
     }
 }
 